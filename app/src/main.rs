@@ -55,6 +55,7 @@ fn App() -> Element {
 
     // TODO: Update
     let path = Path::new("/Users/rubbiekelvin/Projects/dotapi/example/project.nd-project");
+    states::WorkspaceManager::provide(vec![path.to_path_buf()]);
 
     // Ui element
     return rsx! {
@@ -63,8 +64,6 @@ fn App() -> Element {
         document::Link { rel: "stylesheet", href: TAILWIND_CSS }
 
         components::ToastProvider{}
-        views::project::ProjectView {
-            path: path.to_path_buf()
-        }
+        views::workspace::WorkspaceView {}
     };
 }
@@ -0,0 +1,136 @@
+use std::collections::HashMap;
+
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    prelude::spawn,
+    signals::{Readable, Signal, Writable},
+};
+use nativedoctor_core::{
+    cancel::CancelToken,
+    middleware::RequestMiddleware,
+    runner::{CallResult, ResponseObject, Runner},
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+#[derive(Clone, Debug)]
+enum CallEvent {
+    Sending(String),
+    Received(String, u16),
+    Finished(Result<CallResult, String>),
+}
+
+/// A request sent from the app: what it's doing while it runs, then its outcome.
+#[derive(Clone, Debug, Default)]
+pub struct CallState {
+    pub running: bool,
+    // the request and the ones it depends on as they're sent and answered
+    pub progress: Vec<String>,
+    pub result: Option<Result<CallResult, String>>,
+}
+
+/// The requests sent from a project view, keyed by the id of their file object.
+/// Calls run on the tokio runtime, off the ui thread, and report back through a channel.
+#[derive(Clone, Copy, PartialEq)]
+pub struct CallManager {
+    calls: Signal<HashMap<uuid::Uuid, CallState>>,
+    // cancel tokens of the runners of calls in flight
+    cancels: Signal<HashMap<uuid::Uuid, CancelToken>>,
+}
+
+impl CallManager {
+    pub fn provide() -> CallManager {
+        return use_context_provider(|| CallManager {
+            calls: Signal::new(HashMap::new()),
+            cancels: Signal::new(HashMap::new()),
+        });
+    }
+
+    pub fn inject() -> CallManager {
+        return use_context::<CallManager>();
+    }
+
+    pub fn get(&self, id: uuid::Uuid) -> Option<CallState> {
+        return self.calls.read().get(&id).cloned();
+    }
+
+    pub fn is_running(&self, id: uuid::Uuid) -> bool {
+        return self.get(id).is_some_and(|call| call.running);
+    }
+
+    // Calls a request with the runner, replacing the last outcome of the request
+    pub fn send(&mut self, id: uuid::Uuid, name: String, mut runner: Runner) {
+        if self.is_running(id) {
+            return;
+        }
+
+        let (sender, mut receiver) = unbounded_channel();
+        runner.add_middleware(Box::new(ProgressMiddleware {
+            sender: sender.clone(),
+        }));
+
+        self.cancels.write().insert(id, runner.cancel.clone());
+        self.calls.write().insert(
+            id,
+            CallState {
+                running: true,
+                ..CallState::default()
+            },
+        );
+
+        tokio::spawn(async move {
+            // the result is shown in the app, redacted like exports
+            let result = runner
+                .call_request(&name)
+                .await
+                .map(|result| runner.redact_result(&result))
+                .map_err(|e| runner.redact_text(&format!("{:#}", e)));
+            let _ = sender.send(CallEvent::Finished(result));
+        });
+
+        // the channel closes once the runner, holding the middleware, is dropped
+        let (mut calls, mut cancels) = (self.calls, self.cancels);
+        spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                calls.with_mut(|calls| {
+                    let call = calls.entry(id).or_default();
+                    match event {
+                        CallEvent::Sending(name) => call.progress.push(format!("Sending {}", name)),
+                        CallEvent::Received(name, status) => {
+                            call.progress.push(format!("{} answered {}", name, status))
+                        }
+                        CallEvent::Finished(result) => {
+                            cancels.write().remove(&id);
+                            call.running = false;
+                            call.result = Some(result);
+                        }
+                    };
+                });
+            }
+        });
+    }
+
+    // Aborts the call in flight, it finishes with a cancelled error
+    pub fn cancel(&self, id: uuid::Uuid) {
+        if let Some(cancel) = self.cancels.read().get(&id) {
+            cancel.cancel();
+        }
+    }
+}
+
+struct ProgressMiddleware {
+    sender: UnboundedSender<CallEvent>,
+}
+
+impl RequestMiddleware for ProgressMiddleware {
+    fn before_send(&self, name: &str, _request: &mut reqwest::Request) -> anyhow::Result<()> {
+        let _ = self.sender.send(CallEvent::Sending(name.to_string()));
+        return Ok(());
+    }
+
+    fn after_receive(&self, name: &str, response: &mut ResponseObject) -> anyhow::Result<()> {
+        let _ = self
+            .sender
+            .send(CallEvent::Received(name.to_string(), response.status));
+        return Ok(());
+    }
+}
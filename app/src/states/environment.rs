@@ -0,0 +1,43 @@
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    signals::{Readable, Signal, Writable},
+};
+use nativedoctor_core::{interpolation::EnvMap, schema::roots::ProjectRootSchema};
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct EnvironmentManager {
+    active: Signal<Option<String>>,
+}
+
+impl EnvironmentManager {
+    pub fn provide() -> EnvironmentManager {
+        return use_context_provider(|| EnvironmentManager {
+            active: Signal::new(None),
+        });
+    }
+
+    pub fn inject() -> EnvironmentManager {
+        return use_context::<EnvironmentManager>();
+    }
+
+    // The selected environment, None uses the default values
+    pub fn active(&self) -> Option<String> {
+        return self.active.read().clone();
+    }
+
+    pub fn set_active(&mut self, environment: Option<String>) {
+        tracing::debug!("Switched environment to {:?}", &environment);
+        self.active.with_mut(|active| {
+            *active = environment;
+        })
+    }
+
+    pub fn environments(&self, schema: &ProjectRootSchema) -> Vec<String> {
+        return schema.environments();
+    }
+
+    // The env variables resolved for the active environment
+    pub fn build_env(&self, schema: &ProjectRootSchema) -> EnvMap {
+        return schema.build_env(self.active().as_deref());
+    }
+}
@@ -0,0 +1,34 @@
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    signals::{Readable, Signal, Writable},
+};
+use nativedoctor_core::fixtures::FixtureMode;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct FixtureManager {
+    mode: Signal<FixtureMode>,
+}
+
+impl FixtureManager {
+    pub fn provide() -> FixtureManager {
+        return use_context_provider(|| FixtureManager {
+            mode: Signal::new(FixtureMode::Off),
+        });
+    }
+
+    pub fn inject() -> FixtureManager {
+        return use_context::<FixtureManager>();
+    }
+
+    // How the runners of the project use recorded responses
+    pub fn mode(&self) -> FixtureMode {
+        return *self.mode.read();
+    }
+
+    pub fn set_mode(&mut self, mode: FixtureMode) {
+        tracing::debug!("Switched fixtures to {:?}", &mode);
+        self.mode.with_mut(|current| {
+            *current = mode;
+        })
+    }
+}
@@ -0,0 +1,206 @@
+use dioxus::{
+    hooks::{use_context, use_context_provider, use_effect, use_signal},
+    prelude::{KeyboardEvent, Modifiers, ModifiersInteraction},
+    signals::{Readable, Signal, Writable},
+};
+
+/// Something the user can do from the keyboard or the command palette.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Command {
+    OpenPalette,
+    SendRequest,
+    SaveRequest,
+    CloseTab,
+    Search,
+    ImportCurl,
+    ShowVariables,
+    RunCollection,
+    OpenProject,
+}
+
+impl Command {
+    pub fn all() -> Vec<Command> {
+        return vec![
+            Command::OpenPalette,
+            Command::SendRequest,
+            Command::SaveRequest,
+            Command::CloseTab,
+            Command::Search,
+            Command::ImportCurl,
+            Command::ShowVariables,
+            Command::RunCollection,
+            Command::OpenProject,
+        ];
+    }
+
+    pub fn label(&self) -> &'static str {
+        return match self {
+            Command::OpenPalette => "Command palette",
+            Command::SendRequest => "Send request",
+            Command::SaveRequest => "Save request",
+            Command::CloseTab => "Close tab",
+            Command::Search => "Search",
+            Command::ImportCurl => "Import cURL",
+            Command::ShowVariables => "Show variables",
+            Command::RunCollection => "Run collection",
+            Command::OpenProject => "Open project",
+        };
+    }
+}
+
+/// A key combination that runs a command. `primary` is Cmd on macOS and Ctrl elsewhere.
+#[derive(Clone, PartialEq, Debug)]
+pub struct KeyBinding {
+    // the key as named by the keyboard event, lowercase
+    pub key: &'static str,
+    pub primary: bool,
+    pub shift: bool,
+    pub command: Command,
+}
+
+impl KeyBinding {
+    pub fn new(key: &'static str, shift: bool, command: Command) -> KeyBinding {
+        return KeyBinding {
+            key,
+            primary: true,
+            shift,
+            command,
+        };
+    }
+
+    pub fn defaults() -> Vec<KeyBinding> {
+        return vec![
+            KeyBinding::new("k", false, Command::OpenPalette),
+            KeyBinding::new("enter", false, Command::SendRequest),
+            KeyBinding::new("s", false, Command::SaveRequest),
+            KeyBinding::new("w", false, Command::CloseTab),
+            KeyBinding::new("f", true, Command::Search),
+            KeyBinding::new("o", false, Command::OpenProject),
+        ];
+    }
+
+    pub fn matches(&self, event: &KeyboardEvent) -> bool {
+        let modifiers = event.modifiers();
+        let primary = match cfg!(target_os = "macos") {
+            true => modifiers.contains(Modifiers::META),
+            false => modifiers.contains(Modifiers::CONTROL),
+        };
+
+        return primary == self.primary
+            && modifiers.contains(Modifiers::SHIFT) == self.shift
+            && event.key().to_string().to_lowercase() == self.key;
+    }
+
+    // How the shortcut is shown, like ⌘K on macOS or Ctrl+K elsewhere
+    pub fn label(&self) -> String {
+        let mut parts = vec![];
+        let mac = cfg!(target_os = "macos");
+
+        if self.primary {
+            parts.push(if mac { "⌘" } else { "Ctrl" }.to_string());
+        }
+        if self.shift {
+            parts.push(if mac { "⇧" } else { "Shift" }.to_string());
+        }
+
+        let mut key = self.key.to_string();
+        if let Some(first) = key.get_mut(0..1) {
+            first.make_ascii_uppercase();
+        }
+        parts.push(key);
+
+        return parts.join(if mac { "" } else { "+" });
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct DispatchedCommand {
+    id: uuid::Uuid,
+    command: Command,
+}
+
+/// The key bindings of a project view and the last command run in it. Components that
+/// can run a command listen for it with `use_command`.
+#[derive(Clone, Copy, PartialEq)]
+pub struct KeybindingManager {
+    bindings: Signal<Vec<KeyBinding>>,
+    dispatched: Signal<Option<DispatchedCommand>>,
+}
+
+impl KeybindingManager {
+    pub fn provide() -> KeybindingManager {
+        return use_context_provider(|| KeybindingManager {
+            bindings: Signal::new(KeyBinding::defaults()),
+            dispatched: Signal::new(None),
+        });
+    }
+
+    pub fn inject() -> KeybindingManager {
+        return use_context::<KeybindingManager>();
+    }
+
+    pub fn binding_for(&self, command: Command) -> Option<KeyBinding> {
+        return self
+            .bindings
+            .read()
+            .iter()
+            .find(|binding| binding.command == command)
+            .cloned();
+    }
+
+    pub fn dispatch(&mut self, command: Command) {
+        tracing::debug!("Running command {:?}", command);
+        self.dispatched.set(Some(DispatchedCommand {
+            id: uuid::Uuid::new_v4(),
+            command,
+        }));
+    }
+
+    // Runs the command bound to the key pressed, if any. The event is not passed on when it was handled.
+    pub fn handle(&mut self, event: &KeyboardEvent) -> bool {
+        let binding = self
+            .bindings
+            .read()
+            .iter()
+            .find(|binding| binding.matches(event))
+            .cloned();
+
+        return match binding {
+            Some(binding) => {
+                event.prevent_default();
+                event.stop_propagation();
+                self.dispatch(binding.command);
+                true
+            }
+            None => false,
+        };
+    }
+}
+
+// Calls handler each time the command is dispatched. Commands dispatched before the
+// component was mounted are ignored.
+pub fn use_command(command: Command, mut handler: impl FnMut() + 'static) {
+    let keybindings = KeybindingManager::inject();
+    let mut seen = use_signal(|| {
+        keybindings
+            .dispatched
+            .peek()
+            .map(|dispatched| dispatched.id)
+    });
+
+    use_effect(move || {
+        let dispatched = match *keybindings.dispatched.read() {
+            Some(dispatched) => dispatched,
+            None => return,
+        };
+
+        if Some(dispatched.id) == *seen.peek() {
+            return;
+        }
+        seen.set(Some(dispatched.id));
+
+        if dispatched.command == command {
+            handler();
+        }
+    });
+}
@@ -1,5 +1,21 @@
 // mod app;
+mod calls;
+mod environment;
+mod fixtures;
+mod keybindings;
+mod requests;
+mod runs;
+mod tabs;
 mod toasts;
+mod workspaces;
 
 // pub use app::{ApplicationState, ProjectContentLoadingStatus, RequestLoadingStatus};
+pub use calls::{CallManager, CallState};
+pub use environment::EnvironmentManager;
+pub use fixtures::FixtureManager;
+pub use keybindings::{use_command, Command, KeyBinding, KeybindingManager};
+pub use requests::RequestManager;
+pub use runs::{RunManager, RunTarget};
+pub use tabs::{TabItem, TabItemManager, TabType};
 pub use toasts::{ToastCloseMethod, ToastConfig, ToastState, ToastTitle};
+pub use workspaces::{project_label, WorkspaceManager};
@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    prelude::spawn,
+    signals::{Readable, Signal, Writable},
+};
+use nativedoctor_core::{
+    fs::FileObject,
+    index::{RequestIndex, RequestSummary},
+    runner::Runner,
+    schema::roots::{ProjectRootSchema, RequestRootSchema},
+};
+use tokio::sync::Mutex;
+
+use crate::states::{TabItemManager, ToastCloseMethod, ToastConfig, ToastState, ToastTitle};
+
+/// The requests of the project view. They are listed from the index,
+/// and only parsed when they are opened, searched or called.
+#[derive(Clone, Copy, PartialEq)]
+pub struct RequestManager {
+    listed: Signal<Vec<RequestSummary>>,
+    // the requests parsed so far, edited in the request tabs
+    loaded: Signal<Vec<FileObject<RequestRootSchema>>>,
+    index: Signal<Option<Arc<Mutex<RequestIndex>>>>,
+}
+
+impl RequestManager {
+    pub fn provide(loaded: Signal<Vec<FileObject<RequestRootSchema>>>) -> RequestManager {
+        return use_context_provider(|| RequestManager {
+            listed: Signal::new(vec![]),
+            loaded,
+            index: Signal::new(None),
+        });
+    }
+
+    pub fn inject() -> RequestManager {
+        return use_context::<RequestManager>();
+    }
+
+    pub fn listed(&self) -> Vec<RequestSummary> {
+        return self.listed.read().clone();
+    }
+
+    // Lists the requests of the project, the ones loaded before are dropped
+    pub async fn open(&mut self, project: &FileObject<ProjectRootSchema>) -> anyhow::Result<()> {
+        let index = project.index_requests().await?;
+
+        self.listed.set(index.requests.clone());
+        self.loaded.set(vec![]);
+        self.index.set(Some(Arc::new(Mutex::new(index))));
+        return Ok(());
+    }
+
+    // The request with the given name, parsed from its file the first time
+    pub async fn load(&mut self, name: &str) -> anyhow::Result<FileObject<RequestRootSchema>> {
+        if let Some(request) = self.find_loaded(name) {
+            return Ok(request);
+        }
+
+        let index = match self.index.read().clone() {
+            Some(index) => index,
+            None => anyhow::bail!("Request \"{}\" does not exist", name),
+        };
+
+        let mut index = index.lock().await;
+        let request = index.load(name).await?;
+        if let Err(e) = index.save_cache().await {
+            tracing::warn!("{:#}", e);
+        }
+
+        // loaded at the same time by another view, both keep the same request
+        if let Some(request) = self.find_loaded(name) {
+            return Ok(request);
+        }
+
+        self.loaded.write().push(request.clone());
+        return Ok(request);
+    }
+
+    // Loads every request, eg: to search their content
+    pub async fn load_all(&mut self) {
+        for request in self.listed() {
+            if let Err(e) = self.load(&request.name).await {
+                tracing::warn!("{:#}", e);
+            }
+        }
+    }
+
+    // Loads a request and shows it in a tab, a request that doesn't load is reported in a toast
+    pub fn open_in_tab(&self, name: String, mut tabs: TabItemManager, mut toast: ToastState) {
+        let mut manager = *self;
+
+        spawn(async move {
+            match manager.load(&name).await {
+                Ok(request) => tabs.open_request(request.id, name),
+                Err(e) => toast.push(ToastConfig::new(
+                    ToastTitle::Error("Could not open request".to_string()),
+                    Some(format!("{:#}", e)),
+                    ToastCloseMethod::Button,
+                )),
+            };
+        });
+    }
+
+    // Adds a request created in the app, eg: an imported one
+    pub fn add(&mut self, request: FileObject<RequestRootSchema>) {
+        self.listed.write().push(RequestSummary::of(&request));
+        self.loaded.write().push(request);
+    }
+
+    // Replaces a loaded request with its saved version
+    pub fn update(&mut self, request: FileObject<RequestRootSchema>) {
+        self.listed.with_mut(|listed| {
+            for item in listed.iter_mut() {
+                if item.path == request.path {
+                    *item = RequestSummary::of(&request);
+                }
+            }
+        });
+        self.loaded.with_mut(|loaded| {
+            for item in loaded.iter_mut() {
+                if item.id == request.id {
+                    *item = request.clone();
+                }
+            }
+        });
+    }
+
+    // A runner with the loaded requests, edits included, the others are loaded by the runner when called
+    pub async fn runner(
+        &self,
+        project: FileObject<ProjectRootSchema>,
+        edited: Option<FileObject<RequestRootSchema>>,
+    ) -> anyhow::Result<Runner> {
+        let requests = self
+            .loaded
+            .read()
+            .iter()
+            .map(|request| match &edited {
+                Some(edited) if edited.id == request.id => edited.clone(),
+                _ => request.clone(),
+            })
+            .collect();
+
+        return Runner::from_project(project, requests).await;
+    }
+
+    fn find_loaded(&self, name: &str) -> Option<FileObject<RequestRootSchema>> {
+        return self
+            .loaded
+            .read()
+            .iter()
+            .find(|request| request.get_call_name() == name)
+            .cloned();
+    }
+}
@@ -0,0 +1,134 @@
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    prelude::spawn,
+    signals::{Readable, Signal, Writable},
+};
+use nativedoctor_core::{
+    cancel::CancelToken,
+    report::{ReportEntry, ReportFuture, Reporter, RunReport},
+    runner::Runner,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+
+/// What a collection run calls.
+#[derive(Clone, PartialEq, Debug)]
+pub enum RunTarget {
+    Sequence(String),
+    // requests called one after another, like the failed ones of the last run
+    Requests(Vec<String>),
+}
+
+#[derive(Clone, Debug)]
+enum RunEvent {
+    Entry(ReportEntry),
+    Finished(Result<RunReport, String>),
+}
+
+/// The collection run of a project view. Entries show up as each request completes,
+/// the report once the run is over.
+#[derive(Clone, Copy, PartialEq)]
+pub struct RunManager {
+    entries: Signal<Vec<ReportEntry>>,
+    report: Signal<Option<Result<RunReport, String>>>,
+    running: Signal<bool>,
+    cancel: Signal<Option<CancelToken>>,
+}
+
+impl RunManager {
+    pub fn provide() -> RunManager {
+        return use_context_provider(|| RunManager {
+            entries: Signal::new(vec![]),
+            report: Signal::new(None),
+            running: Signal::new(false),
+            cancel: Signal::new(None),
+        });
+    }
+
+    pub fn inject() -> RunManager {
+        return use_context::<RunManager>();
+    }
+
+    pub fn entries(&self) -> Vec<ReportEntry> {
+        return self.entries.read().clone();
+    }
+
+    pub fn report(&self) -> Option<Result<RunReport, String>> {
+        return self.report.read().clone();
+    }
+
+    pub fn is_running(&self) -> bool {
+        return *self.running.read();
+    }
+
+    // Names of the entries that failed in the last run, hooks included
+    pub fn failed(&self) -> Vec<String> {
+        let mut names = vec![];
+        for entry in self.entries.read().iter() {
+            if !entry.passed() && !names.contains(&entry.name) {
+                names.push(entry.name.clone());
+            }
+        }
+        return names;
+    }
+
+    // Runs on the tokio runtime, replacing the last run
+    pub fn start(&mut self, mut runner: Runner, target: RunTarget) {
+        if self.is_running() {
+            return;
+        }
+
+        let (sender, mut receiver) = unbounded_channel();
+        runner.add_reporter(Box::new(ChannelReporter {
+            sender: sender.clone(),
+        }));
+
+        self.entries.set(vec![]);
+        self.report.set(None);
+        self.running.set(true);
+        self.cancel.set(Some(runner.cancel.clone()));
+
+        tokio::spawn(async move {
+            let report = match target {
+                RunTarget::Sequence(name) => runner.run_sequence(&name).await,
+                RunTarget::Requests(names) => runner.run_requests("failed", names).await,
+            };
+            let _ = sender.send(RunEvent::Finished(report.map_err(|e| format!("{:#}", e))));
+        });
+
+        let mut manager = *self;
+        spawn(async move {
+            while let Some(event) = receiver.recv().await {
+                match event {
+                    RunEvent::Entry(entry) => manager.entries.write().push(entry),
+                    RunEvent::Finished(report) => {
+                        manager.report.set(Some(report));
+                        manager.running.set(false);
+                        manager.cancel.set(None);
+                    }
+                };
+            }
+        });
+    }
+
+    pub fn cancel(&self) {
+        if let Some(cancel) = self.cancel.read().as_ref() {
+            cancel.cancel();
+        }
+    }
+}
+
+struct ChannelReporter {
+    sender: UnboundedSender<RunEvent>,
+}
+
+impl Reporter for ChannelReporter {
+    fn on_entry(&mut self, entry: &ReportEntry) {
+        let _ = self.sender.send(RunEvent::Entry(entry.clone()));
+    }
+
+    fn finish<'a>(&'a mut self, _report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async {
+            return Ok(());
+        });
+    }
+}
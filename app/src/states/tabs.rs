@@ -0,0 +1,139 @@
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    signals::{Readable, Signal, Writable},
+};
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum TabType {
+    // id of the request file object
+    Request(uuid::Uuid),
+    // the env variables of the project
+    Environment,
+    // runs a whole sequence
+    Runner,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TabItem {
+    pub id: uuid::Uuid,
+    pub name: String,
+    pub tab_type: TabType,
+    // edited but not saved yet
+    pub dirty: bool,
+}
+
+impl TabItem {
+    pub fn new(name: String, tab_type: TabType) -> TabItem {
+        return TabItem {
+            id: uuid::Uuid::new_v4(),
+            name,
+            tab_type,
+            dirty: false,
+        };
+    }
+}
+
+/// The tabs opened in the project view and the one shown.
+#[derive(Clone, Copy, PartialEq)]
+pub struct TabItemManager {
+    tabs: Signal<Vec<TabItem>>,
+    active: Signal<Option<uuid::Uuid>>,
+}
+
+impl TabItemManager {
+    pub fn provide() -> TabItemManager {
+        return use_context_provider(|| TabItemManager {
+            tabs: Signal::new(vec![]),
+            active: Signal::new(None),
+        });
+    }
+
+    pub fn inject() -> TabItemManager {
+        return use_context::<TabItemManager>();
+    }
+
+    pub fn tabs(&self) -> Vec<TabItem> {
+        return self.tabs.read().clone();
+    }
+
+    pub fn active(&self) -> Option<TabItem> {
+        let active = self.active.read().clone()?;
+        return self
+            .tabs
+            .read()
+            .iter()
+            .find(|tab| tab.id == active)
+            .cloned();
+    }
+
+    // The request shown, if the active tab is a request
+    pub fn active_request(&self) -> Option<uuid::Uuid> {
+        return match self.active()?.tab_type {
+            TabType::Request(id) => Some(id),
+            _ => None,
+        };
+    }
+
+    pub fn set_active(&mut self, id: uuid::Uuid) {
+        self.active.set(Some(id));
+    }
+
+    // Shows the tab of a request, opening one if it isn't open yet
+    pub fn open_request(&mut self, request: uuid::Uuid, name: String) {
+        self.open(name, TabType::Request(request));
+    }
+
+    // Shows a tab of the given type, opening one if there isn't one yet
+    pub fn open(&mut self, name: String, tab_type: TabType) {
+        let existing = self
+            .tabs
+            .read()
+            .iter()
+            .find(|tab| tab.tab_type == tab_type)
+            .map(|tab| tab.id);
+
+        let id = match existing {
+            Some(id) => id,
+            None => {
+                let tab = TabItem::new(name, tab_type);
+                let id = tab.id;
+                self.tabs.write().push(tab);
+                id
+            }
+        };
+
+        self.set_active(id);
+    }
+
+    pub fn is_dirty(&self, id: uuid::Uuid) -> bool {
+        return self.tabs.read().iter().any(|tab| tab.id == id && tab.dirty);
+    }
+
+    pub fn set_dirty(&mut self, id: uuid::Uuid, dirty: bool) {
+        if self.is_dirty(id) == dirty {
+            return;
+        }
+
+        self.tabs.with_mut(|tabs| {
+            if let Some(tab) = tabs.iter_mut().find(|tab| tab.id == id) {
+                tab.dirty = dirty;
+            }
+        });
+    }
+
+    // Closes a tab, its unsaved changes are lost. The tab next to it becomes active.
+    pub fn close(&mut self, id: uuid::Uuid) {
+        let mut tabs = self.tabs.write();
+        let index = match tabs.iter().position(|tab| tab.id == id) {
+            Some(index) => index,
+            None => return,
+        };
+        tabs.remove(index);
+
+        if *self.active.read() == Some(id) {
+            let next = tabs.get(index).or(tabs.last()).map(|tab| tab.id);
+            drop(tabs);
+            self.active.set(next);
+        }
+    }
+}
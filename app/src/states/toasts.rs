@@ -54,7 +54,7 @@ impl ToastConfig {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct ToastState {
     toasts: Signal<Vec<ToastConfig>>,
 }
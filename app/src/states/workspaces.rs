@@ -0,0 +1,86 @@
+use std::path::PathBuf;
+
+use dioxus::{
+    hooks::{use_context, use_context_provider},
+    signals::{Readable, Signal, Writable},
+};
+use rfd::AsyncFileDialog;
+
+/// The project files open in the app and the one shown. Each project gets its own
+/// view, with its own environment and tabs.
+#[derive(Clone, Copy, PartialEq)]
+pub struct WorkspaceManager {
+    projects: Signal<Vec<PathBuf>>,
+    active: Signal<Option<PathBuf>>,
+}
+
+impl WorkspaceManager {
+    pub fn provide(projects: Vec<PathBuf>) -> WorkspaceManager {
+        return use_context_provider(|| WorkspaceManager {
+            active: Signal::new(projects.first().cloned()),
+            projects: Signal::new(projects),
+        });
+    }
+
+    pub fn inject() -> WorkspaceManager {
+        return use_context::<WorkspaceManager>();
+    }
+
+    pub fn projects(&self) -> Vec<PathBuf> {
+        return self.projects.read().clone();
+    }
+
+    pub fn active(&self) -> Option<PathBuf> {
+        return self.active.read().clone();
+    }
+
+    pub fn set_active(&mut self, path: PathBuf) {
+        self.active.set(Some(path));
+    }
+
+    // Shows a project, opening it if it isn't open yet
+    pub fn open(&mut self, path: PathBuf) {
+        if !self.projects.read().contains(&path) {
+            tracing::info!("Opening project {:?}", &path);
+            self.projects.write().push(path.clone());
+        }
+
+        self.set_active(path);
+    }
+
+    // Asks for a project file and opens it
+    pub async fn open_with_picker(&mut self) {
+        let picker = AsyncFileDialog::new()
+            .set_title("Select project file")
+            .add_filter("Project", &["nd-project"]);
+
+        if let Some(file) = picker.pick_file().await {
+            self.open(file.path().to_path_buf());
+        }
+    }
+
+    // Closes a project, the one next to it is shown if it was active
+    pub fn close(&mut self, path: &PathBuf) {
+        let mut projects = self.projects.write();
+        let index = match projects.iter().position(|project| project == path) {
+            Some(index) => index,
+            None => return,
+        };
+        projects.remove(index);
+
+        if self.active.read().as_ref() == Some(path) {
+            let next = projects.get(index).or(projects.last()).cloned();
+            drop(projects);
+            self.active.set(next);
+        }
+    }
+}
+
+// The name shown for a project, the folder holding its project file
+pub fn project_label(path: &PathBuf) -> String {
+    return path
+        .parent()
+        .and_then(|dir| dir.file_name())
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+}
@@ -1 +1,2 @@
-pub mod project;
\ No newline at end of file
+pub mod project;
+pub mod workspace;
\ No newline at end of file
@@ -0,0 +1,55 @@
+use dioxus::prelude::*;
+
+use crate::{states::CallManager, views::project::response_viewer::ResponseViewer};
+
+// The outcome of the last time a request was sent from the app, or its progress while it runs
+#[component]
+pub fn CallResults(request: uuid::Uuid) -> Element {
+    let calls = CallManager::inject();
+
+    let call = match calls.get(request) {
+        Some(call) => call,
+        None => {
+            return rsx! {
+                p { class: "text-sm text-gray-500", "Send the request to see its response" }
+            };
+        }
+    };
+
+    return rsx! {
+        div {
+            class: "flex flex-col gap-2 min-h-0",
+            if call.running {
+                div {
+                    class: "flex flex-col text-sm text-gray-500",
+                    for line in call.progress.iter() {
+                        span { "{line}" }
+                    }
+                    span { "Waiting for a response…" }
+                }
+            }
+
+            match call.result {
+                Some(Ok(result)) => {
+                    let sent = result.started.format("%H:%M:%S UTC").to_string();
+                    rsx! {
+                        p {
+                            class: "text-sm text-gray-500",
+                            "{result.request.method} {result.request.url} sent at {sent}"
+                        }
+                        ResponseViewer {
+                            status: result.response.status,
+                            headers: result.response.headers.clone(),
+                            body: String::from_utf8_lossy(&result.response.body).to_string(),
+                            duration_ms: result.duration.as_millis() as u64,
+                        }
+                    }
+                }
+                Some(Err(e)) => rsx! {
+                    pre { class: "p-2 text-sm text-red-500 whitespace-pre-wrap", "{e}" }
+                },
+                None => rsx! {},
+            }
+        }
+    };
+}
@@ -0,0 +1,241 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    fs::FileObject,
+    report::ReportEntry,
+    schema::roots::{ProjectRootSchema, RequestRootSchema},
+    tags::TagFilter,
+};
+
+use crate::states::{
+    EnvironmentManager, FixtureManager, RequestManager, RunManager, RunTarget, ToastCloseMethod,
+    ToastConfig, ToastState, ToastTitle,
+};
+
+const CELL_CLASS: &'static str = "p-1 border-b";
+
+// Runs a whole sequence, or the steps of it with some tags, showing each request as it completes
+#[component]
+pub fn CollectionRunner() -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let mut requests = RequestManager::inject();
+    let environment = EnvironmentManager::inject();
+    let fixtures = FixtureManager::inject();
+    let mut runs = RunManager::inject();
+    let mut toast = ToastState::inject();
+    let mut sequence = use_signal(|| "main".to_string());
+    let mut tags = use_signal(|| String::new());
+    let mut exclude_tags = use_signal(|| String::new());
+
+    let sequences = match project() {
+        Some(project) => project.object.calls.names(),
+        None => vec![],
+    };
+
+    let mut start = move |target: RunTarget| {
+        let project = match project() {
+            Some(project) => project,
+            None => return,
+        };
+
+        spawn(async move {
+            let mut runner = match requests.runner(project, None).await {
+                Ok(runner) => runner,
+                Err(e) => {
+                    toast.push(ToastConfig::new(
+                        ToastTitle::Error("Could not start the run".to_string()),
+                        Some(format!("{:#}", e)),
+                        ToastCloseMethod::Button,
+                    ));
+                    return;
+                }
+            };
+            runner.environment = environment.active();
+            runner.fixture_mode = fixtures.mode();
+            // failed requests were already selected, their steps' tags aren't known anymore
+            if let RunTarget::Sequence(_) = target {
+                runner.tags = TagFilter::new(split_tags(&tags()), split_tags(&exclude_tags()));
+            }
+            runner.enable_history().await;
+
+            // the rows of the run count the assertions of their request,
+            // requests that don't load fail in the run
+            let names = match &target {
+                RunTarget::Sequence(name) => runner.get_sequence(name).unwrap_or_default(),
+                RunTarget::Requests(names) => names.clone(),
+            };
+            for name in names {
+                let _ = requests.load(&name).await;
+            }
+
+            runs.start(runner, target);
+        });
+    };
+
+    // hooks can't be called on their own, only failed requests are run again
+    let failed = runs
+        .failed()
+        .into_iter()
+        .filter(|name| {
+            requests
+                .listed()
+                .iter()
+                .any(|request| request.name == *name)
+        })
+        .collect::<Vec<String>>();
+
+    return rsx! {
+        div {
+            class: "flex-grow p-4 flex flex-col gap-4 overflow-auto",
+            div {
+                class: "flex gap-2 items-center",
+                select {
+                    class: "border rounded-md px-2 py-1",
+                    value: "{sequence}",
+                    onchange: move |e| sequence.set(e.value()),
+                    for name in sequences {
+                        option { value: "{name}", selected: name == sequence(), "{name}" }
+                    }
+                }
+                input {
+                    value: "{tags}",
+                    placeholder: "Tags, eg: smoke,auth",
+                    spellcheck: false,
+                    oninput: move |e| tags.set(e.value()),
+                }
+                input {
+                    value: "{exclude_tags}",
+                    placeholder: "Excluded tags",
+                    spellcheck: false,
+                    oninput: move |e| exclude_tags.set(e.value()),
+                }
+
+                if runs.is_running() {
+                    button {
+                        class: "px-2 py-1 bg-red-100 hover:bg-red-200 rounded-md",
+                        onclick: move |_| runs.cancel(),
+                        "Cancel"
+                    }
+                } else {
+                    button {
+                        class: "px-2 py-1 bg-blue-500 hover:bg-blue-600 text-white rounded-md",
+                        onclick: move |_| start(RunTarget::Sequence(sequence())),
+                        "Run"
+                    }
+                    button {
+                        class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md disabled:opacity-50",
+                        disabled: failed.is_empty(),
+                        onclick: {
+                            let failed = failed.clone();
+                            move |_| start(RunTarget::Requests(failed.clone()))
+                        },
+                        "Run failed again ({failed.len()})"
+                    }
+                }
+            }
+
+            match runs.report() {
+                Some(Ok(report)) => rsx! {
+                    p {
+                        class: if report.passed() { "text-sm text-green-600" } else { "text-sm text-red-600" },
+                        "{report.name}: {report.entries.len() - report.failures()} passed, {report.failures()} failed, {report.skipped()} skipped in {report.duration_ms}ms"
+                        if report.cancelled { ", cancelled" }
+                    }
+                },
+                Some(Err(e)) => rsx! {
+                    pre { class: "text-sm text-red-500 whitespace-pre-wrap", "{e}" }
+                },
+                None if runs.is_running() => rsx! {
+                    p { class: "text-sm text-gray-500", "Running…" }
+                },
+                None => rsx! {},
+            }
+
+            if !runs.entries().is_empty() {
+                table {
+                    class: "w-full text-sm text-left",
+                    tr {
+                        th { class: CELL_CLASS, "Request" }
+                        th { class: CELL_CLASS, "Status" }
+                        th { class: CELL_CLASS, "Duration" }
+                        th { class: CELL_CLASS, "Assertions" }
+                        th { class: CELL_CLASS, "Error" }
+                    }
+                    for (index, entry) in runs.entries().into_iter().enumerate() {
+                        EntryRow { key: "{index}", entry }
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[component]
+fn EntryRow(entry: ReportEntry) -> Element {
+    let requests = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+
+    // assertions, the response schema and the snapshot are all checked
+    let assertions = requests()
+        .iter()
+        .find(|request| request.get_call_name() == entry.name)
+        .map(|request| {
+            request.object.assert.len()
+                + request.object.assert_lines.len()
+                + request.object.response_schema.iter().count()
+                + request.object.snapshot.iter().count()
+        })
+        .unwrap_or_default();
+
+    let row_class = if entry.skipped {
+        "text-gray-500"
+    } else if !entry.passed() {
+        "bg-red-50"
+    } else {
+        ""
+    };
+
+    return rsx! {
+        tr {
+            class: row_class,
+            td { class: "{CELL_CLASS} font-medium", "{entry.name}" }
+            td {
+                class: CELL_CLASS,
+                match entry.status {
+                    Some(status) => rsx! { "{status}" },
+                    None if entry.skipped => rsx! { "skipped" },
+                    None => rsx! { "—" },
+                }
+            }
+            td { class: CELL_CLASS, "{entry.duration_ms}ms" }
+            td {
+                class: CELL_CLASS,
+                if !entry.assertion_failures.is_empty() {
+                    div {
+                        class: "flex flex-col text-red-600",
+                        for failure in entry.assertion_failures.iter() {
+                            span { "{failure}" }
+                        }
+                    }
+                } else if assertions > 0 && entry.status.is_some() {
+                    span { class: "text-green-600", "{assertions} passed" }
+                } else {
+                    "—"
+                }
+            }
+            td {
+                class: "{CELL_CLASS} break-all",
+                // assertion failures are already shown
+                if entry.assertion_failures.is_empty() {
+                    {entry.error.clone().unwrap_or_default()}
+                }
+            }
+        }
+    };
+}
+
+fn split_tags(value: &str) -> Vec<String> {
+    return value
+        .split(',')
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+}
@@ -0,0 +1,120 @@
+use dioxus::prelude::*;
+use nativedoctor_core::search::fuzzy_score;
+
+use crate::{
+    components::Dialog,
+    states::{Command, KeybindingManager, RequestManager, TabItemManager, ToastState},
+};
+
+const MAX_ITEMS: usize = 15;
+
+#[derive(Clone, PartialEq)]
+enum PaletteItem {
+    Command(Command),
+    // call name of a request to open
+    Request(String),
+}
+
+impl PaletteItem {
+    fn label(&self) -> String {
+        return match self {
+            PaletteItem::Command(command) => command.label().to_string(),
+            PaletteItem::Request(name) => format!("Open {}", name),
+        };
+    }
+}
+
+// Runs any command, or opens a request, by typing part of its name
+#[component]
+pub fn CommandPalette(show: Signal<bool>) -> Element {
+    let requests = RequestManager::inject();
+    let mut keybindings = KeybindingManager::inject();
+    let tabs = TabItemManager::inject();
+    let toast = ToastState::inject();
+    let mut query = use_signal(|| String::new());
+    let mut selected = use_signal(|| 0usize);
+
+    let mut items = Command::all()
+        .into_iter()
+        .filter(|command| *command != Command::OpenPalette)
+        .map(PaletteItem::Command)
+        .chain(
+            requests
+                .listed()
+                .into_iter()
+                .map(|request| PaletteItem::Request(request.name)),
+        )
+        .filter_map(|item| fuzzy_score(&query(), &item.label()).map(|score| (score, item)))
+        .collect::<Vec<(i64, PaletteItem)>>();
+    items.sort_by(|a, b| b.0.cmp(&a.0));
+    let items = items
+        .into_iter()
+        .take(MAX_ITEMS)
+        .map(|(_, item)| item)
+        .collect::<Vec<PaletteItem>>();
+
+    let mut run = move |item: PaletteItem| {
+        query.set(String::new());
+        selected.set(0);
+        show.set(false);
+
+        match item {
+            PaletteItem::Command(command) => keybindings.dispatch(command),
+            PaletteItem::Request(name) => requests.open_in_tab(name, tabs, toast),
+        };
+    };
+
+    return rsx! {
+        Dialog {
+            show,
+            div {
+                class: "p-4 bg-white flex flex-col gap-2 w-[600px]",
+                input {
+                    value: "{query}",
+                    placeholder: "Type a command or a request name",
+                    autofocus: true,
+                    autocomplete: "off",
+                    spellcheck: false,
+                    oninput: move |e| {
+                        query.set(e.value());
+                        selected.set(0);
+                    },
+                    onkeydown: {
+                        let items = items.clone();
+                        move |e: KeyboardEvent| match e.key() {
+                            Key::ArrowDown => selected.set((selected() + 1).min(items.len().saturating_sub(1))),
+                            Key::ArrowUp => selected.set(selected().saturating_sub(1)),
+                            Key::Enter => {
+                                if let Some(item) = items.get(selected()) {
+                                    run(item.clone());
+                                }
+                            }
+                            Key::Escape => show.set(false),
+                            _ => {}
+                        }
+                    },
+                }
+
+                div {
+                    class: "flex flex-col max-h-96 overflow-auto",
+                    for (index, item) in items.into_iter().enumerate() {
+                        button {
+                            key: "{item.label()}",
+                            class: if index == selected() { "p-2 text-left flex justify-between bg-gray-200" } else { "p-2 text-left flex justify-between hover:bg-gray-100" },
+                            onclick: {
+                                let item = item.clone();
+                                move |_| run(item.clone())
+                            },
+                            span { "{item.label()}" }
+                            if let PaletteItem::Command(command) = item {
+                                if let Some(binding) = keybindings.binding_for(command) {
+                                    span { class: "text-sm text-gray-500", "{binding.label()}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
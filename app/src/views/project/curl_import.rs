@@ -0,0 +1,79 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    fs::FileObject, import::curl::parse_curl, schema::roots::ProjectRootSchema,
+};
+
+use crate::{
+    components::Dialog,
+    states::{RequestManager, ToastCloseMethod, ToastConfig, ToastState, ToastTitle},
+};
+
+#[component]
+pub fn CurlImportDialog(show: Signal<bool>) -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let requests = RequestManager::inject();
+    let toast = ToastState::inject();
+    let mut name = use_signal(|| String::new());
+    let mut command = use_signal(|| String::new());
+
+    let import = move |_: MouseEvent| {
+        let mut show = show.clone();
+        let mut requests = requests.clone();
+        let mut toast = toast.clone();
+
+        spawn(async move {
+            let project = match project() {
+                Some(project) => project,
+                None => return,
+            };
+
+            let result = match parse_curl(&command(), &project.get_root_dir()) {
+                Ok(request) => project.create_request(&name(), request).await,
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(request) => {
+                    requests.add(request);
+                    name.set(String::new());
+                    command.set(String::new());
+                    show.set(false);
+                }
+                Err(e) => toast.push(ToastConfig::new(
+                    ToastTitle::Error("Could not import cURL command".to_string()),
+                    Some(e.to_string()),
+                    ToastCloseMethod::Button,
+                )),
+            };
+        });
+    };
+
+    return rsx! {
+        Dialog {
+            show,
+            div {
+                class: "p-4 bg-white flex flex-col gap-2 w-[500px]",
+                h1 { "Import cURL" }
+                input {
+                    value: "{name}",
+                    placeholder: "Request name",
+                    autocomplete: "off",
+                    spellcheck: false,
+                    oninput: move |e| name.set(e.value()),
+                }
+                textarea {
+                    class: "h-40 font-mono text-sm",
+                    value: "{command}",
+                    placeholder: "curl https://example.com",
+                    spellcheck: false,
+                    oninput: move |e| command.set(e.value()),
+                }
+                button {
+                    class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md",
+                    onclick: import,
+                    "Import"
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,233 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    editor::SchemaEditor, fs::FileObject, interpolation::value_to_string,
+    schema::roots::ProjectRootSchema,
+};
+
+use crate::states::{EnvironmentManager, ToastCloseMethod, ToastConfig, ToastState, ToastTitle};
+
+const CELL_CLASS: &'static str = "p-1 border-b";
+
+// Every env variable with its default, its override in each environment and the value
+// resolved for the active environment. Edits are saved to the project file when a cell loses focus.
+#[component]
+pub fn EnvironmentPanel() -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let environment = EnvironmentManager::inject();
+    let toast = ToastState::inject();
+    let mut new_variable = use_signal(|| String::new());
+    let mut revealed = use_signal(|| Vec::<String>::new());
+
+    let project_object = match project() {
+        Some(project) => project,
+        None => return rsx! {},
+    };
+    let schema = &project_object.object;
+    let environments = schema.environments();
+    let resolved = environment.build_env(schema);
+
+    let mut names = schema.env.keys().cloned().collect::<Vec<String>>();
+    names.sort();
+
+    // applies an edit to the project file and reloads the project,
+    // the editor keeps the order and the fields of the file that the schema doesn't know
+    let update = move |edit: Box<dyn FnOnce(&mut SchemaEditor) -> anyhow::Result<()>>| {
+        let mut project = project.clone();
+        let mut toast = toast.clone();
+
+        let path = match project() {
+            Some(project) => project.path,
+            None => return,
+        };
+
+        spawn(async move {
+            let saved = async {
+                let mut editor = SchemaEditor::open(&path).await?;
+                edit(&mut editor)?;
+                editor.save().await?;
+                return ProjectRootSchema::load(&path).await;
+            };
+
+            match saved.await {
+                Ok(saved) => project.set(Some(saved)),
+                Err(e) => toast.push(ToastConfig::new(
+                    ToastTitle::Error("Could not save variables".to_string()),
+                    Some(format!("{:#}", e)),
+                    ToastCloseMethod::Button,
+                )),
+            };
+        });
+    };
+
+    return rsx! {
+        div {
+            class: "flex-grow p-4 flex flex-col gap-4 overflow-auto",
+            table {
+                class: "w-full text-sm text-left",
+                tr {
+                    th { class: CELL_CLASS, "Variable" }
+                    th { class: CELL_CLASS, "default" }
+                    for name in environments.clone() {
+                        th { class: CELL_CLASS, "{name}" }
+                    }
+                    th { class: CELL_CLASS, "Resolved ({environment.active().unwrap_or(\"default\".to_string())})" }
+                    th { class: CELL_CLASS, "Secret" }
+                    th { class: CELL_CLASS }
+                }
+
+                for name in names {
+                    tr {
+                        key: "{name}",
+                        td { class: "{CELL_CLASS} font-medium", "{name}" }
+                        td {
+                            class: CELL_CLASS,
+                            ValueInput {
+                                value: Some(schema.env[&name].default.clone()),
+                                placeholder: "null",
+                                onsave: {
+                                    let (update, name) = (update.clone(), name.clone());
+                                    move |value: serde_yaml::Value| {
+                                        let name = name.clone();
+                                        update(Box::new(move |editor| editor.set_variable(&name, None, value)));
+                                    }
+                                },
+                            }
+                        }
+                        for environment_name in environments.clone() {
+                            td {
+                                class: CELL_CLASS,
+                                ValueInput {
+                                    value: schema.env[&name].overrides.get(&environment_name).cloned(),
+                                    placeholder: "default",
+                                    onsave: {
+                                        let (update, name) = (update.clone(), name.clone());
+                                        move |value: serde_yaml::Value| {
+                                            let (name, environment_name) = (name.clone(), environment_name.clone());
+                                            // an empty override falls back to the default
+                                            update(Box::new(move |editor| match value.is_null() {
+                                                true => editor.unset_override(&name, &environment_name),
+                                                false => editor.set_variable(&name, Some(&environment_name), value),
+                                            }));
+                                        }
+                                    },
+                                }
+                            }
+                        }
+                        td {
+                            class: "{CELL_CLASS} break-all",
+                            {
+                                let value = resolved.get(&name).map(value_to_string).unwrap_or_default();
+                                match schema.is_secret(&name) && !revealed().contains(&name) {
+                                    true => "••••••".to_string(),
+                                    false => value,
+                                }
+                            }
+                        }
+                        td {
+                            class: CELL_CLASS,
+                            input {
+                                r#type: "checkbox",
+                                checked: schema.is_secret(&name),
+                                onchange: {
+                                    let (update, name) = (update.clone(), name.clone());
+                                    move |e: FormEvent| {
+                                        let (name, secret) = (name.clone(), e.checked());
+                                        update(Box::new(move |editor| editor.set_secret(&name, secret)));
+                                    }
+                                },
+                            }
+                            if schema.is_secret(&name) {
+                                button {
+                                    class: "px-1 text-gray-500 hover:text-black",
+                                    onclick: {
+                                        let name = name.clone();
+                                        move |_| revealed.with_mut(|revealed| match revealed.contains(&name) {
+                                            true => revealed.retain(|item| *item != name),
+                                            false => revealed.push(name.clone()),
+                                        })
+                                    },
+                                    if revealed().contains(&name) { "hide" } else { "show" }
+                                }
+                            }
+                        }
+                        td {
+                            class: CELL_CLASS,
+                            button {
+                                class: "text-gray-500 hover:text-black",
+                                onclick: {
+                                    let (update, name) = (update.clone(), name.clone());
+                                    move |_| {
+                                        let name = name.clone();
+                                        update(Box::new(move |editor| editor.remove_variable(&name)));
+                                    }
+                                },
+                                "×"
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "flex gap-2",
+                input {
+                    value: "{new_variable}",
+                    placeholder: "New variable",
+                    autocomplete: "off",
+                    spellcheck: false,
+                    oninput: move |e| new_variable.set(e.value()),
+                }
+                button {
+                    class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md disabled:opacity-50",
+                    disabled: new_variable().trim().is_empty() || schema.env.contains_key(new_variable().trim()),
+                    onclick: {
+                        let update = update.clone();
+                        move |_| {
+                            let name = new_variable().trim().to_string();
+                            new_variable.set(String::new());
+                            update(Box::new(move |editor| {
+                                editor.set_variable(&name, None, serde_yaml::Value::String(String::new()))
+                            }));
+                        }
+                    },
+                    "Add variable"
+                }
+            }
+        }
+    };
+}
+
+// An input for a yaml value, parsed when it loses focus so numbers and booleans keep their type.
+// Clearing it gives null.
+#[component]
+fn ValueInput(
+    value: Option<serde_yaml::Value>,
+    placeholder: String,
+    onsave: EventHandler<serde_yaml::Value>,
+) -> Element {
+    let text = match &value {
+        Some(serde_yaml::Value::Null) | None => String::new(),
+        Some(value) => value_to_string(value),
+    };
+
+    return rsx! {
+        input {
+            class: "w-full",
+            value: "{text}",
+            placeholder: "{placeholder}",
+            spellcheck: false,
+            onchange: move |e: FormEvent| {
+                let input = e.value();
+                let parsed = match input.trim().is_empty() {
+                    true => serde_yaml::Value::Null,
+                    false => serde_yaml::from_str::<serde_yaml::Value>(&input)
+                        .unwrap_or(serde_yaml::Value::String(input.clone())),
+                };
+
+                if Some(&parsed) != value.as_ref() {
+                    onsave.call(parsed);
+                }
+            },
+        }
+    };
+}
@@ -0,0 +1,35 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{fs::FileObject, schema::roots::ProjectRootSchema};
+
+use crate::states::EnvironmentManager;
+
+#[component]
+pub fn EnvironmentSelector() -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let mut manager = EnvironmentManager::inject();
+
+    let environments = match project() {
+        Some(project) => manager.environments(&project.object),
+        None => vec![],
+    };
+    let active = manager.active().unwrap_or_default();
+
+    return rsx! {
+        select {
+            class: "w-full border rounded-md px-2 py-1",
+            value: "{active}",
+            onchange: move |e| {
+                let value = e.value();
+                manager.set_active(if value.is_empty() { None } else { Some(value) });
+            },
+            option { value: "", selected: active.is_empty(), "default" }
+            for environment in environments {
+                option {
+                    value: "{environment}",
+                    selected: environment == active,
+                    "{environment}"
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,36 @@
+use dioxus::prelude::*;
+use nativedoctor_core::fixtures::FixtureMode;
+
+use crate::states::FixtureManager;
+
+const MODES: [(FixtureMode, &str, &str); 4] = [
+    (FixtureMode::Off, "off", "send requests"),
+    (FixtureMode::Record, "record", "record responses"),
+    (FixtureMode::Replay, "replay", "replay fixtures"),
+    (FixtureMode::Verify, "verify", "verify against fixtures"),
+];
+
+#[component]
+pub fn FixtureSelector() -> Element {
+    let mut manager = FixtureManager::inject();
+    let active = manager.mode();
+
+    return rsx! {
+        select {
+            class: "w-full border rounded-md px-2 py-1",
+            onchange: move |e| {
+                let value = e.value();
+                if let Some((mode, _, _)) = MODES.iter().find(|(_, name, _)| *name == value) {
+                    manager.set_mode(*mode);
+                }
+            },
+            for (mode, name, label) in MODES {
+                option {
+                    value: "{name}",
+                    selected: mode == active,
+                    "{label}"
+                }
+            }
+        }
+    };
+}
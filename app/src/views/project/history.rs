@@ -0,0 +1,153 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    diff::{diff_history_entries, DiffOptions},
+    fs::FileObject,
+    history::{HistoryEntry, HistoryStore, HISTORY_LIMIT},
+    schema::roots::ProjectRootSchema,
+};
+
+use crate::views::project::{response_diff::ResponseDiffView, response_viewer::ResponseViewer};
+
+#[component]
+pub fn HistoryPanel(name: String) -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let mut selected = use_signal(|| None::<HistoryEntry>);
+    // an older entry to diff the selected one against
+    let mut compare = use_signal(|| None::<HistoryEntry>);
+
+    let entries = use_resource(move || {
+        let name = name.clone();
+        async move {
+            let project = match project() {
+                Some(project) => project,
+                None => return Ok(vec![]),
+            };
+
+            HistoryStore::for_project(&project.get_root_dir())
+                .for_request(&name, Some(HISTORY_LIMIT))
+                .await
+        }
+    });
+
+    let list = match &*entries.read_unchecked() {
+        Some(Ok(entries)) => entries.clone(),
+        Some(Err(e)) => {
+            return rsx! {
+                p { class: "text-red-500", "{e}" }
+            };
+        }
+        None => {
+            return rsx! {
+                p { "Loading history" }
+            };
+        }
+    };
+
+    if list.is_empty() {
+        return rsx! {
+            p { class: "text-gray-500", "This request hasn't been sent yet" }
+        };
+    }
+
+    return rsx! {
+        div {
+            class: "flex gap-4 min-h-0",
+            div {
+                class: "w-64 flex flex-col overflow-auto",
+                for entry in list {
+                    div {
+                        key: "{entry.id}",
+                        class: "flex items-start",
+                        button {
+                            class: if selected().is_some_and(|s| s.id == entry.id) { "flex-grow p-2 text-left bg-gray-200" } else { "flex-grow p-2 text-left hover:bg-gray-100" },
+                            onclick: {
+                                let entry = entry.clone();
+                                move |_| {
+                                    selected.set(Some(entry.clone()));
+                                    compare.set(None);
+                                }
+                            },
+                            div {
+                                class: "flex gap-2 text-sm",
+                                span { class: "font-medium", "{entry.exchange.response.status}" }
+                                span { "{entry.exchange.response.duration_ms}ms" }
+                                if let Some(environment) = &entry.exchange.environment {
+                                    span { class: "text-gray-500", "{environment}" }
+                                }
+                            }
+                            span { class: "text-xs text-gray-500", "{format_age(entry.exchange.created_at)}" }
+                        }
+                        if selected().is_some_and(|s| s.id != entry.id) {
+                            button {
+                                class: if compare().is_some_and(|c| c.id == entry.id) { "p-2 text-xs bg-gray-300" } else { "p-2 text-xs text-gray-500 hover:bg-gray-100" },
+                                title: "Diff the selected response against this one",
+                                onclick: {
+                                    let entry = entry.clone();
+                                    move |_| match compare().is_some_and(|c| c.id == entry.id) {
+                                        true => compare.set(None),
+                                        false => compare.set(Some(entry.clone())),
+                                    }
+                                },
+                                "diff"
+                            }
+                        }
+                    }
+                }
+            }
+
+            div {
+                class: "flex-grow min-w-0",
+                match (selected(), compare()) {
+                    (Some(entry), Some(other)) => rsx! {
+                        div {
+                            class: "flex flex-col gap-2",
+                            p {
+                                class: "text-sm text-gray-500",
+                                "{format_age(other.exchange.created_at)} → {format_age(entry.exchange.created_at)}"
+                            }
+                            ResponseDiffView {
+                                diff: diff_history_entries(&other, &entry, &DiffOptions::default()),
+                            }
+                        }
+                    },
+                    (Some(entry), None) => rsx! {
+                        div {
+                            class: "flex flex-col gap-2",
+                            p {
+                                class: "text-sm break-all",
+                                "{entry.exchange.request.method} {entry.exchange.request.url}"
+                            }
+                            ResponseViewer {
+                                status: entry.exchange.response.status,
+                                headers: entry.exchange.response.headers.clone(),
+                                body: entry.exchange.response.body.clone(),
+                                duration_ms: entry.exchange.response.duration_ms,
+                            }
+                        }
+                    },
+                    (None, _) => rsx! {
+                        p { class: "text-gray-500", "Select an entry" }
+                    },
+                }
+            }
+        }
+    };
+}
+
+// Formats a unix timestamp relative to now (eg: 5 minutes ago)
+fn format_age(timestamp: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let age = now.saturating_sub(timestamp);
+
+    return match age {
+        0..60 => "just now".to_string(),
+        60..3600 => format!("{} minutes ago", age / 60),
+        3600..86400 => format!("{} hours ago", age / 3600),
+        _ => format!("{} days ago", age / 86400),
+    };
+}
@@ -1,5 +1,20 @@
 mod view;
 mod side;
 mod panel;
+mod request_tree;
+mod call_results;
+mod collection_runner;
+mod command_palette;
+mod curl_import;
+mod environment_panel;
+mod environment_selector;
+mod fixture_selector;
+mod history;
+mod multipart_editor;
+mod response_diff;
+mod response_viewer;
+mod search;
+mod snippets;
+mod variable_input;
 
 pub use view::ProjectView;
\ No newline at end of file
@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+
+use dioxus::{html::HasFileData, prelude::*};
+use nativedoctor_core::{
+    mime::mime_from_path,
+    schema::request_body::{MultipartPartSchema, RequestBodySchema},
+};
+use rfd::AsyncFileDialog;
+
+// Edits the parts of a multipart body. Files dropped on it, or picked, are added as file parts.
+#[component]
+pub fn MultipartEditor(body: Signal<Option<RequestBodySchema>>, base_dir: PathBuf) -> Element {
+    let mut dragging = use_signal(|| false);
+
+    let parts = match body() {
+        Some(RequestBodySchema::Multipart { parts }) => parts,
+        _ => vec![],
+    };
+
+    // removing the last part removes the body
+    let mut set_parts = move |parts: Vec<MultipartPartSchema>| match parts.is_empty() {
+        true => body.set(None),
+        false => body.set(Some(RequestBodySchema::Multipart { parts })),
+    };
+
+    let add_files = {
+        let base_dir = base_dir.clone();
+        move |paths: Vec<PathBuf>| {
+            let mut parts = match body() {
+                Some(RequestBodySchema::Multipart { parts }) => parts,
+                _ => vec![],
+            };
+            parts.extend(paths.iter().map(|path| file_part(path, &base_dir)));
+            set_parts(parts);
+        }
+    };
+
+    return rsx! {
+        div {
+            class: "flex flex-col gap-2",
+            for (index, part) in parts.clone().into_iter().enumerate() {
+                div {
+                    key: "{index}",
+                    class: "flex gap-2 items-center text-sm",
+                    match part {
+                        MultipartPartSchema::Field { name, value } => rsx! {
+                            input {
+                                class: "w-40",
+                                value: "{name}",
+                                placeholder: "Name",
+                                oninput: {
+                                    let (parts, value) = (parts.clone(), value.clone());
+                                    move |e: FormEvent| {
+                                        let mut parts = parts.clone();
+                                        parts[index] = MultipartPartSchema::Field { name: e.value(), value: value.clone() };
+                                        set_parts(parts);
+                                    }
+                                },
+                            }
+                            input {
+                                class: "flex-grow",
+                                value: "{value}",
+                                placeholder: "Value",
+                                oninput: {
+                                    let (parts, name) = (parts.clone(), name.clone());
+                                    move |e: FormEvent| {
+                                        let mut parts = parts.clone();
+                                        parts[index] = MultipartPartSchema::Field { name: name.clone(), value: e.value() };
+                                        set_parts(parts);
+                                    }
+                                },
+                            }
+                        },
+                        MultipartPartSchema::File { name, path, mime_type } => rsx! {
+                            input {
+                                class: "w-40",
+                                value: "{name}",
+                                placeholder: "Name",
+                                oninput: {
+                                    let (parts, path, mime_type) = (parts.clone(), path.clone(), mime_type.clone());
+                                    move |e: FormEvent| {
+                                        let mut parts = parts.clone();
+                                        parts[index] = MultipartPartSchema::File {
+                                            name: e.value(),
+                                            path: path.clone(),
+                                            mime_type: mime_type.clone(),
+                                        };
+                                        set_parts(parts);
+                                    }
+                                },
+                            }
+                            span { class: "flex-grow truncate", title: "{path}", "{path}" }
+                            span { class: "text-gray-500", {mime_type.unwrap_or_default()} }
+                        },
+                    }
+                    button {
+                        class: "text-gray-500 hover:text-black",
+                        onclick: {
+                            let parts = parts.clone();
+                            move |_| {
+                                let mut parts = parts.clone();
+                                parts.remove(index);
+                                set_parts(parts);
+                            }
+                        },
+                        "×"
+                    }
+                }
+            }
+
+            div {
+                class: if dragging() { "p-4 border-2 border-dashed rounded-md text-center bg-gray-100" } else { "p-4 border-2 border-dashed rounded-md text-center text-gray-500" },
+                ondragover: move |e| {
+                    e.prevent_default();
+                    dragging.set(true);
+                },
+                ondragleave: move |_| dragging.set(false),
+                ondrop: {
+                    let mut add_files = add_files.clone();
+                    move |e: DragEvent| {
+                        e.prevent_default();
+                        dragging.set(false);
+                        if let Some(engine) = e.files() {
+                            add_files(engine.files().into_iter().map(PathBuf::from).collect());
+                        }
+                    }
+                },
+                "Drop files here to add them as parts"
+            }
+
+            div {
+                class: "flex gap-2",
+                button {
+                    class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md",
+                    onclick: move |_| {
+                        let mut add_files = add_files.clone();
+                        async move {
+                            if let Some(files) = AsyncFileDialog::new().set_title("Select files").pick_files().await {
+                                add_files(files.iter().map(|file| file.path().to_path_buf()).collect());
+                            }
+                        }
+                    },
+                    "Add files"
+                }
+                button {
+                    class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md",
+                    onclick: {
+                        let parts = parts.clone();
+                        move |_| {
+                            let mut parts = parts.clone();
+                            parts.push(MultipartPartSchema::Field { name: String::new(), value: String::new() });
+                            set_parts(parts);
+                        }
+                    },
+                    "Add field"
+                }
+            }
+        }
+    };
+}
+
+// A file part named after the file, with its path relative to the request file when it's inside its folder
+fn file_part(path: &Path, base_dir: &Path) -> MultipartPartSchema {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or("file".to_string());
+    let relative = path.strip_prefix(base_dir).unwrap_or(path);
+
+    return MultipartPartSchema::File {
+        name,
+        path: relative.to_string_lossy().to_string(),
+        mime_type: mime_from_path(path).map(|mime| mime.to_string()),
+    };
+}
@@ -1,11 +1,367 @@
 use dioxus::prelude::*;
+use nativedoctor_core::{
+    fs::FileObject,
+    interpolation::interpolate_string,
+    schema::{
+        request_body::RequestBodySchema,
+        roots::{ProjectRootSchema, RequestRootSchema},
+    },
+};
 
+use crate::{
+    components::Dialog,
+    states::{
+        use_command, CallManager, Command, EnvironmentManager, FixtureManager, RequestManager,
+        TabItem, TabItemManager, TabType, ToastCloseMethod, ToastConfig, ToastState, ToastTitle,
+    },
+    views::project::{
+        call_results::CallResults, collection_runner::CollectionRunner,
+        environment_panel::EnvironmentPanel, history::HistoryPanel,
+        multipart_editor::MultipartEditor, snippets::CodeSnippets, variable_input::VariableInput,
+    },
+};
+
+#[derive(Clone, PartialEq)]
+enum RequestTab {
+    Request,
+    Response,
+    History,
+}
 
 #[component]
 pub fn RequestPanel() -> Element {
+    let requests = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+    let mut tabs = TabItemManager::inject();
+    let mut closing = use_signal(|| None::<TabItem>);
+    let mut show_close = use_signal(|| false);
+
+    // unsaved changes are only dropped once confirmed
+    let mut close = move |tab: TabItem| {
+        if tab.dirty {
+            closing.set(Some(tab));
+            show_close.set(true);
+        } else {
+            tabs.close(tab.id);
+        }
+    };
+
+    use_command(Command::CloseTab, move || {
+        if let Some(tab) = tabs.active() {
+            close(tab);
+        }
+    });
+
+    if tabs.tabs().is_empty() {
+        return rsx! {
+            div {
+                class: "flex-grow flex items-center justify-center",
+                "Select a request"
+            }
+        };
+    }
+
+    let active = tabs.active().map(|tab| tab.id);
+
     return rsx! {
         div {
-            class: "flex-grow bg-green-400"
+            class: "flex-grow flex flex-col min-w-0",
+            div {
+                class: "flex gap-1 px-2 pt-2 border-b",
+                for tab in tabs.tabs() {
+                    div {
+                        key: "{tab.id}",
+                        class: if Some(tab.id) == active { "flex gap-1 px-2 py-1 rounded-t-md bg-gray-300" } else { "flex gap-1 px-2 py-1 rounded-t-md bg-gray-100" },
+                        button {
+                            onclick: move |_| tabs.set_active(tab.id),
+                            "{tab.name}"
+                            if tab.dirty { " •" }
+                        }
+                        button {
+                            class: "text-gray-500 hover:text-black",
+                            onclick: {
+                                let tab = tab.clone();
+                                move |_| close(tab.clone())
+                            },
+                            "×"
+                        }
+                    }
+                }
+            }
+
+            // every open editor stays mounted so switching tabs keeps unsaved edits
+            for tab in tabs.tabs() {
+                match tab.tab_type {
+                    TabType::Request(id) => match requests().into_iter().find(|request| request.id == id) {
+                        Some(request) => rsx! {
+                            div {
+                                key: "{tab.id}",
+                                class: if Some(tab.id) == active { "flex-grow flex" } else { "hidden" },
+                                RequestEditor { tab: tab.id, request }
+                            }
+                        },
+                        None => rsx! {},
+                    },
+                    TabType::Environment => rsx! {
+                        div {
+                            key: "{tab.id}",
+                            class: if Some(tab.id) == active { "flex-grow flex" } else { "hidden" },
+                            EnvironmentPanel {}
+                        }
+                    },
+                    TabType::Runner => rsx! {
+                        div {
+                            key: "{tab.id}",
+                            class: if Some(tab.id) == active { "flex-grow flex" } else { "hidden" },
+                            CollectionRunner {}
+                        }
+                    },
+                }
+            }
+
+            Dialog {
+                show: show_close,
+                div {
+                    class: "p-4 bg-white flex flex-col gap-2 w-[400px]",
+                    if let Some(tab) = closing() {
+                        p { "{tab.name} has unsaved changes, close it anyway?" }
+                    }
+                    div {
+                        class: "flex gap-2 justify-end",
+                        button {
+                            class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md",
+                            onclick: move |_| show_close.set(false),
+                            "Cancel"
+                        }
+                        button {
+                            class: "px-2 py-1 bg-red-100 hover:bg-red-200 rounded-md",
+                            onclick: move |_| {
+                                if let Some(tab) = closing() {
+                                    tabs.close(tab.id);
+                                }
+                                closing.set(None);
+                                show_close.set(false);
+                            },
+                            "Discard changes"
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
+
+#[component]
+fn RequestEditor(tab: uuid::Uuid, request: FileObject<RequestRootSchema>) -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let requests = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+    let manager = RequestManager::inject();
+    let environment = EnvironmentManager::inject();
+    let fixtures = FixtureManager::inject();
+    let toast = ToastState::inject();
+    let mut tabs = TabItemManager::inject();
+    let mut calls = CallManager::inject();
+    let mut method = use_signal(|| request.object.method.clone());
+    let mut url = use_signal(|| request.object.url.clone());
+    let mut doc = use_signal(|| request.object.doc.clone());
+    let mut body = use_signal(|| request.object.body.clone());
+    let mut section = use_signal(|| RequestTab::Request);
+
+    let dirty = method() != request.object.method
+        || url() != request.object.url
+        || doc() != request.object.doc
+        || body() != request.object.body;
+
+    use_effect(use_reactive!(|(dirty,)| tabs.set_dirty(tab, dirty)));
+
+    let id = request.id;
+    let is_active = move || tabs.active().map(|active| active.id) == Some(tab);
+
+    // drops the edits, back to what the file holds
+    let revert = {
+        let request = request.clone();
+        move |_: MouseEvent| {
+            method.set(request.object.method.clone());
+            url.set(request.object.url.clone());
+            doc.set(request.object.doc.clone());
+            body.set(request.object.body.clone());
+        }
+    };
+
+    // the url with env variables resolved for the active environment
+    let url_preview = match project() {
+        Some(project) => interpolate_string(&url(), &environment.build_env(&project.object)),
+        None => Ok(url()),
+    };
+
+    // the saved request with the edits applied
+    let edited = move || {
+        let mut edited = requests().into_iter().find(|item| item.id == id)?;
+        edited.object.method = method();
+        edited.object.url = url();
+        edited.object.doc = doc();
+        edited.object.body = body();
+        return Some(edited);
+    };
+
+    let save = move || {
+        let edited = match edited() {
+            Some(edited) => edited,
+            None => return,
+        };
+
+        let mut manager = manager.clone();
+        let mut toast = toast.clone();
+
+        spawn(async move {
+            match edited.save().await {
+                Ok(_) => manager.update(edited),
+                Err(e) => toast.push(ToastConfig::new(
+                    ToastTitle::Error("Could not save request".to_string()),
+                    Some(e.to_string()),
+                    ToastCloseMethod::Button,
+                )),
+            };
+        });
+    };
+
+    // sends the request with its unsaved edits, recording it in the history
+    let send = move || {
+        let project = match project() {
+            Some(project) => project,
+            None => return,
+        };
+        let edited = match edited() {
+            Some(edited) => edited,
+            None => return,
+        };
+        let name = edited.get_call_name();
+
+        // the requests it requires are loaded by the runner when it calls them
+        spawn(async move {
+            let mut runner = match manager.runner(project, Some(edited)).await {
+                Ok(runner) => runner,
+                Err(e) => {
+                    toast.clone().push(ToastConfig::new(
+                        ToastTitle::Error("Could not send request".to_string()),
+                        Some(format!("{:#}", e)),
+                        ToastCloseMethod::Button,
+                    ));
+                    return;
+                }
+            };
+            runner.environment = environment.active();
+            runner.fixture_mode = fixtures.mode();
+            runner.enable_history().await;
+            calls.send(id, name, runner);
+            section.set(RequestTab::Response);
+        });
+    };
+
+    use_command(Command::SaveRequest, move || {
+        if is_active() {
+            save();
+        }
+    });
+    use_command(Command::SendRequest, move || {
+        if is_active() {
+            send();
+        }
+    });
+
+    return rsx! {
+        div {
+            class: "flex-grow p-4 flex flex-col gap-4",
+            div {
+                class: "flex gap-2",
+                input {
+                    class: "w-24 font-medium",
+                    value: "{method}",
+                    oninput: move |e| method.set(e.value().to_uppercase()),
+                }
+                VariableInput { value: url, class: "flex-grow" }
+                button {
+                    class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md disabled:opacity-50",
+                    disabled: !dirty,
+                    onclick: move |_| save(),
+                    "Save"
+                }
+                button {
+                    class: "px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md disabled:opacity-50",
+                    disabled: !dirty,
+                    onclick: revert,
+                    "Revert"
+                }
+                if calls.is_running(id) {
+                    button {
+                        class: "px-2 py-1 bg-red-100 hover:bg-red-200 rounded-md",
+                        onclick: move |_| calls.cancel(id),
+                        "Cancel"
+                    }
+                } else {
+                    button {
+                        class: "px-2 py-1 bg-blue-500 hover:bg-blue-600 text-white rounded-md",
+                        onclick: move |_| send(),
+                        "Send"
+                    }
+                }
+            }
+
+            match url_preview {
+                Ok(preview) => rsx! {
+                    p { class: "text-sm text-gray-500 break-all", "{preview}" }
+                },
+                Err(e) => rsx! {
+                    p { class: "text-sm text-red-500", "{e}" }
+                },
+            }
+
+            div {
+                class: "flex gap-2",
+                button {
+                    class: if section() == RequestTab::Request { "px-2 py-1 rounded-md bg-gray-300" } else { "px-2 py-1 rounded-md bg-gray-100" },
+                    onclick: move |_| section.set(RequestTab::Request),
+                    "Request"
+                }
+                button {
+                    class: if section() == RequestTab::Response { "px-2 py-1 rounded-md bg-gray-300" } else { "px-2 py-1 rounded-md bg-gray-100" },
+                    onclick: move |_| section.set(RequestTab::Response),
+                    "Response"
+                }
+                button {
+                    class: if section() == RequestTab::History { "px-2 py-1 rounded-md bg-gray-300" } else { "px-2 py-1 rounded-md bg-gray-100" },
+                    onclick: move |_| section.set(RequestTab::History),
+                    "History"
+                }
+            }
+
+            match section() {
+                RequestTab::Request => rsx! {
+                    textarea {
+                        class: "text-sm",
+                        value: "{doc}",
+                        placeholder: "Documentation",
+                        oninput: move |e| doc.set(e.value()),
+                    }
+
+                    match body() {
+                        None | Some(RequestBodySchema::Multipart { .. }) => rsx! {
+                            MultipartEditor {
+                                body,
+                                base_dir: request.path.parent().map(|dir| dir.to_path_buf()).unwrap_or_default(),
+                            }
+                        },
+                        Some(_) => rsx! {},
+                    }
+
+                    CodeSnippets { name: request.get_call_name() }
+                },
+                RequestTab::Response => rsx! {
+                    CallResults { request: id }
+                },
+                RequestTab::History => rsx! {
+                    HistoryPanel { name: request.get_call_name() }
+                },
+            }
         }
     };
 }
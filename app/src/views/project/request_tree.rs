@@ -0,0 +1,69 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{fs::FileObject, groups::RequestGroup, schema::roots::RequestRootSchema};
+
+use crate::states::{RequestManager, TabItemManager, ToastState};
+
+// The requests of a group, then its folders, which open and close on click
+#[component]
+pub fn RequestTree(group: RequestGroup) -> Element {
+    let loaded = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+    let requests = RequestManager::inject();
+    let tabs = TabItemManager::inject();
+    let toast = ToastState::inject();
+
+    let listed = requests.listed();
+    let items = group
+        .requests
+        .iter()
+        .filter_map(|name| listed.iter().find(|request| request.name == *name).cloned())
+        .collect::<Vec<_>>();
+
+    // the request shown in the active tab, if it's a request tab
+    let active = tabs.active_request().and_then(|id| {
+        loaded()
+            .into_iter()
+            .find(|request| request.id == id)
+            .map(|request| request.get_call_name())
+    });
+
+    return rsx! {
+        div {
+            class: "flex flex-col items-start",
+            for request in items {
+                button {
+                    class: if active.as_ref() == Some(&request.name) { "px-1 rounded-md bg-gray-300" } else { "px-1 rounded-md" },
+                    onclick: {
+                        let name = request.name.clone();
+                        move |_| requests.open_in_tab(name.clone(), tabs, toast)
+                    },
+                    {request.path.file_name().unwrap_or_default().to_string_lossy().to_string()}
+                }
+            }
+            for child in group.groups {
+                RequestFolder { key: "{child.path}", group: child }
+            }
+        }
+    };
+}
+
+#[component]
+fn RequestFolder(group: RequestGroup) -> Element {
+    let mut open = use_signal(|| true);
+
+    return rsx! {
+        div {
+            class: "flex flex-col w-full",
+            button {
+                class: "text-left font-medium",
+                onclick: move |_| open.set(!open()),
+                if open() { "▾ {group.name} ({group.len()})" } else { "▸ {group.name} ({group.len()})" }
+            }
+            if open() {
+                div {
+                    class: "pl-3",
+                    RequestTree { group: group.clone() }
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,64 @@
+use dioxus::prelude::*;
+use nativedoctor_core::diff::{Difference, ResponseDiff};
+
+// Shows what changed between two responses: added values in green, removed in red, changed in yellow
+#[component]
+pub fn ResponseDiffView(diff: ResponseDiff) -> Element {
+    if diff.is_empty() {
+        return rsx! {
+            p { class: "text-gray-500", "The responses are the same" }
+        };
+    }
+
+    return rsx! {
+        div {
+            class: "flex flex-col gap-2 text-sm",
+            if let Some((left, right)) = diff.status {
+                p { class: "font-medium", "Status: {left} → {right}" }
+            }
+
+            if !diff.headers.is_empty() {
+                h2 { class: "font-medium", "Headers" }
+                DifferenceTable { differences: diff.headers.clone() }
+            }
+
+            if !diff.body.is_empty() {
+                h2 { class: "font-medium", "Body" }
+                DifferenceTable { differences: diff.body.clone() }
+            }
+        }
+    };
+}
+
+#[component]
+fn DifferenceTable(differences: Vec<Difference>) -> Element {
+    return rsx! {
+        table {
+            class: "w-full table-fixed font-mono",
+            for difference in differences {
+                tr {
+                    key: "{difference.path}",
+                    class: if difference.is_added() { "bg-green-100" } else if difference.is_removed() { "bg-red-100" } else { "bg-yellow-100" },
+                    td { class: "p-1 w-1/4 break-all", {display_path(&difference.path)} }
+                    td { class: "p-1 break-all", {display_value(&difference.left)} }
+                    td { class: "p-1 break-all", {display_value(&difference.right)} }
+                }
+            }
+        }
+    };
+}
+
+fn display_path(path: &str) -> String {
+    return match path.is_empty() {
+        true => "(body)".to_string(),
+        false => path.to_string(),
+    };
+}
+
+fn display_value(value: &Option<serde_json::Value>) -> String {
+    return match value {
+        Some(serde_json::Value::String(text)) => text.clone(),
+        Some(value) => value.to_string(),
+        None => "—".to_string(),
+    };
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use dioxus::prelude::*;
+
+#[derive(Clone, PartialEq)]
+enum ResponseTab {
+    Body,
+    Headers,
+}
+
+#[component]
+pub fn ResponseViewer(
+    status: u16,
+    headers: HashMap<String, String>,
+    body: String,
+    duration_ms: u64,
+) -> Element {
+    let mut tab = use_signal(|| ResponseTab::Body);
+
+    // pretty print json bodies
+    let body = match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or(body),
+        Err(_) => body,
+    };
+
+    let mut headers = headers.into_iter().collect::<Vec<(String, String)>>();
+    headers.sort();
+
+    let status_class = if status < 400 {
+        "font-medium text-green-600"
+    } else {
+        "font-medium text-red-600"
+    };
+
+    return rsx! {
+        div {
+            class: "flex flex-col gap-2 min-h-0",
+            div {
+                class: "flex gap-4 items-center text-sm",
+                span { class: status_class, "{status}" }
+                span { "{duration_ms}ms" }
+                span { "{body.len()} bytes" }
+            }
+
+            div {
+                class: "flex gap-2",
+                button {
+                    class: if tab() == ResponseTab::Body { "px-2 py-1 rounded-md bg-gray-300" } else { "px-2 py-1 rounded-md bg-gray-100" },
+                    onclick: move |_| tab.set(ResponseTab::Body),
+                    "Body"
+                }
+                button {
+                    class: if tab() == ResponseTab::Headers { "px-2 py-1 rounded-md bg-gray-300" } else { "px-2 py-1 rounded-md bg-gray-100" },
+                    onclick: move |_| tab.set(ResponseTab::Headers),
+                    "Headers ({headers.len()})"
+                }
+            }
+
+            match tab() {
+                ResponseTab::Body => rsx! {
+                    pre {
+                        class: "p-2 bg-gray-100 rounded-md text-sm overflow-auto",
+                        "{body}"
+                    }
+                },
+                ResponseTab::Headers => rsx! {
+                    table {
+                        class: "text-sm",
+                        for (key, value) in headers {
+                            tr {
+                                td { class: "pr-4 font-medium align-top", "{key}" }
+                                td { class: "break-all", "{value}" }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    };
+}
@@ -0,0 +1,107 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    fs::FileObject,
+    history::HistoryStore,
+    schema::roots::{ProjectRootSchema, RequestRootSchema},
+    search::search,
+};
+
+use crate::{
+    components::Dialog,
+    states::{RequestManager, TabItemManager},
+};
+
+const MAX_RESULTS: usize = 20;
+
+#[component]
+pub fn SearchDialog(show: Signal<bool>) -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let requests = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+    let mut manager = RequestManager::inject();
+    let mut tabs = TabItemManager::inject();
+    let mut query = use_signal(|| String::new());
+
+    // the content of every request is searched, the ones not opened yet are loaded with the dialog
+    use_effect(move || {
+        if show() {
+            spawn(async move {
+                manager.load_all().await;
+            });
+        }
+    });
+
+    // recorded responses are searched too, read again each time the dialog opens
+    let history = use_resource(move || async move {
+        let project = match (show(), project()) {
+            (true, Some(project)) => project,
+            _ => return vec![],
+        };
+
+        return HistoryStore::for_project(&project.get_root_dir())
+            .entries()
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Could not read history: {}", e);
+                vec![]
+            });
+    });
+
+    let history = history.read_unchecked().clone().unwrap_or_default();
+    let matches = search(&query(), &requests(), &history, MAX_RESULTS);
+
+    let mut open = move |name: &str| {
+        let request = requests()
+            .into_iter()
+            .find(|request| request.get_call_name() == name);
+
+        if let Some(request) = request {
+            tabs.open_request(request.id, request.get_call_name());
+        }
+        query.set(String::new());
+        show.set(false);
+    };
+
+    return rsx! {
+        Dialog {
+            show,
+            div {
+                class: "p-4 bg-white flex flex-col gap-2 w-[600px]",
+                input {
+                    value: "{query}",
+                    placeholder: "Search requests and responses",
+                    autofocus: true,
+                    autocomplete: "off",
+                    spellcheck: false,
+                    oninput: move |e| query.set(e.value()),
+                    onkeydown: move |e| {
+                        if e.key() == Key::Enter {
+                            if let Some(first) = search(&query(), &requests(), &history, 1).first() {
+                                open(&first.request);
+                            }
+                        }
+                    },
+                }
+
+                div {
+                    class: "flex flex-col max-h-96 overflow-auto",
+                    for item in matches {
+                        button {
+                            key: "{item.request}-{item.field.label()}",
+                            class: "p-2 text-left hover:bg-gray-100 flex flex-col",
+                            onclick: {
+                                let name = item.request.clone();
+                                move |_| open(&name)
+                            },
+                            div {
+                                class: "flex gap-2 text-sm",
+                                span { class: "font-medium", "{item.request}" }
+                                span { class: "text-gray-500", "{item.field.label()}" }
+                            }
+                            span { class: "text-xs text-gray-500 truncate w-full", "{item.text}" }
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
@@ -1,13 +1,54 @@
 use dioxus::prelude::*;
-use nativedoctor_core::{fs::FileObject, schema::roots::{ProjectRootSchema, RequestRootSchema}};
+use nativedoctor_core::{fs::FileObject, groups::RequestGroup, schema::roots::ProjectRootSchema};
+
+use crate::{
+    states::{use_command, Command, RequestManager, TabItemManager, TabType},
+    views::project::{
+        curl_import::CurlImportDialog, environment_selector::EnvironmentSelector,
+        fixture_selector::FixtureSelector, request_tree::RequestTree, search::SearchDialog,
+    },
+};
 
 #[component]
 pub fn SideBar() -> Element {
     let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
-    let requests = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+    let requests = RequestManager::inject();
+    let mut show_curl_import = use_signal(|| false);
+    let mut show_search = use_signal(|| false);
+    let mut tabs = TabItemManager::inject();
+    let mut tag = use_signal(|| None::<String>);
+
+    let mut show_variables = move || tabs.open("Variables".to_string(), TabType::Environment);
+
+    use_command(Command::Search, move || show_search.set(true));
+    use_command(Command::ImportCurl, move || show_curl_import.set(true));
+    let mut show_runner = move || tabs.open("Runner".to_string(), TabType::Runner);
+
+    use_command(Command::ShowVariables, move || show_variables());
+    use_command(Command::RunCollection, move || show_runner());
+
+    // every tag used by the requests, to filter the list with
+    let mut tags = requests
+        .listed()
+        .iter()
+        .flat_map(|request| request.tags.clone())
+        .collect::<Vec<String>>();
+    tags.sort();
+    tags.dedup();
+
+    let visible = requests
+        .listed()
+        .into_iter()
+        .filter(|request| match tag() {
+            Some(tag) => request.tags.contains(&tag),
+            None => true,
+        })
+        .collect::<Vec<_>>();
 
     return match project() {
         Some(project) => rsx! {
+            CurlImportDialog { show: show_curl_import }
+            SearchDialog { show: show_search }
             div {
                 class: "bg-accent w-[300px]",
                 // name and version
@@ -21,17 +62,66 @@ pub fn SideBar() -> Element {
                     }
                 }
 
+                // environment
+                div {
+                    class: "py-1",
+                    EnvironmentSelector {}
+                }
+
+                // fixtures
+                div {
+                    class: "py-1",
+                    FixtureSelector {}
+                }
+
                 // requests
                 div {
                     h1{
                         "requests"
                     }
 
-                    for request in requests() {
-                        button {
-                            {request.get_name()}
+                    button {
+                        onclick: move |_| show_curl_import.set(true),
+                        "Import cURL"
+                    }
+
+                    button {
+                        onclick: move |_| show_search.set(true),
+                        "Search"
+                    }
+
+                    button {
+                        onclick: move |_| show_variables(),
+                        "Variables"
+                    }
+
+                    button {
+                        onclick: move |_| show_runner(),
+                        "Runner"
+                    }
+
+                    if !tags.is_empty() {
+                        div {
+                            class: "flex flex-wrap gap-1 py-1",
+                            button {
+                                class: if tag().is_none() { "px-2 rounded-md bg-gray-300" } else { "px-2 rounded-md bg-gray-100" },
+                                onclick: move |_| tag.set(None),
+                                "all"
+                            }
+                            for item in tags {
+                                button {
+                                    class: if tag() == Some(item.clone()) { "px-2 rounded-md bg-gray-300" } else { "px-2 rounded-md bg-gray-100" },
+                                    onclick: {
+                                        let item = item.clone();
+                                        move |_| tag.set(Some(item.clone()))
+                                    },
+                                    "{item}"
+                                }
+                            }
                         }
                     }
+
+                    RequestTree { group: RequestGroup::from_index(&visible) }
                 }
             },
         },
@@ -0,0 +1,68 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    codegen::CodegenLanguage,
+    fs::FileObject,
+    runner::Runner,
+    schema::roots::{ProjectRootSchema, RequestRootSchema},
+};
+
+use crate::states::EnvironmentManager;
+
+#[component]
+pub fn CodeSnippets(name: String) -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let requests = use_context::<Signal<Vec<FileObject<RequestRootSchema>>>>();
+    let environment = EnvironmentManager::inject();
+    let mut language = use_signal(|| CodegenLanguage::Rust);
+
+    let project = match project() {
+        Some(project) => project,
+        None => return rsx! {},
+    };
+
+    let snippet = Runner::new(project, requests()).and_then(|mut runner| {
+        runner.environment = environment.active();
+        return runner.generate_snippet(&name, language());
+    });
+
+    return rsx! {
+        div {
+            class: "flex flex-col gap-2",
+            div {
+                class: "flex gap-2",
+                for item in CodegenLanguage::all() {
+                    button {
+                        class: if item == language() { "px-2 py-1 rounded-md bg-gray-300" } else { "px-2 py-1 rounded-md bg-gray-100 hover:bg-gray-200" },
+                        onclick: move |_| language.set(item),
+                        "{item.label()}"
+                    }
+                }
+            }
+
+            match snippet {
+                Ok(snippet) => {
+                    let copied = serde_json::to_string(&snippet).unwrap_or_default();
+                    rsx! {
+                        pre {
+                            class: "p-2 bg-gray-100 rounded-md text-sm overflow-auto",
+                            "{snippet}"
+                        }
+                        button {
+                            class: "self-start px-2 py-1 bg-gray-100 hover:bg-gray-200 rounded-md",
+                            onclick: move |_| {
+                                document::eval(&format!("navigator.clipboard.writeText({})", copied));
+                            },
+                            "Copy"
+                        }
+                    }
+                }
+                Err(e) => rsx! {
+                    p {
+                        class: "text-red-500",
+                        "{e}"
+                    }
+                }
+            }
+        }
+    };
+}
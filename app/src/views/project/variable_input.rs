@@ -0,0 +1,116 @@
+use dioxus::prelude::*;
+use nativedoctor_core::{
+    fs::FileObject,
+    interpolation::{
+        open_placeholder, placeholders, referenced_responses, resolve_placeholder, value_to_string,
+    },
+    schema::roots::ProjectRootSchema,
+};
+
+use crate::states::EnvironmentManager;
+
+// A text input that suggests env variables when typing `{{`
+// and shows the value each variable resolves to in the active environment
+#[component]
+pub fn VariableInput(value: Signal<String>, class: Option<String>) -> Element {
+    let project = use_context::<Signal<Option<FileObject<ProjectRootSchema>>>>();
+    let environment = EnvironmentManager::inject();
+    let mut value = value;
+
+    let env = match project() {
+        Some(project) => environment.build_env(&project.object),
+        None => Default::default(),
+    };
+
+    let text = value();
+
+    let mut suggestions = match open_placeholder(&text) {
+        Some(typed) => env
+            .keys()
+            .filter(|name| name.starts_with(typed))
+            .cloned()
+            .collect::<Vec<String>>(),
+        None => vec![],
+    };
+    suggestions.sort();
+
+    let variables = placeholders(&text)
+        .into_iter()
+        .map(|name| {
+            let resolved = resolve_placeholder(&name, &env)
+                .ok()
+                .map(|v| value_to_string(&v));
+            (name, resolved)
+        })
+        .collect::<Vec<(String, Option<String>)>>();
+
+    return rsx! {
+        div {
+            class: "relative flex flex-col gap-1 {class.unwrap_or_default()}",
+            input {
+                class: "w-full",
+                value: "{text}",
+                spellcheck: false,
+                autocomplete: "off",
+                oninput: move |e| value.set(e.value()),
+            }
+
+            if !suggestions.is_empty() {
+                div {
+                    class: "absolute top-full left-0 z-20 mt-1 bg-white border rounded-md shadow-md flex flex-col",
+                    for name in suggestions {
+                        button {
+                            class: "px-2 py-1 text-left text-sm hover:bg-gray-100 flex gap-4 justify-between",
+                            onclick: {
+                                let name = name.clone();
+                                move |_| {
+                                    let current = value();
+                                    if let Some(start) = current.rfind("{{") {
+                                        value.set(format!("{}{{{{{}}}}}", &current[..start], name));
+                                    }
+                                }
+                            },
+                            span { "{name}" }
+                            span {
+                                class: "text-gray-500",
+                                {env.get(&name).map(|v| value_to_string(v)).unwrap_or_default()}
+                            }
+                        }
+                    }
+                }
+            }
+
+            if !variables.is_empty() {
+                div {
+                    class: "flex flex-wrap gap-2 text-xs",
+                    for (name, resolved) in variables {
+                        match resolved {
+                            Some(resolved) => rsx! {
+                                span {
+                                    class: "px-1 rounded bg-gray-100",
+                                    title: "{resolved}",
+                                    "{name} = {resolved}"
+                                }
+                            },
+                            // responses are only known once the run calls the requests
+                            None if !referenced_responses(&name).is_empty() => rsx! {
+                                span {
+                                    class: "px-1 rounded bg-blue-50 text-blue-600",
+                                    title: "Read from the response when the request runs",
+                                    "{name}"
+                                }
+                            },
+                            None => rsx! {
+                                span {
+                                    class: "px-1 rounded bg-red-100 text-red-600",
+                                    title: "Unresolved in this environment",
+                                    "{name}"
+                                }
+                            },
+                        }
+                    }
+                }
+            }
+        }
+    };
+}
@@ -8,7 +8,11 @@ use nativedoctor_core::{
 
 use crate::{
     components::WmDragArea,
-    views::project::{self, panel, side},
+    states::{
+        use_command, CallManager, Command, EnvironmentManager, FixtureManager, KeybindingManager,
+        RequestManager, RunManager, TabItemManager, WorkspaceManager,
+    },
+    views::project::{command_palette::CommandPalette, panel, side},
 };
 
 #[component]
@@ -17,11 +21,28 @@ pub fn ProjectView(path: PathBuf) -> Element {
         use_context_provider(|| Signal::new(None));
     let requests: Signal<Vec<FileObject<RequestRootSchema>>> =
         use_context_provider(|| Signal::new(vec![]));
+    let requests = RequestManager::provide(requests);
+    EnvironmentManager::provide();
+    FixtureManager::provide();
+    TabItemManager::provide();
+    CallManager::provide();
+    RunManager::provide();
+    let mut keybindings = KeybindingManager::provide();
+    let mut workspaces = WorkspaceManager::inject();
+    let mut show_palette = use_signal(|| false);
+
+    use_command(Command::OpenPalette, move || show_palette.set(true));
+    use_command(Command::OpenProject, move || {
+        spawn(async move {
+            workspaces.open_with_picker().await;
+        });
+    });
 
     // load project in scope
     {
         let path = path.clone();
         let mut project = project.clone();
+        let mut requests = requests.clone();
 
         use_effect(move || {
             tracing::info!("Loading project, {:?}", &path);
@@ -31,6 +52,11 @@ pub fn ProjectView(path: PathBuf) -> Element {
                 let p = ProjectRootSchema::load(&path).await;
                 match p {
                     Ok(p) => {
+                        // requests are listed from the index, and parsed when they are opened
+                        if let Err(e) = requests.open(&p).await {
+                            tracing::error!("{e}");
+                        }
+
                         let mut project = project.write();
                         *project = Some(p);
                     }
@@ -41,7 +67,15 @@ pub fn ProjectView(path: PathBuf) -> Element {
     };
 
     return rsx! {
-        div { class: "flex flex-col h-full",
+        div {
+            class: "flex flex-col h-full outline-none",
+            // focusable so shortcuts work when nothing inside is focused
+            tabindex: -1,
+            autofocus: true,
+            onkeydown: move |e| {
+                keybindings.handle(&e);
+            },
+            CommandPalette { show: show_palette }
             WmDragArea { class: " bg-gray-300 h-10 flex items-center", "{path.to_str().unwrap()}" }
 
             match project() {
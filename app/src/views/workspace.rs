@@ -0,0 +1,66 @@
+use dioxus::prelude::*;
+
+use crate::{
+    states::{project_label, WorkspaceManager},
+    views::project::ProjectView,
+};
+
+// The open projects as tabs, with every project view kept mounted so each keeps its tabs
+#[component]
+pub fn WorkspaceView() -> Element {
+    let mut workspaces = WorkspaceManager::inject();
+    let active = workspaces.active();
+
+    return rsx! {
+        div {
+            class: "flex flex-col h-full",
+            div {
+                class: "flex gap-1 px-2 py-1 bg-gray-200 text-sm",
+                for path in workspaces.projects() {
+                    div {
+                        key: "{path.display()}",
+                        class: if Some(&path) == active.as_ref() { "flex gap-1 px-2 rounded-md bg-white" } else { "flex gap-1 px-2 rounded-md" },
+                        title: "{path.display()}",
+                        button {
+                            onclick: {
+                                let path = path.clone();
+                                move |_| workspaces.set_active(path.clone())
+                            },
+                            "{project_label(&path)}"
+                        }
+                        button {
+                            class: "text-gray-500 hover:text-black",
+                            onclick: {
+                                let path = path.clone();
+                                move |_| workspaces.close(&path)
+                            },
+                            "×"
+                        }
+                    }
+                }
+                button {
+                    class: "px-2 rounded-md hover:bg-gray-300",
+                    onclick: move |_| async move {
+                        workspaces.open_with_picker().await;
+                    },
+                    "Open project"
+                }
+            }
+
+            if workspaces.projects().is_empty() {
+                div {
+                    class: "flex-grow flex items-center justify-center",
+                    "Open a project to start"
+                }
+            }
+
+            for path in workspaces.projects() {
+                div {
+                    key: "{path.display()}",
+                    class: if Some(&path) == active.as_ref() { "flex-grow min-h-0" } else { "hidden" },
+                    ProjectView { path: path.clone() }
+                }
+            }
+        }
+    };
+}
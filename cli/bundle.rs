@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use nativedoctor_core::bundle::{BUNDLE_EXTENSION, Bundle};
+
+use crate::run::{load_runner, parse_variable};
+
+#[derive(Args)]
+pub struct BundleArgs {
+    #[command(subcommand)]
+    pub command: BundleCommand,
+}
+
+#[derive(Subcommand)]
+pub enum BundleCommand {
+    /// Calls a request and saves it with its response to a bundle, redacted with the project's rules
+    Save(SaveArgs),
+    /// Prints the request and response saved in a bundle
+    Show(ShowArgs),
+}
+
+#[derive(Args)]
+pub struct SaveArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// The request to call
+    pub request: String,
+
+    /// The environment to resolve variables from
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Sets a variable, overriding the environment
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+
+    /// Where to write the bundle, <request>.ndbundle by default
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ShowArgs {
+    /// The bundle file
+    pub bundle: PathBuf,
+}
+
+pub async fn bundle(args: BundleArgs) -> anyhow::Result<()> {
+    return match args.command {
+        BundleCommand::Save(args) => save(args).await,
+        BundleCommand::Show(args) => show(args).await,
+    };
+}
+
+async fn save(args: SaveArgs) -> anyhow::Result<()> {
+    let mut runner = load_runner(&args.file, args.env.as_deref(), &args.variables).await?;
+
+    // a request whose assertions fail still got a response worth sharing
+    if let Err(e) = runner.call_request(&args.request).await {
+        if !runner.responses.contains_key(&args.request) {
+            return Err(e);
+        }
+        eprintln!("warning: {:#}", e);
+    }
+
+    let bundle = runner.bundle(&args.request)?;
+    let path = args.output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "{}.{}",
+            args.request.replace(['/', '\\'], "_"),
+            BUNDLE_EXTENSION
+        ))
+    });
+
+    bundle.save(&path).await?;
+    println!("Saved {} to {}", args.request, path.display());
+    return Ok(());
+}
+
+async fn show(args: ShowArgs) -> anyhow::Result<()> {
+    let bundle = Bundle::load(&args.bundle).await?;
+    let created = chrono::DateTime::from_timestamp(bundle.created_at as i64, 0)
+        .map(|date| date.with_timezone(&chrono::Local).to_rfc2822())
+        .unwrap_or_default();
+
+    println!("# {}", bundle.name);
+    match &bundle.environment {
+        Some(environment) => println!("environment: {}, saved {}", environment, created),
+        None => println!("saved {}", created),
+    };
+    println!();
+
+    let request = &bundle.request;
+    println!("{} {}", request.method, request.url);
+    for (key, value) in &request.headers {
+        println!("{}: {}", key, value);
+    }
+    if let Some(body) = &request.body {
+        println!();
+        println!("{}", serde_yaml::to_string(body)?.trim_end());
+    }
+    println!();
+
+    let response = &bundle.response;
+    println!("{} ({} ms)", response.status, response.duration_ms);
+    let mut headers = response.headers.iter().collect::<Vec<_>>();
+    headers.sort();
+    for (key, value) in headers {
+        println!("{}: {}", key, value);
+    }
+    if !response.body.is_empty() {
+        println!();
+        println!("{}", response.body);
+    }
+
+    return Ok(());
+}
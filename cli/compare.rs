@@ -0,0 +1,103 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::Args;
+use nativedoctor_core::diff::{DiffOptions, Difference, ResponseDiff};
+
+use crate::run::{check_environment, load_runner, parse_variable};
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// The request, or sequence, to call in both environments
+    pub name: String,
+
+    /// The environment whose responses are on the left
+    #[arg(long)]
+    pub left: String,
+
+    /// The environment whose responses are on the right
+    #[arg(long)]
+    pub right: String,
+
+    /// Leaves a value of the body out, as a dotted path where `*` matches any key or index (eg: data.*.updated_at)
+    #[arg(long = "ignore", value_name = "PATH")]
+    pub ignore_paths: Vec<String>,
+
+    /// Leaves a header out
+    #[arg(long = "ignore-header", value_name = "NAME")]
+    pub ignore_headers: Vec<String>,
+
+    /// Sets a variable in both environments
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+}
+
+// Prints what differs between the responses of each environment, exits with 1 when something does
+pub async fn compare(args: CompareArgs) -> anyhow::Result<ExitCode> {
+    let mut runner = load_runner(&args.file, None, &args.variables).await?;
+    check_environment(&runner, &args.left)?;
+    check_environment(&runner, &args.right)?;
+
+    let options = DiffOptions {
+        ignore_paths: args.ignore_paths.clone(),
+        ignore_headers: args.ignore_headers.clone(),
+    };
+
+    // a sequence compares the response of each of its steps
+    let diffs = match runner.get_sequence(&args.name) {
+        Ok(_) => {
+            runner
+                .compare_sequence(&args.name, &args.left, &args.right, &options)
+                .await?
+        }
+        Err(_) => vec![
+            runner
+                .compare_request(&args.name, &args.left, &args.right, &options)
+                .await?,
+        ],
+    };
+
+    for diff in &diffs {
+        print_diff(diff, &args.left, &args.right);
+    }
+
+    return match diffs.iter().all(ResponseDiff::is_empty) {
+        true => Ok(ExitCode::SUCCESS),
+        false => Ok(ExitCode::FAILURE),
+    };
+}
+
+fn print_diff(diff: &ResponseDiff, left: &str, right: &str) {
+    if diff.is_empty() {
+        println!("{}: the same in {} and {}", diff.name, left, right);
+        return;
+    }
+
+    println!("{}: {} → {}", diff.name, left, right);
+    if let Some((left, right)) = diff.status {
+        println!("  status: {} → {}", left, right);
+    }
+    for difference in &diff.headers {
+        println!("  header {}", describe(difference));
+    }
+    for difference in &diff.body {
+        println!("  {}", describe(difference));
+    }
+}
+
+// A difference as `+ path: right`, `- path: left` or `~ path: left → right`
+fn describe(difference: &Difference) -> String {
+    let path = match difference.path.is_empty() {
+        true => "(body)",
+        false => difference.path.as_str(),
+    };
+
+    return match (&difference.left, &difference.right) {
+        (None, Some(right)) => format!("+ {}: {}", path, right),
+        (Some(left), None) => format!("- {}: {}", path, left),
+        (Some(left), Some(right)) => format!("~ {}: {} → {}", path, left, right),
+        (None, None) => format!("  {}", path),
+    };
+}
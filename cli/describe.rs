@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use nativedoctor_core::schema::{auth::AuthSchema, request_body::RequestBodySchema};
+
+use crate::run::{load_runner, parse_variable};
+
+#[derive(Args)]
+pub struct DescribeArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// The request to describe
+    pub request: String,
+
+    /// The environment to resolve variables from
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Sets a variable, overriding the environment
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+}
+
+// Prints the request as it would be sent, without sending it
+pub async fn describe(args: DescribeArgs) -> anyhow::Result<()> {
+    let mut runner = load_runner(&args.file, args.env.as_deref(), &args.variables).await?;
+    runner.load_request(&args.request).await?;
+    let resolved = runner.resolve_request(&args.request)?;
+
+    println!("{} {}", resolved.method, resolved.url);
+    for (key, value) in &resolved.headers {
+        println!("{}: {}", key, value);
+    }
+
+    if resolved.get_header("content-type").is_none()
+        && let Some(content_type) = resolved.content_type()
+    {
+        println!("Content-Type: {}", content_type);
+    }
+
+    // header based auth is already in the headers
+    match &resolved.auth {
+        Some(AuthSchema::AwsSigv4 { service, .. }) => {
+            println!("(signed with aws sigv4 for {} when sent)", service)
+        }
+        Some(AuthSchema::Hmac { header, .. }) => {
+            println!("(signed with an hmac in {} when sent)", header)
+        }
+        Some(AuthSchema::Digest { .. }) => println!("(answers a digest challenge when sent)"),
+        Some(AuthSchema::Ntlm { .. }) => println!("(authenticates with ntlm when sent)"),
+        _ => {}
+    };
+
+    match &resolved.body {
+        Some(RequestBodySchema::Multipart { parts }) => {
+            println!();
+            println!("{}", serde_yaml::to_string(parts)?.trim_end());
+        }
+        Some(RequestBodySchema::Binary { path, .. }) => {
+            println!();
+            println!("<contents of {}>", resolved.base_dir.join(path).display());
+        }
+        Some(
+            RequestBodySchema::Msgpack { content, .. } | RequestBodySchema::Cbor { content, .. },
+        ) => {
+            println!();
+            println!("{}", serde_yaml::to_string(content)?.trim_end());
+            println!(
+                "(encoded as {} when sent)",
+                resolved.content_type().unwrap_or_default()
+            );
+        }
+        _ => {
+            if let Some(body) = resolved.body_text() {
+                println!();
+                println!("{}", body);
+            }
+        }
+    };
+
+    return Ok(());
+}
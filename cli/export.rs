@@ -0,0 +1,75 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Args, ValueEnum};
+use nativedoctor_core::export::ExportFormat;
+
+use crate::run::{load_runner, parse_variable};
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum ExportTarget {
+    /// An Insomnia v4 export of the whole project
+    Insomnia,
+    /// An OpenAPI 3.1 document describing the project's requests
+    Openapi,
+    /// A curl command for a request
+    Curl,
+    /// An httpie command for a request
+    Httpie,
+}
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// The format to export to
+    #[arg(long, value_enum)]
+    pub to: ExportTarget,
+
+    /// The request to export, for command line formats
+    #[arg(long, required_if_eq_any = [("to", "curl"), ("to", "httpie")])]
+    pub request: Option<String>,
+
+    /// The environment to resolve variables from, for command line formats
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Sets a variable, overriding the environment
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+
+    /// Writes the export to this file instead of printing it
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+}
+
+pub async fn export(args: ExportArgs) -> anyhow::Result<()> {
+    let mut runner = load_runner(&args.file, args.env.as_deref(), &args.variables).await?;
+    let request = args.request.as_deref().unwrap_or_default();
+
+    let content = match args.to {
+        ExportTarget::Insomnia => {
+            runner.load_requests().await?;
+            runner.export_insomnia()?
+        }
+        ExportTarget::Openapi => runner.export_openapi().await?,
+        ExportTarget::Curl => {
+            runner.load_request(request).await?;
+            runner.export_request(request, ExportFormat::Curl)?
+        }
+        ExportTarget::Httpie => {
+            runner.load_request(request).await?;
+            runner.export_request(request, ExportFormat::Httpie)?
+        }
+    };
+
+    match &args.output {
+        Some(path) => tokio::fs::write(path, content)
+            .await
+            .with_context(|| format!("Failed to write {:?}", path))?,
+        None => println!("{}", content),
+    };
+
+    return Ok(());
+}
@@ -0,0 +1,53 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use clap::{Args, ValueEnum};
+use nativedoctor_core::import::{
+    har::parse_har, http_file::parse_http_file, insomnia::parse_insomnia,
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ImportFormat {
+    /// An Insomnia v4 export (json or yaml)
+    Insomnia,
+    /// A HAR log exported from browser dev tools
+    Har,
+    /// A JetBrains or VS Code .http (.rest) file
+    Http,
+}
+
+#[derive(Args)]
+pub struct ImportArgs {
+    /// The file to import
+    pub file: PathBuf,
+
+    /// The format of the file
+    #[arg(long, value_enum)]
+    pub from: ImportFormat,
+
+    /// The folder to create the project in
+    #[arg(long, default_value = ".")]
+    pub out: PathBuf,
+}
+
+// Converts a file from another tool into a project, with a request file for each of its requests
+pub async fn import(args: ImportArgs) -> anyhow::Result<()> {
+    let content = tokio::fs::read_to_string(&args.file)
+        .await
+        .with_context(|| format!("Failed to read {:?}", &args.file))?;
+
+    let imported = match args.from {
+        ImportFormat::Insomnia => parse_insomnia(&content)?,
+        ImportFormat::Har => parse_har(&content)?,
+        ImportFormat::Http => parse_http_file(&content)?,
+    };
+
+    let project = imported.write(&args.out).await?;
+    println!(
+        "Imported {} requests into {}",
+        imported.requests.len(),
+        project.path.display()
+    );
+
+    return Ok(());
+}
@@ -0,0 +1,76 @@
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use nativedoctor_core::{
+    groups::RequestGroup, index::RequestIndex, schema::roots::ProjectRootSchema,
+};
+
+#[derive(Args)]
+pub struct ListArgs {
+    /// The project file
+    pub file: PathBuf,
+}
+
+// Lists the requests from the index, their files are only parsed up to the fields shown
+pub async fn list(args: ListArgs) -> anyhow::Result<()> {
+    let project = ProjectRootSchema::load(&std::path::absolute(&args.file)?).await?;
+    let index = project.index_requests().await?;
+    let root = project.get_root_dir();
+
+    println!("Requests:");
+    print_group(&index, &RequestGroup::from_index(&index.requests), 1, &root);
+
+    println!("Sequences:");
+    let calls = &project.object.calls;
+    let mut sequences = calls.overrides.keys().cloned().collect::<Vec<String>>();
+    sequences.sort();
+    println!(
+        "  main: {}",
+        calls.main.definition().step_names().join(" -> ")
+    );
+    for sequence in sequences {
+        println!(
+            "  {}: {}",
+            sequence,
+            calls.overrides[&sequence]
+                .definition()
+                .step_names()
+                .join(" -> ")
+        );
+    }
+
+    let source = project.path.strip_prefix(&root).unwrap_or(&project.path);
+    println!("Environments ({}):", source.display());
+    println!("  default");
+    for environment in project.object.environments() {
+        println!("  {}", environment);
+    }
+
+    return Ok(());
+}
+
+// Prints the requests of a group, then its folders with their requests indented
+fn print_group(index: &RequestIndex, group: &RequestGroup, depth: usize, root: &Path) {
+    let indent = "  ".repeat(depth);
+
+    for name in &group.requests {
+        let request = match index.find(name) {
+            Some(request) => request,
+            None => continue,
+        };
+        let source = request.path.strip_prefix(root).unwrap_or(&request.path);
+        println!(
+            "{}{} {} {} ({})",
+            indent,
+            name,
+            request.method,
+            request.url,
+            source.display()
+        );
+    }
+
+    for child in &group.groups {
+        println!("{}{}/", indent, child.name);
+        print_group(index, child, depth + 1, root);
+    }
+}
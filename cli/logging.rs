@@ -0,0 +1,128 @@
+use std::{fs::File, io::Write, path::Path, sync::Mutex};
+
+use anyhow::Context;
+use clap::ValueEnum;
+use tracing_subscriber::{
+    Layer,
+    filter::{LevelFilter, Targets},
+    fmt::MakeWriter,
+    layer::SubscriberExt,
+    util::SubscriberInitExt,
+};
+
+// the log file of the run in progress, logs are only written to the console without one
+static RUN_LOG: Mutex<Option<File>> = Mutex::new(None);
+
+#[derive(Clone, Copy, Default, PartialEq, ValueEnum)]
+pub enum LogFormat {
+    /// One line of text per event
+    #[default]
+    Text,
+    /// One json object per event, with the fields of the event
+    Json,
+}
+
+// Logs warnings and errors to stderr, -v adds the requests and responses and -vv their headers
+// along with the http client's own logs. Runs with a log file get every event from -v up.
+pub fn init(verbosity: u8, format: LogFormat) {
+    let console = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(std::io::stderr)
+            .boxed(),
+    };
+
+    let file = match format {
+        LogFormat::Text => tracing_subscriber::fmt::layer()
+            .with_ansi(false)
+            .with_writer(RunLogWriter)
+            .boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_writer(RunLogWriter)
+            .boxed(),
+    };
+
+    tracing_subscriber::registry()
+        .with(console.with_filter(targets(verbosity)))
+        .with(file.with_filter(targets(verbosity.max(1))))
+        .init();
+}
+
+fn targets(verbosity: u8) -> Targets {
+    return match verbosity {
+        0 => Targets::new().with_default(LevelFilter::WARN),
+        1 => Targets::new().with_default(LevelFilter::INFO),
+        _ => Targets::new()
+            .with_default(LevelFilter::DEBUG)
+            .with_target("reqwest", LevelFilter::TRACE)
+            .with_target("hyper_util", LevelFilter::TRACE),
+    };
+}
+
+/// Writes the logs to a file until it's dropped, one for each run.
+pub struct RunLog;
+
+impl RunLog {
+    pub fn start(path: &Path) -> anyhow::Result<RunLog> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {:?}", parent))?;
+        }
+
+        let file = File::create(path).with_context(|| format!("Failed to create {:?}", path))?;
+        if let Ok(mut log) = RUN_LOG.lock() {
+            *log = Some(file);
+        }
+
+        return Ok(RunLog);
+    }
+}
+
+impl Drop for RunLog {
+    fn drop(&mut self) {
+        if let Ok(mut log) = RUN_LOG.lock() {
+            if let Some(file) = log.as_mut() {
+                let _ = file.flush();
+            }
+            *log = None;
+        }
+    }
+}
+
+// Writes to the log file of the run in progress, events are dropped between runs
+#[derive(Clone, Copy)]
+struct RunLogWriter;
+
+impl Write for RunLogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        return match RUN_LOG.lock() {
+            Ok(mut log) => match log.as_mut() {
+                Some(file) => file.write(buf),
+                None => Ok(buf.len()),
+            },
+            Err(_) => Ok(buf.len()),
+        };
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        return match RUN_LOG.lock() {
+            Ok(mut log) => match log.as_mut() {
+                Some(file) => file.flush(),
+                None => Ok(()),
+            },
+            Err(_) => Ok(()),
+        };
+    }
+}
+
+impl<'a> MakeWriter<'a> for RunLogWriter {
+    type Writer = RunLogWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        return *self;
+    }
+}
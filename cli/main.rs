@@ -0,0 +1,80 @@
+use std::process::ExitCode;
+
+use clap::{ArgAction, Parser, Subcommand};
+use logging::LogFormat;
+
+mod bundle;
+mod compare;
+mod describe;
+mod export;
+mod import;
+mod list;
+mod logging;
+mod run;
+mod schedule;
+mod tui;
+mod watch;
+
+/// Runs native doctor projects from the command line.
+#[derive(Parser)]
+#[command(name = "dotapi", version, about)]
+struct Cli {
+    /// Logs more details: -v the requests sent and their responses, -vv their headers and the http client's logs
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Format of the logs written to stderr and to the log files of runs
+    #[arg(long, value_enum, default_value_t, global = true)]
+    log_format: LogFormat,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Calls a request or a sequence and reports each call
+    Run(Box<run::RunArgs>),
+    /// Lists the requests, sequences and environments of a project
+    List(list::ListArgs),
+    /// Prints a request resolved with an environment, without sending it
+    Describe(describe::DescribeArgs),
+    /// Runs sequences periodically, to monitor an api
+    Schedule(schedule::ScheduleArgs),
+    /// Opens a terminal ui to pick and call requests and sequences
+    Tui(tui::TuiArgs),
+    /// Creates a project from a file exported by another tool
+    Import(import::ImportArgs),
+    /// Exports a project, or one of its requests, to another tool's format
+    Export(export::ExportArgs),
+    /// Saves a call to a bundle that can be shared, or prints a saved one
+    Bundle(bundle::BundleArgs),
+    /// Calls a request or a sequence in two environments and prints what differs between the responses
+    Compare(compare::CompareArgs),
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    logging::init(cli.verbose, cli.log_format);
+
+    let result = match cli.command {
+        Command::Run(args) => run::run(*args).await,
+        Command::List(args) => list::list(args).await.map(|_| ExitCode::SUCCESS),
+        Command::Describe(args) => describe::describe(args).await.map(|_| ExitCode::SUCCESS),
+        Command::Schedule(args) => schedule::schedule(args).await,
+        Command::Tui(args) => tui::tui(args).await.map(|_| ExitCode::SUCCESS),
+        Command::Import(args) => import::import(args).await.map(|_| ExitCode::SUCCESS),
+        Command::Export(args) => export::export(args).await.map(|_| ExitCode::SUCCESS),
+        Command::Bundle(args) => bundle::bundle(args).await.map(|_| ExitCode::SUCCESS),
+        Command::Compare(args) => compare::compare(args).await,
+    };
+
+    return match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::from(2)
+        }
+    };
+}
@@ -0,0 +1,454 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use anyhow::Context;
+use clap::{Args, ValueEnum};
+use nativedoctor_core::{
+    fixtures::FixtureMode,
+    interpolation::value_to_string,
+    notify::send_notifications,
+    report::{ConsoleReporter, PrometheusReporter, create_reporter},
+    runner::Runner,
+    schema::{chaos::ChaosSchema, reporter::ReporterSchema, telemetry::TelemetrySchema},
+    snapshot::SnapshotMode,
+    tags::TagFilter,
+};
+
+use crate::{logging::RunLog, schedule::notifications, watch::watch};
+
+#[derive(Args)]
+pub struct RunArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// Calls a single request, along with the requests it requires
+    #[arg(long, conflicts_with = "sequence")]
+    pub request: Option<String>,
+
+    /// Calls a sequence from the project's calls, main when neither is given
+    #[arg(long)]
+    pub sequence: Option<String>,
+
+    /// The environment to resolve variables from
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Sets a variable for the run, overriding the environment
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+
+    /// Only calls the steps with one of these tags (their own or their request's)
+    #[arg(long, value_delimiter = ',', conflicts_with = "request")]
+    pub tags: Vec<String>,
+
+    /// Skips the steps with one of these tags
+    #[arg(long, value_delimiter = ',', conflicts_with = "request")]
+    pub exclude_tags: Vec<String>,
+
+    /// Prints the value of each variable and where it comes from before running, secrets are hidden
+    #[arg(long)]
+    pub debug_env: bool,
+
+    /// Checks every response against an OpenAPI spec, the project's openapi file when no path is given
+    #[arg(long, value_name = "SPEC", num_args = 0..=1)]
+    pub contract: Option<Option<PathBuf>>,
+
+    /// Injects the faults of the project's chaos settings into the requests sent
+    #[arg(long)]
+    pub chaos: bool,
+
+    /// Probability (0 to 1) of delaying a request before sending it, enables chaos
+    #[arg(long, value_name = "PROBABILITY")]
+    pub chaos_delay: Option<f64>,
+
+    /// Probability (0 to 1) of dropping the connection once a request is sent, enables chaos
+    #[arg(long, value_name = "PROBABILITY")]
+    pub chaos_drop: Option<f64>,
+
+    /// Probability (0 to 1) of sending a request twice, enables chaos
+    #[arg(long, value_name = "PROBABILITY")]
+    pub chaos_duplicate: Option<f64>,
+
+    /// Seeds the faults, a run with the same seed injects the same faults, enables chaos
+    #[arg(long, value_name = "SEED")]
+    pub chaos_seed: Option<u64>,
+
+    /// Stores the responses of requests with a snapshot as their new snapshot instead of comparing them
+    #[arg(long)]
+    pub update_snapshots: bool,
+
+    /// Records every response to the project's fixtures folder, replays them without sending
+    /// requests, or checks the live responses against them
+    #[arg(long, value_name = "MODE")]
+    pub fixtures: Option<FixturesArg>,
+
+    /// Prints the requests that would be sent, with secrets redacted, without sending them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Runs again whenever the project, its requests or the files they read change
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Writes the logs of the run to this file, instead of a new file in the project's log_dir
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Writes the messages of every call, as sent and received, to this folder (<n>-<name>.request and .response)
+    #[arg(long, value_name = "DIR")]
+    pub capture_wire: Option<PathBuf>,
+
+    /// Writes the requests and responses of the run to a HAR file
+    #[arg(long, value_name = "PATH")]
+    pub har: Option<PathBuf>,
+
+    /// Writes the request counts, errors and latency histograms of the run to a file, in the Prometheus text format
+    #[arg(long, value_name = "PATH")]
+    pub metrics: Option<PathBuf>,
+
+    /// Reports the run with console, json:PATH, junit:PATH, html:PATH, prometheus:PATH or command:COMMAND,
+    /// along with the reporters of the project file. Can be repeated
+    #[arg(long = "reporter", value_name = "KIND[:PATH]", value_parser = parse_reporter)]
+    pub reporters: Vec<ReporterSchema>,
+
+    /// Leaves out the reporters of the project file, only the console and the ones given with --reporter report the run
+    #[arg(long)]
+    pub no_project_reporters: bool,
+
+    /// Exports a span for the run and each request to this OpenTelemetry collector (otlp/http), eg: http://localhost:4318
+    #[arg(long, value_name = "URL")]
+    pub otlp_endpoint: Option<String>,
+
+    /// Posts the summary of the run to this url once it's over, whether it passed or not
+    #[arg(long, value_name = "URL")]
+    pub notify_webhook: Vec<String>,
+
+    /// Posts the summary of the run to this slack incoming webhook once it's over
+    #[arg(long, value_name = "URL")]
+    pub notify_slack: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+pub enum FixturesArg {
+    /// Saves every response as the fixture of its request
+    Record,
+    /// Answers every request with its fixture, nothing is sent
+    Replay,
+    /// Sends the requests and fails the ones whose response differs from their fixture
+    Verify,
+}
+
+impl From<FixturesArg> for FixtureMode {
+    fn from(mode: FixturesArg) -> Self {
+        return match mode {
+            FixturesArg::Record => FixtureMode::Record,
+            FixturesArg::Replay => FixtureMode::Replay,
+            FixturesArg::Verify => FixtureMode::Verify,
+        };
+    }
+}
+
+// Exits with 1 when a call fails and 130 when cancelled, errors that stop the run exit with 2 from main
+pub async fn run(args: RunArgs) -> anyhow::Result<ExitCode> {
+    if args.watch {
+        return watch(&args).await;
+    }
+
+    return run_once(&args).await;
+}
+
+pub async fn run_once(args: &RunArgs) -> anyhow::Result<ExitCode> {
+    let mut runner = load_runner(&args.file, args.env.as_deref(), &args.variables).await?;
+    runner.tags = TagFilter::new(args.tags.clone(), args.exclude_tags.clone());
+    if args.update_snapshots {
+        runner.snapshot_mode = SnapshotMode::Update;
+    }
+    if let Some(mode) = args.fixtures {
+        runner.fixture_mode = mode.into();
+    }
+    if let Some(spec) = &args.contract {
+        runner.enable_contract(spec.as_deref()).await?;
+    }
+    if let Some(chaos) = chaos_settings(&runner, args) {
+        runner.enable_chaos(chaos)?;
+    }
+    if let Some(dir) = &args.capture_wire {
+        runner.enable_wire_capture(Some(dir.clone()));
+    }
+
+    if args.debug_env {
+        print_env(&runner);
+    }
+
+    // logs the run until it's over
+    let _log = match log_path(&runner, args) {
+        Some(path) => Some(RunLog::start(&path)?),
+        None => None,
+    };
+
+    if args.dry_run {
+        return dry_run(&mut runner, args).await;
+    }
+
+    // the console always reports the run, once even when the project or --reporter asks for it
+    runner.add_reporter(Box::new(ConsoleReporter::new()));
+    let mut reporters = match args.no_project_reporters {
+        true => vec![],
+        false => runner.project.object.reporters.clone(),
+    };
+    let root = runner.project.get_root_dir();
+    reporters.retain(|schema| *schema != ReporterSchema::Console);
+    for schema in reporters {
+        runner.add_reporter(create_reporter(&schema, &root));
+    }
+    // paths given on the command line are relative to the working directory
+    for schema in args
+        .reporters
+        .iter()
+        .filter(|schema| **schema != ReporterSchema::Console)
+    {
+        runner.add_reporter(create_reporter(schema, Path::new("")));
+    }
+    if let Some(path) = &args.metrics {
+        runner.add_reporter(Box::new(PrometheusReporter::new(path.clone())));
+    }
+
+    // the endpoint given on the command line overrides the project's
+    match &args.otlp_endpoint {
+        Some(endpoint) => runner.enable_telemetry(TelemetrySchema {
+            endpoint: Some(endpoint.clone()),
+            ..runner.project.object.telemetry.clone().unwrap_or_default()
+        }),
+        None => runner.use_project_telemetry(),
+    };
+
+    // the first Ctrl+C cancels the call in flight and reports what ran, a second one exits
+    let cancel = runner.cancel.clone();
+    let interrupt = tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            eprintln!("Cancelling, press Ctrl+C again to exit");
+            cancel.cancel();
+        }
+        if tokio::signal::ctrl_c().await.is_ok() {
+            std::process::exit(130);
+        }
+    });
+
+    let outcome = match (&args.request, &args.sequence) {
+        (Some(request), _) => runner.run_request(request).await,
+        (None, Some(sequence)) => runner.run_sequence(sequence).await,
+        (None, None) => runner.run_sequence("main").await,
+    };
+    interrupt.abort();
+    let report = outcome?;
+
+    if let Some(path) = &args.har {
+        tokio::fs::write(path, runner.export_har(&report)?)
+            .await
+            .with_context(|| format!("Failed to write {:?}", path))?;
+    }
+
+    // the project's notifications, then the ones given on the command line
+    let mut notify = runner.project.object.notify.clone();
+    notify.extend(notifications(
+        &args.notify_webhook,
+        &args.notify_slack,
+        true,
+    ));
+    send_notifications(&notify, &report).await;
+
+    if let Some(contract) = &runner.contract {
+        print_contract_violations(&contract.violations);
+    }
+    if let Some(chaos) = &runner.chaos {
+        println!("Injected faults: {}", chaos.summary());
+    }
+
+    if report.cancelled {
+        return Ok(ExitCode::from(130));
+    }
+
+    if report.passed() {
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    return Ok(ExitCode::from(1));
+}
+
+// Prints each request the run would send, failing when one can't be built
+async fn dry_run(runner: &mut Runner, args: &RunArgs) -> anyhow::Result<ExitCode> {
+    let previews = match &args.request {
+        Some(request) => vec![(request.clone(), runner.preview_request(request).await)],
+        None => {
+            runner
+                .preview_sequence(args.sequence.as_deref().unwrap_or("main"))
+                .await?
+        }
+    };
+
+    let mut failed = false;
+    for (name, preview) in previews {
+        println!("# {}", name);
+        match preview {
+            Ok(preview) => println!("{}", preview),
+            Err(e) => {
+                failed = true;
+                println!("error: {:#}", e);
+            }
+        };
+        println!();
+    }
+
+    if failed {
+        return Ok(ExitCode::from(1));
+    }
+
+    return Ok(ExitCode::SUCCESS);
+}
+
+// The log file given on the command line, or a new one in the project's log_dir named after the run
+fn log_path(runner: &Runner, args: &RunArgs) -> Option<PathBuf> {
+    if let Some(path) = &args.log_file {
+        return Some(path.clone());
+    }
+
+    let dir = runner.project.object.log_dir.as_ref()?;
+    let name = match (&args.request, &args.sequence) {
+        (Some(request), _) => request.as_str(),
+        (None, Some(sequence)) => sequence.as_str(),
+        (None, None) => "main",
+    };
+
+    return Some(runner.project.get_root_dir().join(dir).join(format!(
+        "{}-{}.log",
+        chrono::Local::now().format("%Y%m%d-%H%M%S"),
+        name.replace(['/', '\\'], "_")
+    )));
+}
+
+// The chaos settings of the run, the probabilities given on the command line override the project's
+fn chaos_settings(runner: &Runner, args: &RunArgs) -> Option<Option<ChaosSchema>> {
+    let overridden = args.chaos_delay.is_some()
+        || args.chaos_drop.is_some()
+        || args.chaos_duplicate.is_some()
+        || args.chaos_seed.is_some();
+    if !args.chaos && !overridden {
+        return None;
+    }
+    if !overridden {
+        return Some(None);
+    }
+
+    let mut chaos = runner.project.object.chaos.clone().unwrap_or_default();
+    chaos.delay = args.chaos_delay.unwrap_or(chaos.delay);
+    chaos.drop = args.chaos_drop.unwrap_or(chaos.drop);
+    chaos.duplicate = args.chaos_duplicate.unwrap_or(chaos.duplicate);
+    chaos.seed = args.chaos_seed.or(chaos.seed);
+    return Some(Some(chaos));
+}
+
+// Prints the contract violations of the run grouped by endpoint
+fn print_contract_violations(violations: &BTreeMap<String, Vec<String>>) {
+    if violations.is_empty() {
+        println!("Contract: every response follows the spec");
+        return;
+    }
+
+    println!("Contract violations:");
+    for (endpoint, messages) in violations {
+        println!("  {} ({})", endpoint, messages.len());
+        for message in messages {
+            println!("    {}", message);
+        }
+    }
+}
+
+// Prints each variable of the run with its value, references resolved, and the layer it comes from
+fn print_env(runner: &Runner) {
+    let env = runner.build_env();
+    let mut origins = runner.env_origins().into_iter().collect::<Vec<_>>();
+    origins.sort_by(|a, b| a.0.cmp(&b.0));
+
+    for (name, (_, source)) in origins {
+        let value = match runner.project.object.is_secret(&name) {
+            true => "****".to_string(),
+            false => env.get(&name).map(value_to_string).unwrap_or_default(),
+        };
+        eprintln!("{} = {} ({})", name, value, source);
+    }
+    eprintln!();
+}
+
+// Loads the project with the environment selected and the variables set
+pub async fn load_runner(
+    file: &Path,
+    environment: Option<&str>,
+    variables: &[(String, String)],
+) -> anyhow::Result<Runner> {
+    let path = std::path::absolute(file)?;
+    let mut runner = Runner::load(&path).await?;
+
+    if let Some(environment) = environment {
+        check_environment(&runner, environment)?;
+        runner.environment = Some(environment.to_string());
+    }
+
+    // values are read as yaml so numbers and booleans keep their type
+    for (key, value) in variables {
+        let value = serde_yaml::from_str::<serde_yaml::Value>(value)
+            .unwrap_or_else(|_| serde_yaml::Value::String(value.clone()));
+        runner.variables.insert(key.clone(), value);
+    }
+
+    return Ok(runner);
+}
+
+// Fails when the project has no such environment
+pub fn check_environment(runner: &Runner, environment: &str) -> anyhow::Result<()> {
+    let environments = runner.project.object.environments();
+    if !environments.iter().any(|e| e == environment) {
+        anyhow::bail!(
+            "Environment \"{}\" does not exist, available: {}",
+            environment,
+            environments.join(", ")
+        );
+    }
+    return Ok(());
+}
+
+// Parses a reporter given as <kind>[:<path>], the path of a command reporter is its command
+fn parse_reporter(value: &str) -> Result<ReporterSchema, String> {
+    let (kind, target) = match value.split_once(':') {
+        Some((kind, target)) if !target.is_empty() => (kind, Some(target.to_string())),
+        Some((kind, _)) => (kind, None),
+        None => (value, None),
+    };
+
+    return match (kind, target) {
+        ("console", None) => Ok(ReporterSchema::Console),
+        ("console", Some(_)) => Err("the console reporter takes no path".to_string()),
+        ("json", Some(path)) => Ok(ReporterSchema::Json { path }),
+        ("junit", Some(path)) => Ok(ReporterSchema::Junit { path }),
+        ("html", Some(path)) => Ok(ReporterSchema::Html { path }),
+        ("prometheus", Some(path)) => Ok(ReporterSchema::Prometheus { path }),
+        ("command", Some(command)) => Ok(ReporterSchema::Command { command }),
+        ("json" | "junit" | "html" | "prometheus", None) => Err(format!("expected {}:PATH", kind)),
+        ("command", None) => Err("expected command:COMMAND".to_string()),
+        _ => Err(format!(
+            "unknown reporter \"{}\", expected console, json, junit, html, prometheus or command",
+            kind
+        )),
+    };
+}
+
+pub fn parse_variable(value: &str) -> Result<(String, String), String> {
+    return match value.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VALUE, got \"{}\"", value)),
+    };
+}
@@ -0,0 +1,164 @@
+use std::{path::PathBuf, process::ExitCode};
+
+use clap::Args;
+use nativedoctor_core::{
+    runner::Runner,
+    schedule::{Schedule, run_scheduled},
+    schema::{
+        notification::{NotificationChannelSchema, NotificationSchema},
+        schedule::ScheduleSchema,
+    },
+};
+
+#[derive(Args)]
+pub struct ScheduleArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// Only runs the schedule with this name from the project's schedules
+    #[arg(long, conflicts_with_all = ["cron", "every"])]
+    pub name: Option<String>,
+
+    /// Runs a sequence on this cron expression instead of the project's schedules, eg: "*/5 * * * *"
+    #[arg(long, conflicts_with = "every")]
+    pub cron: Option<String>,
+
+    /// Runs a sequence every this many seconds instead of the project's schedules
+    #[arg(long)]
+    pub every: Option<u32>,
+
+    /// The sequence run with --cron or --every
+    #[arg(long, default_value = "main")]
+    pub sequence: String,
+
+    /// The environment of the sequence run with --cron or --every
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Posts the summary of failed runs to this url, with --cron or --every
+    #[arg(long, value_name = "URL")]
+    pub webhook: Vec<String>,
+
+    /// Posts the summary of failed runs to this slack incoming webhook, with --cron or --every
+    #[arg(long, value_name = "URL")]
+    pub slack: Vec<String>,
+}
+
+// Runs the schedules until interrupted, each one in its own task
+pub async fn schedule(args: ScheduleArgs) -> anyhow::Result<ExitCode> {
+    let path = std::path::absolute(&args.file)?;
+    let schedules = schedules(&args).await?;
+
+    let mut tasks = tokio::task::JoinSet::new();
+    for schema in schedules {
+        let schedule = Schedule::from_schema(&schema)?;
+        let path = path.clone();
+
+        tasks.spawn(async move {
+            let mut last = chrono::Local::now();
+            loop {
+                let next = match schedule.next_after(last) {
+                    Some(next) => next,
+                    None => {
+                        eprintln!("{} never runs again", schema.name);
+                        return;
+                    }
+                };
+                println!(
+                    "{}: next run at {}",
+                    schema.name,
+                    next.format("%Y-%m-%d %H:%M:%S")
+                );
+
+                let wait = (next - chrono::Local::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(wait).await;
+                last = next;
+
+                match run_scheduled(&path, &schema).await {
+                    Ok(report) => println!(
+                        "{}: {} passed, {} failed in {}ms",
+                        schema.name,
+                        report.entries.len() - report.failures(),
+                        report.failures(),
+                        report.duration_ms
+                    ),
+                    Err(e) => eprintln!("{}: error: {:#}", schema.name, e),
+                };
+            }
+        });
+    }
+
+    // a schedule that stops leaves the others running, until all are done or interrupted
+    let mut panicked = false;
+    let ctrl_c = tokio::signal::ctrl_c();
+    tokio::pin!(ctrl_c);
+    loop {
+        tokio::select! {
+            joined = tasks.join_next() => match joined {
+                Some(Err(e)) => {
+                    eprintln!("A schedule stopped: {}", e);
+                    panicked |= e.is_panic();
+                }
+                Some(Ok(())) => {}
+                None => break,
+            },
+            _ = &mut ctrl_c => break,
+        };
+    }
+
+    if panicked {
+        return Ok(ExitCode::FAILURE);
+    }
+    return Ok(ExitCode::SUCCESS);
+}
+
+// The schedule given on the command line, or the ones of the project
+async fn schedules(args: &ScheduleArgs) -> anyhow::Result<Vec<ScheduleSchema>> {
+    if args.cron.is_some() || args.every.is_some() {
+        return Ok(vec![ScheduleSchema {
+            name: args.sequence.clone(),
+            sequence: args.sequence.clone(),
+            environment: args.env.clone(),
+            cron: args.cron.clone(),
+            every: args.every,
+            notify: notifications(&args.webhook, &args.slack, false),
+        }]);
+    }
+
+    let runner = Runner::load(&std::path::absolute(&args.file)?).await?;
+    let schedules = runner
+        .project
+        .object
+        .schedules
+        .into_iter()
+        .filter(|schedule| match &args.name {
+            Some(name) => schedule.name == *name,
+            None => true,
+        })
+        .collect::<Vec<ScheduleSchema>>();
+
+    if schedules.is_empty() {
+        anyhow::bail!("No schedules to run, add some to the project or use --cron or --every");
+    }
+
+    return Ok(schedules);
+}
+
+// The notifications given on the command line
+pub fn notifications(
+    webhooks: &[String],
+    slacks: &[String],
+    always: bool,
+) -> Vec<NotificationSchema> {
+    let webhooks = webhooks
+        .iter()
+        .map(|url| NotificationChannelSchema::Webhook { url: url.clone() });
+    let slacks = slacks
+        .iter()
+        .map(|url| NotificationChannelSchema::Slack { url: url.clone() });
+
+    return webhooks
+        .chain(slacks)
+        .map(|channel| NotificationSchema { channel, always })
+        .collect();
+}
@@ -0,0 +1,348 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use nativedoctor_core::runner::{CallResult, Runner};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+};
+
+use crate::run::{load_runner, parse_variable};
+
+#[derive(Args)]
+pub struct TuiArgs {
+    /// The project file
+    pub file: PathBuf,
+
+    /// The environment to start with, e switches environments once open
+    #[arg(long)]
+    pub env: Option<String>,
+
+    /// Sets a variable, overriding the environment
+    #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_variable)]
+    pub variables: Vec<(String, String)>,
+}
+
+#[derive(Clone)]
+enum Item {
+    Request(String),
+    Sequence(String),
+}
+
+struct App {
+    runner: Runner,
+    items: Vec<Item>,
+    list: ListState,
+    title: String,
+    output: Vec<Line<'static>>,
+    scroll: u16,
+}
+
+pub async fn tui(args: TuiArgs) -> anyhow::Result<()> {
+    let runner = load_runner(&args.file, args.env.as_deref(), &args.variables).await?;
+    let mut app = App::new(runner);
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+
+    return result;
+}
+
+impl App {
+    fn new(runner: Runner) -> App {
+        let mut sequences = runner
+            .project
+            .object
+            .calls
+            .overrides
+            .keys()
+            .cloned()
+            .collect::<Vec<String>>();
+        sequences.sort();
+        sequences.insert(0, "main".to_string());
+
+        let mut requests = runner.request_names();
+        requests.sort();
+
+        let mut items = sequences
+            .into_iter()
+            .map(Item::Sequence)
+            .collect::<Vec<Item>>();
+        items.extend(requests.into_iter().map(Item::Request));
+
+        return App {
+            runner,
+            items,
+            list: ListState::default().with_selected(Some(0)),
+            title: "Response".to_string(),
+            output: vec![Line::from("Select a request or sequence and press enter")],
+            scroll: 0,
+        };
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            let key = match event::read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => key,
+                _ => continue,
+            };
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Down | KeyCode::Char('j') => self.list.select_next(),
+                KeyCode::Up | KeyCode::Char('k') => self.list.select_previous(),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                KeyCode::Char('e') => self.next_environment(),
+                KeyCode::Enter => {
+                    self.title = "Running...".to_string();
+                    terminal.draw(|frame| self.draw(frame))?;
+                    self.run_selected().await;
+                }
+                _ => {}
+            };
+        }
+    }
+
+    // Cycles through the default environment and the ones declared in the project
+    fn next_environment(&mut self) {
+        let mut environments = vec![None];
+        environments.extend(
+            self.runner
+                .project
+                .object
+                .environments()
+                .into_iter()
+                .map(Some),
+        );
+
+        let current = environments
+            .iter()
+            .position(|environment| *environment == self.runner.environment)
+            .unwrap_or(0);
+        self.runner.environment = environments[(current + 1) % environments.len()].clone();
+    }
+
+    async fn run_selected(&mut self) {
+        let item = match self.list.selected().and_then(|index| self.items.get(index)) {
+            Some(item) => item.clone(),
+            None => return,
+        };
+
+        // every run starts from the env, like a new run of the cli
+        self.runner.responses.clear();
+        self.scroll = 0;
+
+        if let Err(e) = self.runner.state.reload() {
+            self.show_error("State", e);
+            return;
+        }
+
+        match item {
+            Item::Request(name) => {
+                match self.runner.call_request(&name).await {
+                    Ok(result) => {
+                        self.title = name;
+                        self.output = render_result(&result);
+                    }
+                    Err(e) => self.show_error(&name, e),
+                };
+            }
+            Item::Sequence(name) => {
+                match self.runner.run_sequence(&name).await {
+                    Ok(report) => {
+                        let mut lines = vec![];
+                        for entry in &report.entries {
+                            lines.push(match &entry.error {
+                                None if entry.skipped => {
+                                    Line::from(format!("- {} skipped", entry.name)).dim()
+                                }
+                                None => Line::from(vec![
+                                    Span::styled("✓ ", Style::new().fg(Color::Green)),
+                                    Span::raw(format!(
+                                        "{} {} {} -> {} ({}ms)",
+                                        entry.name,
+                                        entry.method,
+                                        entry.url,
+                                        entry.status.unwrap_or_default(),
+                                        entry.duration_ms
+                                    )),
+                                ]),
+                                Some(error) => Line::from(vec![
+                                    Span::styled("✗ ", Style::new().fg(Color::Red)),
+                                    Span::raw(format!("{}: {}", entry.name, error)),
+                                ]),
+                            });
+                        }
+
+                        // the body of the last call, the one that's usually interesting
+                        let last = report
+                            .entries
+                            .iter()
+                            .rev()
+                            .find_map(|entry| self.runner.responses.get(&entry.name));
+                        if let Some(result) = last {
+                            lines.push(Line::default());
+                            lines.extend(render_result(result));
+                        }
+
+                        self.title = format!("{} ({}ms)", name, report.duration_ms);
+                        self.output = lines;
+                    }
+                    Err(e) => self.show_error(&name, e),
+                };
+            }
+        };
+    }
+
+    fn show_error(&mut self, name: &str, error: anyhow::Error) {
+        self.title = name.to_string();
+        self.output = format!("{:#}", error)
+            .lines()
+            .map(|line| Line::styled(line.to_string(), Style::new().fg(Color::Red)))
+            .collect();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, help] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [sidebar, content] =
+            Layout::horizontal([Constraint::Percentage(30), Constraint::Percentage(70)])
+                .areas(main);
+
+        let items = self
+            .items
+            .iter()
+            .map(|item| match item {
+                Item::Sequence(name) => ListItem::new(format!("» {}", name)),
+                Item::Request(name) => ListItem::new(format!("  {}", name)),
+            })
+            .collect::<Vec<ListItem>>();
+
+        let environment = self.runner.environment.as_deref().unwrap_or("default");
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("Calls ({})", environment)))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, sidebar, &mut self.list);
+
+        let output = Paragraph::new(self.output.clone())
+            .block(Block::bordered().title(self.title.clone()))
+            .scroll((self.scroll, 0));
+        frame.render_widget(output, content);
+
+        frame.render_widget(
+            Line::from("↑↓ select  enter run  e environment  pgup/pgdn scroll  q quit").dim(),
+            help,
+        );
+    }
+}
+
+fn render_result(result: &CallResult) -> Vec<Line<'static>> {
+    let response = &result.response;
+    let color = match response.status {
+        200..=299 => Color::Green,
+        300..=399 => Color::Yellow,
+        _ => Color::Red,
+    };
+
+    let mut lines = vec![
+        Line::from(vec![
+            Span::styled(
+                response.status.to_string(),
+                Style::new().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                " {} {} ({}ms, {} bytes)",
+                result.request.method,
+                result.request.url,
+                result.duration.as_millis(),
+                response.body.len()
+            )),
+        ]),
+        Line::default(),
+    ];
+
+    let mut headers = response.headers.iter().collect::<Vec<_>>();
+    headers.sort();
+    for (key, value) in headers {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}: ", key), Style::new().fg(Color::Cyan)),
+            Span::raw(value.clone()),
+        ]));
+    }
+    lines.push(Line::default());
+
+    match response.json() {
+        Ok(json) => {
+            let pretty = serde_json::to_string_pretty(&json).unwrap_or_else(|_| response.text());
+            lines.extend(pretty.lines().map(highlight_json));
+        }
+        Err(_) => lines.extend(
+            response
+                .text()
+                .lines()
+                .map(|line| Line::from(line.to_string())),
+        ),
+    };
+
+    return lines;
+}
+
+// Colors a line of pretty printed json: keys, strings, numbers and literals
+fn highlight_json(line: &str) -> Line<'static> {
+    let chars = line.chars().collect::<Vec<char>>();
+    let mut spans = vec![];
+    let mut index = 0;
+
+    while index < chars.len() {
+        let start = index;
+        let c = chars[index];
+
+        let color = if c == '"' {
+            index += 1;
+            while index < chars.len() && chars[index] != '"' {
+                if chars[index] == '\\' {
+                    index += 1;
+                }
+                index += 1;
+            }
+            index = (index + 1).min(chars.len());
+
+            let is_key = chars[index..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| *c == ':');
+            Some(if is_key { Color::Cyan } else { Color::Green })
+        } else if c == '-' || c.is_ascii_digit() {
+            while index < chars.len()
+                && (chars[index].is_ascii_digit() || "-+.eE".contains(chars[index]))
+            {
+                index += 1;
+            }
+            Some(Color::Yellow)
+        } else if c.is_alphabetic() {
+            while index < chars.len() && chars[index].is_alphabetic() {
+                index += 1;
+            }
+            Some(Color::Magenta)
+        } else {
+            index += 1;
+            None
+        };
+
+        let text = chars[start..index].iter().collect::<String>();
+        spans.push(match color {
+            Some(color) => Span::styled(text, Style::new().fg(color)),
+            None => Span::raw(text),
+        });
+    }
+
+    return Line::from(spans);
+}
@@ -0,0 +1,97 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+use nativedoctor_core::runner::Runner;
+use notify::{RecursiveMode, Watcher};
+
+use crate::run::{RunArgs, run_once};
+
+// Changes that come in this soon after another are handled together, editors often write several times
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// Runs, then runs again on every change until interrupted
+pub async fn watch(args: &RunArgs) -> anyhow::Result<ExitCode> {
+    let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<()>();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event
+            && (event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove())
+        {
+            let _ = sender.send(());
+        }
+    })?;
+
+    let file = std::path::absolute(&args.file)?;
+    let mut watched: Vec<PathBuf> = vec![];
+
+    loop {
+        // a failing run is reported and watched like any other
+        match run_once(args).await {
+            Ok(_) => {}
+            Err(e) => eprintln!("error: {:#}", e),
+        };
+
+        for path in watched.drain(..) {
+            let _ = watcher.unwatch(&path);
+        }
+
+        for (path, mode) in watched_paths(&file).await {
+            match watcher.watch(&path, mode) {
+                Ok(_) => watched.push(path),
+                Err(e) => eprintln!("Can't watch {}: {}", path.display(), e),
+            };
+        }
+
+        println!("Watching {} files for changes...", watched.len());
+
+        // runs handle Ctrl+C themselves, so it's also listened for while waiting
+        tokio::select! {
+            changed = receiver.recv() => {
+                if changed.is_none() {
+                    return Ok(ExitCode::SUCCESS);
+                }
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(ExitCode::SUCCESS),
+        };
+
+        tokio::time::sleep(DEBOUNCE).await;
+        while receiver.try_recv().is_ok() {}
+
+        println!();
+        println!("Change detected, running again");
+    }
+}
+
+// The project file, the requests folder and every file the requests read.
+// Only the project file and requests folder are watched when the project can't be loaded.
+async fn watched_paths(file: &Path) -> Vec<(PathBuf, RecursiveMode)> {
+    let mut paths = vec![(file.to_path_buf(), RecursiveMode::NonRecursive)];
+
+    let mut runner = match Runner::load(file).await {
+        Ok(runner) => runner,
+        Err(_) => {
+            if let Some(parent) = file.parent() {
+                paths.push((parent.join("requests"), RecursiveMode::Recursive));
+            }
+            return paths;
+        }
+    };
+
+    paths.push((runner.project.get_requests_dir(), RecursiveMode::Recursive));
+    // the files read by requests that don't load aren't known, the requests folder still is
+    let _ = runner.load_requests().await;
+    for request in &runner.requests {
+        for referenced in request.get_referenced_files() {
+            if referenced.exists() && !paths.iter().any(|(path, _)| *path == referenced) {
+                paths.push((referenced, RecursiveMode::NonRecursive));
+            }
+        }
+    }
+
+    return paths
+        .into_iter()
+        .filter(|(path, _)| path.exists())
+        .collect();
+}
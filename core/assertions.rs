@@ -0,0 +1,393 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::{
+    expression::{evaluate, evaluate_value},
+    interpolation::EnvMap,
+    query::json_path_first,
+    runner::{CallResult, ResponseObject, Runner},
+    schema::validation::ResponseValidationSchema,
+};
+
+/// Returned when a response doesn't satisfy the assertions of its request.
+#[derive(Debug, Clone)]
+pub struct AssertionError {
+    pub name: String,
+    pub failures: Vec<String>,
+}
+
+impl std::fmt::Display for AssertionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(
+            f,
+            "Request \"{}\" failed assertions: {}",
+            self.name,
+            self.failures.join("; ")
+        );
+    }
+}
+
+impl std::error::Error for AssertionError {}
+
+impl Runner {
+    // Stores the captures of a request, then checks its assertions, response schema, the contract and snapshot.
+    // Captures and assertions are evaluated with the response available as `response` and the request as `request`.
+    pub(crate) async fn check_response(
+        &mut self,
+        name: &str,
+        result: &CallResult,
+    ) -> anyhow::Result<()> {
+        let request = self.find_request(name)?.clone();
+        let mut context = self.build_context();
+        context.insert("response".to_string(), result.response.to_value());
+        context.insert("request".to_string(), result.request.to_value());
+
+        for (variable, query) in &request.object.capture {
+            let value = capture(query, &context)
+                .with_context(|| format!("Failed to capture \"{}\" from {}", variable, name))?;
+            context.insert(variable.clone(), value.clone());
+            self.state.set(variable, value);
+        }
+
+        if !request.object.capture.is_empty() {
+            self.state.save().await?;
+        }
+
+        let mut failures = vec![];
+        for assertion in &request.object.assert {
+            match evaluate(assertion, &context) {
+                Ok(true) => {}
+                Ok(false) => failures.push(format!("`{}` is false", assertion)),
+                Err(e) => failures.push(format!("`{}`: {:#}", assertion, e)),
+            };
+        }
+
+        if !request.object.assert_lines.is_empty() {
+            failures.extend(check_lines(
+                &request.object.assert_lines,
+                &result.response,
+                &context,
+            ));
+        }
+
+        if let Some(schema) = &request.object.response_schema {
+            let base_dir = request.path.parent().unwrap_or(Path::new("."));
+            let schema = load_schema(schema, base_dir).await?;
+            failures.extend(validate_schema(&schema, &result.response)?);
+        }
+
+        failures.extend(self.check_contract(result));
+
+        if let Some(snapshot) = &request.object.snapshot {
+            failures.extend(
+                self.check_snapshot(name, snapshot, &result.response)
+                    .await?,
+            );
+        }
+
+        if !failures.is_empty() {
+            return Err(AssertionError {
+                name: name.to_string(),
+                failures,
+            }
+            .into());
+        }
+
+        return Ok(());
+    }
+}
+
+// A query starting with `$` is a JSONPath on the response body,
+// anything else is an expression, eg: response.headers.location
+pub(crate) fn capture(query: &str, context: &EnvMap) -> anyhow::Result<serde_yaml::Value> {
+    let query = query.trim();
+
+    if query.starts_with('$') {
+        let body = context
+            .get("response")
+            .and_then(|response| response.get("body"))
+            .cloned()
+            .unwrap_or_default();
+        return match json_path_first(&body, query)? {
+            Some(value) => Ok(value),
+            None => anyhow::bail!("`{}` matched nothing", query),
+        };
+    }
+
+    return evaluate_value(query, context);
+}
+
+// Evaluates each assertion on every line of the body, with the line available as `line` and its position
+// (from 1) as `index`. A failing assertion is reported once, with the number of lines it failed on.
+fn check_lines(assertions: &[String], response: &ResponseObject, context: &EnvMap) -> Vec<String> {
+    let lines = match response.json() {
+        Ok(serde_json::Value::Array(lines)) => lines,
+        Ok(_) => return vec!["The response body is not a list of lines".to_string()],
+        Err(e) => return vec![format!("{:#}", e)],
+    };
+
+    let mut context = context.clone();
+    let mut failures = vec![];
+    for assertion in assertions {
+        let mut failed = vec![];
+        let mut error = None;
+
+        for (index, line) in lines.iter().enumerate() {
+            context.insert(
+                "line".to_string(),
+                serde_yaml::to_value(line).unwrap_or_default(),
+            );
+            context.insert("index".to_string(), ((index + 1) as u64).into());
+
+            match evaluate(assertion, &context) {
+                Ok(true) => {}
+                Ok(false) => failed.push(index + 1),
+                Err(e) => {
+                    error = Some(format!("`{}` on line {}: {:#}", assertion, index + 1, e));
+                    break;
+                }
+            };
+        }
+
+        if let Some(error) = error {
+            failures.push(error);
+        } else if let Some(first) = failed.first() {
+            failures.push(format!(
+                "`{}` is false on {} of {} lines (first on line {})",
+                assertion,
+                failed.len(),
+                lines.len(),
+                first
+            ));
+        }
+    }
+
+    return failures;
+}
+
+async fn load_schema(
+    schema: &ResponseValidationSchema,
+    base_dir: &Path,
+) -> anyhow::Result<serde_json::Value> {
+    let schema = match schema {
+        ResponseValidationSchema::Inline(schema) => schema.clone(),
+        ResponseValidationSchema::Path(path) => {
+            let path = base_dir.join(path);
+            let content = tokio::fs::read_to_string(&path)
+                .await
+                .with_context(|| format!("Failed to read response schema {:?}", path))?;
+            // yaml is a superset of json, so both formats are read the same way
+            serde_yaml::from_str::<serde_yaml::Value>(&content)
+                .with_context(|| format!("Invalid response schema {:?}", path))?
+        }
+    };
+
+    return Ok(serde_json::to_value(schema)?);
+}
+
+// Validates the response body, returning a failure for each violation along with its path
+fn validate_schema(
+    schema: &serde_json::Value,
+    response: &ResponseObject,
+) -> anyhow::Result<Vec<String>> {
+    let body = match response.json() {
+        Ok(body) => body,
+        Err(_) => return Ok(vec!["response body is not json".to_string()]),
+    };
+
+    return schema_violations(schema, &body);
+}
+
+// Validates a json value, returning a failure for each violation along with its path
+pub(crate) fn schema_violations(
+    schema: &serde_json::Value,
+    body: &serde_json::Value,
+) -> anyhow::Result<Vec<String>> {
+    let validator = match jsonschema::validator_for(schema) {
+        Ok(validator) => validator,
+        Err(e) => anyhow::bail!("Invalid response schema: {}", e),
+    };
+
+    return Ok(validator
+        .iter_errors(body)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            format!(
+                "schema violation at {}: {}",
+                if path.is_empty() { "/" } else { &path },
+                error
+            )
+        })
+        .collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{fs::FileObject, schema::roots::RequestRootSchema};
+
+    // A runner with a request given as its yaml
+    fn runner(request: &str) -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-assert-{}", uuid::Uuid::new_v4()));
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![FileObject::new(
+                root.join("requests/user.nd"),
+                serde_yaml::from_str::<RequestRootSchema>(request).unwrap(),
+            )],
+        )
+        .unwrap();
+    }
+
+    fn response(content_type: &str, body: &str) -> ResponseObject {
+        return ResponseObject {
+            status: 200,
+            headers: [("content-type".to_string(), content_type.to_string())].into(),
+            body: body.as_bytes().to_vec(),
+            ..Default::default()
+        };
+    }
+
+    // The failures of the assertions of the user request on this response
+    async fn failures(runner: &mut Runner, response: ResponseObject) -> Vec<String> {
+        let result = CallResult {
+            request: runner.resolve_request("user").unwrap(),
+            response,
+            duration: Duration::from_millis(5),
+            started: chrono::Utc::now(),
+        };
+
+        return match runner.check_response("user", &result).await {
+            Ok(()) => vec![],
+            Err(e) => {
+                let error = e.downcast::<AssertionError>().unwrap();
+                assert_eq!(error.name, "user");
+                error.failures
+            }
+        };
+    }
+
+    #[tokio::test]
+    async fn each_assertion_that_fails_is_reported() {
+        let mut runner = runner(
+            "method: GET
+url: http://localhost/users/1
+assert:
+  - response.status == 200
+  - response.body.name == 'b'
+  - request.method == 'GET'
+  - response.status ==",
+        );
+
+        let failures = failures(
+            &mut runner,
+            response("application/json", "{\"id\": 1, \"name\": \"a\"}"),
+        )
+        .await;
+
+        assert_eq!(failures.len(), 2, "{:?}", failures);
+        assert_eq!(failures[0], "`response.body.name == 'b'` is false");
+        assert!(
+            failures[1].starts_with("`response.status ==`: "),
+            "{}",
+            failures[1]
+        );
+    }
+
+    #[test]
+    fn failures_are_listed_in_the_error() {
+        let error = AssertionError {
+            name: "user".to_string(),
+            failures: vec!["`a` is false".to_string(), "`b` is false".to_string()],
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Request \"user\" failed assertions: `a` is false; `b` is false"
+        );
+    }
+
+    #[tokio::test]
+    async fn captures_are_stored_before_the_assertions_run() {
+        let mut runner = runner(
+            "method: GET
+url: http://localhost/users/1
+capture:
+  id: $.id
+  status: response.status
+assert: [\"id == 1\"]",
+        );
+
+        let failures = failures(&mut runner, response("application/json", "{\"id\": 1}")).await;
+
+        assert!(failures.is_empty(), "{:?}", failures);
+        assert_eq!(runner.state.get("id"), Some(&serde_yaml::Value::from(1)));
+        assert_eq!(
+            runner.state.get("status"),
+            Some(&serde_yaml::Value::from(200))
+        );
+    }
+
+    #[test]
+    fn a_json_path_capture_fails_when_it_matches_nothing() {
+        let mut context = EnvMap::new();
+        context.insert(
+            "response".to_string(),
+            serde_yaml::from_str("body: {items: [{id: 4}]}").unwrap(),
+        );
+
+        assert_eq!(
+            capture(" $.items[0].id ", &context).unwrap(),
+            serde_yaml::Value::from(4)
+        );
+        assert_eq!(
+            capture("$.items[1].id", &context).unwrap_err().to_string(),
+            "`$.items[1].id` matched nothing"
+        );
+    }
+
+    #[tokio::test]
+    async fn line_assertions_report_how_many_lines_failed() {
+        let mut runner = runner(
+            "method: GET
+url: http://localhost/events
+assert_lines:
+  - line.n > 1
+  - index <= 3
+  - line.level == 'info'",
+        );
+
+        let failures = failures(
+            &mut runner,
+            response(
+                "application/x-ndjson",
+                "{\"n\": 1, \"level\": \"info\"}\n{\"n\": 2, \"level\": \"warn\"}\n{\"n\": 3, \"level\": \"warn\"}\n",
+            ),
+        )
+        .await;
+
+        assert_eq!(
+            failures,
+            vec![
+                "`line.n > 1` is false on 1 of 3 lines (first on line 1)".to_string(),
+                "`line.level == 'info'` is false on 2 of 3 lines (first on line 2)".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn line_assertions_need_a_list_of_lines() {
+        let mut runner = runner(
+            "method: GET
+url: http://localhost/events
+assert_lines: [\"line.n > 0\"]",
+        );
+
+        let failures = failures(&mut runner, response("application/json", "{\"n\": 1}")).await;
+
+        assert_eq!(failures, vec!["The response body is not a list of lines"]);
+    }
+}
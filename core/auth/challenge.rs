@@ -0,0 +1,422 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use sha2::Digest;
+
+use super::hex;
+
+// Builds the authorization of a digest challenge:
+// https://datatracker.ietf.org/doc/html/rfc7616
+pub fn digest_authorization(
+    request: &reqwest::Request,
+    username: &str,
+    password: &str,
+    challenge: &str,
+) -> anyhow::Result<String> {
+    let cnonce = hex(&rand::random::<[u8; 16]>());
+    return digest_with_cnonce(request, username, password, challenge, &cnonce);
+}
+
+fn digest_with_cnonce(
+    request: &reqwest::Request,
+    username: &str,
+    password: &str,
+    challenge: &str,
+    cnonce: &str,
+) -> anyhow::Result<String> {
+    let params = parse_params(challenge);
+    let param = |key: &str| {
+        params
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    };
+
+    let realm = param("realm").unwrap_or_default();
+    let nonce = match param("nonce") {
+        Some(nonce) => nonce,
+        None => anyhow::bail!("Digest challenge has no nonce"),
+    };
+    let algorithm = param("algorithm").unwrap_or("MD5");
+
+    let hash = |value: &[u8]| -> anyhow::Result<String> {
+        return Ok(match algorithm.to_uppercase().trim_end_matches("-SESS") {
+            "MD5" => hex(&md5::Md5::digest(value)),
+            "SHA-256" => hex(&sha2::Sha256::digest(value)),
+            "SHA-512-256" => hex(&sha2::Sha512_256::digest(value)),
+            other => anyhow::bail!("Unsupported digest algorithm {}", other),
+        });
+    };
+
+    // prefer auth over auth-int, auth-int hashes the body too
+    let qop = param("qop").map(|qop| {
+        let options = qop.split(',').map(|q| q.trim()).collect::<Vec<&str>>();
+        if options.contains(&"auth") || !options.contains(&"auth-int") {
+            "auth"
+        } else {
+            "auth-int"
+        }
+    });
+
+    let uri = match request.url().query() {
+        Some(query) => format!("{}?{}", request.url().path(), query),
+        None => request.url().path().to_string(),
+    };
+    let nc = "00000001";
+
+    let mut ha1 = hash(format!("{}:{}:{}", username, realm, password).as_bytes())?;
+    if algorithm.to_uppercase().ends_with("-SESS") {
+        ha1 = hash(format!("{}:{}:{}", ha1, nonce, cnonce).as_bytes())?;
+    }
+
+    let ha2 = match qop {
+        Some("auth-int") => {
+            let body = request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .unwrap_or_default();
+            let body_hash = hash(body)?;
+            hash(format!("{}:{}:{}", request.method(), uri, body_hash).as_bytes())?
+        }
+        _ => hash(format!("{}:{}", request.method(), uri).as_bytes())?,
+    };
+
+    let response = match qop {
+        Some(qop) => {
+            hash(format!("{}:{}:{}:{}:{}:{}", ha1, nonce, nc, cnonce, qop, ha2).as_bytes())?
+        }
+        None => hash(format!("{}:{}:{}", ha1, nonce, ha2).as_bytes())?,
+    };
+
+    let mut fields = vec![
+        format!("username=\"{}\"", username),
+        format!("realm=\"{}\"", realm),
+        format!("nonce=\"{}\"", nonce),
+        format!("uri=\"{}\"", uri),
+        format!("algorithm={}", algorithm),
+        format!("response=\"{}\"", response),
+    ];
+
+    if let Some(qop) = qop {
+        fields.push(format!("qop={}", qop));
+        fields.push(format!("nc={}", nc));
+        fields.push(format!("cnonce=\"{}\"", cnonce));
+    }
+
+    if let Some(opaque) = param("opaque") {
+        fields.push(format!("opaque=\"{}\"", opaque));
+    }
+
+    return Ok(format!("Digest {}", fields.join(", ")));
+}
+
+// Splits `key=value, key="quoted, value"` into pairs
+fn parse_params(input: &str) -> Vec<(String, String)> {
+    let mut params = vec![];
+    let mut rest = input.trim();
+
+    while !rest.is_empty() {
+        let (key, after) = match rest.split_once('=') {
+            Some((key, after)) => (
+                key.trim().trim_start_matches(',').trim(),
+                after.trim_start(),
+            ),
+            None => break,
+        };
+
+        let (value, after) = match after.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], quoted.get(end + 1..).unwrap_or_default())
+            }
+            None => {
+                let end = after.find(',').unwrap_or(after.len());
+                (after[..end].trim(), &after[end..])
+            }
+        };
+
+        params.push((key.to_string(), value.to_string()));
+        rest = after.trim_start().trim_start_matches(',').trim_start();
+    }
+
+    return params;
+}
+
+// unicode, oem, request target, ntlm, always sign, extended session security, target info, 128, 56
+const NTLM_FLAGS: u32 = 0x00000001
+    | 0x00000002
+    | 0x00000004
+    | 0x00000200
+    | 0x00008000
+    | 0x00080000
+    | 0x00800000
+    | 0x20000000
+    | 0x80000000;
+
+// Seconds between 1601-01-01, where windows time starts, and the unix epoch
+const WINDOWS_EPOCH_OFFSET: u64 = 11_644_473_600;
+
+// The negotiate message that starts an ntlm handshake
+pub fn ntlm_negotiate() -> String {
+    let mut message = b"NTLMSSP\0".to_vec();
+    message.extend(1u32.to_le_bytes());
+    message.extend(NTLM_FLAGS.to_le_bytes());
+    message.extend([0u8; 16]); // empty domain and workstation
+
+    return format!(
+        "NTLM {}",
+        base64::engine::general_purpose::STANDARD.encode(message)
+    );
+}
+
+// Answers the challenge message of the server with an NTLMv2 authenticate message:
+// https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-nlmp
+pub fn ntlm_authenticate(
+    username: &str,
+    password: &str,
+    domain: &str,
+    workstation: &str,
+    challenge: &str,
+) -> anyhow::Result<String> {
+    let challenge = base64::engine::general_purpose::STANDARD
+        .decode(challenge.trim())
+        .map_err(|_| anyhow::anyhow!("Invalid ntlm challenge"))?;
+
+    if challenge.len() < 48 || !challenge.starts_with(b"NTLMSSP\0") || challenge[8] != 2 {
+        anyhow::bail!("Invalid ntlm challenge");
+    }
+
+    let server_challenge = &challenge[24..32];
+    let target_info = {
+        let length = u16::from_le_bytes([challenge[40], challenge[41]]) as usize;
+        let offset =
+            u32::from_le_bytes([challenge[44], challenge[45], challenge[46], challenge[47]])
+                as usize;
+        match challenge.get(offset..offset + length) {
+            Some(target_info) => target_info.to_vec(),
+            None => anyhow::bail!("Invalid ntlm challenge"),
+        }
+    };
+
+    let ntlmv2_hash = ntowfv2(username, password, domain)?;
+    let client_challenge = rand::random::<[u8; 8]>();
+    let timestamp = (chrono::Utc::now().timestamp() as u64 + WINDOWS_EPOCH_OFFSET) * 10_000_000;
+    let (lm_response, nt_response) = ntlmv2_responses(
+        &ntlmv2_hash,
+        server_challenge,
+        &client_challenge,
+        timestamp,
+        &target_info,
+    )?;
+
+    let fields = [
+        lm_response,
+        nt_response,
+        utf16(domain),
+        utf16(username),
+        utf16(workstation),
+        vec![], // no session key
+    ];
+
+    // header, six security buffers and the flags come before the payload
+    let mut offset = 64u32;
+    let mut message = b"NTLMSSP\0".to_vec();
+    message.extend(3u32.to_le_bytes());
+    for field in &fields {
+        message.extend((field.len() as u16).to_le_bytes());
+        message.extend((field.len() as u16).to_le_bytes());
+        message.extend(offset.to_le_bytes());
+        offset += field.len() as u32;
+    }
+    message.extend(NTLM_FLAGS.to_le_bytes());
+    for field in fields {
+        message.extend(field);
+    }
+
+    return Ok(format!(
+        "NTLM {}",
+        base64::engine::general_purpose::STANDARD.encode(message)
+    ));
+}
+
+// The NTLMv2 hash of the credentials (NTOWFv2)
+fn ntowfv2(username: &str, password: &str, domain: &str) -> anyhow::Result<Vec<u8>> {
+    let nt_hash = md4::Md4::digest(utf16(password)).to_vec();
+    let identity = utf16(&format!("{}{}", username.to_uppercase(), domain));
+    return hmac_md5(&nt_hash, &[&identity]);
+}
+
+// The LMv2 and NTLMv2 responses to the server challenge, timestamp is in windows time
+fn ntlmv2_responses(
+    ntlmv2_hash: &[u8],
+    server_challenge: &[u8],
+    client_challenge: &[u8; 8],
+    timestamp: u64,
+    target_info: &[u8],
+) -> anyhow::Result<(Vec<u8>, Vec<u8>)> {
+    let mut blob = vec![1u8, 1, 0, 0, 0, 0, 0, 0];
+    blob.extend(timestamp.to_le_bytes());
+    blob.extend(client_challenge);
+    blob.extend([0u8; 4]);
+    blob.extend(target_info);
+    blob.extend([0u8; 4]);
+
+    let proof = hmac_md5(ntlmv2_hash, &[server_challenge, &blob])?;
+    let nt_response = [proof, blob].concat();
+    let lm_response = [
+        hmac_md5(ntlmv2_hash, &[server_challenge, client_challenge])?,
+        client_challenge.to_vec(),
+    ]
+    .concat();
+
+    return Ok((lm_response, nt_response));
+}
+
+fn utf16(value: &str) -> Vec<u8> {
+    return value
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+}
+
+fn hmac_md5(key: &[u8], parts: &[&[u8]]) -> anyhow::Result<Vec<u8>> {
+    let mut mac = Hmac::<md5::Md5>::new_from_slice(key)?;
+    for part in parts {
+        mac.update(part);
+    }
+    return Ok(mac.finalize().into_bytes().to_vec());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the example of RFC 7616 section 3.9.1
+    const DIGEST_CHALLENGE: &str = r#"realm="http-auth@example.org", qop="auth, auth-int", algorithm=ALGORITHM, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+    const DIGEST_CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+    fn digest(algorithm: &str) -> String {
+        let request = reqwest::Request::new(
+            reqwest::Method::GET,
+            reqwest::Url::parse("http://www.example.org/dir/index.html").unwrap(),
+        );
+        return digest_with_cnonce(
+            &request,
+            "Mufasa",
+            "Circle of Life",
+            &DIGEST_CHALLENGE.replace("ALGORITHM", algorithm),
+            DIGEST_CNONCE,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn digest_md5_of_rfc_7616() {
+        let authorization = digest("MD5");
+
+        assert!(authorization.starts_with("Digest username=\"Mufasa\""));
+        assert!(authorization.contains("uri=\"/dir/index.html\""));
+        assert!(authorization.contains("response=\"8ca523f5e9506fed4657c9700eebdbec\""));
+        assert!(authorization.contains("qop=auth, nc=00000001"));
+        assert!(authorization.contains("opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\""));
+    }
+
+    #[test]
+    fn digest_sha256_of_rfc_7616() {
+        assert!(digest("SHA-256").contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+    }
+
+    #[test]
+    fn digest_params_keep_quoted_commas() {
+        assert_eq!(
+            parse_params(r#"realm="a, b", qop="auth,auth-int", stale=false"#),
+            [
+                ("realm".to_string(), "a, b".to_string()),
+                ("qop".to_string(), "auth,auth-int".to_string()),
+                ("stale".to_string(), "false".to_string()),
+            ]
+        );
+    }
+
+    // the NTLMv2 example of MS-NLMP section 4.2.4
+    fn target_info() -> Vec<u8> {
+        let mut target_info = vec![0x02, 0x00, 0x0c, 0x00];
+        target_info.extend(utf16("Domain"));
+        target_info.extend([0x01, 0x00, 0x0c, 0x00]);
+        target_info.extend(utf16("Server"));
+        target_info.extend([0u8; 4]);
+        return target_info;
+    }
+
+    #[test]
+    fn ntowfv2_of_ms_nlmp() {
+        assert_eq!(
+            hex(&ntowfv2("User", "Password", "Domain").unwrap()),
+            "0c868a403bfd7a93a3001ef22ef02e3f"
+        );
+    }
+
+    #[test]
+    fn ntlmv2_responses_of_ms_nlmp() {
+        let hash = ntowfv2("User", "Password", "Domain").unwrap();
+        let (lm_response, nt_response) = ntlmv2_responses(
+            &hash,
+            &[0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef],
+            &[0xaa; 8],
+            0,
+            &target_info(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            hex(&lm_response),
+            "86c35097ac9cec102554764a57cccc19aaaaaaaaaaaaaaaa"
+        );
+        assert_eq!(hex(&nt_response[..16]), "68cd0ab851e51c96aabc927bebef6a1c");
+    }
+
+    #[test]
+    fn ntlm_authenticate_answers_the_challenge() {
+        let target_info = target_info();
+        let mut challenge = b"NTLMSSP\0".to_vec();
+        challenge.extend(2u32.to_le_bytes());
+        challenge.extend([0u8; 8]); // target name
+        challenge.extend(NTLM_FLAGS.to_le_bytes());
+        challenge.extend([0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef]);
+        challenge.extend([0u8; 8]); // reserved
+        challenge.extend((target_info.len() as u16).to_le_bytes());
+        challenge.extend((target_info.len() as u16).to_le_bytes());
+        challenge.extend(48u32.to_le_bytes());
+        challenge.extend(&target_info);
+        let challenge = base64::engine::general_purpose::STANDARD.encode(challenge);
+
+        let authorization =
+            ntlm_authenticate("User", "Password", "Domain", "COMPUTER", &challenge).unwrap();
+        let message = base64::engine::general_purpose::STANDARD
+            .decode(authorization.strip_prefix("NTLM ").unwrap())
+            .unwrap();
+
+        assert!(message.starts_with(b"NTLMSSP\0"));
+        assert_eq!(message[8], 3);
+
+        // the domain, user and workstation security buffers point into the payload
+        let field = |index: usize| {
+            let header = 12 + index * 8;
+            let length = u16::from_le_bytes([message[header], message[header + 1]]) as usize;
+            let offset = u32::from_le_bytes([
+                message[header + 4],
+                message[header + 5],
+                message[header + 6],
+                message[header + 7],
+            ]) as usize;
+            message[offset..offset + length].to_vec()
+        };
+        assert_eq!(field(2), utf16("Domain"));
+        assert_eq!(field(3), utf16("User"));
+        assert_eq!(field(4), utf16("COMPUTER"));
+        assert!(field(1).ends_with(&[target_info, vec![0u8; 4]].concat()));
+
+        assert!(ntlm_authenticate("User", "Password", "Domain", "", "bm9wZQ==").is_err());
+    }
+}
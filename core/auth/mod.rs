@@ -0,0 +1,184 @@
+mod challenge;
+mod signature;
+mod sigv4;
+
+use base64::Engine;
+
+use crate::{
+    interpolation::{EnvMap, interpolate_string},
+    schema::auth::AuthSchema,
+};
+
+pub use challenge::{digest_authorization, ntlm_authenticate, ntlm_negotiate};
+pub use signature::sign_hmac;
+pub use sigv4::sign_sigv4;
+
+impl AuthSchema {
+    // Replaces the placeholders in every value
+    pub fn interpolate(&self, env: &EnvMap) -> anyhow::Result<AuthSchema> {
+        let optional = |value: &Option<String>| -> anyhow::Result<Option<String>> {
+            return match value {
+                Some(value) => Ok(Some(interpolate_string(value, env)?)),
+                None => Ok(None),
+            };
+        };
+
+        return Ok(match self {
+            AuthSchema::Basic { username, password } => AuthSchema::Basic {
+                username: interpolate_string(username, env)?,
+                password: interpolate_string(password, env)?,
+            },
+            AuthSchema::Bearer { token } => AuthSchema::Bearer {
+                token: interpolate_string(token, env)?,
+            },
+            AuthSchema::Digest { username, password } => AuthSchema::Digest {
+                username: interpolate_string(username, env)?,
+                password: interpolate_string(password, env)?,
+            },
+            AuthSchema::Ntlm {
+                username,
+                password,
+                domain,
+                workstation,
+            } => AuthSchema::Ntlm {
+                username: interpolate_string(username, env)?,
+                password: interpolate_string(password, env)?,
+                domain: interpolate_string(domain, env)?,
+                workstation: interpolate_string(workstation, env)?,
+            },
+            AuthSchema::AwsSigv4 {
+                access_key,
+                secret_key,
+                session_token,
+                region,
+                service,
+            } => AuthSchema::AwsSigv4 {
+                access_key: optional(access_key)?,
+                secret_key: optional(secret_key)?,
+                session_token: optional(session_token)?,
+                region: optional(region)?,
+                service: interpolate_string(service, env)?,
+            },
+            // payload and value are rendered from the built request when signing
+            AuthSchema::Hmac {
+                algorithm,
+                key,
+                header,
+                payload,
+                value,
+                encoding,
+            } => AuthSchema::Hmac {
+                algorithm: *algorithm,
+                key: interpolate_string(key, env)?,
+                header: interpolate_string(header, env)?,
+                payload: payload.clone(),
+                value: value.clone(),
+                encoding: *encoding,
+            },
+        });
+    }
+
+    // The authorization header for schemes that only need one, the others sign the built request
+    pub fn header(&self) -> Option<(String, String)> {
+        return match self {
+            AuthSchema::Basic { username, password } => {
+                let token = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password));
+                Some(("Authorization".to_string(), format!("Basic {}", token)))
+            }
+            AuthSchema::Bearer { token } => {
+                Some(("Authorization".to_string(), format!("Bearer {}", token)))
+            }
+            _ => None,
+        };
+    }
+
+    // The scheme of the challenge this auth answers, in www-authenticate headers
+    pub fn challenge_scheme(&self) -> Option<&'static str> {
+        return match self {
+            AuthSchema::Digest { .. } => Some("Digest"),
+            AuthSchema::Ntlm { .. } => Some("NTLM"),
+            _ => None,
+        };
+    }
+
+    // The authorization answering the challenge of a 401 response, for the rebuilt request
+    pub fn answer_challenge(
+        &self,
+        request: &reqwest::Request,
+        challenge: &str,
+    ) -> anyhow::Result<String> {
+        return match self {
+            AuthSchema::Digest { username, password } => {
+                digest_authorization(request, username, password, challenge)
+            }
+            AuthSchema::Ntlm {
+                username,
+                password,
+                domain,
+                workstation,
+            } => ntlm_authenticate(username, password, domain, workstation, challenge),
+            _ => anyhow::bail!("Auth doesn't answer challenges"),
+        };
+    }
+}
+
+// Signs a built request for the schemes that depend on its final content
+pub fn sign_request(request: &mut reqwest::Request, auth: &AuthSchema) -> anyhow::Result<()> {
+    return match auth {
+        AuthSchema::AwsSigv4 {
+            access_key,
+            secret_key,
+            session_token,
+            region,
+            service,
+        } => {
+            let access_key = or_env(access_key, "AWS_ACCESS_KEY_ID")?;
+            let secret_key = or_env(secret_key, "AWS_SECRET_ACCESS_KEY")?;
+            let session_token = session_token
+                .clone()
+                .or_else(|| std::env::var("AWS_SESSION_TOKEN").ok());
+            let region = or_env(region, "AWS_REGION")?;
+
+            sign_sigv4(
+                request,
+                &access_key,
+                &secret_key,
+                session_token.as_deref(),
+                &region,
+                service,
+                chrono::Utc::now(),
+            )
+        }
+        AuthSchema::Hmac {
+            algorithm,
+            key,
+            header,
+            payload,
+            value,
+            encoding,
+        } => sign_hmac(request, *algorithm, key, header, payload, value, *encoding),
+        AuthSchema::Ntlm { .. } => {
+            request
+                .headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, ntlm_negotiate().parse()?);
+            Ok(())
+        }
+        _ => Ok(()),
+    };
+}
+
+fn or_env(value: &Option<String>, variable: &str) -> anyhow::Result<String> {
+    if let Some(value) = value {
+        return Ok(value.clone());
+    }
+
+    return match std::env::var(variable) {
+        Ok(value) => Ok(value),
+        Err(_) => anyhow::bail!("{} is not set", variable),
+    };
+}
+
+fn hex(bytes: &[u8]) -> String {
+    return bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
@@ -0,0 +1,75 @@
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde_yaml::Value;
+
+use super::hex;
+use crate::{
+    interpolation::{EnvMap, interpolate_string},
+    schema::auth::{HmacAlgorithmSchema, SignatureEncodingSchema},
+};
+
+// Computes an hmac of the rendered payload and sets it as a header of the request
+pub fn sign_hmac(
+    request: &mut reqwest::Request,
+    algorithm: HmacAlgorithmSchema,
+    key: &str,
+    header: &str,
+    payload: &str,
+    value: &str,
+    encoding: SignatureEncodingSchema,
+) -> anyhow::Result<()> {
+    let body = match request.body() {
+        Some(body) => match body.as_bytes() {
+            Some(bytes) => String::from_utf8_lossy(bytes).to_string(),
+            None => anyhow::bail!("Streamed bodies can't be signed"),
+        },
+        None => String::new(),
+    };
+
+    let mut env = EnvMap::new();
+    env.insert("body".to_string(), Value::String(body));
+    env.insert(
+        "method".to_string(),
+        Value::String(request.method().to_string()),
+    );
+    env.insert("url".to_string(), Value::String(request.url().to_string()));
+    env.insert(
+        "path".to_string(),
+        Value::String(request.url().path().to_string()),
+    );
+    env.insert(
+        "timestamp".to_string(),
+        Value::Number(chrono::Utc::now().timestamp().into()),
+    );
+
+    let payload = interpolate_string(payload, &env)?;
+    let digest = match algorithm {
+        HmacAlgorithmSchema::Sha1 => {
+            let mut mac = Hmac::<sha1::Sha1>::new_from_slice(key.as_bytes())?;
+            mac.update(payload.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+        HmacAlgorithmSchema::Sha256 => {
+            let mut mac = Hmac::<sha2::Sha256>::new_from_slice(key.as_bytes())?;
+            mac.update(payload.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+        HmacAlgorithmSchema::Sha512 => {
+            let mut mac = Hmac::<sha2::Sha512>::new_from_slice(key.as_bytes())?;
+            mac.update(payload.as_bytes());
+            mac.finalize().into_bytes().to_vec()
+        }
+    };
+
+    let signature = match encoding {
+        SignatureEncodingSchema::Hex => hex(&digest),
+        SignatureEncodingSchema::Base64 => base64::engine::general_purpose::STANDARD.encode(digest),
+    };
+    env.insert("signature".to_string(), Value::String(signature));
+
+    let header = reqwest::header::HeaderName::from_bytes(header.as_bytes())?;
+    let value = interpolate_string(value, &env)?;
+    request.headers_mut().insert(header, value.parse()?);
+
+    return Ok(());
+}
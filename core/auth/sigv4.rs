@@ -0,0 +1,323 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use super::hex;
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+// Adds the headers of an AWS Signature Version 4 to a request:
+// https://docs.aws.amazon.com/IAM/latest/UserGuide/create-signed-request.html
+pub fn sign_sigv4(
+    request: &mut reqwest::Request,
+    access_key: &str,
+    secret_key: &str,
+    session_token: Option<&str>,
+    region: &str,
+    service: &str,
+    time: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    let amz_date = time.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = time.format("%Y%m%d").to_string();
+
+    // streamed bodies (multipart) can't be hashed up front
+    let payload_hash = match request.body().and_then(|body| body.as_bytes()) {
+        Some(body) => hex(&Sha256::digest(body)),
+        None if request.body().is_none() => hex(&Sha256::digest(b"")),
+        None => "UNSIGNED-PAYLOAD".to_string(),
+    };
+
+    let url = request.url().clone();
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (Some(host), None) => host.to_string(),
+        (None, _) => anyhow::bail!("Url has no host to sign"),
+    };
+
+    let headers = request.headers_mut();
+    headers.insert("x-amz-date", amz_date.parse()?);
+    headers.insert("x-amz-content-sha256", payload_hash.parse()?);
+    if let Some(token) = session_token {
+        headers.insert("x-amz-security-token", token.parse()?);
+    }
+
+    // host is added by the client when sending, it's signed with the same value
+    let mut signed = vec![("host".to_string(), host)];
+    for (key, value) in headers.iter() {
+        let key = key.as_str().to_lowercase();
+        if key.starts_with("x-amz-") || key == "content-type" {
+            signed.push((key, value.to_str()?.trim().to_string()));
+        }
+    }
+    signed.sort();
+
+    let canonical_headers = signed
+        .iter()
+        .map(|(key, value)| format!("{}:{}\n", key, value))
+        .collect::<String>();
+    let signed_headers = signed
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect::<Vec<&str>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method().as_str(),
+        canonical_uri(&url, service),
+        canonical_query(&url),
+        canonical_headers,
+        signed_headers,
+        payload_hash
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let signature = signature(
+        &canonical_request,
+        secret_key,
+        &scope,
+        time,
+        region,
+        service,
+    )?;
+
+    let authorization = format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, access_key, scope, signed_headers, signature
+    );
+    request
+        .headers_mut()
+        .insert(reqwest::header::AUTHORIZATION, authorization.parse()?);
+
+    return Ok(());
+}
+
+// The signature of the canonical request, with the key derived for the day, region and service
+fn signature(
+    canonical_request: &str,
+    secret_key: &str,
+    scope: &str,
+    time: DateTime<Utc>,
+    region: &str,
+    service: &str,
+) -> anyhow::Result<String> {
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        time.format("%Y%m%dT%H%M%SZ"),
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = signing_key(
+        secret_key,
+        &time.format("%Y%m%d").to_string(),
+        region,
+        service,
+    )?;
+    return Ok(hex(&hmac(&key, string_to_sign.as_bytes())?));
+}
+
+fn signing_key(
+    secret_key: &str,
+    date: &str,
+    region: &str,
+    service: &str,
+) -> anyhow::Result<Vec<u8>> {
+    let mut key = hmac(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes())?;
+    for part in [region, service, "aws4_request"] {
+        key = hmac(&key, part.as_bytes())?;
+    }
+
+    return Ok(key);
+}
+
+// The path with each segment encoded, s3 is the only service that doesn't encode it twice
+fn canonical_uri(url: &reqwest::Url, service: &str) -> String {
+    let path = url.path();
+    if path.is_empty() {
+        return "/".to_string();
+    }
+
+    if service == "s3" {
+        return path.to_string();
+    }
+
+    return path
+        .split('/')
+        .map(encode)
+        .collect::<Vec<String>>()
+        .join("/");
+}
+
+fn canonical_query(url: &reqwest::Url) -> String {
+    let mut pairs = url
+        .query_pairs()
+        .map(|(key, value)| (encode(&key), encode(&value)))
+        .collect::<Vec<(String, String)>>();
+    pairs.sort();
+
+    return pairs
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<String>>()
+        .join("&");
+}
+
+// Percent encodes everything but unreserved characters, as sigv4 expects
+fn encode(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                result.push(byte as char)
+            }
+            _ => result.push_str(&format!("%{:02X}", byte)),
+        };
+    }
+
+    return result;
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)?;
+    mac.update(data);
+    return Ok(mac.finalize().into_bytes().to_vec());
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    // credentials and time of the aws sigv4 test suite and of the IAM examples
+    const ACCESS_KEY: &str = "AKIDEXAMPLE";
+    const SECRET_KEY: &str = "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY";
+    const EMPTY_HASH: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    fn time() -> DateTime<Utc> {
+        return Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+    }
+
+    fn url(url: &str) -> reqwest::Url {
+        return reqwest::Url::parse(url).unwrap();
+    }
+
+    #[test]
+    fn signing_key_of_the_iam_example() {
+        let key = signing_key(SECRET_KEY, "20150830", "us-east-1", "iam").unwrap();
+        assert_eq!(
+            hex(&key),
+            "c4afb1cc5771d871763a393e44b703571b55cc28424d1a5e86da6ed3c154a4b9"
+        );
+    }
+
+    // get-vanilla of the test suite
+    #[test]
+    fn signature_of_get_vanilla() {
+        let canonical_request = format!(
+            "GET\n/\n\nhost:example.amazonaws.com\nx-amz-date:20150830T123600Z\n\nhost;x-amz-date\n{}",
+            EMPTY_HASH
+        );
+        let signature = signature(
+            &canonical_request,
+            SECRET_KEY,
+            "20150830/us-east-1/service/aws4_request",
+            time(),
+            "us-east-1",
+            "service",
+        )
+        .unwrap();
+
+        assert_eq!(
+            signature,
+            "5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    // the ListUsers example of the IAM documentation
+    #[test]
+    fn signature_of_iam_list_users() {
+        let canonical_request = format!(
+            "GET\n/\nAction=ListUsers&Version=2010-05-08\n\
+             content-type:application/x-www-form-urlencoded; charset=utf-8\n\
+             host:iam.amazonaws.com\nx-amz-date:20150830T123600Z\n\n\
+             content-type;host;x-amz-date\n{}",
+            EMPTY_HASH
+        );
+        let signature = signature(
+            &canonical_request,
+            SECRET_KEY,
+            "20150830/us-east-1/iam/aws4_request",
+            time(),
+            "us-east-1",
+            "iam",
+        )
+        .unwrap();
+
+        assert_eq!(
+            signature,
+            "5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7"
+        );
+    }
+
+    // get-vanilla-query-order-key-case and get-vanilla-query-unreserved of the test suite
+    #[test]
+    fn canonical_query_is_sorted_and_encoded() {
+        assert_eq!(
+            canonical_query(&url(
+                "https://example.amazonaws.com/?Param2=value2&Param1=value1"
+            )),
+            "Param1=value1&Param2=value2"
+        );
+        assert_eq!(
+            canonical_query(&url(
+                "https://example.amazonaws.com/?-._~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz=-._~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+            )),
+            "-._~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz=-._~0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz"
+        );
+        assert_eq!(
+            canonical_query(&url("https://example.amazonaws.com/?a=b c&b=/")),
+            "a=b%20c&b=%2F"
+        );
+    }
+
+    #[test]
+    fn canonical_uri_encodes_segments_again_except_for_s3() {
+        let url = url("https://example.amazonaws.com/a b/c");
+        assert_eq!(canonical_uri(&url, "execute-api"), "/a%2520b/c");
+        assert_eq!(canonical_uri(&url, "s3"), "/a%20b/c");
+    }
+
+    #[test]
+    fn signs_the_request_headers() {
+        let mut request =
+            reqwest::Request::new(reqwest::Method::GET, url("https://example.amazonaws.com/"));
+        sign_sigv4(
+            &mut request,
+            ACCESS_KEY,
+            SECRET_KEY,
+            Some("token"),
+            "us-east-1",
+            "service",
+            time(),
+        )
+        .unwrap();
+
+        let headers = request.headers();
+        assert_eq!(headers["x-amz-date"], "20150830T123600Z");
+        assert_eq!(headers["x-amz-content-sha256"], EMPTY_HASH);
+        assert_eq!(headers["x-amz-security-token"], "token");
+        assert!(
+            headers["authorization"]
+                .to_str()
+                .unwrap()
+                .starts_with(
+                    "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+                     SignedHeaders=host;x-amz-content-sha256;x-amz-date;x-amz-security-token, Signature="
+                )
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::Context;
+
+/// The binary formats bodies can be encoded in, decoded into the same values as json.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryFormat {
+    Msgpack,
+    Cbor,
+}
+
+impl BinaryFormat {
+    // The format of a body from its content type, eg: application/msgpack, application/vnd.api+cbor
+    pub fn from_content_type(content_type: &str) -> Option<BinaryFormat> {
+        let mime = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase();
+
+        if mime.ends_with("msgpack") || mime.ends_with("messagepack") {
+            return Some(BinaryFormat::Msgpack);
+        }
+        if mime.ends_with("cbor") {
+            return Some(BinaryFormat::Cbor);
+        }
+
+        return None;
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        return match self {
+            BinaryFormat::Msgpack => "application/msgpack",
+            BinaryFormat::Cbor => "application/cbor",
+        };
+    }
+
+    pub fn encode(&self, value: &serde_yaml::Value) -> anyhow::Result<Vec<u8>> {
+        return match self {
+            BinaryFormat::Msgpack => {
+                rmp_serde::to_vec_named(value).context("Failed to encode the body as MessagePack")
+            }
+            BinaryFormat::Cbor => {
+                let mut bytes = vec![];
+                ciborium::into_writer(value, &mut bytes)
+                    .context("Failed to encode the body as CBOR")?;
+                Ok(bytes)
+            }
+        };
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> anyhow::Result<serde_json::Value> {
+        return match self {
+            BinaryFormat::Msgpack => {
+                rmp_serde::from_slice(bytes).context("Response body is not valid MessagePack")
+            }
+            BinaryFormat::Cbor => {
+                ciborium::from_reader(bytes).context("Response body is not valid CBOR")
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_are_found_from_the_content_type() {
+        for (content_type, format) in [
+            ("application/msgpack", Some(BinaryFormat::Msgpack)),
+            (
+                "application/x-msgpack; charset=binary",
+                Some(BinaryFormat::Msgpack),
+            ),
+            ("application/vnd.messagepack", Some(BinaryFormat::Msgpack)),
+            ("Application/CBOR", Some(BinaryFormat::Cbor)),
+            ("application/vnd.api+cbor", Some(BinaryFormat::Cbor)),
+            ("application/json", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                BinaryFormat::from_content_type(content_type),
+                format,
+                "{}",
+                content_type
+            );
+        }
+    }
+
+    #[test]
+    fn values_decode_to_the_json_they_were_encoded_from() {
+        let value = serde_yaml::from_str::<serde_yaml::Value>(
+            "{id: 7, name: a, tags: [x, y], admin: false, score: 1.5, parent: null}",
+        )
+        .unwrap();
+
+        for format in [BinaryFormat::Msgpack, BinaryFormat::Cbor] {
+            let bytes = format.encode(&value).unwrap();
+            assert_eq!(
+                format.decode(&bytes).unwrap(),
+                serde_json::json!({
+                    "id": 7, "name": "a", "tags": ["x", "y"], "admin": false, "score": 1.5, "parent": null
+                }),
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[test]
+    fn maps_are_encoded_with_their_keys() {
+        let value = serde_yaml::from_str::<serde_yaml::Value>("{a: 1}").unwrap();
+
+        // a fixmap of one entry, "a" as a fixstr, 1 as a positive fixint
+        assert_eq!(
+            BinaryFormat::Msgpack.encode(&value).unwrap(),
+            vec![0x81, 0xa1, b'a', 0x01]
+        );
+        // a map of one entry, "a" as a text string, 1 as an unsigned integer
+        assert_eq!(
+            BinaryFormat::Cbor.encode(&value).unwrap(),
+            vec![0xa1, 0x61, b'a', 0x01]
+        );
+    }
+
+    #[test]
+    fn invalid_bodies_fail_to_decode() {
+        assert_eq!(
+            BinaryFormat::Msgpack
+                .decode(&[0xc1])
+                .unwrap_err()
+                .to_string(),
+            "Response body is not valid MessagePack"
+        );
+        assert_eq!(
+            BinaryFormat::Cbor.decode(&[0xff]).unwrap_err().to_string(),
+            "Response body is not valid CBOR"
+        );
+    }
+}
@@ -0,0 +1,566 @@
+use std::{ops::Range, path::Path};
+
+use anyhow::Context;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+use super::{BodyFuture, BodySerializer, binary::BinaryFormat, ndjson};
+use crate::{
+    graphql::graphql_payload,
+    runner::{ResolvedRequest, soap_envelope},
+    schema::{
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        soap::SoapVersionSchema,
+    },
+};
+
+pub(super) fn serializers() -> Vec<Box<dyn BodySerializer>> {
+    return vec![
+        Box::new(JsonBody),
+        Box::new(GraphqlBody),
+        Box::new(XmlBody),
+        Box::new(SoapBody),
+        Box::new(TextBody),
+        Box::new(FormUrlencodedBody),
+        Box::new(MultipartBody),
+        Box::new(BinaryBody),
+        Box::new(EncodedBody(BinaryFormat::Msgpack)),
+        Box::new(EncodedBody(BinaryFormat::Cbor)),
+        Box::new(NdjsonBody),
+    ];
+}
+
+// A body of a built-in type whose fields didn't match the type
+fn invalid(body: &RequestBodySchema) -> anyhow::Error {
+    return anyhow::anyhow!("Invalid {} body, check its fields", body.body_type());
+}
+
+struct JsonBody;
+
+impl BodySerializer for JsonBody {
+    fn body_type(&self) -> &str {
+        return "json";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        _resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::Json { content, .. } => Ok(builder.json(content)),
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+struct GraphqlBody;
+
+impl BodySerializer for GraphqlBody {
+    fn body_type(&self) -> &str {
+        return "graphql";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        _resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::Graphql {
+                    query,
+                    variables,
+                    persisted,
+                    ..
+                } => Ok(builder.json(&graphql_payload(query, variables, *persisted, false))),
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+struct XmlBody;
+
+impl BodySerializer for XmlBody {
+    fn body_type(&self) -> &str {
+        return "xml";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::Xml { content, .. } => {
+                    Ok(with_content_type(builder, resolved, "application/xml")
+                        .body(content.clone()))
+                }
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+struct SoapBody;
+
+impl BodySerializer for SoapBody {
+    fn body_type(&self) -> &str {
+        return "soap";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        mut builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            let (content, header, action, version) = match body {
+                RequestBodySchema::Soap {
+                    content,
+                    header,
+                    action,
+                    version,
+                    ..
+                } => (content, header, action, version),
+                _ => return Err(invalid(body)),
+            };
+
+            if let (SoapVersionSchema::V11, Some(action), None) =
+                (version, action, resolved.get_header("soapaction"))
+            {
+                builder = builder.header("SOAPAction", format!("\"{}\"", action));
+            }
+
+            let content_type = version.content_type(action.as_deref());
+            return Ok(
+                with_content_type(builder, resolved, &content_type).body(soap_envelope(
+                    content,
+                    header.as_deref(),
+                    *version,
+                )),
+            );
+        });
+    }
+}
+
+struct TextBody;
+
+impl BodySerializer for TextBody {
+    fn body_type(&self) -> &str {
+        return "text";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::Text { content, .. } => {
+                    Ok(with_content_type(builder, resolved, "text/plain").body(content.clone()))
+                }
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+struct FormUrlencodedBody;
+
+impl BodySerializer for FormUrlencodedBody {
+    fn body_type(&self) -> &str {
+        return "form-urlencoded";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::FormUrlencoded { content, .. } => {
+                    Ok(
+                        with_content_type(builder, resolved, "application/x-www-form-urlencoded")
+                            .body(content.clone()),
+                    )
+                }
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+struct MultipartBody;
+
+impl BodySerializer for MultipartBody {
+    fn body_type(&self) -> &str {
+        return "multipart";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::Multipart { parts } => {
+                    Ok(builder.multipart(build_multipart(parts, &resolved.base_dir).await?))
+                }
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+struct BinaryBody;
+
+impl BodySerializer for BinaryBody {
+    fn body_type(&self) -> &str {
+        return "binary";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            let path = match body {
+                RequestBodySchema::Binary { path, .. } => resolved.base_dir.join(path),
+                _ => return Err(invalid(body)),
+            };
+
+            let content = match &resolved.chunk {
+                Some(chunk) => read_chunk(&path, chunk.clone()).await,
+                None => tokio::fs::read(&path).await,
+            }
+            .with_context(|| format!("Failed to read body file {:?}", &path))?;
+
+            let content_type = resolved.content_type().unwrap_or_default();
+            return Ok(with_content_type(builder, resolved, &content_type).body(content));
+        });
+    }
+}
+
+// A yaml value encoded in a binary format
+struct EncodedBody(BinaryFormat);
+
+impl BodySerializer for EncodedBody {
+    fn body_type(&self) -> &str {
+        return match self.0 {
+            BinaryFormat::Msgpack => "msgpack",
+            BinaryFormat::Cbor => "cbor",
+        };
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            let content = match (self.0, body) {
+                (BinaryFormat::Msgpack, RequestBodySchema::Msgpack { content, .. })
+                | (BinaryFormat::Cbor, RequestBodySchema::Cbor { content, .. }) => content,
+                _ => return Err(invalid(body)),
+            };
+
+            let bytes = self.0.encode(content)?;
+            return Ok(with_content_type(builder, resolved, self.0.content_type()).body(bytes));
+        });
+    }
+}
+
+struct NdjsonBody;
+
+impl BodySerializer for NdjsonBody {
+    fn body_type(&self) -> &str {
+        return "ndjson";
+    }
+
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a> {
+        return Box::pin(async move {
+            return match body {
+                RequestBodySchema::Ndjson { content, .. } => {
+                    let bytes = ndjson::encode(content)?;
+                    Ok(
+                        with_content_type(builder, resolved, ndjson::NDJSON_CONTENT_TYPE)
+                            .body(bytes),
+                    )
+                }
+                _ => Err(invalid(body)),
+            };
+        });
+    }
+}
+
+// The bytes of a file in the range, for the chunks of an upload
+async fn read_chunk(path: &Path, chunk: Range<u64>) -> std::io::Result<Vec<u8>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    file.seek(std::io::SeekFrom::Start(chunk.start)).await?;
+
+    let mut content = vec![0; (chunk.end - chunk.start) as usize];
+    file.read_exact(&mut content).await?;
+    return Ok(content);
+}
+
+// Sets the content type, unless the request sets one in its headers
+pub fn with_content_type(
+    builder: reqwest::RequestBuilder,
+    resolved: &ResolvedRequest,
+    content_type: &str,
+) -> reqwest::RequestBuilder {
+    if resolved.get_header("content-type").is_some() {
+        return builder;
+    }
+
+    return builder.header(reqwest::header::CONTENT_TYPE, content_type);
+}
+
+async fn build_multipart(
+    parts: &Vec<MultipartPartSchema>,
+    base_dir: &Path,
+) -> anyhow::Result<reqwest::multipart::Form> {
+    let mut form = reqwest::multipart::Form::new();
+
+    for part in parts {
+        form = match part {
+            MultipartPartSchema::Field { name, value } => form.text(name.clone(), value.clone()),
+            MultipartPartSchema::File {
+                name,
+                path,
+                mime_type,
+            } => {
+                let path = base_dir.join(path);
+                let content = tokio::fs::read(&path)
+                    .await
+                    .with_context(|| format!("Failed to read multipart file {:?}", &path))?;
+
+                let mut file_part = reqwest::multipart::Part::bytes(content);
+                if let Some(file_name) = path.file_name() {
+                    file_part = file_part.file_name(file_name.to_string_lossy().to_string());
+                }
+                if let Some(mime_type) = mime_type {
+                    file_part = file_part.mime_str(mime_type)?;
+                }
+
+                form.part(name.clone(), file_part)
+            }
+        };
+    }
+
+    return Ok(form);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(headers: &[(&str, &str)], base_dir: &Path) -> ResolvedRequest {
+        return ResolvedRequest {
+            name: "a".to_string(),
+            method: "POST".to_string(),
+            url: "https://x.dev/a".to_string(),
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: None,
+            config: Default::default(),
+            auth: None,
+            base_dir: base_dir.to_path_buf(),
+            warnings: vec![],
+            chunk: None,
+        };
+    }
+
+    // Applies the built-in serializer of the body's type, returning the request it builds
+    async fn build(body: &str, resolved: &ResolvedRequest) -> anyhow::Result<reqwest::Request> {
+        let body = serde_yaml::from_str::<RequestBodySchema>(body).unwrap();
+        let serializer = serializers()
+            .into_iter()
+            .find(|serializer| serializer.body_type() == body.body_type())
+            .unwrap();
+
+        let builder = reqwest::Client::new().post(&resolved.url);
+        return Ok(serializer.apply(builder, &body, resolved).await?.build()?);
+    }
+
+    fn content_type(request: &reqwest::Request) -> &str {
+        return request
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap())
+            .unwrap_or_default();
+    }
+
+    fn body(request: &reqwest::Request) -> &[u8] {
+        return request.body().and_then(|body| body.as_bytes()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn text_bodies_are_sent_with_their_content_type() {
+        let resolved = resolved(&[], Path::new("."));
+
+        let request = build("{type: json, content: {a: [1, true]}}", &resolved)
+            .await
+            .unwrap();
+        assert_eq!(content_type(&request), "application/json");
+        assert_eq!(body(&request), b"{\"a\":[1,true]}");
+
+        let request = build("{type: xml, content: <a/>}", &resolved)
+            .await
+            .unwrap();
+        assert_eq!(content_type(&request), "application/xml");
+        assert_eq!(body(&request), b"<a/>");
+
+        let request = build("{type: text, content: hi}", &resolved).await.unwrap();
+        assert_eq!(content_type(&request), "text/plain");
+        assert_eq!(body(&request), b"hi");
+
+        let request = build("{type: form-urlencoded, content: a=1&b=2}", &resolved)
+            .await
+            .unwrap();
+        assert_eq!(content_type(&request), "application/x-www-form-urlencoded");
+        assert_eq!(body(&request), b"a=1&b=2");
+
+        let request = build("{type: ndjson, content: [{a: 1}, {a: 2}]}", &resolved)
+            .await
+            .unwrap();
+        assert_eq!(content_type(&request), ndjson::NDJSON_CONTENT_TYPE);
+        assert_eq!(body(&request), b"{\"a\":1}\n{\"a\":2}\n");
+    }
+
+    #[tokio::test]
+    async fn the_content_type_of_the_request_is_kept() {
+        let resolved = resolved(&[("Content-Type", "text/csv")], Path::new("."));
+        let request = build("{type: text, content: \"a,b\"}", &resolved)
+            .await
+            .unwrap();
+
+        // the header itself is set by the runner, the serializer leaves it out
+        assert_eq!(content_type(&request), "");
+        assert_eq!(body(&request), b"a,b");
+    }
+
+    #[tokio::test]
+    async fn graphql_bodies_are_sent_as_json() {
+        let resolved = resolved(&[], Path::new("."));
+        let request = build(
+            "{type: graphql, query: \"{ me { id } }\", variables: {id: 1}}",
+            &resolved,
+        )
+        .await
+        .unwrap();
+
+        let payload = serde_json::from_slice::<serde_json::Value>(body(&request)).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({ "query": "{ me { id } }", "variables": { "id": 1 } })
+        );
+    }
+
+    #[tokio::test]
+    async fn soap_bodies_are_wrapped_in_an_envelope() {
+        let resolved = resolved(&[], Path::new("."));
+        let request = build(
+            "{type: soap, content: <Ping/>, action: urn:ping}",
+            &resolved,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(content_type(&request), "text/xml; charset=utf-8");
+        assert_eq!(request.headers()["SOAPAction"], "\"urn:ping\"");
+        let envelope = String::from_utf8(body(&request).to_vec()).unwrap();
+        assert!(envelope.contains("<Ping/>"));
+        assert!(envelope.contains("Envelope"));
+    }
+
+    #[tokio::test]
+    async fn encoded_bodies_decode_to_their_content() {
+        let resolved = resolved(&[], Path::new("."));
+
+        for (body_type, format) in [
+            ("msgpack", BinaryFormat::Msgpack),
+            ("cbor", BinaryFormat::Cbor),
+        ] {
+            let request = build(
+                &format!("{{type: {}, content: {{a: 1}}}}", body_type),
+                &resolved,
+            )
+            .await
+            .unwrap();
+            assert_eq!(content_type(&request), format.content_type());
+            assert_eq!(
+                format.decode(body(&request)).unwrap(),
+                serde_json::json!({ "a": 1 })
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn binary_bodies_read_the_file_or_a_chunk_of_it() {
+        let dir = std::env::temp_dir().join(format!("dotapi-body-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("file.bin"), "0123456789").unwrap();
+
+        let mut resolved = resolved(&[], &dir);
+        let body_yaml = "{type: binary, path: file.bin, content_type: image/png}";
+        let request = build(body_yaml, &resolved).await.unwrap();
+        assert_eq!(body(&request), b"0123456789");
+
+        resolved.chunk = Some(4..8);
+        let request = build(body_yaml, &resolved).await.unwrap();
+        assert_eq!(body(&request), b"4567");
+
+        let error = build("{type: binary, path: missing.bin}", &resolved)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().starts_with("Failed to read body file"));
+    }
+
+    #[tokio::test]
+    async fn multipart_bodies_set_a_boundary() {
+        let resolved = resolved(&[], Path::new("."));
+        let request = build(
+            "{type: multipart, parts: [{kind: field, name: a, value: \"1\"}]}",
+            &resolved,
+        )
+        .await
+        .unwrap();
+
+        assert!(content_type(&request).starts_with("multipart/form-data; boundary="));
+    }
+
+    #[tokio::test]
+    async fn bodies_of_another_type_are_invalid() {
+        let resolved = resolved(&[], Path::new("."));
+        let body = serde_yaml::from_str::<RequestBodySchema>("{type: text, content: a}").unwrap();
+        let builder = reqwest::Client::new().post(&resolved.url);
+
+        let error = JsonBody.apply(builder, &body, &resolved).await.unwrap_err();
+        assert_eq!(error.to_string(), "Invalid text body, check its fields");
+    }
+}
@@ -0,0 +1,115 @@
+use std::{collections::HashMap, future::Future, pin::Pin};
+
+use crate::{
+    runner::{ResolvedRequest, Runner},
+    schema::request_body::RequestBodySchema,
+};
+
+pub mod binary;
+mod builtin;
+pub mod ndjson;
+
+pub use builtin::with_content_type;
+
+pub type BodyFuture<'a> =
+    Pin<Box<dyn Future<Output = anyhow::Result<reqwest::RequestBuilder>> + Send + 'a>>;
+
+/// Sets the bodies of one type on the requests being built, the type being the `type` of the body.
+/// Applications register their own to support new body types, eg: protobuf.
+pub trait BodySerializer: Send + Sync {
+    // eg: json
+    fn body_type(&self) -> &str;
+
+    // sets the body and its content type on the request, the content type set by the request is kept
+    fn apply<'a>(
+        &'a self,
+        builder: reqwest::RequestBuilder,
+        body: &'a RequestBodySchema,
+        resolved: &'a ResolvedRequest,
+    ) -> BodyFuture<'a>;
+}
+
+/// The serializers known to a runner by body type, the built-in ones and the ones registered.
+pub struct BodySerializers {
+    serializers: HashMap<String, Box<dyn BodySerializer>>,
+}
+
+impl Default for BodySerializers {
+    fn default() -> Self {
+        let mut serializers = BodySerializers {
+            serializers: HashMap::new(),
+        };
+
+        for serializer in builtin::serializers() {
+            serializers.register(serializer);
+        }
+
+        return serializers;
+    }
+}
+
+impl BodySerializers {
+    // Replaces the serializer of the same type, if any
+    pub fn register(&mut self, serializer: Box<dyn BodySerializer>) {
+        self.serializers
+            .insert(serializer.body_type().to_string(), serializer);
+    }
+
+    pub fn get(&self, body_type: &str) -> Option<&dyn BodySerializer> {
+        return self
+            .serializers
+            .get(body_type)
+            .map(|serializer| &**serializer);
+    }
+
+    pub fn body_types(&self) -> Vec<&str> {
+        let mut types = self
+            .serializers
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        types.sort();
+        return types;
+    }
+}
+
+impl Runner {
+    pub fn register_body_serializer(&mut self, serializer: Box<dyn BodySerializer>) {
+        self.body_serializers.register(serializer);
+    }
+
+    // Sets the body of the request with the serializer of its type
+    pub(crate) async fn apply_body(
+        &self,
+        builder: reqwest::RequestBuilder,
+        body: &RequestBodySchema,
+        resolved: &ResolvedRequest,
+    ) -> anyhow::Result<reqwest::RequestBuilder> {
+        let body_type = body.body_type();
+
+        return match self.body_serializers.get(body_type) {
+            Some(serializer) => serializer.apply(builder, body, resolved).await,
+            None => anyhow::bail!(
+                "Unknown body type \"{}\", expected one of {}",
+                body_type,
+                self.body_serializers.body_types().join(", ")
+            ),
+        };
+    }
+}
+
+// Reads a streamed body (eg: multipart) into memory, so it can be compressed or hashed as it's sent
+pub(crate) async fn buffer_body(request: &mut reqwest::Request) -> anyhow::Result<()> {
+    let body = match request.body_mut().take() {
+        Some(body) if body.as_bytes().is_none() => body,
+        body => {
+            *request.body_mut() = body;
+            return Ok(());
+        }
+    };
+
+    let bytes = http_body_util::BodyExt::collect(body).await?.to_bytes();
+    *request.body_mut() = Some(bytes.into());
+
+    return Ok(());
+}
@@ -0,0 +1,80 @@
+use anyhow::Context;
+
+use crate::body_limit::TRUNCATED_MARKER;
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+// Newline delimited json, eg: application/x-ndjson, application/jsonl, application/json-lines
+pub fn is_ndjson(content_type: &str) -> bool {
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    return mime.ends_with("ndjson") || mime.ends_with("jsonl") || mime.ends_with("json-lines");
+}
+
+// Each row on its own line, as compact json
+pub fn encode(rows: &[serde_yaml::Value]) -> anyhow::Result<Vec<u8>> {
+    let mut bytes = vec![];
+    for row in rows {
+        serde_json::to_writer(&mut bytes, row).context("Failed to encode a row of the body")?;
+        bytes.push(b'\n');
+    }
+
+    return Ok(bytes);
+}
+
+// The rows of a data file, a line of json per row (.ndjson, .jsonl) or a json or yaml list
+pub fn read_rows(content: &str, path: &str) -> anyhow::Result<Vec<serde_yaml::Value>> {
+    if path.ends_with(".ndjson") || path.ends_with(".jsonl") {
+        return content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                serde_yaml::from_str(line).with_context(|| {
+                    format!(
+                        "Line {} of body file \"{}\" is not valid json",
+                        index + 1,
+                        path
+                    )
+                })
+            })
+            .collect();
+    }
+
+    return serde_yaml::from_str(content)
+        .with_context(|| format!("Body file \"{}\" is not a json or yaml list", path));
+}
+
+// Parses the body line by line, blank lines are skipped.
+// A truncated body keeps its complete lines, the line cut by the limit is dropped.
+pub fn parse_lines(bytes: &[u8], truncated: bool) -> anyhow::Result<Vec<serde_json::Value>> {
+    let mut bytes = bytes;
+    if truncated {
+        bytes = bytes
+            .strip_suffix(TRUNCATED_MARKER.as_bytes())
+            .unwrap_or(bytes);
+        bytes = match bytes.iter().rposition(|b| *b == b'\n') {
+            Some(end) => &bytes[..end],
+            None => &[],
+        };
+    }
+
+    let mut lines = vec![];
+    for (index, line) in bytes.split(|b| *b == b'\n').enumerate() {
+        if line.iter().all(|b| b.is_ascii_whitespace()) {
+            continue;
+        }
+
+        let value = serde_json::from_slice(line).with_context(|| {
+            format!("Line {} of the response body is not valid json", index + 1)
+        })?;
+        lines.push(value);
+    }
+
+    return Ok(lines);
+}
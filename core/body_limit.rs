@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    runner::{ResolvedRequest, Runner},
+    schema::body_limit::{BodyLimitPolicySchema, BodyLimitSchema},
+};
+
+pub const TRUNCATED_MARKER: &str = "\n[truncated by dotapi]";
+
+/// What was done with a response body over the size limit, kept on the response.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BodyOverflow {
+    // the body kept ends with TRUNCATED_MARKER
+    Truncated,
+    // the whole body is in this file
+    File(PathBuf),
+}
+
+/// A response body read within the size limit.
+pub struct LimitedBody {
+    pub bytes: Vec<u8>,
+    // size of the whole body when it was read to the end
+    pub size: usize,
+    pub overflow: Option<BodyOverflow>,
+}
+
+impl Runner {
+    // The limit of the request, the project's when it has none
+    pub(crate) fn body_limit<'a>(
+        &'a self,
+        resolved: &'a ResolvedRequest,
+    ) -> Option<&'a BodyLimitSchema> {
+        return resolved
+            .config
+            .body_limit
+            .as_ref()
+            .or(self.project.object.body_limit.as_ref());
+    }
+}
+
+// Reads the body chunk by chunk, applying the policy of the limit once it's over it
+pub(crate) async fn read_body(
+    mut response: reqwest::Response,
+    limit: Option<&BodyLimitSchema>,
+) -> anyhow::Result<LimitedBody> {
+    let limit = match limit {
+        Some(limit) => limit,
+        None => {
+            let bytes = response.bytes().await?.to_vec();
+            return Ok(LimitedBody {
+                size: bytes.len(),
+                bytes,
+                overflow: None,
+            });
+        }
+    };
+
+    let max_size = limit.max_size as usize;
+    let mut bytes = vec![];
+
+    while let Some(chunk) = response.chunk().await? {
+        if bytes.len() + chunk.len() <= max_size {
+            bytes.extend_from_slice(&chunk);
+            continue;
+        }
+
+        return match limit.policy {
+            BodyLimitPolicySchema::Abort => {
+                anyhow::bail!("Response body is over the limit of {} bytes", max_size)
+            }
+            BodyLimitPolicySchema::Truncate => {
+                bytes.extend_from_slice(&chunk[..max_size - bytes.len()]);
+                let size = bytes.len();
+                bytes.extend_from_slice(TRUNCATED_MARKER.as_bytes());
+                Ok(LimitedBody {
+                    bytes,
+                    size,
+                    overflow: Some(BodyOverflow::Truncated),
+                })
+            }
+            BodyLimitPolicySchema::File => {
+                let (path, size) = write_to_file(&bytes, &chunk, response).await?;
+                bytes.extend_from_slice(&chunk[..max_size - bytes.len()]);
+                Ok(LimitedBody {
+                    bytes,
+                    size,
+                    overflow: Some(BodyOverflow::File(path)),
+                })
+            }
+        };
+    }
+
+    return Ok(LimitedBody {
+        size: bytes.len(),
+        bytes,
+        overflow: None,
+    });
+}
+
+// Writes what was read so far and the rest of the body to a temporary file
+async fn write_to_file(
+    read: &[u8],
+    chunk: &[u8],
+    mut response: reqwest::Response,
+) -> anyhow::Result<(PathBuf, usize)> {
+    let path = std::env::temp_dir().join(format!("dotapi-{}.body", uuid::Uuid::new_v4()));
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .with_context(|| format!("Failed to create {:?}", path))?;
+
+    file.write_all(read).await?;
+    file.write_all(chunk).await?;
+    let mut size = read.len() + chunk.len();
+
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk).await?;
+        size += chunk.len();
+    }
+
+    file.flush().await?;
+    return Ok((path, size));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Serves the body once on a local port and returns the response to it
+    async fn respond_with(body: &'static [u8]) -> reqwest::Response {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut stream, &mut request).await;
+
+            let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n", body.len());
+            stream.write_all(head.as_bytes()).await.unwrap();
+            // in two writes, so the body may come in more than one chunk
+            stream.write_all(&body[..body.len() / 2]).await.unwrap();
+            stream.flush().await.unwrap();
+            stream.write_all(&body[body.len() / 2..]).await.unwrap();
+        });
+
+        return reqwest::get(format!("http://{}", address)).await.unwrap();
+    }
+
+    fn limit(max_size: u64, policy: BodyLimitPolicySchema) -> BodyLimitSchema {
+        return BodyLimitSchema { max_size, policy };
+    }
+
+    #[tokio::test]
+    async fn bodies_within_the_limit_are_kept_whole() {
+        let response = respond_with(b"0123456789").await;
+        let body = read_body(response, Some(&limit(10, BodyLimitPolicySchema::Abort)))
+            .await
+            .unwrap();
+
+        assert_eq!(body.bytes, b"0123456789");
+        assert_eq!(body.size, 10);
+        assert_eq!(body.overflow, None);
+    }
+
+    #[tokio::test]
+    async fn abort_fails_the_request() {
+        let response = respond_with(b"0123456789").await;
+        let error = read_body(response, Some(&limit(4, BodyLimitPolicySchema::Abort)))
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            error.to_string(),
+            "Response body is over the limit of 4 bytes"
+        );
+    }
+
+    #[tokio::test]
+    async fn truncate_keeps_the_start_and_a_marker() {
+        let response = respond_with(b"0123456789").await;
+        let body = read_body(response, Some(&limit(4, BodyLimitPolicySchema::Truncate)))
+            .await
+            .unwrap();
+
+        assert_eq!(body.bytes, [b"0123", TRUNCATED_MARKER.as_bytes()].concat());
+        assert_eq!(body.size, 4);
+        assert_eq!(body.overflow, Some(BodyOverflow::Truncated));
+    }
+
+    #[tokio::test]
+    async fn file_keeps_the_start_and_writes_the_whole_body() {
+        let response = respond_with(b"0123456789").await;
+        let body = read_body(response, Some(&limit(4, BodyLimitPolicySchema::File)))
+            .await
+            .unwrap();
+
+        assert_eq!(body.bytes, b"0123");
+        assert_eq!(body.size, 10);
+        let path = match body.overflow {
+            Some(BodyOverflow::File(path)) => path,
+            overflow => panic!("unexpected overflow {:?}", overflow),
+        };
+        assert_eq!(std::fs::read(&path).unwrap(), b"0123456789");
+        std::fs::remove_file(path).unwrap();
+    }
+}
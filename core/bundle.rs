@@ -0,0 +1,241 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interpolation::EnvMap,
+    runner::{CallResult, Runner},
+    schema::{redaction::RedactionSchema, request_body::RequestBodySchema},
+};
+
+pub const BUNDLE_EXTENSION: &str = "ndbundle";
+
+/// A request and the response it got, saved so it can be shared and opened elsewhere.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bundle {
+    pub name: String,
+    #[serde(default)]
+    pub environment: Option<String>,
+    // seconds since the unix epoch
+    pub created_at: u64,
+    pub request: BundleRequest,
+    pub response: BundleResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleRequest {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub body: Option<RequestBodySchema>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BundleResponse {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+    pub duration_ms: u64,
+}
+
+impl Bundle {
+    // The values of the redacted variables are looked up in env
+    pub fn new(
+        result: &CallResult,
+        environment: Option<String>,
+        redaction: &RedactionSchema,
+        env: &EnvMap,
+    ) -> Bundle {
+        let request = &result.request;
+        let response = &result.response;
+
+        return Bundle {
+            name: request.name.clone(),
+            environment,
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            request: BundleRequest {
+                method: request.method.clone(),
+                url: redaction.redact_values(&request.url, env),
+                headers: request
+                    .headers
+                    .iter()
+                    .map(|(k, v)| {
+                        let value = redaction.redact_values(&redaction.redact_header(k, v), env);
+                        (k.clone(), value)
+                    })
+                    .collect(),
+                body: request
+                    .body
+                    .as_ref()
+                    .map(|body| redaction.redact_request_body(body, env)),
+            },
+            response: BundleResponse {
+                status: response.status,
+                headers: response
+                    .headers
+                    .iter()
+                    .map(|(k, v)| {
+                        let value = redaction.redact_values(&redaction.redact_header(k, v), env);
+                        (k.clone(), value)
+                    })
+                    .collect(),
+                body: redaction.redact_body(&response.text(), env),
+                duration_ms: result.duration.as_millis() as u64,
+            },
+        };
+    }
+
+    pub async fn load(path: &Path) -> anyhow::Result<Bundle> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .context("Failed to open bundle")?;
+
+        return serde_json::from_str::<Bundle>(&content).context("Invalid bundle file");
+    }
+
+    pub async fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let content = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, content)
+            .await
+            .context("Failed to write bundle")?;
+        return Ok(());
+    }
+}
+
+impl Runner {
+    // Bundles the last result of a request called in this run, redacted with the project rules
+    pub fn bundle(&self, name: &str) -> anyhow::Result<Bundle> {
+        return match self.responses.get(name) {
+            Some(result) => Ok(Bundle::new(
+                result,
+                self.environment.clone(),
+                &self.project.object.redact,
+                &self.build_env(),
+            )),
+            None => anyhow::bail!("Request \"{}\" has not been called in this run", name),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        redaction::REDACTED,
+        runner::{ResolvedRequest, ResponseObject},
+        schema::request_config::RequestConfigSchema,
+    };
+
+    fn result() -> CallResult {
+        return CallResult {
+            request: ResolvedRequest {
+                name: "login".to_string(),
+                method: "POST".to_string(),
+                url: "https://api.test/login?key=s3cret-key".to_string(),
+                headers: vec![
+                    ("Authorization".to_string(), "Bearer abc".to_string()),
+                    ("X-Trace".to_string(), "s3cret-key".to_string()),
+                    ("Accept".to_string(), "application/json".to_string()),
+                ],
+                body: Some(RequestBodySchema::Json {
+                    content: serde_yaml::from_str(
+                        "{user: admin, password: hunter2, key: s3cret-key}",
+                    )
+                    .unwrap(),
+                    content_file: None,
+                }),
+                config: RequestConfigSchema::default(),
+                auth: None,
+                base_dir: Default::default(),
+                warnings: vec![],
+                chunk: None,
+            },
+            response: ResponseObject {
+                status: 200,
+                headers: HashMap::from([
+                    ("set-cookie".to_string(), "session=1".to_string()),
+                    ("content-type".to_string(), "application/json".to_string()),
+                ]),
+                body: br#"{"token":"t0ken","user":"admin"}"#.to_vec(),
+                ..Default::default()
+            },
+            duration: Duration::from_millis(42),
+            started: chrono::Utc::now(),
+        };
+    }
+
+    fn redaction() -> RedactionSchema {
+        return RedactionSchema {
+            variables: vec!["key".to_string()],
+            paths: vec!["$.token".to_string()],
+            ..Default::default()
+        };
+    }
+
+    fn env() -> EnvMap {
+        return EnvMap::from([("key".to_string(), serde_yaml::Value::from("s3cret-key"))]);
+    }
+
+    #[test]
+    fn headers_are_redacted_by_name_and_value() {
+        let bundle = Bundle::new(&result(), Some("staging".to_string()), &redaction(), &env());
+
+        assert_eq!(
+            bundle.request.headers,
+            vec![
+                ("Authorization".to_string(), REDACTED.to_string()),
+                ("X-Trace".to_string(), REDACTED.to_string()),
+                ("Accept".to_string(), "application/json".to_string()),
+            ]
+        );
+        assert_eq!(bundle.response.headers["set-cookie"], REDACTED);
+        assert_eq!(bundle.response.headers["content-type"], "application/json");
+        assert_eq!(
+            bundle.request.url,
+            format!("https://api.test/login?key={}", REDACTED)
+        );
+        assert_eq!(bundle.environment.as_deref(), Some("staging"));
+        assert_eq!(bundle.response.duration_ms, 42);
+    }
+
+    #[test]
+    fn bodies_are_redacted_by_path_and_value() {
+        let bundle = Bundle::new(&result(), None, &redaction(), &env());
+
+        let body = serde_yaml::to_string(&bundle.request.body).unwrap();
+        assert!(!body.contains("s3cret-key"));
+        assert!(body.contains(REDACTED));
+        assert!(body.contains("hunter2"));
+
+        let response = serde_json::from_str::<serde_json::Value>(&bundle.response.body).unwrap();
+        assert_eq!(response["token"], REDACTED);
+        assert_eq!(response["user"], "admin");
+    }
+
+    #[tokio::test]
+    async fn bundles_load_as_saved() {
+        let bundle = Bundle::new(&result(), None, &redaction(), &env());
+        let path = std::env::temp_dir().join(format!(
+            "dotapi-bundle-{}.{}",
+            uuid::Uuid::new_v4(),
+            BUNDLE_EXTENSION
+        ));
+
+        bundle.save(&path).await.unwrap();
+        assert_eq!(Bundle::load(&path).await.unwrap(), bundle);
+    }
+}
@@ -0,0 +1,497 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use crate::runner::{ResolvedRequest, ResponseObject, Runner};
+
+pub const CACHE_HIT: &str = "HIT";
+pub const CACHE_MISS: &str = "MISS";
+pub const CACHE_REVALIDATED: &str = "REVALIDATED";
+
+// Headers carrying credentials, a response to one user isn't reused for another
+const CREDENTIAL_HEADERS: &[&str] = &["authorization", "proxy-authorization", "cookie"];
+
+struct CacheEntry {
+    response: ResponseObject,
+    stored: Instant,
+    max_age: Option<Duration>,
+    no_cache: bool,
+    // the request headers named by the response's Vary and their values, None for `Vary: *`
+    vary: Option<Vec<(String, Option<String>)>>,
+}
+
+impl CacheEntry {
+    fn is_fresh(&self) -> bool {
+        return !self.no_cache && self.max_age.is_some_and(|age| self.stored.elapsed() < age);
+    }
+
+    // Whether the response was stored for a request with the same values of the Vary headers
+    fn matches(&self, headers: &[(String, String)]) -> bool {
+        return match &self.vary {
+            Some(vary) => vary
+                .iter()
+                .all(|(name, value)| header(headers, name) == value.as_deref()),
+            None => false,
+        };
+    }
+}
+
+/// Responses to GET requests kept for a run, following their cache-control and vary headers.
+///
+/// Fresh responses are returned without sending the request. Stale ones are revalidated
+/// with If-None-Match / If-Modified-Since, and a 304 returns the cached response.
+/// A response is only reused for a request with the same values of the headers its Vary names.
+#[derive(Default)]
+pub struct HttpCache {
+    entries: Mutex<HashMap<String, CacheEntry>>,
+}
+
+impl HttpCache {
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+
+    // The cached response if it can be used without asking the server
+    pub fn fresh(&self, key: &str, headers: &[(String, String)]) -> Option<ResponseObject> {
+        let entries = self.entries.lock().ok()?;
+        let entry = entries
+            .get(key)
+            .filter(|entry| entry.is_fresh() && entry.matches(headers))?;
+
+        let mut response = entry.response.clone();
+        response.cache = Some(CACHE_HIT.to_string());
+        return Some(response);
+    }
+
+    // Adds the validators of a stale cached response, so the server can answer 304
+    pub fn add_validators(
+        &self,
+        key: &str,
+        headers: &[(String, String)],
+        request: &mut reqwest::Request,
+    ) {
+        let entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return,
+        };
+
+        let response = match entries.get(key).filter(|entry| entry.matches(headers)) {
+            Some(entry) => &entry.response,
+            None => return,
+        };
+
+        let headers = request.headers_mut();
+        if let Some(etag) = response.headers.get("etag").and_then(|v| v.parse().ok()) {
+            headers.insert(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = response
+            .headers
+            .get("last-modified")
+            .and_then(|v| v.parse().ok())
+        {
+            headers.insert(reqwest::header::IF_MODIFIED_SINCE, modified);
+        }
+    }
+
+    // Stores a response from the server, or answers a 304 with the cached response
+    pub fn store(
+        &self,
+        key: &str,
+        headers: &[(String, String)],
+        mut response: ResponseObject,
+    ) -> ResponseObject {
+        let mut entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return response,
+        };
+
+        if response.status == 304
+            && let Some(entry) = entries.get_mut(key).filter(|entry| entry.matches(headers))
+        {
+            let directives = CacheControl::parse(response.headers.get("cache-control"));
+            entry.stored = Instant::now();
+            entry.max_age = directives.max_age.or(entry.max_age);
+
+            let mut cached = entry.response.clone();
+            cached.cache = Some(CACHE_REVALIDATED.to_string());
+            return cached;
+        }
+
+        response.cache = Some(CACHE_MISS.to_string());
+
+        let directives = CacheControl::parse(response.headers.get("cache-control"));
+        let validated =
+            response.headers.contains_key("etag") || response.headers.contains_key("last-modified");
+
+        if directives.no_store || response.status != 200 {
+            entries.remove(key);
+            return response;
+        }
+
+        // nothing to reuse the response with: no freshness and no validators
+        if directives.max_age.is_none() && !validated {
+            return response;
+        }
+
+        entries.insert(
+            key.to_string(),
+            CacheEntry {
+                response: response.clone(),
+                stored: Instant::now(),
+                max_age: directives.max_age,
+                no_cache: directives.no_cache,
+                vary: vary(response.headers.get("vary"), headers),
+            },
+        );
+
+        return response;
+    }
+}
+
+#[derive(Default)]
+struct CacheControl {
+    max_age: Option<Duration>,
+    no_cache: bool,
+    no_store: bool,
+}
+
+impl CacheControl {
+    fn parse(header: Option<&String>) -> CacheControl {
+        let mut result = CacheControl::default();
+
+        for directive in header.map(|h| h.as_str()).unwrap_or_default().split(',') {
+            let (name, value) = match directive.trim().split_once('=') {
+                Some((name, value)) => (name.trim(), Some(value.trim().trim_matches('"'))),
+                None => (directive.trim(), None),
+            };
+
+            match name.to_lowercase().as_str() {
+                "max-age" => {
+                    result.max_age = value
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .map(Duration::from_secs)
+                }
+                "no-cache" => result.no_cache = true,
+                "no-store" => result.no_store = true,
+                _ => {}
+            };
+        }
+
+        return result;
+    }
+}
+
+// The request headers named by a Vary header with their values, None when it's `*`
+fn vary(
+    header: Option<&String>,
+    headers: &[(String, String)],
+) -> Option<Vec<(String, Option<String>)>> {
+    let mut result = vec![];
+
+    for name in header.map(|h| h.as_str()).unwrap_or_default().split(',') {
+        let name = name.trim().to_lowercase();
+        match name.as_str() {
+            "" => continue,
+            "*" => return None,
+            _ => {
+                let value = self::header(headers, &name).map(|value| value.to_string());
+                result.push((name, value));
+            }
+        };
+    }
+
+    return Some(result);
+}
+
+fn header<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    return headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str());
+}
+
+impl Runner {
+    // Requests that go through the cache are keyed by method, url and credentials, only GET is cached.
+    // Requests signed by their auth (digest, sigv4, ...) differ on each call and aren't cached.
+    // Ranged requests aren't either, their partial bodies aren't the response to the url.
+    pub(crate) fn cache_key(&self, resolved: &ResolvedRequest) -> Option<String> {
+        let enabled = resolved.config.cache.unwrap_or(self.project.object.cache);
+        if !enabled
+            || !resolved.method.eq_ignore_ascii_case("GET")
+            || resolved.auth.is_some()
+            || header(&resolved.headers, "range").is_some()
+        {
+            return None;
+        }
+
+        let mut key = format!("GET {}", resolved.url);
+        for name in CREDENTIAL_HEADERS {
+            if let Some(value) = header(&resolved.headers, name) {
+                key.push_str(&format!("\n{}: {}", name, value));
+            }
+        }
+
+        return Some(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: u16, headers: &[(&str, &str)]) -> ResponseObject {
+        return ResponseObject {
+            status,
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: b"cached".to_vec(),
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn parses_cache_control() {
+        let header = "public, max-age=\"60\", No-Cache".to_string();
+        let directives = CacheControl::parse(Some(&header));
+        assert_eq!(directives.max_age, Some(Duration::from_secs(60)));
+        assert!(directives.no_cache);
+        assert!(!directives.no_store);
+
+        assert!(CacheControl::parse(Some(&"no-store".to_string())).no_store);
+        assert!(CacheControl::parse(None).max_age.is_none());
+    }
+
+    #[test]
+    fn fresh_responses_are_hits() {
+        let cache = HttpCache::default();
+        let stored = cache.store(
+            "GET /a",
+            &[],
+            response(200, &[("cache-control", "max-age=60")]),
+        );
+        assert_eq!(stored.cache.as_deref(), Some(CACHE_MISS));
+
+        let hit = cache.fresh("GET /a", &[]).unwrap();
+        assert_eq!(hit.cache.as_deref(), Some(CACHE_HIT));
+        assert_eq!(hit.body, b"cached");
+        assert!(cache.fresh("GET /b", &[]).is_none());
+
+        cache.clear();
+        assert!(cache.fresh("GET /a", &[]).is_none());
+    }
+
+    #[test]
+    fn no_cache_no_store_and_errors_are_not_fresh() {
+        let cache = HttpCache::default();
+        cache.store(
+            "GET /a",
+            &[],
+            response(200, &[("cache-control", "no-cache"), ("etag", "\"1\"")]),
+        );
+        assert!(cache.fresh("GET /a", &[]).is_none());
+
+        cache.store(
+            "GET /b",
+            &[],
+            response(200, &[("cache-control", "max-age=60, no-store")]),
+        );
+        assert!(cache.fresh("GET /b", &[]).is_none());
+
+        cache.store(
+            "GET /c",
+            &[],
+            response(500, &[("cache-control", "max-age=60")]),
+        );
+        assert!(cache.fresh("GET /c", &[]).is_none());
+    }
+
+    #[test]
+    fn stale_responses_are_revalidated() {
+        let cache = HttpCache::default();
+        cache.store(
+            "GET /a",
+            &[],
+            response(
+                200,
+                &[
+                    ("etag", "\"1\""),
+                    ("last-modified", "Wed, 21 Oct 2015 07:28:00 GMT"),
+                ],
+            ),
+        );
+
+        let mut request = reqwest::Request::new(
+            reqwest::Method::GET,
+            reqwest::Url::parse("http://localhost/a").unwrap(),
+        );
+        cache.add_validators("GET /a", &[], &mut request);
+        assert_eq!(request.headers()["if-none-match"], "\"1\"");
+        assert_eq!(
+            request.headers()["if-modified-since"],
+            "Wed, 21 Oct 2015 07:28:00 GMT"
+        );
+
+        let revalidated = cache.store("GET /a", &[], response(304, &[]));
+        assert_eq!(revalidated.status, 200);
+        assert_eq!(revalidated.body, b"cached");
+        assert_eq!(revalidated.cache.as_deref(), Some(CACHE_REVALIDATED));
+    }
+
+    fn headers(headers: &[(&str, &str)]) -> Vec<(String, String)> {
+        return headers
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+    }
+
+    #[test]
+    fn responses_are_reused_for_the_same_vary_headers() {
+        let cache = HttpCache::default();
+        let english = headers(&[("Accept-Language", "en")]);
+        cache.store(
+            "GET /a",
+            &english,
+            response(
+                200,
+                &[
+                    ("cache-control", "max-age=60"),
+                    ("vary", "accept-language, Accept"),
+                ],
+            ),
+        );
+
+        assert!(cache.fresh("GET /a", &english).is_some());
+        assert!(
+            cache
+                .fresh("GET /a", &headers(&[("accept-language", "en")]))
+                .is_some()
+        );
+        assert!(
+            cache
+                .fresh("GET /a", &headers(&[("Accept-Language", "fr")]))
+                .is_none()
+        );
+        assert!(
+            cache
+                .fresh(
+                    "GET /a",
+                    &headers(&[("Accept-Language", "en"), ("Accept", "text/html")])
+                )
+                .is_none()
+        );
+
+        cache.store(
+            "GET /b",
+            &english,
+            response(200, &[("cache-control", "max-age=60"), ("vary", "*")]),
+        );
+        assert!(cache.fresh("GET /b", &english).is_none());
+    }
+
+    #[test]
+    fn stale_responses_for_other_vary_headers_are_not_revalidated() {
+        let cache = HttpCache::default();
+        cache.store(
+            "GET /a",
+            &headers(&[("Accept", "text/html")]),
+            response(200, &[("etag", "\"1\""), ("vary", "accept")]),
+        );
+
+        let json = headers(&[("Accept", "application/json")]);
+        let mut request = reqwest::Request::new(
+            reqwest::Method::GET,
+            reqwest::Url::parse("http://localhost/a").unwrap(),
+        );
+        cache.add_validators("GET /a", &json, &mut request);
+        assert!(request.headers().get("if-none-match").is_none());
+
+        let response = cache.store("GET /a", &json, response(304, &[]));
+        assert_eq!(response.status, 304);
+    }
+
+    fn resolved(headers: &[(&str, &str)]) -> ResolvedRequest {
+        return ResolvedRequest {
+            name: "a".to_string(),
+            method: "GET".to_string(),
+            url: "https://x.dev/a".to_string(),
+            headers: self::headers(headers),
+            body: None,
+            config: Default::default(),
+            auth: None,
+            base_dir: Default::default(),
+            warnings: vec![],
+            chunk: None,
+        };
+    }
+
+    #[test]
+    fn credentials_are_part_of_the_key() {
+        let runner = Runner::new(
+            crate::fs::FileObject::new(
+                "/tmp/dotapi-cache/.nd-project".into(),
+                crate::schema::roots::ProjectRootSchema {
+                    cache: true,
+                    ..Default::default()
+                },
+            ),
+            vec![],
+        )
+        .unwrap();
+
+        let anonymous = runner.cache_key(&resolved(&[("Accept", "*/*")])).unwrap();
+        let alice = runner
+            .cache_key(&resolved(&[("Authorization", "Bearer alice")]))
+            .unwrap();
+        let bob = runner
+            .cache_key(&resolved(&[
+                ("authorization", "Bearer bob"),
+                ("Cookie", "a=1"),
+            ]))
+            .unwrap();
+        assert_eq!(anonymous, "GET https://x.dev/a");
+        assert_ne!(alice, anonymous);
+        assert_ne!(alice, bob);
+
+        let mut signed = resolved(&[]);
+        signed.auth =
+            Some(serde_yaml::from_str("{type: digest, username: a, password: b}").unwrap());
+        assert!(runner.cache_key(&signed).is_none());
+
+        let mut post = resolved(&[]);
+        post.method = "POST".to_string();
+        assert!(runner.cache_key(&post).is_none());
+    }
+
+    #[test]
+    fn ranged_requests_are_not_cached() {
+        let runner = Runner::new(
+            crate::fs::FileObject::new(
+                "/tmp/dotapi-cache/.nd-project".into(),
+                crate::schema::roots::ProjectRootSchema {
+                    cache: true,
+                    ..Default::default()
+                },
+            ),
+            vec![],
+        )
+        .unwrap();
+
+        assert!(
+            runner
+                .cache_key(&resolved(&[("Range", "bytes=0-99")]))
+                .is_none()
+        );
+        assert!(
+            runner
+                .cache_key(&resolved(&[("range", "bytes=100-")]))
+                .is_none()
+        );
+        assert!(runner.cache_key(&resolved(&[])).is_some());
+    }
+}
@@ -0,0 +1,217 @@
+use std::{
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use tokio::sync::Notify;
+
+use crate::runner::Runner;
+
+/// Cancels the calls of a runner from another task, like on Ctrl+C or from a cancel button.
+/// Clones share the same state, cancelling one cancels them all.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<CancelState>,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    notify: Notify,
+}
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        return CancelToken::default();
+    }
+
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::SeqCst);
+        self.inner.notify.notify_waiters();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        return self.inner.cancelled.load(Ordering::SeqCst);
+    }
+
+    // Lets calls run again after a cancel
+    pub fn reset(&self) {
+        self.inner.cancelled.store(false, Ordering::SeqCst);
+    }
+
+    // Completes once the token is cancelled
+    pub async fn cancelled(&self) {
+        loop {
+            let notified = self.inner.notify.notified();
+            tokio::pin!(notified);
+            // registered before checking so a cancel in between isn't missed
+            notified.as_mut().enable();
+
+            if self.is_cancelled() {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    // Runs future until it completes or the token is cancelled, dropping it when cancelled.
+    // Dropping a request in flight aborts it.
+    pub async fn run<T>(
+        &self,
+        future: impl Future<Output = anyhow::Result<T>>,
+    ) -> anyhow::Result<T> {
+        if self.is_cancelled() {
+            return Err(CancelledError.into());
+        }
+
+        return tokio::select! {
+            result = future => result,
+            _ = self.cancelled() => Err(CancelledError.into()),
+        };
+    }
+}
+
+/// Returned by the calls stopped through the runner's cancel token.
+#[derive(Debug, Clone)]
+pub struct CancelledError;
+
+impl std::fmt::Display for CancelledError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "Cancelled");
+    }
+}
+
+impl std::error::Error for CancelledError {}
+
+impl Runner {
+    pub fn is_cancelled(&self) -> bool {
+        return self.cancel.is_cancelled();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{fs::FileObject, schema::roots::RequestRootSchema};
+
+    // Answers /ok right away and never answers anything else
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(async move {
+                    let mut request = [0; 4096];
+                    let read = stream.read(&mut request).await.unwrap_or_default();
+                    if !request[..read].starts_with(b"GET /ok ") {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        return;
+                    }
+
+                    let body = "{\"id\": 1}";
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    let _ = stream.write_all(response.as_bytes()).await;
+                });
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    fn runner(url: &str) -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-cancel-{}", uuid::Uuid::new_v4()));
+        let requests = [
+            (
+                "slow",
+                format!("method: GET\nurl: {}/slow\ncapture: {{slow_id: $.id}}", url),
+            ),
+            (
+                "ok",
+                format!("method: GET\nurl: {}/ok\ncapture: {{id: $.id}}", url),
+            ),
+        ];
+
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            requests
+                .iter()
+                .map(|(name, yaml)| {
+                    FileObject::new(
+                        root.join(format!("requests/{}.nd", name)),
+                        serde_yaml::from_str::<RequestRootSchema>(yaml).unwrap(),
+                    )
+                })
+                .collect(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn cancelling_aborts_the_call_in_flight_and_stops_the_run() {
+        let mut runner = runner(&serve().await);
+        let cancel = runner.cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            cancel.cancel();
+        });
+
+        let report = tokio::time::timeout(
+            Duration::from_secs(10),
+            runner.run_requests("main", vec!["slow".to_string(), "ok".to_string()]),
+        )
+        .await
+        .expect("the run wasn't aborted")
+        .unwrap();
+
+        // the call in flight is reported as cancelled and the next one isn't made
+        assert!(report.cancelled);
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.entries[0].name, "slow");
+        assert_eq!(report.entries[0].error.as_deref(), Some("Cancelled"));
+
+        // nothing from the aborted call was kept
+        assert!(runner.sent.contains_key("slow"));
+        assert!(!runner.responses.contains_key("slow"));
+        assert!(!runner.sent.contains_key("ok"));
+        assert!(runner.state.values().is_empty());
+    }
+
+    #[tokio::test]
+    async fn a_reset_token_lets_the_runner_call_again() {
+        let mut runner = runner(&serve().await);
+        runner.cancel.cancel();
+
+        let error = runner.call_request("ok").await.unwrap_err();
+        assert!(error.is::<CancelledError>());
+        assert!(!runner.responses.contains_key("ok"));
+
+        runner.cancel.reset();
+        runner.call_request("ok").await.unwrap();
+        assert_eq!(runner.state.get("id"), Some(&serde_yaml::Value::from(1)));
+    }
+
+    #[tokio::test]
+    async fn cancelled_completes_for_a_token_cancelled_before() {
+        let token = CancelToken::new();
+        let clone = token.clone();
+        token.cancel();
+
+        assert!(clone.is_cancelled());
+        tokio::time::timeout(Duration::from_secs(1), clone.cancelled())
+            .await
+            .unwrap();
+    }
+}
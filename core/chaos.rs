@@ -0,0 +1,312 @@
+use std::{
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+use crate::{
+    runner::{ResolvedRequest, ResponseObject, Runner},
+    schema::chaos::ChaosSchema,
+};
+
+/// Injects the faults of a chaos schema into the requests of a run and counts them.
+///
+/// Faults happen below the retries, so a dropped connection is retried like a real one.
+#[derive(Debug)]
+pub struct Chaos {
+    pub schema: ChaosSchema,
+    rng: Mutex<StdRng>,
+    delayed: AtomicUsize,
+    dropped: AtomicUsize,
+    duplicated: AtomicUsize,
+}
+
+impl Chaos {
+    pub fn new(schema: ChaosSchema) -> anyhow::Result<Chaos> {
+        for (name, probability) in [
+            ("delay", schema.delay),
+            ("drop", schema.drop),
+            ("duplicate", schema.duplicate),
+        ] {
+            if !(0.0..=1.0).contains(&probability) {
+                anyhow::bail!(
+                    "The {} probability must be between 0 and 1, got {}",
+                    name,
+                    probability
+                );
+            }
+        }
+
+        let rng = match schema.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
+        return Ok(Chaos {
+            schema,
+            rng: Mutex::new(rng),
+            delayed: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+            duplicated: AtomicUsize::new(0),
+        });
+    }
+
+    // eg: "3 delayed, 1 dropped, 0 duplicated"
+    pub fn summary(&self) -> String {
+        return format!(
+            "{} delayed, {} dropped, {} duplicated",
+            self.delayed.load(Ordering::Relaxed),
+            self.dropped.load(Ordering::Relaxed),
+            self.duplicated.load(Ordering::Relaxed)
+        );
+    }
+
+    // Draws whether a fault with this probability happens
+    fn happens(&self, probability: f64) -> bool {
+        return self.rng.lock().unwrap().random_bool(probability);
+    }
+
+    fn delay(&self) -> Option<Duration> {
+        if !self.happens(self.schema.delay) {
+            return None;
+        }
+
+        let min = self.schema.delay_min;
+        let max = self.schema.delay_max.max(min);
+        self.delayed.fetch_add(1, Ordering::Relaxed);
+        let delay = self.rng.lock().unwrap().random_range(min..=max);
+        return Some(Duration::from_millis(delay as u64));
+    }
+
+    fn duplicate_call(&self) -> bool {
+        let duplicate = self.happens(self.schema.duplicate);
+        if duplicate {
+            self.duplicated.fetch_add(1, Ordering::Relaxed);
+        }
+        return duplicate;
+    }
+
+    fn drop_connection(&self) -> bool {
+        let drop = self.happens(self.schema.drop);
+        if drop {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+        return drop;
+    }
+}
+
+impl Runner {
+    // Injects faults into the requests of the run, the project's chaos settings when none are given
+    pub fn enable_chaos(&mut self, schema: Option<ChaosSchema>) -> anyhow::Result<()> {
+        let schema = match schema.or_else(|| self.project.object.chaos.clone()) {
+            Some(schema) => schema,
+            None => anyhow::bail!("The project has no chaos settings to inject faults with"),
+        };
+
+        self.chaos = Some(Chaos::new(schema)?);
+        return Ok(());
+    }
+
+    // Sends the request, delaying, duplicating or dropping it when chaos is enabled
+    pub(crate) async fn execute_with_faults(
+        &self,
+        resolved: &ResolvedRequest,
+    ) -> anyhow::Result<ResponseObject> {
+        let chaos = match &self.chaos {
+            Some(chaos) => chaos,
+            None => return self.execute(resolved).await,
+        };
+
+        if let Some(delay) = chaos.delay() {
+            tracing::warn!("Chaos: delaying {} by {:?}", resolved.name, delay);
+            tokio::time::sleep(delay).await;
+        }
+
+        // the first call only matters for what it does to the api
+        if chaos.duplicate_call() {
+            tracing::warn!("Chaos: sending {} twice", resolved.name);
+            if let Err(e) = self.execute(resolved).await {
+                let error = self.redact_text(&e.to_string());
+                tracing::warn!(
+                    "Chaos: the duplicate of {} failed: {}",
+                    resolved.name,
+                    error
+                );
+            }
+        }
+
+        let response = self.execute(resolved).await?;
+
+        if chaos.drop_connection() {
+            tracing::warn!("Chaos: dropping the connection of {}", resolved.name);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionAborted,
+                format!(
+                    "Connection dropped by fault injection, the response of {} {} was lost",
+                    resolved.method, resolved.url
+                ),
+            )
+            .into());
+        }
+
+        return Ok(response);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{fs::FileObject, schema::roots::RequestRootSchema};
+
+    fn chaos(delay: f64, drop: f64, duplicate: f64, seed: u64) -> Chaos {
+        return Chaos::new(ChaosSchema {
+            delay,
+            drop,
+            duplicate,
+            seed: Some(seed),
+            ..Default::default()
+        })
+        .unwrap();
+    }
+
+    // The faults drawn for a number of requests
+    fn draw(chaos: &Chaos, requests: usize) -> Vec<(Option<Duration>, bool, bool)> {
+        return (0..requests)
+            .map(|_| {
+                (
+                    chaos.delay(),
+                    chaos.duplicate_call(),
+                    chaos.drop_connection(),
+                )
+            })
+            .collect();
+    }
+
+    #[test]
+    fn probabilities_outside_0_and_1_are_refused() {
+        let error = Chaos::new(ChaosSchema {
+            drop: 1.5,
+            ..Default::default()
+        })
+        .unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "The drop probability must be between 0 and 1, got 1.5"
+        );
+    }
+
+    #[test]
+    fn the_same_seed_injects_the_same_faults() {
+        let first = chaos(0.5, 0.5, 0.5, 7);
+        let second = chaos(0.5, 0.5, 0.5, 7);
+
+        let faults = draw(&first, 50);
+        assert_eq!(faults, draw(&second, 50));
+        assert_eq!(first.summary(), second.summary());
+        // with even odds some requests get each fault and some don't
+        assert!(faults.iter().any(|(delay, _, _)| delay.is_some()));
+        assert!(faults.iter().any(|(delay, _, _)| delay.is_none()));
+
+        assert_ne!(faults, draw(&chaos(0.5, 0.5, 0.5, 8), 50));
+    }
+
+    #[test]
+    fn faults_follow_their_probability_and_are_counted() {
+        let chaos = Chaos::new(ChaosSchema {
+            delay: 1.0,
+            delay_min: 10,
+            delay_max: 20,
+            drop: 0.0,
+            duplicate: 1.0,
+            seed: Some(1),
+        })
+        .unwrap();
+
+        for (delay, duplicate, drop) in draw(&chaos, 20) {
+            let delay = delay.unwrap();
+            assert!(delay >= Duration::from_millis(10) && delay <= Duration::from_millis(20));
+            assert!(duplicate);
+            assert!(!drop);
+        }
+        assert_eq!(chaos.summary(), "20 delayed, 0 dropped, 20 duplicated");
+    }
+
+    // Counts the requests it answers
+    async fn serve_count(count: Arc<AtomicUsize>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await;
+                count.fetch_add(1, Ordering::SeqCst);
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    #[tokio::test]
+    async fn duplicated_requests_are_sent_twice_and_dropped_ones_fail() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let url = serve_count(count.clone()).await;
+        let root = std::env::temp_dir().join(format!("dotapi-chaos-{}", uuid::Uuid::new_v4()));
+        let request =
+            serde_yaml::from_str::<RequestRootSchema>(&format!("method: GET\nurl: {}/users", url))
+                .unwrap();
+        let mut runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![FileObject::new(root.join("requests/users.nd"), request)],
+        )
+        .unwrap();
+        runner
+            .enable_chaos(Some(ChaosSchema {
+                drop: 1.0,
+                duplicate: 1.0,
+                seed: Some(3),
+                ..Default::default()
+            }))
+            .unwrap();
+        let resolved = runner.resolve_request("users").unwrap();
+
+        let error = runner.execute_with_faults(&resolved).await.unwrap_err();
+
+        assert!(
+            error
+                .to_string()
+                .starts_with("Connection dropped by fault injection, the response of GET"),
+            "{}",
+            error
+        );
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            runner.chaos.as_ref().unwrap().summary(),
+            "0 delayed, 1 dropped, 1 duplicated"
+        );
+    }
+
+    #[test]
+    fn chaos_needs_settings() {
+        let mut runner = Runner::new(
+            FileObject::new(std::env::temp_dir().join(".nd-project"), Default::default()),
+            vec![],
+        )
+        .unwrap();
+
+        assert!(runner.enable_chaos(None).is_err());
+        assert!(runner.chaos.is_none());
+    }
+}
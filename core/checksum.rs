@@ -0,0 +1,141 @@
+use base64::Engine;
+use sha2::Digest;
+
+use crate::schema::checksum::{ChecksumAlgorithmSchema, ChecksumEncodingSchema, ChecksumSchema};
+
+// The header and its value for a checksum of the body
+pub fn checksum_header(checksum: &ChecksumSchema, body: &[u8]) -> (String, String) {
+    let hash = match checksum.algorithm {
+        ChecksumAlgorithmSchema::Md5 => md5::Md5::digest(body).to_vec(),
+        ChecksumAlgorithmSchema::Sha256 => sha2::Sha256::digest(body).to_vec(),
+    };
+    let base64 = base64::engine::general_purpose::STANDARD.encode(&hash);
+
+    if let Some(header) = &checksum.header {
+        let value = match checksum.encoding {
+            ChecksumEncodingSchema::Base64 => base64,
+            ChecksumEncodingSchema::Hex => hash.iter().map(|b| format!("{:02x}", b)).collect(),
+        };
+        return (header.clone(), value);
+    }
+
+    return match checksum.algorithm {
+        ChecksumAlgorithmSchema::Md5 => ("Content-MD5".to_string(), base64),
+        // rfc 9530
+        ChecksumAlgorithmSchema::Sha256 => (
+            "Content-Digest".to_string(),
+            format!("sha-256=:{}:", base64),
+        ),
+    };
+}
+
+// Sets the checksum headers of a built request over its body, as it will be sent.
+// Headers already set by the request are kept.
+pub fn add_checksums(
+    request: &mut reqwest::Request,
+    checksums: &[ChecksumSchema],
+) -> anyhow::Result<()> {
+    let body = request
+        .body()
+        .and_then(|body| body.as_bytes())
+        .unwrap_or_default()
+        .to_vec();
+
+    for checksum in checksums {
+        let (header, value) = checksum_header(checksum, &body);
+        let header = reqwest::header::HeaderName::from_bytes(header.as_bytes())?;
+        if !request.headers().contains_key(&header) {
+            request.headers_mut().insert(header, value.parse()?);
+        }
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checksum(yaml: &str) -> ChecksumSchema {
+        return serde_yaml::from_str(yaml).unwrap();
+    }
+
+    fn header(name: &str, value: &str) -> (String, String) {
+        return (name.to_string(), value.to_string());
+    }
+
+    // the rfc 1321 and fips 180-2 examples of "abc"
+    #[test]
+    fn known_vectors() {
+        assert_eq!(
+            checksum_header(&checksum("{algorithm: md5}"), b"abc"),
+            header("Content-MD5", "kAFQmDzST7DWlj99KOF/cg==")
+        );
+        assert_eq!(
+            checksum_header(&checksum("{algorithm: sha256}"), b"abc"),
+            header(
+                "Content-Digest",
+                "sha-256=:ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=:"
+            )
+        );
+        assert_eq!(
+            checksum_header(&checksum("{algorithm: md5}"), b""),
+            header("Content-MD5", "1B2M2Y8AsgTpgAmY7PhCfg==")
+        );
+    }
+
+    #[test]
+    fn custom_headers_take_the_encoding() {
+        assert_eq!(
+            checksum_header(
+                &checksum("{algorithm: sha256, header: x-amz-content-sha256, encoding: hex}"),
+                b"abc"
+            ),
+            header(
+                "x-amz-content-sha256",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+            )
+        );
+        assert_eq!(
+            checksum_header(&checksum("{algorithm: md5, header: x-md5}"), b"abc"),
+            header("x-md5", "kAFQmDzST7DWlj99KOF/cg==")
+        );
+    }
+
+    #[test]
+    fn a_different_body_doesnt_match() {
+        let md5 = checksum("{algorithm: md5}");
+        assert_ne!(checksum_header(&md5, b"abd"), checksum_header(&md5, b"abc"));
+        let sha256 = checksum("{algorithm: sha256}");
+        assert_ne!(
+            checksum_header(&sha256, b"abc "),
+            checksum_header(&sha256, b"abc")
+        );
+    }
+
+    #[test]
+    fn requests_get_the_checksums_of_their_body() {
+        let mut request = reqwest::Client::new()
+            .put("https://x.dev/a")
+            .header("Content-MD5", "set by the request")
+            .body("abc")
+            .build()
+            .unwrap();
+
+        add_checksums(
+            &mut request,
+            &[
+                checksum("{algorithm: md5}"),
+                checksum("{algorithm: sha256}"),
+            ],
+        )
+        .unwrap();
+
+        // the header of the request is kept even when it doesn't match the body
+        assert_eq!(request.headers()["content-md5"], "set by the request");
+        assert_eq!(
+            request.headers()["content-digest"],
+            "sha-256=:ungWv48Bz+pBQUDeXa4iI7ADYaOWF3qctBD/YfIAFa0=:"
+        );
+    }
+}
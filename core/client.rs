@@ -0,0 +1,238 @@
+use std::path::Path;
+
+use crate::{
+    body::BodySerializer,
+    error::ExecutorError,
+    middleware::RequestMiddleware,
+    report::RunReport,
+    runner::{CallResult, ResolvedRequest, Runner},
+};
+
+/// Entry point for using a project as a library.
+///
+/// Wraps a `Runner`, with every error returned as an `ExecutorError`
+/// rather than an `anyhow::Error`.
+pub struct Client {
+    runner: Runner,
+}
+
+impl Client {
+    // Loads the project file at path, its requests are loaded when they are called
+    pub async fn open(path: &Path) -> Result<Client, ExecutorError> {
+        let path = std::path::absolute(path).map_err(|e| ExecutorError::Parse {
+            path: Some(path.to_path_buf()),
+            message: e.to_string(),
+        })?;
+
+        let runner = Runner::load(&path)
+            .await
+            .map_err(|e| ExecutorError::from_load(&path, e))?;
+
+        return Ok(Client { runner });
+    }
+
+    pub fn from_runner(runner: Runner) -> Client {
+        return Client { runner };
+    }
+
+    pub fn runner(&self) -> &Runner {
+        return &self.runner;
+    }
+
+    pub fn runner_mut(&mut self) -> &mut Runner {
+        return &mut self.runner;
+    }
+
+    pub fn set_environment(&mut self, environment: Option<&str>) {
+        self.runner.environment = environment.map(|environment| environment.to_string());
+    }
+
+    pub fn environments(&self) -> Vec<String> {
+        return self.runner.project.object.environments();
+    }
+
+    // Names of the requests in the project, as used by calls
+    pub fn requests(&self) -> Vec<String> {
+        return self.runner.request_names();
+    }
+
+    pub fn add_middleware(&mut self, middleware: Box<dyn RequestMiddleware>) {
+        self.runner.add_middleware(middleware);
+    }
+
+    // Sends the bodies of the serializer's type with it, replacing the built-in serializer of that type if any
+    pub fn register_body_serializer(&mut self, serializer: Box<dyn BodySerializer>) {
+        self.runner.register_body_serializer(serializer);
+    }
+
+    // The request with its placeholders replaced, without sending it
+    pub async fn resolve(&mut self, name: &str) -> Result<ResolvedRequest, ExecutorError> {
+        self.runner
+            .load_request(name)
+            .await
+            .map_err(|e| ExecutorError::from_call(name, e))?;
+
+        return self
+            .runner
+            .resolve_request(name)
+            .map_err(|e| ExecutorError::from_call(name, e));
+    }
+
+    // Calls a request, along with the requests it requires
+    pub async fn call(&mut self, name: &str) -> Result<CallResult, ExecutorError> {
+        return self
+            .runner
+            .call_request(name)
+            .await
+            .map_err(|e| ExecutorError::from_call(name, e));
+    }
+
+    // Calls every request of a sequence, stopping at the first failure its policy doesn't allow
+    pub async fn call_sequence(&mut self, name: &str) -> Result<Vec<CallResult>, ExecutorError> {
+        return self
+            .runner
+            .call_sequence(name)
+            .await
+            .map_err(|e| ExecutorError::from_call(name, e));
+    }
+
+    // Runs a sequence to completion, failures are in the report
+    pub async fn run(&mut self, name: &str) -> Result<RunReport, ExecutorError> {
+        return self
+            .runner
+            .run_sequence(name)
+            .await
+            .map_err(|e| ExecutorError::from_call(name, e));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    // Answers every request with 200 and an empty json object
+    async fn serve_ok() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: 2\r\nconnection: close\r\n\r\n{}";
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    // A project folder with these requests, returns the path of its project file
+    async fn project(requests: &[(&str, String)]) -> std::path::PathBuf {
+        let root = std::env::temp_dir().join(format!("dotapi-client-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(root.join("requests"))
+            .await
+            .unwrap();
+
+        for (name, content) in requests {
+            tokio::fs::write(root.join("requests").join(format!("{}.nd", name)), content)
+                .await
+                .unwrap();
+        }
+
+        let path = root.join(".nd-project");
+        tokio::fs::write(&path, "project:\n  name: client\ncalls:\n  main: []\n")
+            .await
+            .unwrap();
+        return path;
+    }
+
+    #[tokio::test]
+    async fn a_project_that_does_not_load_is_a_parse_error() {
+        let path = std::env::temp_dir().join(format!("dotapi-client-{}", uuid::Uuid::new_v4()));
+
+        let error = Client::open(&path.join(".nd-project")).await.err().unwrap();
+
+        match error {
+            ExecutorError::Parse { path: Some(p), .. } => assert_eq!(p, path.join(".nd-project")),
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn call_errors_are_classified() {
+        let base = serve_ok().await;
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let down = listener.local_addr().unwrap();
+        drop(listener);
+
+        let path = project(&[
+            ("ok", format!("method: GET\nurl: \"{}/\"\n", base)),
+            (
+                "checked",
+                format!(
+                    "method: GET\nurl: \"{}/\"\nassert: [\"response.status == 201\"]\n",
+                    base
+                ),
+            ),
+            (
+                "skipped",
+                format!("method: GET\nurl: \"{}/\"\nwhen: \"1 == 2\"\n", base),
+            ),
+            (
+                "unresolved",
+                "method: GET\nurl: \"{{missing}}/\"\n".to_string(),
+            ),
+            ("down", format!("method: GET\nurl: \"http://{}/\"\n", down)),
+        ])
+        .await;
+        let mut client = Client::open(&path).await.unwrap();
+
+        let mut requests = client.requests();
+        requests.sort();
+        assert_eq!(
+            requests,
+            vec!["checked", "down", "ok", "skipped", "unresolved"]
+        );
+        assert_eq!(client.call("ok").await.unwrap().response.status, 200);
+
+        match client.call("checked").await.err().unwrap() {
+            ExecutorError::Assertion { request, failures } => {
+                assert_eq!(request, "checked");
+                assert_eq!(failures, vec!["`response.status == 201` is false"]);
+            }
+            other => panic!("{:?}", other),
+        }
+
+        match client.call("skipped").await.err().unwrap() {
+            ExecutorError::Skipped { request, condition } => {
+                assert_eq!(request, "skipped");
+                assert_eq!(condition, "1 == 2");
+            }
+            other => panic!("{:?}", other),
+        }
+
+        match client.resolve("unresolved").await.err().unwrap() {
+            ExecutorError::Interpolation {
+                request, variable, ..
+            } => {
+                assert_eq!(request, "unresolved");
+                assert_eq!(variable, "missing");
+            }
+            other => panic!("{:?}", other),
+        }
+
+        match client.call("down").await.err().unwrap() {
+            ExecutorError::Network { request, .. } => assert_eq!(request, "down"),
+            other => panic!("{:?}", other),
+        }
+
+        match client.call("nothing").await.err().unwrap() {
+            ExecutorError::Other(_) => {}
+            other => panic!("{:?}", other),
+        }
+    }
+}
@@ -0,0 +1,486 @@
+use crate::{
+    runner::{ResolvedRequest, Runner},
+    schema::request_body::{MultipartPartSchema, RequestBodySchema},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CodegenLanguage {
+    Rust,
+    Python,
+    JavaScript,
+    Go,
+}
+
+impl CodegenLanguage {
+    pub fn all() -> Vec<CodegenLanguage> {
+        return vec![
+            CodegenLanguage::Rust,
+            CodegenLanguage::Python,
+            CodegenLanguage::JavaScript,
+            CodegenLanguage::Go,
+        ];
+    }
+
+    pub fn label(&self) -> &'static str {
+        return match self {
+            CodegenLanguage::Rust => "Rust (reqwest)",
+            CodegenLanguage::Python => "Python (requests)",
+            CodegenLanguage::JavaScript => "JavaScript (fetch)",
+            CodegenLanguage::Go => "Go (net/http)",
+        };
+    }
+}
+
+impl Runner {
+    // Renders the request, resolved with the active environment, as code for the given language
+    pub fn generate_snippet(
+        &self,
+        name: &str,
+        language: CodegenLanguage,
+    ) -> anyhow::Result<String> {
+        let resolved = self.resolve_request(name)?;
+        return Ok(generate(&resolved, language));
+    }
+}
+
+pub fn generate(request: &ResolvedRequest, language: CodegenLanguage) -> String {
+    return match language {
+        CodegenLanguage::Rust => rust(request),
+        CodegenLanguage::Python => python(request),
+        CodegenLanguage::JavaScript => javascript(request),
+        CodegenLanguage::Go => go(request),
+    };
+}
+
+// Headers to send, including the content type implied by the body
+fn headers(request: &ResolvedRequest) -> Vec<(String, String)> {
+    let mut headers = request.headers.clone();
+
+    if request.get_header("content-type").is_none()
+        && !is_multipart(request)
+        && let Some(content_type) = request.content_type()
+    {
+        headers.push(("Content-Type".to_string(), content_type));
+    }
+
+    return headers;
+}
+
+fn multipart_parts(request: &ResolvedRequest) -> Option<&Vec<MultipartPartSchema>> {
+    return match &request.body {
+        Some(RequestBodySchema::Multipart { parts }) => Some(parts),
+        _ => None,
+    };
+}
+
+fn is_multipart(request: &ResolvedRequest) -> bool {
+    return multipart_parts(request).is_some();
+}
+
+fn binary_path(request: &ResolvedRequest) -> Option<&String> {
+    return match &request.body {
+        Some(RequestBodySchema::Binary { path, .. }) => Some(path),
+        _ => None,
+    };
+}
+
+// A double quoted string literal, valid in python, javascript and go
+fn string_literal(value: &str) -> String {
+    return serde_json::to_string(value).unwrap_or_else(|_| format!("\"{}\"", value));
+}
+
+fn rust(request: &ResolvedRequest) -> String {
+    let mut lines = vec![
+        "let client = reqwest::Client::new();".to_string(),
+        "let response = client".to_string(),
+        format!(
+            "    .request(reqwest::Method::from_bytes(b{:?})?, {:?})",
+            request.method, request.url
+        ),
+    ];
+
+    for (key, value) in headers(request) {
+        lines.push(format!("    .header({:?}, {:?})", key, value));
+    }
+
+    if let Some(parts) = multipart_parts(request) {
+        lines.push("    .multipart(".to_string());
+        lines.push("        reqwest::multipart::Form::new()".to_string());
+        for part in parts {
+            match part {
+                MultipartPartSchema::Field { name, value } => {
+                    lines.push(format!("            .text({:?}, {:?})", name, value))
+                }
+                MultipartPartSchema::File { name, path, .. } => lines.push(format!(
+                    "            .part({:?}, reqwest::multipart::Part::bytes(std::fs::read({:?})?).file_name({:?}))",
+                    name, path, path
+                )),
+            };
+        }
+        lines.push("    )".to_string());
+    } else if let Some(path) = binary_path(request) {
+        lines.push(format!("    .body(std::fs::read({:?})?)", path));
+    } else if let Some(body) = request.body_text() {
+        lines.push(format!("    .body({:?})", body));
+    }
+
+    lines.push("    .send()".to_string());
+    lines.push("    .await?;".to_string());
+    lines.push(String::new());
+    lines.push("println!(\"{}\", response.text().await?);".to_string());
+
+    return lines.join("\n");
+}
+
+fn python(request: &ResolvedRequest) -> String {
+    let mut lines = vec![
+        "import requests".to_string(),
+        String::new(),
+        "response = requests.request(".to_string(),
+        format!("    {},", string_literal(&request.method)),
+        format!("    {},", string_literal(&request.url)),
+    ];
+
+    let headers = headers(request);
+    if !headers.is_empty() {
+        lines.push("    headers={".to_string());
+        for (key, value) in headers {
+            lines.push(format!(
+                "        {}: {},",
+                string_literal(&key),
+                string_literal(&value)
+            ));
+        }
+        lines.push("    },".to_string());
+    }
+
+    if let Some(parts) = multipart_parts(request) {
+        let mut fields = vec![];
+        let mut files = vec![];
+
+        for part in parts {
+            match part {
+                MultipartPartSchema::Field { name, value } => fields.push(format!(
+                    "        {}: {},",
+                    string_literal(name),
+                    string_literal(value)
+                )),
+                MultipartPartSchema::File { name, path, .. } => files.push(format!(
+                    "        {}: open({}, \"rb\"),",
+                    string_literal(name),
+                    string_literal(path)
+                )),
+            };
+        }
+
+        if !fields.is_empty() {
+            lines.push("    data={".to_string());
+            lines.extend(fields);
+            lines.push("    },".to_string());
+        }
+
+        if !files.is_empty() {
+            lines.push("    files={".to_string());
+            lines.extend(files);
+            lines.push("    },".to_string());
+        }
+    } else if let Some(path) = binary_path(request) {
+        lines.push(format!("    data=open({}, \"rb\"),", string_literal(path)));
+    } else if let Some(body) = request.body_text() {
+        lines.push(format!("    data={},", string_literal(&body)));
+    }
+
+    if request.config.insecure {
+        lines.push("    verify=False,".to_string());
+    }
+
+    lines.push(")".to_string());
+    lines.push(String::new());
+    lines.push("print(response.text)".to_string());
+
+    return lines.join("\n");
+}
+
+fn javascript(request: &ResolvedRequest) -> String {
+    let mut lines = vec![];
+
+    if let Some(parts) = multipart_parts(request) {
+        if parts
+            .iter()
+            .any(|part| matches!(part, MultipartPartSchema::File { .. }))
+        {
+            lines.push("import { openAsBlob } from \"node:fs\";".to_string());
+            lines.push(String::new());
+        }
+
+        lines.push("const body = new FormData();".to_string());
+        for part in parts {
+            match part {
+                MultipartPartSchema::Field { name, value } => lines.push(format!(
+                    "body.append({}, {});",
+                    string_literal(name),
+                    string_literal(value)
+                )),
+                MultipartPartSchema::File {
+                    name,
+                    path,
+                    mime_type,
+                } => lines.push(format!(
+                    "body.append({}, await openAsBlob({}, {{ type: {} }}), {});",
+                    string_literal(name),
+                    string_literal(path),
+                    string_literal(mime_type.as_deref().unwrap_or_default()),
+                    string_literal(path)
+                )),
+            };
+        }
+        lines.push(String::new());
+    } else if binary_path(request).is_some() {
+        lines.push("import { readFile } from \"node:fs/promises\";".to_string());
+        lines.push(String::new());
+    }
+
+    lines.push(format!(
+        "const response = await fetch({}, {{",
+        string_literal(&request.url)
+    ));
+    lines.push(format!("  method: {},", string_literal(&request.method)));
+
+    let headers = headers(request);
+    if !headers.is_empty() {
+        lines.push("  headers: {".to_string());
+        for (key, value) in headers {
+            lines.push(format!(
+                "    {}: {},",
+                string_literal(&key),
+                string_literal(&value)
+            ));
+        }
+        lines.push("  },".to_string());
+    }
+
+    if is_multipart(request) {
+        lines.push("  body,".to_string());
+    } else if let Some(path) = binary_path(request) {
+        lines.push(format!("  body: await readFile({}),", string_literal(path)));
+    } else if let Some(body) = request.body_text() {
+        lines.push(format!("  body: {},", string_literal(&body)));
+    }
+
+    lines.push("});".to_string());
+    lines.push(String::new());
+    lines.push("console.log(await response.text());".to_string());
+
+    return lines.join("\n");
+}
+
+fn go(request: &ResolvedRequest) -> String {
+    let multipart = multipart_parts(request);
+    let body = request.body_text();
+
+    let mut imports = vec!["\"fmt\"", "\"io\"", "\"net/http\""];
+    if multipart.is_some() {
+        imports.extend(["\"bytes\"", "\"mime/multipart\"", "\"os\""]);
+    } else if binary_path(request).is_some() {
+        imports.push("\"os\"");
+    } else if body.is_some() {
+        imports.push("\"strings\"");
+    }
+    imports.sort();
+
+    let mut lines = vec![
+        "package main".to_string(),
+        String::new(),
+        "import (".to_string(),
+    ];
+    for import in imports {
+        lines.push(format!("\t{}", import));
+    }
+    lines.push(")".to_string());
+    lines.push(String::new());
+    lines.push("func main() {".to_string());
+
+    let body_arg = if let Some(parts) = multipart {
+        lines.push("\tbody := &bytes.Buffer{}".to_string());
+        lines.push("\twriter := multipart.NewWriter(body)".to_string());
+        for part in parts {
+            match part {
+                MultipartPartSchema::Field { name, value } => lines.push(format!(
+                    "\twriter.WriteField({}, {})",
+                    string_literal(name),
+                    string_literal(value)
+                )),
+                MultipartPartSchema::File { name, path, .. } => {
+                    lines.push("\t{".to_string());
+                    lines.push(format!(
+                        "\t\tcontent, err := os.ReadFile({})",
+                        string_literal(path)
+                    ));
+                    lines.push("\t\tif err != nil {\n\t\t\tpanic(err)\n\t\t}".to_string());
+                    lines.push(format!(
+                        "\t\tpart, _ := writer.CreateFormFile({}, {})",
+                        string_literal(name),
+                        string_literal(path)
+                    ));
+                    lines.push("\t\tpart.Write(content)".to_string());
+                    lines.push("\t}".to_string());
+                }
+            };
+        }
+        lines.push("\twriter.Close()".to_string());
+        lines.push(String::new());
+        "body"
+    } else if let Some(path) = binary_path(request) {
+        lines.push(format!("\tbody, err := os.Open({})", string_literal(path)));
+        lines.push("\tif err != nil {\n\t\tpanic(err)\n\t}".to_string());
+        lines.push("\tdefer body.Close()".to_string());
+        lines.push(String::new());
+        "body"
+    } else if let Some(body) = &body {
+        lines.push(format!(
+            "\tbody := strings.NewReader({})",
+            string_literal(body)
+        ));
+        "body"
+    } else {
+        "nil"
+    };
+
+    lines.push(format!(
+        "\treq, err := http.NewRequest({}, {}, {})",
+        string_literal(&request.method),
+        string_literal(&request.url),
+        body_arg
+    ));
+    lines.push("\tif err != nil {\n\t\tpanic(err)\n\t}".to_string());
+
+    for (key, value) in headers(request) {
+        lines.push(format!(
+            "\treq.Header.Set({}, {})",
+            string_literal(&key),
+            string_literal(&value)
+        ));
+    }
+    if multipart.is_some() {
+        lines.push("\treq.Header.Set(\"Content-Type\", writer.FormDataContentType())".to_string());
+    }
+
+    lines.push(String::new());
+    lines.push("\tres, err := http.DefaultClient.Do(req)".to_string());
+    lines.push("\tif err != nil {\n\t\tpanic(err)\n\t}".to_string());
+    lines.push("\tdefer res.Body.Close()".to_string());
+    lines.push(String::new());
+    lines.push("\tdata, _ := io.ReadAll(res.Body)".to_string());
+    lines.push("\tfmt.Println(string(data))".to_string());
+    lines.push("}".to_string());
+
+    return lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A POST with a header, a json body and a query param added to the ones of the url
+    fn request() -> ResolvedRequest {
+        let root = std::env::temp_dir().join("dotapi-codegen");
+        let request = serde_yaml::from_str::<crate::schema::roots::RequestRootSchema>(
+            "method: POST
+url: https://x.dev/users?page=2
+headers: {Authorization: Bearer abc}
+query: {tag: \"a b\"}
+body: {type: json, content: {name: \"Ada \\\"L\\\"\"}}",
+        )
+        .unwrap();
+        let runner = Runner::new(
+            crate::fs::FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![crate::fs::FileObject::new(
+                root.join("requests/create.nd"),
+                request,
+            )],
+        )
+        .unwrap();
+
+        return runner.resolve_request("create").unwrap();
+    }
+
+    #[test]
+    fn rust_snapshot() {
+        let expected = r#"let client = reqwest::Client::new();
+let response = client
+    .request(reqwest::Method::from_bytes(b"POST")?, "https://x.dev/users?page=2&tag=a+b")
+    .header("Authorization", "Bearer abc")
+    .header("Content-Type", "application/json")
+    .body("{\"name\":\"Ada \\\"L\\\"\"}")
+    .send()
+    .await?;
+
+println!("{}", response.text().await?);"#;
+        assert_eq!(generate(&request(), CodegenLanguage::Rust), expected);
+    }
+
+    #[test]
+    fn python_snapshot() {
+        let expected = r#"import requests
+
+response = requests.request(
+    "POST",
+    "https://x.dev/users?page=2&tag=a+b",
+    headers={
+        "Authorization": "Bearer abc",
+        "Content-Type": "application/json",
+    },
+    data="{\"name\":\"Ada \\\"L\\\"\"}",
+)
+
+print(response.text)"#;
+        assert_eq!(generate(&request(), CodegenLanguage::Python), expected);
+    }
+
+    #[test]
+    fn javascript_snapshot() {
+        let expected = r#"const response = await fetch("https://x.dev/users?page=2&tag=a+b", {
+  method: "POST",
+  headers: {
+    "Authorization": "Bearer abc",
+    "Content-Type": "application/json",
+  },
+  body: "{\"name\":\"Ada \\\"L\\\"\"}",
+});
+
+console.log(await response.text());"#;
+        assert_eq!(generate(&request(), CodegenLanguage::JavaScript), expected);
+    }
+
+    #[test]
+    fn go_snapshot() {
+        let expected = r#"package main
+
+import (
+	"fmt"
+	"io"
+	"net/http"
+	"strings"
+)
+
+func main() {
+	body := strings.NewReader("{\"name\":\"Ada \\\"L\\\"\"}")
+	req, err := http.NewRequest("POST", "https://x.dev/users?page=2&tag=a+b", body)
+	if err != nil {
+		panic(err)
+	}
+	req.Header.Set("Authorization", "Bearer abc")
+	req.Header.Set("Content-Type", "application/json")
+
+	res, err := http.DefaultClient.Do(req)
+	if err != nil {
+		panic(err)
+	}
+	defer res.Body.Close()
+
+	data, _ := io.ReadAll(res.Body)
+	fmt.Println(string(data))
+}"#;
+        assert_eq!(generate(&request(), CodegenLanguage::Go), expected);
+    }
+}
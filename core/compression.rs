@@ -0,0 +1,164 @@
+use std::io::{Read, Write};
+
+use anyhow::Context;
+
+use crate::schema::compression::CompressionSchema;
+
+// Decodes a body according to its content-encoding header, eg: "gzip" or "gzip, br".
+// Encodings are listed in the order they were applied, so they are undone in reverse.
+pub fn decode(content: &[u8], content_encoding: &str) -> anyhow::Result<Vec<u8>> {
+    let mut result = content.to_vec();
+
+    for encoding in content_encoding.split(',').rev() {
+        let encoding = encoding.trim().to_lowercase();
+        let mut decoded = vec![];
+
+        match encoding.as_str() {
+            "" | "identity" => continue,
+            "gzip" | "x-gzip" => {
+                flate2::read::GzDecoder::new(result.as_slice()).read_to_end(&mut decoded)?
+            }
+            // http's deflate is zlib wrapped
+            "deflate" => {
+                flate2::read::ZlibDecoder::new(result.as_slice()).read_to_end(&mut decoded)?
+            }
+            "br" => brotli::Decompressor::new(result.as_slice(), 4096).read_to_end(&mut decoded)?,
+            other => anyhow::bail!("Unsupported content encoding \"{}\"", other),
+        };
+
+        result = decoded;
+    }
+
+    return Ok(result);
+}
+
+pub fn encode(content: &[u8], compression: CompressionSchema) -> anyhow::Result<Vec<u8>> {
+    let mut encoded = vec![];
+
+    match compression {
+        CompressionSchema::Gzip => {
+            let mut encoder =
+                flate2::write::GzEncoder::new(&mut encoded, flate2::Compression::default());
+            encoder.write_all(content)?;
+            encoder.finish()?;
+        }
+        CompressionSchema::Br => {
+            // buffer size, quality and window of the brotli cli defaults
+            let mut encoder = brotli::CompressorWriter::new(&mut encoded, 4096, 11, 22);
+            encoder.write_all(content)?;
+            // ends the stream
+            encoder.into_inner();
+        }
+    };
+
+    return Ok(encoded);
+}
+
+// Compresses the body of a built request and sets its content-encoding.
+// Encodings already set by the request are kept, the new one is listed last as it's applied last.
+pub fn compress_body(
+    request: &mut reqwest::Request,
+    compression: CompressionSchema,
+) -> anyhow::Result<()> {
+    // streamed bodies are buffered first, see buffer_body
+    let content = match request.body() {
+        Some(body) => body
+            .as_bytes()
+            .context("Streamed bodies can't be compressed")?,
+        None => return Ok(()),
+    };
+
+    let encoded = encode(content, compression)?;
+    *request.body_mut() = Some(encoded.into());
+
+    let headers = request.headers_mut();
+    let encoding = match headers
+        .get(reqwest::header::CONTENT_ENCODING)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(existing) => format!("{}, {}", existing, compression.encoding()),
+        None => compression.encoding().to_string(),
+    };
+    headers.insert(reqwest::header::CONTENT_ENCODING, encoding.parse()?);
+    headers.remove(reqwest::header::CONTENT_LENGTH);
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONTENT: &[u8] =
+        b"{\"items\": [1, 2, 3], \"name\": \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\"}";
+
+    #[test]
+    fn encoded_bodies_decode_to_the_content() {
+        for compression in [CompressionSchema::Gzip, CompressionSchema::Br] {
+            let encoded = encode(CONTENT, compression).unwrap();
+            assert_ne!(encoded, CONTENT);
+            assert_eq!(decode(&encoded, compression.encoding()).unwrap(), CONTENT);
+        }
+    }
+
+    #[test]
+    fn deflate_and_identity_decode() {
+        let mut encoder = flate2::write::ZlibEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(CONTENT).unwrap();
+        let deflated = encoder.finish().unwrap();
+
+        assert_eq!(decode(&deflated, "Deflate").unwrap(), CONTENT);
+        assert_eq!(decode(CONTENT, "identity").unwrap(), CONTENT);
+        assert_eq!(decode(CONTENT, "").unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn stacked_encodings_are_undone_in_reverse() {
+        let gzipped = encode(CONTENT, CompressionSchema::Gzip).unwrap();
+        let encoded = encode(&gzipped, CompressionSchema::Br).unwrap();
+
+        assert_eq!(decode(&encoded, "gzip, br").unwrap(), CONTENT);
+        assert!(decode(&encoded, "br, gzip").is_err());
+    }
+
+    #[test]
+    fn unsupported_encodings_fail() {
+        let error = decode(CONTENT, "zstd").unwrap_err();
+        assert_eq!(error.to_string(), "Unsupported content encoding \"zstd\"");
+    }
+
+    #[test]
+    fn compressed_requests_list_the_encoding_last() {
+        let mut request = reqwest::Client::new()
+            .post("https://x.dev/a")
+            .header(reqwest::header::CONTENT_ENCODING, "gzip")
+            .header(reqwest::header::CONTENT_LENGTH, CONTENT.len())
+            .body(CONTENT)
+            .build()
+            .unwrap();
+
+        compress_body(&mut request, CompressionSchema::Br).unwrap();
+
+        let headers = request.headers();
+        assert_eq!(headers[reqwest::header::CONTENT_ENCODING], "gzip, br");
+        assert!(!headers.contains_key(reqwest::header::CONTENT_LENGTH));
+        let body = request.body().and_then(|body| body.as_bytes()).unwrap();
+        assert_eq!(decode(body, "br").unwrap(), CONTENT);
+    }
+
+    #[test]
+    fn requests_without_a_body_are_left_as_is() {
+        let mut request = reqwest::Client::new()
+            .get("https://x.dev/a")
+            .build()
+            .unwrap();
+
+        compress_body(&mut request, CompressionSchema::Gzip).unwrap();
+        assert!(request.body().is_none());
+        assert!(
+            !request
+                .headers()
+                .contains_key(reqwest::header::CONTENT_ENCODING)
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use std::collections::HashMap;
+
+use crate::{
+    runner::{CallResult, Runner},
+    schema::calls::SequenceContextSchema,
+    state::StateStore,
+};
+
+/// The responses and state of a run, swapped out while a sequence runs in its own context.
+pub struct RunContext {
+    pub responses: HashMap<String, CallResult>,
+    pub state: StateStore,
+}
+
+impl Runner {
+    // Gives the sequence the context it asks for, returning the one to restore after it ran.
+    // Shared sequences keep using the current context, so there's nothing to restore.
+    pub fn enter_context(&mut self, context: SequenceContextSchema) -> Option<RunContext> {
+        let (responses, state) = match context {
+            SequenceContextSchema::Shared => return None,
+            SequenceContextSchema::Inherit => (self.responses.clone(), self.state.scoped(true)),
+            SequenceContextSchema::Isolated => (HashMap::new(), self.state.scoped(false)),
+        };
+
+        return Some(RunContext {
+            responses: std::mem::replace(&mut self.responses, responses),
+            state: std::mem::replace(&mut self.state, state),
+        });
+    }
+
+    // Restores the context that was active before the sequence, discarding the sequence's own
+    pub fn exit_context(&mut self, parent: Option<RunContext>) {
+        if let Some(parent) = parent {
+            self.responses = parent.responses;
+            self.state = parent.state;
+        }
+    }
+}
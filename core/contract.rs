@@ -0,0 +1,437 @@
+use std::{collections::BTreeMap, path::Path};
+
+use anyhow::Context;
+use serde_json::{Map, Value};
+
+use crate::{
+    assertions::schema_violations,
+    runner::{CallResult, ResponseObject, Runner},
+};
+
+/// An OpenAPI spec the responses of a run are checked against: the path and method are documented,
+/// the status is one of the operation's responses, and the content type and body match it.
+#[derive(Debug, Clone)]
+pub struct Contract {
+    spec: Value,
+    // path of each server url, stripped from request paths (eg: /v1 for https://api.example.com/v1)
+    base_paths: Vec<String>,
+    // the violations found in the run, keyed by endpoint (eg: GET /users/{id})
+    pub violations: BTreeMap<String, Vec<String>>,
+}
+
+/// A way a response doesn't follow the spec.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractViolation {
+    pub endpoint: String,
+    pub message: String,
+}
+
+impl ContractViolation {
+    fn new(endpoint: &str, message: &str) -> ContractViolation {
+        return ContractViolation {
+            endpoint: endpoint.to_string(),
+            message: message.to_string(),
+        };
+    }
+}
+
+impl std::fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "contract ({}): {}", self.endpoint, self.message);
+    }
+}
+
+impl Contract {
+    // Reads a spec in json or yaml
+    pub async fn load(path: &Path) -> anyhow::Result<Contract> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read openapi spec {:?}", path))?;
+        // yaml is a superset of json, so both formats are read the same way
+        let spec = serde_yaml::from_str::<serde_yaml::Value>(&content)
+            .with_context(|| format!("Invalid openapi spec {:?}", path))?;
+
+        return Ok(Contract::new(serde_json::to_value(spec)?));
+    }
+
+    pub fn new(spec: Value) -> Contract {
+        let mut base_paths = spec["servers"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|server| server["url"].as_str())
+            .filter_map(|url| reqwest::Url::parse(url).ok())
+            .map(|url| url.path().trim_end_matches('/').to_string())
+            .filter(|path| !path.is_empty())
+            .collect::<Vec<String>>();
+        // the longest base path is stripped first
+        base_paths.sort_by_key(|path| std::cmp::Reverse(path.len()));
+
+        return Contract {
+            spec,
+            base_paths,
+            violations: BTreeMap::new(),
+        };
+    }
+
+    // The violations of a call, found against the operation of the spec matching it
+    pub fn check(
+        &self,
+        method: &str,
+        url: &str,
+        response: &ResponseObject,
+    ) -> Vec<ContractViolation> {
+        let method = method.to_lowercase();
+        let path = match reqwest::Url::parse(url) {
+            Ok(url) => url.path().to_string(),
+            Err(_) => url.to_string(),
+        };
+
+        let (template, item) = match self.find_path(&path) {
+            Some(found) => found,
+            None => {
+                let endpoint = format!("{} {}", method.to_uppercase(), path);
+                return vec![ContractViolation::new(&endpoint, "not in the spec")];
+            }
+        };
+        let endpoint = format!("{} {}", method.to_uppercase(), template);
+
+        let operation = match item.get(&method) {
+            Some(operation) => operation,
+            None => return vec![ContractViolation::new(&endpoint, "not in the spec")],
+        };
+
+        let documented = match find_response(&operation["responses"], response.status) {
+            Some(documented) => self.resolve(documented),
+            None => {
+                let message = format!("status {} is not documented", response.status);
+                return vec![ContractViolation::new(&endpoint, &message)];
+            }
+        };
+
+        // responses without content, eg: 204, have nothing more to check
+        let content = match documented["content"].as_object() {
+            Some(content) if !response.body.is_empty() => content,
+            _ => return vec![],
+        };
+
+        let content_type = response
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| {
+                value
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_lowercase()
+            })
+            .unwrap_or_default();
+        let media = match find_media_type(content, &content_type) {
+            Some(media) => media,
+            None => {
+                let message = format!(
+                    "content type \"{}\" is not one of {}",
+                    content_type,
+                    content.keys().cloned().collect::<Vec<String>>().join(", ")
+                );
+                return vec![ContractViolation::new(&endpoint, &message)];
+            }
+        };
+
+        let schema = match media.get("schema") {
+            Some(schema) => schema,
+            None => return vec![],
+        };
+        let body = match response.json() {
+            Ok(body) => body,
+            Err(_) if content_type.contains("json") => {
+                return vec![ContractViolation::new(
+                    &endpoint,
+                    "response body is not json",
+                )];
+            }
+            // only json bodies are validated against their schema
+            Err(_) => return vec![],
+        };
+
+        return match schema_violations(&self.with_components(schema), &body) {
+            Ok(violations) => violations
+                .iter()
+                .map(|violation| ContractViolation::new(&endpoint, violation))
+                .collect(),
+            Err(e) => vec![ContractViolation::new(&endpoint, &format!("{:#}", e))],
+        };
+    }
+
+    // The path item matching a request path, with its template (eg: /users/{id})
+    fn find_path(&self, path: &str) -> Option<(String, &Value)> {
+        let paths = self.spec["paths"].as_object()?;
+
+        let mut candidates = vec![path.to_string()];
+        for base in &self.base_paths {
+            if let Some(rest) = path.strip_prefix(base.as_str()) {
+                candidates.push(rest.to_string());
+            }
+        }
+
+        for candidate in candidates {
+            // literal paths win over templated ones, like /users/me over /users/{id}
+            if let Some(item) = paths.get(&candidate) {
+                return Some((candidate, self.resolve(item)));
+            }
+
+            let found = paths
+                .iter()
+                .find(|(template, _)| matches_template(template, &candidate));
+            if let Some((template, item)) = found {
+                return Some((template.clone(), self.resolve(item)));
+            }
+        }
+
+        return None;
+    }
+
+    // Follows a local $ref (eg: #/components/responses/NotFound)
+    fn resolve<'a>(&'a self, value: &'a Value) -> &'a Value {
+        return match value["$ref"]
+            .as_str()
+            .and_then(|reference| reference.strip_prefix('#'))
+        {
+            Some(pointer) => self.spec.pointer(pointer).unwrap_or(value),
+            None => value,
+        };
+    }
+
+    // The schema with the spec's components alongside, so its $refs resolve
+    fn with_components(&self, schema: &Value) -> Value {
+        let mut document = match schema {
+            Value::Object(schema) => schema.clone(),
+            other => return other.clone(),
+        };
+        if let Some(components) = self.spec.get("components") {
+            document.insert("components".to_string(), components.clone());
+        }
+
+        return Value::Object(document);
+    }
+}
+
+// Segments match literally, or any value for a {param}
+fn matches_template(template: &str, path: &str) -> bool {
+    let template = template
+        .trim_end_matches('/')
+        .split('/')
+        .collect::<Vec<&str>>();
+    let path = path.trim_end_matches('/').split('/').collect::<Vec<&str>>();
+
+    return template.len() == path.len()
+        && template.iter().zip(path.iter()).all(|(expected, actual)| {
+            (expected.starts_with('{') && expected.ends_with('}') && !actual.is_empty())
+                || expected == actual
+        });
+}
+
+// The documented response of a status: the exact code, then its range (eg: 2XX), then the default
+fn find_response(responses: &Value, status: u16) -> Option<&Value> {
+    let responses = responses.as_object()?;
+    let range = format!("{}XX", status / 100);
+
+    return responses
+        .get(&status.to_string())
+        .or_else(|| {
+            responses
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&range))
+                .map(|(_, value)| value)
+        })
+        .or_else(|| responses.get("default"));
+}
+
+// The media type of a content type, wildcards like application/* and */* included
+fn find_media_type<'a>(content: &'a Map<String, Value>, content_type: &str) -> Option<&'a Value> {
+    let kind = content_type.split('/').next().unwrap_or_default();
+
+    return content
+        .get(content_type)
+        .or_else(|| content.get(&format!("{}/*", kind)))
+        .or_else(|| content.get("*/*"));
+}
+
+impl Runner {
+    // Checks the responses of the run against an openapi spec, the project's openapi file by default
+    pub async fn enable_contract(&mut self, path: Option<&Path>) -> anyhow::Result<()> {
+        let path = match (path, &self.project.object.openapi) {
+            (Some(path), _) => path.to_path_buf(),
+            (None, Some(spec)) => self.project.get_root_dir().join(spec),
+            (None, None) => {
+                anyhow::bail!("The project has no openapi spec to check responses against")
+            }
+        };
+
+        self.contract = Some(Contract::load(&path).await?);
+        return Ok(());
+    }
+
+    // Checks a call against the contract, the violations are kept by endpoint for the end of the run
+    pub(crate) fn check_contract(&mut self, result: &CallResult) -> Vec<String> {
+        let contract = match &mut self.contract {
+            Some(contract) => contract,
+            None => return vec![],
+        };
+
+        let violations = contract.check(
+            &result.request.method,
+            &result.request.url,
+            &result.response,
+        );
+        for violation in &violations {
+            contract
+                .violations
+                .entry(violation.endpoint.clone())
+                .or_default()
+                .push(violation.message.clone());
+        }
+
+        return violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn contract() -> Contract {
+        return Contract::new(json!({
+            "servers": [{ "url": "https://api.x.dev/v1" }],
+            "paths": {
+                "/users/{id}": {
+                    "get": { "responses": {
+                        "200": { "content": { "application/json": {
+                            "schema": { "$ref": "#/components/schemas/User" }
+                        } } },
+                        "4XX": { "$ref": "#/components/responses/Error" },
+                    } },
+                    "delete": { "responses": { "204": { "description": "Deleted" } } },
+                },
+                "/users/me": {
+                    "get": { "responses": { "200": { "content": { "text/*": {} } } } },
+                },
+            },
+            "components": {
+                "schemas": {
+                    "User": {
+                        "type": "object",
+                        "required": ["id"],
+                        "properties": { "id": { "type": "integer" } },
+                    },
+                },
+                "responses": {
+                    "Error": { "content": { "application/json": { "schema": { "type": "object" } } } },
+                },
+            },
+        }));
+    }
+
+    fn response(status: u16, content_type: &str, body: &str) -> ResponseObject {
+        return ResponseObject {
+            status,
+            headers: [("Content-Type".to_string(), content_type.to_string())].into(),
+            body: body.as_bytes().to_vec(),
+            ..Default::default()
+        };
+    }
+
+    fn messages(violations: Vec<ContractViolation>) -> Vec<String> {
+        return violations
+            .iter()
+            .map(|violation| violation.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn documented_responses_pass() {
+        let contract = contract();
+        let url = "https://api.x.dev/v1/users/1";
+
+        let ok = response(200, "application/json; charset=utf-8", "{\"id\": 1}");
+        assert!(contract.check("GET", url, &ok).is_empty());
+        // the range and the $ref of the response are followed
+        let not_found = response(404, "application/json", "{\"error\": \"nope\"}");
+        assert!(contract.check("GET", url, &not_found).is_empty());
+        assert!(
+            contract
+                .check("DELETE", url, &response(204, "", ""))
+                .is_empty()
+        );
+        // literal paths win over templates, and wildcards match the content type
+        let me = response(200, "text/plain", "me");
+        assert!(
+            contract
+                .check("GET", "https://api.x.dev/v1/users/me", &me)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn undocumented_calls_fail() {
+        let contract = contract();
+        let ok = response(200, "application/json", "{\"id\": 1}");
+
+        assert_eq!(
+            messages(contract.check("GET", "https://api.x.dev/v1/posts", &ok)),
+            vec!["contract (GET /v1/posts): not in the spec"]
+        );
+        assert_eq!(
+            messages(contract.check("PUT", "https://api.x.dev/v1/users/1", &ok)),
+            vec!["contract (PUT /users/{id}): not in the spec"]
+        );
+        assert_eq!(
+            messages(contract.check(
+                "GET",
+                "https://api.x.dev/v1/users/1",
+                &response(500, "application/json", "{}")
+            )),
+            vec!["contract (GET /users/{id}): status 500 is not documented"]
+        );
+    }
+
+    #[test]
+    fn bodies_that_dont_match_fail() {
+        let contract = contract();
+        let url = "https://api.x.dev/v1/users/1";
+
+        assert_eq!(
+            messages(contract.check("GET", url, &response(200, "text/html", "<p>"))),
+            vec![
+                "contract (GET /users/{id}): content type \"text/html\" is not one of application/json"
+            ]
+        );
+        assert_eq!(
+            messages(contract.check("GET", url, &response(200, "application/json", "{"))),
+            vec!["contract (GET /users/{id}): response body is not json"]
+        );
+
+        let violations = contract.check(
+            "GET",
+            url,
+            &response(200, "application/json", "{\"id\": \"1\"}"),
+        );
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].endpoint, "GET /users/{id}");
+    }
+
+    #[test]
+    fn templates_match_whole_segments() {
+        assert!(matches_template("/users/{id}", "/users/1"));
+        assert!(matches_template("/users/{id}/", "/users/1"));
+        assert!(!matches_template("/users/{id}", "/users/"));
+        assert!(!matches_template("/users/{id}", "/users/1/posts"));
+    }
+}
@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    history::HistoryEntry,
+    runner::{ResponseObject, Runner},
+};
+
+/// Paths to leave out when diffing two responses.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DiffOptions {
+    // dotted json paths in the body, `*` matches any key or index (eg: data.*.updated_at)
+    #[serde(default)]
+    pub ignore_paths: Vec<String>,
+    // header names, compared case-insensitively
+    #[serde(default)]
+    pub ignore_headers: Vec<String>,
+}
+
+/// A single value that differs between the left and right response.
+/// A missing side means the value only exists on the other one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Difference {
+    pub path: String,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+impl Difference {
+    pub fn is_added(&self) -> bool {
+        return self.left.is_none();
+    }
+
+    pub fn is_removed(&self) -> bool {
+        return self.right.is_none();
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ResponseDiff {
+    pub name: String,
+    pub status: Option<(u16, u16)>,
+    pub headers: Vec<Difference>,
+    pub body: Vec<Difference>,
+}
+
+impl ResponseDiff {
+    pub fn is_empty(&self) -> bool {
+        return self.status.is_none() && self.headers.is_empty() && self.body.is_empty();
+    }
+}
+
+pub fn diff_responses(
+    name: &str,
+    left: &ResponseObject,
+    right: &ResponseObject,
+    options: &DiffOptions,
+) -> ResponseDiff {
+    return diff_parts(
+        name,
+        (left.status, &left.headers, normalize_body(left)),
+        (right.status, &right.headers, normalize_body(right)),
+        options,
+    );
+}
+
+// Diffs the responses recorded in two history entries, eg: to find what changed since a call that worked
+pub fn diff_history_entries(
+    left: &HistoryEntry,
+    right: &HistoryEntry,
+    options: &DiffOptions,
+) -> ResponseDiff {
+    let (l, r) = (&left.exchange.response, &right.exchange.response);
+    let normalize = |body: &String| {
+        serde_json::from_str::<Value>(body).unwrap_or_else(|_| Value::String(body.clone()))
+    };
+
+    return diff_parts(
+        &right.exchange.name,
+        (l.status, &l.headers, normalize(&l.body)),
+        (r.status, &r.headers, normalize(&r.body)),
+        options,
+    );
+}
+
+// Diffs the status, headers and normalized body of two responses
+fn diff_parts(
+    name: &str,
+    (left_status, left_headers, left_body): (u16, &HashMap<String, String>, Value),
+    (right_status, right_headers, right_body): (u16, &HashMap<String, String>, Value),
+    options: &DiffOptions,
+) -> ResponseDiff {
+    let status = if left_status != right_status {
+        Some((left_status, right_status))
+    } else {
+        None
+    };
+
+    let mut headers = vec![];
+    let mut names = left_headers
+        .keys()
+        .chain(right_headers.keys())
+        .collect::<Vec<&String>>();
+    names.sort();
+    names.dedup();
+
+    for header in names {
+        let ignored = options
+            .ignore_headers
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(header));
+
+        let l = left_headers.get(header);
+        let r = right_headers.get(header);
+        if ignored || l == r {
+            continue;
+        }
+
+        headers.push(Difference {
+            path: header.clone(),
+            left: l.map(|v| Value::String(v.clone())),
+            right: r.map(|v| Value::String(v.clone())),
+        });
+    }
+
+    let mut body = vec![];
+    diff_json(
+        &mut vec![],
+        &left_body,
+        &right_body,
+        &options.ignore_paths,
+        &mut body,
+    );
+
+    return ResponseDiff {
+        name: name.to_string(),
+        status,
+        headers,
+        body,
+    };
+}
+
+// Parses the body as json when possible so formatting and key order don't show up as changes
+pub(crate) fn normalize_body(response: &ResponseObject) -> Value {
+    return match response.json() {
+        Ok(value) => value,
+        Err(_) => Value::String(response.text()),
+    };
+}
+
+pub fn diff_json(
+    path: &mut Vec<String>,
+    left: &Value,
+    right: &Value,
+    ignore_paths: &[String],
+    out: &mut Vec<Difference>,
+) {
+    if is_ignored(path, ignore_paths) {
+        return;
+    }
+
+    match (left, right) {
+        (Value::Object(l), Value::Object(r)) => {
+            let mut keys = l.keys().chain(r.keys()).collect::<Vec<&String>>();
+            keys.sort();
+            keys.dedup();
+
+            for key in keys {
+                path.push(key.clone());
+                match (l.get(key), r.get(key)) {
+                    (Some(lv), Some(rv)) => diff_json(path, lv, rv, ignore_paths, out),
+                    (lv, rv) => push_difference(path, lv, rv, ignore_paths, out),
+                };
+                path.pop();
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for index in 0..l.len().max(r.len()) {
+                path.push(index.to_string());
+                match (l.get(index), r.get(index)) {
+                    (Some(lv), Some(rv)) => diff_json(path, lv, rv, ignore_paths, out),
+                    (lv, rv) => push_difference(path, lv, rv, ignore_paths, out),
+                };
+                path.pop();
+            }
+        }
+        (l, r) => {
+            if l != r {
+                push_difference(path, Some(l), Some(r), ignore_paths, out);
+            }
+        }
+    };
+}
+
+fn push_difference(
+    path: &[String],
+    left: Option<&Value>,
+    right: Option<&Value>,
+    ignore_paths: &[String],
+    out: &mut Vec<Difference>,
+) {
+    if is_ignored(path, ignore_paths) {
+        return;
+    }
+
+    out.push(Difference {
+        path: path.join("."),
+        left: left.cloned(),
+        right: right.cloned(),
+    });
+}
+
+fn is_ignored(path: &[String], ignore_paths: &[String]) -> bool {
+    return ignore_paths.iter().any(|pattern| {
+        let segments = pattern.split('.').collect::<Vec<&str>>();
+        segments.len() == path.len()
+            && segments
+                .iter()
+                .zip(path.iter())
+                .all(|(pattern, segment)| *pattern == "*" || *pattern == segment.as_str())
+    });
+}
+
+impl Runner {
+    // Calls the request once in each environment and diffs the responses
+    pub async fn compare_request(
+        &mut self,
+        name: &str,
+        left_environment: &str,
+        right_environment: &str,
+        options: &DiffOptions,
+    ) -> anyhow::Result<ResponseDiff> {
+        let diffs = self
+            .compare(
+                vec![name.to_string()],
+                left_environment,
+                right_environment,
+                options,
+            )
+            .await?;
+
+        return match diffs.into_iter().next() {
+            Some(diff) => Ok(diff),
+            None => anyhow::bail!("Request \"{}\" was not called", name),
+        };
+    }
+
+    // Runs the sequence once in each environment and diffs the response of each step
+    pub async fn compare_sequence(
+        &mut self,
+        name: &str,
+        left_environment: &str,
+        right_environment: &str,
+        options: &DiffOptions,
+    ) -> anyhow::Result<Vec<ResponseDiff>> {
+        let sequence = self.get_sequence(name)?;
+        return self
+            .compare(sequence, left_environment, right_environment, options)
+            .await;
+    }
+
+    async fn compare(
+        &mut self,
+        requests: Vec<String>,
+        left_environment: &str,
+        right_environment: &str,
+        options: &DiffOptions,
+    ) -> anyhow::Result<Vec<ResponseDiff>> {
+        let previous_environment = self.environment.clone();
+
+        let left = self.call_in_environment(&requests, left_environment).await;
+        let right = self.call_in_environment(&requests, right_environment).await;
+
+        self.environment = previous_environment;
+        self.responses.clear();
+
+        let (left, right) = (left?, right?);
+        let mut result = vec![];
+
+        for (index, name) in requests.iter().enumerate() {
+            result.push(diff_responses(name, &left[index], &right[index], options));
+        }
+
+        return Ok(result);
+    }
+
+    async fn call_in_environment(
+        &mut self,
+        requests: &Vec<String>,
+        environment: &str,
+    ) -> anyhow::Result<Vec<ResponseObject>> {
+        self.environment = Some(environment.to_string());
+        self.responses.clear();
+
+        let mut result = vec![];
+        for name in requests {
+            result.push(self.call_request(name).await?.response);
+        }
+
+        return Ok(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{
+        fs::FileObject,
+        schema::roots::{ProjectRootSchema, RequestRootSchema},
+    };
+
+    fn diff(left: Value, right: Value, ignore_paths: &[&str]) -> Vec<Difference> {
+        let ignore_paths = ignore_paths
+            .iter()
+            .map(|path| path.to_string())
+            .collect::<Vec<String>>();
+        let mut out = vec![];
+        diff_json(&mut vec![], &left, &right, &ignore_paths, &mut out);
+        return out;
+    }
+
+    fn difference(path: &str, left: Option<Value>, right: Option<Value>) -> Difference {
+        return Difference {
+            path: path.to_string(),
+            left,
+            right,
+        };
+    }
+
+    fn response(status: u16, headers: &[(&str, &str)], body: &str) -> ResponseObject {
+        return ResponseObject {
+            status,
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+            ..Default::default()
+        };
+    }
+
+    #[test]
+    fn added_removed_and_changed_keys() {
+        let differences = diff(
+            json!({ "id": 1, "name": "a", "user": { "email": "a@x.dev", "age": 3 } }),
+            json!({ "id": 1, "name": "b", "user": { "age": 3, "admin": true } }),
+            &[],
+        );
+
+        assert_eq!(
+            differences,
+            vec![
+                difference("name", Some(json!("a")), Some(json!("b"))),
+                difference("user.admin", None, Some(json!(true))),
+                difference("user.email", Some(json!("a@x.dev")), None),
+            ]
+        );
+        assert!(differences[1].is_added());
+        assert!(differences[2].is_removed());
+    }
+
+    #[test]
+    fn arrays_are_compared_by_index() {
+        let differences = diff(
+            json!({ "items": [1, { "id": 2 }, 3] }),
+            json!({ "items": [1, { "id": 4 }] }),
+            &[],
+        );
+        assert_eq!(
+            differences,
+            vec![
+                difference("items.1.id", Some(json!(2)), Some(json!(4))),
+                difference("items.2", Some(json!(3)), None),
+            ]
+        );
+
+        let differences = diff(json!([]), json!(["a"]), &[]);
+        assert_eq!(differences, vec![difference("0", None, Some(json!("a")))]);
+    }
+
+    #[test]
+    fn values_of_different_types_are_changed() {
+        assert_eq!(
+            diff(json!({ "a": [1] }), json!({ "a": { "0": 1 } }), &[]),
+            vec![difference("a", Some(json!([1])), Some(json!({ "0": 1 })))]
+        );
+        assert!(diff(json!({ "a": [1, 2] }), json!({ "a": [1, 2] }), &[]).is_empty());
+    }
+
+    #[test]
+    fn ignored_paths_match_any_key_with_a_star() {
+        let differences = diff(
+            json!({ "data": [{ "id": 1, "updated_at": "1" }], "at": "1" }),
+            json!({ "data": [{ "id": 2, "updated_at": "2" }, { "updated_at": "3" }], "at": "2" }),
+            &["data.*.updated_at", "at"],
+        );
+        assert_eq!(
+            differences,
+            vec![
+                difference("data.0.id", Some(json!(1)), Some(json!(2))),
+                difference("data.1", None, Some(json!({ "updated_at": "3" }))),
+            ]
+        );
+    }
+
+    #[test]
+    fn responses_diff_status_headers_and_body() {
+        let options = DiffOptions {
+            ignore_headers: vec!["date".to_string()],
+            ..Default::default()
+        };
+        let diff = diff_responses(
+            "get",
+            &response(200, &[("Date", "1"), ("etag", "a")], "{\"a\": 1, \"b\": 2}"),
+            &response(
+                201,
+                &[("Date", "2"), ("etag", "b")],
+                "{\"b\": 2,\n \"a\": 1}",
+            ),
+            &options,
+        );
+
+        assert_eq!(diff.status, Some((200, 201)));
+        assert_eq!(
+            diff.headers,
+            vec![difference("etag", Some(json!("a")), Some(json!("b")))]
+        );
+        assert!(diff.body.is_empty());
+        assert!(!diff.is_empty());
+
+        let same = diff_responses(
+            "get",
+            &response(200, &[], "a"),
+            &response(200, &[], "a"),
+            &options,
+        );
+        assert!(same.is_empty());
+    }
+
+    // Answers /<environment>/user with a user that differs between environments
+    async fn serve_user() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let head = String::from_utf8_lossy(&request[..read]).to_string();
+                let target = head.split(' ').nth(1).unwrap_or_default().to_string();
+
+                let body = match target.as_str() {
+                    "/staging/user" => "{\"id\": 1, \"name\": \"a\", \"updated_at\": \"1\"}",
+                    _ => "{\"id\": 1, \"name\": \"b\", \"updated_at\": \"2\", \"admin\": true}",
+                };
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    #[tokio::test]
+    async fn a_request_is_compared_across_two_environments() {
+        let url = serve_user().await;
+        let root = std::env::temp_dir().join(format!("dotapi-diff-{}", uuid::Uuid::new_v4()));
+        let project = serde_yaml::from_str::<ProjectRootSchema>(
+            "project: {name: diff}\ncalls: {main: [user]}\nenv:\n  stage:\n    default: staging\n    staging: staging\n    prod: prod",
+        )
+        .unwrap();
+        let request = serde_yaml::from_str::<RequestRootSchema>(&format!(
+            "method: GET\nurl: {}/{{{{stage}}}}/user",
+            url
+        ))
+        .unwrap();
+        let mut runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), project),
+            vec![FileObject::new(root.join("requests/user.nd"), request)],
+        )
+        .unwrap();
+        let options = DiffOptions {
+            ignore_paths: vec!["updated_at".to_string()],
+            ..Default::default()
+        };
+
+        let diff = runner
+            .compare_request("user", "staging", "prod", &options)
+            .await
+            .unwrap();
+
+        assert_eq!(diff.name, "user");
+        assert_eq!(diff.status, None);
+        assert_eq!(
+            diff.body,
+            vec![
+                difference("admin", None, Some(json!(true))),
+                difference("name", Some(json!("a")), Some(json!("b"))),
+            ]
+        );
+        // the runner is left in the environment it was in
+        assert_eq!(runner.environment, None);
+        assert!(!runner.responses.contains_key("user"));
+    }
+}
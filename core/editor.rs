@@ -0,0 +1,433 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde_yaml::{Mapping, Value};
+
+use crate::schema::roots::ProjectRootSchema;
+
+const DEFAULT_KEY: &str = "default";
+
+// fields of a variable, the other keys of a variable are environments
+const RESERVED_KEYS: [&str; 5] = [DEFAULT_KEY, "type", "required", "choices", "os_env"];
+
+/// Edits the env section and base_url of a project file and writes it back.
+///
+/// Edits are made on the yaml document itself rather than on `ProjectRootSchema`,
+/// so key order and fields the schema doesn't know about are kept when saving.
+/// Comments are not kept, serde_yaml drops them when parsing.
+pub struct SchemaEditor {
+    pub path: PathBuf,
+    document: Value,
+}
+
+impl SchemaEditor {
+    pub async fn open(path: &Path) -> anyhow::Result<SchemaEditor> {
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .context("Failed to open project file")?;
+
+        let document = serde_yaml::from_str::<Value>(&content)?;
+        if !document.is_mapping() {
+            anyhow::bail!("Project file must be a mapping");
+        }
+
+        return Ok(SchemaEditor {
+            path: path.to_path_buf(),
+            document,
+        });
+    }
+
+    // The schema as it will be saved
+    pub fn schema(&self) -> anyhow::Result<ProjectRootSchema> {
+        return serde_yaml::from_value::<ProjectRootSchema>(self.document.clone())
+            .context("Edited project is not valid");
+    }
+
+    pub fn environments(&self) -> anyhow::Result<Vec<String>> {
+        return Ok(self.schema()?.environments());
+    }
+
+    // Adds an environment, with every variable and the base_url starting out with their default value
+    pub fn add_environment(&mut self, name: &str) -> anyhow::Result<()> {
+        validate_environment_name(name)?;
+
+        if self.environments()?.iter().any(|e| e == name) {
+            anyhow::bail!("Environment \"{}\" already exists", name);
+        }
+
+        for definition in self.definitions_mut()? {
+            let default = definition.get(DEFAULT_KEY).cloned().unwrap_or(Value::Null);
+            definition.insert(Value::String(name.to_string()), default);
+        }
+
+        return Ok(());
+    }
+
+    pub fn remove_environment(&mut self, name: &str) -> anyhow::Result<()> {
+        validate_environment_name(name)?;
+
+        for definition in self.definitions_mut()? {
+            definition.remove(name);
+        }
+
+        return Ok(());
+    }
+
+    pub fn rename_environment(&mut self, name: &str, new_name: &str) -> anyhow::Result<()> {
+        validate_environment_name(name)?;
+        validate_environment_name(new_name)?;
+
+        if self.environments()?.iter().any(|e| e == new_name) {
+            anyhow::bail!("Environment \"{}\" already exists", new_name);
+        }
+
+        for definition in self.definitions_mut()? {
+            if let Some(value) = definition.remove(name) {
+                definition.insert(Value::String(new_name.to_string()), value);
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Sets the value of a variable for an environment, or its default when environment is None.
+    // The variable is created if it doesn't exist.
+    pub fn set_variable(
+        &mut self,
+        variable: &str,
+        environment: Option<&str>,
+        value: Value,
+    ) -> anyhow::Result<()> {
+        if let Some(environment) = environment {
+            validate_environment_name(environment)?;
+        }
+
+        let variables = self.variables_mut()?;
+        let key = Value::String(variable.to_string());
+
+        if !variables.contains_key(&key) {
+            let mut definition = Mapping::new();
+            definition.insert(Value::String(DEFAULT_KEY.to_string()), Value::Null);
+            variables.insert(key.clone(), Value::Mapping(definition));
+        }
+
+        let definition = match variables.get_mut(&key) {
+            Some(Value::Mapping(definition)) => definition,
+            _ => anyhow::bail!("Variable \"{}\" is not a mapping", variable),
+        };
+
+        definition.insert(
+            Value::String(environment.unwrap_or(DEFAULT_KEY).to_string()),
+            value,
+        );
+
+        return Ok(());
+    }
+
+    // Removes the override of a variable for an environment, so it falls back to its default
+    pub fn unset_override(&mut self, variable: &str, environment: &str) -> anyhow::Result<()> {
+        validate_environment_name(environment)?;
+
+        if let Some(Value::Mapping(definition)) = self.variables_mut()?.get_mut(variable) {
+            definition.remove(environment);
+        }
+
+        return Ok(());
+    }
+
+    pub fn remove_variable(&mut self, variable: &str) -> anyhow::Result<()> {
+        self.variables_mut()?.remove(variable);
+        return self.set_secret(variable, false);
+    }
+
+    // Hides the value of a variable when requests leave the runner, listing it in redact.variables
+    pub fn set_secret(&mut self, variable: &str, secret: bool) -> anyhow::Result<()> {
+        let root = match &mut self.document {
+            Value::Mapping(root) => root,
+            _ => anyhow::bail!("Project file must be a mapping"),
+        };
+
+        if !matches!(root.get("redact"), Some(Value::Mapping(_))) {
+            root.insert(
+                Value::String("redact".to_string()),
+                Value::Mapping(Mapping::new()),
+            );
+        }
+        let redact = match root.get_mut("redact") {
+            Some(Value::Mapping(redact)) => redact,
+            _ => anyhow::bail!("redact must be a mapping"),
+        };
+
+        let mut variables = match redact.remove("variables") {
+            Some(Value::Sequence(variables)) => variables,
+            _ => vec![],
+        };
+        variables.retain(|name| name.as_str() != Some(variable));
+        if secret {
+            variables.push(Value::String(variable.to_string()));
+        }
+
+        // an empty list isn't written, nor the redact section when it has nothing else
+        if !variables.is_empty() {
+            redact.insert(
+                Value::String("variables".to_string()),
+                Value::Sequence(variables),
+            );
+        }
+        if redact.is_empty() {
+            root.remove("redact");
+        }
+
+        return Ok(());
+    }
+
+    // Writes the document back to the project file, after checking it is still a valid project
+    pub async fn save(&self) -> anyhow::Result<()> {
+        self.schema()?;
+
+        let content = serde_yaml::to_string(&self.document)?;
+        tokio::fs::write(&self.path, content)
+            .await
+            .context("Failed to write project file")?;
+
+        return Ok(());
+    }
+
+    // The definitions keyed by environment: every variable, and the base_url
+    fn definitions_mut(&mut self) -> anyhow::Result<Vec<&mut Mapping>> {
+        let root = match &mut self.document {
+            Value::Mapping(root) => root,
+            _ => anyhow::bail!("Project file must be a mapping"),
+        };
+
+        let mut definitions = vec![];
+        for (key, value) in root.iter_mut() {
+            match (key.as_str(), value) {
+                (Some("env"), Value::Mapping(variables)) => {
+                    for (_, variable) in variables.iter_mut() {
+                        if let Value::Mapping(variable) = variable {
+                            definitions.push(variable);
+                        }
+                    }
+                }
+                (Some("base_url"), Value::Mapping(base_url)) => definitions.push(base_url),
+                _ => {}
+            };
+        }
+
+        return Ok(definitions);
+    }
+
+    fn variables_mut(&mut self) -> anyhow::Result<&mut Mapping> {
+        let root = match &mut self.document {
+            Value::Mapping(root) => root,
+            _ => anyhow::bail!("Project file must be a mapping"),
+        };
+
+        let key = Value::String("env".to_string());
+        if !matches!(root.get(&key), Some(Value::Mapping(_))) {
+            root.insert(key.clone(), Value::Mapping(Mapping::new()));
+        }
+
+        return match root.get_mut(&key) {
+            Some(Value::Mapping(variables)) => Ok(variables),
+            _ => anyhow::bail!("env must be a mapping"),
+        };
+    }
+}
+
+fn validate_environment_name(name: &str) -> anyhow::Result<()> {
+    if name.trim().is_empty() {
+        anyhow::bail!("Environment name cannot be empty");
+    }
+
+    if RESERVED_KEYS.contains(&name) {
+        anyhow::bail!(
+            "\"{}\" is a field of variables, it can't name an environment",
+            name
+        );
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PROJECT: &str = "project:
+  name: editor
+env:
+  token:
+    default: abc
+    staging: def
+  user:
+    default: admin
+base_url:
+  default: http://localhost
+  staging: https://staging.example.com
+x-note: kept
+calls:
+  main: []
+";
+
+    async fn editor() -> SchemaEditor {
+        let root = std::env::temp_dir().join(format!("dotapi-editor-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&root).await.unwrap();
+
+        let path = root.join(".nd-project");
+        tokio::fs::write(&path, PROJECT).await.unwrap();
+        return SchemaEditor::open(&path).await.unwrap();
+    }
+
+    fn value(editor: &SchemaEditor, variable: &str, environment: &str) -> Option<Value> {
+        let schema = editor.schema().unwrap();
+        return schema.env[variable].overrides.get(environment).cloned();
+    }
+
+    #[tokio::test]
+    async fn added_environments_start_with_the_defaults() {
+        let mut editor = editor().await;
+        editor.add_environment("prod").unwrap();
+
+        assert_eq!(editor.environments().unwrap(), vec!["prod", "staging"]);
+        assert_eq!(value(&editor, "token", "prod"), Some(Value::from("abc")));
+        assert_eq!(value(&editor, "user", "prod"), Some(Value::from("admin")));
+    }
+
+    #[tokio::test]
+    async fn environments_cant_be_added_twice_or_named_after_variable_fields() {
+        let mut editor = editor().await;
+
+        assert!(editor.add_environment("staging").is_err());
+        assert!(editor.add_environment(" ").is_err());
+        for field in ["default", "type", "required", "choices", "os_env"] {
+            assert!(editor.add_environment(field).is_err());
+            assert!(editor.rename_environment("staging", field).is_err());
+        }
+        assert_eq!(editor.environments().unwrap(), vec!["staging"]);
+    }
+
+    #[tokio::test]
+    async fn base_url_follows_the_environments() {
+        let mut editor = editor().await;
+        let base_url = |editor: &SchemaEditor, environment: &str| {
+            let schema = editor.schema().unwrap();
+            return schema.base_url.unwrap().overrides.get(environment).cloned();
+        };
+
+        editor.add_environment("prod").unwrap();
+        assert_eq!(
+            base_url(&editor, "prod"),
+            Some(Value::from("http://localhost"))
+        );
+
+        editor.rename_environment("staging", "qa").unwrap();
+        assert_eq!(base_url(&editor, "staging"), None);
+        assert_eq!(
+            base_url(&editor, "qa"),
+            Some(Value::from("https://staging.example.com"))
+        );
+
+        editor.remove_environment("qa").unwrap();
+        assert_eq!(base_url(&editor, "qa"), None);
+        assert_eq!(editor.environments().unwrap(), vec!["prod"]);
+    }
+
+    #[tokio::test]
+    async fn renamed_environments_keep_their_values() {
+        let mut editor = editor().await;
+        editor.rename_environment("staging", "qa").unwrap();
+
+        assert_eq!(editor.environments().unwrap(), vec!["qa"]);
+        assert_eq!(value(&editor, "token", "qa"), Some(Value::from("def")));
+        assert!(editor.rename_environment("qa", "qa").is_err());
+    }
+
+    #[tokio::test]
+    async fn removed_environments_are_gone_from_every_variable() {
+        let mut editor = editor().await;
+        editor.remove_environment("staging").unwrap();
+
+        assert!(editor.environments().unwrap().is_empty());
+        assert_eq!(value(&editor, "token", "staging"), None);
+    }
+
+    #[tokio::test]
+    async fn setting_a_variable_creates_it_and_unsetting_falls_back_to_the_default() {
+        let mut editor = editor().await;
+        editor
+            .set_variable("region", Some("staging"), Value::from("eu"))
+            .unwrap();
+        editor
+            .set_variable("token", None, Value::from("xyz"))
+            .unwrap();
+
+        let schema = editor.schema().unwrap();
+        assert_eq!(schema.env["region"].default, Value::Null);
+        assert_eq!(value(&editor, "region", "staging"), Some(Value::from("eu")));
+        assert_eq!(schema.env["token"].default, Value::from("xyz"));
+
+        editor.unset_override("token", "staging").unwrap();
+        assert_eq!(value(&editor, "token", "staging"), None);
+
+        editor.remove_variable("region").unwrap();
+        assert!(!editor.schema().unwrap().env.contains_key("region"));
+    }
+
+    #[tokio::test]
+    async fn secrets_are_listed_in_redact_variables() {
+        let mut editor = editor().await;
+        editor.set_secret("token", true).unwrap();
+        editor.set_secret("token", true).unwrap();
+        assert_eq!(editor.schema().unwrap().redact.variables, vec!["token"]);
+
+        editor.set_secret("token", false).unwrap();
+        assert!(editor.schema().unwrap().redact.variables.is_empty());
+        assert!(
+            !serde_yaml::to_string(&editor.document)
+                .unwrap()
+                .contains("redact")
+        );
+
+        // removed variables aren't secret anymore
+        editor.set_secret("user", true).unwrap();
+        editor.remove_variable("user").unwrap();
+        assert!(!editor.schema().unwrap().is_secret("user"));
+    }
+
+    #[tokio::test]
+    async fn saving_keeps_key_order_and_unknown_fields() {
+        let mut editor = editor().await;
+        editor
+            .set_variable("token", Some("staging"), Value::from("ghi"))
+            .unwrap();
+        editor.save().await.unwrap();
+
+        let content = tokio::fs::read_to_string(&editor.path).await.unwrap();
+        let project = content.find("project:").unwrap();
+        let env = content.find("env:").unwrap();
+        let calls = content.find("calls:").unwrap();
+        assert!(project < env && env < calls);
+        assert!(content.find("token:").unwrap() < content.find("user:").unwrap());
+        assert!(content.contains("x-note: kept"));
+
+        let reopened = SchemaEditor::open(&editor.path).await.unwrap();
+        assert_eq!(
+            value(&reopened, "token", "staging"),
+            Some(Value::from("ghi"))
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_projects_are_not_saved() {
+        let mut editor = editor().await;
+        if let Value::Mapping(root) = &mut editor.document {
+            root.remove("calls");
+        }
+
+        assert!(editor.save().await.is_err());
+        let content = tokio::fs::read_to_string(&editor.path).await.unwrap();
+        assert_eq!(content, PROJECT);
+    }
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use crate::{
+    interpolation::{EnvMap, interpolate_value, placeholders, value_to_string},
+    runner::Runner,
+    schema::{
+        env::{EnvironmentVariableSchema, VariableTypeSchema},
+        roots::ProjectRootSchema,
+    },
+};
+
+/// Where the value of an env variable comes from. Each layer overrides the ones before it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EnvSource {
+    // the variable's default in the project file
+    Default,
+    // the override of the selected environment
+    Environment(String),
+    // the os environment variable mapped by the variable's os_env, eg: API_KEY
+    OsEnv(String),
+    // given when starting the run, eg: --var on the cli
+    Variable,
+    // set by the run, eg: by captures
+    State,
+}
+
+impl std::fmt::Display for EnvSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            EnvSource::Default => write!(f, "project default"),
+            EnvSource::Environment(environment) => write!(f, "{} environment", environment),
+            EnvSource::OsEnv(name) => write!(f, "os environment ${}", name),
+            EnvSource::Variable => write!(f, "--var"),
+            EnvSource::State => write!(f, "set by the run"),
+        };
+    }
+}
+
+impl ProjectRootSchema {
+    // Resolves the value of each env variable for the given environment,
+    // with references to other variables interpolated (eg: {{base}}/api).
+    pub fn build_env(&self, environment: Option<&str>) -> EnvMap {
+        let mut env = self.env_values(environment);
+        // variables in a cycle keep their value as written, check_env reports the cycle
+        let _ = resolve_references(&mut env);
+        return env;
+    }
+
+    // The value of each env variable for the given environment, as written
+    pub fn env_values(&self, environment: Option<&str>) -> EnvMap {
+        return self
+            .env_origins(environment)
+            .into_iter()
+            .map(|(name, (value, _))| (name, value))
+            .collect();
+    }
+
+    // The value of each env variable for the given environment and where it comes from.
+    // Variables without an override for the environment use their default, and variables
+    // mapped to an os environment variable (os_env) take its value when it's set.
+    pub fn env_origins(
+        &self,
+        environment: Option<&str>,
+    ) -> HashMap<String, (serde_yaml::Value, EnvSource)> {
+        let mut result = HashMap::new();
+
+        for (name, variable) in &self.env {
+            let os_value = variable
+                .os_env
+                .as_ref()
+                .and_then(|key| Some((key.clone(), std::env::var(key).ok()?)));
+
+            let value = match (
+                os_value,
+                environment.map(|e| (e, variable.overrides.get(e))),
+            ) {
+                (Some((key, value)), _) => {
+                    (serde_yaml::Value::String(value), EnvSource::OsEnv(key))
+                }
+                (None, Some((environment, Some(value)))) => (
+                    value.clone(),
+                    EnvSource::Environment(environment.to_string()),
+                ),
+                _ => (variable.default.clone(), EnvSource::Default),
+            };
+
+            result.insert(name.clone(), value);
+        }
+
+        return result;
+    }
+
+    // Checks the values of env against the types of the variables and that required ones are set,
+    // failing with every problem found
+    pub fn validate_env(&self, env: &EnvMap, environment: Option<&str>) -> anyhow::Result<()> {
+        let mut names = self.env.keys().collect::<Vec<&String>>();
+        names.sort();
+
+        let problems = names
+            .into_iter()
+            .filter_map(|name| {
+                let problem = self.env[name].validate(env.get(name))?;
+                Some(format!("  {}: {}", name, problem))
+            })
+            .collect::<Vec<String>>();
+
+        if !problems.is_empty() {
+            anyhow::bail!(
+                "Invalid {} environment:\n{}",
+                environment.unwrap_or("default"),
+                problems.join("\n")
+            );
+        }
+
+        return Ok(());
+    }
+
+    // The base url for the given environment, or its default.
+    // It's a yaml value like env variables, anything other than a string is ignored.
+    pub fn base_url(&self, environment: Option<&str>) -> Option<String> {
+        let base_url = self.base_url.as_ref()?;
+        let value = environment
+            .and_then(|environment| base_url.overrides.get(environment))
+            .unwrap_or(&base_url.default);
+
+        return value.as_str().map(|value| value.to_string());
+    }
+
+    // Whether the value of a variable is hidden when requests leave the runner (redact.variables)
+    pub fn is_secret(&self, name: &str) -> bool {
+        return self
+            .redact
+            .variables
+            .iter()
+            .any(|variable| variable == name);
+    }
+
+    // Returns the names of all environments declared across env variables and the base url
+    pub fn environments(&self) -> Vec<String> {
+        let mut result = vec![];
+
+        for variable in self.env.values().chain(self.base_url.as_ref()) {
+            for name in variable.overrides.keys() {
+                if !result.contains(name) {
+                    result.push(name.clone());
+                }
+            }
+        }
+
+        result.sort();
+        return result;
+    }
+}
+
+impl EnvironmentVariableSchema {
+    // What's wrong with the value of the variable, if anything.
+    // Strings with placeholders are only known once interpolated and aren't checked.
+    pub fn validate(&self, value: Option<&serde_yaml::Value>) -> Option<String> {
+        let value = match value {
+            Some(serde_yaml::Value::Null) | None => {
+                return match self.required {
+                    true => Some("is required but has no value".to_string()),
+                    false => None,
+                };
+            }
+            Some(value) => value,
+        };
+
+        let text = value_to_string(value);
+        if self.required && text.is_empty() {
+            return Some("is required but is empty".to_string());
+        }
+
+        let kind = match self.kind {
+            Some(kind) if !text.contains("{{") => kind,
+            _ => return None,
+        };
+
+        let valid = match kind {
+            VariableTypeSchema::String => value.is_string(),
+            VariableTypeSchema::Int => {
+                value.is_i64() || value.is_u64() || text.parse::<i64>().is_ok()
+            }
+            VariableTypeSchema::Bool => value.is_bool() || text == "true" || text == "false",
+            VariableTypeSchema::Url => url::Url::parse(&text).is_ok(),
+            VariableTypeSchema::Enum => self
+                .choices
+                .iter()
+                .any(|choice| choice == value || value_to_string(choice) == text),
+        };
+        if valid {
+            return None;
+        }
+
+        return Some(match kind {
+            VariableTypeSchema::Enum => format!(
+                "\"{}\" is not one of {}",
+                text,
+                self.choices
+                    .iter()
+                    .map(value_to_string)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            kind => format!(
+                "expected {}, got \"{}\"",
+                serde_yaml::to_string(&kind).unwrap_or_default().trim(),
+                text
+            ),
+        });
+    }
+}
+
+impl Runner {
+    // The value of each variable of the run and where it comes from, in order of precedence:
+    // project default < selected environment < os environment (os_env) < --var < set by the run
+    pub fn env_origins(&self) -> HashMap<String, (serde_yaml::Value, EnvSource)> {
+        let mut origins = self.project.object.env_origins(self.environment.as_deref());
+
+        for (name, value) in &self.variables {
+            origins.insert(name.clone(), (value.clone(), EnvSource::Variable));
+        }
+        for (name, value) in self.state.values() {
+            origins.insert(name.clone(), (value.clone(), EnvSource::State));
+        }
+
+        return origins;
+    }
+
+    // Checks the env of the run, --var and state values included, against the project's variables
+    pub fn check_env(&self) -> anyhow::Result<()> {
+        let mut env = self.env_values();
+        resolve_references(&mut env)?;
+
+        return self
+            .project
+            .object
+            .validate_env(&env, self.environment.as_deref());
+    }
+}
+
+// Interpolates the values referencing other variables, the ones they reference first.
+// Variables in a cycle are left as written and the cycle is returned as an error.
+pub fn resolve_references(env: &mut EnvMap) -> anyhow::Result<()> {
+    let mut names = env.keys().cloned().collect::<Vec<String>>();
+    names.sort();
+
+    let mut order = vec![];
+    let mut visiting = vec![];
+    let mut cycles = vec![];
+    for name in &names {
+        visit_references(name, env, &mut visiting, &mut order, &mut cycles);
+    }
+
+    for name in order {
+        if cycles
+            .iter()
+            .any(|cycle: &Vec<String>| cycle.contains(&name))
+        {
+            continue;
+        }
+
+        // placeholders that aren't variables, eg: responses, are left for the request to resolve
+        if let Ok(value) = interpolate_value(&env[&name], env) {
+            env.insert(name, value);
+        }
+    }
+
+    if let Some(cycle) = cycles.first() {
+        anyhow::bail!("Circular variable reference: {}", cycle.join(" -> "));
+    }
+
+    return Ok(());
+}
+
+fn visit_references(
+    name: &str,
+    env: &EnvMap,
+    visiting: &mut Vec<String>,
+    order: &mut Vec<String>,
+    cycles: &mut Vec<Vec<String>>,
+) {
+    if order.iter().any(|n| n == name) {
+        return;
+    }
+
+    if let Some(start) = visiting.iter().position(|n| n == name) {
+        let mut cycle = visiting[start..].to_vec();
+        cycle.push(name.to_string());
+        cycles.push(cycle);
+        return;
+    }
+
+    visiting.push(name.to_string());
+    for reference in references(&env[name], env) {
+        visit_references(&reference, env, visiting, order, cycles);
+    }
+    visiting.pop();
+
+    order.push(name.to_string());
+}
+
+// Names of the variables the placeholders of a value read, function arguments included
+fn references(value: &serde_yaml::Value, env: &EnvMap) -> Vec<String> {
+    let mut names = vec![];
+
+    match value {
+        serde_yaml::Value::String(content) => {
+            for key in placeholders(content) {
+                // only the first segment of a path names a variable, `responses.token` isn't `token`
+                let mut previous = ' ';
+                let mut word = String::new();
+                for c in key.chars().chain(std::iter::once(' ')) {
+                    if c.is_alphanumeric() || c == '_' || c == '-' {
+                        word.push(c);
+                        continue;
+                    }
+
+                    if !word.is_empty()
+                        && previous != '.'
+                        && env.contains_key(&word)
+                        && !names.contains(&word)
+                    {
+                        names.push(word.clone());
+                    }
+
+                    if !word.is_empty() || !c.is_whitespace() {
+                        previous = c;
+                    }
+                    word.clear();
+                }
+            }
+        }
+        serde_yaml::Value::Sequence(items) => {
+            for item in items {
+                names.extend(references(item, env));
+            }
+        }
+        serde_yaml::Value::Mapping(map) => {
+            for item in map.values() {
+                names.extend(references(item, env));
+            }
+        }
+        _ => {}
+    };
+
+    return names;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(values: &[(&str, &str)]) -> EnvMap {
+        return values
+            .iter()
+            .map(|(name, value)| (name.to_string(), serde_yaml::from_str(value).unwrap()))
+            .collect();
+    }
+
+    #[test]
+    fn resolves_a_chain_in_dependency_order() {
+        let mut env = env(&[
+            ("url", "'{{api}}/users'"),
+            ("api", "'{{host}}/v{{version}}'"),
+            ("host", "https://x.dev"),
+            ("version", "2"),
+            (
+                "auth",
+                "{token: '{{secret}}', header: 'Basic {{base64(secret)}}'}",
+            ),
+            ("secret", "s3cret"),
+        ]);
+        resolve_references(&mut env).unwrap();
+
+        assert_eq!(env["url"], "https://x.dev/v2/users");
+        assert_eq!(env["api"], "https://x.dev/v2");
+        assert_eq!(env["auth"]["token"], "s3cret");
+        assert_eq!(env["auth"]["header"], "Basic czNjcmV0");
+    }
+
+    #[test]
+    fn placeholders_that_are_not_variables_are_left_for_the_request() {
+        let mut env = env(&[("token", "'{{responses.login.body.token}}'")]);
+        resolve_references(&mut env).unwrap();
+
+        assert_eq!(env["token"], "{{responses.login.body.token}}");
+    }
+
+    #[test]
+    fn cycles_are_reported_and_left_as_written() {
+        let mut env = env(&[
+            ("a", "'{{b}}'"),
+            ("b", "'{{c}}/x'"),
+            ("c", "'{{a}}'"),
+            ("plain", "'{{d}}'"),
+            ("d", "ok"),
+        ]);
+        let error = resolve_references(&mut env).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Circular variable reference: a -> b -> c -> a"
+        );
+        assert_eq!(env["a"], "{{b}}");
+        assert_eq!(env["b"], "{{c}}/x");
+        assert_eq!(env["plain"], "ok");
+    }
+
+    #[test]
+    fn a_variable_referencing_itself_is_a_cycle() {
+        let mut env = env(&[("path", "'{{path}}/bin'")]);
+        let error = resolve_references(&mut env).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Circular variable reference: path -> path"
+        );
+        assert_eq!(env["path"], "{{path}}/bin");
+    }
+}
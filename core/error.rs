@@ -0,0 +1,191 @@
+use std::path::PathBuf;
+
+use crate::{assertions::AssertionError, interpolation::UnresolvedError, runner::SkippedError};
+
+/// Errors returned by the library api, so applications can match on what went wrong.
+#[derive(Debug)]
+pub enum ExecutorError {
+    // a project or request file couldn't be read or parsed
+    Parse {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    // a placeholder couldn't be resolved
+    Interpolation {
+        request: String,
+        variable: String,
+        message: String,
+    },
+    // the request couldn't be sent or its response couldn't be read
+    Network {
+        request: String,
+        message: String,
+    },
+    // the response didn't pass the captures, assertions or schema of the request
+    Assertion {
+        request: String,
+        failures: Vec<String>,
+    },
+    // the `when` condition of the request was false
+    Skipped {
+        request: String,
+        condition: String,
+    },
+    // anything else, eg: an unknown request or a failing hook
+    Other(String),
+}
+
+impl ExecutorError {
+    // Classifies an error from loading the project at path
+    pub fn from_load(path: &std::path::Path, error: anyhow::Error) -> ExecutorError {
+        return ExecutorError::Parse {
+            path: Some(path.to_path_buf()),
+            message: format!("{:#}", error),
+        };
+    }
+
+    // Classifies an error from resolving or calling a request
+    pub fn from_call(request: &str, error: anyhow::Error) -> ExecutorError {
+        if let Some(e) = error.downcast_ref::<AssertionError>() {
+            return ExecutorError::Assertion {
+                request: e.name.clone(),
+                failures: e.failures.clone(),
+            };
+        }
+
+        if let Some(e) = error.downcast_ref::<SkippedError>() {
+            return ExecutorError::Skipped {
+                request: e.name.clone(),
+                condition: e.condition.clone(),
+            };
+        }
+
+        let message = format!("{:#}", error);
+
+        if let Some(e) = error.downcast_ref::<UnresolvedError>() {
+            return ExecutorError::Interpolation {
+                request: request.to_string(),
+                variable: e.name.clone(),
+                message,
+            };
+        }
+
+        if error.downcast_ref::<reqwest::Error>().is_some() {
+            return ExecutorError::Network {
+                request: request.to_string(),
+                message,
+            };
+        }
+
+        if error.downcast_ref::<serde_yaml::Error>().is_some() {
+            return ExecutorError::Parse {
+                path: None,
+                message,
+            };
+        }
+
+        return ExecutorError::Other(message);
+    }
+}
+
+impl std::fmt::Display for ExecutorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            ExecutorError::Parse {
+                path: Some(path),
+                message,
+            } => write!(f, "Failed to load {:?}: {}", path, message),
+            ExecutorError::Parse {
+                path: None,
+                message,
+            } => {
+                write!(f, "Failed to parse: {}", message)
+            }
+            ExecutorError::Interpolation {
+                request, message, ..
+            } => write!(f, "Failed to resolve \"{}\": {}", request, message),
+            ExecutorError::Network { request, message } => {
+                write!(f, "Request \"{}\" failed: {}", request, message)
+            }
+            ExecutorError::Assertion { request, failures } => {
+                write!(f, "{} failed: {}", request, failures.join("; "))
+            }
+            ExecutorError::Skipped { request, condition } => {
+                write!(f, "{} skipped, `{}` is false", request, condition)
+            }
+            ExecutorError::Other(message) => write!(f, "{}", message),
+        };
+    }
+}
+
+impl std::error::Error for ExecutorError {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn errors_are_classified_through_their_context() {
+        let error = Err::<(), _>(UnresolvedError {
+            name: "token".to_string(),
+        })
+        .context("Failed to resolve the headers")
+        .unwrap_err();
+
+        match ExecutorError::from_call("login", error) {
+            ExecutorError::Interpolation {
+                request,
+                variable,
+                message,
+            } => {
+                assert_eq!(request, "login");
+                assert_eq!(variable, "token");
+                assert!(message.starts_with("Failed to resolve the headers: "));
+            }
+            other => panic!("{:?}", other),
+        }
+    }
+
+    #[test]
+    fn yaml_errors_are_parse_errors_and_the_rest_are_other() {
+        let yaml = serde_yaml::from_str::<Vec<String>>("a: b").unwrap_err();
+
+        assert!(matches!(
+            ExecutorError::from_call("users", yaml.into()),
+            ExecutorError::Parse { path: None, .. }
+        ));
+        assert!(matches!(
+            ExecutorError::from_call("users", anyhow::anyhow!("Hook \"seed\" failed")),
+            ExecutorError::Other(message) if message == "Hook \"seed\" failed"
+        ));
+    }
+
+    #[test]
+    fn messages_name_the_request() {
+        let assertion = ExecutorError::from_call(
+            "users",
+            AssertionError {
+                name: "users".to_string(),
+                failures: vec!["a".to_string(), "b".to_string()],
+            }
+            .into(),
+        );
+        let skipped = ExecutorError::Skipped {
+            request: "users".to_string(),
+            condition: "false".to_string(),
+        };
+        let network = ExecutorError::Network {
+            request: "users".to_string(),
+            message: "connection refused".to_string(),
+        };
+
+        assert_eq!(assertion.to_string(), "users failed: a; b");
+        assert_eq!(skipped.to_string(), "users skipped, `false` is false");
+        assert_eq!(
+            network.to_string(),
+            "Request \"users\" failed: connection refused"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+use serde_json::{Value, json};
+
+use crate::{
+    interpolation::EnvMap,
+    report::RunReport,
+    runner::{CallResult, Runner},
+};
+
+impl Runner {
+    // Describes the requests called in a run and their responses as a HAR 1.2 log,
+    // in the order of the report. Headers, body fields and variable values are redacted with the project rules.
+    pub fn export_har(&self, report: &RunReport) -> anyhow::Result<String> {
+        let env = self.build_env();
+        let entries = report
+            .entries
+            .iter()
+            .filter(|entry| !entry.skipped)
+            .filter_map(|entry| self.responses.get(&entry.name))
+            .map(|result| self.har_entry(result, &env))
+            .collect::<Vec<Value>>();
+
+        return Ok(serde_json::to_string_pretty(&json!({
+            "log": {
+                "version": "1.2",
+                "creator": { "name": "dotapi", "version": env!("CARGO_PKG_VERSION") },
+                "pages": [],
+                "entries": entries,
+            }
+        }))?);
+    }
+
+    fn har_entry(&self, result: &CallResult, env: &EnvMap) -> Value {
+        let redact = &self.project.object.redact;
+        let request = &result.request;
+        let response = &result.response;
+        let time = result.duration.as_secs_f64() * 1000.0;
+
+        let headers = |headers: Vec<(&String, &String)>| -> Vec<Value> {
+            return headers
+                .into_iter()
+                .map(|(name, value)| {
+                    let value = redact.redact_values(&redact.redact_header(name, value), env);
+                    json!({ "name": name, "value": value })
+                })
+                .collect();
+        };
+
+        let query = url::Url::parse(&request.url)
+            .map(|url| {
+                url.query_pairs()
+                    .map(|(name, value)| {
+                        json!({ "name": name, "value": redact.redact_values(&value, env) })
+                    })
+                    .collect::<Vec<Value>>()
+            })
+            .unwrap_or_default();
+
+        let mut har_request = json!({
+            "method": request.method,
+            "url": redact.redact_values(&request.url, env),
+            "httpVersion": "HTTP/1.1",
+            "cookies": [],
+            "headers": headers(request.headers.iter().map(|(k, v)| (k, v)).collect()),
+            "queryString": query,
+            "headersSize": -1,
+            "bodySize": -1,
+        });
+
+        if let Some(text) = request.body_text() {
+            har_request["bodySize"] = text.len().into();
+            har_request["postData"] = json!({
+                "mimeType": request.content_type().unwrap_or_default(),
+                "text": redact.redact_body(&text, env),
+            });
+        }
+
+        let mut response_headers = response.headers.iter().collect::<Vec<_>>();
+        response_headers.sort();
+        let mime_type = response
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+        let redirect = response
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("location"))
+            .map(|(_, value)| value.clone())
+            .unwrap_or_default();
+
+        return json!({
+            "startedDateTime": result.started.to_rfc3339(),
+            "time": time,
+            "request": har_request,
+            "response": {
+                "status": response.status,
+                "statusText": reqwest::StatusCode::from_u16(response.status)
+                    .ok()
+                    .and_then(|status| status.canonical_reason())
+                    .unwrap_or_default(),
+                "httpVersion": "HTTP/1.1",
+                "cookies": [],
+                "headers": headers(response_headers),
+                "content": {
+                    "size": response.body.len(),
+                    "mimeType": mime_type,
+                    "text": redact.redact_body(&response.text(), env),
+                },
+                "redirectURL": redirect,
+                "headersSize": -1,
+                "bodySize": response.raw_size,
+            },
+            "cache": {},
+            // only the total time is measured
+            "timings": { "send": 0, "wait": time, "receive": 0 },
+        });
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use indexmap::IndexMap;
+
+use serde_json::{Value, json};
+
+use crate::{
+    body::ndjson,
+    fs::FileObject,
+    runner::{Runner, read_content_file},
+    schema::{
+        auth::AuthSchema,
+        env::EnvironmentVariableSchema,
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        roots::RequestRootSchema,
+    },
+};
+
+// The variable holding the project's base url, prefixed to relative request urls
+const BASE_URL_VARIABLE: &str = "base_url";
+
+impl Runner {
+    // Converts the project into an Insomnia v4 export, with placeholders left for Insomnia to resolve.
+    // Classes become folders, env variables the base environment and their overrides sub environments.
+    pub fn export_insomnia(&self) -> anyhow::Result<String> {
+        let project = &self.project.object;
+        let workspace_id = insomnia_id("wrk");
+        let base_environment_id = insomnia_id("env");
+
+        let mut env = project.env.clone();
+        if let Some(base_url) = &project.base_url {
+            env.entry(BASE_URL_VARIABLE.to_string())
+                .or_insert(base_url.clone());
+        }
+
+        let mut resources = vec![
+            json!({
+                "_id": workspace_id,
+                "_type": "workspace",
+                "name": project.project.name,
+                "description": project.project.description,
+                "scope": "collection",
+            }),
+            json!({
+                "_id": base_environment_id,
+                "_type": "environment",
+                "parentId": workspace_id,
+                "name": "Base Environment",
+                "data": environment_data(&env, None),
+            }),
+        ];
+
+        let mut environments = project.environments();
+        environments.sort();
+        for environment in environments {
+            resources.push(json!({
+                "_id": insomnia_id("env"),
+                "_type": "environment",
+                "parentId": base_environment_id,
+                "name": environment,
+                "data": environment_data(&env, Some(&environment)),
+            }));
+        }
+
+        let mut folders = HashMap::<String, String>::new();
+        let mut requests = self.requests.iter().collect::<Vec<_>>();
+        requests.sort_by_key(|request| request.get_call_name());
+
+        for request in requests {
+            let class = request
+                .object
+                .config
+                .as_ref()
+                .and_then(|config| config.class.clone());
+
+            let parent_id = match class {
+                Some(class) => folders
+                    .entry(class.clone())
+                    .or_insert_with(|| {
+                        let id = insomnia_id("fld");
+                        resources.push(json!({
+                            "_id": id,
+                            "_type": "request_group",
+                            "parentId": workspace_id,
+                            "name": class,
+                        }));
+                        id
+                    })
+                    .clone(),
+                None => workspace_id.clone(),
+            };
+
+            resources.push(insomnia_request(
+                request,
+                &parent_id,
+                project.base_url.is_some(),
+            )?);
+        }
+
+        return Ok(serde_json::to_string_pretty(&json!({
+            "_type": "export",
+            "__export_format": 4,
+            "__export_source": "dotapi",
+            "resources": resources,
+        }))?);
+    }
+}
+
+fn insomnia_id(prefix: &str) -> String {
+    return format!("{}_{}", prefix, uuid::Uuid::new_v4().simple());
+}
+
+// Native doctor references variables as {{name}}, Insomnia as {{ _.name }}
+pub fn insomnia_template(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+
+        result.push_str(&format!("{{{{ _.{} }}}}", rest[start + 2..end].trim()));
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    return result;
+}
+
+// The values of the variables in an environment, only the overridden ones for named environments
+fn environment_data(
+    env: &IndexMap<String, EnvironmentVariableSchema>,
+    environment: Option<&str>,
+) -> Value {
+    let mut data = serde_json::Map::new();
+
+    for (name, variable) in env {
+        let value = match environment {
+            Some(environment) => match variable.overrides.get(environment) {
+                Some(value) => value,
+                None => continue,
+            },
+            None => &variable.default,
+        };
+
+        data.insert(
+            name.clone(),
+            serde_json::to_value(value).unwrap_or_default(),
+        );
+    }
+
+    return Value::Object(data);
+}
+
+fn insomnia_request(
+    request: &FileObject<RequestRootSchema>,
+    parent_id: &str,
+    has_base_url: bool,
+) -> anyhow::Result<Value> {
+    let schema = &request.object;
+    let pairs = |values: &Option<IndexMap<String, String>>| -> Vec<Value> {
+        let mut values = values
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect::<Vec<_>>();
+        values.sort();
+
+        return values
+            .into_iter()
+            .map(|(name, value)| json!({ "name": name, "value": insomnia_template(&value) }))
+            .collect();
+    };
+
+    let mut url = insomnia_template(&schema.url);
+    if has_base_url && !url.contains("://") && !url.starts_with("{{") {
+        url = format!("{{{{ _.{} }}}}{}", BASE_URL_VARIABLE, url);
+    }
+
+    let dir = request
+        .path
+        .parent()
+        .map(|dir| dir.to_path_buf())
+        .unwrap_or_default();
+    let body = match &schema.body {
+        Some(body) => insomnia_body(&read_content_file(body, &dir)?)?,
+        None => json!({}),
+    };
+
+    return Ok(json!({
+        "_id": insomnia_id("req"),
+        "_type": "request",
+        "parentId": parent_id,
+        "name": request.get_call_name(),
+        "description": schema.doc,
+        "method": schema.method.to_uppercase(),
+        "url": url,
+        "headers": pairs(&schema.headers),
+        "parameters": pairs(&schema.query),
+        "body": body,
+        "authentication": insomnia_auth(&schema.auth),
+    }));
+}
+
+fn insomnia_body(body: &RequestBodySchema) -> anyhow::Result<Value> {
+    let text = |mime_type: &str, text: &str| {
+        return json!({ "mimeType": mime_type, "text": insomnia_template(text) });
+    };
+
+    return Ok(match body {
+        RequestBodySchema::Json { content, .. } => {
+            text("application/json", &serde_json::to_string_pretty(content)?)
+        }
+        RequestBodySchema::Graphql {
+            query, variables, ..
+        } => text(
+            "application/graphql",
+            &json!({ "query": query, "variables": variables }).to_string(),
+        ),
+        RequestBodySchema::Xml { content, .. } | RequestBodySchema::Soap { content, .. } => {
+            text("application/xml", content)
+        }
+        RequestBodySchema::Text { content, .. } => text("text/plain", content),
+        RequestBodySchema::Ndjson { content, .. } => text(
+            ndjson::NDJSON_CONTENT_TYPE,
+            &String::from_utf8_lossy(&ndjson::encode(content)?),
+        ),
+        RequestBodySchema::FormUrlencoded {
+            content, fields, ..
+        } => {
+            let mut params = url::form_urlencoded::parse(content.as_bytes())
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<_>>();
+            let mut fields = fields
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect::<Vec<_>>();
+            fields.sort();
+            params.extend(fields);
+
+            json!({
+                "mimeType": "application/x-www-form-urlencoded",
+                "params": params
+                    .into_iter()
+                    .map(|(name, value)| json!({ "name": name, "value": insomnia_template(&value) }))
+                    .collect::<Vec<Value>>(),
+            })
+        }
+        RequestBodySchema::Multipart { parts } => json!({
+            "mimeType": "multipart/form-data",
+            "params": parts
+                .iter()
+                .map(|part| match part {
+                    MultipartPartSchema::Field { name, value } => {
+                        json!({ "name": name, "value": insomnia_template(value) })
+                    }
+                    MultipartPartSchema::File { name, path, .. } => {
+                        json!({ "name": name, "type": "file", "fileName": path })
+                    }
+                })
+                .collect::<Vec<Value>>(),
+        }),
+        RequestBodySchema::Binary { path, content_type } => json!({
+            "mimeType": content_type.clone().unwrap_or("application/octet-stream".to_string()),
+            "fileName": path,
+        }),
+        // insomnia has no equivalent of binary encoded bodies or the types registered by applications
+        RequestBodySchema::Msgpack { .. }
+        | RequestBodySchema::Cbor { .. }
+        | RequestBodySchema::Custom { .. } => json!({}),
+    });
+}
+
+fn insomnia_auth(auth: &Option<AuthSchema>) -> Value {
+    return match auth {
+        Some(AuthSchema::Basic { username, password }) => json!({
+            "type": "basic",
+            "username": insomnia_template(username),
+            "password": insomnia_template(password),
+        }),
+        Some(AuthSchema::Bearer { token }) => json!({
+            "type": "bearer",
+            "token": insomnia_template(token),
+        }),
+        Some(AuthSchema::Digest { username, password }) => json!({
+            "type": "digest",
+            "username": insomnia_template(username),
+            "password": insomnia_template(password),
+        }),
+        Some(AuthSchema::Ntlm {
+            username, password, ..
+        }) => json!({
+            "type": "ntlm",
+            "username": insomnia_template(username),
+            "password": insomnia_template(password),
+        }),
+        Some(_) => {
+            tracing::warn!("Only basic, bearer, digest and ntlm auth can be exported to Insomnia");
+            json!({})
+        }
+        None => json!({}),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::{import::insomnia::parse_insomnia, schema::roots::ProjectRootSchema};
+
+    #[test]
+    fn converts_templates() {
+        assert_eq!(
+            insomnia_template("{{host}}/a/{{ id }}"),
+            "{{ _.host }}/a/{{ _.id }}"
+        );
+        assert_eq!(insomnia_template("no {{ end"), "no {{ end");
+    }
+
+    #[test]
+    fn exports_what_the_importer_reads_back() {
+        let mut project = ProjectRootSchema::default();
+        project.project.name = "Shop".to_string();
+        project.env.insert(
+            "host".to_string(),
+            EnvironmentVariableSchema::new(
+                "http://localhost".into(),
+                vec![("prod".to_string(), "https://shop.dev".into())],
+            ),
+        );
+
+        let request = RequestRootSchema {
+            method: "post".to_string(),
+            url: "{{host}}/items".to_string(),
+            headers: Some(IndexMap::from([(
+                "Accept".to_string(),
+                "application/json".to_string(),
+            )])),
+            auth: Some(AuthSchema::Bearer {
+                token: "{{token}}".to_string(),
+            }),
+            body: Some(RequestBodySchema::Json {
+                content: serde_yaml::from_str("name: a").unwrap(),
+                content_file: None,
+            }),
+            ..Default::default()
+        };
+
+        let root = PathBuf::from("/tmp/dotapi-insomnia");
+        let runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), project),
+            vec![FileObject::new(
+                root.join("requests/create.nd"),
+                request.clone(),
+            )],
+        )
+        .unwrap();
+        let imported = parse_insomnia(&runner.export_insomnia().unwrap()).unwrap();
+
+        assert_eq!(imported.project.project.name, "Shop");
+        assert_eq!(imported.project.env["host"].default, "http://localhost");
+        assert_eq!(
+            imported.project.env["host"].overrides["prod"],
+            "https://shop.dev"
+        );
+
+        let (name, imported) = &imported.requests[0];
+        assert_eq!(name, "create");
+        assert_eq!(imported.method, "POST");
+        assert_eq!(imported.url, request.url);
+        assert_eq!(imported.headers, request.headers);
+        assert_eq!(imported.auth, request.auth);
+        assert_eq!(imported.body, request.body);
+    }
+}
@@ -0,0 +1,320 @@
+pub mod har;
+pub mod insomnia;
+pub mod openapi;
+
+use base64::Engine;
+
+use crate::{
+    body::binary::BinaryFormat,
+    runner::{ResolvedRequest, Runner},
+    schema::{
+        auth::AuthSchema,
+        request_body::{MultipartPartSchema, RequestBodySchema},
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Curl,
+    Httpie,
+}
+
+impl Runner {
+    // Renders the request, resolved with the active environment, as a command line
+    pub fn export_request(&self, name: &str, format: ExportFormat) -> anyhow::Result<String> {
+        let resolved = self.resolve_request(name)?;
+
+        return Ok(match format {
+            ExportFormat::Curl => to_curl(&resolved),
+            ExportFormat::Httpie => to_httpie(&resolved),
+        });
+    }
+}
+
+pub fn to_curl(request: &ResolvedRequest) -> String {
+    let mut args = vec!["curl".to_string()];
+
+    if request.method != "GET" {
+        args.push(format!("-X {}", quote(&request.method)));
+    }
+
+    args.push(quote(&request.url));
+
+    for (key, value) in &request.headers {
+        args.push(format!("-H {}", quote(&format!("{}: {}", key, value))));
+    }
+
+    if request.body.is_some()
+        && request.get_header("content-type").is_none()
+        && let Some(content_type) = request.content_type().filter(|_| !is_multipart(request))
+    {
+        args.push(format!(
+            "-H {}",
+            quote(&format!("Content-Type: {}", content_type))
+        ));
+    }
+
+    if let Some(RequestBodySchema::Multipart { parts }) = &request.body {
+        for part in parts {
+            let value = match part {
+                MultipartPartSchema::Field { name, value } => format!("{}={}", name, value),
+                MultipartPartSchema::File {
+                    name,
+                    path,
+                    mime_type,
+                } => match mime_type {
+                    Some(mime_type) => {
+                        format!("{}=@{};type={}", name, file_path(request, path), mime_type)
+                    }
+                    None => format!("{}=@{}", name, file_path(request, path)),
+                },
+            };
+            args.push(format!("-F {}", quote(&value)));
+        }
+    } else if let Some(RequestBodySchema::Binary { path, .. }) = &request.body {
+        args.push(format!(
+            "--data-binary {}",
+            quote(&format!("@{}", file_path(request, path)))
+        ));
+    } else if encoded_body(request).is_some() {
+        args.push("--data-binary @-".to_string());
+    } else if let Some(body) = request.body_text() {
+        args.push(format!("--data-raw {}", quote(&body)));
+    }
+
+    match &request.auth {
+        Some(AuthSchema::Digest { username, password }) => {
+            args.push("--digest".to_string());
+            args.push(format!(
+                "--user {}",
+                quote(&format!("{}:{}", username, password))
+            ));
+        }
+        Some(AuthSchema::Ntlm {
+            username,
+            password,
+            domain,
+            ..
+        }) => {
+            args.push("--ntlm".to_string());
+            args.push(format!(
+                "--user {}",
+                quote(&format!("{}:{}", ntlm_user(username, domain), password))
+            ));
+        }
+        _ => {}
+    };
+
+    if request.config.insecure {
+        args.push("--insecure".to_string());
+    }
+
+    if let Some(socket) = &request.config.unix_socket {
+        args.push(format!("--unix-socket {}", quote(socket)));
+    }
+
+    return command(request, args);
+}
+
+pub fn to_httpie(request: &ResolvedRequest) -> String {
+    let mut args = vec!["http".to_string()];
+
+    if is_multipart(request) {
+        args.push("--multipart".to_string());
+    }
+
+    if request.config.insecure {
+        args.push("--verify=no".to_string());
+    }
+
+    // ntlm needs the httpie-ntlm plugin
+    match &request.auth {
+        Some(AuthSchema::Digest { username, password }) => {
+            args.push("--auth-type=digest".to_string());
+            args.push(format!(
+                "--auth={}",
+                quote(&format!("{}:{}", username, password))
+            ));
+        }
+        Some(AuthSchema::Ntlm {
+            username,
+            password,
+            domain,
+            ..
+        }) => {
+            args.push("--auth-type=ntlm".to_string());
+            args.push(format!(
+                "--auth={}",
+                quote(&format!("{}:{}", ntlm_user(username, domain), password))
+            ));
+        }
+        _ => {}
+    };
+
+    args.push(quote(&request.method));
+    args.push(quote(&request.url));
+
+    for (key, value) in &request.headers {
+        args.push(quote(&format!("{}:{}", key, value)));
+    }
+
+    if let Some(RequestBodySchema::Multipart { parts }) = &request.body {
+        for part in parts {
+            args.push(quote(&match part {
+                MultipartPartSchema::Field { name, value } => format!("{}={}", name, value),
+                MultipartPartSchema::File {
+                    name,
+                    path,
+                    mime_type,
+                } => match mime_type {
+                    Some(mime_type) => {
+                        format!("{}@{};type={}", name, file_path(request, path), mime_type)
+                    }
+                    None => format!("{}@{}", name, file_path(request, path)),
+                },
+            }));
+        }
+    } else if let Some(RequestBodySchema::Binary { path, .. }) = &request.body {
+        if request.get_header("content-type").is_none()
+            && let Some(content_type) = request.content_type()
+        {
+            args.push(quote(&format!("Content-Type:{}", content_type)));
+        }
+        args.push(quote(&format!("@{}", file_path(request, path))));
+    } else if encoded_body(request).is_some() {
+        // httpie reads the body piped to it
+        if request.get_header("content-type").is_none()
+            && let Some(content_type) = request.content_type()
+        {
+            args.push(quote(&format!("Content-Type:{}", content_type)));
+        }
+    } else if let Some(body) = request.body_text() {
+        if request.get_header("content-type").is_none()
+            && let Some(content_type) = request.content_type()
+        {
+            args.push(quote(&format!("Content-Type:{}", content_type)));
+        }
+        args.push(format!("--raw={}", quote(&body)));
+    }
+
+    return command(request, args);
+}
+
+// The arguments as a command. Bodies encoded by the runner are piped to it from their base64,
+// and auth the command can't do is noted above it.
+fn command(request: &ResolvedRequest, args: Vec<String>) -> String {
+    let mut command = args.join(" \\\n  ");
+
+    if let Some(bytes) = encoded_body(request) {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+        command = format!("printf %s {} | base64 -d | {}", quote(&encoded), command);
+    }
+
+    let note = match &request.auth {
+        Some(AuthSchema::AwsSigv4 { service, .. }) => Some(format!(
+            "# not exported: the request is signed with aws sigv4 for {}",
+            service
+        )),
+        Some(AuthSchema::Hmac { header, .. }) => Some(format!(
+            "# not exported: the request is signed with an hmac in the {} header",
+            header
+        )),
+        _ => None,
+    };
+
+    return match note {
+        Some(note) => format!("{}\n{}", note, command),
+        None => command,
+    };
+}
+
+// The msgpack and cbor bodies as sent, they have no text form
+fn encoded_body(request: &ResolvedRequest) -> Option<Vec<u8>> {
+    return match &request.body {
+        Some(RequestBodySchema::Msgpack { content, .. }) => {
+            BinaryFormat::Msgpack.encode(content).ok()
+        }
+        Some(RequestBodySchema::Cbor { content, .. }) => BinaryFormat::Cbor.encode(content).ok(),
+        _ => None,
+    };
+}
+
+// Files of the body are relative to the request, the command can be run from anywhere with their absolute path
+fn file_path(request: &ResolvedRequest, path: &str) -> String {
+    let path = request.base_dir.join(path);
+    return std::path::absolute(&path)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string();
+}
+
+// DOMAIN\\user, or only the user without a domain
+fn ntlm_user(username: &str, domain: &str) -> String {
+    if domain.is_empty() {
+        return username.to_string();
+    }
+
+    return format!("{}\\{}", domain, username);
+}
+
+fn is_multipart(request: &ResolvedRequest) -> bool {
+    return matches!(request.body, Some(RequestBodySchema::Multipart { .. }));
+}
+
+// Quotes a value for posix shells
+pub fn quote(value: &str) -> String {
+    let safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@,+%".contains(c));
+
+    if safe {
+        return value.to_string();
+    }
+
+    return format!("'{}'", value.replace('\'', "'\\''"));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(method: &str) -> ResolvedRequest {
+        return ResolvedRequest {
+            name: "a".to_string(),
+            method: method.to_string(),
+            url: "https://x.dev/a?b=1&c=2".to_string(),
+            headers: vec![],
+            body: None,
+            config: Default::default(),
+            auth: None,
+            base_dir: Default::default(),
+            warnings: vec![],
+            chunk: None,
+        };
+    }
+
+    #[test]
+    fn methods_are_quoted() {
+        assert_eq!(
+            to_curl(&resolved("GET")),
+            "curl \\\n  'https://x.dev/a?b=1&c=2'"
+        );
+        assert_eq!(
+            to_curl(&resolved("PURGE")),
+            "curl \\\n  -X PURGE \\\n  'https://x.dev/a?b=1&c=2'"
+        );
+        assert_eq!(
+            to_curl(&resolved("GET; rm -rf ~")),
+            "curl \\\n  -X 'GET; rm -rf ~' \\\n  'https://x.dev/a?b=1&c=2'"
+        );
+        assert!(to_httpie(&resolved("A B")).contains("\n  'A B' \\\n"));
+    }
+
+    #[test]
+    fn quote_leaves_safe_values_as_is() {
+        assert_eq!(quote("https://x.dev/a"), "https://x.dev/a");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("it's"), "'it'\\''s'");
+    }
+}
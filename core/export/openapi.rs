@@ -0,0 +1,484 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Value, json};
+
+use crate::{
+    body::ndjson,
+    fixtures::ResponseFixture,
+    fs::FileObject,
+    runner::{Runner, read_content_file},
+    schema::{
+        auth::AuthSchema,
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        roots::RequestRootSchema,
+    },
+};
+
+// Headers described by the content type, the security schemes or the http layer itself
+const SKIPPED_HEADERS: &[&str] = &["content-type", "authorization", "accept", "content-length"];
+
+impl Runner {
+    // Describes the project as an OpenAPI 3.1 document, in yaml.
+    // Paths come from request urls, with :name segments and placeholders as path parameters.
+    // Recorded fixtures are used as response examples.
+    pub async fn export_openapi(&mut self) -> anyhow::Result<String> {
+        self.load_requests().await?;
+        let project = &self.project.object;
+        let mut paths = BTreeMap::<String, Map<String, Value>>::new();
+        let mut security_schemes = Map::new();
+
+        let mut requests = self.requests.iter().collect::<Vec<_>>();
+        requests.sort_by_key(|request| request.get_call_name());
+
+        for request in requests {
+            let (path, path_params) = openapi_path(&request.object.url);
+            let mut operation = self.openapi_operation(request, &path_params).await?;
+
+            if let Some((name, scheme)) = security_scheme(&request.object.auth) {
+                operation.insert("security".to_string(), json!([{ name: [] }]));
+                security_schemes.insert(name.to_string(), scheme);
+            }
+
+            paths.entry(path).or_default().insert(
+                request.object.method.to_lowercase(),
+                Value::Object(operation),
+            );
+        }
+
+        let mut document = json!({
+            "openapi": "3.1.0",
+            "info": {
+                "title": project.project.name,
+                "description": project.project.description,
+                "version": project.project.get_version(),
+            },
+            "servers": self.openapi_servers(),
+            "paths": paths,
+        });
+
+        if !security_schemes.is_empty() {
+            document["components"] = json!({ "securitySchemes": security_schemes });
+        }
+
+        return Ok(serde_yaml::to_string(&document)?);
+    }
+
+    // The base url of the project, the default first then one server per environment
+    fn openapi_servers(&self) -> Vec<Value> {
+        let project = &self.project.object;
+        let mut servers = vec![];
+
+        if let Some(url) = project.base_url(None) {
+            servers.push(json!({ "url": url }));
+        }
+
+        let mut environments = project.environments();
+        environments.sort();
+        for environment in environments {
+            match project.base_url(Some(&environment)) {
+                Some(url) if !servers.iter().any(|server| server["url"] == url) => {
+                    servers.push(json!({ "url": url, "description": environment }))
+                }
+                _ => {}
+            };
+        }
+
+        return servers;
+    }
+
+    async fn openapi_operation(
+        &self,
+        request: &FileObject<RequestRootSchema>,
+        path_params: &[String],
+    ) -> anyhow::Result<Map<String, Value>> {
+        let schema = &request.object;
+        let name = request.get_call_name();
+        let mut operation = Map::new();
+
+        operation.insert("operationId".to_string(), name.clone().into());
+        if let Some(summary) = schema.doc.lines().next().filter(|line| !line.is_empty()) {
+            operation.insert("summary".to_string(), summary.into());
+            operation.insert("description".to_string(), schema.doc.trim().into());
+        }
+        if let Some(class) = schema
+            .config
+            .as_ref()
+            .and_then(|config| config.class.clone())
+        {
+            operation.insert("tags".to_string(), json!([class]));
+        }
+
+        let mut parameters = vec![];
+        for param in path_params {
+            let example = schema
+                .path_params
+                .as_ref()
+                .and_then(|params| params.get(param));
+            parameters.push(parameter(param, "path", example, true));
+        }
+        for (location, values) in [("query", &schema.query), ("header", &schema.headers)] {
+            let mut values = values
+                .clone()
+                .unwrap_or_default()
+                .into_iter()
+                .collect::<Vec<_>>();
+            values.sort();
+
+            for (key, value) in values {
+                if location == "header" && SKIPPED_HEADERS.contains(&key.to_lowercase().as_str()) {
+                    continue;
+                }
+                parameters.push(parameter(&key, location, Some(&value), false));
+            }
+        }
+        if !parameters.is_empty() {
+            operation.insert("parameters".to_string(), parameters.into());
+        }
+
+        if let Some(body) = &schema.body {
+            let dir = request
+                .path
+                .parent()
+                .map(|dir| dir.to_path_buf())
+                .unwrap_or_default();
+            let body = read_content_file(body, &dir)?;
+            let content_type = content_type(schema, &body);
+
+            operation.insert(
+                "requestBody".to_string(),
+                json!({ "content": { content_type: media_type(&body)? } }),
+            );
+        }
+
+        operation.insert("responses".to_string(), self.openapi_responses(&name).await);
+        return Ok(operation);
+    }
+
+    // The recorded fixture of the request as an example, a default response otherwise
+    async fn openapi_responses(&self, name: &str) -> Value {
+        let fixture = match tokio::fs::read_to_string(self.get_fixture_path(name)).await {
+            Ok(content) => serde_json::from_str::<ResponseFixture>(&content).ok(),
+            Err(_) => None,
+        };
+
+        let fixture = match fixture {
+            Some(fixture) => fixture,
+            None => return json!({ "default": { "description": "Response" } }),
+        };
+
+        let mut response = json!({ "description": format!("Recorded response of {}", name) });
+        let content_type = fixture
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| {
+                value
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string()
+            });
+
+        // bodies that aren't text are saved encoded and left out of the examples
+        let has_example = !fixture.body.is_empty() && fixture.encoding.is_none();
+        if let Some(content_type) = content_type.filter(|_| has_example) {
+            let example = match serde_json::from_str::<Value>(&fixture.body) {
+                Ok(json) if content_type.contains("json") => json,
+                _ => fixture.body.clone().into(),
+            };
+
+            response["content"] = json!({
+                content_type: { "schema": infer_schema(&example), "example": example }
+            });
+        }
+
+        return json!({ fixture.status.to_string(): response });
+    }
+}
+
+// The path of a url without its origin, with :name segments and {{placeholders}} as {name}.
+// Returns the path and its parameters.
+pub fn openapi_path(url: &str) -> (String, Vec<String>) {
+    // the origin is either scheme://host or a leading placeholder such as {{base_url}}
+    let rest = match url.find("://") {
+        Some(scheme_end) => match url[scheme_end + 3..].find('/') {
+            Some(index) => &url[scheme_end + 3 + index..],
+            None => "/",
+        },
+        None if url.starts_with("{{") => match url.find("}}") {
+            Some(end) => &url[end + 2..],
+            None => url,
+        },
+        None => url,
+    };
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+
+    let mut params = vec![];
+    let segments = rest
+        .split('/')
+        .map(|segment| {
+            let name = segment.strip_prefix(':').or_else(|| {
+                segment
+                    .strip_prefix("{{")
+                    .and_then(|segment| segment.strip_suffix("}}"))
+                    .map(|name| name.trim())
+            });
+
+            match name {
+                Some(name) if !name.is_empty() => {
+                    params.push(name.to_string());
+                    format!("{{{}}}", name)
+                }
+                _ => segment.to_string(),
+            }
+        })
+        .collect::<Vec<String>>();
+
+    let path = segments.join("/");
+    let path = if path.starts_with('/') {
+        path
+    } else {
+        format!("/{}", path)
+    };
+    return (path, params);
+}
+
+fn parameter(name: &str, location: &str, example: Option<&String>, required: bool) -> Value {
+    let mut parameter = json!({
+        "name": name,
+        "in": location,
+        "required": required,
+        "schema": { "type": "string" },
+    });
+
+    // placeholders are filled per environment, they're not useful examples
+    if let Some(example) = example.filter(|example| !example.contains("{{")) {
+        parameter["example"] = example.clone().into();
+    }
+
+    return parameter;
+}
+
+fn content_type(request: &RequestRootSchema, body: &RequestBodySchema) -> String {
+    let header = request.headers.iter().flatten().find_map(|(key, value)| {
+        match key.eq_ignore_ascii_case("content-type") {
+            true => Some(
+                value
+                    .split(';')
+                    .next()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            ),
+            false => None,
+        }
+    });
+
+    return header.unwrap_or_else(|| {
+        match body {
+            RequestBodySchema::Json { .. } | RequestBodySchema::Graphql { .. } => {
+                "application/json"
+            }
+            RequestBodySchema::Xml { .. } => "application/xml",
+            RequestBodySchema::Soap { .. } => "text/xml",
+            RequestBodySchema::Text { .. } => "text/plain",
+            RequestBodySchema::FormUrlencoded { .. } => "application/x-www-form-urlencoded",
+            RequestBodySchema::Multipart { .. } => "multipart/form-data",
+            RequestBodySchema::Binary { content_type, .. } => {
+                return content_type
+                    .clone()
+                    .unwrap_or("application/octet-stream".to_string());
+            }
+            RequestBodySchema::Msgpack { .. } => "application/msgpack",
+            RequestBodySchema::Cbor { .. } => "application/cbor",
+            RequestBodySchema::Ndjson { .. } => ndjson::NDJSON_CONTENT_TYPE,
+            RequestBodySchema::Custom { .. } => "application/octet-stream",
+        }
+        .to_string()
+    });
+}
+
+fn media_type(body: &RequestBodySchema) -> anyhow::Result<Value> {
+    let example = match body {
+        RequestBodySchema::Json { content, .. }
+        | RequestBodySchema::Msgpack { content, .. }
+        | RequestBodySchema::Cbor { content, .. } => serde_json::to_value(content)?,
+        RequestBodySchema::Graphql {
+            query, variables, ..
+        } => json!({ "query": query, "variables": variables }),
+        RequestBodySchema::Xml { content, .. }
+        | RequestBodySchema::Soap { content, .. }
+        | RequestBodySchema::Text { content, .. } => content.clone().into(),
+        RequestBodySchema::Ndjson { content, .. } => {
+            String::from_utf8_lossy(&ndjson::encode(content)?).into()
+        }
+        RequestBodySchema::FormUrlencoded {
+            content, fields, ..
+        } => {
+            let mut object = url::form_urlencoded::parse(content.as_bytes())
+                .map(|(key, value)| (key.to_string(), Value::from(value.to_string())))
+                .collect::<Map<String, Value>>();
+            for (key, value) in fields.iter().flatten() {
+                object.insert(key.clone(), value.clone().into());
+            }
+            Value::Object(object)
+        }
+        RequestBodySchema::Multipart { parts } => {
+            let mut properties = Map::new();
+            for part in parts {
+                let (name, property) = match part {
+                    MultipartPartSchema::Field { name, .. } => (name, json!({ "type": "string" })),
+                    MultipartPartSchema::File { name, .. } => {
+                        (name, json!({ "type": "string", "format": "binary" }))
+                    }
+                };
+                properties.insert(name.clone(), property);
+            }
+            return Ok(json!({ "schema": { "type": "object", "properties": properties } }));
+        }
+        RequestBodySchema::Binary { .. } | RequestBodySchema::Custom { .. } => {
+            return Ok(json!({ "schema": { "type": "string", "format": "binary" } }));
+        }
+    };
+
+    return Ok(json!({ "schema": infer_schema(&example), "example": example }));
+}
+
+// A json schema describing the shape of an example value
+pub fn infer_schema(value: &Value) -> Value {
+    return match value {
+        Value::Null => json!({ "type": "null" }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(number) if number.is_i64() || number.is_u64() => json!({ "type": "integer" }),
+        Value::Number(_) => json!({ "type": "number" }),
+        Value::String(_) => json!({ "type": "string" }),
+        Value::Array(items) => match items.first() {
+            Some(item) => json!({ "type": "array", "items": infer_schema(item) }),
+            None => json!({ "type": "array" }),
+        },
+        Value::Object(object) => {
+            let properties = object
+                .iter()
+                .map(|(key, value)| (key.clone(), infer_schema(value)))
+                .collect::<Map<String, Value>>();
+            json!({ "type": "object", "properties": properties })
+        }
+    };
+}
+
+fn security_scheme(auth: &Option<AuthSchema>) -> Option<(&'static str, Value)> {
+    return match auth.as_ref()? {
+        AuthSchema::Basic { .. } => Some(("basic", json!({ "type": "http", "scheme": "basic" }))),
+        AuthSchema::Bearer { .. } => {
+            Some(("bearer", json!({ "type": "http", "scheme": "bearer" })))
+        }
+        AuthSchema::Digest { .. } => {
+            Some(("digest", json!({ "type": "http", "scheme": "digest" })))
+        }
+        AuthSchema::Hmac { header, .. } => Some((
+            "hmac",
+            json!({ "type": "apiKey", "in": "header", "name": header }),
+        )),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::schema::roots::ProjectRootSchema;
+
+    #[test]
+    fn paths_and_their_parameters() {
+        assert_eq!(
+            openapi_path("https://x.dev/users/:id/posts?page=1"),
+            ("/users/{id}/posts".to_string(), vec!["id".to_string()])
+        );
+        assert_eq!(
+            openapi_path("{{base_url}}/orders/{{ order_id }}#top"),
+            (
+                "/orders/{order_id}".to_string(),
+                vec!["order_id".to_string()]
+            )
+        );
+        assert_eq!(openapi_path("https://x.dev"), ("/".to_string(), vec![]));
+        assert_eq!(openapi_path("users"), ("/users".to_string(), vec![]));
+    }
+
+    #[test]
+    fn infers_schemas_from_examples() {
+        let schema = infer_schema(&json!({ "id": 1, "price": 2.5, "tags": ["a"], "next": null }));
+
+        assert_eq!(schema["type"], "object");
+        assert_eq!(schema["properties"]["id"]["type"], "integer");
+        assert_eq!(schema["properties"]["price"]["type"], "number");
+        assert_eq!(schema["properties"]["tags"]["items"]["type"], "string");
+        assert_eq!(schema["properties"]["next"]["type"], "null");
+        assert_eq!(infer_schema(&json!([])), json!({ "type": "array" }));
+    }
+
+    #[tokio::test]
+    async fn exports_operations_bodies_and_security() {
+        let mut project = ProjectRootSchema::default();
+        project.project.name = "Shop".to_string();
+
+        let request = RequestRootSchema {
+            method: "post".to_string(),
+            url: "{{base_url}}/users/:id".to_string(),
+            doc: "Updates a user\nwith more details".to_string(),
+            headers: Some(IndexMap::from([
+                ("Authorization".to_string(), "Bearer {{token}}".to_string()),
+                ("X-Trace".to_string(), "on".to_string()),
+            ])),
+            path_params: Some(IndexMap::from([("id".to_string(), "7".to_string())])),
+            auth: Some(AuthSchema::Bearer {
+                token: "{{token}}".to_string(),
+            }),
+            body: Some(RequestBodySchema::Json {
+                content: serde_yaml::from_str("name: a").unwrap(),
+                content_file: None,
+            }),
+            ..Default::default()
+        };
+
+        let root = PathBuf::from("/tmp/dotapi-openapi-missing");
+        let mut runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), project),
+            vec![FileObject::new(
+                root.join("requests/update_user.nd"),
+                request,
+            )],
+        )
+        .unwrap();
+        let document =
+            serde_yaml::from_str::<Value>(&runner.export_openapi().await.unwrap()).unwrap();
+
+        assert_eq!(document["openapi"], "3.1.0");
+        assert_eq!(document["info"]["title"], "Shop");
+
+        let operation = &document["paths"]["/users/{id}"]["post"];
+        assert_eq!(operation["operationId"], "update_user");
+        assert_eq!(operation["summary"], "Updates a user");
+        assert_eq!(operation["security"], json!([{ "bearer": [] }]));
+
+        let parameters = operation["parameters"].as_array().unwrap();
+        assert_eq!(parameters.len(), 2);
+        assert_eq!(parameters[0]["in"], "path");
+        assert_eq!(parameters[0]["example"], "7");
+        assert_eq!(parameters[1]["name"], "X-Trace");
+
+        let body = &operation["requestBody"]["content"]["application/json"];
+        assert_eq!(body["example"], json!({ "name": "a" }));
+        assert_eq!(body["schema"]["properties"]["name"]["type"], "string");
+        assert_eq!(operation["responses"]["default"]["description"], "Response");
+        assert_eq!(
+            document["components"]["securitySchemes"]["bearer"]["scheme"],
+            "bearer"
+        );
+    }
+}
@@ -0,0 +1,527 @@
+use std::cmp::Ordering;
+
+use serde_yaml::Value;
+
+use crate::{
+    interpolation::{EnvMap, lookup, value_to_string},
+    query::{css, json_path, json_path_first, xpath},
+    runner::sha256,
+};
+
+// A small expression language used for conditions, eg:
+//   responses.login.status == 200 && !cached_token
+// Supports literals (numbers, 'strings', "strings", true, false, null), variable paths,
+// comparisons (== != < <= > >=), logic (&& || !) and parentheses.
+// `&&` and `||` short-circuit, eg: response.body && xpath(response.body, '/id') doesn't query a missing body.
+// Variables that don't resolve evaluate to null.
+// Functions: json_path(value, '$.path'), json_path_all(value, '$.path'), len(value), contains(value, item),
+// xpath(value, '/path'), xpath_all(value, '/path'), css(value, 'selector'), css_all(value, 'selector'),
+// sha256(value), file_sha256('path') (relative to the working directory).
+// `value.function(args)` is the same as `function(value, args)`, eg: response.body.json_path('$.data.id').
+// Called on a response (`response` or `responses.<name>`) the function gets its body: response.json_path('$.data.id')
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(Value),
+    Path(String),
+    Call(String),
+    Operator(&'static str),
+    Open,
+    Close,
+    Comma,
+}
+
+const OPERATORS: &[&str] = &["==", "!=", "<=", ">=", "&&", "||", "<", ">", "!"];
+
+pub fn evaluate(expression: &str, env: &EnvMap) -> anyhow::Result<bool> {
+    return Ok(is_truthy(&evaluate_value(expression, env)?));
+}
+
+pub fn evaluate_value(expression: &str, env: &EnvMap) -> anyhow::Result<Value> {
+    let tokens = tokenize(expression)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+        env,
+        skip: false,
+    };
+
+    let value = parser.or()?;
+    if parser.position < parser.tokens.len() {
+        anyhow::bail!(
+            "Unexpected {:?} in expression `{}`",
+            parser.tokens[parser.position],
+            expression
+        );
+    }
+
+    return Ok(value);
+}
+
+pub fn is_truthy(value: &Value) -> bool {
+    return match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(s) => !s.is_empty(),
+        Value::Sequence(items) => !items.is_empty(),
+        Value::Mapping(map) => !map.is_empty(),
+        Value::Tagged(tagged) => is_truthy(&tagged.value),
+    };
+}
+
+fn tokenize(expression: &str) -> anyhow::Result<Vec<Token>> {
+    let chars = expression.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    'outer: while index < chars.len() {
+        let c = chars[index];
+
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        if c == ',' {
+            tokens.push(Token::Comma);
+            index += 1;
+            continue;
+        }
+
+        if c == '(' || c == ')' {
+            tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            index += 1;
+            continue;
+        }
+
+        if c == '\'' || c == '"' {
+            let mut content = String::new();
+            index += 1;
+
+            while index < chars.len() && chars[index] != c {
+                if chars[index] == '\\' && index + 1 < chars.len() {
+                    index += 1;
+                }
+                content.push(chars[index]);
+                index += 1;
+            }
+
+            if index >= chars.len() {
+                anyhow::bail!("Unclosed string in expression `{}`", expression);
+            }
+
+            tokens.push(Token::Literal(Value::String(content)));
+            index += 1;
+            continue;
+        }
+
+        let negative_number = c == '-'
+            && chars
+                .get(index + 1)
+                .is_some_and(|next| next.is_ascii_digit());
+
+        if c.is_ascii_digit() || negative_number {
+            let start = index;
+            index += 1;
+            while index < chars.len() && (chars[index].is_ascii_digit() || chars[index] == '.') {
+                index += 1;
+            }
+
+            let text = chars[start..index].iter().collect::<String>();
+            let value = serde_yaml::from_str::<Value>(&text)
+                .map_err(|_| anyhow::anyhow!("Invalid number {} in expression", text))?;
+            tokens.push(Token::Literal(value));
+            continue;
+        }
+
+        if c.is_alphabetic() || c == '_' {
+            let start = index;
+            while index < chars.len()
+                && (chars[index].is_alphanumeric() || "_.[]-".contains(chars[index]))
+            {
+                index += 1;
+            }
+
+            let word = chars[start..index].iter().collect::<String>();
+            let is_call = chars[index..]
+                .iter()
+                .find(|c| !c.is_whitespace())
+                .is_some_and(|c| *c == '(');
+
+            tokens.push(match word.as_str() {
+                _ if is_call => Token::Call(word),
+                "true" => Token::Literal(Value::Bool(true)),
+                "false" => Token::Literal(Value::Bool(false)),
+                "null" => Token::Literal(Value::Null),
+                _ => Token::Path(word),
+            });
+            continue;
+        }
+
+        for operator in OPERATORS {
+            let matches = operator
+                .chars()
+                .enumerate()
+                .all(|(offset, oc)| chars.get(index + offset) == Some(&oc));
+
+            if matches {
+                tokens.push(Token::Operator(operator));
+                index += operator.len();
+                continue 'outer;
+            }
+        }
+
+        anyhow::bail!("Unexpected '{}' in expression `{}`", c, expression);
+    }
+
+    return Ok(tokens);
+}
+
+struct Parser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    env: &'a EnvMap,
+    // parses an operand without evaluating it, when the other operand of && or || decides the result
+    skip: bool,
+}
+
+impl<'a> Parser<'a> {
+    fn peek_operator(&self, operator: &str) -> bool {
+        return matches!(self.tokens.get(self.position), Some(Token::Operator(o)) if *o == operator);
+    }
+
+    fn or(&mut self) -> anyhow::Result<Value> {
+        let mut left = self.and()?;
+
+        while self.peek_operator("||") {
+            self.position += 1;
+            let decided = is_truthy(&left);
+            let right = self.skipping(decided, Self::and)?;
+            left = Value::Bool(decided || is_truthy(&right));
+        }
+
+        return Ok(left);
+    }
+
+    fn and(&mut self) -> anyhow::Result<Value> {
+        let mut left = self.not()?;
+
+        while self.peek_operator("&&") {
+            self.position += 1;
+            let decided = !is_truthy(&left);
+            let right = self.skipping(decided, Self::not)?;
+            left = Value::Bool(!decided && is_truthy(&right));
+        }
+
+        return Ok(left);
+    }
+
+    // Parses with `parse`, without evaluating anything when `skip` is set
+    fn skipping(
+        &mut self,
+        skip: bool,
+        parse: fn(&mut Self) -> anyhow::Result<Value>,
+    ) -> anyhow::Result<Value> {
+        let previous = self.skip;
+        self.skip = previous || skip;
+        let value = parse(self);
+        self.skip = previous;
+        return value;
+    }
+
+    fn not(&mut self) -> anyhow::Result<Value> {
+        if self.peek_operator("!") {
+            self.position += 1;
+            let value = self.not()?;
+            return Ok(Value::Bool(!is_truthy(&value)));
+        }
+
+        return self.comparison();
+    }
+
+    fn comparison(&mut self) -> anyhow::Result<Value> {
+        let left = self.primary()?;
+
+        let operator = match self.tokens.get(self.position) {
+            Some(Token::Operator(o)) if ["==", "!=", "<", "<=", ">", ">="].contains(o) => *o,
+            _ => return Ok(left),
+        };
+
+        self.position += 1;
+        let right = self.primary()?;
+
+        return Ok(Value::Bool(match operator {
+            "==" => equals(&left, &right),
+            "!=" => !equals(&left, &right),
+            "<" => compare(&left, &right) == Some(Ordering::Less),
+            "<=" => matches!(
+                compare(&left, &right),
+                Some(Ordering::Less | Ordering::Equal)
+            ),
+            ">" => compare(&left, &right) == Some(Ordering::Greater),
+            _ => matches!(
+                compare(&left, &right),
+                Some(Ordering::Greater | Ordering::Equal)
+            ),
+        }));
+    }
+
+    fn primary(&mut self) -> anyhow::Result<Value> {
+        let token = match self.tokens.get(self.position) {
+            Some(token) => token.clone(),
+            None => anyhow::bail!("Unexpected end of expression"),
+        };
+        self.position += 1;
+
+        return match token {
+            Token::Literal(value) => Ok(value),
+            Token::Path(_) if self.skip => Ok(Value::Null),
+            Token::Path(path) => Ok(lookup(&path, self.env).unwrap_or(Value::Null)),
+            Token::Call(name) => {
+                let arguments = self.arguments()?;
+                match self.skip {
+                    true => Ok(Value::Null),
+                    false => self.call(&name, arguments),
+                }
+            }
+            Token::Open => {
+                let value = self.or()?;
+                match self.tokens.get(self.position) {
+                    Some(Token::Close) => {
+                        self.position += 1;
+                        Ok(value)
+                    }
+                    _ => anyhow::bail!("Missing closing parenthesis"),
+                }
+            }
+            other => anyhow::bail!("Unexpected {:?}", other),
+        };
+    }
+}
+
+impl<'a> Parser<'a> {
+    // Parses `(argument, ...)` after a function name
+    fn arguments(&mut self) -> anyhow::Result<Vec<Value>> {
+        if self.tokens.get(self.position) != Some(&Token::Open) {
+            anyhow::bail!("Expected ( after function name");
+        }
+        self.position += 1;
+
+        let mut arguments = vec![];
+        if self.tokens.get(self.position) == Some(&Token::Close) {
+            self.position += 1;
+            return Ok(arguments);
+        }
+
+        loop {
+            arguments.push(self.or()?);
+
+            match self.tokens.get(self.position) {
+                Some(Token::Comma) => self.position += 1,
+                Some(Token::Close) => {
+                    self.position += 1;
+                    return Ok(arguments);
+                }
+                _ => anyhow::bail!("Missing closing parenthesis"),
+            };
+        }
+    }
+
+    fn call(&self, name: &str, mut arguments: Vec<Value>) -> anyhow::Result<Value> {
+        let function = match name.rsplit_once('.') {
+            Some((receiver, function)) => {
+                let receiver = match is_response(receiver) {
+                    true => format!("{}.body", receiver),
+                    false => receiver.to_string(),
+                };
+                arguments.insert(0, lookup(&receiver, self.env).unwrap_or(Value::Null));
+                function
+            }
+            None => name,
+        };
+
+        return match (function, arguments.as_slice()) {
+            ("json_path", [value, path]) => {
+                Ok(json_path_first(value, &value_to_string(path))?.unwrap_or(Value::Null))
+            }
+            ("json_path_all", [value, path]) => {
+                Ok(Value::Sequence(json_path(value, &value_to_string(path))?))
+            }
+            ("xpath", [value, path]) => Ok(xpath(value, &value_to_string(path))?
+                .into_iter()
+                .next()
+                .unwrap_or(Value::Null)),
+            ("xpath_all", [value, path]) => {
+                Ok(Value::Sequence(xpath(value, &value_to_string(path))?))
+            }
+            ("css", [value, selector]) => Ok(css(value, &value_to_string(selector))?
+                .into_iter()
+                .next()
+                .unwrap_or(Value::Null)),
+            ("css_all", [value, selector]) => {
+                Ok(Value::Sequence(css(value, &value_to_string(selector))?))
+            }
+            ("sha256", [value]) => Ok(Value::String(sha256(value_to_string(value).as_bytes()))),
+            ("file_sha256", [path]) => {
+                let path = value_to_string(path);
+                let content = std::fs::read(&path)
+                    .map_err(|e| anyhow::anyhow!("Failed to read \"{}\": {}", path, e))?;
+                Ok(Value::String(sha256(&content)))
+            }
+            ("len", [value]) => Ok(Value::Number(
+                (match value {
+                    Value::String(s) => s.chars().count(),
+                    Value::Sequence(items) => items.len(),
+                    Value::Mapping(map) => map.len(),
+                    _ => 0,
+                } as u64)
+                    .into(),
+            )),
+            ("contains", [value, item]) => Ok(Value::Bool(match value {
+                Value::String(s) => s.contains(&value_to_string(item)),
+                Value::Sequence(items) => items.iter().any(|i| equals(i, item)),
+                Value::Mapping(map) => map.contains_key(item),
+                _ => false,
+            })),
+            (function, arguments) => anyhow::bail!(
+                "Unknown function {}() with {} arguments",
+                function,
+                arguments.len()
+            ),
+        };
+    }
+}
+
+// The path of a whole response, eg: response or responses.login
+fn is_response(path: &str) -> bool {
+    return path == "response"
+        || path
+            .strip_prefix("responses.")
+            .is_some_and(|name| !name.is_empty() && !name.contains(['.', '[']));
+}
+
+fn as_number(value: &Value) -> Option<f64> {
+    return match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+}
+
+// Both sides as numbers when one of them is a number, eg: a status compared with "200".
+// Two strings are compared as they are, so '01' and '1' differ.
+fn numbers(left: &Value, right: &Value) -> Option<(f64, f64)> {
+    if !matches!(left, Value::Number(_)) && !matches!(right, Value::Number(_)) {
+        return None;
+    }
+
+    return Some((as_number(left)?, as_number(right)?));
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    if let Some((l, r)) = numbers(left, right) {
+        return l == r;
+    }
+
+    return match (left, right) {
+        (Value::Null, Value::Null) => true,
+        (Value::Null, _) | (_, Value::Null) => false,
+        (Value::Bool(l), Value::Bool(r)) => l == r,
+        (Value::String(_), _) | (_, Value::String(_)) => {
+            value_to_string(left) == value_to_string(right)
+        }
+        _ => left == right,
+    };
+}
+
+fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    if let Some((l, r)) = numbers(left, right) {
+        return l.partial_cmp(&r);
+    }
+
+    return match (left, right) {
+        (Value::String(l), Value::String(r)) => Some(l.cmp(r)),
+        _ => None,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> EnvMap {
+        let response = serde_yaml::from_str::<Value>("{status: 200, body: null}").unwrap();
+        return EnvMap::from([
+            ("response".to_string(), response),
+            ("count".to_string(), Value::from(3)),
+        ]);
+    }
+
+    fn value(expression: &str) -> Value {
+        return evaluate_value(expression, &env()).unwrap();
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        assert_eq!(value("true || false && false"), Value::Bool(true));
+        assert_eq!(value("false && false || true"), Value::Bool(true));
+        assert_eq!(value("(true || false) && false"), Value::Bool(false));
+    }
+
+    #[test]
+    fn not_and_comparisons_bind_tighter_than_logic() {
+        assert_eq!(value("!false && false"), Value::Bool(false));
+        assert_eq!(value("!(false && false)"), Value::Bool(true));
+        assert_eq!(value("count > 2 && count <= 3"), Value::Bool(true));
+        assert_eq!(
+            value("response.status == 404 || count != 3"),
+            Value::Bool(false)
+        );
+    }
+
+    #[test]
+    fn operands_that_dont_decide_are_not_evaluated() {
+        assert_eq!(
+            value("response.body && xpath(response.body, '/id')"),
+            Value::Bool(false)
+        );
+        assert_eq!(value("count == 3 || unknown(1)"), Value::Bool(true));
+        assert_eq!(
+            value("false && (true || unknown(1)) || true"),
+            Value::Bool(true)
+        );
+        assert_eq!(value("true || false && unknown(1)"), Value::Bool(true));
+    }
+
+    #[test]
+    fn operands_that_decide_are_evaluated() {
+        assert!(evaluate_value("true && unknown(1)", &env()).is_err());
+        assert!(evaluate_value("false || unknown(1)", &env()).is_err());
+    }
+
+    #[test]
+    fn skipped_operands_are_still_parsed() {
+        assert!(evaluate_value("false && (true", &env()).is_err());
+        assert!(evaluate_value("true || len(", &env()).is_err());
+        assert!(evaluate_value("false && true true", &env()).is_err());
+    }
+
+    #[test]
+    fn strings_are_compared_as_strings() {
+        assert_eq!(value("'01' == '1'"), Value::Bool(false));
+        assert_eq!(value("\"1.10\" == \"1.1\""), Value::Bool(false));
+        assert_eq!(value("'1.10' != '1.1'"), Value::Bool(true));
+        assert_eq!(value("'10' > '9'"), Value::Bool(false));
+        assert_eq!(value("'abc' == 'abc'"), Value::Bool(true));
+    }
+
+    #[test]
+    fn strings_are_read_as_numbers_against_a_number() {
+        assert_eq!(value("response.status == '200'"), Value::Bool(true));
+        assert_eq!(value("'01' == 1"), Value::Bool(true));
+        assert_eq!(value("'1.10' == 1.1"), Value::Bool(true));
+        assert_eq!(value("'10' > 9"), Value::Bool(true));
+        assert_eq!(value("count == 'three'"), Value::Bool(false));
+    }
+}
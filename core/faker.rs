@@ -0,0 +1,53 @@
+use fake::{
+    Fake,
+    faker::{
+        address::en::{BuildingNumber, CityName, CountryName, StreetName, ZipCode},
+        company::en::CompanyName,
+        internet::en::{SafeEmail, Username},
+        lorem::en::{Paragraph, Sentence, Word},
+        name::en::{FirstName, LastName, Name},
+        phone_number::en::PhoneNumber,
+    },
+};
+
+// Fake data generators, called from placeholders as `{{fake.<name>()}}`, eg: {{fake.email()}}
+pub const FAKERS: &[&str] = &[
+    "name",
+    "first_name",
+    "last_name",
+    "email",
+    "username",
+    "phone",
+    "address",
+    "city",
+    "country",
+    "zip",
+    "company",
+    "word",
+    "sentence",
+    "paragraph",
+];
+
+pub fn fake(name: &str) -> Option<String> {
+    return Some(match name {
+        "name" => Name().fake(),
+        "first_name" => FirstName().fake(),
+        "last_name" => LastName().fake(),
+        "email" => SafeEmail().fake(),
+        "username" => Username().fake(),
+        "phone" => PhoneNumber().fake(),
+        "address" => format!(
+            "{} {}",
+            BuildingNumber().fake::<String>(),
+            StreetName().fake::<String>()
+        ),
+        "city" => CityName().fake(),
+        "country" => CountryName().fake(),
+        "zip" => ZipCode().fake(),
+        "company" => CompanyName().fake(),
+        "word" => Word().fake(),
+        "sentence" => Sentence(4..10).fake(),
+        "paragraph" => Paragraph(3..6).fake(),
+        _ => return None,
+    });
+}
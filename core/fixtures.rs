@@ -0,0 +1,229 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use anyhow::Context;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use crate::runner::{ResponseObject, Runner};
+
+/// Controls how the runner uses recorded responses.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FixtureMode {
+    #[default]
+    Off,
+    // save every response to the fixtures folder
+    Record,
+    // serve responses from the fixtures folder without sending requests
+    Replay,
+    // send requests and fail if the response differs from the fixture
+    Verify,
+}
+
+/// A response saved to disk, keyed by the name of the request it belongs to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseFixture {
+    pub status: u16,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+    // set when the body isn't utf-8, eg: msgpack or an image, so it's saved encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<FixtureEncoding>,
+}
+
+/// How the body of a fixture is encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FixtureEncoding {
+    Base64,
+}
+
+impl ResponseFixture {
+    // The bytes of the body, decoded if it was saved encoded
+    pub fn body_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        return match self.encoding {
+            None => Ok(self.body.as_bytes().to_vec()),
+            Some(FixtureEncoding::Base64) => base64::engine::general_purpose::STANDARD
+                .decode(&self.body)
+                .context("Invalid base64 body"),
+        };
+    }
+}
+
+impl From<&ResponseObject> for ResponseFixture {
+    fn from(response: &ResponseObject) -> Self {
+        let (body, encoding) = match String::from_utf8(response.body.clone()) {
+            Ok(text) => (text, None),
+            Err(_) => (
+                base64::engine::general_purpose::STANDARD.encode(&response.body),
+                Some(FixtureEncoding::Base64),
+            ),
+        };
+
+        return ResponseFixture {
+            status: response.status,
+            headers: response.headers.clone(),
+            body,
+            encoding,
+        };
+    }
+}
+
+impl TryFrom<ResponseFixture> for ResponseObject {
+    type Error = anyhow::Error;
+
+    fn try_from(fixture: ResponseFixture) -> anyhow::Result<Self> {
+        let body = fixture.body_bytes()?;
+
+        return Ok(ResponseObject {
+            status: fixture.status,
+            headers: fixture.headers,
+            raw_size: body.len(),
+            body,
+            redirects: vec![],
+            encoding: None,
+            cache: None,
+            pages: None,
+            upload: None,
+            download: None,
+            overflow: None,
+            wire: None,
+        });
+    }
+}
+
+impl Runner {
+    pub fn get_fixtures_dir(&self) -> PathBuf {
+        return self
+            .project
+            .get_root_dir()
+            .join(match &self.project.object.fixtures_dir {
+                Some(dir) => dir,
+                None => "fixtures",
+            });
+    }
+
+    pub fn get_fixture_path(&self, name: &str) -> PathBuf {
+        return self.get_fixtures_dir().join(format!("{}.json", name));
+    }
+
+    pub async fn read_fixture(&self, name: &str) -> anyhow::Result<ResponseObject> {
+        let path = self.get_fixture_path(name);
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("No fixture recorded for \"{}\" at {:?}", name, &path))?;
+
+        let fixture = serde_json::from_str::<ResponseFixture>(&content)
+            .with_context(|| format!("Invalid fixture for \"{}\"", name))?;
+
+        return ResponseObject::try_from(fixture)
+            .with_context(|| format!("Invalid fixture for \"{}\"", name));
+    }
+
+    pub async fn write_fixture(&self, name: &str, response: &ResponseObject) -> anyhow::Result<()> {
+        let path = self.get_fixture_path(name);
+        tokio::fs::create_dir_all(self.get_fixtures_dir()).await?;
+
+        let content = serde_json::to_string_pretty(&ResponseFixture::from(response))?;
+        tokio::fs::write(&path, content)
+            .await
+            .with_context(|| format!("Failed to write fixture {:?}", &path))?;
+
+        tracing::info!("Recorded fixture for {}", name);
+        return Ok(());
+    }
+
+    // Compares a live response against its fixture. Headers are not compared since
+    // they usually carry dates and ids that change between calls.
+    pub async fn verify_fixture(
+        &self,
+        name: &str,
+        response: &ResponseObject,
+    ) -> anyhow::Result<()> {
+        let fixture = ResponseFixture::from(&self.read_fixture(name).await?);
+        let actual = ResponseFixture::from(response);
+
+        if fixture.status != actual.status {
+            anyhow::bail!(
+                "Fixture mismatch for \"{}\": expected status {}, got {}",
+                name,
+                fixture.status,
+                actual.status
+            );
+        }
+
+        if fixture.body != actual.body {
+            anyhow::bail!("Fixture mismatch for \"{}\": response body differs", name);
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn runner() -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-fixtures-{}", uuid::Uuid::new_v4()));
+        return Runner::new(
+            crate::fs::FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![],
+        )
+        .unwrap();
+    }
+
+    fn response(body: &[u8]) -> ResponseObject {
+        return ResponseObject {
+            status: 200,
+            headers: HashMap::from([(
+                "content-type".to_string(),
+                "application/msgpack".to_string(),
+            )]),
+            raw_size: body.len(),
+            body: body.to_vec(),
+            ..Default::default()
+        };
+    }
+
+    #[tokio::test]
+    async fn bodies_that_arent_utf8_are_replayed_as_recorded() {
+        let runner = runner();
+        let body = [0x82, 0xa1, 0x61, 0x01, 0xff, 0xfe, 0x00];
+        runner
+            .write_fixture("binary", &response(&body))
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(runner.get_fixture_path("binary")).unwrap();
+        let fixture = serde_json::from_str::<ResponseFixture>(&content).unwrap();
+        assert_eq!(fixture.encoding, Some(FixtureEncoding::Base64));
+
+        let replayed = runner.read_fixture("binary").await.unwrap();
+        assert_eq!(replayed.body, body);
+        assert_eq!(replayed.status, 200);
+        runner
+            .verify_fixture("binary", &response(&body))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn text_bodies_are_saved_as_is() {
+        let runner = runner();
+        runner
+            .write_fixture("text", &response(b"{\"id\": 1}"))
+            .await
+            .unwrap();
+
+        let content = std::fs::read_to_string(runner.get_fixture_path("text")).unwrap();
+        let fixture = serde_json::from_str::<ResponseFixture>(&content).unwrap();
+        assert_eq!(fixture.body, "{\"id\": 1}");
+        assert_eq!(fixture.encoding, None);
+        assert!(!content.contains("encoding"));
+
+        let replayed = runner.read_fixture("text").await.unwrap();
+        assert_eq!(replayed.body, b"{\"id\": 1}");
+    }
+}
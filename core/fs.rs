@@ -1,10 +1,61 @@
 use std::path::{Path, PathBuf};
 
 use anyhow::Context;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tokio::io::AsyncReadExt;
 
-use crate::schema::roots::{ProjectRootSchema, RequestRootSchema};
+use crate::{
+    graphql::query_files,
+    schema::{
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        roots::{ProjectRootSchema, RequestRootSchema},
+        validation::ResponseValidationSchema,
+    },
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LoadFailureKind {
+    Missing,
+    Parse,
+    Conflict,
+}
+
+/// A file of the project that couldn't be loaded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadFailure {
+    pub path: PathBuf,
+    pub kind: LoadFailureKind,
+    pub message: String,
+}
+
+impl std::fmt::Display for LoadFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let kind = match self.kind {
+            LoadFailureKind::Missing => "missing",
+            LoadFailureKind::Parse => "invalid",
+            LoadFailureKind::Conflict => "conflict",
+        };
+        return write!(f, "{:?} ({}): {}", self.path, kind, self.message);
+    }
+}
+
+/// Every file that failed while loading the requests of a project.
+#[derive(Debug)]
+pub struct LoadError {
+    pub failures: Vec<LoadFailure>,
+}
+
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to load {} request file(s)", self.failures.len())?;
+        for failure in &self.failures {
+            write!(f, "\n  {}", failure)?;
+        }
+        return Ok(());
+    }
+}
+
+impl std::error::Error for LoadError {}
 
 #[derive(Clone, PartialEq)]
 pub struct FileObject<T: Clone + PartialEq + Deserialize<'static>> {
@@ -23,6 +74,18 @@ impl<T: Clone + PartialEq + Deserialize<'static>> FileObject<T> {
     }
 }
 
+impl<T: Clone + PartialEq + Serialize + Deserialize<'static>> FileObject<T> {
+    // Writes the object back to the file it was loaded from
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let content = serde_yaml::to_string(&self.object)?;
+        tokio::fs::write(&self.path, content)
+            .await
+            .with_context(|| format!("Failed to write {:?}", &self.path))?;
+
+        return Ok(());
+    }
+}
+
 impl ProjectRootSchema {
     pub async fn load(path: &Path) -> anyhow::Result<FileObject<ProjectRootSchema>> {
         tracing::info!("Opening file");
@@ -39,49 +102,254 @@ impl ProjectRootSchema {
         let mut content = String::new();
 
         reader.read_to_string(&mut content).await?;
-        tracing::info!("{}", &content);
-        let object = serde_yaml::from_str::<ProjectRootSchema>(&content)?;
+        tracing::debug!("Loading project {:?}", path);
+        let object = serde_yaml::from_str::<ProjectRootSchema>(&content)
+            .with_context(|| format!("Failed to parse project {:?}", path))?;
 
         return Ok(FileObject::new(path.to_path_buf(), object));
     }
 }
 
 impl FileObject<ProjectRootSchema> {
-    fn get_requests_dir(&self) -> PathBuf {
-        return self.path.join(match &self.object.requests_dir {
+    // The folder holding the project file
+    pub fn get_root_dir(&self) -> PathBuf {
+        return match self.path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => self.path.clone(),
+        };
+    }
+
+    pub fn get_requests_dir(&self) -> PathBuf {
+        return self.get_root_dir().join(match &self.object.requests_dir {
             Some(dir) => dir,
             None => "requests",
         });
     }
 
+    // Loads every request file in the requests folder, unchanged files come from the schema cache.
+    // Every file that fails is reported together, unless the project skips invalid requests.
     pub async fn get_requests(&self) -> anyhow::Result<Vec<FileObject<RequestRootSchema>>> {
-        let dir = self.get_requests_dir();
-        let mut reader = tokio::fs::read_dir(dir).await?;
-        let mut result = vec![];
+        let mut index = self.build_index().await;
+        let result = index.load_all().await;
 
-        while let Some(entry) = reader.next_entry().await? {
-            let path = entry.path();
-            let file = tokio::fs::File::open(&path)
-                .await
-                .context("Failed to open request")?;
+        if let Err(e) = index.save_cache().await {
+            tracing::warn!("{:#}", e);
+        }
 
-            let mut file_reader = tokio::io::BufReader::new(file);
-            let mut content = String::new();
+        return result;
+    }
 
-            file_reader.read_to_string(&mut content).await?;
-            let object = serde_yaml::from_str::<RequestRootSchema>(&content)
-                .context("Parsing request root error")?;
+    // Writes a new request file to the requests folder
+    pub async fn create_request(
+        &self,
+        name: &str,
+        request: RequestRootSchema,
+    ) -> anyhow::Result<FileObject<RequestRootSchema>> {
+        let name = name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Request name cannot be empty");
+        }
+
+        let dir = self.get_requests_dir();
+        let path = dir.join(format!("{}.nd", name));
 
-            result.push(FileObject::new(path, object));
+        if path.exists() {
+            anyhow::bail!("Request \"{}\" already exists", name);
         }
 
-        return Ok(result);
+        tokio::fs::create_dir_all(&dir).await?;
+        tokio::fs::write(&path, serde_yaml::to_string(&request)?)
+            .await
+            .context("Failed to write request")?;
+
+        return Ok(FileObject::new(path, request));
+    }
+}
+
+// Files in the requests folder, sorted so conflicts are reported the same way every time
+pub(crate) async fn request_paths(dir: &Path) -> Result<Vec<PathBuf>, LoadFailure> {
+    let failure = |e: std::io::Error| LoadFailure {
+        path: dir.to_path_buf(),
+        kind: LoadFailureKind::Missing,
+        message: e.to_string(),
+    };
+
+    let mut reader = tokio::fs::read_dir(dir).await.map_err(failure)?;
+    let mut paths = vec![];
+
+    while let Some(entry) = reader.next_entry().await.map_err(failure)? {
+        let path = entry.path();
+        if path.is_file() {
+            paths.push(path);
+        }
     }
+
+    paths.sort();
+    return Ok(paths);
+}
+
+pub(crate) async fn read_request(path: &Path) -> Result<RequestRootSchema, LoadFailure> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) => {
+            return Err(LoadFailure {
+                path: path.to_path_buf(),
+                kind: LoadFailureKind::Missing,
+                message: e.to_string(),
+            });
+        }
+    };
+
+    return serde_yaml::from_str::<RequestRootSchema>(&content).map_err(|e| LoadFailure {
+        path: path.to_path_buf(),
+        kind: LoadFailureKind::Parse,
+        message: e.to_string(),
+    });
+}
+
+pub(crate) fn call_name(path: &Path) -> String {
+    return match path.file_stem() {
+        Some(stem) => stem.to_string_lossy().to_string(),
+        None => String::new(),
+    };
 }
 
 impl FileObject<RequestRootSchema> {
     pub fn get_name(&self) -> String {
-        let filename = self.path.file_name().unwrap();
-        return filename.to_str().unwrap().to_string();
+        return match self.path.file_name() {
+            Some(filename) => filename.to_string_lossy().to_string(),
+            None => String::new(),
+        };
     }
-}
\ No newline at end of file
+
+    // The name used to reference this request from calls (the file name without extension)
+    pub fn get_call_name(&self) -> String {
+        return call_name(&self.path);
+    }
+
+    // Files read when the request is called (body files, response schema), resolved from its folder
+    pub fn get_referenced_files(&self) -> Vec<PathBuf> {
+        let mut files = vec![];
+
+        match &self.object.body {
+            Some(RequestBodySchema::Json {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Xml {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Text {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Soap {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::FormUrlencoded {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Msgpack {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Cbor {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Ndjson {
+                content_file: Some(path),
+                ..
+            })
+            | Some(RequestBodySchema::Binary { path, .. }) => files.push(path.clone()),
+            Some(RequestBodySchema::Multipart { parts }) => {
+                for part in parts {
+                    if let MultipartPartSchema::File { path, .. } = part {
+                        files.push(path.clone());
+                    }
+                }
+            }
+            _ => {}
+        };
+
+        if let Some(ResponseValidationSchema::Path(path)) = &self.object.response_schema {
+            files.push(path.clone());
+        }
+
+        let dir = match self.path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::new(),
+        };
+
+        // query files bring the fragments they import along
+        if let Some(RequestBodySchema::Graphql {
+            query_file: Some(path),
+            ..
+        }) = &self.object.body
+        {
+            return files
+                .iter()
+                .map(|file| dir.join(file))
+                .chain(query_files(&dir.join(path)).unwrap_or_else(|_| vec![dir.join(path)]))
+                .collect();
+        }
+
+        return files.iter().map(|file| dir.join(file)).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("dotapi-fs-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        return root.join(name);
+    }
+
+    #[tokio::test]
+    async fn saved_requests_only_hold_what_was_set_in_order() {
+        let content = "method: POST
+url: /users
+config:
+  class: users
+headers:
+  X-Zeta: '1'
+  Accept: application/json
+  X-Alpha: '2'
+query:
+  page: '1'
+  limit: '20'
+  after: abc
+";
+        let path = temp_path("users.nd");
+        let request = FileObject::new(
+            path.clone(),
+            serde_yaml::from_str::<RequestRootSchema>(content).unwrap(),
+        );
+
+        request.save().await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn saved_projects_dont_write_defaults() {
+        let content = "project:
+  name: api
+calls:
+  main: []
+";
+        let path = temp_path(".nd-project");
+        let project = FileObject::new(
+            path.clone(),
+            serde_yaml::from_str::<ProjectRootSchema>(content).unwrap(),
+        );
+
+        project.save().await.unwrap();
+        assert_eq!(tokio::fs::read_to_string(&path).await.unwrap(), content);
+    }
+}
@@ -0,0 +1,258 @@
+use base64::Engine;
+use serde_yaml::Value;
+
+use crate::{
+    faker,
+    interpolation::{EnvMap, UnresolvedError, lookup, value_to_string},
+};
+
+// Functions that can be called from a placeholder to generate values, eg:
+//   {{uuid()}}, {{now('%Y-%m-%d')}}, {{random_int(1, 100)}}, {{base64(credentials)}}, {{env('HOME')}}
+// Arguments are 'quoted' or "quoted" strings, numbers or env variables.
+// Fake data is generated with `fake.<name>()`, see faker::FAKERS.
+
+pub const FUNCTIONS: &[&str] = &["uuid", "now", "timestamp", "random_int", "base64", "env"];
+
+// Whether a placeholder is a function call rather than a variable
+pub fn is_call(key: &str) -> bool {
+    return parse_call(key).is_some();
+}
+
+pub fn call(key: &str, env: &EnvMap) -> anyhow::Result<Value> {
+    let (name, arguments) = match parse_call(key) {
+        Some(call) => call,
+        None => anyhow::bail!("\"{}\" is not a function call", key),
+    };
+
+    let arguments = split_arguments(arguments)?
+        .iter()
+        .map(|argument| evaluate_argument(argument, env))
+        .collect::<anyhow::Result<Vec<Value>>>()?;
+
+    return match (name, arguments.as_slice()) {
+        ("uuid", []) => Ok(Value::String(uuid::Uuid::new_v4().to_string())),
+        ("now", []) => Ok(Value::String(chrono::Utc::now().to_rfc3339())),
+        ("now", [format]) => Ok(Value::String(format_now(&value_to_string(format))?)),
+        ("timestamp", []) => Ok(Value::Number(chrono::Utc::now().timestamp().into())),
+        ("random_int", [min, max]) => {
+            let (min, max) = (as_integer(min)?, as_integer(max)?);
+            if min > max {
+                anyhow::bail!("random_int: {} is greater than {}", min, max);
+            }
+            Ok(Value::Number(rand::random_range(min..=max).into()))
+        }
+        ("base64", [value]) => Ok(Value::String(
+            base64::engine::general_purpose::STANDARD.encode(value_to_string(value)),
+        )),
+        ("env", [name]) => {
+            let name = value_to_string(name);
+            match std::env::var(&name) {
+                Ok(value) => Ok(Value::String(value)),
+                Err(_) => anyhow::bail!("Environment variable \"{}\" is not set", name),
+            }
+        }
+        (name, []) if name.starts_with("fake.") => match faker::fake(&name["fake.".len()..]) {
+            Some(value) => Ok(Value::String(value)),
+            None => anyhow::bail!(
+                "Unknown fake data {}(), expected one of: {}",
+                name,
+                faker::FAKERS.join(", ")
+            ),
+        },
+        (name, arguments) if FUNCTIONS.contains(&name) || name.starts_with("fake.") => {
+            anyhow::bail!(
+                "Wrong number of arguments ({}) for {}()",
+                arguments.len(),
+                name
+            )
+        }
+        (name, _) => anyhow::bail!("Unknown function {}()", name),
+    };
+}
+
+// The current time in a strftime format, an invalid format fails instead of panicking when displayed
+fn format_now(format: &str) -> anyhow::Result<String> {
+    let items = chrono::format::StrftimeItems::new(format).collect::<Vec<_>>();
+    if items.contains(&chrono::format::Item::Error) {
+        anyhow::bail!("now: invalid date format '{}'", format);
+    }
+
+    return Ok(chrono::Utc::now()
+        .format_with_items(items.into_iter())
+        .to_string());
+}
+
+// Splits `name(arguments)` into the name and the raw arguments
+fn parse_call(key: &str) -> Option<(&str, &str)> {
+    let key = key.trim();
+    let open = key.find('(')?;
+    let inner = key[open + 1..].strip_suffix(')')?;
+    let name = key[..open].trim();
+
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+    {
+        return None;
+    }
+
+    return Some((name, inner));
+}
+
+// Splits arguments on the commas that are not within quotes
+fn split_arguments(arguments: &str) -> anyhow::Result<Vec<String>> {
+    let mut result = vec![];
+    let mut current = String::new();
+    let mut quote = None;
+
+    for c in arguments.chars() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (',', None) => {
+                result.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        };
+        current.push(c);
+    }
+
+    if quote.is_some() {
+        anyhow::bail!("Unclosed string in \"{}\"", arguments);
+    }
+
+    if !current.trim().is_empty() || !result.is_empty() {
+        result.push(current);
+    }
+
+    return Ok(result);
+}
+
+fn evaluate_argument(argument: &str, env: &EnvMap) -> anyhow::Result<Value> {
+    let argument = argument.trim();
+
+    for quote in ['\'', '"'] {
+        if let Some(content) = argument
+            .strip_prefix(quote)
+            .and_then(|a| a.strip_suffix(quote))
+        {
+            return Ok(Value::String(content.to_string()));
+        }
+    }
+
+    if let Ok(number) = argument.parse::<i64>() {
+        return Ok(Value::Number(number.into()));
+    }
+
+    if let Ok(number) = argument.parse::<f64>() {
+        return Ok(Value::Number(number.into()));
+    }
+
+    return match lookup(argument, env) {
+        Some(value) => Ok(value),
+        None => Err(UnresolvedError {
+            name: argument.to_string(),
+        }
+        .into()),
+    };
+}
+
+fn as_integer(value: &Value) -> anyhow::Result<i64> {
+    return match value {
+        Value::Number(n) if n.as_i64().is_some() => Ok(n.as_i64().unwrap_or_default()),
+        Value::String(s) => s
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| anyhow::anyhow!("\"{}\" is not an integer", s)),
+        other => anyhow::bail!("{:?} is not an integer", other),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env() -> EnvMap {
+        return EnvMap::from([
+            ("user".to_string(), Value::String("me:secret".to_string())),
+            ("low".to_string(), Value::Number(5.into())),
+        ]);
+    }
+
+    #[test]
+    fn parses_calls_and_leaves_variables() {
+        assert!(is_call("uuid()"));
+        assert!(is_call(" random_int(1, 2) "));
+        assert!(!is_call("user"));
+        assert!(!is_call("user.name"));
+        assert!(!is_call("a b(1)"));
+    }
+
+    #[test]
+    fn splits_arguments_outside_quotes() {
+        assert_eq!(split_arguments("'a, b', 2").unwrap(), ["'a, b'", " 2"]);
+        assert!(split_arguments("").unwrap().is_empty());
+        assert!(split_arguments("'a").is_err());
+    }
+
+    #[test]
+    fn arguments_are_strings_numbers_or_variables() {
+        assert_eq!(
+            call("base64(user)", &env()).unwrap(),
+            Value::String("bWU6c2VjcmV0".to_string())
+        );
+        assert_eq!(
+            call("base64(\"a,b\")", &env()).unwrap(),
+            Value::String("YSxi".to_string())
+        );
+        assert!(call("base64(missing)", &env()).is_err());
+    }
+
+    #[test]
+    fn random_int_stays_within_bounds() {
+        for _ in 0..20 {
+            let value = call("random_int(low, 7)", &env()).unwrap();
+            let value = value.as_i64().unwrap();
+            assert!((5..=7).contains(&value));
+        }
+        assert!(call("random_int(3, 1)", &env()).is_err());
+        assert!(call("random_int('a', 1)", &env()).is_err());
+    }
+
+    #[test]
+    fn now_uses_the_format() {
+        let value = call("now('%Y')", &env()).unwrap();
+        assert_eq!(value_to_string(&value).len(), 4);
+        assert!(uuid::Uuid::parse_str(&value_to_string(&call("uuid()", &env()).unwrap())).is_ok());
+    }
+
+    #[test]
+    fn now_rejects_invalid_formats() {
+        for format in ["%Q", "%", "%Y-%"] {
+            let error = call(&format!("now('{}')", format), &env()).unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("now: invalid date format '{}'", format)
+            );
+        }
+    }
+
+    #[test]
+    fn fake_data_for_every_faker() {
+        for name in faker::FAKERS {
+            let value = call(&format!("fake.{}()", name), &env()).unwrap();
+            assert!(!value_to_string(&value).is_empty(), "{}", name);
+        }
+        assert!(value_to_string(&call("fake.email()", &env()).unwrap()).contains('@'));
+        assert!(call("fake.nope()", &env()).is_err());
+        assert!(call("fake.email(1)", &env()).is_err());
+    }
+
+    #[test]
+    fn unknown_functions_and_wrong_arity_fail() {
+        assert!(call("nope()", &env()).is_err());
+        assert!(call("uuid(1)", &env()).is_err());
+    }
+}
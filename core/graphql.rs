@@ -0,0 +1,655 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::{
+    runner::{ResolvedRequest, Runner, sha256},
+    schema::request_body::RequestBodySchema,
+};
+
+// Only what validation needs: the root types and the fields of every type
+const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types { name fields(includeDeprecated: true) { name type { ...TypeRef } } }
+  }
+}
+fragment TypeRef on __Type { name ofType { name ofType { name ofType { name ofType { name } } } } }";
+
+/// The types of a GraphQL endpoint, from its introspection, used to check queries before sending them.
+#[derive(Debug, Clone, Default)]
+pub struct GraphqlSchema {
+    // operation (query, mutation, subscription) -> root type
+    roots: HashMap<String, String>,
+    // type -> field -> type of the field, without list and non-null wrappers
+    types: HashMap<String, HashMap<String, String>>,
+}
+
+impl GraphqlSchema {
+    // Reads the result of the introspection query
+    pub fn from_introspection(result: &Value) -> anyhow::Result<GraphqlSchema> {
+        let schema = result
+            .pointer("/data/__schema")
+            .context("The introspection result has no data.__schema")?;
+
+        let mut roots = HashMap::new();
+        for (operation, key) in [
+            ("query", "queryType"),
+            ("mutation", "mutationType"),
+            ("subscription", "subscriptionType"),
+        ] {
+            if let Some(name) = schema
+                .pointer(&format!("/{}/name", key))
+                .and_then(Value::as_str)
+            {
+                roots.insert(operation.to_string(), name.to_string());
+            }
+        }
+
+        let mut types = HashMap::new();
+        for object in schema["types"].as_array().cloned().unwrap_or_default() {
+            let name = match object["name"].as_str() {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+
+            let mut fields = HashMap::new();
+            for field in object["fields"].as_array().cloned().unwrap_or_default() {
+                if let (Some(field_name), Some(field_type)) =
+                    (field["name"].as_str(), named_type(&field["type"]))
+                {
+                    fields.insert(field_name.to_string(), field_type);
+                }
+            }
+
+            types.insert(name, fields);
+        }
+
+        return Ok(GraphqlSchema { roots, types });
+    }
+
+    // Lists the fields of the document that its types don't have, eg: Unknown field "emial" on type "User"
+    pub fn validate(&self, document: &str) -> anyhow::Result<Vec<String>> {
+        let mut parser = DocumentParser {
+            tokens: tokenize(document)?,
+            position: 0,
+            schema: self,
+            errors: vec![],
+        };
+
+        parser.document()?;
+        return Ok(parser.errors);
+    }
+}
+
+// The name of a type, unwrapping lists and non-null types
+fn named_type(value: &Value) -> Option<String> {
+    return match value["name"].as_str() {
+        Some(name) => Some(name.to_string()),
+        None if value["ofType"].is_object() => named_type(&value["ofType"]),
+        None => None,
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Punctuator(char),
+    Spread,
+    Value, // strings and numbers, their content doesn't matter to validation
+}
+
+fn tokenize(document: &str) -> anyhow::Result<Vec<Token>> {
+    let chars = document.chars().collect::<Vec<char>>();
+    let mut tokens = vec![];
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+
+        if c.is_whitespace() || c == ',' || c == '\u{feff}' {
+            index += 1;
+        } else if c == '#' {
+            while index < chars.len() && chars[index] != '\n' {
+                index += 1;
+            }
+        } else if chars[index..].starts_with(&['.', '.', '.']) {
+            tokens.push(Token::Spread);
+            index += 3;
+        } else if chars[index..].starts_with(&['"', '"', '"']) {
+            index += 3;
+            while index < chars.len() && !chars[index..].starts_with(&['"', '"', '"']) {
+                index += if chars[index..].starts_with(&['\\', '"', '"', '"']) {
+                    4
+                } else {
+                    1
+                };
+            }
+            if index >= chars.len() {
+                anyhow::bail!("Unclosed block string in GraphQL document");
+            }
+            tokens.push(Token::Value);
+            index += 3;
+        } else if c == '"' {
+            index += 1;
+            while index < chars.len() && chars[index] != '"' {
+                index += if chars[index] == '\\' { 2 } else { 1 };
+            }
+            if index >= chars.len() {
+                anyhow::bail!("Unclosed string in GraphQL document");
+            }
+            tokens.push(Token::Value);
+            index += 1;
+        } else if c == '-' || c.is_ascii_digit() {
+            index += 1;
+            while index < chars.len()
+                && (chars[index].is_ascii_alphanumeric() || "+-.".contains(chars[index]))
+            {
+                index += 1;
+            }
+            tokens.push(Token::Value);
+        } else if c == '_' || c.is_ascii_alphabetic() {
+            let start = index;
+            while index < chars.len()
+                && (chars[index] == '_' || chars[index].is_ascii_alphanumeric())
+            {
+                index += 1;
+            }
+            tokens.push(Token::Name(chars[start..index].iter().collect()));
+        } else if "!$&()=:@[]{}|".contains(c) {
+            tokens.push(Token::Punctuator(c));
+            index += 1;
+        } else {
+            anyhow::bail!("Unexpected \"{}\" in GraphQL document", c);
+        }
+    }
+
+    return Ok(tokens);
+}
+
+// Walks the selection sets of a document, checking every field against the type it's selected on
+struct DocumentParser<'a> {
+    tokens: Vec<Token>,
+    position: usize,
+    schema: &'a GraphqlSchema,
+    errors: Vec<String>,
+}
+
+impl DocumentParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        return self.tokens.get(self.position);
+    }
+
+    fn next(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .context("Unexpected end of GraphQL document")?;
+        self.position += 1;
+        return Ok(token);
+    }
+
+    fn is(&self, c: char) -> bool {
+        return self.peek() == Some(&Token::Punctuator(c));
+    }
+
+    fn expect(&mut self, c: char) -> anyhow::Result<()> {
+        return match self.next()? {
+            Token::Punctuator(found) if found == c => Ok(()),
+            token => anyhow::bail!("Expected \"{}\" in GraphQL document, found {:?}", c, token),
+        };
+    }
+
+    fn name(&mut self) -> anyhow::Result<String> {
+        return match self.next()? {
+            Token::Name(name) => Ok(name),
+            token => anyhow::bail!("Expected a name in GraphQL document, found {:?}", token),
+        };
+    }
+
+    fn document(&mut self) -> anyhow::Result<()> {
+        while self.peek().is_some() {
+            if self.is('{') {
+                let root = self.root("query")?;
+                self.selection_set(root.as_deref())?;
+                continue;
+            }
+
+            match self.name()?.as_str() {
+                "fragment" => {
+                    self.name()?;
+                    self.type_condition()?;
+
+                    let on = self.name()?;
+                    let on = self.known_type(on);
+                    self.directives()?;
+                    self.selection_set(on.as_deref())?;
+                }
+                operation @ ("query" | "mutation" | "subscription") => {
+                    let root = self.root(operation)?;
+                    if matches!(self.peek(), Some(Token::Name(_))) {
+                        self.name()?;
+                    }
+                    if self.is('(') {
+                        self.skip_balanced('(', ')')?;
+                    }
+                    self.directives()?;
+                    self.selection_set(root.as_deref())?;
+                }
+                other => anyhow::bail!("Unexpected \"{}\" in GraphQL document", other),
+            };
+        }
+
+        return Ok(());
+    }
+
+    // The root type of an operation, None (after recording the error) when the schema has none
+    fn root(&mut self, operation: &str) -> anyhow::Result<Option<String>> {
+        let root = self.schema.roots.get(operation).cloned();
+        if root.is_none() {
+            self.errors.push(format!(
+                "The schema doesn't support {} operations",
+                operation
+            ));
+        }
+        return Ok(root);
+    }
+
+    // Reads the `on` of a type condition, leaving the type name to the caller
+    fn type_condition(&mut self) -> anyhow::Result<()> {
+        return match self.name()?.as_str() {
+            "on" => Ok(()),
+            other => anyhow::bail!("Expected \"on\" in GraphQL document, found \"{}\"", other),
+        };
+    }
+
+    fn known_type(&mut self, name: String) -> Option<String> {
+        if self.schema.types.contains_key(&name) {
+            return Some(name);
+        }
+
+        self.errors.push(format!("Unknown type \"{}\"", name));
+        return None;
+    }
+
+    // Parent is None when the type couldn't be determined, its fields are parsed but not checked
+    fn selection_set(&mut self, parent: Option<&str>) -> anyhow::Result<()> {
+        self.expect('{')?;
+
+        while !self.is('}') {
+            if self.peek() == Some(&Token::Spread) {
+                self.next()?;
+
+                if self.peek() == Some(&Token::Name("on".to_string())) {
+                    self.type_condition()?;
+                    let on = self.name()?;
+                    let on = self.known_type(on);
+                    self.directives()?;
+                    self.selection_set(on.as_deref())?;
+                } else if self.is('@') || self.is('{') {
+                    self.directives()?;
+                    self.selection_set(parent)?;
+                } else {
+                    // fragment spreads are checked with their definition
+                    self.name()?;
+                    self.directives()?;
+                }
+                continue;
+            }
+
+            let mut field = self.name()?;
+            if self.is(':') {
+                self.next()?;
+                field = self.name()?;
+            }
+            if self.is('(') {
+                self.skip_balanced('(', ')')?;
+            }
+            self.directives()?;
+
+            let field_type = match parent {
+                Some(_) if field.starts_with("__") => None,
+                Some(parent) => {
+                    let field_type = self
+                        .schema
+                        .types
+                        .get(parent)
+                        .and_then(|fields| fields.get(&field))
+                        .cloned();
+                    if field_type.is_none() {
+                        self.errors.push(format!(
+                            "Unknown field \"{}\" on type \"{}\"",
+                            field, parent
+                        ));
+                    }
+                    field_type
+                }
+                None => None,
+            };
+
+            if self.is('{') {
+                self.selection_set(field_type.as_deref())?;
+            }
+        }
+
+        return self.expect('}');
+    }
+
+    fn directives(&mut self) -> anyhow::Result<()> {
+        while self.is('@') {
+            self.next()?;
+            self.name()?;
+            if self.is('(') {
+                self.skip_balanced('(', ')')?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn skip_balanced(&mut self, open: char, close: char) -> anyhow::Result<()> {
+        let mut depth = 0;
+
+        loop {
+            match self.next()? {
+                Token::Punctuator(c) if c == open => depth += 1,
+                Token::Punctuator(c) if c == close => depth -= 1,
+                _ => {}
+            };
+
+            if depth == 0 {
+                return Ok(());
+            }
+        }
+    }
+}
+
+// Reads a query file along with the fragments it imports, eg: #import "./fragments/user.graphql".
+// Imports are relative to the file importing them, and each file is included once.
+pub fn read_query_file(path: &Path) -> anyhow::Result<String> {
+    let mut query = String::new();
+
+    for file in query_files(path)? {
+        let content = std::fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read query file {:?}", &file))?;
+
+        for line in content.lines().filter(|line| import_path(line).is_none()) {
+            query.push_str(line);
+            query.push('\n');
+        }
+    }
+
+    return Ok(query);
+}
+
+// The query file at path followed by every file it imports, directly or not
+pub fn query_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    collect_query_files(path, &mut files)?;
+    return Ok(files);
+}
+
+fn collect_query_files(path: &Path, files: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let path = std::path::absolute(path)?;
+    if files.contains(&path) {
+        return Ok(());
+    }
+
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read query file {:?}", &path))?;
+    files.push(path.clone());
+
+    let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+    for import in content.lines().filter_map(import_path) {
+        collect_query_files(&dir.join(import), files)
+            .with_context(|| format!("Failed to import {:?} in {:?}", import, &path))?;
+    }
+
+    return Ok(());
+}
+
+// The path of an #import line
+fn import_path(line: &str) -> Option<&str> {
+    let path = line.trim().strip_prefix("#import")?.trim();
+    return path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+        .or_else(|| {
+            path.strip_prefix('\'')
+                .and_then(|path| path.strip_suffix('\''))
+        });
+}
+
+// The json sent for a graphql body. Persisted queries send the hash of the query in place of it,
+// following the automatic persisted queries convention, unless the query has to be registered.
+pub fn graphql_payload(
+    query: &str,
+    variables: &Option<serde_yaml::Value>,
+    persisted: bool,
+    register: bool,
+) -> Value {
+    let mut payload = serde_json::json!({ "variables": variables });
+
+    if !persisted || register {
+        payload["query"] = query.into();
+    }
+    if persisted {
+        payload["extensions"] = serde_json::json!({
+            "persistedQuery": { "version": 1, "sha256Hash": sha256(query.as_bytes()) }
+        });
+    }
+
+    return payload;
+}
+
+// Whether the server doesn't know the hash of a persisted query and needs the query itself
+pub fn is_persisted_query_not_found(body: &[u8]) -> bool {
+    let body = match serde_json::from_slice::<Value>(body) {
+        Ok(body) => body,
+        Err(_) => return false,
+    };
+
+    return body["errors"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|error| {
+            error["message"] == "PersistedQueryNotFound"
+                || error.pointer("/extensions/code") == Some(&"PERSISTED_QUERY_NOT_FOUND".into())
+        });
+}
+
+impl Runner {
+    // Checks the query of a graphql body against the schema of its endpoint before it's sent.
+    // The schema is fetched once per url with an introspection query, using the request's headers and auth.
+    pub(crate) async fn validate_graphql(&self, resolved: &ResolvedRequest) -> anyhow::Result<()> {
+        let query = match &resolved.body {
+            Some(RequestBodySchema::Graphql {
+                query,
+                validate: true,
+                ..
+            }) => query,
+            _ => return Ok(()),
+        };
+
+        let cached = self
+            .graphql_schemas
+            .lock()
+            .ok()
+            .and_then(|schemas| schemas.get(&resolved.url).cloned());
+
+        let schema = match cached {
+            Some(schema) => schema,
+            None => {
+                let mut introspection = resolved.clone();
+                introspection.method = "POST".to_string();
+                introspection.body = Some(RequestBodySchema::Graphql {
+                    query: INTROSPECTION_QUERY.to_string(),
+                    query_file: None,
+                    variables: None,
+                    validate: false,
+                    persisted: false,
+                });
+
+                let response = Box::pin(self.execute(&introspection))
+                    .await
+                    .with_context(|| format!("Failed to introspect {}", resolved.url))?;
+                let schema = GraphqlSchema::from_introspection(&response.json()?)
+                    .with_context(|| format!("Failed to introspect {}", resolved.url))?;
+
+                if let Ok(mut schemas) = self.graphql_schemas.lock() {
+                    schemas.insert(resolved.url.clone(), schema.clone());
+                }
+                schema
+            }
+        };
+
+        let errors = schema.validate(query)?;
+        if !errors.is_empty() {
+            anyhow::bail!(
+                "The query of \"{}\" doesn't match the schema of {}:\n{}",
+                resolved.name,
+                resolved.url,
+                errors.join("\n")
+            );
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // Query { user: User, users: [User!]! } and User { id, email, friends: [User] }, without mutations
+    fn schema() -> GraphqlSchema {
+        let user = json!({ "kind": "OBJECT", "name": "User" });
+        let introspection = json!({
+            "data": { "__schema": {
+                "queryType": { "name": "Query" },
+                "mutationType": null,
+                "subscriptionType": null,
+                "types": [
+                    { "name": "Query", "fields": [
+                        { "name": "user", "type": user },
+                        { "name": "users", "type": { "name": null, "ofType": { "name": null, "ofType": { "name": null, "ofType": user } } } },
+                    ] },
+                    { "name": "User", "fields": [
+                        { "name": "id", "type": { "name": null, "ofType": { "name": "ID" } } },
+                        { "name": "email", "type": { "name": "String" } },
+                        { "name": "friends", "type": { "name": null, "ofType": user } },
+                    ] },
+                    { "name": "ID", "fields": null },
+                    { "name": "String", "fields": null },
+                ],
+            } }
+        });
+
+        return GraphqlSchema::from_introspection(&introspection).unwrap();
+    }
+
+    #[test]
+    fn reads_the_roots_and_unwraps_field_types() {
+        let schema = schema();
+        assert_eq!(schema.roots.get("query").map(String::as_str), Some("Query"));
+        assert!(!schema.roots.contains_key("mutation"));
+        assert_eq!(schema.types["Query"]["users"], "User");
+        assert_eq!(schema.types["User"]["id"], "ID");
+
+        assert!(GraphqlSchema::from_introspection(&json!({ "data": {} })).is_err());
+    }
+
+    #[test]
+    fn valid_documents_have_no_errors() {
+        let errors = schema()
+            .validate("query Users($first: Int = 10) { users(first: $first) { id email friends { id } } }")
+            .unwrap();
+        assert_eq!(errors, Vec::<String>::new());
+    }
+
+    #[test]
+    fn unknown_fields_and_types_are_listed() {
+        let errors = schema()
+            .validate("{ user { emial friends { nope } } ... on Admin { id } }")
+            .unwrap();
+        assert_eq!(
+            errors,
+            vec![
+                "Unknown field \"emial\" on type \"User\"",
+                "Unknown field \"nope\" on type \"User\"",
+                "Unknown type \"Admin\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn aliases_fragments_and_typename_are_followed() {
+        let document = r#"
+            query {
+                me: user { ...Fields __typename }
+                user { ... on User { address: email } ... @include(if: true) { id } }
+            }
+            fragment Fields on User { id friends { email } }
+        "#;
+        assert_eq!(schema().validate(document).unwrap(), Vec::<String>::new());
+
+        // the field of an alias is the one checked, not the alias
+        let errors = schema().validate("{ email: user { id } }").unwrap();
+        assert_eq!(errors, Vec::<String>::new());
+        let errors = schema().validate("{ user: nope { id } }").unwrap();
+        assert_eq!(errors, vec!["Unknown field \"nope\" on type \"Query\""]);
+
+        let errors = schema()
+            .validate("fragment Fields on User { nope }")
+            .unwrap();
+        assert_eq!(errors, vec!["Unknown field \"nope\" on type \"User\""]);
+    }
+
+    #[test]
+    fn strings_are_skipped() {
+        let document = r#"{ user(id: "a \" } b", bio: """ a \""" } "" b """) { id } }"#;
+        assert_eq!(schema().validate(document).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn unclosed_strings_fail() {
+        let error = schema().validate(r#"{ user(id: "a) { id } }"#).unwrap_err();
+        assert_eq!(error.to_string(), "Unclosed string in GraphQL document");
+
+        let error = schema()
+            .validate(r#"{ user(bio: """ a \""") { id } }"#)
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unclosed block string in GraphQL document"
+        );
+    }
+
+    #[test]
+    fn operations_without_a_root_are_errors() {
+        let errors = schema()
+            .validate("mutation Create { createUser { id } }")
+            .unwrap();
+        assert_eq!(
+            errors,
+            vec!["The schema doesn't support mutation operations"]
+        );
+    }
+
+    #[test]
+    fn malformed_documents_fail() {
+        assert!(schema().validate("{ user { id }").is_err());
+        assert!(schema().validate("query { user ; }").is_err());
+        assert!(schema().validate("type User { id }").is_err());
+    }
+}
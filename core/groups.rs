@@ -0,0 +1,181 @@
+use crate::{fs::FileObject, index::RequestSummary, schema::roots::RequestRootSchema};
+
+/// A folder of requests, made from their `class` with `/` separating nested folders,
+/// eg: `users/admin`. The root group holds the requests without a class.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct RequestGroup {
+    // last segment of the class, empty for the root
+    pub name: String,
+    // the whole class, empty for the root
+    pub path: String,
+    pub groups: Vec<RequestGroup>,
+    // call names of the requests directly in this group
+    pub requests: Vec<String>,
+}
+
+impl RequestGroup {
+    // Arranges requests in folders, groups and requests sorted by name
+    pub fn build(requests: &[FileObject<RequestRootSchema>]) -> RequestGroup {
+        return RequestGroup::from_classes(requests.iter().map(|request| {
+            let class = request
+                .object
+                .config
+                .as_ref()
+                .and_then(|config| config.class.clone());
+
+            (class, request.get_call_name())
+        }));
+    }
+
+    // Same as build, from the requests of an index
+    pub fn from_index(requests: &[RequestSummary]) -> RequestGroup {
+        return RequestGroup::from_classes(
+            requests
+                .iter()
+                .map(|request| (request.class.clone(), request.name.clone())),
+        );
+    }
+
+    fn from_classes(requests: impl Iterator<Item = (Option<String>, String)>) -> RequestGroup {
+        let mut root = RequestGroup::default();
+
+        for (class, name) in requests {
+            root.insert(&class_segments(&class.unwrap_or_default()), name);
+        }
+
+        root.sort();
+        return root;
+    }
+
+    fn insert(&mut self, segments: &[&str], request: String) {
+        let (name, rest) = match segments.split_first() {
+            Some(first) => first,
+            None => {
+                self.requests.push(request);
+                return;
+            }
+        };
+
+        let index = match self.groups.iter().position(|group| group.name == *name) {
+            Some(index) => index,
+            None => {
+                let path = match self.path.is_empty() {
+                    true => name.to_string(),
+                    false => format!("{}/{}", self.path, name),
+                };
+                self.groups.push(RequestGroup {
+                    name: name.to_string(),
+                    path,
+                    ..Default::default()
+                });
+                self.groups.len() - 1
+            }
+        };
+
+        self.groups[index].insert(rest, request);
+    }
+
+    fn sort(&mut self) {
+        self.requests.sort();
+        self.groups.sort_by(|a, b| a.name.cmp(&b.name));
+        for group in self.groups.iter_mut() {
+            group.sort();
+        }
+    }
+
+    // Number of requests in this group and the groups inside it
+    pub fn len(&self) -> usize {
+        return self.requests.len() + self.groups.iter().map(|group| group.len()).sum::<usize>();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.len() == 0;
+    }
+}
+
+// The folders of a class, eg: " users / admin/" -> [users, admin]
+pub fn class_segments(class: &str) -> Vec<&str> {
+    return class
+        .split('/')
+        .map(|segment| segment.trim())
+        .filter(|segment| !segment.is_empty())
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn request(name: &str, class: Option<&str>) -> FileObject<RequestRootSchema> {
+        let yaml = match class {
+            Some(class) => format!(
+                "method: GET\nurl: http://localhost\nconfig: {{class: \"{}\"}}",
+                class
+            ),
+            None => "method: GET\nurl: http://localhost".to_string(),
+        };
+
+        return FileObject::new(
+            PathBuf::from(format!("/project/requests/{}.nd", name)),
+            serde_yaml::from_str(&yaml).unwrap(),
+        );
+    }
+
+    #[test]
+    fn class_segments_are_trimmed_and_empty_ones_dropped() {
+        assert_eq!(class_segments(" users / admin/"), vec!["users", "admin"]);
+        assert_eq!(class_segments("//users"), vec!["users"]);
+        assert!(class_segments("").is_empty());
+    }
+
+    #[test]
+    fn requests_are_grouped_in_nested_folders_by_class() {
+        let root = RequestGroup::build(&[
+            request("zeta", None),
+            request("list_admins", Some("users/admin")),
+            request("login", None),
+            request("list_users", Some("users")),
+            request("create_order", Some(" orders ")),
+            request("ban", Some("users/ admin")),
+        ]);
+
+        assert_eq!(root.name, "");
+        assert_eq!(root.requests, vec!["login", "zeta"]);
+        assert_eq!(root.len(), 6);
+
+        let names = root
+            .groups
+            .iter()
+            .map(|g| g.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["orders", "users"]);
+
+        let users = &root.groups[1];
+        assert_eq!(users.path, "users");
+        assert_eq!(users.requests, vec!["list_users"]);
+        assert_eq!(users.len(), 3);
+
+        let admin = &users.groups[0];
+        assert_eq!(admin.name, "admin");
+        assert_eq!(admin.path, "users/admin");
+        assert_eq!(admin.requests, vec!["ban", "list_admins"]);
+        assert!(admin.groups.is_empty());
+    }
+
+    #[test]
+    fn the_index_is_grouped_like_the_requests() {
+        let requests = [
+            request("list_admins", Some("users/admin")),
+            request("login", None),
+        ];
+        let summaries = requests.iter().map(RequestSummary::of).collect::<Vec<_>>();
+
+        assert_eq!(
+            RequestGroup::from_index(&summaries),
+            RequestGroup::build(&requests)
+        );
+        assert!(RequestGroup::from_index(&[]).is_empty());
+    }
+}
@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    bundle::Bundle,
+    runner::{CallResult, Runner},
+};
+
+pub const HISTORY_FILE: &str = ".nd-history.jsonl";
+
+// Entries kept for each request when the history is trimmed
+pub const HISTORY_LIMIT: usize = 50;
+
+/// A request executed by the runner, with the response it got.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: uuid::Uuid,
+    #[serde(flatten)]
+    pub exchange: Bundle,
+}
+
+/// Executed requests, stored as json lines (one entry per line, oldest first).
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryStore {
+    pub path: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new(path: PathBuf) -> HistoryStore {
+        return HistoryStore { path };
+    }
+
+    // The history store kept next to a project file
+    pub fn for_project(root: &Path) -> HistoryStore {
+        return HistoryStore::new(root.join(HISTORY_FILE));
+    }
+
+    pub async fn append(&self, entry: &HistoryEntry) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Failed to open history file {:?}", &self.path))?;
+
+        file.write_all(line.as_bytes()).await?;
+        return Ok(());
+    }
+
+    // All entries, newest first
+    pub async fn entries(&self) -> anyhow::Result<Vec<HistoryEntry>> {
+        if !self.path.exists() {
+            return Ok(vec![]);
+        }
+
+        let content = tokio::fs::read_to_string(&self.path)
+            .await
+            .context("Failed to read history")?;
+
+        let mut result = vec![];
+        for (index, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<HistoryEntry>(line) {
+                Ok(entry) => result.push(entry),
+                Err(e) => tracing::warn!(
+                    "Skipping invalid history entry on line {}: {}",
+                    index + 1,
+                    e
+                ),
+            };
+        }
+
+        result.reverse();
+        return Ok(result);
+    }
+
+    // Entries for a request, newest first
+    pub async fn for_request(
+        &self,
+        name: &str,
+        limit: Option<usize>,
+    ) -> anyhow::Result<Vec<HistoryEntry>> {
+        let entries = self
+            .entries()
+            .await?
+            .into_iter()
+            .filter(|entry| entry.exchange.name == name);
+
+        return Ok(match limit {
+            Some(limit) => entries.take(limit).collect(),
+            None => entries.collect(),
+        });
+    }
+
+    pub async fn get(&self, id: uuid::Uuid) -> anyhow::Result<Option<HistoryEntry>> {
+        return Ok(self
+            .entries()
+            .await?
+            .into_iter()
+            .find(|entry| entry.id == id));
+    }
+
+    // Removes the entries of a request, or every entry when name is None
+    pub async fn clear(&self, name: Option<&str>) -> anyhow::Result<()> {
+        let kept = self
+            .entries()
+            .await?
+            .into_iter()
+            .filter(|entry| name.is_some_and(|name| entry.exchange.name != name))
+            .collect::<Vec<HistoryEntry>>();
+
+        return self.write(kept).await;
+    }
+
+    // Keeps the newest entries of each request, up to limit, so the file doesn't grow forever
+    pub async fn trim(&self, limit: usize) -> anyhow::Result<()> {
+        let entries = self.entries().await?;
+        let count = entries.len();
+
+        let mut seen = std::collections::HashMap::<String, usize>::new();
+        let kept = entries
+            .into_iter()
+            .filter(|entry| {
+                let seen = seen.entry(entry.exchange.name.clone()).or_default();
+                *seen += 1;
+                *seen <= limit
+            })
+            .collect::<Vec<HistoryEntry>>();
+
+        if kept.len() == count {
+            return Ok(());
+        }
+
+        return self.write(kept).await;
+    }
+
+    // Replaces the stored entries, given newest first
+    async fn write(&self, mut entries: Vec<HistoryEntry>) -> anyhow::Result<()> {
+        entries.reverse();
+
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(&entry)?);
+            content.push('\n');
+        }
+
+        tokio::fs::write(&self.path, content)
+            .await
+            .context("Failed to write history")?;
+        return Ok(());
+    }
+}
+
+impl Runner {
+    // Records every request called from now on in the project's history store,
+    // trimmed first to the last HISTORY_LIMIT entries of each request
+    pub async fn enable_history(&mut self) {
+        let store = HistoryStore::for_project(&self.project.get_root_dir());
+        if let Err(e) = store.trim(HISTORY_LIMIT).await {
+            tracing::warn!("Failed to trim history: {:#}", e);
+        }
+
+        self.history = Some(store);
+    }
+
+    pub(crate) async fn record_history(&self, result: &CallResult) {
+        let store = match &self.history {
+            Some(store) => store,
+            None => return,
+        };
+
+        let entry = HistoryEntry {
+            id: uuid::Uuid::new_v4(),
+            exchange: Bundle::new(
+                result,
+                self.environment.clone(),
+                &self.project.object.redact,
+                &self.build_env(),
+            ),
+        };
+
+        // a failure to record shouldn't fail the call
+        if let Err(e) = store.append(&entry).await {
+            tracing::error!("Failed to record history: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bundle::{BundleRequest, BundleResponse};
+
+    fn store() -> HistoryStore {
+        return HistoryStore::new(
+            std::env::temp_dir().join(format!("dotapi-history-{}.jsonl", uuid::Uuid::new_v4())),
+        );
+    }
+
+    fn entry(name: &str, status: u16) -> HistoryEntry {
+        return HistoryEntry {
+            id: uuid::Uuid::new_v4(),
+            exchange: Bundle {
+                name: name.to_string(),
+                environment: None,
+                created_at: 0,
+                request: BundleRequest {
+                    method: "GET".to_string(),
+                    url: format!("http://localhost/{}", name),
+                    headers: vec![],
+                    body: None,
+                },
+                response: BundleResponse {
+                    status,
+                    headers: Default::default(),
+                    body: String::new(),
+                    duration_ms: 1,
+                },
+            },
+        };
+    }
+
+    fn statuses(entries: &[HistoryEntry]) -> Vec<u16> {
+        return entries
+            .iter()
+            .map(|entry| entry.exchange.response.status)
+            .collect();
+    }
+
+    #[tokio::test]
+    async fn entries_are_appended_and_read_newest_first() {
+        let store = store();
+        assert!(store.entries().await.unwrap().is_empty());
+
+        let first = entry("users", 200);
+        store.append(&first).await.unwrap();
+        store.append(&entry("login", 201)).await.unwrap();
+        store.append(&entry("users", 500)).await.unwrap();
+
+        assert_eq!(
+            statuses(&store.entries().await.unwrap()),
+            vec![500, 201, 200]
+        );
+        assert_eq!(
+            statuses(&store.for_request("users", None).await.unwrap()),
+            vec![500, 200]
+        );
+        assert_eq!(
+            statuses(&store.for_request("users", Some(1)).await.unwrap()),
+            vec![500]
+        );
+        assert_eq!(store.get(first.id).await.unwrap(), Some(first));
+        std::fs::remove_file(&store.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn invalid_lines_are_skipped() {
+        let store = store();
+        store.append(&entry("users", 200)).await.unwrap();
+        let mut content = std::fs::read_to_string(&store.path).unwrap();
+        content.push_str("{not json\n\n");
+        std::fs::write(&store.path, content).unwrap();
+        store.append(&entry("users", 204)).await.unwrap();
+
+        assert_eq!(statuses(&store.entries().await.unwrap()), vec![204, 200]);
+        std::fs::remove_file(&store.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn trimming_keeps_the_newest_entries_of_each_request() {
+        let store = store();
+        for status in [200, 201, 202] {
+            store.append(&entry("users", status)).await.unwrap();
+        }
+        store.append(&entry("login", 200)).await.unwrap();
+        store.append(&entry("users", 203)).await.unwrap();
+
+        store.trim(2).await.unwrap();
+
+        assert_eq!(
+            statuses(&store.entries().await.unwrap()),
+            vec![203, 200, 202]
+        );
+        assert_eq!(
+            statuses(&store.for_request("login", None).await.unwrap()),
+            vec![200]
+        );
+        std::fs::remove_file(&store.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn clearing_removes_a_request_or_everything() {
+        let store = store();
+        store.append(&entry("users", 200)).await.unwrap();
+        store.append(&entry("login", 201)).await.unwrap();
+
+        store.clear(Some("users")).await.unwrap();
+        assert_eq!(statuses(&store.entries().await.unwrap()), vec![201]);
+
+        store.clear(None).await.unwrap();
+        assert!(store.entries().await.unwrap().is_empty());
+        std::fs::remove_file(&store.path).unwrap();
+    }
+}
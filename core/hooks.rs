@@ -0,0 +1,226 @@
+use anyhow::Context;
+
+use crate::{
+    runner::{Runner, SkippedError},
+    schema::calls::HookSchema,
+};
+
+// A command run by the platform's shell
+pub(crate) fn shell(command: &str) -> std::process::Command {
+    let mut process = if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+    } else {
+        std::process::Command::new("sh")
+    };
+
+    process.args([
+        if cfg!(target_os = "windows") {
+            "/C"
+        } else {
+            "-c"
+        },
+        command,
+    ]);
+    return process;
+}
+
+impl Runner {
+    // Runs hooks in order, stopping at the first one that fails.
+    // Commands run from the project root.
+    pub async fn run_hooks(&mut self, hooks: &[HookSchema]) -> anyhow::Result<()> {
+        for hook in hooks {
+            match hook {
+                HookSchema::Request(name) => match self.call_request(name).await {
+                    Ok(_) => {}
+                    Err(e) if e.is::<SkippedError>() => tracing::info!("{}", e),
+                    Err(e) => return Err(e.context(format!("Hook \"{}\" failed", name))),
+                },
+                HookSchema::Command { command } => {
+                    let output = tokio::process::Command::from(shell(command))
+                        .current_dir(self.project.get_root_dir())
+                        .output()
+                        .await
+                        .with_context(|| format!("Failed to start hook \"{}\"", command))?;
+
+                    // logged along with the run, shown on the console with -v
+                    for line in String::from_utf8_lossy(&output.stdout).lines() {
+                        tracing::info!(hook = %command, "{}", line);
+                    }
+                    for line in String::from_utf8_lossy(&output.stderr).lines() {
+                        tracing::warn!(hook = %command, "{}", line);
+                    }
+
+                    if !output.status.success() {
+                        anyhow::bail!("Hook \"{}\" exited with {}", command, output.status);
+                    }
+                }
+            };
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{
+        fs::FileObject,
+        schema::{
+            calls::{SequenceDefinitionSchema, StepSchema},
+            roots::RequestRootSchema,
+        },
+    };
+
+    // Answers every request with 200
+    async fn serve_ok() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    // A runner in a new project folder, with these requests given as their yaml
+    fn runner(requests: &[(&str, String)]) -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-hooks-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            requests
+                .iter()
+                .map(|(name, yaml)| {
+                    FileObject::new(
+                        root.join(format!("requests/{}.nd", name)),
+                        serde_yaml::from_str::<RequestRootSchema>(yaml).unwrap(),
+                    )
+                })
+                .collect(),
+        )
+        .unwrap();
+    }
+
+    // A request to a port nothing listens on
+    async fn unreachable() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        return format!("method: GET\nurl: http://{}/", address);
+    }
+
+    fn command(command: &str) -> HookSchema {
+        return HookSchema::Command {
+            command: command.to_string(),
+        };
+    }
+
+    fn read(runner: &Runner, file: &str) -> String {
+        return std::fs::read_to_string(runner.project.get_root_dir().join(file))
+            .unwrap_or_default();
+    }
+
+    #[tokio::test]
+    async fn hooks_run_from_the_project_root_until_one_fails() {
+        let mut runner = runner(&[]);
+
+        let error = runner
+            .run_hooks(&[
+                command("echo a >> hooks.log"),
+                command("exit 4"),
+                command("echo c >> hooks.log"),
+            ])
+            .await
+            .unwrap_err();
+
+        assert!(
+            error.to_string().starts_with("Hook \"exit 4\" exited with"),
+            "{}",
+            error
+        );
+        assert_eq!(read(&runner, "hooks.log"), "a\n");
+    }
+
+    #[tokio::test]
+    async fn a_failing_request_hook_names_the_hook() {
+        let mut runner = runner(&[("down", unreachable().await)]);
+
+        let error = runner
+            .run_hooks(&[HookSchema::Request("down".to_string())])
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.to_string(), "Hook \"down\" failed");
+    }
+
+    #[tokio::test]
+    async fn after_hooks_run_when_a_step_fails() {
+        let mut runner = runner(&[("down", unreachable().await)]);
+        let definition = SequenceDefinitionSchema {
+            steps: vec![StepSchema::Request("down".to_string())],
+            after: vec![command("echo teardown > after.log")],
+            ..Default::default()
+        };
+
+        let run = runner.sequence("main", &definition).await;
+
+        assert!(run.outcome.is_err());
+        assert_eq!(run.entries.len(), 1);
+        assert_eq!(read(&runner, "after.log"), "teardown\n");
+    }
+
+    #[tokio::test]
+    async fn a_failing_before_hook_stops_the_sequence() {
+        let url = serve_ok().await;
+        let mut runner = runner(&[("users", format!("method: GET\nurl: {}/users", url))]);
+        let definition = SequenceDefinitionSchema {
+            before: vec![command("exit 1")],
+            steps: vec![StepSchema::Request("users".to_string())],
+            after: vec![command("echo teardown > after.log")],
+            ..Default::default()
+        };
+
+        let run = runner.sequence("main", &definition).await;
+
+        assert!(run.outcome.is_err());
+        // the failing hook is reported, the step isn't called
+        assert_eq!(run.entries.len(), 1);
+        assert_eq!(run.entries[0].name, "$ exit 1");
+        assert!(!runner.sent.contains_key("users"));
+        assert_eq!(read(&runner, "after.log"), "teardown\n");
+    }
+
+    #[tokio::test]
+    async fn after_hooks_run_when_the_run_is_cancelled() {
+        let url = serve_ok().await;
+        let mut runner = runner(&[
+            ("users", format!("method: GET\nurl: {}/users", url)),
+            ("logout", format!("method: POST\nurl: {}/logout", url)),
+        ]);
+        let definition = SequenceDefinitionSchema {
+            steps: vec![StepSchema::Request("users".to_string())],
+            after: vec![HookSchema::Request("logout".to_string())],
+            ..Default::default()
+        };
+        runner.cancel.cancel();
+
+        let run = runner.sequence("main", &definition).await;
+
+        assert!(run.outcome.is_err());
+        assert!(!runner.responses.contains_key("users"));
+        assert_eq!(runner.responses.get("logout").unwrap().response.status, 200);
+        // the run stays cancelled once the teardown is over
+        assert!(runner.is_cancelled());
+    }
+}
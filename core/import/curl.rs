@@ -0,0 +1,796 @@
+use std::path::Path;
+
+use indexmap::IndexMap;
+
+use anyhow::Context;
+use base64::Engine;
+
+use crate::schema::{
+    request_body::{MultipartPartSchema, RequestBodySchema},
+    request_config::RequestConfigSchema,
+    roots::RequestRootSchema,
+};
+
+// Long flags that take a value, every one of curl's so their value isn't mistaken for the url
+const VALUE_FLAGS: &[&str] = &[
+    "--abstract-unix-socket",
+    "--alt-svc",
+    "--aws-sigv4",
+    "--cacert",
+    "--capath",
+    "--cert",
+    "--cert-type",
+    "--ciphers",
+    "--config",
+    "--connect-timeout",
+    "--connect-to",
+    "--continue-at",
+    "--cookie",
+    "--cookie-jar",
+    "--create-file-mode",
+    "--crlfile",
+    "--curves",
+    "--data",
+    "--data-ascii",
+    "--data-binary",
+    "--data-raw",
+    "--data-urlencode",
+    "--delegation",
+    "--dns-interface",
+    "--dns-ipv4-addr",
+    "--dns-ipv6-addr",
+    "--dns-servers",
+    "--doh-url",
+    "--dump-header",
+    "--ech",
+    "--egd-file",
+    "--engine",
+    "--etag-compare",
+    "--etag-save",
+    "--expect100-timeout",
+    "--form",
+    "--form-string",
+    "--ftp-account",
+    "--ftp-alternative-to-user",
+    "--ftp-method",
+    "--ftp-port",
+    "--ftp-ssl-ccc-mode",
+    "--happy-eyeballs-timeout-ms",
+    "--haproxy-clientip",
+    "--header",
+    "--hostpubmd5",
+    "--hostpubsha256",
+    "--hsts",
+    "--interface",
+    "--ip-tos",
+    "--ipfs-gateway",
+    "--json",
+    "--keepalive-cnt",
+    "--keepalive-time",
+    "--key",
+    "--key-type",
+    "--krb",
+    "--libcurl",
+    "--limit-rate",
+    "--local-port",
+    "--login-options",
+    "--mail-auth",
+    "--mail-from",
+    "--mail-rcpt",
+    "--max-filesize",
+    "--max-redirs",
+    "--max-time",
+    "--netrc-file",
+    "--noproxy",
+    "--oauth2-bearer",
+    "--output",
+    "--output-dir",
+    "--parallel-max",
+    "--pass",
+    "--pinnedpubkey",
+    "--preproxy",
+    "--proto",
+    "--proto-default",
+    "--proto-redir",
+    "--proxy",
+    "--proxy-cacert",
+    "--proxy-capath",
+    "--proxy-cert",
+    "--proxy-cert-type",
+    "--proxy-ciphers",
+    "--proxy-crlfile",
+    "--proxy-header",
+    "--proxy-key",
+    "--proxy-key-type",
+    "--proxy-pass",
+    "--proxy-pinnedpubkey",
+    "--proxy-service-name",
+    "--proxy-tls13-ciphers",
+    "--proxy-tlsauthtype",
+    "--proxy-tlspassword",
+    "--proxy-tlsuser",
+    "--proxy-user",
+    "--proxy1.0",
+    "--pubkey",
+    "--quote",
+    "--random-file",
+    "--range",
+    "--rate",
+    "--referer",
+    "--request",
+    "--request-target",
+    "--resolve",
+    "--retry",
+    "--retry-delay",
+    "--retry-max-time",
+    "--sasl-authzid",
+    "--service-name",
+    "--socks4",
+    "--socks4a",
+    "--socks5",
+    "--socks5-gssapi-service",
+    "--socks5-hostname",
+    "--speed-limit",
+    "--speed-time",
+    "--stderr",
+    "--telnet-option",
+    "--tftp-blksize",
+    "--time-cond",
+    "--tls-max",
+    "--tls13-ciphers",
+    "--tlsauthtype",
+    "--tlspassword",
+    "--tlsuser",
+    "--trace",
+    "--trace-ascii",
+    "--trace-config",
+    "--unix-socket",
+    "--upload-file",
+    "--url",
+    "--url-query",
+    "--user",
+    "--user-agent",
+    "--variable",
+    "--write-out",
+];
+
+// Short flags that take a value
+const VALUE_SHORT_FLAGS: &[char] = &[
+    'A', 'b', 'c', 'C', 'd', 'D', 'e', 'E', 'F', 'H', 'K', 'm', 'o', 'P', 'Q', 'r', 't', 'T', 'u',
+    'U', 'w', 'x', 'X', 'y', 'Y', 'z',
+];
+
+#[derive(Default)]
+struct CurlCommand {
+    method: Option<String>,
+    url: Option<String>,
+    headers: Vec<(String, String)>,
+    data: Vec<String>,
+    json: Vec<String>,
+    form: Vec<String>,
+    query: Vec<String>,
+    upload: Option<String>,
+    user: Option<String>,
+    get: bool,
+    head: bool,
+    insecure: bool,
+    timeout: Option<u32>,
+}
+
+// Converts a curl command line into a request.
+// Files the command reads (-d @file, -T file) are resolved from base_dir, where the command would run.
+pub fn parse_curl(command: &str, base_dir: &Path) -> anyhow::Result<RequestRootSchema> {
+    let words = split_words(command)?;
+    let mut words = words.into_iter();
+
+    match words.next() {
+        Some(program) if program == "curl" => {}
+        _ => anyhow::bail!("Command must start with curl"),
+    };
+
+    let mut curl = CurlCommand::default();
+
+    while let Some(word) = words.next() {
+        if let Some(flag) = word.strip_prefix("--") {
+            let (flag, inline_value) = match flag.split_once('=') {
+                Some((flag, value)) => (format!("--{}", flag), Some(value.to_string())),
+                None => (word.clone(), None),
+            };
+
+            if VALUE_FLAGS.contains(&flag.as_str()) {
+                let value = match inline_value.or_else(|| words.next()) {
+                    Some(value) => value,
+                    None => anyhow::bail!("Missing value for {}", flag),
+                };
+                apply_flag(&mut curl, &flag, value, base_dir)?;
+            } else {
+                apply_switch(&mut curl, &flag);
+            }
+        } else if word.starts_with('-') && word.len() > 1 {
+            let mut chars = word[1..].chars();
+
+            while let Some(c) = chars.next() {
+                if VALUE_SHORT_FLAGS.contains(&c) {
+                    let attached = chars.as_str().to_string();
+                    let value = if !attached.is_empty() {
+                        attached
+                    } else {
+                        match words.next() {
+                            Some(value) => value,
+                            None => anyhow::bail!("Missing value for -{}", c),
+                        }
+                    };
+                    apply_flag(&mut curl, &format!("-{}", c), value, base_dir)?;
+                    break;
+                }
+
+                apply_switch(&mut curl, &format!("-{}", c));
+            }
+        } else if curl.url.is_none() {
+            curl.url = Some(word);
+        }
+    }
+
+    return build_request(curl, base_dir);
+}
+
+// Converts a curl command line into the yaml of a request file
+pub fn curl_to_yaml(command: &str, base_dir: &Path) -> anyhow::Result<String> {
+    return Ok(serde_yaml::to_string(&parse_curl(command, base_dir)?)?);
+}
+
+fn apply_flag(
+    curl: &mut CurlCommand,
+    flag: &str,
+    value: String,
+    base_dir: &Path,
+) -> anyhow::Result<()> {
+    match flag {
+        "-X" | "--request" => curl.method = Some(value.to_uppercase()),
+        "-H" | "--header" => match value.split_once(':') {
+            Some((key, value)) => curl
+                .headers
+                .push((key.trim().to_string(), value.trim().to_string())),
+            None => anyhow::bail!("Invalid header \"{}\"", value),
+        },
+        "--data-raw" => curl.data.push(value),
+        // like curl, -d strips the line breaks of the file and --data-binary keeps them
+        "-d" | "--data" | "--data-ascii" => curl.data.push(match value.strip_prefix('@') {
+            Some(path) => read_data_file(path, base_dir)?.replace(['\r', '\n'], ""),
+            None => value,
+        }),
+        "--data-binary" => curl.data.push(match value.strip_prefix('@') {
+            Some(path) => read_data_file(path, base_dir)?,
+            None => value,
+        }),
+        "--data-urlencode" => curl.data.push(encode_data(&value, base_dir)?),
+        "--url-query" => curl.query.push(encode_data(&value, base_dir)?),
+        "-T" | "--upload-file" => curl.upload = Some(value),
+        "--oauth2-bearer" => curl
+            .headers
+            .push(("Authorization".to_string(), format!("Bearer {}", value))),
+        "--json" => curl.json.push(match value.strip_prefix('@') {
+            Some(path) => read_data_file(path, base_dir)?,
+            None => value,
+        }),
+        "-F" | "--form" | "--form-string" => curl.form.push(value),
+        "-u" | "--user" => curl.user = Some(value),
+        "-A" | "--user-agent" => curl.headers.push(("User-Agent".to_string(), value)),
+        "-e" | "--referer" => curl.headers.push(("Referer".to_string(), value)),
+        "-b" | "--cookie" => curl.headers.push(("Cookie".to_string(), value)),
+        "--url" => curl.url = Some(value),
+        "-m" | "--max-time" => {
+            let seconds = value
+                .parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("Invalid max time \"{}\"", value))?;
+            curl.timeout = Some((seconds * 1000.0) as u32);
+        }
+        _ => {}
+    };
+
+    return Ok(());
+}
+
+fn apply_switch(curl: &mut CurlCommand, flag: &str) {
+    match flag {
+        "-G" | "--get" => curl.get = true,
+        "-I" | "--head" => curl.head = true,
+        "-k" | "--insecure" => curl.insecure = true,
+        // everything else (-s, -L, -v, --compressed...) doesn't change the request
+        _ => {}
+    };
+}
+
+// --data-urlencode takes `content`, `name=content`, `=content`, `@file` or `name@file`
+fn encode_data(value: &str, base_dir: &Path) -> anyhow::Result<String> {
+    if let Some((name, path)) = value.split_once('@')
+        && !name.contains('=')
+    {
+        let content = urlencode(&read_data_file(path, base_dir)?);
+        return Ok(match name {
+            "" => content,
+            name => format!("{}={}", name, content),
+        });
+    }
+
+    return Ok(match value.split_once('=') {
+        Some(("", content)) => urlencode(content),
+        Some((name, content)) => format!("{}={}", name, urlencode(content)),
+        None => urlencode(value),
+    });
+}
+
+fn read_data_file(path: &str, base_dir: &Path) -> anyhow::Result<String> {
+    if path == "-" {
+        anyhow::bail!("Data read from stdin (@-) can't be imported");
+    }
+
+    let path = base_dir.join(path);
+    return std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read data file {:?}", path));
+}
+
+fn urlencode(value: &str) -> String {
+    return url::form_urlencoded::byte_serialize(value.as_bytes()).collect();
+}
+
+fn build_request(curl: CurlCommand, base_dir: &Path) -> anyhow::Result<RequestRootSchema> {
+    let url = match curl.url {
+        Some(url) => url,
+        None => anyhow::bail!("No url in curl command"),
+    };
+
+    let mut headers = curl.headers;
+    let mut query = vec![];
+    for pairs in &curl.query {
+        query.extend(parse_pairs(pairs));
+    }
+
+    if let Some(user) = &curl.user {
+        let token = base64::engine::general_purpose::STANDARD.encode(user);
+        headers.push(("Authorization".to_string(), format!("Basic {}", token)));
+    }
+
+    let content_type = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.to_lowercase())
+        .unwrap_or_default();
+
+    let mut body = None;
+
+    if !curl.json.is_empty() {
+        if content_type.is_empty() {
+            headers.push(("Content-Type".to_string(), "application/json".to_string()));
+        }
+        body = Some(json_or_text(&curl.json.join("")));
+    } else if !curl.form.is_empty() {
+        body = Some(RequestBodySchema::Multipart {
+            parts: curl
+                .form
+                .iter()
+                .map(|field| form_part(field, base_dir))
+                .collect::<anyhow::Result<_>>()?,
+        });
+    } else if !curl.data.is_empty() {
+        let data = curl.data.join("&");
+
+        if curl.get {
+            query.extend(parse_pairs(&data));
+        } else if content_type.contains("json") {
+            body = Some(json_or_text(&data));
+        } else if content_type.contains("xml") {
+            body = Some(RequestBodySchema::Xml {
+                content: data,
+                content_file: None,
+            });
+        } else if content_type.starts_with("text/") {
+            body = Some(RequestBodySchema::Text {
+                content: data,
+                content_file: None,
+            });
+        } else {
+            body = Some(RequestBodySchema::FormUrlencoded {
+                content: data,
+                content_file: None,
+                fields: None,
+            });
+        }
+    }
+
+    if let Some(path) = &curl.upload {
+        body = Some(RequestBodySchema::Binary {
+            path: std::path::absolute(base_dir.join(path))?
+                .to_string_lossy()
+                .to_string(),
+            content_type: None,
+        });
+    }
+
+    // keep the query in its own block so it's easier to edit, unless a key repeats
+    let (url, query) = split_query(&url, query);
+
+    let method = match curl.method {
+        Some(method) => method,
+        None if curl.head => "HEAD".to_string(),
+        None if curl.upload.is_some() => "PUT".to_string(),
+        None if body.is_some() => "POST".to_string(),
+        None => "GET".to_string(),
+    };
+
+    let config = if curl.insecure || curl.timeout.is_some() {
+        Some(RequestConfigSchema {
+            insecure: curl.insecure,
+            timeout: curl.timeout,
+            ..Default::default()
+        })
+    } else {
+        None
+    };
+
+    return Ok(RequestRootSchema {
+        method,
+        url,
+        config,
+        headers: if headers.is_empty() {
+            None
+        } else {
+            Some(merge_headers(headers))
+        },
+        query: if query.is_empty() { None } else { Some(query) },
+        body,
+        ..Default::default()
+    });
+}
+
+fn parse_pairs(pairs: &str) -> Vec<(String, String)> {
+    return url::form_urlencoded::parse(pairs.as_bytes())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+}
+
+// Moves the query of the url and the extra pairs into the query block.
+// A block can't hold a key twice, so they're all kept in the url when one repeats.
+fn split_query(url: &str, extra: Vec<(String, String)>) -> (String, IndexMap<String, String>) {
+    let mut parsed = match reqwest::Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(_) => return (url.to_string(), extra.into_iter().collect()),
+    };
+
+    let mut pairs = parsed
+        .query_pairs()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<Vec<_>>();
+    pairs.extend(extra);
+
+    let query = pairs.iter().cloned().collect::<IndexMap<_, _>>();
+    if query.len() != pairs.len() {
+        parsed.query_pairs_mut().clear().extend_pairs(&pairs);
+        return (parsed.to_string(), IndexMap::new());
+    }
+
+    parsed.set_query(None);
+    return (parsed.to_string(), query);
+}
+
+// Headers given more than once are combined into one, the way http allows
+fn merge_headers(headers: Vec<(String, String)>) -> IndexMap<String, String> {
+    let mut merged = IndexMap::<String, String>::new();
+
+    for (key, value) in headers {
+        let existing = merged
+            .keys()
+            .find(|existing| existing.eq_ignore_ascii_case(&key))
+            .cloned();
+
+        match existing {
+            Some(existing) => {
+                let separator = match key.eq_ignore_ascii_case("cookie") {
+                    true => "; ",
+                    false => ", ",
+                };
+                let combined = merged.get_mut(&existing).unwrap();
+                combined.push_str(separator);
+                combined.push_str(&value);
+            }
+            None => {
+                merged.insert(key, value);
+            }
+        };
+    }
+
+    return merged;
+}
+
+fn json_or_text(content: &str) -> RequestBodySchema {
+    return match serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|value| serde_yaml::to_value(value).ok())
+    {
+        Some(content) => RequestBodySchema::Json {
+            content,
+            content_file: None,
+        },
+        None => RequestBodySchema::Text {
+            content: content.to_string(),
+            content_file: None,
+        },
+    };
+}
+
+// Parses a -F value: `name=value`, `name=@path;type=mime` or `name=<path`
+fn form_part(field: &str, base_dir: &Path) -> anyhow::Result<MultipartPartSchema> {
+    let (name, value) = field.split_once('=').unwrap_or((field, ""));
+
+    let path = match value.strip_prefix('@').or_else(|| value.strip_prefix('<')) {
+        Some(path) => path,
+        None => {
+            return Ok(MultipartPartSchema::Field {
+                name: name.to_string(),
+                value: value.to_string(),
+            });
+        }
+    };
+
+    let mut segments = path.split(';');
+    let path = std::path::absolute(base_dir.join(segments.next().unwrap_or_default()))?
+        .to_string_lossy()
+        .to_string();
+    let mime_type = segments
+        .filter_map(|segment| segment.trim().strip_prefix("type="))
+        .map(|mime| mime.to_string())
+        .next();
+
+    return Ok(MultipartPartSchema::File {
+        name: name.to_string(),
+        path,
+        mime_type,
+    });
+}
+
+// Splits a command line into words the way a posix shell would,
+// handling quotes, escapes and line continuations
+fn split_words(command: &str) -> anyhow::Result<Vec<String>> {
+    let mut words = vec![];
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unclosed single quote"),
+                    };
+                }
+            }
+            // ansi-c quoting, used by browsers' "copy as curl"
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => current.push('\n'),
+                            Some('t') => current.push('\t'),
+                            Some('r') => current.push('\r'),
+                            Some(c) => current.push(c),
+                            None => anyhow::bail!("Unclosed single quote"),
+                        },
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unclosed single quote"),
+                    };
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => current.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                current.push('\\');
+                                current.push(c);
+                            }
+                            None => anyhow::bail!("Unclosed double quote"),
+                        },
+                        Some(c) => current.push(c),
+                        None => anyhow::bail!("Unclosed double quote"),
+                    };
+                }
+            }
+            '\\' => match chars.next() {
+                Some('\n') | Some('\r') | None => {}
+                Some(c) => {
+                    in_word = true;
+                    current.push(c);
+                }
+            },
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            c => {
+                in_word = true;
+                current.push(c);
+            }
+        };
+    }
+
+    if in_word {
+        words.push(current);
+    }
+
+    return Ok(words);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(command: &str) -> RequestRootSchema {
+        return parse_curl(command, Path::new("/tmp")).unwrap();
+    }
+
+    #[test]
+    fn value_of_unlisted_options_is_not_the_url() {
+        for command in [
+            "curl --retry 3 https://x.dev",
+            "curl -x http://proxy:8080 https://x.dev",
+            "curl -c jar.txt --cacert ca.pem --max-redirs 5 https://x.dev",
+            "curl --resolve x.dev:443:127.0.0.1 --cert me.pem https://x.dev",
+        ] {
+            assert_eq!(parse(command).url, "https://x.dev/", "{}", command);
+        }
+    }
+
+    #[test]
+    fn method_headers_and_json() {
+        let request = parse(
+            r#"curl -X PATCH https://x.dev/users -H 'Content-Type: application/json' -d '{"name": "a"}'"#,
+        );
+
+        assert_eq!(request.method, "PATCH");
+        assert_eq!(request.headers.unwrap()["Content-Type"], "application/json");
+        match request.body {
+            Some(RequestBodySchema::Json { content, .. }) => assert_eq!(content["name"], "a"),
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn method_follows_the_body_and_switches() {
+        assert_eq!(parse("curl https://x.dev").method, "GET");
+        assert_eq!(parse("curl -I https://x.dev").method, "HEAD");
+        assert_eq!(parse("curl https://x.dev -d a=1").method, "POST");
+        assert_eq!(parse("curl -XDELETE https://x.dev").method, "DELETE");
+    }
+
+    #[test]
+    fn get_moves_data_to_the_query() {
+        let request =
+            parse("curl -G 'https://x.dev/search?q=a' -d page=2 --data-urlencode 'tag=a b'");
+
+        assert_eq!(request.url, "https://x.dev/search");
+        assert!(request.body.is_none());
+        let query = request.query.unwrap();
+        assert_eq!(query["q"], "a");
+        assert_eq!(query["page"], "2");
+        assert_eq!(query["tag"], "a b");
+    }
+
+    #[test]
+    fn user_becomes_basic_auth_and_switches_go_to_config() {
+        let request = parse("curl -sk -m 1.5 -u me:secret https://x.dev");
+
+        assert_eq!(
+            request.headers.unwrap()["Authorization"],
+            "Basic bWU6c2VjcmV0"
+        );
+        let config = request.config.unwrap();
+        assert!(config.insecure);
+        assert_eq!(config.timeout, Some(1500));
+    }
+
+    #[test]
+    fn form_fields_and_files() {
+        let request = parse("curl https://x.dev -F name=a -F 'file=@photo.png;type=image/png'");
+
+        match request.body {
+            Some(RequestBodySchema::Multipart { parts }) => {
+                assert!(matches!(
+                    &parts[0],
+                    MultipartPartSchema::Field { name, value } if name == "name" && value == "a"
+                ));
+                assert!(matches!(
+                    &parts[1],
+                    MultipartPartSchema::File { name, path, mime_type }
+                        if name == "file" && path == "/tmp/photo.png" && mime_type.as_deref() == Some("image/png")
+                ));
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn repeated_headers_are_combined() {
+        let request = parse(
+            "curl https://x.dev -H 'Accept: text/html' -H 'accept: application/json' -b a=1 -b b=2",
+        );
+        let headers = request.headers.unwrap();
+
+        assert_eq!(headers["Accept"], "text/html, application/json");
+        assert_eq!(headers["Cookie"], "a=1; b=2");
+    }
+
+    #[test]
+    fn query_moves_to_its_block_unless_a_key_repeats() {
+        let request = parse("curl 'https://x.dev/search?q=a&page=2'");
+        assert_eq!(request.url, "https://x.dev/search");
+        assert_eq!(request.query.unwrap()["page"], "2");
+
+        let request = parse("curl 'https://x.dev/search?tag=a&tag=b'");
+        assert_eq!(request.url, "https://x.dev/search?tag=a&tag=b");
+        assert!(request.query.is_none());
+
+        let request = parse("curl -G https://x.dev/search?tag=a -d tag=b");
+        assert_eq!(request.url, "https://x.dev/search?tag=a&tag=b");
+    }
+
+    #[test]
+    fn data_file_is_read_from_the_base_dir() {
+        let dir = std::env::temp_dir().join(format!("dotapi-curl-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("body.txt"), "a=1\r\n&b=2\n").unwrap();
+
+        let request = parse_curl("curl https://x.dev -d @body.txt", &dir).unwrap();
+        match request.body {
+            Some(RequestBodySchema::FormUrlencoded { content, .. }) => {
+                assert_eq!(content, "a=1&b=2")
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+
+        let request = parse_curl("curl https://x.dev --data-binary @body.txt", &dir).unwrap();
+        match request.body {
+            Some(RequestBodySchema::FormUrlencoded { content, .. }) => {
+                assert_eq!(content, "a=1\r\n&b=2\n")
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+
+        assert!(parse_curl("curl https://x.dev -d @missing.txt", &dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn upload_file_is_a_put_of_the_absolute_path() {
+        let request = parse("curl -T data/file.bin https://x.dev/upload");
+
+        assert_eq!(request.method, "PUT");
+        match request.body {
+            Some(RequestBodySchema::Binary { path, .. }) => {
+                assert_eq!(path, "/tmp/data/file.bin")
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn split_words_handles_quotes_and_continuations() {
+        let words = split_words("curl 'a b' \"c \\\"d\" $'e\\nf' g\\\n h").unwrap();
+        assert_eq!(words, ["curl", "a b", "c \"d", "e\nf", "g", "h"]);
+
+        assert!(split_words("curl 'a").is_err());
+        assert!(parse_curl("wget https://x.dev", Path::new("/tmp")).is_err());
+    }
+}
@@ -0,0 +1,294 @@
+use indexmap::IndexMap;
+
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::{
+    import::ImportedProject,
+    schema::{
+        calls::{CallSchema, SequenceSchema},
+        project::ProjectDefinationSchema,
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        roots::{ProjectRootSchema, RequestRootSchema},
+    },
+};
+
+// Headers set by the browser or the http layer, sending them again would be wrong
+const SKIPPED_HEADERS: &[&str] = &["host", "content-length", "cookie", "connection"];
+
+// Converts a HAR log, as exported by browser dev tools, into a project with one request per entry
+pub fn parse_har(content: &str) -> anyhow::Result<ImportedProject> {
+    let har = serde_json::from_str::<Value>(content).context("Invalid HAR file")?;
+    let entries = har["log"]["entries"]
+        .as_array()
+        .context("The HAR file has no entries")?;
+
+    let mut imported = ImportedProject::new(ProjectRootSchema {
+        project: ProjectDefinationSchema {
+            name: "Imported from HAR".to_string(),
+            description: String::new(),
+            version: None,
+        },
+        ..Default::default()
+    });
+
+    let mut names = vec![];
+    for entry in entries {
+        let request = convert_request(&entry["request"])?;
+        let name = format!("{} {}", request.method, last_segment(&request.url));
+        names.push(imported.add_request(&name, request));
+    }
+
+    imported.project.calls = CallSchema {
+        main: SequenceSchema::Steps(names),
+        overrides: IndexMap::new(),
+    };
+
+    return Ok(imported);
+}
+
+// The last path segment of a url, the host when it has no path
+fn last_segment(url: &str) -> String {
+    return match url::Url::parse(url) {
+        Ok(url) => url
+            .path_segments()
+            .and_then(|mut segments| segments.rfind(|segment| !segment.is_empty()))
+            .map(|segment| segment.to_string())
+            .or_else(|| url.host_str().map(|host| host.to_string()))
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+}
+
+fn pairs(items: &Value) -> Vec<(String, String)> {
+    return items
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            Some((
+                item["name"].as_str()?.to_string(),
+                item["value"].as_str().unwrap_or_default().to_string(),
+            ))
+        })
+        .collect();
+}
+
+fn convert_request(request: &Value) -> anyhow::Result<RequestRootSchema> {
+    let raw_url = request["url"].as_str().context("A HAR entry has no url")?;
+
+    // the query string is kept in the query block, the url without it
+    let (url, query) = match url::Url::parse(raw_url) {
+        Ok(mut url) => {
+            let query = url
+                .query_pairs()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect::<IndexMap<String, String>>();
+            url.set_query(None);
+            url.set_fragment(None);
+            (url.to_string(), query)
+        }
+        Err(_) => (raw_url.to_string(), IndexMap::new()),
+    };
+
+    // http/2 pseudo headers start with a colon
+    let headers = pairs(&request["headers"])
+        .into_iter()
+        .filter(|(key, _)| !key.starts_with(':'))
+        .filter(|(key, _)| !SKIPPED_HEADERS.contains(&key.to_lowercase().as_str()))
+        .collect::<IndexMap<String, String>>();
+
+    return Ok(RequestRootSchema {
+        method: request["method"].as_str().unwrap_or("GET").to_uppercase(),
+        url,
+        headers: if headers.is_empty() {
+            None
+        } else {
+            Some(headers)
+        },
+        query: if query.is_empty() { None } else { Some(query) },
+        body: convert_body(&request["postData"])?,
+        ..Default::default()
+    });
+}
+
+fn convert_body(post_data: &Value) -> anyhow::Result<Option<RequestBodySchema>> {
+    if post_data.is_null() {
+        return Ok(None);
+    }
+
+    let mime_type = post_data["mimeType"]
+        .as_str()
+        .unwrap_or_default()
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let content = post_data["text"].as_str().unwrap_or_default().to_string();
+    let params = post_data["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .collect::<Vec<&Value>>();
+
+    return Ok(Some(match mime_type.as_str() {
+        _ if content.is_empty() && params.is_empty() => return Ok(None),
+        mime if mime.contains("json") => match serde_json::from_str::<Value>(&content) {
+            Ok(json) => RequestBodySchema::Json {
+                content: serde_yaml::to_value(json)?,
+                content_file: None,
+            },
+            Err(_) => RequestBodySchema::Text {
+                content,
+                content_file: None,
+            },
+        },
+        "application/x-www-form-urlencoded" if !params.is_empty() => {
+            RequestBodySchema::FormUrlencoded {
+                content: String::new(),
+                content_file: None,
+                fields: Some(pairs(&post_data["params"]).into_iter().collect()),
+            }
+        }
+        "application/x-www-form-urlencoded" => RequestBodySchema::FormUrlencoded {
+            content,
+            content_file: None,
+            fields: None,
+        },
+        // browsers give the parts in params, the files themselves aren't in the log
+        "multipart/form-data" if !params.is_empty() => RequestBodySchema::Multipart {
+            parts: params
+                .iter()
+                .map(|param| {
+                    let name = param["name"].as_str().unwrap_or_default().to_string();
+                    match param["fileName"].as_str() {
+                        Some(file_name) => MultipartPartSchema::File {
+                            name,
+                            path: file_name.to_string(),
+                            mime_type: param["contentType"].as_str().map(|mime| mime.to_string()),
+                        },
+                        None => MultipartPartSchema::Field {
+                            name,
+                            value: param["value"].as_str().unwrap_or_default().to_string(),
+                        },
+                    }
+                })
+                .collect(),
+        },
+        mime if mime.contains("xml") => RequestBodySchema::Xml {
+            content,
+            content_file: None,
+        },
+        _ => RequestBodySchema::Text {
+            content,
+            content_file: None,
+        },
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAR: &str = r#"{
+        "log": {
+            "version": "1.2",
+            "entries": [
+                {"request": {
+                    "method": "get",
+                    "url": "https://shop.dev/api/users/?page=2&sort=name#list",
+                    "headers": [
+                        {"name": ":authority", "value": "shop.dev"},
+                        {"name": "Cookie", "value": "session=1"},
+                        {"name": "Accept", "value": "application/json"}
+                    ]
+                }},
+                {"request": {
+                    "method": "POST",
+                    "url": "https://shop.dev/api/users",
+                    "headers": [],
+                    "postData": {"mimeType": "application/json; charset=utf-8", "text": "{\"name\": \"a\"}"}
+                }},
+                {"request": {
+                    "method": "POST",
+                    "url": "https://shop.dev/login",
+                    "postData": {"mimeType": "application/x-www-form-urlencoded",
+                                 "params": [{"name": "user", "value": "me"}]}
+                }},
+                {"request": {
+                    "method": "POST",
+                    "url": "https://shop.dev/upload",
+                    "postData": {"mimeType": "multipart/form-data; boundary=x",
+                                 "params": [{"name": "title", "value": "a"},
+                                            {"name": "file", "fileName": "a.png", "contentType": "image/png"}]}
+                }}
+            ]
+        }
+    }"#;
+
+    #[test]
+    fn names_requests_after_method_and_last_segment() {
+        let imported = parse_har(HAR).unwrap();
+        let names = imported
+            .requests
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            names,
+            ["get_users", "post_users", "post_login", "post_upload"]
+        );
+        assert_eq!(last_segment("https://shop.dev"), "shop.dev");
+    }
+
+    #[test]
+    fn keeps_the_query_apart_and_drops_browser_headers() {
+        let imported = parse_har(HAR).unwrap();
+        let request = &imported.requests[0].1;
+
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.url, "https://shop.dev/api/users/");
+        assert_eq!(request.query.as_ref().unwrap()["page"], "2");
+
+        let headers = request.headers.as_ref().unwrap();
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers["Accept"], "application/json");
+    }
+
+    #[test]
+    fn converts_bodies() {
+        let imported = parse_har(HAR).unwrap();
+
+        match &imported.requests[1].1.body {
+            Some(RequestBodySchema::Json { content, .. }) => assert_eq!(content["name"], "a"),
+            body => panic!("unexpected body {:?}", body),
+        };
+        match &imported.requests[2].1.body {
+            Some(RequestBodySchema::FormUrlencoded { fields, .. }) => {
+                assert_eq!(fields.as_ref().unwrap()["user"], "me")
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+        match &imported.requests[3].1.body {
+            Some(RequestBodySchema::Multipart { parts }) => {
+                assert!(
+                    matches!(&parts[0], MultipartPartSchema::Field { value, .. } if value == "a")
+                );
+                assert!(matches!(
+                    &parts[1],
+                    MultipartPartSchema::File { path, mime_type, .. }
+                        if path == "a.png" && mime_type.as_deref() == Some("image/png")
+                ));
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn rejects_files_without_entries() {
+        assert!(parse_har(r#"{"log": {}}"#).is_err());
+        assert!(parse_har("not json").is_err());
+    }
+}
@@ -0,0 +1,454 @@
+use indexmap::IndexMap;
+
+use crate::{
+    import::ImportedProject,
+    schema::{
+        calls::{CallSchema, SequenceSchema},
+        env::EnvironmentVariableSchema,
+        project::ProjectDefinationSchema,
+        request_body::RequestBodySchema,
+        roots::{ProjectRootSchema, RequestRootSchema},
+    },
+};
+
+const METHODS: &[&str] = &[
+    "GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS", "TRACE", "CONNECT",
+];
+
+/// A request as written in a .http file, before it's converted.
+#[derive(Default)]
+struct HttpFileRequest {
+    // from `### title` or `# @name name`
+    name: Option<String>,
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Vec<String>,
+}
+
+// Converts a JetBrains or VS Code .http (.rest) file into a project.
+// Requests are separated by ###, `@name = value` lines become project variables
+// and {{$dynamic}} variables the matching functions.
+pub fn parse_http_file(content: &str) -> anyhow::Result<ImportedProject> {
+    let mut env = IndexMap::<String, EnvironmentVariableSchema>::new();
+    let mut requests = vec![];
+
+    for block in split_blocks(content) {
+        if let Some(request) = parse_block(&block, &mut env) {
+            requests.push(request);
+        }
+    }
+
+    if requests.is_empty() {
+        anyhow::bail!("The file has no requests");
+    }
+
+    let mut imported = ImportedProject::new(ProjectRootSchema {
+        project: ProjectDefinationSchema {
+            name: "Imported from .http file".to_string(),
+            description: String::new(),
+            version: None,
+        },
+        env,
+        ..Default::default()
+    });
+
+    let mut names = vec![];
+    for request in requests {
+        let name = request
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", request.method, last_segment(&request.url)));
+        names.push(imported.add_request(&name, convert_request(request)?));
+    }
+
+    imported.project.calls = CallSchema {
+        main: SequenceSchema::Steps(names),
+        overrides: IndexMap::new(),
+    };
+
+    return Ok(imported);
+}
+
+// Splits the file on ### lines, the text after ### titles the block
+fn split_blocks(content: &str) -> Vec<(Option<String>, Vec<&str>)> {
+    let mut blocks = vec![(None, vec![])];
+
+    for line in content.lines() {
+        match line.trim_start().strip_prefix("###") {
+            Some(title) => {
+                let title = title.trim();
+                blocks.push((Some(title.to_string()).filter(|t| !t.is_empty()), vec![]));
+            }
+            None => blocks.last_mut().unwrap().1.push(line),
+        };
+    }
+
+    return blocks;
+}
+
+fn parse_block(
+    (title, lines): &(Option<String>, Vec<&str>),
+    env: &mut IndexMap<String, EnvironmentVariableSchema>,
+) -> Option<HttpFileRequest> {
+    let mut request = HttpFileRequest {
+        name: title.clone(),
+        ..Default::default()
+    };
+    let mut lines = lines.iter().peekable();
+
+    // variables, comments and blank lines come before the request line
+    loop {
+        let line = lines.peek()?.trim();
+
+        if let Some(variable) = line.strip_prefix('@') {
+            if let Some((key, value)) = variable.split_once('=') {
+                env.insert(
+                    key.trim().to_string(),
+                    EnvironmentVariableSchema::new(
+                        serde_yaml::Value::String(convert_template(value.trim())),
+                        vec![],
+                    ),
+                );
+            }
+        } else if let Some(comment) = line.strip_prefix('#').or(line.strip_prefix("//")) {
+            if let Some(name) = comment.trim().strip_prefix("@name") {
+                request.name = Some(name.trim_start_matches([' ', '=']).trim().to_string());
+            }
+        } else if !line.is_empty() {
+            break;
+        }
+
+        lines.next();
+    }
+
+    // [METHOD] url [HTTP/version], the query may continue on lines starting with ? or &
+    let request_line = lines.next()?.trim();
+    let mut parts = request_line.split_whitespace().collect::<Vec<&str>>();
+    if parts.len() > 1 && parts.last()?.starts_with("HTTP/") {
+        parts.pop();
+    }
+    match METHODS.contains(&parts[0].to_uppercase().as_str()) {
+        true => {
+            request.method = parts[0].to_uppercase();
+            request.url = parts[1..].join(" ");
+        }
+        false => {
+            request.method = "GET".to_string();
+            request.url = parts.join(" ");
+        }
+    };
+
+    while let Some(line) = lines.peek() {
+        let line = line.trim();
+        if !line.starts_with('?') && !line.starts_with('&') {
+            break;
+        }
+        request.url.push_str(line);
+        lines.next();
+    }
+
+    for line in lines.by_ref() {
+        let line = line.trim();
+        if line.is_empty() {
+            break;
+        }
+        if line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        if let Some((key, value)) = line.split_once(':') {
+            request
+                .headers
+                .push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    for line in lines {
+        // response handlers and references are JetBrains scripts
+        if line.starts_with("> ") || line.starts_with("<> ") {
+            tracing::warn!("Response handlers can't be imported, skipped \"{}\"", line);
+            continue;
+        }
+        request.body.push(line.to_string());
+    }
+
+    return Some(request);
+}
+
+// The last path segment of a url without its query, eg: {{host}}/users/:id?x=1 -> id
+fn last_segment(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+
+    return path
+        .rsplit('/')
+        .find(|segment| !segment.is_empty() && !segment.contains("://"))
+        .unwrap_or_default()
+        .to_string();
+}
+
+// Turns {{$dynamic}} variables into function calls, other placeholders are kept as they are
+pub fn convert_template(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+
+        let placeholder = rest[start + 2..end].trim();
+        let converted = match placeholder.strip_prefix('$') {
+            Some(dynamic) => convert_dynamic(dynamic),
+            None => placeholder.to_string(),
+        };
+
+        result.push_str(&format!("{{{{{}}}}}", converted));
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    return result;
+}
+
+fn convert_dynamic(dynamic: &str) -> String {
+    let mut words = dynamic.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let arguments = words.collect::<Vec<&str>>();
+
+    return match (name, arguments.as_slice()) {
+        ("uuid" | "guid" | "random.uuid", _) => "uuid()".to_string(),
+        ("timestamp", _) => "timestamp()".to_string(),
+        ("isoTimestamp" | "datetime" | "localDatetime", _) => "now()".to_string(),
+        ("randomInt", [min, max]) => format!("random_int({}, {})", min, max),
+        ("processEnv" | "dotenv", [name]) => {
+            format!("env('{}')", name.trim_start_matches('%'))
+        }
+        _ => {
+            tracing::warn!(
+                "Unknown dynamic variable ${}, it's kept as a variable",
+                dynamic
+            );
+            dynamic.to_string()
+        }
+    };
+}
+
+fn convert_request(request: HttpFileRequest) -> anyhow::Result<RequestRootSchema> {
+    let headers = request
+        .headers
+        .iter()
+        .map(|(key, value)| (key.clone(), convert_template(value)))
+        .collect::<IndexMap<String, String>>();
+    let content_type = headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.to_lowercase())
+        .unwrap_or_default();
+
+    return Ok(RequestRootSchema {
+        method: request.method,
+        url: convert_template(&request.url),
+        headers: if headers.is_empty() {
+            None
+        } else {
+            Some(headers)
+        },
+        body: convert_body(&request.body, &content_type)?,
+        ..Default::default()
+    });
+}
+
+fn convert_body(lines: &[String], content_type: &str) -> anyhow::Result<Option<RequestBodySchema>> {
+    let content = convert_template(lines.join("\n").trim());
+    if content.is_empty() {
+        return Ok(None);
+    }
+
+    // `< ./path` reads the body from a file, relative to the .http file
+    if let Some(path) = content
+        .strip_prefix("< ")
+        .filter(|path| !path.contains('\n'))
+    {
+        tracing::warn!(
+            "{} is relative to the .http file, check it resolves from the request file",
+            path.trim()
+        );
+        let path = Some(path.trim().to_string());
+
+        return Ok(Some(match content_type {
+            mime if mime.contains("json") => RequestBodySchema::Json {
+                content: serde_yaml::Value::Null,
+                content_file: path,
+            },
+            mime if mime.contains("xml") => RequestBodySchema::Xml {
+                content: String::new(),
+                content_file: path,
+            },
+            _ => RequestBodySchema::Text {
+                content: String::new(),
+                content_file: path,
+            },
+        }));
+    }
+
+    return Ok(Some(match content_type {
+        mime if mime.contains("json") => {
+            match serde_json::from_str::<serde_json::Value>(&content) {
+                Ok(json) => RequestBodySchema::Json {
+                    content: serde_yaml::to_value(json)?,
+                    content_file: None,
+                },
+                // json with placeholders in place of values doesn't parse, it's kept as text
+                Err(_) => RequestBodySchema::Text {
+                    content,
+                    content_file: None,
+                },
+            }
+        }
+        mime if mime.contains("x-www-form-urlencoded") => RequestBodySchema::FormUrlencoded {
+            content: content.lines().map(|line| line.trim()).collect(),
+            content_file: None,
+            fields: None,
+        },
+        mime if mime.contains("graphql") => RequestBodySchema::Graphql {
+            query: content,
+            query_file: None,
+            variables: None,
+            validate: false,
+            persisted: false,
+        },
+        mime if mime.contains("xml") => RequestBodySchema::Xml {
+            content,
+            content_file: None,
+        },
+        _ => RequestBodySchema::Text {
+            content,
+            content_file: None,
+        },
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"@host = https://shop.dev
+@token = {{$processEnv API_TOKEN}}
+
+### List users
+GET {{host}}/users
+    ?page=2
+    &sort=name
+Accept: application/json
+
+###
+# @name create_user
+POST {{host}}/users HTTP/1.1
+Content-Type: application/json
+Authorization: Bearer {{token}}
+
+{"id": "{{$uuid}}", "name": "a"}
+
+> {% client.global.set("id", response.body.id) %}
+
+### Login
+POST {{host}}/login
+Content-Type: application/x-www-form-urlencoded
+
+user=me
+&password=secret
+
+###
+{{host}}/health
+"#;
+
+    fn request<'a>(imported: &'a ImportedProject, name: &str) -> &'a RequestRootSchema {
+        return &imported
+            .requests
+            .iter()
+            .find(|(existing, _)| existing == name)
+            .unwrap()
+            .1;
+    }
+
+    #[test]
+    fn splits_requests_on_separators_and_names_them() {
+        let imported = parse_http_file(FILE).unwrap();
+        let names = imported
+            .requests
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, ["list_users", "create_user", "login", "get_health"]);
+        assert_eq!(request(&imported, "get_health").method, "GET");
+    }
+
+    #[test]
+    fn variables_become_env_and_dynamic_variables_functions() {
+        let imported = parse_http_file(FILE).unwrap();
+
+        assert_eq!(imported.project.env["host"].default, "https://shop.dev");
+        assert_eq!(
+            imported.project.env["token"].default,
+            "{{env('API_TOKEN')}}"
+        );
+        assert_eq!(
+            convert_template("{{$randomInt 1 9}}/{{id}}"),
+            "{{random_int(1, 9)}}/{{id}}"
+        );
+        assert_eq!(convert_template("no {{ end"), "no {{ end");
+    }
+
+    #[test]
+    fn request_line_query_and_headers() {
+        let imported = parse_http_file(FILE).unwrap();
+
+        let list = request(&imported, "list_users");
+        assert_eq!(list.url, "{{host}}/users?page=2&sort=name");
+        assert_eq!(list.headers.as_ref().unwrap()["Accept"], "application/json");
+        assert!(list.body.is_none());
+
+        let create = request(&imported, "create_user");
+        assert_eq!(create.method, "POST");
+        assert_eq!(create.url, "{{host}}/users");
+        assert_eq!(
+            create.headers.as_ref().unwrap()["Authorization"],
+            "Bearer {{token}}"
+        );
+    }
+
+    #[test]
+    fn bodies_follow_the_content_type() {
+        let imported = parse_http_file(FILE).unwrap();
+
+        // the placeholder in the json is quoted, so the body still parses
+        match &request(&imported, "create_user").body {
+            Some(RequestBodySchema::Json { content, .. }) => {
+                assert_eq!(content["id"], "{{uuid()}}");
+                assert_eq!(content["name"], "a");
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+        match &request(&imported, "login").body {
+            Some(RequestBodySchema::FormUrlencoded { content, .. }) => {
+                assert_eq!(content, "user=me&password=secret")
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+
+        let file = convert_body(&["< ./body.json".to_string()], "application/json").unwrap();
+        assert!(matches!(
+            file,
+            Some(RequestBodySchema::Json { content_file: Some(path), .. }) if path == "./body.json"
+        ));
+    }
+
+    #[test]
+    fn files_without_requests_fail() {
+        assert!(parse_http_file("@host = x\n# nothing else\n").is_err());
+    }
+}
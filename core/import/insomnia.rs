@@ -0,0 +1,414 @@
+use indexmap::IndexMap;
+
+use anyhow::Context;
+use serde_json::Value;
+
+use crate::{
+    import::ImportedProject,
+    schema::{
+        auth::AuthSchema,
+        calls::{CallSchema, SequenceSchema},
+        env::EnvironmentVariableSchema,
+        project::ProjectDefinationSchema,
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        request_config::RequestConfigSchema,
+        roots::{ProjectRootSchema, RequestRootSchema},
+    },
+};
+
+// Converts an Insomnia v4 export (json or yaml) into a project.
+// Folders become the class of their requests, the base environment gives the defaults
+// of variables and its sub environments their overrides.
+pub fn parse_insomnia(content: &str) -> anyhow::Result<ImportedProject> {
+    let export = serde_yaml::from_str::<Value>(content).context("Invalid Insomnia export")?;
+
+    if export["__export_format"].as_u64() != Some(4) {
+        anyhow::bail!("Only Insomnia exports in format 4 can be imported");
+    }
+
+    let resources = export["resources"]
+        .as_array()
+        .context("The Insomnia export has no resources")?;
+    let of_type = |kind: &'static str| {
+        resources
+            .iter()
+            .filter(move |resource| resource["_type"] == kind)
+    };
+
+    let workspace = of_type("workspace").next();
+    let workspace_id = workspace.and_then(|workspace| workspace["_id"].as_str());
+    let folders = of_type("request_group")
+        .filter_map(|folder| Some((folder["_id"].as_str()?, folder)))
+        .collect::<IndexMap<&str, &Value>>();
+
+    let mut imported = ImportedProject::new(ProjectRootSchema {
+        project: ProjectDefinationSchema {
+            name: text(workspace, "name").unwrap_or("Imported from Insomnia".to_string()),
+            description: text(workspace, "description").unwrap_or_default(),
+            version: None,
+        },
+        env: environments(of_type("environment").collect(), workspace_id),
+        ..Default::default()
+    });
+
+    let mut names = vec![];
+    for request in of_type("request") {
+        let name = text(Some(request), "name").unwrap_or_default();
+        names.push(imported.add_request(&name, convert_request(request, &folders)?));
+    }
+
+    imported.project.calls = CallSchema {
+        main: SequenceSchema::Steps(names),
+        overrides: IndexMap::new(),
+    };
+
+    return Ok(imported);
+}
+
+fn text(resource: Option<&Value>, key: &str) -> Option<String> {
+    return resource
+        .and_then(|resource| resource[key].as_str())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string());
+}
+
+// Insomnia references variables as {{ _.name }}, native doctor as {{name}}
+pub fn convert_template(value: &str) -> String {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        result.push_str(&rest[..start]);
+
+        let variable = rest[start + 2..end].trim();
+        let variable = variable.strip_prefix("_.").unwrap_or(variable);
+        result.push_str(&format!("{{{{{}}}}}", variable));
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    return result;
+}
+
+fn environments(
+    environments: Vec<&Value>,
+    workspace_id: Option<&str>,
+) -> IndexMap<String, EnvironmentVariableSchema> {
+    let base = environments
+        .iter()
+        .find(|environment| environment["parentId"].as_str() == workspace_id)
+        .copied();
+    let base_id = base.and_then(|base| base["_id"].as_str());
+
+    let mut env = IndexMap::<String, EnvironmentVariableSchema>::new();
+    let to_yaml = |value: &Value| serde_yaml::to_value(value).unwrap_or_default();
+
+    if let Some(data) = base.and_then(|base| base["data"].as_object()) {
+        for (key, value) in data {
+            env.insert(
+                key.clone(),
+                EnvironmentVariableSchema::new(to_yaml(value), vec![]),
+            );
+        }
+    }
+
+    for environment in &environments {
+        if environment["parentId"].as_str() != base_id || base_id.is_none() {
+            continue;
+        }
+
+        let name = match text(Some(environment), "name") {
+            Some(name) => name,
+            None => continue,
+        };
+
+        for (key, value) in environment["data"].as_object().into_iter().flatten() {
+            env.entry(key.clone())
+                .or_insert_with(|| EnvironmentVariableSchema::new(serde_yaml::Value::Null, vec![]))
+                .overrides
+                .insert(name.clone(), to_yaml(value));
+        }
+    }
+
+    return env;
+}
+
+fn convert_request(
+    request: &Value,
+    folders: &IndexMap<&str, &Value>,
+) -> anyhow::Result<RequestRootSchema> {
+    let enabled = |key: &str| -> Vec<(String, String)> {
+        return request[key]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|item| item["disabled"] != true)
+            .filter_map(|item| {
+                Some((
+                    item["name"].as_str()?.to_string(),
+                    convert_template(item["value"].as_str().unwrap_or_default()),
+                ))
+            })
+            .collect();
+    };
+
+    let headers = enabled("headers")
+        .into_iter()
+        .collect::<IndexMap<String, String>>();
+    let query = enabled("parameters")
+        .into_iter()
+        .collect::<IndexMap<String, String>>();
+
+    let class = request["parentId"]
+        .as_str()
+        .and_then(|parent| folders.get(parent))
+        .and_then(|folder| text(Some(*folder), "name"));
+
+    return Ok(RequestRootSchema {
+        method: text(Some(request), "method").unwrap_or("GET".to_string()),
+        url: convert_template(request["url"].as_str().unwrap_or_default()),
+        doc: text(Some(request), "description").unwrap_or_default(),
+        config: class.map(|class| RequestConfigSchema {
+            class: Some(class),
+            ..Default::default()
+        }),
+        auth: convert_auth(&request["authentication"]),
+        headers: if headers.is_empty() {
+            None
+        } else {
+            Some(headers)
+        },
+        query: if query.is_empty() { None } else { Some(query) },
+        body: convert_body(&request["body"])?,
+        ..Default::default()
+    });
+}
+
+fn convert_auth(authentication: &Value) -> Option<AuthSchema> {
+    if authentication["disabled"] == true {
+        return None;
+    }
+
+    let field = |key: &str| convert_template(authentication[key].as_str().unwrap_or_default());
+
+    return match authentication["type"].as_str()? {
+        "basic" => Some(AuthSchema::Basic {
+            username: field("username"),
+            password: field("password"),
+        }),
+        "bearer" => Some(AuthSchema::Bearer {
+            token: field("token"),
+        }),
+        "digest" => Some(AuthSchema::Digest {
+            username: field("username"),
+            password: field("password"),
+        }),
+        "ntlm" => Some(AuthSchema::Ntlm {
+            username: field("username"),
+            password: field("password"),
+            domain: field("domain"),
+            workstation: field("workstation"),
+        }),
+        other => {
+            tracing::warn!("Insomnia {} authentication can't be imported", other);
+            None
+        }
+    };
+}
+
+fn convert_body(body: &Value) -> anyhow::Result<Option<RequestBodySchema>> {
+    let mime_type = body["mimeType"].as_str().unwrap_or_default();
+    let content = convert_template(body["text"].as_str().unwrap_or_default());
+
+    let params = body["params"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|param| param["disabled"] != true)
+        .collect::<Vec<&Value>>();
+
+    return Ok(Some(match mime_type {
+        "" if content.is_empty() => return Ok(None),
+        "application/json" => match serde_json::from_str::<Value>(&content) {
+            Ok(json) => RequestBodySchema::Json {
+                content: serde_yaml::to_value(json)?,
+                content_file: None,
+            },
+            // json with placeholders in place of values doesn't parse, it's kept as text
+            Err(_) => RequestBodySchema::Text {
+                content,
+                content_file: None,
+            },
+        },
+        "application/graphql" => {
+            let graphql = serde_json::from_str::<Value>(&content).unwrap_or_default();
+            RequestBodySchema::Graphql {
+                query: graphql["query"].as_str().unwrap_or_default().to_string(),
+                query_file: None,
+                variables: match &graphql["variables"] {
+                    Value::Null => None,
+                    variables => Some(serde_yaml::to_value(variables)?),
+                },
+                validate: false,
+                persisted: false,
+            }
+        }
+        "application/x-www-form-urlencoded" => RequestBodySchema::FormUrlencoded {
+            content: String::new(),
+            content_file: None,
+            fields: Some(
+                params
+                    .iter()
+                    .map(|param| {
+                        (
+                            param["name"].as_str().unwrap_or_default().to_string(),
+                            convert_template(param["value"].as_str().unwrap_or_default()),
+                        )
+                    })
+                    .collect(),
+            ),
+        },
+        "multipart/form-data" => RequestBodySchema::Multipart {
+            parts: params
+                .iter()
+                .map(|param| {
+                    let name = param["name"].as_str().unwrap_or_default().to_string();
+                    match param["type"].as_str() {
+                        Some("file") => MultipartPartSchema::File {
+                            name,
+                            path: param["fileName"].as_str().unwrap_or_default().to_string(),
+                            mime_type: None,
+                        },
+                        _ => MultipartPartSchema::Field {
+                            name,
+                            value: convert_template(param["value"].as_str().unwrap_or_default()),
+                        },
+                    }
+                })
+                .collect(),
+        },
+        mime if mime.contains("xml") => RequestBodySchema::Xml {
+            content,
+            content_file: None,
+        },
+        mime if body["fileName"].is_string() => RequestBodySchema::Binary {
+            path: body["fileName"].as_str().unwrap_or_default().to_string(),
+            content_type: Some(mime.to_string()).filter(|mime| !mime.is_empty()),
+        },
+        _ => RequestBodySchema::Text {
+            content,
+            content_file: None,
+        },
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "_type": "export",
+        "__export_format": 4,
+        "resources": [
+            {"_id": "wrk_1", "_type": "workspace", "name": "Shop", "description": "The shop api"},
+            {"_id": "env_1", "_type": "environment", "parentId": "wrk_1", "name": "Base",
+             "data": {"host": "http://localhost", "token": "dev"}},
+            {"_id": "env_2", "_type": "environment", "parentId": "env_1", "name": "prod",
+             "data": {"host": "https://shop.dev"}},
+            {"_id": "fld_1", "_type": "request_group", "parentId": "wrk_1", "name": "users"},
+            {"_id": "req_1", "_type": "request", "parentId": "fld_1", "name": "Get user",
+             "method": "GET", "url": "{{ _.host }}/users/1",
+             "headers": [{"name": "Accept", "value": "application/json"},
+                         {"name": "X-Old", "value": "1", "disabled": true}],
+             "parameters": [{"name": "expand", "value": "{{_.expand}}"}],
+             "authentication": {"type": "bearer", "token": "{{ _.token }}"},
+             "body": {}},
+            {"_id": "req_2", "_type": "request", "parentId": "wrk_1", "name": "Get user",
+             "method": "POST", "url": "{{ _.host }}/login",
+             "body": {"mimeType": "application/x-www-form-urlencoded",
+                      "params": [{"name": "user", "value": "me"}]}},
+            {"_id": "req_3", "_type": "request", "parentId": "wrk_1", "name": "Create",
+             "method": "POST", "url": "{{ _.host }}/items",
+             "body": {"mimeType": "application/json", "text": "{\"name\": \"a\"}"}}
+        ]
+    }"#;
+
+    #[test]
+    fn converts_templates() {
+        assert_eq!(
+            convert_template("{{ _.host }}/a/{{id}}"),
+            "{{host}}/a/{{id}}"
+        );
+        assert_eq!(convert_template("no {{ end"), "no {{ end");
+    }
+
+    #[test]
+    fn imports_workspace_environments_and_requests() {
+        let imported = parse_insomnia(EXPORT).unwrap();
+
+        assert_eq!(imported.project.project.name, "Shop");
+        let host = &imported.project.env["host"];
+        assert_eq!(host.default, "http://localhost");
+        assert_eq!(host.overrides["prod"], "https://shop.dev");
+
+        let names = imported
+            .requests
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["get_user", "get_user_2", "create"]);
+        assert_eq!(
+            imported.project.calls.main,
+            SequenceSchema::Steps(vec![
+                "get_user".to_string(),
+                "get_user_2".to_string(),
+                "create".to_string()
+            ])
+        );
+
+        let request = &imported.requests[0].1;
+        assert_eq!(request.url, "{{host}}/users/1");
+        assert_eq!(
+            request.config.as_ref().unwrap().class.as_deref(),
+            Some("users")
+        );
+        let headers = request.headers.as_ref().unwrap();
+        assert_eq!(headers["Accept"], "application/json");
+        assert!(!headers.contains_key("X-Old"));
+        assert_eq!(request.query.as_ref().unwrap()["expand"], "{{expand}}");
+        assert_eq!(
+            request.auth,
+            Some(AuthSchema::Bearer {
+                token: "{{token}}".to_string()
+            })
+        );
+        assert!(request.body.is_none());
+    }
+
+    #[test]
+    fn imports_bodies() {
+        let imported = parse_insomnia(EXPORT).unwrap();
+
+        match &imported.requests[1].1.body {
+            Some(RequestBodySchema::FormUrlencoded { fields, .. }) => {
+                assert_eq!(fields.as_ref().unwrap()["user"], "me")
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+        match &imported.requests[2].1.body {
+            Some(RequestBodySchema::Json { content, .. }) => assert_eq!(content["name"], "a"),
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn rejects_other_formats() {
+        assert!(parse_insomnia(r#"{"__export_format": 3, "resources": []}"#).is_err());
+        assert!(parse_insomnia("not: [an, export").is_err());
+    }
+}
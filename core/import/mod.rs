@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::{
+    fs::FileObject,
+    schema::roots::{ProjectRootSchema, RequestRootSchema},
+};
+
+pub mod curl;
+pub mod har;
+pub mod http_file;
+pub mod insomnia;
+
+pub const PROJECT_FILE: &str = ".nd-project";
+
+/// A project converted from another tool, written to disk as a project file and its request files.
+pub struct ImportedProject {
+    pub project: ProjectRootSchema,
+    // request name -> request, in the order they were found
+    pub requests: Vec<(String, RequestRootSchema)>,
+}
+
+impl ImportedProject {
+    pub fn new(project: ProjectRootSchema) -> ImportedProject {
+        return ImportedProject {
+            project,
+            requests: vec![],
+        };
+    }
+
+    // Adds a request under a file name made from its name, numbered when it's already taken
+    pub fn add_request(&mut self, name: &str, request: RequestRootSchema) -> String {
+        let base = request_file_name(name);
+        let mut name = base.clone();
+        let mut index = 2;
+
+        while self.requests.iter().any(|(existing, _)| *existing == name) {
+            name = format!("{}_{}", base, index);
+            index += 1;
+        }
+
+        self.requests.push((name.clone(), request));
+        return name;
+    }
+
+    // Writes the project file and its requests into dir, which must not hold a project already
+    pub async fn write(&self, dir: &Path) -> anyhow::Result<FileObject<ProjectRootSchema>> {
+        let path = std::path::absolute(dir.join(PROJECT_FILE))?;
+        if path.exists() {
+            anyhow::bail!("A project already exists at {:?}", path);
+        }
+
+        tokio::fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("Failed to create {:?}", dir))?;
+
+        let project = FileObject::new(path, self.project.clone());
+        project.save().await?;
+
+        for (name, request) in &self.requests {
+            project.create_request(name, request.clone()).await?;
+        }
+
+        return Ok(project);
+    }
+}
+
+// A name usable as a file name and a call name, eg: "Get user (v2)" -> get_user_v2
+pub fn request_file_name(name: &str) -> String {
+    let mut result = String::new();
+
+    for c in name.trim().chars() {
+        if c.is_alphanumeric() {
+            result.extend(c.to_lowercase());
+        } else if !result.ends_with('_') {
+            result.push('_');
+        }
+    }
+
+    let result = result.trim_matches('_').to_string();
+    if result.is_empty() {
+        return "request".to_string();
+    }
+
+    return result;
+}
@@ -0,0 +1,420 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fs::{FileObject, LoadError, LoadFailure, LoadFailureKind, call_name, request_paths},
+    schema::roots::{ProjectRootSchema, RequestRootSchema},
+    schema_cache::SchemaCache,
+};
+
+// The fields needed to list a request, the rest of the file is ignored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub(crate) struct RequestHeaderSchema {
+    #[serde(default)]
+    method: String,
+    #[serde(default)]
+    url: String,
+    #[serde(default)]
+    doc: String,
+    #[serde(default)]
+    config: Option<RequestHeaderConfigSchema>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RequestHeaderConfigSchema {
+    #[serde(default, alias = "group")]
+    class: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// A request file as listed in the index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestSummary {
+    pub name: String,
+    pub path: PathBuf,
+    pub method: String,
+    pub url: String,
+    pub doc: String,
+    pub class: Option<String>,
+    pub tags: Vec<String>,
+}
+
+/// The requests of a project, listed from the few fields shown for them.
+///
+/// Requests that haven't changed are listed from the schema cache without reading their files,
+/// the others are parsed once and cached. Loading a request takes it from the cache.
+pub struct RequestIndex {
+    pub requests: Vec<RequestSummary>,
+    // files that couldn't be listed, reported with the ones that fail to load
+    failures: Vec<LoadFailure>,
+    skip_invalid: bool,
+    cache: SchemaCache,
+}
+
+impl RequestSummary {
+    // The summary of a request that is already loaded
+    pub fn of(request: &FileObject<RequestRootSchema>) -> RequestSummary {
+        let config = request.object.config.clone().unwrap_or_default();
+
+        return RequestSummary {
+            name: request.get_call_name(),
+            path: request.path.clone(),
+            method: request.object.method.clone(),
+            url: request.object.url.clone(),
+            doc: request.object.doc.clone(),
+            class: config.class,
+            tags: config.tags,
+        };
+    }
+}
+
+impl FileObject<ProjectRootSchema> {
+    // Lists the requests of the project without parsing them.
+    // Every file that fails is reported together, unless the project skips invalid requests.
+    pub async fn index_requests(&self) -> anyhow::Result<RequestIndex> {
+        let mut index = self.build_index().await;
+        index.check(vec![])?;
+
+        if let Err(e) = index.save_cache().await {
+            tracing::warn!("{:#}", e);
+        }
+
+        return Ok(index);
+    }
+
+    pub(crate) async fn build_index(&self) -> RequestIndex {
+        let mut cache = SchemaCache::open(&self.get_root_dir()).await;
+        let mut failures = vec![];
+        let mut requests = Vec::<RequestSummary>::new();
+
+        let paths = match request_paths(&self.get_requests_dir()).await {
+            Ok(paths) => paths,
+            Err(failure) => {
+                failures.push(failure);
+                vec![]
+            }
+        };
+
+        for path in paths {
+            let header = match cache.read_header(&path).await {
+                Ok(header) => header,
+                Err(failure) => {
+                    failures.push(failure);
+                    continue;
+                }
+            };
+
+            // calls reference requests by name, two files with the same name can't both be called
+            let name = call_name(&path);
+            if let Some(existing) = requests.iter().find(|r| r.name == name) {
+                failures.push(LoadFailure {
+                    path: path.clone(),
+                    kind: LoadFailureKind::Conflict,
+                    message: format!(
+                        "Request \"{}\" is already defined in {:?}",
+                        name, existing.path
+                    ),
+                });
+                continue;
+            }
+
+            let config = header.config.unwrap_or_default();
+            requests.push(RequestSummary {
+                name,
+                path,
+                method: header.method,
+                url: header.url,
+                doc: header.doc,
+                class: config.class,
+                tags: config.tags,
+            });
+        }
+
+        return RequestIndex {
+            requests,
+            failures,
+            skip_invalid: self.object.skip_invalid_requests,
+            cache,
+        };
+    }
+}
+
+impl RequestIndex {
+    pub fn find(&self, name: &str) -> Option<&RequestSummary> {
+        return self.requests.iter().find(|request| request.name == name);
+    }
+
+    // Parses the full request, from the schema cache if the file hasn't changed
+    pub async fn load(&mut self, name: &str) -> anyhow::Result<FileObject<RequestRootSchema>> {
+        let path = match self.find(name) {
+            Some(request) => request.path.clone(),
+            None => anyhow::bail!("Request \"{}\" does not exist", name),
+        };
+
+        let schema = self
+            .cache
+            .read_request(&path)
+            .await
+            .map_err(|failure| LoadError {
+                failures: vec![failure],
+            })?;
+
+        return Ok(FileObject::new(path, schema));
+    }
+
+    // Parses every request, eg: to create a runner.
+    // Requests that fail are reported with the files that couldn't be listed.
+    pub async fn load_all(&mut self) -> anyhow::Result<Vec<FileObject<RequestRootSchema>>> {
+        let mut failures = vec![];
+        let mut result = vec![];
+
+        for path in self.paths() {
+            match self.cache.read_request(&path).await {
+                Ok(schema) => result.push(FileObject::new(path, schema)),
+                Err(failure) => failures.push(failure),
+            };
+        }
+
+        self.check(failures)?;
+        return Ok(result);
+    }
+
+    // Keeps what was parsed for the next time the project is opened
+    pub async fn save_cache(&mut self) -> anyhow::Result<()> {
+        return self.cache.save().await;
+    }
+
+    fn paths(&self) -> Vec<PathBuf> {
+        return self
+            .requests
+            .iter()
+            .map(|request| request.path.clone())
+            .collect();
+    }
+
+    // Fails with every file that couldn't be listed or loaded, or skips them if the project allows it
+    fn check(&mut self, failures: Vec<LoadFailure>) -> anyhow::Result<()> {
+        let mut failures = std::mem::take(&mut self.failures)
+            .into_iter()
+            .chain(failures)
+            .collect::<Vec<LoadFailure>>();
+
+        failures.sort_by(|a, b| a.path.cmp(&b.path));
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        if !self.skip_invalid {
+            return Err(LoadError { failures }.into());
+        }
+
+        for failure in &failures {
+            tracing::warn!("Skipped {}", failure);
+        }
+
+        // skipped requests can't be loaded later either
+        let skipped = failures
+            .iter()
+            .map(|failure| failure.path.as_path())
+            .collect::<Vec<&Path>>();
+        self.requests
+            .retain(|request| !skipped.contains(&request.path.as_path()));
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn project(files: &[(&str, &str)], skip_invalid: bool) -> FileObject<ProjectRootSchema> {
+        let root = std::env::temp_dir().join(format!("dotapi-index-{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(root.join("requests"))
+            .await
+            .unwrap();
+
+        for (name, content) in files {
+            tokio::fs::write(root.join("requests").join(name), content)
+                .await
+                .unwrap();
+        }
+
+        let path = root.join(".nd-project");
+        tokio::fs::write(
+            &path,
+            format!(
+                "project:\n  name: index\nskip_invalid_requests: {}\ncalls:\n  main: []\n",
+                skip_invalid
+            ),
+        )
+        .await
+        .unwrap();
+
+        return ProjectRootSchema::load(&path).await.unwrap();
+    }
+
+    const USERS: &str =
+        "method: GET\nurl: /users\ndoc: Lists users\nconfig:\n  class: admin\n  tags: [slow]\n";
+
+    // the header reads, the rest of the request doesn't
+    const BROKEN: &str = "method: POST\nurl: /posts\nconfig:\n  retries: many\n";
+
+    #[tokio::test]
+    async fn requests_are_listed_from_their_header() {
+        let project = project(&[("users.nd", USERS), ("posts.nd", BROKEN)], false).await;
+        let index = project.index_requests().await.unwrap();
+
+        assert_eq!(
+            index.find("users"),
+            Some(&RequestSummary {
+                name: "users".to_string(),
+                path: project.get_requests_dir().join("users.nd"),
+                method: "GET".to_string(),
+                url: "/users".to_string(),
+                doc: "Lists users".to_string(),
+                class: Some("admin".to_string()),
+                tags: vec!["slow".to_string()],
+            })
+        );
+        assert_eq!(index.find("posts").unwrap().method, "POST");
+    }
+
+    #[tokio::test]
+    async fn requests_are_parsed_when_loaded() {
+        let project = project(&[("users.nd", USERS), ("posts.nd", BROKEN)], false).await;
+        let mut index = project.index_requests().await.unwrap();
+
+        let users = index.load("users").await.unwrap();
+        assert_eq!(users.object.url, "/users");
+        assert!(index.load("posts").await.is_err());
+        assert!(index.load("missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn failures_are_reported_together() {
+        let project = project(
+            &[
+                ("users.nd", USERS),
+                ("posts.nd", BROKEN),
+                ("users.yaml", USERS),
+                ("broken.nd", "method: [GET"),
+            ],
+            false,
+        )
+        .await;
+
+        let error = match project.get_requests().await {
+            Ok(_) => panic!("the requests loaded"),
+            Err(error) => error,
+        };
+        let error = error.downcast_ref::<LoadError>().unwrap();
+        let failures = error
+            .failures
+            .iter()
+            .map(|failure| (call_name(&failure.path), failure.kind))
+            .collect::<Vec<(String, LoadFailureKind)>>();
+
+        assert_eq!(
+            failures,
+            vec![
+                ("broken".to_string(), LoadFailureKind::Parse),
+                ("posts".to_string(), LoadFailureKind::Parse),
+                ("users".to_string(), LoadFailureKind::Conflict),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn skipped_requests_are_dropped_from_the_index() {
+        let project = project(
+            &[
+                ("users.nd", USERS),
+                ("posts.nd", BROKEN),
+                ("broken.nd", "method: [GET"),
+            ],
+            true,
+        )
+        .await;
+
+        let mut index = project.index_requests().await.unwrap();
+        assert_eq!(index.requests.len(), 2);
+
+        let requests = index.load_all().await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].get_call_name(), "users");
+        assert!(index.find("posts").is_none());
+    }
+
+    #[tokio::test]
+    async fn listed_requests_are_cached_until_they_change() {
+        let project = project(&[("users.nd", USERS)], false).await;
+        project.index_requests().await.unwrap();
+        assert!(project.get_root_dir().join(".nd-cache.json").exists());
+
+        // listing or loading the request again doesn't parse the file
+        let cache = SchemaCache::open(&project.get_root_dir()).await;
+        let path = project.get_requests_dir().join("users.nd");
+        assert!(cache.cached_header(&path).await.is_some());
+
+        tokio::fs::write(&path, "method: DELETE\nurl: /users\n")
+            .await
+            .unwrap();
+        let index = project.index_requests().await.unwrap();
+        assert_eq!(index.find("users").unwrap().method, "DELETE");
+    }
+
+    #[tokio::test]
+    async fn runners_load_the_requests_they_call() {
+        let project = project(
+            &[
+                (
+                    "users.nd",
+                    "method: GET\nurl: /users\nconfig:\n  require: [login]\n",
+                ),
+                ("login.nd", "method: POST\nurl: /login\n"),
+                ("posts.nd", BROKEN),
+            ],
+            false,
+        )
+        .await;
+
+        let mut runner = crate::runner::Runner::load(&project.path).await.unwrap();
+        assert!(runner.requests.is_empty());
+        assert_eq!(runner.request_names().len(), 3);
+
+        runner.load_request("users").await.unwrap();
+        let mut loaded = runner
+            .requests
+            .iter()
+            .map(|request| request.get_call_name())
+            .collect::<Vec<String>>();
+        loaded.sort();
+        assert_eq!(loaded, vec!["login", "users"]);
+        assert!(runner.load_request("posts").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn only_loaded_requests_are_cached_whole() {
+        let project = project(&[("users.nd", USERS), ("posts.nd", BROKEN)], false).await;
+        let mut index = project.index_requests().await.unwrap();
+        index.load("users").await.unwrap();
+        index.save_cache().await.unwrap();
+
+        let content = tokio::fs::read_to_string(project.get_root_dir().join(".nd-cache.json"))
+            .await
+            .unwrap();
+        let entries = serde_json::from_str::<serde_json::Value>(&content).unwrap();
+        let dir = project.get_requests_dir();
+        let entry = |name: &str| entries[dir.join(name).to_str().unwrap()].clone();
+        assert!(entry("users.nd").get("schema").is_some());
+        assert!(entry("posts.nd").get("schema").is_none());
+        assert_eq!(entry("posts.nd")["header"]["method"], "POST");
+
+        // the header of a loaded request is listed from the cache like the others
+        let index = project.index_requests().await.unwrap();
+        assert_eq!(index.find("users").unwrap().tags, vec!["slow".to_string()]);
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use serde_yaml::{Mapping, Value};
+
+use crate::functions;
+
+/// Resolved environment variables, keyed by variable name.
+pub type EnvMap = HashMap<String, Value>;
+
+/// A placeholder referencing a variable that isn't in the env.
+#[derive(Debug)]
+pub struct UnresolvedError {
+    pub name: String,
+}
+
+impl std::fmt::Display for UnresolvedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "Unresolved variable \"{}\"", self.name);
+    }
+}
+
+impl std::error::Error for UnresolvedError {}
+
+// Replaces every {{placeholder}} in the input with its value from env
+pub fn interpolate_string(input: &str, env: &EnvMap) -> anyhow::Result<String> {
+    let mut result = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+
+        let after = &rest[start + 2..];
+        let end = match after.find("}}") {
+            Some(end) => end,
+            None => anyhow::bail!("Unclosed placeholder in \"{}\"", input),
+        };
+
+        let value = resolve_placeholder(after[..end].trim(), env)?;
+        result.push_str(&value_to_string(&value));
+        rest = &after[end + 2..];
+    }
+
+    result.push_str(rest);
+    return Ok(result);
+}
+
+// Interpolates every string within a yaml value.
+// A string that is made up of a single placeholder is replaced by the raw value,
+// so lists and objects from the env keep their shape.
+pub fn interpolate_value(value: &Value, env: &EnvMap) -> anyhow::Result<Value> {
+    return Ok(match value {
+        Value::String(content) => match single_placeholder(content) {
+            Some(key) => resolve_placeholder(key, env)?,
+            None => Value::String(interpolate_string(content, env)?),
+        },
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .iter()
+                .map(|item| interpolate_value(item, env))
+                .collect::<anyhow::Result<Vec<Value>>>()?,
+        ),
+        Value::Mapping(map) => {
+            let mut result = Mapping::new();
+            for (key, item) in map {
+                result.insert(interpolate_value(key, env)?, interpolate_value(item, env)?);
+            }
+            Value::Mapping(result)
+        }
+        other => other.clone(),
+    });
+}
+
+// Returns the keys of every placeholder in the input, in order of appearance
+pub fn placeholders(input: &str) -> Vec<String> {
+    let mut result = vec![];
+    let mut rest = input;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = match after.find("}}") {
+            Some(end) => end,
+            None => break,
+        };
+
+        result.push(after[..end].trim().to_string());
+        rest = &after[end + 2..];
+    }
+
+    return result;
+}
+
+// Returns what has been typed of a placeholder left open at the end of the input,
+// eg: "{{base" returns "base"
+pub fn open_placeholder(input: &str) -> Option<&str> {
+    let start = input.rfind("{{")?;
+    let typed = &input[start + 2..];
+
+    if typed.contains("}}") {
+        return None;
+    }
+
+    return Some(typed.trim_start());
+}
+
+// Resolves the content of a placeholder, either a function call or a variable
+pub fn resolve_placeholder(key: &str, env: &EnvMap) -> anyhow::Result<Value> {
+    if functions::is_call(key) {
+        return functions::call(key, env);
+    }
+
+    return match lookup(key, env) {
+        Some(value) => Ok(value),
+        None => Err(UnresolvedError {
+            name: key.to_string(),
+        }
+        .into()),
+    };
+}
+
+// Looks up a dotted path (eg: credentials.username, items[0].price) in env.
+// Keys that don't match exactly are matched ignoring case, so headers can be written as sent (eg: X-Request-Id).
+pub fn lookup(path: &str, env: &EnvMap) -> Option<Value> {
+    // values set for a whole path, eg: by a lenient interpolation mode
+    if let Some(value) = env.get(path) {
+        return Some(value.clone());
+    }
+
+    let normalized = path.replace('[', ".").replace(']', "");
+    let mut segments = normalized.split('.').filter(|segment| !segment.is_empty());
+    let mut current = env.get(segments.next()?)?;
+
+    for segment in segments {
+        current = match current {
+            Value::Mapping(map) => match map.get(segment) {
+                Some(value) => value,
+                None => {
+                    map.iter()
+                        .find(|(key, _)| {
+                            key.as_str()
+                                .is_some_and(|key| key.eq_ignore_ascii_case(segment))
+                        })?
+                        .1
+                }
+            },
+            Value::Sequence(items) => items.get(segment.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+
+    return Some(current.clone());
+}
+
+pub fn value_to_string(value: &Value) -> String {
+    return match value {
+        Value::Null => String::new(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Tagged(tagged) => value_to_string(&tagged.value),
+        other => serde_json::to_string(other).unwrap_or_default(),
+    };
+}
+
+// Names of the requests a placeholder reads the response of, eg: login for responses.login.body.token
+pub fn referenced_responses(key: &str) -> Vec<String> {
+    let mut names = vec![];
+
+    for (start, _) in key.match_indices("responses.") {
+        let preceded = key[..start]
+            .chars()
+            .last()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if preceded {
+            continue;
+        }
+
+        let name = key[start + "responses.".len()..]
+            .chars()
+            .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+            .collect::<String>();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    return names;
+}
+
+fn single_placeholder(content: &str) -> Option<&str> {
+    let content = content.trim();
+    let inner = content.strip_prefix("{{")?.strip_suffix("}}")?;
+
+    if inner.contains("{{") || inner.contains("}}") {
+        return None;
+    }
+
+    return Some(inner.trim());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::{
+        fs::FileObject,
+        runner::Runner,
+        schema::{
+            interpolation::InterpolationModeSchema,
+            roots::{ProjectRootSchema, RequestRootSchema},
+        },
+    };
+
+    fn env() -> EnvMap {
+        return EnvMap::from([
+            (
+                "host".to_string(),
+                Value::String("https://x.dev".to_string()),
+            ),
+            (
+                "user".to_string(),
+                serde_yaml::from_str("{name: a, Roles: [admin, dev], X-Request-Id: '7'}").unwrap(),
+            ),
+        ]);
+    }
+
+    #[test]
+    fn replaces_placeholders_in_strings() {
+        assert_eq!(
+            interpolate_string("{{ host }}/users/{{user.name}}", &env()).unwrap(),
+            "https://x.dev/users/a"
+        );
+        assert!(interpolate_string("{{host", &env()).is_err());
+
+        let error = interpolate_string("{{missing}}", &env()).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<UnresolvedError>().unwrap().name,
+            "missing"
+        );
+    }
+
+    #[test]
+    fn looks_up_nested_paths() {
+        assert_eq!(lookup("user.name", &env()), Some("a".into()));
+        assert_eq!(lookup("user.Roles[1]", &env()), Some("dev".into()));
+        // keys that don't match exactly are matched ignoring case
+        assert_eq!(lookup("user.roles.0", &env()), Some("admin".into()));
+        assert_eq!(lookup("user.x-request-id", &env()), Some("7".into()));
+        assert_eq!(lookup("user.name.first", &env()), None);
+        assert_eq!(lookup("user.Roles[9]", &env()), None);
+        assert_eq!(lookup("missing.name", &env()), None);
+    }
+
+    #[test]
+    fn exact_keys_win_over_paths() {
+        // a lenient mode sets the whole unresolved path as a key, it must resolve on the next try
+        let mut env = env();
+        env.insert("user.email".to_string(), "{{user.email}}".into());
+
+        assert_eq!(lookup("user.email", &env), Some("{{user.email}}".into()));
+        assert_eq!(
+            interpolate_string("{{user.email}}", &env).unwrap(),
+            "{{user.email}}"
+        );
+    }
+
+    #[test]
+    fn single_placeholders_keep_the_shape_of_values() {
+        let value = interpolate_value(&"{{user.Roles}}".into(), &env()).unwrap();
+        assert_eq!(
+            value,
+            serde_yaml::from_str::<Value>("[admin, dev]").unwrap()
+        );
+
+        let value = interpolate_value(&"roles: {{user.Roles}}".into(), &env()).unwrap();
+        assert_eq!(
+            value,
+            Value::String("roles: [\"admin\",\"dev\"]".to_string())
+        );
+    }
+
+    #[test]
+    fn finds_placeholders_and_references() {
+        assert_eq!(placeholders("{{a}}/{{ b.c }}/{{open"), ["a", "b.c"]);
+        assert_eq!(open_placeholder("{{host}}/{{ us"), Some("us"));
+        assert_eq!(open_placeholder("{{host}}"), None);
+        assert_eq!(
+            referenced_responses("responses.login.body.token + old.responses.x"),
+            ["login"]
+        );
+    }
+
+    fn runner(mode: InterpolationModeSchema) -> Runner {
+        let project = ProjectRootSchema {
+            interpolation: mode,
+            ..Default::default()
+        };
+        let request = RequestRootSchema {
+            method: "GET".to_string(),
+            url: "https://x.dev/users/{{id}}".to_string(),
+            headers: Some(IndexMap::from([(
+                "X-Token".to_string(),
+                "{{auth.token}}".to_string(),
+            )])),
+            ..Default::default()
+        };
+
+        let root = PathBuf::from("/tmp/dotapi-interpolation");
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), project),
+            vec![FileObject::new(root.join("requests/user.nd"), request)],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn strict_mode_fails_on_unresolved_placeholders() {
+        let error = runner(InterpolationModeSchema::Strict)
+            .resolve_request("user")
+            .unwrap_err();
+        assert!(error.downcast_ref::<UnresolvedError>().is_some());
+    }
+
+    #[test]
+    fn lenient_modes_resolve_and_warn() {
+        let resolved = runner(InterpolationModeSchema::Literal)
+            .resolve_request("user")
+            .unwrap();
+        assert_eq!(resolved.url, "https://x.dev/users/%7B%7Bid%7D%7D");
+        assert_eq!(resolved.get_header("x-token").unwrap(), "{{auth.token}}");
+        assert_eq!(resolved.warnings.len(), 2);
+
+        let resolved = runner(InterpolationModeSchema::Empty)
+            .resolve_request("user")
+            .unwrap();
+        assert_eq!(resolved.url, "https://x.dev/users/");
+        assert_eq!(resolved.get_header("x-token").unwrap(), "");
+        assert_eq!(resolved.warnings.len(), 2);
+    }
+}
@@ -1,23 +1,74 @@
 use crate::schema::{
-    calls::CallSchema,
+    calls::{CallSchema, SequenceSchema},
     env::EnvironmentVariableSchema,
     project::ProjectDefinationSchema,
     roots::{ProjectRootSchema, RequestRootSchema},
 };
-use std::{
-    collections::HashMap,
-    path::{Path, PathBuf},
-};
+use indexmap::IndexMap;
+use std::path::{Path, PathBuf};
 
 pub mod schema;
 pub mod fs;
+pub mod assertions;
+pub mod auth;
+pub mod body;
+pub mod body_limit;
+pub mod bundle;
+pub mod cache;
+pub mod cancel;
+pub mod chaos;
+pub mod checksum;
+pub mod client;
+pub mod codegen;
+pub mod compression;
+pub mod context;
+pub mod contract;
+pub mod diff;
+pub mod editor;
+pub mod environment;
+pub mod error;
+pub mod export;
+pub mod expression;
+pub mod faker;
+pub mod fixtures;
+pub mod functions;
+pub mod graphql;
+pub mod groups;
+pub mod history;
+pub mod hooks;
+pub mod import;
+pub mod index;
+pub mod interpolation;
+pub mod middleware;
+pub mod mime;
+pub mod notify;
+pub mod paginate;
+pub mod preview;
+pub mod query;
+pub mod range;
+pub mod rate_limit;
+pub mod redaction;
+pub mod report;
+pub mod runner;
+pub mod schedule;
+pub mod schema_cache;
+pub mod search;
+pub mod snapshot;
+pub mod state;
+pub mod tags;
+pub mod telemetry;
+pub mod upload;
+pub mod wire;
 #[cfg(test)]
 mod tests;
 
+pub use client::Client;
+pub use error::ExecutorError;
+
 // Initializes a new project at path
 pub async fn init(name: &str, path: &Path) -> anyhow::Result<PathBuf> {
     // Create schemas
-    let mut env = HashMap::<String, EnvironmentVariableSchema>::new();
+    let mut env = IndexMap::<String, EnvironmentVariableSchema>::new();
     env.insert(
         "baseurl".to_string(),
         EnvironmentVariableSchema::new(
@@ -37,8 +88,8 @@ pub async fn init(name: &str, path: &Path) -> anyhow::Result<PathBuf> {
         },
         env,
         calls: CallSchema {
-            main: vec!["hello".to_string()],
-            overrides: HashMap::new(),
+            main: SequenceSchema::Steps(vec!["hello".to_string()]),
+            overrides: IndexMap::new(),
         },
         ..Default::default()
     };
@@ -57,11 +108,11 @@ pub async fn init(name: &str, path: &Path) -> anyhow::Result<PathBuf> {
     // create
     tokio::fs::write(
         &project_path,
-        serde_yaml::to_string(&project_schema).unwrap(),
+        serde_yaml::to_string(&project_schema)?,
     )
     .await?;
     tokio::fs::create_dir(request_folder).await?;
-    tokio::fs::write(&home_folder, serde_yaml::to_string(&hello_request).unwrap()).await?;
+    tokio::fs::write(&home_folder, serde_yaml::to_string(&hello_request)?).await?;
 
     return Ok(project_path.to_path_buf());
 }
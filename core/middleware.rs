@@ -0,0 +1,182 @@
+use crate::runner::{ResponseObject, Runner};
+
+/// Hooks into every request the runner sends, for applications embedding the runner.
+/// Middlewares run in the order they were added.
+pub trait RequestMiddleware: Send + Sync {
+    // called with the built request before it's signed and sent
+    fn before_send(&self, _name: &str, _request: &mut reqwest::Request) -> anyhow::Result<()> {
+        return Ok(());
+    }
+
+    // called with the decoded response, before captures and assertions see it
+    fn after_receive(&self, _name: &str, _response: &mut ResponseObject) -> anyhow::Result<()> {
+        return Ok(());
+    }
+}
+
+impl Runner {
+    pub fn add_middleware(&mut self, middleware: Box<dyn RequestMiddleware>) {
+        self.middlewares.push(middleware);
+    }
+
+    pub(crate) fn before_send(
+        &self,
+        name: &str,
+        request: &mut reqwest::Request,
+    ) -> anyhow::Result<()> {
+        for middleware in &self.middlewares {
+            middleware.before_send(name, request)?;
+        }
+
+        return Ok(());
+    }
+
+    pub(crate) fn after_receive(
+        &self,
+        name: &str,
+        response: &mut ResponseObject,
+    ) -> anyhow::Result<()> {
+        for middleware in &self.middlewares {
+            middleware.after_receive(name, response)?;
+        }
+
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{fs::FileObject, schema::roots::RequestRootSchema};
+
+    // Answers every request with its own head as the body
+    async fn serve_echo() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let body = String::from_utf8_lossy(&request[..read]).to_lowercase();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    fn runner(url: &str) -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-middleware-{}", uuid::Uuid::new_v4()));
+
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![FileObject::new(
+                root.join("requests/echo.nd"),
+                serde_yaml::from_str::<RequestRootSchema>(&format!(
+                    "method: GET\nurl: \"{}/\"",
+                    url
+                ))
+                .unwrap(),
+            )],
+        )
+        .unwrap();
+    }
+
+    /// Appends its label to the x-trail header, and logs the hooks it's called with.
+    struct Trail {
+        label: &'static str,
+        log: Arc<Mutex<Vec<String>>>,
+        fail: bool,
+    }
+
+    impl RequestMiddleware for Trail {
+        fn before_send(&self, name: &str, request: &mut reqwest::Request) -> anyhow::Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("before {} {}", self.label, name));
+            if self.fail {
+                anyhow::bail!("{} refused", self.label);
+            }
+
+            let trail = match request.headers().get("x-trail") {
+                Some(value) => format!("{},{}", value.to_str()?, self.label),
+                None => self.label.to_string(),
+            };
+            request.headers_mut().insert("x-trail", trail.parse()?);
+            return Ok(());
+        }
+
+        fn after_receive(&self, name: &str, response: &mut ResponseObject) -> anyhow::Result<()> {
+            self.log
+                .lock()
+                .unwrap()
+                .push(format!("after {} {}", self.label, name));
+
+            let trail = match response.headers.get("x-trail") {
+                Some(value) => format!("{},{}", value, self.label),
+                None => self.label.to_string(),
+            };
+            response.headers.insert("x-trail".to_string(), trail);
+            return Ok(());
+        }
+    }
+
+    fn trail(label: &'static str, log: &Arc<Mutex<Vec<String>>>, fail: bool) -> Box<Trail> {
+        return Box::new(Trail {
+            label,
+            log: log.clone(),
+            fail,
+        });
+    }
+
+    #[tokio::test]
+    async fn middlewares_run_in_the_order_they_were_added() {
+        let log = Arc::new(Mutex::new(vec![]));
+        let mut runner = runner(&serve_echo().await);
+        runner.add_middleware(trail("a", &log, false));
+        runner.add_middleware(trail("b", &log, false));
+
+        let result = runner.call_request("echo").await.unwrap();
+
+        assert!(
+            String::from_utf8_lossy(&result.response.body).contains("x-trail: a,b\r\n"),
+            "{}",
+            String::from_utf8_lossy(&result.response.body)
+        );
+        assert_eq!(result.response.headers["x-trail"], "a,b");
+        assert_eq!(
+            *log.lock().unwrap(),
+            vec![
+                "before a echo",
+                "before b echo",
+                "after a echo",
+                "after b echo"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn a_failing_middleware_stops_the_request() {
+        let log = Arc::new(Mutex::new(vec![]));
+        let mut runner = runner(&serve_echo().await);
+        runner.add_middleware(trail("a", &log, true));
+        runner.add_middleware(trail("b", &log, false));
+
+        let error = runner.call_request("echo").await.unwrap_err();
+
+        assert!(format!("{:#}", error).contains("a refused"), "{:#}", error);
+        assert_eq!(*log.lock().unwrap(), vec!["before a echo"]);
+    }
+}
@@ -0,0 +1,35 @@
+use std::process::Stdio;
+
+use anyhow::Context;
+
+use crate::hooks::shell;
+
+use super::{Notifier, NotifyFuture, RunSummary};
+
+/// Shows the headline of the summary as a notification of the operating system,
+/// with osascript on macos and notify-send elsewhere.
+pub struct DesktopNotifier;
+
+impl Notifier for DesktopNotifier {
+    fn notify<'a>(&'a self, summary: &'a RunSummary) -> NotifyFuture<'a> {
+        return Box::pin(async move {
+            let headline = summary.headline();
+            let command = match cfg!(target_os = "macos") {
+                true => format!(
+                    "osascript -e 'display notification \"{}\" with title \"dotapi\"'",
+                    headline.replace(['"', '\''], "")
+                ),
+                false => format!("notify-send dotapi '{}'", headline.replace('\'', "")),
+            };
+
+            let status = shell(&command)
+                .stdout(Stdio::null())
+                .status()
+                .context("Failed to show a desktop notification")?;
+            if !status.success() {
+                anyhow::bail!("\"{}\" exited with {}", command, status);
+            }
+            return Ok(());
+        });
+    }
+}
@@ -0,0 +1,357 @@
+mod desktop;
+mod slack;
+mod webhook;
+
+use std::{future::Future, pin::Pin};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    report::RunReport,
+    schema::notification::{NotificationChannelSchema, NotificationSchema},
+};
+
+pub use desktop::DesktopNotifier;
+pub use slack::SlackNotifier;
+pub use webhook::WebhookNotifier;
+
+// How many of the slowest requests a summary lists
+const SLOWEST_COUNT: usize = 5;
+
+pub type NotifyFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>;
+
+/// The outcome of a run, short enough to be posted once it's over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub name: String,
+    pub environment: Option<String>,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub duration_ms: u64,
+    pub cancelled: bool,
+    pub failures: Vec<FailedRequest>,
+    pub latency: LatencySummary,
+}
+
+/// A request that failed the run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailedRequest {
+    pub name: String,
+    pub status: Option<u16>,
+    pub error: String,
+}
+
+/// How long the requests that were sent took, skipped ones aren't counted.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub average_ms: u64,
+    pub p95_ms: u64,
+    pub max_ms: u64,
+    // the slowest requests, slowest first
+    pub slowest: Vec<(String, u64)>,
+}
+
+impl RunSummary {
+    pub fn from_report(report: &RunReport) -> RunSummary {
+        let failures = report
+            .entries
+            .iter()
+            .filter(|entry| !entry.passed())
+            .map(|entry| FailedRequest {
+                name: entry.name.clone(),
+                status: entry.status,
+                error: entry.error.clone().unwrap_or_default(),
+            })
+            .collect::<Vec<FailedRequest>>();
+
+        return RunSummary {
+            name: report.name.clone(),
+            environment: report.environment.clone(),
+            passed: report.entries.len() - report.failures() - report.skipped(),
+            failed: failures.len(),
+            skipped: report.skipped(),
+            duration_ms: report.duration_ms,
+            cancelled: report.cancelled,
+            failures,
+            latency: LatencySummary::from_report(report),
+        };
+    }
+
+    // A run that failed before it produced a report, eg: the project didn't load
+    pub fn from_error(name: &str, environment: Option<String>, error: String) -> RunSummary {
+        return RunSummary {
+            name: name.to_string(),
+            environment,
+            passed: 0,
+            failed: 1,
+            skipped: 0,
+            duration_ms: 0,
+            cancelled: false,
+            failures: vec![FailedRequest {
+                name: name.to_string(),
+                status: None,
+                error,
+            }],
+            latency: LatencySummary::default(),
+        };
+    }
+
+    pub fn succeeded(&self) -> bool {
+        return self.failed == 0 && !self.cancelled;
+    }
+
+    // A single line, eg: "main (staging): 2 of 10 requests failed in 1530ms"
+    pub fn headline(&self) -> String {
+        let name = match &self.environment {
+            Some(environment) => format!("{} ({})", self.name, environment),
+            None => self.name.clone(),
+        };
+        let total = self.passed + self.failed;
+
+        let outcome = if self.cancelled {
+            format!("cancelled after {} requests", total)
+        } else if self.failed > 0 {
+            format!("{} of {} requests failed", self.failed, total)
+        } else {
+            format!("all {} requests passed", total)
+        };
+
+        return format!("{}: {} in {}ms", name, outcome, self.duration_ms);
+    }
+}
+
+impl LatencySummary {
+    fn from_report(report: &RunReport) -> LatencySummary {
+        let mut durations = report
+            .entries
+            .iter()
+            .filter(|entry| !entry.skipped)
+            .map(|entry| (entry.name.clone(), entry.duration_ms))
+            .collect::<Vec<(String, u64)>>();
+        if durations.is_empty() {
+            return LatencySummary::default();
+        }
+        durations.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+
+        let total = durations.iter().map(|(_, duration)| duration).sum::<u64>();
+        // durations are sorted slowest first, 5% of them are slower than the p95
+        let p95 = durations[(durations.len() - 1) * 5 / 100].1;
+
+        return LatencySummary {
+            average_ms: total / durations.len() as u64,
+            p95_ms: p95,
+            max_ms: durations[0].1,
+            slowest: durations.into_iter().take(SLOWEST_COUNT).collect(),
+        };
+    }
+}
+
+/// Sends the summary of a run somewhere, once the run is over.
+pub trait Notifier: Send + Sync {
+    fn notify<'a>(&'a self, summary: &'a RunSummary) -> NotifyFuture<'a>;
+}
+
+// Creates a notifier from its definition in the project file
+pub fn create_notifier(schema: &NotificationChannelSchema) -> Box<dyn Notifier> {
+    return match schema {
+        NotificationChannelSchema::Webhook { url } => Box::new(WebhookNotifier::new(url.clone())),
+        NotificationChannelSchema::Slack { url } => Box::new(SlackNotifier::new(url.clone())),
+        NotificationChannelSchema::Desktop => Box::new(DesktopNotifier),
+    };
+}
+
+// Sends the summary of the report to each notification that wants it.
+// A notification that can't be sent is logged, it doesn't fail the run.
+pub async fn send_notifications(notifications: &[NotificationSchema], report: &RunReport) {
+    send_summary(notifications, &RunSummary::from_report(report)).await;
+}
+
+// Sends a summary to each notification that wants it, see send_notifications
+pub async fn send_summary(notifications: &[NotificationSchema], summary: &RunSummary) {
+    for notification in notifications {
+        if summary.succeeded() && !notification.always {
+            continue;
+        }
+
+        let notifier = create_notifier(&notification.channel);
+        if let Err(e) = notifier.notify(summary).await {
+            tracing::warn!("Could not send the summary of {}: {:#}", summary.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::report::ReportEntry;
+
+    fn entry(
+        name: &str,
+        status: Option<u16>,
+        duration_ms: u64,
+        error: Option<&str>,
+    ) -> ReportEntry {
+        return ReportEntry {
+            name: name.to_string(),
+            method: "GET".to_string(),
+            url: format!("https://x.dev/{}", name),
+            status,
+            duration_ms,
+            error: error.map(|e| e.to_string()),
+            skipped: false,
+            allow_failure: false,
+            assertion_failures: vec![],
+            warnings: vec![],
+        };
+    }
+
+    pub(super) fn report() -> RunReport {
+        return RunReport {
+            name: "main".to_string(),
+            environment: Some("staging".to_string()),
+            entries: vec![
+                entry("login", Some(200), 120, None),
+                entry(
+                    "users",
+                    Some(500),
+                    900,
+                    Some("`response.status == 200` is false"),
+                ),
+                ReportEntry {
+                    skipped: true,
+                    ..entry("cleanup", None, 0, None)
+                },
+                ReportEntry {
+                    allow_failure: true,
+                    ..entry("flaky", None, 40, Some("timed out"))
+                },
+            ],
+            duration_ms: 1530,
+            attempts: 1,
+            cancelled: false,
+        };
+    }
+
+    #[test]
+    fn the_summary_counts_failures_and_latency() {
+        let summary = RunSummary::from_report(&report());
+
+        assert_eq!(
+            summary.headline(),
+            "main (staging): 1 of 3 requests failed in 1530ms"
+        );
+        assert!(!summary.succeeded());
+
+        let cancelled = RunSummary {
+            cancelled: true,
+            failed: 0,
+            ..summary.clone()
+        };
+        assert_eq!(
+            cancelled.headline(),
+            "main (staging): cancelled after 2 requests in 1530ms"
+        );
+        assert!(!cancelled.succeeded());
+    }
+
+    // Answers every request with 200 and sends the body it received
+    async fn serve(bodies: tokio::sync::mpsc::UnboundedSender<String>) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut received = vec![];
+                let mut buffer = [0; 4096];
+                // reads until the whole body given by content-length is in
+                let body = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    received.extend_from_slice(&buffer[..read]);
+                    let text = String::from_utf8_lossy(&received).to_string();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                line.to_lowercase()
+                                    .strip_prefix("content-length: ")
+                                    .map(str::to_string)
+                            })
+                            .and_then(|length| length.trim().parse::<usize>().ok())
+                            .unwrap_or_default();
+                        if body.len() >= length || read == 0 {
+                            break body.to_string();
+                        }
+                    }
+                };
+
+                bodies.send(body).unwrap();
+                let response = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    #[tokio::test]
+    async fn webhooks_receive_the_summary_as_json() {
+        let (sender, mut bodies) = tokio::sync::mpsc::unbounded_channel();
+        let url = serve(sender).await;
+
+        WebhookNotifier::new(url)
+            .notify(&RunSummary::from_report(&report()))
+            .await
+            .unwrap();
+
+        let body = serde_json::from_str::<serde_json::Value>(&bodies.recv().await.unwrap());
+        assert_eq!(
+            body.unwrap(),
+            json!({
+                "name": "main",
+                "environment": "staging",
+                "passed": 2,
+                "failed": 1,
+                "skipped": 1,
+                "duration_ms": 1530,
+                "cancelled": false,
+                "failures": [
+                    { "name": "users", "status": 500, "error": "`response.status == 200` is false" }
+                ],
+                "latency": {
+                    "average_ms": 353,
+                    "p95_ms": 900,
+                    "max_ms": 900,
+                    "slowest": [["users", 900], ["login", 120], ["flaky", 40]]
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn only_failed_runs_are_sent_unless_always_is_set() {
+        let (sender, mut bodies) = tokio::sync::mpsc::unbounded_channel();
+        let url = serve(sender).await;
+        let notifications = serde_yaml::from_str::<Vec<NotificationSchema>>(&format!(
+            "- {{type: webhook, url: \"{}/failed\"}}\n- {{type: webhook, url: \"{}/always\", always: true}}",
+            url, url
+        ))
+        .unwrap();
+        let mut passed = report();
+        passed.entries.truncate(1);
+
+        send_notifications(&notifications, &passed).await;
+        send_notifications(&notifications, &report()).await;
+
+        let failed = (0..3)
+            .map(|_| bodies.try_recv().unwrap())
+            .map(|body| serde_json::from_str::<RunSummary>(&body).unwrap().failed)
+            .collect::<Vec<usize>>();
+        assert_eq!(failed, vec![0, 1, 1]);
+        assert!(bodies.try_recv().is_err());
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::Context;
+use serde_json::json;
+
+use super::{Notifier, NotifyFuture, RunSummary};
+
+// Errors are cut to this many characters so the message stays readable
+const MAX_ERROR_LENGTH: usize = 200;
+
+/// Posts the summary as a message to a slack incoming webhook.
+pub struct SlackNotifier {
+    url: String,
+}
+
+impl SlackNotifier {
+    pub fn new(url: String) -> SlackNotifier {
+        return SlackNotifier { url };
+    }
+}
+
+impl Notifier for SlackNotifier {
+    fn notify<'a>(&'a self, summary: &'a RunSummary) -> NotifyFuture<'a> {
+        return Box::pin(async move {
+            reqwest::Client::new()
+                .post(&self.url)
+                .json(&json!({ "text": message(summary) }))
+                .send()
+                .await?
+                .error_for_status()
+                .with_context(|| format!("Slack refused the summary of {}", summary.name))?;
+            return Ok(());
+        });
+    }
+}
+
+// The summary in slack's mrkdwn
+fn message(summary: &RunSummary) -> String {
+    let icon = match summary.succeeded() {
+        true => ":white_check_mark:",
+        false => ":x:",
+    };
+    let mut lines = vec![format!("{} *{}*", icon, summary.headline())];
+
+    if summary.skipped > 0 {
+        lines.push(format!("{} skipped", summary.skipped));
+    }
+
+    let latency = &summary.latency;
+    if !latency.slowest.is_empty() {
+        lines.push(format!(
+            "Latency: avg {}ms, p95 {}ms, max {}ms",
+            latency.average_ms, latency.p95_ms, latency.max_ms
+        ));
+    }
+
+    if !summary.failures.is_empty() {
+        lines.push("*Failed*".to_string());
+        for failure in &summary.failures {
+            let status = match failure.status {
+                Some(status) => format!(" ({})", status),
+                None => String::new(),
+            };
+            let error = failure
+                .error
+                .chars()
+                .take(MAX_ERROR_LENGTH)
+                .collect::<String>();
+            lines.push(format!("• `{}`{}: {}", failure.name, status, error));
+        }
+    }
+
+    return lines.join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notify::tests::report;
+
+    #[test]
+    fn the_message_lists_latency_and_failures() {
+        assert_eq!(
+            message(&RunSummary::from_report(&report())),
+            ":x: *main (staging): 1 of 3 requests failed in 1530ms*
+1 skipped
+Latency: avg 353ms, p95 900ms, max 900ms
+*Failed*
+• `users` (500): `response.status == 200` is false"
+        );
+    }
+
+    #[test]
+    fn a_passed_run_is_a_single_line_and_long_errors_are_cut() {
+        let mut passed = report();
+        passed.entries.truncate(1);
+        passed.environment = None;
+        assert_eq!(
+            message(&RunSummary::from_report(&passed)),
+            ":white_check_mark: *main: all 1 requests passed in 1530ms*
+Latency: avg 120ms, p95 120ms, max 120ms"
+        );
+
+        let summary = RunSummary::from_error("main", None, "x".repeat(500));
+        assert_eq!(
+            message(&summary),
+            format!(
+                ":x: *main: 1 of 1 requests failed in 0ms*\n*Failed*\n• `main`: {}",
+                "x".repeat(MAX_ERROR_LENGTH)
+            )
+        );
+    }
+}
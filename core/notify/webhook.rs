@@ -0,0 +1,29 @@
+use anyhow::Context;
+
+use super::{Notifier, NotifyFuture, RunSummary};
+
+/// Posts the summary as json to a url.
+pub struct WebhookNotifier {
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> WebhookNotifier {
+        return WebhookNotifier { url };
+    }
+}
+
+impl Notifier for WebhookNotifier {
+    fn notify<'a>(&'a self, summary: &'a RunSummary) -> NotifyFuture<'a> {
+        return Box::pin(async move {
+            reqwest::Client::new()
+                .post(&self.url)
+                .json(summary)
+                .send()
+                .await?
+                .error_for_status()
+                .with_context(|| format!("Webhook {} refused the summary", self.url))?;
+            return Ok(());
+        });
+    }
+}
@@ -0,0 +1,344 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use serde_yaml::Value;
+
+use crate::{
+    expression::evaluate,
+    query::json_path,
+    runner::{ResolvedRequest, ResponseObject, Runner},
+    schema::paginate::PaginateSchema,
+};
+
+/// The pages followed for a paginated request and the items gathered from all of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pages {
+    pub count: u32,
+    pub items: Vec<Value>,
+}
+
+impl Runner {
+    // Calls the pages following the first response, the last page is returned with the items of every page
+    pub(crate) async fn follow_pages(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        paginate: &PaginateSchema,
+        first: ResponseObject,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        let strategies = [
+            paginate.next.is_some(),
+            paginate.link_header,
+            paginate.page_param.is_some(),
+        ];
+        if strategies.iter().filter(|set| **set).count() != 1 {
+            anyhow::bail!(
+                "Request \"{}\" has to paginate with exactly one of next, link_header or page_param",
+                name
+            );
+        }
+
+        let mut request = resolved.clone();
+        let mut response = first;
+        let mut page = page_items(paginate, &response)?;
+        let mut items = page.clone();
+        let mut count = 1;
+        let mut duration = Duration::ZERO;
+
+        while count < paginate.max_pages && !self.last_page(paginate, &response)? {
+            let url = match next_page_url(paginate, &request.url, &response, page.is_empty())? {
+                Some(url) => url,
+                None => break,
+            };
+
+            tracing::info!(
+                "{} following page {}: {}",
+                name,
+                count + 1,
+                self.redact_text(&url)
+            );
+            request.url = url;
+            let elapsed;
+            (response, elapsed) = self.execute_with_retries(name, &request).await?;
+            duration += elapsed;
+
+            page = page_items(paginate, &response)?;
+            items.extend(page.clone());
+            count += 1;
+        }
+
+        response.pages = Some(Pages { count, items });
+        return Ok((response, duration));
+    }
+
+    // Evaluates the until condition with the latest page available as `response`
+    fn last_page(
+        &self,
+        paginate: &PaginateSchema,
+        response: &ResponseObject,
+    ) -> anyhow::Result<bool> {
+        let until = match &paginate.until {
+            Some(until) => until,
+            None => return Ok(false),
+        };
+
+        let mut context = self.build_context();
+        context.insert("response".to_string(), response.to_value());
+
+        return evaluate(until, &context)
+            .with_context(|| format!("Invalid pagination condition `{}`", until));
+    }
+}
+
+// The items of a page, a path matching a single list gives the items of the list
+fn page_items(paginate: &PaginateSchema, response: &ResponseObject) -> anyhow::Result<Vec<Value>> {
+    let mut items = json_path(&response.to_value()["body"], &paginate.items)?;
+
+    if let [Value::Sequence(list)] = items.as_slice() {
+        items = list.clone();
+    }
+
+    return Ok(items);
+}
+
+// The url of the page after the response, None once there are no more pages
+fn next_page_url(
+    paginate: &PaginateSchema,
+    url: &str,
+    response: &ResponseObject,
+    empty: bool,
+) -> anyhow::Result<Option<String>> {
+    let current = reqwest::Url::parse(url).with_context(|| format!("Invalid url {}", url))?;
+
+    let next = if let Some(path) = &paginate.next {
+        json_path(&response.to_value()["body"], path)?
+            .into_iter()
+            .find_map(|value| value.as_str().map(str::to_string))
+            .filter(|next| !next.is_empty())
+    } else if paginate.link_header {
+        response
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("link"))
+            .and_then(|(_, value)| next_link(value))
+    } else if let Some(param) = &paginate.page_param {
+        // a page without items is past the last one
+        if empty {
+            return Ok(None);
+        }
+        return Ok(Some(next_page_number(&current, param).to_string()));
+    } else {
+        None
+    };
+
+    // next links may be relative to the current page
+    return match next {
+        Some(next) => Ok(Some(current.join(&next)?.to_string())),
+        None => Ok(None),
+    };
+}
+
+// The url with the page parameter incremented, the first page being 1 when the url doesn't set it
+fn next_page_number(url: &reqwest::Url, param: &str) -> reqwest::Url {
+    let page = url
+        .query_pairs()
+        .find(|(key, _)| key == param)
+        .and_then(|(_, value)| value.parse::<u64>().ok())
+        .unwrap_or(1);
+
+    let pairs = url
+        .query_pairs()
+        .filter(|(key, _)| key != param)
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect::<Vec<_>>();
+
+    let mut next = url.clone();
+    next.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(param, &(page + 1).to_string());
+    return next;
+}
+
+// The rel="next" url of a Link header, eg: <https://api.example.com/items?page=2>; rel="next"
+fn next_link(header: &str) -> Option<String> {
+    return header.split(',').find_map(|link| {
+        let mut parts = link.split(';');
+        let url = parts.next()?.trim();
+        let is_next = parts.any(|param| {
+            let param = param.trim().replace(' ', "");
+            param.eq_ignore_ascii_case("rel=\"next\"") || param.eq_ignore_ascii_case("rel=next")
+        });
+
+        return match is_next {
+            true => Some(
+                url.trim_start_matches('<')
+                    .trim_end_matches('>')
+                    .to_string(),
+            ),
+            false => None,
+        };
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    // Serves the (headers, body) given for the path and query of each request
+    async fn serve(respond: fn(&str) -> (String, String)) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let read = stream.read(&mut request).await.unwrap();
+                let head = String::from_utf8_lossy(&request[..read]).to_string();
+                let target = head.split(' ').nth(1).unwrap_or_default().to_string();
+
+                let (headers, body) = respond(&target);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}",
+                    headers,
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    // Calls a request to url paginated with the yaml of its paginate config
+    async fn call_pages(url: &str, paginate: &str) -> Pages {
+        let root = std::env::temp_dir().join(format!("dotapi-paginate-{}", uuid::Uuid::new_v4()));
+        let request = serde_yaml::from_str::<crate::schema::roots::RequestRootSchema>(&format!(
+            "method: GET\nurl: {}\nconfig: {{paginate: {}}}",
+            url, paginate
+        ))
+        .unwrap();
+        let mut runner = Runner::new(
+            crate::fs::FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![crate::fs::FileObject::new(
+                root.join("requests/items.nd"),
+                request,
+            )],
+        )
+        .unwrap();
+
+        let result = runner.call_request("items").await.unwrap();
+        return result.response.pages.unwrap();
+    }
+
+    fn numbers(items: &[Value]) -> Vec<u64> {
+        return items.iter().filter_map(Value::as_u64).collect();
+    }
+
+    #[tokio::test]
+    async fn link_headers_are_followed_until_there_is_no_next() {
+        let url = serve(|target| match target {
+            "/items" => (
+                "link: </items?page=2>; rel=\"next\", </items>; rel=\"first\"\r\n".to_string(),
+                "[1, 2]".to_string(),
+            ),
+            _ => (
+                "link: </items>; rel=\"prev\"\r\n".to_string(),
+                "[3]".to_string(),
+            ),
+        })
+        .await;
+
+        let pages = call_pages(&format!("{}/items", url), "{items: $, link_header: true}").await;
+        assert_eq!(pages.count, 2);
+        assert_eq!(numbers(&pages.items), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn cursors_are_followed_until_they_are_empty() {
+        let url = serve(|target| {
+            let body = match target {
+                "/items" => "{\"data\": [1], \"next\": \"items?cursor=b\"}",
+                "/items?cursor=b" => "{\"data\": [2], \"next\": \"/items?cursor=c\"}",
+                _ => "{\"data\": [3], \"next\": \"\"}",
+            };
+            return (String::new(), body.to_string());
+        })
+        .await;
+
+        let pages = call_pages(&format!("{}/items", url), "{items: $.data, next: $.next}").await;
+        assert_eq!(pages.count, 3);
+        assert_eq!(numbers(&pages.items), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn page_numbers_stop_at_an_empty_page() {
+        let url = serve(|target| {
+            let body = match target {
+                "/items?size=2" => "[1, 2]",
+                "/items?size=2&page=2" => "[3]",
+                _ => "[]",
+            };
+            return (String::new(), body.to_string());
+        })
+        .await;
+
+        let pages = call_pages(
+            &format!("{}/items?size=2", url),
+            "{items: $, page_param: page}",
+        )
+        .await;
+        assert_eq!(pages.count, 3);
+        assert_eq!(numbers(&pages.items), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn max_pages_and_until_stop_early() {
+        // every page has a next one
+        let url = serve(|_| {
+            (
+                String::new(),
+                "{\"data\": [1], \"last\": false}".to_string(),
+            )
+        })
+        .await;
+
+        let pages = call_pages(&url, "{items: $.data, page_param: page, max_pages: 3}").await;
+        assert_eq!(pages.count, 3);
+        assert_eq!(numbers(&pages.items), vec![1, 1, 1]);
+
+        let url = serve(|target| {
+            let last = target.contains("page=2");
+            return (
+                String::new(),
+                format!("{{\"data\": [1], \"last\": {}}}", last),
+            );
+        })
+        .await;
+        let pages = call_pages(
+            &url,
+            "{items: $.data, page_param: page, until: \"response.body.last == true\"}",
+        )
+        .await;
+        assert_eq!(pages.count, 2);
+    }
+
+    #[test]
+    fn next_links_and_page_numbers() {
+        assert_eq!(
+            next_link("<https://x.dev/a?page=2>; rel=next"),
+            Some("https://x.dev/a?page=2".to_string())
+        );
+        assert_eq!(next_link("<https://x.dev/a>; rel=\"prev\""), None);
+
+        let url = reqwest::Url::parse("https://x.dev/a?page=4&size=2").unwrap();
+        assert_eq!(
+            next_page_number(&url, "page").as_str(),
+            "https://x.dev/a?size=2&page=5"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use crate::runner::Runner;
+
+/// A request as it would be sent, with sensitive values redacted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestPreview {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+}
+
+impl std::fmt::Display for RequestPreview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method, self.url)?;
+        for (key, value) in &self.headers {
+            write!(f, "\n{}: {}", key, value)?;
+        }
+
+        if let Some(body) = &self.body {
+            write!(f, "\n\n{}", body)?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl Runner {
+    // Builds a request the way it would be sent, without sending it
+    pub async fn preview_request(&mut self, name: &str) -> anyhow::Result<RequestPreview> {
+        self.load_request(name).await?;
+        let resolved = self.resolve_request(name)?;
+        let request = self.build_request(&resolved).await?;
+
+        let env = self.build_env();
+        let redaction = &self.project.object.redact;
+
+        let headers = request
+            .headers()
+            .iter()
+            .map(|(key, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes());
+                (
+                    key.as_str().to_string(),
+                    redaction.redact_values(&redaction.redact_header(key.as_str(), &value), &env),
+                )
+            })
+            .collect();
+
+        // streamed bodies (multipart) are only read when sending
+        let body = request.body().map(|body| match body.as_bytes() {
+            Some(bytes) => redaction.redact_body(&String::from_utf8_lossy(bytes), &env),
+            None => "<streamed body>".to_string(),
+        });
+
+        return Ok(RequestPreview {
+            name: name.to_string(),
+            method: request.method().to_string(),
+            url: redaction.redact_values(request.url().as_str(), &env),
+            headers,
+            body,
+        });
+    }
+
+    // Previews every request a sequence would call, in order, with the requests they require.
+    // Requests that can't be built (eg: a variable captured by an earlier response) keep their error.
+    pub async fn preview_sequence(
+        &mut self,
+        name: &str,
+    ) -> anyhow::Result<Vec<(String, anyhow::Result<RequestPreview>)>> {
+        let mut order = Vec::<String>::new();
+        for step in self.get_sequence_definition(name)?.steps {
+            self.load_request(step.name()).await?;
+            if !self.is_selected(&step) {
+                continue;
+            }
+
+            for request in self.resolve_call_order(step.name())? {
+                if !order.contains(&request) {
+                    order.push(request);
+                }
+            }
+        }
+
+        let mut previews = vec![];
+        for request in order {
+            let preview = self.preview_request(&request).await;
+            previews.push((request, preview));
+        }
+
+        return Ok(previews);
+    }
+}
@@ -0,0 +1,220 @@
+use serde_json_path::JsonPath;
+use serde_yaml::Value;
+
+use crate::interpolation::value_to_string;
+
+// Queries a value with a JSONPath expression (RFC 9535), eg: $.data.items[0].id
+pub fn json_path(value: &Value, path: &str) -> anyhow::Result<Vec<Value>> {
+    let query = match JsonPath::parse(path) {
+        Ok(query) => query,
+        Err(e) => anyhow::bail!("Invalid JSONPath `{}`: {}", path, e),
+    };
+
+    let document = serde_json::to_value(value)?;
+
+    return query
+        .query(&document)
+        .all()
+        .into_iter()
+        .map(|item| Ok(serde_yaml::to_value(item)?))
+        .collect();
+}
+
+// The first value matched by a JSONPath expression, or None when nothing matched
+pub fn json_path_first(value: &Value, path: &str) -> anyhow::Result<Option<Value>> {
+    return Ok(json_path(value, path)?.into_iter().next());
+}
+
+// Text of the nodes matched by an XPath expression in an xml document, eg: /order/items/item/@id
+pub fn xpath(value: &Value, expression: &str) -> anyhow::Result<Vec<Value>> {
+    let content = value_to_string(value);
+    let package = match sxd_document::parser::parse(&content) {
+        Ok(package) => package,
+        Err(e) => anyhow::bail!("Invalid xml: {}", e),
+    };
+    let document = package.as_document();
+
+    let query = match sxd_xpath::Factory::new().build(expression) {
+        Ok(Some(query)) => query,
+        Ok(None) => anyhow::bail!("XPath is empty"),
+        Err(e) => anyhow::bail!("Invalid XPath `{}`: {}", expression, e),
+    };
+
+    // prefixes declared on the root element are usable in the expression, eg: /soap:Envelope/soap:Body
+    let mut context = sxd_xpath::Context::new();
+    if let Some(root) = document
+        .root()
+        .children()
+        .iter()
+        .find_map(|child| child.element())
+    {
+        for namespace in root.namespaces_in_scope() {
+            context.set_namespace(namespace.prefix(), namespace.uri());
+        }
+    }
+
+    let result = query
+        .evaluate(&context, document.root())
+        .map_err(|e| anyhow::anyhow!("Failed to evaluate XPath `{}`: {}", expression, e))?;
+
+    return Ok(match result {
+        sxd_xpath::Value::Nodeset(nodes) => nodes
+            .document_order()
+            .iter()
+            .map(|node| Value::String(node.string_value()))
+            .collect(),
+        sxd_xpath::Value::String(text) => vec![Value::String(text)],
+        sxd_xpath::Value::Number(number) => vec![Value::Number(number.into())],
+        sxd_xpath::Value::Boolean(boolean) => vec![Value::Bool(boolean)],
+    });
+}
+
+// Text of the elements matched by a CSS selector in an html document, eg: ul.items > li
+pub fn css(value: &Value, selector: &str) -> anyhow::Result<Vec<Value>> {
+    let parsed = match scraper::Selector::parse(selector) {
+        Ok(parsed) => parsed,
+        Err(e) => anyhow::bail!("Invalid CSS selector `{}`: {}", selector, e),
+    };
+
+    let document = scraper::Html::parse_document(&value_to_string(value));
+
+    return Ok(document
+        .select(&parsed)
+        .map(|element| Value::String(element.text().collect::<String>().trim().to_string()))
+        .collect());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn yaml(content: &str) -> Value {
+        return serde_yaml::from_str(content).unwrap();
+    }
+
+    fn strings(values: &[&str]) -> Vec<Value> {
+        return values
+            .iter()
+            .map(|v| Value::String(v.to_string()))
+            .collect();
+    }
+
+    #[test]
+    fn json_path_matches_every_node() {
+        let value = yaml("data: {items: [{id: 1, tags: [a]}, {id: 2, tags: [b, c]}]}");
+
+        assert_eq!(
+            json_path(&value, "$.data.items[*].id").unwrap(),
+            vec![Value::from(1), Value::from(2)]
+        );
+        assert_eq!(
+            json_path(&value, "$..tags[*]").unwrap(),
+            strings(&["a", "b", "c"])
+        );
+        assert_eq!(
+            json_path(&value, "$.data.items[?@.id > 1].tags[0]").unwrap(),
+            strings(&["b"])
+        );
+        assert_eq!(
+            json_path_first(&value, "$.data.items[1].id").unwrap(),
+            Some(Value::from(2))
+        );
+    }
+
+    #[test]
+    fn json_path_missing_paths_match_nothing() {
+        let value = yaml("data: {items: []}");
+
+        assert!(json_path(&value, "$.data.users[*]").unwrap().is_empty());
+        assert_eq!(json_path_first(&value, "$.data.items[0]").unwrap(), None);
+    }
+
+    #[test]
+    fn json_path_malformed_expressions_fail() {
+        let error = json_path(&yaml("a: 1"), "$.a[").unwrap_err();
+        assert!(
+            error.to_string().starts_with("Invalid JSONPath `$.a[`"),
+            "{}",
+            error
+        );
+        assert!(json_path(&yaml("a: 1"), "a").is_err());
+    }
+
+    const ORDER: &str = r#"<?xml version="1.0"?>
+<order id="7"><items><item id="a">Tea</item><item id="b">Milk</item></items></order>"#;
+
+    #[test]
+    fn xpath_returns_the_text_of_nodes_and_values() {
+        let value = Value::String(ORDER.to_string());
+
+        assert_eq!(
+            xpath(&value, "/order/items/item").unwrap(),
+            strings(&["Tea", "Milk"])
+        );
+        assert_eq!(
+            xpath(&value, "/order/items/item/@id").unwrap(),
+            strings(&["a", "b"])
+        );
+        assert_eq!(
+            xpath(&value, "count(//item)").unwrap(),
+            vec![Value::from(2.0)]
+        );
+        assert_eq!(
+            xpath(&value, "/order/@id = '7'").unwrap(),
+            vec![Value::Bool(true)]
+        );
+    }
+
+    #[test]
+    fn xpath_uses_the_prefixes_of_the_root_element() {
+        let value = Value::String(
+            r#"<soap:Envelope xmlns:soap="http://schemas.xmlsoap.org/soap/envelope/"><soap:Body><ok>yes</ok></soap:Body></soap:Envelope>"#
+                .to_string(),
+        );
+
+        assert_eq!(
+            xpath(&value, "/soap:Envelope/soap:Body/ok").unwrap(),
+            strings(&["yes"])
+        );
+    }
+
+    #[test]
+    fn xpath_missing_paths_and_malformed_input() {
+        let value = Value::String(ORDER.to_string());
+
+        assert!(xpath(&value, "/order/customer").unwrap().is_empty());
+        assert!(
+            xpath(&value, "/order/[")
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid XPath `/order/[`")
+        );
+        assert!(xpath(&value, "").is_err());
+        assert!(
+            xpath(&Value::String("<order>".to_string()), "/order")
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid xml")
+        );
+    }
+
+    #[test]
+    fn css_returns_the_trimmed_text_of_elements() {
+        let value = Value::String(
+            "<html><body><ul class=\"items\"><li> Tea </li><li>Milk <b>2%</b></li></ul><p>Tea</p></body></html>"
+                .to_string(),
+        );
+
+        assert_eq!(
+            css(&value, "ul.items > li").unwrap(),
+            strings(&["Tea", "Milk 2%"])
+        );
+        assert!(css(&value, "ol li").unwrap().is_empty());
+        assert!(
+            css(&value, "ul >")
+                .unwrap_err()
+                .to_string()
+                .starts_with("Invalid CSS selector `ul >`")
+        );
+    }
+}
@@ -0,0 +1,497 @@
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::{
+    runner::{ResolvedRequest, ResponseObject, Runner, sha256},
+    schema::range::DownloadSchema,
+};
+
+/// The Content-Range of a partial response, eg: bytes 0-499/1234.
+/// The range is missing for an unsatisfied range (bytes */1234), the size when it's unknown (bytes 0-499/*).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentRange {
+    pub start: Option<u64>,
+    pub end: Option<u64>,
+    pub size: Option<u64>,
+}
+
+impl ContentRange {
+    pub fn to_value(&self) -> serde_yaml::Value {
+        let mut value = serde_yaml::Mapping::new();
+        value.insert(
+            "start".into(),
+            self.start.map(Into::into).unwrap_or_default(),
+        );
+        value.insert("end".into(), self.end.map(Into::into).unwrap_or_default());
+        value.insert("size".into(), self.size.map(Into::into).unwrap_or_default());
+        value.insert(
+            "length".into(),
+            match (self.start, self.end) {
+                (Some(start), Some(end)) => {
+                    match end.checked_add(1).and_then(|end| end.checked_sub(start)) {
+                        Some(length) => length.into(),
+                        None => serde_yaml::Value::Null,
+                    }
+                }
+                _ => serde_yaml::Value::Null,
+            },
+        );
+        return serde_yaml::Value::Mapping(value);
+    }
+}
+
+/// A request whose body was downloaded in ranges and reassembled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Download {
+    pub size: u64,
+    pub parts: u32,
+    // the reassembled file was compared with the whole file
+    pub verified: bool,
+}
+
+impl Download {
+    pub fn to_value(&self) -> serde_yaml::Value {
+        let mut value = serde_yaml::Mapping::new();
+        value.insert("size".into(), self.size.into());
+        value.insert("parts".into(), self.parts.into());
+        value.insert("verified".into(), self.verified.into());
+        return serde_yaml::Value::Mapping(value);
+    }
+}
+
+// The Range header asking for the ranges, eg: ["0-499", "-500"] -> bytes=0-499,-500
+pub fn range_header(ranges: &[String]) -> anyhow::Result<String> {
+    let mut specs = vec![];
+
+    for range in ranges {
+        let (start, end) = range
+            .trim()
+            .split_once('-')
+            .with_context(|| format!("Invalid byte range \"{}\"", range))?;
+        let (start, end) = (start.trim(), end.trim());
+
+        let valid = match (start.parse::<u64>(), end.parse::<u64>()) {
+            (Ok(start), Ok(end)) => start <= end,
+            (Ok(_), Err(_)) => end.is_empty(),
+            (Err(_), Ok(_)) => start.is_empty(),
+            _ => false,
+        };
+        if !valid {
+            anyhow::bail!(
+                "Invalid byte range \"{}\", expected start-end, start- or -suffix",
+                range
+            );
+        }
+
+        specs.push(format!("{}-{}", start, end));
+    }
+
+    if specs.is_empty() {
+        anyhow::bail!("The range has to list at least one byte range");
+    }
+
+    return Ok(format!("bytes={}", specs.join(",")));
+}
+
+pub fn parse_content_range(value: &str) -> Option<ContentRange> {
+    let (range, size) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+
+    let size = match size.trim() {
+        "*" => None,
+        size => Some(size.parse().ok()?),
+    };
+    let (start, end) = match range.trim() {
+        "*" => (None, None),
+        range => {
+            let (start, end) = range.split_once('-')?;
+            let (start, end) = (start.parse::<u64>().ok()?, end.parse::<u64>().ok()?);
+            // eg: bytes 5-3/10 and bytes 0-10/10 are not ranges
+            if end < start || size.is_some_and(|size| end >= size) {
+                return None;
+            }
+            (Some(start), Some(end))
+        }
+    };
+
+    return Some(ContentRange { start, end, size });
+}
+
+// The parts of a multipart/byteranges body, each with its content type, content range, size and sha256.
+// The content of a part is its body when it's text, parts are split on bytes so binary ones aren't altered.
+pub fn byteranges(content_type: &str, body: &[u8]) -> Option<Vec<serde_yaml::Value>> {
+    let mut params = content_type.split(';');
+    if !params
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("multipart/byteranges")
+    {
+        return None;
+    }
+
+    let boundary = params.find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        return match key.trim().eq_ignore_ascii_case("boundary") {
+            true => Some(value.trim().trim_matches('"').to_string()),
+            false => None,
+        };
+    })?;
+
+    let delimiter = format!("--{}", boundary);
+    let mut parts = vec![];
+
+    // the first section is the preamble, the last one starts with the closing "--"
+    for section in split_bytes(body, delimiter.as_bytes()).into_iter().skip(1) {
+        if section.starts_with(b"--") {
+            break;
+        }
+
+        let section = section.strip_prefix(b"\r\n").unwrap_or(section);
+        let (head, content) = match find_bytes(section, b"\r\n\r\n") {
+            Some(index) => (&section[..index], &section[index + 4..]),
+            None => continue,
+        };
+
+        let mut part = serde_yaml::Mapping::new();
+        for line in String::from_utf8_lossy(head).lines() {
+            if let Some((key, value)) = line.split_once(':') {
+                match key.trim().to_lowercase().as_str() {
+                    "content-type" => {
+                        part.insert("content_type".into(), value.trim().into());
+                    }
+                    "content-range" => {
+                        if let Some(range) = parse_content_range(value) {
+                            part.insert("content_range".into(), range.to_value());
+                        }
+                    }
+                    _ => {}
+                };
+            }
+        }
+
+        let content = content.strip_suffix(b"\r\n").unwrap_or(content);
+        part.insert(
+            "body".into(),
+            match std::str::from_utf8(content) {
+                Ok(text) => text.into(),
+                Err(_) => serde_yaml::Value::Null,
+            },
+        );
+        part.insert("size".into(), (content.len() as u64).into());
+        part.insert("sha256".into(), sha256(content).into());
+        parts.push(serde_yaml::Value::Mapping(part));
+    }
+
+    return Some(parts);
+}
+
+fn find_bytes(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    return haystack
+        .windows(needle.len())
+        .position(|window| window == needle);
+}
+
+fn split_bytes<'a>(bytes: &'a [u8], delimiter: &[u8]) -> Vec<&'a [u8]> {
+    let mut sections = vec![];
+    let mut rest = bytes;
+
+    while let Some(index) = find_bytes(rest, delimiter) {
+        sections.push(&rest[..index]);
+        rest = &rest[index + delimiter.len()..];
+    }
+    sections.push(rest);
+
+    return sections;
+}
+
+impl Runner {
+    // Fetches the first range to learn the size of the file, then the others `parallel` at a time.
+    // The response is the one of the first range with the reassembled body.
+    pub(crate) async fn download_ranges(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        download: &DownloadSchema,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        if download.chunk_size == 0 || download.parallel == 0 {
+            anyhow::bail!(
+                "The chunk_size and parallel of request \"{}\" can't be 0",
+                name
+            );
+        }
+
+        let started = Instant::now();
+        let first = self
+            .fetch_range(name, resolved, 0..download.chunk_size)
+            .await?;
+        let size = content_range(&first)
+            .and_then(|range| range.size)
+            .context("The server didn't return the size of the file in Content-Range")?;
+
+        let ranges = (download.chunk_size..size)
+            .step_by(download.chunk_size as usize)
+            .map(|start| start..(start + download.chunk_size).min(size))
+            .collect::<Vec<_>>();
+
+        tracing::info!(
+            "{} downloading {} bytes in {} ranges",
+            name,
+            size,
+            ranges.len() + 1
+        );
+
+        let parts = futures::stream::iter(ranges)
+            .map(|range| self.fetch_range(name, resolved, range))
+            .buffered(download.parallel)
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut body = first.body.clone();
+        for part in &parts {
+            // a file changing during the download can't be reassembled
+            for validator in ["etag", "last-modified"] {
+                if part.header(validator) != first.header(validator) {
+                    anyhow::bail!(
+                        "The {} of the ranges of \"{}\" differ, the file changed during the download",
+                        validator,
+                        name
+                    );
+                }
+            }
+            body.extend_from_slice(&part.body);
+        }
+
+        if body.len() as u64 != size {
+            anyhow::bail!(
+                "The ranges of \"{}\" reassemble into {} bytes instead of {}",
+                name,
+                body.len(),
+                size
+            );
+        }
+
+        if download.verify {
+            let mut whole = resolved.clone();
+            whole.remove_header("range");
+            let (whole, _) = self.execute_with_retries(name, &whole).await?;
+            if whole.body != body {
+                anyhow::bail!(
+                    "The ranges of \"{}\" don't reassemble into the file downloaded whole ({} bytes)",
+                    name,
+                    whole.body.len()
+                );
+            }
+        }
+
+        let mut response = first;
+        response.body = body;
+        response.raw_size = size as usize;
+        response.download = Some(Download {
+            size,
+            parts: parts.len() as u32 + 1,
+            verified: download.verify,
+        });
+        return Ok((response, started.elapsed()));
+    }
+
+    // A single range, checked to be the partial content asked for.
+    // The range may end past the file, the server then returns what's left.
+    async fn fetch_range(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        range: Range<u64>,
+    ) -> anyhow::Result<ResponseObject> {
+        let mut request = resolved.clone();
+        request.set_header("Range", &format!("bytes={}-{}", range.start, range.end - 1));
+
+        let (response, _) = self.execute_with_retries(name, &request).await?;
+
+        if response.status != 206 {
+            anyhow::bail!(
+                "Range {}-{} of \"{}\" returned {} instead of 206 Partial Content",
+                range.start,
+                range.end - 1,
+                name,
+                response.status
+            );
+        }
+        if response.overflow.is_some() || response.encoding.is_some() {
+            anyhow::bail!(
+                "Range {}-{} of \"{}\" was truncated or compressed, it can't be reassembled",
+                range.start,
+                range.end - 1,
+                name
+            );
+        }
+
+        let received = content_range(&response).with_context(|| {
+            format!(
+                "Range {}-{} of \"{}\" has no valid Content-Range",
+                range.start,
+                range.end - 1,
+                name
+            )
+        })?;
+        let expected_end = match received.size {
+            Some(size) => (range.end - 1).min(size.saturating_sub(1)),
+            None => range.end - 1,
+        };
+        let expected_length = (expected_end + 1)
+            .checked_sub(range.start)
+            .with_context(|| {
+                format!(
+                    "Range {}-{} of \"{}\" starts past the end of the file ({} bytes)",
+                    range.start,
+                    range.end - 1,
+                    name,
+                    received.size.unwrap_or_default()
+                )
+            })?;
+        if received.start != Some(range.start)
+            || received.end != Some(expected_end)
+            || response.body.len() as u64 != expected_length
+        {
+            anyhow::bail!(
+                "Range {}-{} of \"{}\" returned bytes {}-{} ({} bytes)",
+                range.start,
+                expected_end,
+                name,
+                received.start.unwrap_or_default(),
+                received.end.unwrap_or_default(),
+                response.body.len()
+            );
+        }
+
+        return Ok(response);
+    }
+}
+
+fn content_range(response: &ResponseObject) -> Option<ContentRange> {
+    return response
+        .header("content-range")
+        .and_then(|value| parse_content_range(value));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ranges(ranges: &[&str]) -> Vec<String> {
+        return ranges.iter().map(|range| range.to_string()).collect();
+    }
+
+    #[test]
+    fn builds_the_range_header() {
+        assert_eq!(
+            range_header(&ranges(&["0-499", " 1000- ", "-500"])).unwrap(),
+            "bytes=0-499,1000-,-500"
+        );
+        assert!(range_header(&ranges(&["500-0"])).is_err());
+        assert!(range_header(&ranges(&["-"])).is_err());
+        assert!(range_header(&ranges(&["10"])).is_err());
+        assert!(range_header(&[]).is_err());
+    }
+
+    #[test]
+    fn parses_content_ranges() {
+        assert_eq!(
+            parse_content_range("bytes 0-499/1234"),
+            Some(ContentRange {
+                start: Some(0),
+                end: Some(499),
+                size: Some(1234),
+            })
+        );
+        assert_eq!(
+            parse_content_range("bytes 0-499/1234").unwrap().to_value()["length"],
+            500
+        );
+        assert_eq!(parse_content_range("items 0-1/2"), None);
+        assert_eq!(parse_content_range("bytes a-b/2"), None);
+    }
+
+    #[test]
+    fn splits_multipart_byteranges() {
+        let body = "preamble\r\n--XYZ\r\n\
+                    Content-Type: text/plain\r\n\
+                    Content-Range: bytes 0-4/20\r\n\r\n\
+                    hello\r\n\
+                    --XYZ\r\n\
+                    Content-Range: bytes 15-19/20\r\n\r\n\
+                    world\r\n\
+                    --XYZ--\r\n";
+        let parts = byteranges("multipart/byteranges; boundary=\"XYZ\"", body.as_bytes()).unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0]["content_type"], "text/plain");
+        assert_eq!(parts[0]["content_range"]["end"], 4);
+        assert_eq!(parts[0]["body"], "hello");
+        assert_eq!(parts[1]["content_range"]["start"], 15);
+        assert_eq!(parts[1]["body"], "world");
+        assert_eq!(byteranges("text/plain", body.as_bytes()), None);
+    }
+
+    #[test]
+    fn rejects_inverted_and_oversized_content_ranges() {
+        assert_eq!(parse_content_range("bytes 5-3/10"), None);
+        assert_eq!(parse_content_range("bytes 0-10/10"), None);
+        assert_eq!(parse_content_range("bytes 5-3/*"), None);
+        assert!(parse_content_range("bytes 9-9/10").is_some());
+    }
+
+    #[test]
+    fn unsatisfied_ranges_have_no_start_or_length() {
+        let range = parse_content_range("bytes */1234").unwrap();
+        assert_eq!(
+            range,
+            ContentRange {
+                start: None,
+                end: None,
+                size: Some(1234),
+            }
+        );
+
+        let value = range.to_value();
+        assert_eq!(value["size"], 1234);
+        assert!(value["start"].is_null());
+        assert!(value["length"].is_null());
+    }
+
+    #[test]
+    fn unknown_sizes_keep_the_range() {
+        let range = parse_content_range("bytes 500-999/*").unwrap();
+        assert_eq!(range.size, None);
+
+        let value = range.to_value();
+        assert!(value["size"].is_null());
+        assert_eq!(value["length"], 500);
+
+        let last = format!("bytes 0-{}/*", u64::MAX);
+        let value = parse_content_range(&last).unwrap().to_value();
+        assert!(value["length"].is_null());
+    }
+
+    #[test]
+    fn binary_byteranges_keep_their_size_and_hash() {
+        let mut body = b"--XYZ\r\nContent-Type: application/octet-stream\r\n\
+                         Content-Range: bytes 0-3/8\r\n\r\n"
+            .to_vec();
+        body.extend_from_slice(&[0xff, 0x00, b'\r', 0xfe]);
+        body.extend_from_slice(b"\r\n--XYZ\r\nContent-Range: bytes 4-7/8\r\n\r\nabcd\r\n--XYZ--");
+
+        let parts = byteranges("multipart/byteranges; boundary=XYZ", &body).unwrap();
+
+        assert_eq!(parts.len(), 2);
+        assert!(parts[0]["body"].is_null());
+        assert_eq!(parts[0]["size"], 4);
+        assert_eq!(parts[0]["sha256"], sha256(&[0xff, 0x00, b'\r', 0xfe]));
+        assert_eq!(parts[0]["content_range"]["length"], 4);
+        assert_eq!(parts[1]["body"], "abcd");
+        assert_eq!(parts[1]["size"], 4);
+    }
+}
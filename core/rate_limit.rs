@@ -0,0 +1,164 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use tokio::time::Instant;
+
+use crate::{
+    runner::{ResolvedRequest, Runner},
+    schema::rate_limit::RateLimitSchema,
+};
+
+const PROJECT_SLOT: &str = "";
+
+/// Spaces out the requests of a run following the project and request rate limits.
+///
+/// Each limit keeps the time its next request may be sent at, and a request waits
+/// for the later of its own slot and the project's.
+#[derive(Default)]
+pub struct RateLimiter {
+    next: Mutex<HashMap<String, Instant>>,
+}
+
+impl RateLimiter {
+    pub fn clear(&self) {
+        if let Ok(mut next) = self.next.lock() {
+            next.clear();
+        }
+    }
+
+    // Reserves the next slot for each limit and returns when the request can be sent
+    fn reserve(&self, limits: &[(&str, Duration)]) -> Option<Instant> {
+        let mut next = self.next.lock().ok()?;
+        let now = Instant::now();
+
+        let at = limits
+            .iter()
+            .filter_map(|(key, _)| next.get(*key).copied())
+            .fold(now, Instant::max);
+
+        for (key, spacing) in limits {
+            next.insert(key.to_string(), at + *spacing);
+        }
+
+        return if at > now { Some(at) } else { None };
+    }
+}
+
+impl Runner {
+    // Waits until the project and request rate limits allow sending the request
+    pub(crate) async fn wait_for_rate_limit(&self, resolved: &ResolvedRequest) {
+        let project = self.project.object.rate_limit.as_ref();
+        let request = resolved.config.rate_limit.as_ref();
+
+        let mut limits = vec![];
+        if let Some(spacing) = project.and_then(RateLimitSchema::spacing) {
+            limits.push((PROJECT_SLOT, spacing));
+        }
+        if let Some(spacing) = request.and_then(RateLimitSchema::spacing) {
+            limits.push((resolved.name.as_str(), spacing));
+        }
+
+        if limits.is_empty() {
+            return;
+        }
+
+        if let Some(at) = self.rate_limiter.reserve(&limits) {
+            tracing::debug!(
+                "{} waiting {:?} for the rate limit",
+                resolved.name,
+                at - Instant::now()
+            );
+            tokio::time::sleep_until(at).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fs::FileObject,
+        schema::roots::{ProjectRootSchema, RequestRootSchema},
+    };
+
+    // A runner limited to 10 requests a second, its slow request to one every 500ms
+    fn runner() -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-rate-{}", uuid::Uuid::new_v4()));
+        let project = serde_yaml::from_str::<ProjectRootSchema>(
+            "project: {name: rate}\ncalls: {main: []}\nrate_limit: {max_per_second: 10}",
+        )
+        .unwrap();
+        let requests = [
+            ("fast", "method: GET\nurl: http://localhost/fast"),
+            (
+                "slow",
+                "method: GET\nurl: http://localhost/slow\nconfig: {rate_limit: {min_interval: 500}}",
+            ),
+        ];
+
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), project),
+            requests
+                .iter()
+                .map(|(name, yaml)| {
+                    FileObject::new(
+                        root.join(format!("requests/{}.nd", name)),
+                        serde_yaml::from_str::<RequestRootSchema>(yaml).unwrap(),
+                    )
+                })
+                .collect(),
+        )
+        .unwrap();
+    }
+
+    // The time each request was let through at, from the start
+    async fn pace(runner: &Runner, requests: &[&str]) -> Vec<Duration> {
+        let started = Instant::now();
+        let mut times = vec![];
+
+        for name in requests {
+            let resolved = runner.resolve_request(name).unwrap();
+            runner.wait_for_rate_limit(&resolved).await;
+            times.push(started.elapsed());
+        }
+
+        return times;
+    }
+
+    fn millis(times: &[u64]) -> Vec<Duration> {
+        return times.iter().map(|ms| Duration::from_millis(*ms)).collect();
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requests_are_spaced_by_the_project_limit() {
+        let runner = runner();
+
+        let times = pace(&runner, &["fast", "fast", "fast"]).await;
+
+        assert_eq!(times, millis(&[0, 100, 200]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_request_waits_for_the_stricter_of_its_limit_and_the_project_one() {
+        let runner = runner();
+
+        let times = pace(&runner, &["slow", "fast", "slow", "fast"]).await;
+
+        // the second slow request waits 500ms after the first, the fast one after it 100ms more
+        assert_eq!(times, millis(&[0, 100, 500, 600]));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn time_that_passed_counts_towards_the_next_slot() {
+        let runner = runner();
+
+        pace(&runner, &["fast"]).await;
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let times = pace(&runner, &["fast", "fast"]).await;
+
+        assert_eq!(times, millis(&[0, 100]));
+
+        // a cleared limiter lets the next request through right away
+        runner.rate_limiter.clear();
+        assert_eq!(pace(&runner, &["fast"]).await, millis(&[0]));
+    }
+}
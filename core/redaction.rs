@@ -0,0 +1,404 @@
+use serde_json_path::JsonPath;
+
+use crate::{
+    interpolation::{EnvMap, lookup, value_to_string},
+    runner::{CallResult, Runner},
+    schema::{
+        redaction::RedactionSchema,
+        request_body::{MultipartPartSchema, RequestBodySchema},
+    },
+};
+
+pub const REDACTED: &str = "<redacted>";
+
+impl RedactionSchema {
+    pub fn is_redacted_header(&self, name: &str) -> bool {
+        return self
+            .headers
+            .iter()
+            .any(|header| header.eq_ignore_ascii_case(name));
+    }
+
+    pub fn redact_header(&self, name: &str, value: &str) -> String {
+        if self.is_redacted_header(name) {
+            return REDACTED.to_string();
+        }
+
+        return value.to_string();
+    }
+
+    // Replaces the values of the redacted variables in text
+    pub fn redact_values(&self, text: &str, env: &EnvMap) -> String {
+        let mut result = text.to_string();
+
+        for variable in &self.variables {
+            let value = match lookup(variable, env) {
+                Some(value) => value_to_string(&value),
+                None => continue,
+            };
+
+            if value.is_empty() {
+                continue;
+            }
+
+            for form in encoded_forms(&value) {
+                result = result.replace(&form, REDACTED);
+            }
+        }
+
+        return result;
+    }
+
+    // Replaces the fields matched by the redacted paths in a json value
+    pub fn redact_json(&self, value: &mut serde_json::Value) {
+        for path in &self.paths {
+            let query = match JsonPath::parse(path) {
+                Ok(query) => query,
+                Err(e) => {
+                    tracing::warn!("Invalid redacted path `{}`: {}", path, e);
+                    continue;
+                }
+            };
+
+            let pointers = query
+                .query_located(value)
+                .locations()
+                .map(|location| location.to_json_pointer())
+                .collect::<Vec<_>>();
+
+            for pointer in pointers {
+                if let Some(field) = value.pointer_mut(&pointer) {
+                    *field = serde_json::Value::String(REDACTED.to_string());
+                }
+            }
+        }
+    }
+
+    // Redacts the fields of a json body, then the values of the redacted variables
+    pub fn redact_body(&self, text: &str, env: &EnvMap) -> String {
+        let text = match serde_json::from_str::<serde_json::Value>(text) {
+            Ok(mut value) if !self.paths.is_empty() => {
+                self.redact_json(&mut value);
+                value.to_string()
+            }
+            _ => text.to_string(),
+        };
+
+        return self.redact_values(&text, env);
+    }
+
+    // The body of a request with its text redacted, file based bodies are kept as they are.
+    // Each variant is listed so a new one has to decide what of it is redacted.
+    pub fn redact_request_body(&self, body: &RequestBodySchema, env: &EnvMap) -> RequestBodySchema {
+        let mut body = body.clone();
+
+        match &mut body {
+            RequestBodySchema::Json { content, .. }
+            | RequestBodySchema::Msgpack { content, .. }
+            | RequestBodySchema::Cbor { content, .. } => {
+                self.redact_yaml(content, env);
+            }
+            RequestBodySchema::Ndjson { content, .. } => {
+                for row in content.iter_mut() {
+                    self.redact_yaml(row, env);
+                }
+            }
+            RequestBodySchema::Graphql {
+                query, variables, ..
+            } => {
+                *query = self.redact_values(query, env);
+                if let Some(variables) = variables {
+                    self.redact_yaml(variables, env);
+                }
+            }
+            RequestBodySchema::Soap {
+                content,
+                header,
+                action,
+                ..
+            } => {
+                *content = self.redact_values(content, env);
+                for text in [header, action].into_iter().flatten() {
+                    *text = self.redact_values(text, env);
+                }
+            }
+            RequestBodySchema::Xml { content, .. } | RequestBodySchema::Text { content, .. } => {
+                *content = self.redact_values(content, env);
+            }
+            RequestBodySchema::FormUrlencoded {
+                content, fields, ..
+            } => {
+                *content = self.redact_values(content, env);
+                if let Some(fields) = fields {
+                    for (name, value) in fields.iter_mut() {
+                        *value = self.redact_field(name, value, env);
+                    }
+                }
+            }
+            RequestBodySchema::Multipart { parts } => {
+                for part in parts.iter_mut() {
+                    match part {
+                        MultipartPartSchema::Field { name, value } => {
+                            *value = self.redact_field(name, value, env);
+                        }
+                        MultipartPartSchema::File { .. } => {}
+                    };
+                }
+            }
+            RequestBodySchema::Custom { fields, .. } => {
+                for value in fields.values_mut() {
+                    self.redact_yaml(value, env);
+                }
+            }
+            RequestBodySchema::Binary { .. } => {}
+        };
+
+        return body;
+    }
+
+    // Redacts a yaml value like a json body, through its json text
+    fn redact_yaml(&self, value: &mut serde_yaml::Value, env: &EnvMap) {
+        if let Ok(json) = serde_json::to_string(value)
+            && let Ok(redacted) = serde_yaml::from_str(&self.redact_body(&json, env))
+        {
+            // json is valid yaml, the redacted text is parsed back
+            *value = redacted;
+        }
+    }
+
+    // The value of a form field, redacted when a path matches its name as it would in a json object
+    fn redact_field(&self, name: &str, value: &str, env: &EnvMap) -> String {
+        let mut object = serde_json::json!({ name: value });
+        self.redact_json(&mut object);
+
+        return match object[name].as_str() {
+            Some(REDACTED) => REDACTED.to_string(),
+            _ => self.redact_values(value, env),
+        };
+    }
+}
+
+// A value as written and as it appears once encoded in urls and forms, eg: a b, a+b, a%20b
+fn encoded_forms(value: &str) -> Vec<String> {
+    let form = url::form_urlencoded::byte_serialize(value.as_bytes()).collect::<String>();
+    let percent = percent_encoding::utf8_percent_encode(value, percent_encoding::NON_ALPHANUMERIC)
+        .to_string();
+
+    let mut forms = vec![value.to_string()];
+    for encoded in [form.replace('+', "%20"), form, percent] {
+        if !forms.contains(&encoded) {
+            forms.push(encoded);
+        }
+    }
+
+    return forms;
+}
+
+impl Runner {
+    // The result with its url, headers and bodies redacted with the project rules, to show it outside the runner
+    pub fn redact_result(&self, result: &CallResult) -> CallResult {
+        let redaction = &self.project.object.redact;
+        let env = self.build_env();
+        let mut result = result.clone();
+
+        let request = &mut result.request;
+        request.url = redaction.redact_values(&request.url, &env);
+        for (key, value) in request.headers.iter_mut() {
+            *value = redaction.redact_values(&redaction.redact_header(key, value), &env);
+        }
+        request.body = request
+            .body
+            .as_ref()
+            .map(|body| redaction.redact_request_body(body, &env));
+
+        let response = &mut result.response;
+        for (key, value) in response.headers.iter_mut() {
+            *value = redaction.redact_values(&redaction.redact_header(key, value), &env);
+        }
+        // binary bodies have nothing to redact
+        if let Ok(text) = std::str::from_utf8(&response.body) {
+            response.body = redaction.redact_body(text, &env).into_bytes();
+        }
+
+        return result;
+    }
+
+    // Headers as `name: value` lines with the redaction rules applied, for the logs
+    pub(crate) fn redact_header_lines(&self, headers: &reqwest::header::HeaderMap) -> String {
+        let redaction = &self.project.object.redact;
+        let env = self.build_env();
+
+        return headers
+            .iter()
+            .map(|(key, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes());
+                let value =
+                    redaction.redact_values(&redaction.redact_header(key.as_str(), &value), &env);
+                format!("{}: {}", key, value)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+
+    // Text with the values of the redacted variables replaced, eg: urls written to the logs
+    pub fn redact_text(&self, text: &str) -> String {
+        return self
+            .project
+            .object
+            .redact
+            .redact_values(text, &self.build_env());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redaction() -> RedactionSchema {
+        return RedactionSchema {
+            variables: vec!["token".to_string(), "auth.password".to_string()],
+            paths: vec!["$.password".to_string(), "$..secret".to_string()],
+            ..Default::default()
+        };
+    }
+
+    fn env() -> EnvMap {
+        return serde_yaml::from_str("{token: t0k3n, auth: {password: hunter2}, empty: ''}")
+            .unwrap();
+    }
+
+    #[test]
+    fn redacts_headers_by_name() {
+        let redaction = redaction();
+
+        assert_eq!(
+            redaction.redact_header("Authorization", "Bearer x"),
+            REDACTED
+        );
+        assert_eq!(redaction.redact_header("Accept", "*/*"), "*/*");
+    }
+
+    #[test]
+    fn replaces_the_values_of_variables() {
+        let redaction = redaction();
+
+        assert_eq!(
+            redaction.redact_values("https://x.dev/?token=t0k3n&p=hunter2", &env()),
+            "https://x.dev/?token=<redacted>&p=<redacted>"
+        );
+    }
+
+    #[test]
+    fn redacts_json_fields_by_path() {
+        let redaction = redaction();
+        let body = r#"{"password":"a","user":{"secret":"b","name":"t0k3n"}}"#;
+        let redacted =
+            serde_json::from_str::<serde_json::Value>(&redaction.redact_body(body, &env()))
+                .unwrap();
+
+        assert_eq!(redacted["password"], REDACTED);
+        assert_eq!(redacted["user"]["secret"], REDACTED);
+        assert_eq!(redacted["user"]["name"], REDACTED);
+        assert_eq!(
+            redaction.redact_body("not json t0k3n", &env()),
+            "not json <redacted>"
+        );
+    }
+
+    #[test]
+    fn redacts_request_bodies() {
+        let redaction = redaction();
+        let json = RequestBodySchema::Json {
+            content: serde_yaml::from_str("{password: a, name: b}").unwrap(),
+            content_file: None,
+        };
+        match redaction.redact_request_body(&json, &env()) {
+            RequestBodySchema::Json { content, .. } => {
+                assert_eq!(content["password"], REDACTED);
+                assert_eq!(content["name"], "b");
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+
+        let text = RequestBodySchema::Text {
+            content: "token t0k3n".to_string(),
+            content_file: None,
+        };
+        match redaction.redact_request_body(&text, &env()) {
+            RequestBodySchema::Text { content, .. } => assert_eq!(content, "token <redacted>"),
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn replaces_the_encoded_values_of_variables() {
+        let redaction = RedactionSchema {
+            variables: vec!["key".to_string()],
+            ..Default::default()
+        };
+        let env = serde_yaml::from_str("{key: 'p@ss w/rd+'}").unwrap();
+
+        for text in [
+            "?key=p%40ss+w%2Frd%2B",
+            "?key=p%40ss%20w%2Frd%2B",
+            "/keys/p%40ss%20w%2Frd%2B",
+            "key: p@ss w/rd+",
+        ] {
+            let redacted = redaction.redact_values(text, &env);
+            assert!(redacted.ends_with(REDACTED), "{}", redacted);
+        }
+    }
+
+    #[test]
+    fn redacts_every_body_with_text_or_fields() {
+        let redaction = redaction();
+        let env = env();
+        let redact = |yaml: &str| {
+            let body = serde_yaml::from_str::<RequestBodySchema>(yaml).unwrap();
+            return serde_yaml::to_value(redaction.redact_request_body(&body, &env)).unwrap();
+        };
+
+        let graphql = redact(
+            "{type: graphql, query: 'query { me(token: \"t0k3n\") }', variables: {password: a, user: {secret: b}, id: 1}}",
+        );
+        assert_eq!(graphql["query"], "query { me(token: \"<redacted>\") }");
+        assert_eq!(graphql["variables"]["password"], REDACTED);
+        assert_eq!(graphql["variables"]["user"]["secret"], REDACTED);
+        assert_eq!(graphql["variables"]["id"], 1);
+
+        let form = redact(
+            "{type: form-urlencoded, content: 'a=t0k3n', fields: {password: a, note: 'pw hunter2', name: b}}",
+        );
+        assert_eq!(form["content"], "a=<redacted>");
+        assert_eq!(form["fields"]["password"], REDACTED);
+        assert_eq!(form["fields"]["note"], "pw <redacted>");
+        assert_eq!(form["fields"]["name"], "b");
+
+        let multipart = redact(
+            "{type: multipart, parts: [{kind: field, name: password, value: a}, {kind: field, name: note, value: t0k3n}, {kind: field, name: name, value: b}, {kind: file, name: secret, path: t0k3n.png}]}",
+        );
+        assert_eq!(multipart["parts"][0]["value"], REDACTED);
+        assert_eq!(multipart["parts"][1]["value"], REDACTED);
+        assert_eq!(multipart["parts"][2]["value"], "b");
+        assert_eq!(multipart["parts"][3]["path"], "t0k3n.png");
+
+        let soap = redact(
+            "{type: soap, content: '<Pay>t0k3n</Pay>', header: '<Password>hunter2</Password>', action: urn:pay}",
+        );
+        assert_eq!(soap["content"], "<Pay><redacted></Pay>");
+        assert_eq!(soap["header"], "<Password><redacted></Password>");
+        assert_eq!(soap["action"], "urn:pay");
+
+        let ndjson = redact("{type: ndjson, content: [{password: a}, {name: t0k3n}]}");
+        assert_eq!(ndjson["content"][0]["password"], REDACTED);
+        assert_eq!(ndjson["content"][1]["name"], REDACTED);
+
+        let custom = redact("{type: protobuf, message: {password: a, name: b}, key: t0k3n}");
+        assert_eq!(custom["message"]["password"], REDACTED);
+        assert_eq!(custom["message"]["name"], "b");
+        assert_eq!(custom["key"], REDACTED);
+
+        let binary = redact("{type: binary, path: t0k3n.bin}");
+        assert_eq!(binary["path"], "t0k3n.bin");
+    }
+}
@@ -0,0 +1,52 @@
+use std::process::Stdio;
+
+use anyhow::Context;
+use tokio::io::AsyncWriteExt;
+
+use crate::{
+    hooks::shell,
+    report::{ReportFuture, Reporter, RunReport},
+};
+
+/// Hands the json report to an external program on its stdin,
+/// so custom reporters can be written in any language.
+pub struct CommandReporter {
+    command: String,
+}
+
+impl CommandReporter {
+    pub fn new(command: String) -> CommandReporter {
+        return CommandReporter { command };
+    }
+}
+
+impl Reporter for CommandReporter {
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async move {
+            let content = serde_json::to_vec(report)?;
+
+            let mut child = tokio::process::Command::from(shell(&self.command))
+                .stdin(Stdio::piped())
+                .spawn()
+                .with_context(|| {
+                    format!("Failed to start reporter command \"{}\"", &self.command)
+                })?;
+
+            // dropped once written, so the command sees the end of its input
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(&content).await?;
+            }
+
+            let status = child.wait().await?;
+            if !status.success() {
+                anyhow::bail!(
+                    "Reporter command \"{}\" exited with {}",
+                    &self.command,
+                    status
+                );
+            }
+
+            return Ok(());
+        });
+    }
+}
@@ -0,0 +1,107 @@
+use std::io::IsTerminal;
+
+use crate::report::{ReportEntry, ReportFuture, Reporter, RunReport};
+
+const GREEN: &str = "32";
+const RED: &str = "31";
+const YELLOW: &str = "33";
+const DIM: &str = "2";
+
+/// Prints each result to stdout as it completes, followed by a summary.
+/// Results are colored when stdout is a terminal and NO_COLOR isn't set.
+pub struct ConsoleReporter {
+    color: bool,
+}
+
+impl ConsoleReporter {
+    pub fn new() -> ConsoleReporter {
+        return ConsoleReporter {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        };
+    }
+
+    fn paint(&self, text: &str, color: &str) -> String {
+        if !self.color {
+            return text.to_string();
+        }
+
+        return format!("\x1b[{}m{}\x1b[0m", color, text);
+    }
+}
+
+impl Default for ConsoleReporter {
+    fn default() -> Self {
+        return ConsoleReporter::new();
+    }
+}
+
+impl Reporter for ConsoleReporter {
+    fn on_entry(&mut self, entry: &ReportEntry) {
+        match &entry.error {
+            None if entry.skipped => {
+                println!("{}", self.paint(&format!("- {} skipped", entry.name), DIM))
+            }
+            None => println!(
+                "{} {} {} {} -> {} ({}ms)",
+                self.paint("✓", GREEN),
+                entry.name,
+                entry.method,
+                entry.url,
+                entry.status.unwrap_or_default(),
+                entry.duration_ms
+            ),
+            Some(error) if entry.allow_failure => println!(
+                "{} {} {} {}: {} (failure allowed)",
+                self.paint("!", YELLOW),
+                entry.name,
+                entry.method,
+                entry.url,
+                error
+            ),
+            Some(error) => println!(
+                "{} {} {} {}: {}",
+                self.paint("✗", RED),
+                entry.name,
+                entry.method,
+                entry.url,
+                self.paint(error, RED)
+            ),
+        };
+
+        for warning in &entry.warnings {
+            println!("  {}", self.paint(&format!("warning: {}", warning), YELLOW));
+        }
+    }
+
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async move {
+            let summary = format!(
+                "{}: {} passed, {} failed, {} allowed to fail, {} skipped in {}ms",
+                report.name,
+                report.entries.len() - report.failures() - report.allowed_failures(),
+                report.failures(),
+                report.allowed_failures(),
+                report.skipped(),
+                report.duration_ms
+            );
+            println!(
+                "{}",
+                self.paint(&summary, if report.passed() { GREEN } else { RED })
+            );
+
+            if report.warnings() > 0 {
+                println!(
+                    "{}",
+                    self.paint(&format!("{} warnings", report.warnings()), YELLOW)
+                );
+            }
+            if report.attempts > 1 {
+                println!("{} ran {} times", report.name, report.attempts);
+            }
+            if report.cancelled {
+                println!("{} was cancelled", report.name);
+            }
+            return Ok(());
+        });
+    }
+}
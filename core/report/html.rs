@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::report::{ReportFuture, Reporter, RunReport, junit::escape};
+
+/// Writes the report as a standalone html page.
+pub struct HtmlReporter {
+    path: PathBuf,
+}
+
+impl HtmlReporter {
+    pub fn new(path: PathBuf) -> HtmlReporter {
+        return HtmlReporter { path };
+    }
+}
+
+impl Reporter for HtmlReporter {
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async move {
+            tokio::fs::write(&self.path, page(report))
+                .await
+                .with_context(|| format!("Failed to write html report to {:?}", &self.path))?;
+            return Ok(());
+        });
+    }
+}
+
+// The report as a page with a row per entry
+fn page(report: &RunReport) -> String {
+    let mut rows = String::new();
+
+    for entry in &report.entries {
+        rows.push_str(&format!(
+            "<tr class=\"{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}ms</td><td>{}</td></tr>\n",
+            if entry.skipped {
+                "skipped"
+            } else if entry.allow_failure && entry.error.is_some() {
+                "allowed"
+            } else if entry.passed() {
+                "passed"
+            } else {
+                "failed"
+            },
+            escape(&entry.name),
+            escape(&entry.method),
+            escape(&entry.url),
+            entry.status.map(|s| s.to_string()).unwrap_or_default(),
+            entry.duration_ms,
+            escape(entry.error.as_deref().unwrap_or_default()),
+        ));
+    }
+
+    let content = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+<style>
+body {{ font-family: sans-serif; }}
+table {{ border-collapse: collapse; width: 100%; }}
+td, th {{ border: 1px solid #ddd; padding: 4px 8px; text-align: left; }}
+tr.passed {{ background: #e8f5e9; }}
+tr.failed {{ background: #ffebee; }}
+tr.skipped {{ color: #888; }}
+tr.allowed {{ background: #fff8e1; }}
+</style>
+</head>
+<body>
+<h1>{name}</h1>
+<p>{passed} passed, {failed} failed, {allowed} allowed to fail, {skipped} skipped in {duration}ms ({attempts} attempts)</p>
+<table>
+<tr><th>Request</th><th>Method</th><th>Url</th><th>Status</th><th>Duration</th><th>Error</th></tr>
+{rows}</table>
+</body>
+</html>
+"#,
+        name = escape(&report.name),
+        passed = report.entries.len() - report.failures() - report.allowed_failures(),
+        failed = report.failures(),
+        allowed = report.allowed_failures(),
+        skipped = report.skipped(),
+        attempts = report.attempts,
+        duration = report.duration_ms,
+        rows = rows
+    );
+
+    return content;
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::report::{ReportFuture, Reporter, RunReport};
+
+/// Writes the report as json to a file.
+pub struct JsonReporter {
+    path: PathBuf,
+}
+
+impl JsonReporter {
+    pub fn new(path: PathBuf) -> JsonReporter {
+        return JsonReporter { path };
+    }
+}
+
+impl Reporter for JsonReporter {
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async move {
+            let content = serde_json::to_string_pretty(report)?;
+            tokio::fs::write(&self.path, content)
+                .await
+                .with_context(|| format!("Failed to write json report to {:?}", &self.path))?;
+            return Ok(());
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ReportEntry;
+
+    #[tokio::test]
+    async fn the_report_reads_back_from_its_json() {
+        let path = std::env::temp_dir().join(format!("dotapi-json-{}.json", uuid::Uuid::new_v4()));
+        let report = RunReport {
+            name: "main".to_string(),
+            environment: Some("staging".to_string()),
+            entries: vec![ReportEntry {
+                name: "login".to_string(),
+                method: "POST".to_string(),
+                url: "https://x.dev/login".to_string(),
+                status: Some(401),
+                duration_ms: 12,
+                error: Some("status == 200 failed".to_string()),
+                skipped: false,
+                allow_failure: false,
+                assertion_failures: vec!["status == 200".to_string()],
+                warnings: vec![],
+            }],
+            duration_ms: 15,
+            attempts: 2,
+            cancelled: false,
+        };
+
+        JsonReporter::new(path.clone())
+            .finish(&report)
+            .await
+            .unwrap();
+
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(serde_json::from_str::<RunReport>(&written).unwrap(), report);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
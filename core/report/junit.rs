@@ -0,0 +1,147 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::report::{ReportFuture, Reporter, RunReport};
+
+/// Writes the report as JUnit xml, for CI systems that display test results.
+pub struct JunitReporter {
+    path: PathBuf,
+}
+
+impl JunitReporter {
+    pub fn new(path: PathBuf) -> JunitReporter {
+        return JunitReporter { path };
+    }
+}
+
+impl Reporter for JunitReporter {
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async move {
+            tokio::fs::write(&self.path, xml(report))
+                .await
+                .with_context(|| format!("Failed to write junit report to {:?}", &self.path))?;
+            return Ok(());
+        });
+    }
+}
+
+// The report as a junit testsuite, one testcase per entry
+fn xml(report: &RunReport) -> String {
+    let mut content = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+
+    content.push_str(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n",
+        escape(&report.name),
+        report.entries.len(),
+        report.failures(),
+        report.skipped(),
+        report.duration_ms as f64 / 1000.0
+    ));
+
+    for entry in &report.entries {
+        content.push_str(&format!(
+            "  <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape(&entry.name),
+            escape(&report.name),
+            entry.duration_ms as f64 / 1000.0
+        ));
+
+        match &entry.error {
+            None if entry.skipped => content.push_str(">\n    <skipped />\n  </testcase>\n"),
+            Some(error) if entry.allow_failure => content.push_str(&format!(
+                ">\n    <system-out>failure allowed: {}</system-out>\n  </testcase>\n",
+                escape(error)
+            )),
+            Some(error) => content.push_str(&format!(
+                ">\n    <failure message=\"{}\">{} {}</failure>\n  </testcase>\n",
+                escape(error),
+                escape(&entry.method),
+                escape(&entry.url)
+            )),
+            None => content.push_str(" />\n"),
+        };
+    }
+
+    content.push_str("</testsuite>\n");
+
+    return content;
+}
+
+pub(crate) fn escape(value: &str) -> String {
+    return value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ReportEntry;
+
+    fn entry(name: &str, error: Option<&str>) -> ReportEntry {
+        return ReportEntry {
+            name: name.to_string(),
+            method: "GET".to_string(),
+            url: format!("https://x.dev/{}", name),
+            status: error.is_none().then_some(200),
+            duration_ms: 250,
+            error: error.map(|e| e.to_string()),
+            skipped: false,
+            allow_failure: false,
+            assertion_failures: vec![],
+            warnings: vec![],
+        };
+    }
+
+    #[tokio::test]
+    async fn each_entry_is_a_testcase() {
+        let path = std::env::temp_dir().join(format!("dotapi-junit-{}.xml", uuid::Uuid::new_v4()));
+        let report = RunReport {
+            name: "main".to_string(),
+            environment: None,
+            entries: vec![
+                entry("users", None),
+                entry("login", Some("expected <200>, got 401")),
+                ReportEntry {
+                    allow_failure: true,
+                    ..entry("flaky", Some("timed out"))
+                },
+                ReportEntry {
+                    skipped: true,
+                    ..entry("cleanup", None)
+                },
+            ],
+            duration_ms: 1500,
+            attempts: 1,
+            cancelled: false,
+        };
+
+        JunitReporter::new(path.clone())
+            .finish(&report)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="main" tests="4" failures="1" skipped="1" time="1.500">
+  <testcase name="users" classname="main" time="0.250" />
+  <testcase name="login" classname="main" time="0.250">
+    <failure message="expected &lt;200&gt;, got 401">GET https://x.dev/login</failure>
+  </testcase>
+  <testcase name="flaky" classname="main" time="0.250">
+    <system-out>failure allowed: timed out</system-out>
+  </testcase>
+  <testcase name="cleanup" classname="main" time="0.250">
+    <skipped />
+  </testcase>
+</testsuite>
+"#
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,345 @@
+mod command;
+mod console;
+mod html;
+mod json;
+mod junit;
+mod prometheus;
+
+use std::{future::Future, path::Path, pin::Pin, time::Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assertions::AssertionError,
+    runner::{CallResult, Runner, SkippedError},
+    schema::{
+        calls::{FailurePolicySchema, HookSchema, SequenceDefinitionSchema, StepSchema},
+        reporter::ReporterSchema,
+    },
+};
+
+pub use command::CommandReporter;
+pub use console::ConsoleReporter;
+pub use html::HtmlReporter;
+pub use json::JsonReporter;
+pub use junit::JunitReporter;
+pub use prometheus::PrometheusReporter;
+
+pub type ReportFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>;
+
+/// The outcome of a single request within a run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReportEntry {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub status: Option<u16>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    // the request's `when` condition was false
+    #[serde(default)]
+    pub skipped: bool,
+    // the step is marked allow_failure, an error doesn't fail the run
+    #[serde(default)]
+    pub allow_failure: bool,
+    // the assertions the response didn't satisfy, the error holds them too
+    #[serde(default)]
+    pub assertion_failures: Vec<String>,
+    // placeholders a lenient interpolation mode let through
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+impl ReportEntry {
+    pub fn passed(&self) -> bool {
+        return self.error.is_none() || self.allow_failure;
+    }
+}
+
+/// The outcome of a run of a request or call sequence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunReport {
+    pub name: String,
+    pub environment: Option<String>,
+    pub entries: Vec<ReportEntry>,
+    pub duration_ms: u64,
+    // times the sequence ran, more than one when it was retried
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    // the run was stopped through the runner's cancel token, entries holds what ran until then
+    #[serde(default)]
+    pub cancelled: bool,
+}
+
+fn default_attempts() -> u32 {
+    return 1;
+}
+
+impl RunReport {
+    pub fn passed(&self) -> bool {
+        return self.entries.iter().all(|entry| entry.passed());
+    }
+
+    pub fn failures(&self) -> usize {
+        return self.entries.iter().filter(|entry| !entry.passed()).count();
+    }
+
+    pub fn allowed_failures(&self) -> usize {
+        return self
+            .entries
+            .iter()
+            .filter(|entry| entry.allow_failure && entry.error.is_some())
+            .count();
+    }
+
+    pub fn skipped(&self) -> usize {
+        return self.entries.iter().filter(|entry| entry.skipped).count();
+    }
+
+    pub fn warnings(&self) -> usize {
+        return self.entries.iter().map(|entry| entry.warnings.len()).sum();
+    }
+}
+
+/// Receives the results of a run. Several reporters can be active at once.
+pub trait Reporter: Send + Sync {
+    // called as soon as a request in the run completes
+    fn on_entry(&mut self, _entry: &ReportEntry) {}
+
+    // called once the run is complete
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a>;
+}
+
+// Creates a reporter from its definition in the project file.
+// Relative paths are resolved from root.
+pub fn create_reporter(schema: &ReporterSchema, root: &Path) -> Box<dyn Reporter> {
+    return match schema {
+        ReporterSchema::Console => Box::new(ConsoleReporter::new()),
+        ReporterSchema::Json { path } => Box::new(JsonReporter::new(root.join(path))),
+        ReporterSchema::Junit { path } => Box::new(JunitReporter::new(root.join(path))),
+        ReporterSchema::Html { path } => Box::new(HtmlReporter::new(root.join(path))),
+        ReporterSchema::Prometheus { path } => Box::new(PrometheusReporter::new(root.join(path))),
+        ReporterSchema::Command { command } => Box::new(CommandReporter::new(command.clone())),
+    };
+}
+
+impl Runner {
+    pub fn add_reporter(&mut self, reporter: Box<dyn Reporter>) {
+        self.reporters.push(reporter);
+    }
+
+    // Adds the reporters declared in the project file
+    pub fn use_project_reporters(&mut self) {
+        let root = self.project.get_root_dir();
+        let schemas = self.project.object.reporters.clone();
+
+        for schema in schemas {
+            self.add_reporter(create_reporter(&schema, &root));
+        }
+    }
+
+    // Calls a request and reports the outcome
+    pub async fn run_request(&mut self, name: &str) -> anyhow::Result<RunReport> {
+        let sequence = SequenceDefinitionSchema {
+            steps: vec![StepSchema::Request(name.to_string())],
+            ..Default::default()
+        };
+        return self.run(name, sequence).await;
+    }
+
+    // Calls every request in a sequence following its failure policy, and reports the outcome.
+    // Before hooks run first and after hooks always run, only failing hooks are reported.
+    // When the sequence is retried, the report holds the entries of the last attempt.
+    pub async fn run_sequence(&mut self, name: &str) -> anyhow::Result<RunReport> {
+        let sequence = self.get_sequence_definition(name)?;
+        return self.run(name, sequence).await;
+    }
+
+    // Calls requests one after another, going on when one fails, and reports the outcome.
+    // Used to run the failed requests of a run again.
+    pub async fn run_requests(
+        &mut self,
+        name: &str,
+        requests: Vec<String>,
+    ) -> anyhow::Result<RunReport> {
+        let sequence = SequenceDefinitionSchema {
+            steps: requests.into_iter().map(StepSchema::Request).collect(),
+            on_failure: FailurePolicySchema::Continue,
+            ..Default::default()
+        };
+        return self.run(name, sequence).await;
+    }
+
+    async fn run(
+        &mut self,
+        name: &str,
+        sequence: SequenceDefinitionSchema,
+    ) -> anyhow::Result<RunReport> {
+        // a misconfigured environment fails the run before anything is sent
+        self.check_env()?;
+
+        let started = Instant::now();
+
+        if let Some(tracer) = &self.tracer {
+            tracer.start_run(name, self.environment.as_deref());
+        }
+
+        let run = self.sequence(name, &sequence).await;
+
+        let report = RunReport {
+            name: name.to_string(),
+            environment: self.environment.clone(),
+            entries: run.entries,
+            duration_ms: started.elapsed().as_millis() as u64,
+            attempts: run.attempts,
+            cancelled: self.is_cancelled(),
+        };
+
+        if let Some(tracer) = &self.tracer {
+            tracer.end_run(&report, |text| self.redact_text(text));
+            if let Err(e) = tracer.export().await {
+                tracing::warn!("Could not export the spans of {}: {:#}", name, e);
+            }
+        }
+
+        // a reporter failing doesn't keep the others from writing their report
+        let mut errors = vec![];
+        for reporter in self.reporters.iter_mut() {
+            if let Err(e) = reporter.finish(&report).await {
+                errors.push(format!("{:#}", e));
+            }
+        }
+
+        if !errors.is_empty() {
+            anyhow::bail!("Failed to report {}:\n{}", name, errors.join("\n"));
+        }
+
+        return Ok(report);
+    }
+
+    pub(crate) fn report_entry(&mut self, entry: &ReportEntry) {
+        for reporter in self.reporters.iter_mut() {
+            reporter.on_entry(entry);
+        }
+    }
+
+    // The entry of a step of a sequence, from the result of calling its request.
+    // Its url and error go into report files, so they're redacted.
+    pub(crate) fn step_entry(
+        &self,
+        name: &str,
+        result: &anyhow::Result<CallResult>,
+    ) -> ReportEntry {
+        return match result {
+            Ok(result) => ReportEntry {
+                name: name.to_string(),
+                method: result.request.method.clone(),
+                url: self.redact_text(&result.request.url),
+                status: Some(result.response.status),
+                duration_ms: result.duration.as_millis() as u64,
+                error: None,
+                skipped: false,
+                allow_failure: false,
+                assertion_failures: vec![],
+                warnings: result.request.warnings.clone(),
+            },
+            Err(e) => {
+                // the request as it was sent, it isn't resolved again since that could differ
+                let resolved = self.sent.get(name);
+                let skipped = e.is::<SkippedError>();
+                // a response that failed its assertions was still received
+                let assertion_failures = e
+                    .downcast_ref::<AssertionError>()
+                    .map(|error| error.failures.clone())
+                    .unwrap_or_default();
+                let response = match assertion_failures.is_empty() {
+                    true => None,
+                    false => self.responses.get(name),
+                };
+
+                ReportEntry {
+                    name: name.to_string(),
+                    method: resolved
+                        .as_ref()
+                        .map(|r| r.method.clone())
+                        .unwrap_or_default(),
+                    url: resolved
+                        .as_ref()
+                        .map(|r| self.redact_text(&r.url))
+                        .unwrap_or_default(),
+                    status: response.map(|result| result.response.status),
+                    duration_ms: response
+                        .map(|result| result.duration.as_millis() as u64)
+                        .unwrap_or_default(),
+                    error: if skipped {
+                        None
+                    } else {
+                        Some(self.redact_text(&format!("{:#}", e)))
+                    },
+                    skipped,
+                    allow_failure: false,
+                    assertion_failures,
+                    warnings: resolved.map(|r| r.warnings.clone()).unwrap_or_default(),
+                }
+            }
+        };
+    }
+
+    // The entry of a hook that failed, its error is redacted like the one of a step
+    pub(crate) fn hook_entry(&self, hook: &HookSchema, error: &anyhow::Error) -> ReportEntry {
+        return ReportEntry {
+            name: hook.label(),
+            method: String::new(),
+            url: String::new(),
+            status: None,
+            duration_ms: 0,
+            error: Some(self.redact_text(&format!("{:#}", error))),
+            skipped: false,
+            allow_failure: false,
+            assertion_failures: vec![],
+            warnings: vec![],
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::FileObject;
+
+    #[tokio::test]
+    async fn every_reporter_finishes_before_the_run_fails() {
+        let root = std::env::temp_dir().join(format!("dotapi-report-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        let mut runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![],
+        )
+        .unwrap();
+
+        runner.add_reporter(Box::new(CommandReporter::new("exit 3".to_string())));
+        runner.add_reporter(Box::new(JsonReporter::new(
+            root.join("missing/report.json"),
+        )));
+        runner.add_reporter(Box::new(JsonReporter::new(root.join("report.json"))));
+
+        let error = runner.run_requests("smoke", vec![]).await.unwrap_err();
+
+        let message = format!("{:#}", error);
+        assert!(
+            message.starts_with("Failed to report smoke:"),
+            "{}",
+            message
+        );
+        assert!(message.contains("\"exit 3\" exited with"), "{}", message);
+        assert!(message.contains("missing/report.json"), "{}", message);
+
+        // the reporter after the failing ones still wrote its report
+        let written = std::fs::read_to_string(root.join("report.json")).unwrap();
+        let report = serde_json::from_str::<RunReport>(&written).unwrap();
+        assert_eq!(report.name, "smoke");
+        assert!(report.entries.is_empty());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+use anyhow::Context;
+
+use crate::report::{ReportEntry, ReportFuture, Reporter, RunReport};
+
+// Upper bounds of the latency histogram buckets, in seconds
+const BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Writes the metrics of the run to a file in the Prometheus text format, for the
+/// node exporter's textfile collector or a pushgateway.
+pub struct PrometheusReporter {
+    path: PathBuf,
+}
+
+impl PrometheusReporter {
+    pub fn new(path: PathBuf) -> PrometheusReporter {
+        return PrometheusReporter { path };
+    }
+}
+
+impl Reporter for PrometheusReporter {
+    fn finish<'a>(&'a mut self, report: &'a RunReport) -> ReportFuture<'a> {
+        return Box::pin(async move {
+            tokio::fs::write(&self.path, metrics(report))
+                .await
+                .with_context(|| format!("Failed to write metrics to {:?}", &self.path))?;
+            return Ok(());
+        });
+    }
+}
+
+// The report as metrics, requests that were skipped aren't counted
+pub fn metrics(report: &RunReport) -> String {
+    let run = escape(&report.name);
+    let mut requests = BTreeMap::<&str, Vec<&ReportEntry>>::new();
+    for entry in report.entries.iter().filter(|entry| !entry.skipped) {
+        requests.entry(&entry.name).or_default().push(entry);
+    }
+
+    let mut out = String::new();
+
+    out.push_str("# HELP dotapi_requests_total Requests sent, by response status.\n");
+    out.push_str("# TYPE dotapi_requests_total counter\n");
+    for (name, entries) in &requests {
+        let mut statuses = BTreeMap::<String, usize>::new();
+        for entry in entries {
+            let status = match entry.status {
+                Some(status) => status.to_string(),
+                None => "none".to_string(),
+            };
+            *statuses.entry(status).or_default() += 1;
+        }
+        for (status, count) in statuses {
+            let _ = writeln!(
+                out,
+                "dotapi_requests_total{{run=\"{}\",request=\"{}\",method=\"{}\",status=\"{}\"}} {}",
+                run,
+                escape(name),
+                escape(&entries[0].method),
+                status,
+                count
+            );
+        }
+    }
+
+    out.push_str("# HELP dotapi_request_errors_total Requests that failed the run.\n");
+    out.push_str("# TYPE dotapi_request_errors_total counter\n");
+    for (name, entries) in &requests {
+        let errors = entries.iter().filter(|entry| !entry.passed()).count();
+        let _ = writeln!(
+            out,
+            "dotapi_request_errors_total{{run=\"{}\",request=\"{}\"}} {}",
+            run,
+            escape(name),
+            errors
+        );
+    }
+
+    out.push_str("# HELP dotapi_request_duration_seconds Time requests took to be answered.\n");
+    out.push_str("# TYPE dotapi_request_duration_seconds histogram\n");
+    for (name, entries) in &requests {
+        let labels = format!("run=\"{}\",request=\"{}\"", run, escape(name));
+        let durations = entries
+            .iter()
+            .map(|entry| entry.duration_ms as f64 / 1000.0)
+            .collect::<Vec<f64>>();
+
+        for bound in BUCKETS {
+            let count = durations.iter().filter(|d| **d <= bound).count();
+            let _ = writeln!(
+                out,
+                "dotapi_request_duration_seconds_bucket{{{},le=\"{}\"}} {}",
+                labels, bound, count
+            );
+        }
+        let _ = writeln!(
+            out,
+            "dotapi_request_duration_seconds_bucket{{{},le=\"+Inf\"}} {}",
+            labels,
+            durations.len()
+        );
+        let _ = writeln!(
+            out,
+            "dotapi_request_duration_seconds_sum{{{}}} {}",
+            labels,
+            durations.iter().sum::<f64>()
+        );
+        let _ = writeln!(
+            out,
+            "dotapi_request_duration_seconds_count{{{}}} {}",
+            labels,
+            durations.len()
+        );
+    }
+
+    out.push_str("# HELP dotapi_run_duration_seconds Time the whole run took.\n");
+    out.push_str("# TYPE dotapi_run_duration_seconds gauge\n");
+    let _ = writeln!(
+        out,
+        "dotapi_run_duration_seconds{{run=\"{}\"}} {}",
+        run,
+        report.duration_ms as f64 / 1000.0
+    );
+
+    out.push_str("# HELP dotapi_run_passed Whether the run passed, 1 or 0.\n");
+    out.push_str("# TYPE dotapi_run_passed gauge\n");
+    let _ = writeln!(
+        out,
+        "dotapi_run_passed{{run=\"{}\"}} {}",
+        run,
+        report.passed() as u8
+    );
+
+    return out;
+}
+
+// Label values escape backslashes, quotes and newlines
+fn escape(value: &str) -> String {
+    return value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(
+        name: &str,
+        status: Option<u16>,
+        duration_ms: u64,
+        error: Option<&str>,
+    ) -> ReportEntry {
+        return ReportEntry {
+            name: name.to_string(),
+            method: "GET".to_string(),
+            url: format!("https://x.dev/{}", name),
+            status,
+            duration_ms,
+            error: error.map(|e| e.to_string()),
+            skipped: false,
+            allow_failure: false,
+            assertion_failures: vec![],
+            warnings: vec![],
+        };
+    }
+
+    #[test]
+    fn metrics_are_written_in_the_text_format() {
+        let report = RunReport {
+            name: "main \"nightly\"".to_string(),
+            environment: None,
+            entries: vec![
+                entry("users", Some(200), 30, None),
+                entry("users", Some(500), 700, Some("status")),
+                entry("login", None, 12000, Some("timed out")),
+                ReportEntry {
+                    skipped: true,
+                    ..entry("cleanup", None, 0, None)
+                },
+            ],
+            duration_ms: 12800,
+            attempts: 1,
+            cancelled: false,
+        };
+
+        // skipped requests aren't counted, label values are escaped
+        assert_eq!(
+            metrics(&report),
+            r#"# HELP dotapi_requests_total Requests sent, by response status.
+# TYPE dotapi_requests_total counter
+dotapi_requests_total{run="main \"nightly\"",request="login",method="GET",status="none"} 1
+dotapi_requests_total{run="main \"nightly\"",request="users",method="GET",status="200"} 1
+dotapi_requests_total{run="main \"nightly\"",request="users",method="GET",status="500"} 1
+# HELP dotapi_request_errors_total Requests that failed the run.
+# TYPE dotapi_request_errors_total counter
+dotapi_request_errors_total{run="main \"nightly\"",request="login"} 1
+dotapi_request_errors_total{run="main \"nightly\"",request="users"} 1
+# HELP dotapi_request_duration_seconds Time requests took to be answered.
+# TYPE dotapi_request_duration_seconds histogram
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.005"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.01"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.025"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.05"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.1"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.25"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="0.5"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="1"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="2.5"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="5"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="10"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="login",le="+Inf"} 1
+dotapi_request_duration_seconds_sum{run="main \"nightly\"",request="login"} 12
+dotapi_request_duration_seconds_count{run="main \"nightly\"",request="login"} 1
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.005"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.01"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.025"} 0
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.05"} 1
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.1"} 1
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.25"} 1
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="0.5"} 1
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="1"} 2
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="2.5"} 2
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="5"} 2
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="10"} 2
+dotapi_request_duration_seconds_bucket{run="main \"nightly\"",request="users",le="+Inf"} 2
+dotapi_request_duration_seconds_sum{run="main \"nightly\"",request="users"} 0.73
+dotapi_request_duration_seconds_count{run="main \"nightly\"",request="users"} 2
+# HELP dotapi_run_duration_seconds Time the whole run took.
+# TYPE dotapi_run_duration_seconds gauge
+dotapi_run_duration_seconds{run="main \"nightly\""} 12.8
+# HELP dotapi_run_passed Whether the run passed, 1 or 0.
+# TYPE dotapi_run_passed gauge
+dotapi_run_passed{run="main \"nightly\""} 0
+"#
+        );
+    }
+}
@@ -0,0 +1,2188 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use anyhow::Context;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::{
+    auth::sign_request,
+    body::{BodySerializers, binary::BinaryFormat, buffer_body, ndjson},
+    body_limit::{BodyOverflow, LimitedBody, read_body},
+    cache::HttpCache,
+    cancel::CancelToken,
+    chaos::Chaos,
+    checksum::add_checksums,
+    compression::{compress_body, decode},
+    contract::Contract,
+    environment::resolve_references,
+    expression::evaluate,
+    fixtures::FixtureMode,
+    fs::FileObject,
+    graphql::{GraphqlSchema, graphql_payload, is_persisted_query_not_found, read_query_file},
+    history::HistoryStore,
+    index::RequestIndex,
+    interpolation::{
+        EnvMap, UnresolvedError, interpolate_string, interpolate_value, placeholders,
+        referenced_responses,
+    },
+    middleware::RequestMiddleware,
+    paginate::Pages,
+    range::{Download, byteranges, parse_content_range, range_header},
+    rate_limit::RateLimiter,
+    report::{ReportEntry, Reporter},
+    schema::{
+        auth::AuthSchema,
+        calls::{FailurePolicySchema, SequenceDefinitionSchema},
+        http::HttpClientSchema,
+        interpolation::InterpolationModeSchema,
+        poll::PollSchema,
+        redirect::{RedirectPolicySchema, RedirectSchema},
+        request_body::{MultipartPartSchema, RequestBodySchema},
+        request_config::RequestConfigSchema,
+        roots::{ProjectRootSchema, RequestRootSchema},
+        soap::SoapVersionSchema,
+    },
+    snapshot::SnapshotMode,
+    state::StateStore,
+    tags::TagFilter,
+    telemetry::Tracer,
+    upload::Upload,
+    wire::{WireCapture, request_bytes, response_head},
+};
+
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+/// A request with every placeholder replaced by its value from the active environment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedRequest {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<RequestBodySchema>,
+    pub config: RequestConfigSchema,
+    // auth that has to sign the built request, header based auth is already in headers
+    pub auth: Option<AuthSchema>,
+    // folder of the file defining the request, relative paths in the body resolve from here
+    pub base_dir: PathBuf,
+    // placeholders that didn't resolve, left as is or emptied by a lenient interpolation mode
+    pub warnings: Vec<String>,
+    // the bytes of the binary body sent, when it's uploaded in chunks
+    pub chunk: Option<Range<u64>>,
+}
+
+impl ResolvedRequest {
+    pub fn get_header(&self, name: &str) -> Option<&String> {
+        return self
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value);
+    }
+
+    // Replaces the values of the header, whatever their case
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        self.remove_header(name);
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers
+            .retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+    }
+
+    // The key sent in the Idempotency-Key header, set in the headers or generated by the idempotency_key config
+    pub fn idempotency_key(&self) -> Option<&String> {
+        return self.get_header(IDEMPOTENCY_KEY);
+    }
+
+    // The request as a yaml value with name, method, url, headers and idempotency_key, for use in conditions
+    pub fn to_value(&self) -> serde_yaml::Value {
+        let mut headers = serde_yaml::Mapping::new();
+        for (key, value) in &self.headers {
+            headers.insert(
+                serde_yaml::Value::String(key.clone()),
+                serde_yaml::Value::String(value.clone()),
+            );
+        }
+
+        let mut value = serde_yaml::Mapping::new();
+        value.insert("name".into(), self.name.clone().into());
+        value.insert("method".into(), self.method.clone().into());
+        value.insert("url".into(), self.url.clone().into());
+        value.insert("headers".into(), serde_yaml::Value::Mapping(headers));
+        value.insert(
+            "idempotency_key".into(),
+            match self.idempotency_key() {
+                Some(key) => key.clone().into(),
+                None => serde_yaml::Value::Null,
+            },
+        );
+
+        return serde_yaml::Value::Mapping(value);
+    }
+
+    // The content type sent with the body, if any
+    pub fn content_type(&self) -> Option<String> {
+        if let Some(content_type) = self.get_header("content-type") {
+            return Some(content_type.clone());
+        }
+
+        return match &self.body {
+            Some(RequestBodySchema::Json { .. }) | Some(RequestBodySchema::Graphql { .. }) => {
+                Some("application/json".to_string())
+            }
+            Some(RequestBodySchema::Xml { .. }) => Some("application/xml".to_string()),
+            Some(RequestBodySchema::Soap {
+                action, version, ..
+            }) => Some(version.content_type(action.as_deref())),
+            Some(RequestBodySchema::Text { .. }) => Some("text/plain".to_string()),
+            Some(RequestBodySchema::FormUrlencoded { .. }) => {
+                Some("application/x-www-form-urlencoded".to_string())
+            }
+            Some(RequestBodySchema::Multipart { .. }) => Some("multipart/form-data".to_string()),
+            Some(RequestBodySchema::Binary { content_type, .. }) => Some(
+                content_type
+                    .clone()
+                    .unwrap_or("application/octet-stream".to_string()),
+            ),
+            Some(RequestBodySchema::Msgpack { .. }) => {
+                Some(BinaryFormat::Msgpack.content_type().to_string())
+            }
+            Some(RequestBodySchema::Cbor { .. }) => {
+                Some(BinaryFormat::Cbor.content_type().to_string())
+            }
+            Some(RequestBodySchema::Ndjson { .. }) => Some(ndjson::NDJSON_CONTENT_TYPE.to_string()),
+            // set by the serializer of the type when sent
+            Some(RequestBodySchema::Custom { .. }) | None => None,
+        };
+    }
+
+    // The body as text. Multipart and binary bodies have no text form and return None.
+    pub fn body_text(&self) -> Option<String> {
+        return match &self.body {
+            Some(RequestBodySchema::Json { content, .. }) => serde_json::to_string(content).ok(),
+            Some(RequestBodySchema::Graphql {
+                query,
+                variables,
+                persisted,
+                ..
+            }) => Some(graphql_payload(query, variables, *persisted, false).to_string()),
+            Some(RequestBodySchema::Soap {
+                content,
+                header,
+                version,
+                ..
+            }) => Some(soap_envelope(content, header.as_deref(), *version)),
+            Some(RequestBodySchema::Xml { content, .. })
+            | Some(RequestBodySchema::Text { content, .. })
+            | Some(RequestBodySchema::FormUrlencoded { content, .. }) => Some(content.clone()),
+            Some(RequestBodySchema::Ndjson { content, .. }) => ndjson::encode(content)
+                .ok()
+                .map(|bytes| String::from_utf8_lossy(&bytes).to_string()),
+            Some(RequestBodySchema::Multipart { .. })
+            | Some(RequestBodySchema::Binary { .. })
+            | Some(RequestBodySchema::Msgpack { .. })
+            | Some(RequestBodySchema::Cbor { .. })
+            | Some(RequestBodySchema::Custom { .. })
+            | None => None,
+        };
+    }
+}
+
+/// The response received for a request.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResponseObject {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+    // redirects followed to get this response, when the redirect policy is record
+    pub redirects: Vec<RedirectHop>,
+    // content-encoding of the body as received, and its size before decoding
+    pub encoding: Option<String>,
+    pub raw_size: usize,
+    // HIT, MISS or REVALIDATED when the request went through the http cache
+    pub cache: Option<String>,
+    // the pages followed and their items, when the request paginates
+    pub pages: Option<Pages>,
+    // the file sent, when the request uploads it in chunks
+    pub upload: Option<Upload>,
+    // the ranges fetched, when the request downloads the body in ranges
+    pub download: Option<Download>,
+    // set when the body was over the body limit, the body is then only the start of it
+    pub overflow: Option<BodyOverflow>,
+    // the messages as sent and received, when wire capture is enabled
+    pub wire: Option<WireCapture>,
+}
+
+/// A redirect followed while calling a request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RedirectHop {
+    pub status: u16,
+    pub url: String,
+    pub location: String,
+}
+
+impl ResponseObject {
+    pub fn text(&self) -> String {
+        return String::from_utf8_lossy(&self.body).to_string();
+    }
+
+    pub fn header(&self, name: &str) -> Option<&String> {
+        return self
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value);
+    }
+
+    pub fn json(&self) -> anyhow::Result<serde_json::Value> {
+        let content_type = self
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default();
+        if let Some(format) = BinaryFormat::from_content_type(content_type) {
+            return format.decode(&self.body);
+        }
+
+        // the lines, as a list
+        if ndjson::is_ndjson(content_type) {
+            let truncated = self.overflow == Some(BodyOverflow::Truncated);
+            return Ok(serde_json::Value::Array(ndjson::parse_lines(
+                &self.body, truncated,
+            )?));
+        }
+
+        return serde_json::from_slice(&self.body).context("Response body is not valid json");
+    }
+
+    // The response as a yaml value with status, headers, body, size, sha256, redirects
+    // and encoding details, plus pages and items when paginated, for use in conditions.
+    // Bodies over the body limit are truncated, with the file holding them as body_file.
+    // The body is parsed when it's json (or MessagePack, CBOR, a list of its lines for ndjson),
+    // kept as text otherwise.
+    pub fn to_value(&self) -> serde_yaml::Value {
+        let mut headers = serde_yaml::Mapping::new();
+        for (key, value) in &self.headers {
+            headers.insert(
+                serde_yaml::Value::String(key.clone()),
+                serde_yaml::Value::String(value.clone()),
+            );
+        }
+
+        let body = match self.json().ok().and_then(|b| serde_yaml::to_value(b).ok()) {
+            Some(body) => body,
+            None => serde_yaml::Value::String(self.text()),
+        };
+
+        let mut value = serde_yaml::Mapping::new();
+        value.insert("status".into(), self.status.into());
+        value.insert("headers".into(), serde_yaml::Value::Mapping(headers));
+        value.insert("body".into(), body);
+        value.insert("size".into(), (self.body.len() as u64).into());
+        value.insert("raw_size".into(), (self.raw_size as u64).into());
+        value.insert("compressed".into(), self.encoding.is_some().into());
+        value.insert(
+            "encoding".into(),
+            match &self.encoding {
+                Some(encoding) => encoding.clone().into(),
+                None => serde_yaml::Value::Null,
+            },
+        );
+        value.insert("sha256".into(), sha256(&self.body).into());
+        value.insert(
+            "cache".into(),
+            match &self.cache {
+                Some(cache) => cache.clone().into(),
+                None => serde_yaml::Value::Null,
+            },
+        );
+        value.insert(
+            "redirects".into(),
+            serde_yaml::to_value(&self.redirects).unwrap_or_default(),
+        );
+        value.insert("truncated".into(), self.overflow.is_some().into());
+        if let Some(BodyOverflow::File(path)) = &self.overflow {
+            value.insert(
+                "body_file".into(),
+                path.to_string_lossy().to_string().into(),
+            );
+        }
+        if let Some(upload) = &self.upload {
+            value.insert("upload".into(), upload.to_value());
+        }
+        if let Some(download) = &self.download {
+            value.insert("download".into(), download.to_value());
+        }
+        if let Some(range) = self
+            .header("content-range")
+            .and_then(|value| parse_content_range(value))
+        {
+            value.insert("content_range".into(), range.to_value());
+        }
+        if let Some(parts) = self
+            .header("content-type")
+            .and_then(|content_type| byteranges(content_type, &self.body))
+        {
+            value.insert("ranges".into(), serde_yaml::Value::Sequence(parts));
+        }
+        if let Some(pages) = &self.pages {
+            value.insert("pages".into(), pages.count.into());
+            value.insert(
+                "items".into(),
+                serde_yaml::Value::Sequence(pages.items.clone()),
+            );
+        }
+
+        return serde_yaml::Value::Mapping(value);
+    }
+}
+
+/// The outcome of calling a single request.
+#[derive(Debug, Clone)]
+pub struct CallResult {
+    pub request: ResolvedRequest,
+    pub response: ResponseObject,
+    pub duration: Duration,
+    // when the request was sent
+    pub started: chrono::DateTime<chrono::Utc>,
+}
+
+/// What a run of a sequence produced, for its last attempt.
+pub(crate) struct SequenceRun {
+    pub results: Vec<CallResult>,
+    // an entry for each step that ran and for the hook that failed
+    pub entries: Vec<ReportEntry>,
+    pub attempts: u32,
+    // the first failure that fails the sequence
+    pub outcome: anyhow::Result<()>,
+}
+
+/// Returned when a request is not called because its `when` condition is false.
+#[derive(Debug, Clone)]
+pub struct SkippedError {
+    pub name: String,
+    pub condition: String,
+}
+
+impl std::fmt::Display for SkippedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(
+            f,
+            "Request \"{}\" skipped, `{}` is false",
+            self.name, self.condition
+        );
+    }
+}
+
+impl std::error::Error for SkippedError {}
+
+pub struct Runner {
+    pub project: FileObject<ProjectRootSchema>,
+    // the requests loaded so far, the others are loaded from the index when they are called
+    pub requests: Vec<FileObject<RequestRootSchema>>,
+    pub index: Option<RequestIndex>,
+    pub environment: Option<String>,
+    pub fixture_mode: FixtureMode,
+    pub snapshot_mode: SnapshotMode,
+    // the openapi spec responses are checked against, when contract testing is enabled
+    pub contract: Option<Contract>,
+    // faults injected into the requests sent, when chaos is enabled
+    pub chaos: Option<Chaos>,
+    // results of requests already called in this run, keyed by request name
+    pub responses: HashMap<String, CallResult>,
+    // the requests as they were last sent, even when no response came back, for the reports
+    pub(crate) sent: HashMap<String, ResolvedRequest>,
+    // where called requests are recorded, if enabled
+    pub history: Option<HistoryStore>,
+    // variables given when starting the run (eg: --var on the cli), they take precedence over the env
+    pub variables: EnvMap,
+    // values set in this run, eg: by captures, they take precedence over the env
+    pub state: StateStore,
+    // the env resolved for the request being called, so values like {{uuid()}} are the same
+    // in what's sent, logged and redacted
+    call_env: Option<EnvMap>,
+    // selects the steps of the sequences called by their tags
+    pub tags: TagFilter,
+    // cancelling it aborts the call in flight and stops the run
+    pub cancel: CancelToken,
+    pub(crate) reporters: Vec<Box<dyn Reporter>>,
+    pub(crate) middlewares: Vec<Box<dyn RequestMiddleware>>,
+    // how bodies are sent, by their type
+    pub(crate) body_serializers: BodySerializers,
+    // records the spans of runs when telemetry is enabled
+    pub(crate) tracer: Option<Arc<Tracer>>,
+    // keeps the messages of every call as sent and received, see enable_wire_capture
+    pub capture_wire: bool,
+    pub http_cache: HttpCache,
+    pub rate_limiter: RateLimiter,
+    // introspected schemas of graphql endpoints, keyed by url
+    pub(crate) graphql_schemas: Mutex<HashMap<String, GraphqlSchema>>,
+    client: reqwest::Client,
+}
+
+impl Runner {
+    pub fn new(
+        project: FileObject<ProjectRootSchema>,
+        requests: Vec<FileObject<RequestRootSchema>>,
+    ) -> anyhow::Result<Runner> {
+        let state = match &project.object.state_file {
+            Some(file) => {
+                StateStore::persisted(project.get_root_dir().join(file)).unwrap_or_else(|e| {
+                    tracing::warn!("{:#}, starting with an empty state", e);
+                    StateStore::default()
+                })
+            }
+            None => StateStore::default(),
+        };
+
+        // settings that can't be used fail the runner, requests aren't sent without them
+        let client = client_builder(&project.object.http)
+            .build()
+            .context("Invalid http settings")?;
+
+        return Ok(Runner {
+            project,
+            requests,
+            index: None,
+            environment: None,
+            fixture_mode: FixtureMode::Off,
+            snapshot_mode: SnapshotMode::Compare,
+            contract: None,
+            chaos: None,
+            responses: HashMap::new(),
+            sent: HashMap::new(),
+            history: None,
+            variables: HashMap::new(),
+            state,
+            call_env: None,
+            tags: TagFilter::default(),
+            cancel: CancelToken::new(),
+            reporters: vec![],
+            middlewares: vec![],
+            body_serializers: BodySerializers::default(),
+            tracer: None,
+            capture_wire: false,
+            http_cache: HttpCache::default(),
+            rate_limiter: RateLimiter::default(),
+            graphql_schemas: Mutex::new(HashMap::new()),
+            client,
+        });
+    }
+
+    // Loads the project at path and lists its requests, they are parsed when they are first called
+    pub async fn load(path: &Path) -> anyhow::Result<Runner> {
+        let project = ProjectRootSchema::load(path).await?;
+        return Runner::from_project(project, vec![]).await;
+    }
+
+    // A runner with the requests already loaded, eg: ones being edited,
+    // the others of the project are loaded from its index when they are called
+    pub async fn from_project(
+        project: FileObject<ProjectRootSchema>,
+        requests: Vec<FileObject<RequestRootSchema>>,
+    ) -> anyhow::Result<Runner> {
+        let index = project.index_requests().await?;
+
+        let mut runner = Runner::new(project, requests)?;
+        runner.index = Some(index);
+        return Ok(runner);
+    }
+
+    // Loads a request from the index along with the requests it depends on, unless they are loaded.
+    // Names the index doesn't know are left for find_request to report.
+    pub async fn load_request(&mut self, name: &str) -> anyhow::Result<()> {
+        let mut pending = vec![name.to_string()];
+
+        while let Some(name) = pending.pop() {
+            if self.requests.iter().any(|r| r.get_call_name() == name) {
+                continue;
+            }
+
+            let request = match &mut self.index {
+                Some(index) if index.find(&name).is_some() => index.load(&name).await?,
+                _ => continue,
+            };
+            self.requests.push(request);
+
+            if let Some(config) = &self.find_request(&name)?.object.config {
+                pending.extend(config.require.iter().cloned());
+            }
+            pending.extend(self.referenced_requests(&name)?);
+        }
+
+        self.save_index_cache().await;
+        return Ok(());
+    }
+
+    // Loads every request of the index that isn't loaded yet, eg: to export the project
+    pub async fn load_requests(&mut self) -> anyhow::Result<()> {
+        let index = match &mut self.index {
+            Some(index) => index,
+            None => return Ok(()),
+        };
+
+        for request in index.load_all().await? {
+            let name = request.get_call_name();
+            if !self.requests.iter().any(|r| r.get_call_name() == name) {
+                self.requests.push(request);
+            }
+        }
+
+        self.save_index_cache().await;
+        return Ok(());
+    }
+
+    // Names of the requests of the project, loaded or not
+    pub fn request_names(&self) -> Vec<String> {
+        let mut names = self
+            .requests
+            .iter()
+            .map(|request| request.get_call_name())
+            .collect::<Vec<String>>();
+
+        for request in self.index.iter().flat_map(|index| &index.requests) {
+            if !names.contains(&request.name) {
+                names.push(request.name.clone());
+            }
+        }
+
+        return names;
+    }
+
+    fn has_request(&self, name: &str) -> bool {
+        return self.requests.iter().any(|r| r.get_call_name() == name)
+            || self
+                .index
+                .as_ref()
+                .is_some_and(|index| index.find(name).is_some());
+    }
+
+    // Keeps the requests parsed so far for the next run
+    async fn save_index_cache(&mut self) {
+        if let Some(index) = &mut self.index
+            && let Err(e) = index.save_cache().await
+        {
+            tracing::warn!("{:#}", e);
+        }
+    }
+
+    // The env of the run, values referencing other variables see the ones given with --var or set by the run.
+    // While a request is called, it's the env resolved for that call.
+    pub fn build_env(&self) -> EnvMap {
+        if let Some(env) = &self.call_env {
+            return env.clone();
+        }
+
+        let mut env = self.env_values();
+        // variables in a cycle keep their value as written, check_env reports the cycle
+        let _ = resolve_references(&mut env);
+        return env;
+    }
+
+    // The env of the run as written, see env_origins for the precedence of the values
+    pub(crate) fn env_values(&self) -> EnvMap {
+        return self
+            .env_origins()
+            .into_iter()
+            .map(|(name, (value, _))| (name, value))
+            .collect();
+    }
+
+    // The env with the responses of requests called in this run under `responses`,
+    // eg: responses.login.status, responses.login.body.token
+    pub fn build_context(&self) -> EnvMap {
+        let mut context = self.build_env();
+        let mut responses = serde_yaml::Mapping::new();
+
+        for (name, result) in &self.responses {
+            responses.insert(
+                serde_yaml::Value::String(name.clone()),
+                result.response.to_value(),
+            );
+        }
+
+        context.insert(
+            "responses".to_string(),
+            serde_yaml::Value::Mapping(responses),
+        );
+        return context;
+    }
+
+    pub fn find_request(&self, name: &str) -> anyhow::Result<&FileObject<RequestRootSchema>> {
+        return match self.requests.iter().find(|r| r.get_call_name() == name) {
+            Some(request) => Ok(request),
+            None => anyhow::bail!("Request \"{}\" does not exist", name),
+        };
+    }
+
+    pub fn get_sequence_definition(&self, name: &str) -> anyhow::Result<SequenceDefinitionSchema> {
+        let calls = &self.project.object.calls;
+
+        if name == "main" {
+            return Ok(calls.main.definition());
+        }
+
+        return match calls.overrides.get(name) {
+            Some(sequence) => Ok(sequence.definition()),
+            None => anyhow::bail!("Call sequence \"{}\" does not exist", name),
+        };
+    }
+
+    // Returns the request names in a call sequence
+    pub fn get_sequence(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        return Ok(self.get_sequence_definition(name)?.step_names());
+    }
+
+    // Resolves the placeholders of a request. Ones that don't resolve fail the request in strict mode,
+    // the request's mode or else the project's, and become a warning in the lenient modes.
+    pub fn resolve_request(&self, name: &str) -> anyhow::Result<ResolvedRequest> {
+        let mode = self
+            .find_request(name)?
+            .object
+            .interpolation
+            .unwrap_or(self.project.object.interpolation);
+        // responses of requests called before can be referenced, eg: {{responses.login.body.token}}
+        let mut env = self.build_context();
+        let mut warnings = vec![];
+
+        loop {
+            let error = match self.resolve_with(name, &env) {
+                Ok(mut resolved) => {
+                    resolved.warnings = warnings;
+                    return Ok(resolved);
+                }
+                Err(error) => error,
+            };
+
+            // the placeholder is given a value and the request resolved again
+            let unresolved = match error.downcast_ref::<UnresolvedError>() {
+                Some(unresolved)
+                    if mode != InterpolationModeSchema::Strict
+                        && !env.contains_key(&unresolved.name) =>
+                {
+                    unresolved.name.clone()
+                }
+                _ => return Err(error),
+            };
+
+            let (value, warning) = match mode {
+                InterpolationModeSchema::Empty => (
+                    String::new(),
+                    format!("Unresolved variable \"{}\" was left empty", unresolved),
+                ),
+                _ => (
+                    format!("{{{{{}}}}}", unresolved),
+                    format!("Unresolved variable \"{}\" was left as is", unresolved),
+                ),
+            };
+            warnings.push(warning);
+            env.insert(unresolved, serde_yaml::Value::String(value));
+        }
+    }
+
+    fn resolve_with(&self, name: &str, env: &EnvMap) -> anyhow::Result<ResolvedRequest> {
+        let request = self.find_request(name)?;
+        let schema = &request.object;
+
+        let mut url = interpolate_string(&schema.url, env)?;
+        if let Some(path_params) = &schema.path_params {
+            let mut params = HashMap::new();
+            for (key, value) in path_params {
+                params.insert(key.clone(), interpolate_string(value, env)?);
+            }
+            url = apply_path_params(&url, &params);
+        }
+
+        if !is_absolute_url(&url)
+            && let Some(base_url) = self.project.object.base_url(self.environment.as_deref())
+        {
+            url = join_url(&interpolate_string(&base_url, env)?, &url);
+        }
+
+        let mut url = reqwest::Url::parse(&url)
+            .with_context(|| format!("Invalid url for request \"{}\"", name))?;
+
+        if let Some(query) = schema.query.as_ref().filter(|query| !query.is_empty()) {
+            let mut pairs = url.query_pairs_mut();
+            for (key, value) in query {
+                pairs.append_pair(key, &interpolate_string(value, env)?);
+            }
+        }
+
+        let mut headers = vec![];
+        if let Some(schema_headers) = &schema.headers {
+            for (key, value) in schema_headers {
+                headers.push((key.clone(), interpolate_string(value, env)?));
+            }
+        }
+
+        let mut auth = match &schema.auth {
+            Some(auth) => Some(auth.interpolate(env)?),
+            None => None,
+        };
+
+        if let Some((key, value)) = auth.as_ref().and_then(|auth| auth.header()) {
+            if !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case(&key)) {
+                headers.push((key, value));
+            }
+            auth = None;
+        }
+
+        let base_dir = match request.path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::new(),
+        };
+
+        let body = match &schema.body {
+            Some(body) => Some(interpolate_body(&read_content_file(body, &base_dir)?, env)?),
+            None => None,
+        };
+
+        let mut config = schema.config.clone().unwrap_or_default();
+
+        let mut resolve = IndexMap::new();
+        for (host, address) in self.project.object.resolve.iter().chain(&config.resolve) {
+            resolve.insert(host.clone(), interpolate_string(address, env)?);
+        }
+        config.resolve = resolve;
+
+        if let Some(socket) = &config.unix_socket {
+            let socket = interpolate_string(socket, env)?;
+            config.unix_socket = Some(base_dir.join(socket).to_string_lossy().to_string());
+        }
+
+        if let Some(range) = &config.range
+            && !headers.iter().any(|(k, _)| k.eq_ignore_ascii_case("range"))
+        {
+            let ranges = range
+                .ranges()
+                .iter()
+                .map(|range| interpolate_string(range, env))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let header = range_header(&ranges)
+                .with_context(|| format!("Invalid range for request \"{}\"", name))?;
+            headers.push(("Range".to_string(), header));
+        }
+
+        // resolved once per call, so retries of the call send the same key
+        if config.idempotency_key
+            && !headers
+                .iter()
+                .any(|(k, _)| k.eq_ignore_ascii_case(IDEMPOTENCY_KEY))
+        {
+            headers.push((
+                IDEMPOTENCY_KEY.to_string(),
+                uuid::Uuid::new_v4().to_string(),
+            ));
+        }
+
+        return Ok(ResolvedRequest {
+            name: name.to_string(),
+            method: schema.method.to_uppercase(),
+            url: url.to_string(),
+            headers,
+            body,
+            config,
+            auth,
+            base_dir,
+            warnings: vec![],
+            chunk: None,
+        });
+    }
+
+    pub async fn build_request(
+        &self,
+        resolved: &ResolvedRequest,
+    ) -> anyhow::Result<reqwest::Request> {
+        let method = reqwest::Method::from_bytes(resolved.method.as_bytes())
+            .with_context(|| format!("Invalid method \"{}\"", resolved.method))?;
+
+        let mut builder = self.client.request(method, &resolved.url);
+
+        for (key, value) in &resolved.headers {
+            builder = builder.header(key, value);
+        }
+
+        if !resolved.config.accept_encoding.is_empty()
+            && resolved.get_header("accept-encoding").is_none()
+        {
+            builder = builder.header(
+                reqwest::header::ACCEPT_ENCODING,
+                resolved.config.accept_encoding.join(", "),
+            );
+        }
+
+        if let Some(timeout) = resolved.config.timeout {
+            builder = builder.timeout(Duration::from_millis(timeout as u64));
+        }
+
+        if let Some(body) = &resolved.body {
+            builder = self.apply_body(builder, body, resolved).await?;
+        }
+
+        let mut request = builder.build()?;
+        // before signing, so the signature covers the body as sent
+        if resolved.config.compress.is_some() || !resolved.config.checksums.is_empty() {
+            buffer_body(&mut request).await?;
+        }
+        if let Some(compression) = resolved.config.compress {
+            compress_body(&mut request, compression)
+                .with_context(|| format!("Failed to compress the body of \"{}\"", resolved.name))?;
+        }
+        add_checksums(&mut request, &resolved.config.checksums)?;
+        self.before_send(&resolved.name, &mut request)?;
+
+        if let Some(auth) = &resolved.auth {
+            sign_request(&mut request, auth)
+                .with_context(|| format!("Failed to sign request \"{}\"", resolved.name))?;
+        }
+
+        return Ok(request);
+    }
+
+    // Calls a request, calling the requests it requires first if they haven't been called in this run
+    pub async fn call_request(&mut self, name: &str) -> anyhow::Result<CallResult> {
+        self.sent.remove(name);
+        self.check_env()?;
+        self.load_request(name).await?;
+
+        for dependency in self.resolve_call_order(name)? {
+            if dependency != name && self.responses.contains_key(&dependency) {
+                continue;
+            }
+
+            self.call_env = Some(self.build_env());
+            let outcome = self.call_dependency(name, &dependency).await;
+            self.call_env = None;
+            outcome?;
+        }
+
+        return match self.responses.get(name) {
+            Some(result) => Ok(result.clone()),
+            None => anyhow::bail!("Request \"{}\" was not called", name),
+        };
+    }
+
+    // Calls a request of the call order of name, unless its `when` condition is false
+    async fn call_dependency(&mut self, name: &str, dependency: &str) -> anyhow::Result<()> {
+        if let Some(condition) = self.find_request(dependency)?.object.when.clone()
+            && !evaluate(&condition, &self.build_context())?
+        {
+            if dependency == name {
+                return Err(SkippedError {
+                    name: name.to_string(),
+                    condition,
+                }
+                .into());
+            }
+
+            tracing::info!("Skipping {}, `{}` is false", dependency, condition);
+            return Ok(());
+        }
+
+        let resolved = self.resolve_request(dependency)?;
+        self.sent.insert(dependency.to_string(), resolved.clone());
+
+        let result = self.cancel.run(self.send(dependency, resolved)).await?;
+        self.record_history(&result).await;
+        self.responses
+            .insert(dependency.to_string(), result.clone());
+        self.check_response(dependency, &result).await?;
+        return Ok(());
+    }
+
+    // Calls every step of a sequence, following its failure policy
+    pub async fn call_sequence(&mut self, name: &str) -> anyhow::Result<Vec<CallResult>> {
+        let definition = self.get_sequence_definition(name)?;
+        let run = self.sequence(name, &definition).await;
+
+        run.outcome?;
+        return Ok(run.results);
+    }
+
+    // Runs a sequence in its context, again while it fails and has attempts left.
+    // Each attempt starts from the responses there were when the sequence was entered.
+    // Used by call_sequence and by the runs that are reported.
+    pub(crate) async fn sequence(
+        &mut self,
+        name: &str,
+        definition: &SequenceDefinitionSchema,
+    ) -> SequenceRun {
+        let attempts = definition.attempts();
+        let mut attempt = 1;
+
+        let parent = self.enter_context(definition.context);
+        // each attempt starts from the responses and state from before the first one
+        let responses = self.responses.clone();
+        let state = self.state.snapshot();
+        let run = loop {
+            let mut run = self.sequence_once(name, definition).await;
+            run.attempts = attempt;
+
+            match &run.outcome {
+                Err(e) if attempt < attempts && !self.is_cancelled() => {
+                    let error = self.redact_text(&format!("{:#}", e));
+                    tracing::warn!("{} failed ({}), running it again", name, error);
+                    self.responses = responses.clone();
+                    if let Err(e) = self.state.restore(state.clone()).await {
+                        run.outcome = Err(e);
+                        break run;
+                    }
+                    attempt += 1;
+                }
+                _ => break run,
+            };
+        };
+        self.exit_context(parent);
+
+        return run;
+    }
+
+    async fn sequence_once(
+        &mut self,
+        name: &str,
+        definition: &SequenceDefinitionSchema,
+    ) -> SequenceRun {
+        let mut run = SequenceRun {
+            results: vec![],
+            entries: vec![],
+            attempts: 1,
+            outcome: Ok(()),
+        };
+
+        for hook in &definition.before {
+            if let Err(e) = self.run_hooks(std::slice::from_ref(hook)).await {
+                let entry = self.hook_entry(hook, &e);
+                self.report_entry(&entry);
+                run.entries.push(entry);
+                run.outcome = Err(e);
+                break;
+            }
+        }
+
+        if run.outcome.is_ok() {
+            for step in &definition.steps {
+                // a request that doesn't load is only selected by the step's tags, and fails when called
+                let _ = self.load_request(step.name()).await;
+                if !self.is_selected(step) {
+                    continue;
+                }
+
+                let result = self.call_request(step.name()).await;
+                let mut entry = self.step_entry(step.name(), &result);
+                entry.allow_failure = step.allow_failure();
+                self.report_entry(&entry);
+                run.entries.push(entry);
+
+                match result {
+                    Ok(result) => run.results.push(result),
+                    Err(e) if e.is::<SkippedError>() => tracing::info!("{}", e),
+                    Err(e) if step.allow_failure() => {
+                        let error = self.redact_text(&format!("{:#}", e));
+                        tracing::warn!("{} failed, failure allowed: {}", step.name(), error)
+                    }
+                    Err(e) => {
+                        let error = self.redact_text(&format!("{:#}", e));
+                        tracing::error!("{} failed: {}", step.name(), error);
+                        if run.outcome.is_ok() {
+                            run.outcome = Err(e);
+                        }
+
+                        if definition.on_failure != FailurePolicySchema::Continue {
+                            break;
+                        }
+                    }
+                };
+
+                if self.is_cancelled() {
+                    break;
+                }
+            }
+        }
+
+        // teardown runs even when a step failed or the run was cancelled. Once cancelled, it runs
+        // with a token of its own, the cancelled one would stop its requests too.
+        let cancelled = match self.is_cancelled() {
+            true => Some(std::mem::replace(&mut self.cancel, CancelToken::new())),
+            false => None,
+        };
+        if cancelled.is_some() && run.outcome.is_ok() {
+            run.outcome = Err(anyhow::anyhow!("{} was cancelled", name));
+        }
+
+        for hook in &definition.after {
+            if let Err(e) = self.run_hooks(std::slice::from_ref(hook)).await {
+                let entry = self.hook_entry(hook, &e);
+                self.report_entry(&entry);
+                run.entries.push(entry);
+
+                match run.outcome {
+                    Ok(()) => run.outcome = Err(e),
+                    Err(_) => {
+                        let error = self.redact_text(&format!("{:#}", e));
+                        tracing::error!("Teardown of {} failed: {}", name, error);
+                    }
+                };
+                break;
+            }
+        }
+
+        if let Some(cancel) = cancelled {
+            self.cancel = cancel;
+        }
+
+        return run;
+    }
+
+    // Returns the requests to call (dependencies first) in order to call the named request
+    pub(crate) fn resolve_call_order(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let mut order = vec![];
+        let mut visiting = vec![];
+        self.visit_dependencies(name, &mut visiting, &mut order)?;
+        return Ok(order);
+    }
+
+    fn visit_dependencies(
+        &self,
+        name: &str,
+        visiting: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> anyhow::Result<()> {
+        if order.iter().any(|n| n == name) {
+            return Ok(());
+        }
+
+        if visiting.iter().any(|n| n == name) {
+            visiting.push(name.to_string());
+            anyhow::bail!("Circular request dependency: {}", visiting.join(" -> "));
+        }
+
+        visiting.push(name.to_string());
+
+        let request = self.find_request(name)?;
+        if let Some(config) = &request.object.config {
+            for dependency in &config.require {
+                self.visit_dependencies(dependency, visiting, order)?;
+            }
+        }
+
+        // a request reading the response of another one requires it
+        for dependency in self.referenced_requests(name)? {
+            self.visit_dependencies(&dependency, visiting, order)?;
+        }
+
+        visiting.pop();
+        order.push(name.to_string());
+        return Ok(());
+    }
+
+    // Requests whose responses a request's placeholders read, eg: login for {{responses.login.body.token}}
+    fn referenced_requests(&self, name: &str) -> anyhow::Result<Vec<String>> {
+        let content = serde_yaml::to_string(&self.find_request(name)?.object)?;
+        let mut names = vec![];
+
+        for key in placeholders(&content) {
+            for referenced in referenced_responses(&key) {
+                if referenced != name
+                    && self.has_request(&referenced)
+                    && !names.contains(&referenced)
+                {
+                    names.push(referenced);
+                }
+            }
+        }
+
+        return Ok(names);
+    }
+
+    async fn send(&self, name: &str, resolved: ResolvedRequest) -> anyhow::Result<CallResult> {
+        let started = chrono::Utc::now();
+
+        if self.fixture_mode == FixtureMode::Replay {
+            let response = self.read_fixture(name).await?;
+            tracing::info!("Replayed {} from fixture", name);
+            return Ok(CallResult {
+                request: resolved,
+                response,
+                duration: Duration::ZERO,
+                started,
+            });
+        }
+
+        if let Some(delay) = resolved.config.delay {
+            tokio::time::sleep(Duration::from_millis(delay as u64)).await;
+        }
+
+        let (mut response, mut duration) = if let Some(upload) = &resolved.config.upload {
+            self.upload(name, &resolved, upload).await?
+        } else if let Some(download) = &resolved.config.download {
+            self.download_ranges(name, &resolved, download).await?
+        } else {
+            self.execute_with_retries(name, &resolved).await?
+        };
+
+        if let Some(poll) = &resolved.config.poll {
+            let polling = Instant::now();
+            let mut attempt = 1;
+
+            while !self.poll_done(poll, &response)? {
+                if attempt >= poll.max_attempts {
+                    anyhow::bail!(
+                        "Request \"{}\" did not satisfy `{}` after {} attempts",
+                        name,
+                        poll.until,
+                        attempt
+                    );
+                }
+
+                tracing::info!("{} not done polling, attempt {}", name, attempt);
+                tokio::time::sleep(Duration::from_millis(poll.interval as u64)).await;
+                (response, _) = self.execute_with_retries(name, &resolved).await?;
+                attempt += 1;
+            }
+
+            duration += polling.elapsed();
+        }
+
+        if let Some(paginate) = &resolved.config.paginate {
+            let elapsed;
+            (response, elapsed) = self
+                .follow_pages(name, &resolved, paginate, response)
+                .await?;
+            duration += elapsed;
+        }
+
+        let url = self.redact_text(&resolved.url);
+        tracing::info!(
+            request = name,
+            method = %resolved.method,
+            url = %url,
+            status = response.status,
+            duration_ms = duration.as_millis() as u64,
+            "{} {} -> {}",
+            resolved.method,
+            url,
+            response.status
+        );
+
+        match self.fixture_mode {
+            FixtureMode::Record => self.write_fixture(name, &response).await?,
+            FixtureMode::Verify => self.verify_fixture(name, &response).await?,
+            _ => {}
+        };
+
+        return Ok(CallResult {
+            request: resolved,
+            response,
+            duration,
+            started,
+        });
+    }
+
+    pub(crate) async fn execute_with_retries(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        let mut attempt = 0;
+
+        loop {
+            self.wait_for_rate_limit(resolved).await;
+            let started = Instant::now();
+
+            let retrying = attempt < resolved.config.retries;
+            match self.execute_with_faults(resolved).await {
+                Ok(response)
+                    if retrying && resolved.config.retry_statuses.contains(&response.status) =>
+                {
+                    attempt += 1;
+                    tracing::warn!(
+                        request = name,
+                        attempt,
+                        "{} answered {}, retrying {}",
+                        name,
+                        response.status,
+                        attempt
+                    );
+                }
+                Ok(response) => return Ok((response, started.elapsed())),
+                Err(e) if retrying && is_transient(&e) => {
+                    attempt += 1;
+                    let error = self.redact_text(&e.to_string());
+                    tracing::warn!(
+                        request = name,
+                        attempt,
+                        "{} failed ({}), retrying {}",
+                        name,
+                        error,
+                        attempt
+                    );
+                }
+                Err(e) => return Err(e.context(format!("Request \"{}\" failed", name))),
+            }
+        }
+    }
+
+    // Evaluates the poll condition with the latest response available as `response`
+    fn poll_done(&self, poll: &PollSchema, response: &ResponseObject) -> anyhow::Result<bool> {
+        let mut context = self.build_context();
+        context.insert("response".to_string(), response.to_value());
+
+        return evaluate(&poll.until, &context)
+            .with_context(|| format!("Invalid poll condition `{}`", poll.until));
+    }
+
+    pub(crate) async fn execute(
+        &self,
+        resolved: &ResolvedRequest,
+    ) -> anyhow::Result<ResponseObject> {
+        let config = &resolved.config;
+        let redirects = Arc::new(Mutex::new(Vec::<RedirectHop>::new()));
+
+        let client = if config.insecure
+            || config.redirect.is_some()
+            || !config.resolve.is_empty()
+            || config.unix_socket.is_some()
+        {
+            let mut builder = client_builder(&self.project.object.http)
+                .danger_accept_invalid_certs(config.insecure);
+
+            if let Some(redirect) = &config.redirect {
+                builder = builder.redirect(redirect_policy(redirect, redirects.clone()));
+            }
+
+            // the port of the address is ignored by reqwest, the one in the url is used
+            for (host, address) in &config.resolve {
+                let ip = address
+                    .parse::<IpAddr>()
+                    .with_context(|| format!("Invalid ip \"{}\" for {}", address, host))?;
+                builder = builder.resolve(host, SocketAddr::new(ip, 0));
+            }
+
+            if let Some(socket) = &config.unix_socket {
+                builder = with_unix_socket(builder, socket)?;
+            }
+
+            builder.build()?
+        } else {
+            self.client.clone()
+        };
+
+        let cache_key = self.cache_key(resolved);
+        if let Some(cached) = cache_key
+            .as_ref()
+            .and_then(|key| self.http_cache.fresh(key, &resolved.headers))
+        {
+            return Ok(cached);
+        }
+
+        self.validate_graphql(resolved).await?;
+
+        let mut request = self.build_request(resolved).await?;
+        if let Some(key) = &cache_key {
+            self.http_cache
+                .add_validators(key, &resolved.headers, &mut request);
+        }
+
+        tracing::debug!(
+            request = %resolved.name,
+            "> {} {}\n{}",
+            request.method(),
+            self.redact_text(request.url().as_str()),
+            self.redact_header_lines(request.headers())
+        );
+        let capture_wire = self.capture_wire || config.capture_wire;
+        let mut wire_request = capture_wire.then(|| request_bytes(&request));
+        let mut response = client.execute(request).await?;
+
+        // digest and ntlm answer the challenge of the first response, on the same client
+        if let Some(auth) = &resolved.auth
+            && let Some(challenge) = challenge(auth, &response)
+        {
+            response.bytes().await?;
+
+            let mut request = self.build_request(resolved).await?;
+            let authorization = auth.answer_challenge(&request, &challenge)?;
+            request
+                .headers_mut()
+                .insert(reqwest::header::AUTHORIZATION, authorization.parse()?);
+
+            if capture_wire {
+                wire_request = Some(request_bytes(&request));
+            }
+            response = client.execute(request).await?;
+        }
+
+        let status = response.status().as_u16();
+        let wire_head = capture_wire.then(|| response_head(&response));
+        tracing::debug!(
+            request = %resolved.name,
+            "< {}\n{}",
+            response.status(),
+            self.redact_header_lines(response.headers())
+        );
+
+        let mut headers = HashMap::<String, String>::new();
+        for (key, value) in response.headers() {
+            let value = String::from_utf8_lossy(value.as_bytes()).to_string();
+            headers
+                .entry(key.as_str().to_string())
+                .and_modify(|existing| {
+                    existing.push_str(", ");
+                    existing.push_str(&value);
+                })
+                .or_insert(value);
+        }
+
+        let LimitedBody {
+            bytes: raw,
+            size: raw_size,
+            overflow,
+        } = read_body(response, self.body_limit(resolved)).await?;
+
+        let wire = match (wire_request, wire_head) {
+            (Some(request), Some(mut response)) => {
+                response.extend_from_slice(&raw);
+                Some(WireCapture { request, response })
+            }
+            _ => None,
+        };
+
+        let encoding = headers
+            .get("content-encoding")
+            .filter(|encoding| !encoding.trim().eq_ignore_ascii_case("identity"))
+            .cloned();
+
+        // the start of a compressed body can't be decoded on its own
+        let body = match &encoding {
+            Some(encoding) if !config.skip_decompression && overflow.is_none() => {
+                decode(&raw, encoding).context("Failed to decode response body")?
+            }
+            _ => raw,
+        };
+
+        let redirects = match redirects.lock() {
+            Ok(redirects) => redirects.clone(),
+            Err(_) => vec![],
+        };
+
+        let mut response = ResponseObject {
+            status,
+            headers,
+            body,
+            redirects,
+            encoding,
+            raw_size,
+            cache: None,
+            pages: None,
+            upload: None,
+            download: None,
+            overflow,
+            wire,
+        };
+
+        // the server doesn't know the persisted query yet, it's registered by sending it with its hash
+        if let Some(RequestBodySchema::Graphql {
+            query,
+            variables,
+            persisted: true,
+            ..
+        }) = &resolved.body
+            && is_persisted_query_not_found(&response.body)
+        {
+            let mut register = resolved.clone();
+            register.body = Some(RequestBodySchema::Json {
+                content: serde_yaml::to_value(graphql_payload(query, variables, true, true))?,
+                content_file: None,
+            });
+            return Box::pin(self.execute(&register)).await;
+        }
+
+        if let Some(key) = cache_key.as_ref().filter(|_| response.overflow.is_none()) {
+            response = self.http_cache.store(key, &resolved.headers, response);
+        }
+        self.after_receive(&resolved.name, &mut response)?;
+
+        return Ok(response);
+    }
+}
+
+// A client builder with the connection settings of the project
+// Whether a call failed in a way that may pass when sent again: the connection couldn't be made or was lost,
+// or the request timed out. Invalid urls, unresolved placeholders and tls settings fail the same way every time.
+fn is_transient(error: &anyhow::Error) -> bool {
+    return error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_connect() || e.is_timeout();
+        }
+
+        if let Some(e) = cause.downcast_ref::<std::io::Error>() {
+            return matches!(
+                e.kind(),
+                std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::TimedOut
+            );
+        }
+
+        return false;
+    });
+}
+
+fn client_builder(http: &HttpClientSchema) -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(max) = http.pool_max_idle_per_host {
+        builder = builder.pool_max_idle_per_host(max);
+    }
+    if let Some(timeout) = http.pool_idle_timeout {
+        builder = builder.pool_idle_timeout(Duration::from_millis(timeout as u64));
+    }
+    if let Some(interval) = http.tcp_keepalive {
+        builder = builder.tcp_keepalive(Duration::from_millis(interval as u64));
+    }
+    if let Some(timeout) = http.connect_timeout {
+        builder = builder.connect_timeout(Duration::from_millis(timeout as u64));
+    }
+    if let Some(nodelay) = http.tcp_nodelay {
+        builder = builder.tcp_nodelay(nodelay);
+    }
+
+    return builder;
+}
+
+// The parameters of the challenge for the auth's scheme, from a 401 response
+fn challenge(auth: &AuthSchema, response: &reqwest::Response) -> Option<String> {
+    let scheme = auth.challenge_scheme()?;
+    if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+        return None;
+    }
+
+    return response
+        .headers()
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|value| {
+            let (name, params) = value.trim().split_once(' ')?;
+            if name.eq_ignore_ascii_case(scheme) {
+                Some(params.trim().to_string())
+            } else {
+                None
+            }
+        });
+}
+
+// The url's host is still sent in the host header, the connection goes to the socket
+#[cfg(unix)]
+fn with_unix_socket(
+    builder: reqwest::ClientBuilder,
+    socket: &str,
+) -> anyhow::Result<reqwest::ClientBuilder> {
+    return Ok(builder.unix_socket(socket));
+}
+
+#[cfg(not(unix))]
+fn with_unix_socket(
+    _builder: reqwest::ClientBuilder,
+    socket: &str,
+) -> anyhow::Result<reqwest::ClientBuilder> {
+    anyhow::bail!(
+        "Unix sockets are not supported on this platform ({})",
+        socket
+    );
+}
+
+fn redirect_policy(
+    redirect: &RedirectSchema,
+    hops: Arc<Mutex<Vec<RedirectHop>>>,
+) -> reqwest::redirect::Policy {
+    let (policy, max) = (redirect.policy, redirect.max);
+
+    return match policy {
+        RedirectPolicySchema::None => reqwest::redirect::Policy::none(),
+        RedirectPolicySchema::Follow => reqwest::redirect::Policy::limited(max),
+        RedirectPolicySchema::Record => reqwest::redirect::Policy::custom(move |attempt| {
+            if let Ok(mut hops) = hops.lock() {
+                hops.push(RedirectHop {
+                    status: attempt.status().as_u16(),
+                    url: attempt
+                        .previous()
+                        .last()
+                        .map(|url| url.to_string())
+                        .unwrap_or_default(),
+                    location: attempt.url().to_string(),
+                });
+            }
+
+            if attempt.previous().len() > max {
+                attempt.error(format!("Too many redirects (max {})", max))
+            } else {
+                attempt.follow()
+            }
+        }),
+    };
+}
+
+// Whether the url has a scheme, a :// in its query (eg: ?next=https://...) doesn't count
+pub fn is_absolute_url(url: &str) -> bool {
+    return url
+        .split(['?', '#'])
+        .next()
+        .is_some_and(|path| path.contains("://"));
+}
+
+// Joins a base url and a relative url, keeping the path of the base url,
+// eg: https://api.example.com/v1 and /users gives https://api.example.com/v1/users.
+// An absolute url is returned as is.
+pub fn join_url(base_url: &str, url: &str) -> String {
+    if is_absolute_url(url) {
+        return url.to_string();
+    }
+
+    if url.is_empty() {
+        return base_url.to_string();
+    }
+
+    if url.starts_with('?') {
+        return format!("{}{}", base_url, url);
+    }
+
+    return format!(
+        "{}/{}",
+        base_url.trim_end_matches('/'),
+        url.trim_start_matches('/')
+    );
+}
+
+// Replaces the `:name` segments in the path of a url with their url encoded value,
+// eg: https://api.example.com/users/:id
+pub fn apply_path_params(url: &str, params: &HashMap<String, String>) -> String {
+    let path_start = match url.find("://") {
+        Some(scheme_end) => url[scheme_end + 3..]
+            .find('/')
+            .map(|index| scheme_end + 3 + index)
+            .unwrap_or(url.len()),
+        None => 0,
+    };
+
+    let (origin, rest) = url.split_at(path_start);
+    let (path, suffix) = match rest.find(['?', '#']) {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+
+    let path = path
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix(':').and_then(|name| params.get(name)) {
+                Some(value) => {
+                    percent_encoding::utf8_percent_encode(value, PATH_SEGMENT).to_string()
+                }
+                None => segment.to_string(),
+            },
+        )
+        .collect::<Vec<String>>()
+        .join("/");
+
+    return format!("{}{}{}", origin, path, suffix);
+}
+
+// Characters encoded in a path segment, as defined by the url standard
+const PATH_SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'`')
+    .add(b'{')
+    .add(b'}')
+    .add(b'/')
+    .add(b'%');
+
+// Hex encoded sha256 checksum
+pub fn sha256(content: &[u8]) -> String {
+    return format!("{:x}", sha2::Sha256::digest(content));
+}
+
+// Wraps the payload of a soap body in an envelope of its version
+pub fn soap_envelope(content: &str, header: Option<&str>, version: SoapVersionSchema) -> String {
+    let header = match header {
+        Some(header) => format!("<soap:Header>{}</soap:Header>", header),
+        None => String::new(),
+    };
+
+    return format!(
+        "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<soap:Envelope xmlns:soap=\"{}\">{}<soap:Body>{}</soap:Body></soap:Envelope>",
+        version.namespace(),
+        header,
+        content.trim()
+    );
+}
+
+// Replaces the content of a body with the content of its content_file, if it has one
+pub(crate) fn read_content_file(
+    body: &RequestBodySchema,
+    base_dir: &Path,
+) -> anyhow::Result<RequestBodySchema> {
+    let read = |path: &String| -> anyhow::Result<String> {
+        let path = base_dir.join(path);
+        return std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read body file {:?}", &path));
+    };
+
+    return Ok(match body {
+        RequestBodySchema::Json {
+            content_file: Some(path),
+            ..
+        } => RequestBodySchema::Json {
+            content: serde_yaml::from_str(&read(path)?)
+                .with_context(|| format!("Body file \"{}\" is not valid json", path))?,
+            content_file: None,
+        },
+        RequestBodySchema::Msgpack {
+            content_file: Some(path),
+            ..
+        } => RequestBodySchema::Msgpack {
+            content: serde_yaml::from_str(&read(path)?)
+                .with_context(|| format!("Body file \"{}\" is not valid json or yaml", path))?,
+            content_file: None,
+        },
+        RequestBodySchema::Cbor {
+            content_file: Some(path),
+            ..
+        } => RequestBodySchema::Cbor {
+            content: serde_yaml::from_str(&read(path)?)
+                .with_context(|| format!("Body file \"{}\" is not valid json or yaml", path))?,
+            content_file: None,
+        },
+        RequestBodySchema::Ndjson {
+            content_file: Some(path),
+            ..
+        } => RequestBodySchema::Ndjson {
+            content: ndjson::read_rows(&read(path)?, path)?,
+            content_file: None,
+        },
+        RequestBodySchema::Xml {
+            content_file: Some(path),
+            ..
+        } => RequestBodySchema::Xml {
+            content: read(path)?,
+            content_file: None,
+        },
+        RequestBodySchema::Text {
+            content_file: Some(path),
+            ..
+        } => RequestBodySchema::Text {
+            content: read(path)?,
+            content_file: None,
+        },
+        RequestBodySchema::Soap {
+            content_file: Some(path),
+            header,
+            action,
+            version,
+            ..
+        } => RequestBodySchema::Soap {
+            content: read(path)?,
+            content_file: None,
+            header: header.clone(),
+            action: action.clone(),
+            version: *version,
+        },
+        RequestBodySchema::FormUrlencoded {
+            content_file: Some(path),
+            fields,
+            ..
+        } => RequestBodySchema::FormUrlencoded {
+            content: read(path)?,
+            content_file: None,
+            fields: fields.clone(),
+        },
+        RequestBodySchema::Graphql {
+            query_file: Some(path),
+            variables,
+            validate,
+            persisted,
+            ..
+        } => RequestBodySchema::Graphql {
+            query: read_query_file(&base_dir.join(path))?,
+            query_file: None,
+            variables: variables.clone(),
+            validate: *validate,
+            persisted: *persisted,
+        },
+        other => other.clone(),
+    });
+}
+
+fn interpolate_body(body: &RequestBodySchema, env: &EnvMap) -> anyhow::Result<RequestBodySchema> {
+    return Ok(match body {
+        RequestBodySchema::Json {
+            content,
+            content_file,
+        } => RequestBodySchema::Json {
+            content: interpolate_value(content, env)?,
+            content_file: content_file.clone(),
+        },
+        RequestBodySchema::Graphql {
+            query,
+            query_file,
+            variables,
+            validate,
+            persisted,
+        } => RequestBodySchema::Graphql {
+            query: query.clone(),
+            query_file: query_file.clone(),
+            variables: match variables {
+                Some(variables) => Some(interpolate_value(variables, env)?),
+                None => None,
+            },
+            validate: *validate,
+            persisted: *persisted,
+        },
+        RequestBodySchema::Xml {
+            content,
+            content_file,
+        } => RequestBodySchema::Xml {
+            content: interpolate_string(content, env)?,
+            content_file: content_file.clone(),
+        },
+        RequestBodySchema::Text {
+            content,
+            content_file,
+        } => RequestBodySchema::Text {
+            content: interpolate_string(content, env)?,
+            content_file: content_file.clone(),
+        },
+        RequestBodySchema::Soap {
+            content,
+            content_file,
+            header,
+            action,
+            version,
+        } => RequestBodySchema::Soap {
+            content: interpolate_string(content, env)?,
+            content_file: content_file.clone(),
+            header: match header {
+                Some(header) => Some(interpolate_string(header, env)?),
+                None => None,
+            },
+            action: match action {
+                Some(action) => Some(interpolate_string(action, env)?),
+                None => None,
+            },
+            version: *version,
+        },
+        RequestBodySchema::FormUrlencoded {
+            content,
+            content_file,
+            fields,
+        } => {
+            let mut content = interpolate_string(content, env)?;
+
+            // fields are interpolated one by one before being encoded,
+            // so values from the env don't need to be url encoded
+            if let Some(fields) = fields.as_ref().filter(|fields| !fields.is_empty()) {
+                let mut keys = fields.keys().collect::<Vec<&String>>();
+                keys.sort();
+
+                let mut serializer = url::form_urlencoded::Serializer::new(String::new());
+                for key in keys {
+                    serializer.append_pair(key, &interpolate_string(&fields[key], env)?);
+                }
+
+                let encoded = serializer.finish();
+                content = if content.is_empty() {
+                    encoded
+                } else {
+                    format!("{}&{}", content, encoded)
+                };
+            }
+
+            RequestBodySchema::FormUrlencoded {
+                content,
+                content_file: content_file.clone(),
+                fields: None,
+            }
+        }
+        RequestBodySchema::Multipart { parts } => RequestBodySchema::Multipart {
+            parts: parts
+                .iter()
+                .map(|part| -> anyhow::Result<MultipartPartSchema> {
+                    Ok(match part {
+                        MultipartPartSchema::Field { name, value } => MultipartPartSchema::Field {
+                            name: name.clone(),
+                            value: interpolate_string(value, env)?,
+                        },
+                        MultipartPartSchema::File {
+                            name,
+                            path,
+                            mime_type,
+                        } => MultipartPartSchema::File {
+                            name: name.clone(),
+                            path: interpolate_string(path, env)?,
+                            mime_type: mime_type.clone(),
+                        },
+                    })
+                })
+                .collect::<anyhow::Result<Vec<MultipartPartSchema>>>()?,
+        },
+        RequestBodySchema::Binary { path, content_type } => RequestBodySchema::Binary {
+            path: interpolate_string(path, env)?,
+            content_type: content_type.clone(),
+        },
+        RequestBodySchema::Msgpack {
+            content,
+            content_file,
+        } => RequestBodySchema::Msgpack {
+            content: interpolate_value(content, env)?,
+            content_file: content_file.clone(),
+        },
+        RequestBodySchema::Cbor {
+            content,
+            content_file,
+        } => RequestBodySchema::Cbor {
+            content: interpolate_value(content, env)?,
+            content_file: content_file.clone(),
+        },
+        RequestBodySchema::Ndjson {
+            content,
+            content_file,
+        } => RequestBodySchema::Ndjson {
+            content: content
+                .iter()
+                .map(|row| interpolate_value(row, env))
+                .collect::<anyhow::Result<_>>()?,
+            content_file: content_file.clone(),
+        },
+        RequestBodySchema::Custom { kind, fields } => RequestBodySchema::Custom {
+            kind: kind.clone(),
+            fields: fields
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), interpolate_value(value, env)?)))
+                .collect::<anyhow::Result<_>>()?,
+        },
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+
+    // Serves {"n": <count>} to every request, counting them from 1
+    async fn serve_count() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await;
+
+                let body = format!("{{\"n\": {}}}", count.fetch_add(1, Ordering::SeqCst) + 1);
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                    body.len(),
+                    body
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return format!("http://{}", address);
+    }
+
+    // A runner with these requests, each given as its yaml
+    fn runner(requests: &[(&str, String)]) -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-runner-{}", uuid::Uuid::new_v4()));
+
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            requests
+                .iter()
+                .map(|(name, yaml)| {
+                    FileObject::new(
+                        root.join(format!("requests/{}.nd", name)),
+                        serde_yaml::from_str::<RequestRootSchema>(yaml).unwrap(),
+                    )
+                })
+                .collect(),
+        )
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_retry_starts_without_the_state_of_the_failed_attempt() {
+        let url = serve_count().await;
+        let mut runner = runner(&[
+            (
+                "check",
+                format!(
+                    "method: GET\nurl: {}/check\nassert: [\"token == null\"]",
+                    url
+                ),
+            ),
+            (
+                "login",
+                format!(
+                    "method: GET\nurl: {}/login\ncapture: {{token: $.n}}\nassert: [\"response.body.n > 2\"]",
+                    url
+                ),
+            ),
+        ]);
+        let definition = serde_yaml::from_str::<SequenceDefinitionSchema>(
+            "steps: [check, login]\non_failure: retry",
+        )
+        .unwrap();
+
+        // the first attempt captures the token from the 2nd response and fails, the second one passes
+        let run = runner.sequence("main", &definition).await;
+
+        assert!(run.outcome.is_ok(), "{:?}", run.outcome);
+        assert_eq!(run.attempts, 2);
+        assert_eq!(runner.state.get("token"), Some(&serde_yaml::Value::from(4)));
+    }
+
+    #[tokio::test]
+    async fn a_failed_step_is_reported_with_the_request_that_was_sent() {
+        // nothing listens on the port once the listener is dropped
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+
+        let mut runner = runner(&[(
+            "create",
+            format!("method: POST\nurl: http://{}/{{{{uuid()}}}}", address),
+        )]);
+        let definition =
+            serde_yaml::from_str::<SequenceDefinitionSchema>("steps: [create]").unwrap();
+
+        let run = runner.sequence("main", &definition).await;
+
+        assert!(run.outcome.is_err());
+        let sent = runner.sent.get("create").unwrap();
+        assert_eq!(run.entries[0].url, sent.url);
+        assert_eq!(run.entries[0].method, "POST");
+        assert!(run.entries[0].error.is_some());
+    }
+
+    // Answers the nth connection with the nth response, the ones past the list with the last one.
+    // None holds the connection without answering. Returns the url and the number of connections.
+    async fn serve_script(responses: Vec<Option<&'static str>>) -> (String, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let connections = count.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let n = count.fetch_add(1, Ordering::SeqCst);
+                let response = responses[n.min(responses.len() - 1)];
+
+                tokio::spawn(async move {
+                    let mut request = [0; 4096];
+                    let _ = stream.read(&mut request).await;
+                    match response {
+                        Some(response) => stream.write_all(response.as_bytes()).await.unwrap(),
+                        None => tokio::time::sleep(Duration::from_secs(5)).await,
+                    }
+                });
+            }
+        });
+
+        return (format!("http://{}", address), connections);
+    }
+
+    const UNAVAILABLE: &str =
+        "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+    const OK: &str = "HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+
+    #[tokio::test]
+    async fn the_configured_statuses_are_retried() {
+        let (url, connections) = serve_script(vec![Some(UNAVAILABLE), Some(OK)]).await;
+        let mut runner = runner(&[
+            (
+                "retried",
+                format!(
+                    "method: GET\nurl: \"{}/\"\nconfig:\n  retries: 2\n  retry_statuses: [503]",
+                    url
+                ),
+            ),
+            (
+                "kept",
+                format!("method: GET\nurl: \"{}/\"\nconfig:\n  retries: 2", url),
+            ),
+        ]);
+
+        let result = runner.call_request("retried").await.unwrap();
+        assert_eq!(result.response.status, 200);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+        // statuses not listed are answers like any other
+        let (url, connections) = serve_script(vec![Some(UNAVAILABLE), Some(OK)]).await;
+        runner.requests[1].object.url = format!("{}/", url);
+        let result = runner.call_request("kept").await.unwrap();
+        assert_eq!(result.response.status, 503);
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn timeouts_are_retried() {
+        let (url, connections) = serve_script(vec![None, Some(OK)]).await;
+        let mut runner = runner(&[(
+            "slow",
+            format!(
+                "method: GET\nurl: \"{}/\"\nconfig:\n  retries: 1\n  timeout: 200",
+                url
+            ),
+        )]);
+
+        let result = runner.call_request("slow").await.unwrap();
+
+        assert_eq!(result.response.status, 200);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn errors_that_would_happen_again_are_not_retried() {
+        let (url, connections) = serve_script(vec![Some(
+            "HTTP/1.1 200 OK\r\ncontent-encoding: gzip\r\ncontent-length: 4\r\nconnection: close\r\n\r\nnope",
+        )])
+        .await;
+        let mut runner = runner(&[
+            (
+                "garbled",
+                format!("method: GET\nurl: \"{}/\"\nconfig:\n  retries: 3", url),
+            ),
+            (
+                "invalid",
+                "method: GET\nurl: \"http://exa mple.dev/\"\nconfig:\n  retries: 3".to_string(),
+            ),
+        ]);
+
+        assert!(runner.call_request("garbled").await.is_err());
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+        assert!(runner.call_request("invalid").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn lost_connections_and_timeouts_are_transient() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        drop(listener);
+        let refused = reqwest::get(format!("http://{}/", address))
+            .await
+            .unwrap_err();
+
+        assert!(is_transient(
+            &anyhow::Error::from(refused).context("Request \"users\" failed")
+        ));
+        assert!(is_transient(
+            &std::io::Error::from(std::io::ErrorKind::ConnectionAborted).into()
+        ));
+        assert!(!is_transient(&anyhow::anyhow!(
+            "Invalid ip \"x\" for api.dev"
+        )));
+        assert!(!is_transient(
+            &crate::interpolation::UnresolvedError {
+                name: "token".to_string()
+            }
+            .into()
+        ));
+    }
+
+    #[test]
+    fn join_url_keeps_one_slash_between_the_parts() {
+        assert_eq!(
+            join_url("https://x.dev/v1", "/users"),
+            "https://x.dev/v1/users"
+        );
+        assert_eq!(
+            join_url("https://x.dev/v1/", "/users"),
+            "https://x.dev/v1/users"
+        );
+        assert_eq!(
+            join_url("https://x.dev/v1/", "users/"),
+            "https://x.dev/v1/users/"
+        );
+        assert_eq!(join_url("https://x.dev", "users"), "https://x.dev/users");
+        assert_eq!(join_url("https://x.dev/v1", ""), "https://x.dev/v1");
+    }
+
+    #[test]
+    fn join_url_keeps_query_strings() {
+        assert_eq!(
+            join_url("https://x.dev/v1", "?page=2"),
+            "https://x.dev/v1?page=2"
+        );
+        assert_eq!(
+            join_url("https://x.dev/v1", "/users?next=https://y.dev/a"),
+            "https://x.dev/v1/users?next=https://y.dev/a"
+        );
+    }
+
+    #[test]
+    fn absolute_urls_bypass_the_base_url() {
+        assert_eq!(
+            join_url("https://x.dev/v1", "http://localhost:8080/users"),
+            "http://localhost:8080/users"
+        );
+        assert!(is_absolute_url("https://y.dev"));
+        assert!(!is_absolute_url("/login?next=https://y.dev"));
+        assert!(!is_absolute_url("/a#https://y.dev"));
+    }
+
+    #[test]
+    fn path_params_replace_their_segments() {
+        let params = HashMap::from([
+            ("id".to_string(), "a b/c".to_string()),
+            ("post".to_string(), "7".to_string()),
+        ]);
+
+        assert_eq!(
+            apply_path_params("https://x.dev/users/:id/posts/:post", &params),
+            "https://x.dev/users/a%20b%2Fc/posts/7"
+        );
+        // a param missing from the values is left as is
+        assert_eq!(
+            apply_path_params("https://x.dev/users/:missing/posts/:post", &params),
+            "https://x.dev/users/:missing/posts/7"
+        );
+        assert_eq!(apply_path_params("/users/:post", &params), "/users/7");
+        // the port and the query aren't path segments
+        assert_eq!(
+            apply_path_params("http://localhost:8080/users/:id?sort=:id#:post", &params),
+            "http://localhost:8080/users/a%20b%2Fc?sort=:id#:post"
+        );
+        assert_eq!(
+            apply_path_params("https://x.dev:post", &params),
+            "https://x.dev:post"
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::{path::Path, time::Duration};
+
+use anyhow::Context;
+use chrono::{DateTime, Datelike, Local, TimeDelta, Timelike};
+
+use crate::{
+    notify::{RunSummary, send_notifications, send_summary},
+    report::RunReport,
+    runner::Runner,
+    schema::schedule::ScheduleSchema,
+};
+
+// A cron expression that never matches stops being looked at after this many minutes (a bit over 4 years)
+const MAX_CRON_SEARCH: i64 = 4 * 366 * 24 * 60;
+
+/// A cron expression: minute, hour, day of month, month and day of week.
+/// Fields take `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and lists of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpression {
+    minutes: Vec<u32>,
+    hours: Vec<u32>,
+    days: Vec<u32>,
+    months: Vec<u32>,
+    weekdays: Vec<u32>,
+    // a restricted day of month or week, when both are, either one matching is enough
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> anyhow::Result<CronExpression> {
+        let fields = expression.split_whitespace().collect::<Vec<&str>>();
+        if fields.len() != 5 {
+            anyhow::bail!(
+                "Invalid cron expression \"{}\", expected 5 fields: minute hour day month weekday",
+                expression
+            );
+        }
+
+        let parse = |field: &str, min: u32, max: u32| {
+            parse_field(field, min, max)
+                .with_context(|| format!("Invalid cron expression \"{}\"", expression))
+        };
+
+        // 7 is also sunday
+        let mut weekdays = parse(fields[4], 0, 7)?
+            .into_iter()
+            .map(|day| day % 7)
+            .collect::<Vec<u32>>();
+        weekdays.sort();
+        weekdays.dedup();
+
+        return Ok(CronExpression {
+            minutes: parse(fields[0], 0, 59)?,
+            hours: parse(fields[1], 0, 23)?,
+            days: parse(fields[2], 1, 31)?,
+            months: parse(fields[3], 1, 12)?,
+            weekdays,
+            // like cron, a field starting with * (eg: */2) isn't a restriction on the day
+            any_day: fields[2].starts_with('*'),
+            any_weekday: fields[4].starts_with('*'),
+        });
+    }
+
+    pub fn matches(&self, time: &DateTime<Local>) -> bool {
+        let day = self.days.contains(&time.day());
+        let weekday = self
+            .weekdays
+            .contains(&time.weekday().num_days_from_sunday());
+
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+
+        return day_matches
+            && self.minutes.contains(&time.minute())
+            && self.hours.contains(&time.hour())
+            && self.months.contains(&time.month());
+    }
+
+    // The first minute after time the expression matches
+    pub fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        let mut next = time.with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+
+        for _ in 0..MAX_CRON_SEARCH {
+            if self.matches(&next) {
+                return Some(next);
+            }
+            next += TimeDelta::minutes(1);
+        }
+
+        return None;
+    }
+}
+
+// The values of a cron field, sorted
+fn parse_field(field: &str, min: u32, max: u32) -> anyhow::Result<Vec<u32>> {
+    let mut values = vec![];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>()?),
+            None => (part, 1),
+        };
+        if step == 0 {
+            anyhow::bail!("A step of 0 in \"{}\"", part);
+        }
+
+        let (start, end) = match range {
+            "*" => (min, max),
+            range => match range.split_once('-') {
+                Some((start, end)) => (start.parse::<u32>()?, end.parse::<u32>()?),
+                // a single value with a step runs from it to the end, like 5/15
+                None if part.contains('/') => (range.parse::<u32>()?, max),
+                None => {
+                    let value = range.parse::<u32>()?;
+                    (value, value)
+                }
+            },
+        };
+
+        if start < min || end > max || start > end {
+            anyhow::bail!("\"{}\" is out of {}-{}", part, min, max);
+        }
+
+        values.extend((start..=end).step_by(step as usize));
+    }
+
+    values.sort();
+    values.dedup();
+    return Ok(values);
+}
+
+/// When the runs of a schedule happen.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Cron(CronExpression),
+    Every(Duration),
+}
+
+impl Schedule {
+    pub fn from_schema(schema: &ScheduleSchema) -> anyhow::Result<Schedule> {
+        return match (&schema.cron, schema.every) {
+            (Some(cron), _) => Ok(Schedule::Cron(CronExpression::parse(cron)?)),
+            (None, Some(every)) if every > 0 => {
+                Ok(Schedule::Every(Duration::from_secs(every as u64)))
+            }
+            _ => anyhow::bail!("Schedule \"{}\" needs a cron or every", schema.name),
+        };
+    }
+
+    // When the next run happens, after a run at time
+    pub fn next_after(&self, time: DateTime<Local>) -> Option<DateTime<Local>> {
+        return match self {
+            Schedule::Cron(cron) => cron.next_after(time),
+            Schedule::Every(every) => Some(time + TimeDelta::from_std(*every).ok()?),
+        };
+    }
+}
+
+// Runs the sequence of a schedule once, with the project loaded again so changes are picked up.
+// Calls are recorded in the history and the summary is sent to the schedule's notifications,
+// a project that doesn't load or a sequence that errors is sent as a failed run.
+pub async fn run_scheduled(path: &Path, schedule: &ScheduleSchema) -> anyhow::Result<RunReport> {
+    let mut runner = match Runner::load(path).await {
+        Ok(runner) => runner,
+        Err(e) => {
+            notify_error(schedule, format!("{:#}", e)).await;
+            return Err(e);
+        }
+    };
+    runner.environment = schedule.environment.clone();
+    runner.enable_history().await;
+    runner.use_project_reporters();
+    runner.use_project_telemetry();
+
+    let report = match runner.run_sequence(&schedule.sequence).await {
+        Ok(report) => report,
+        Err(e) => {
+            notify_error(schedule, runner.redact_text(&format!("{:#}", e))).await;
+            return Err(e);
+        }
+    };
+
+    send_notifications(&schedule.notify, &report).await;
+
+    return Ok(report);
+}
+
+async fn notify_error(schedule: &ScheduleSchema, error: String) {
+    let summary = RunSummary::from_error(&schedule.name, schedule.environment.clone(), error);
+    send_summary(&schedule.notify, &summary).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        return Local
+            .with_ymd_and_hms(year, month, day, hour, minute, 0)
+            .unwrap();
+    }
+
+    #[test]
+    fn fields_take_ranges_steps_and_lists() {
+        assert_eq!(parse_field("1-5", 0, 59).unwrap(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(parse_field("*/15", 0, 59).unwrap(), vec![0, 15, 30, 45]);
+        assert_eq!(parse_field("0-30/10", 0, 59).unwrap(), vec![0, 10, 20, 30]);
+        assert_eq!(parse_field("50/5", 0, 59).unwrap(), vec![50, 55]);
+        assert_eq!(parse_field("7,1-3,2", 0, 59).unwrap(), vec![1, 2, 3, 7]);
+        assert_eq!(
+            parse_field("*", 1, 12).unwrap(),
+            (1..=12).collect::<Vec<u32>>()
+        );
+    }
+
+    #[test]
+    fn invalid_fields_are_rejected() {
+        assert!(parse_field("60", 0, 59).is_err());
+        assert!(parse_field("0", 1, 31).is_err());
+        assert!(parse_field("5-1", 0, 59).is_err());
+        assert!(parse_field("*/0", 0, 59).is_err());
+        assert!(parse_field("a", 0, 59).is_err());
+        assert!(parse_field("1,", 0, 59).is_err());
+        assert!(CronExpression::parse("* * * *").is_err());
+        assert!(CronExpression::parse("* * * * * *").is_err());
+        assert!(CronExpression::parse("* 24 * * *").is_err());
+    }
+
+    #[test]
+    fn seven_is_sunday() {
+        let cron = CronExpression::parse("0 0 * * 7").unwrap();
+        // 2024-06-02 is a sunday
+        assert!(cron.matches(&at(2024, 6, 2, 0, 0)));
+        assert!(!cron.matches(&at(2024, 6, 3, 0, 0)));
+    }
+
+    #[test]
+    fn restricted_day_and_weekday_match_either() {
+        // the 1st, or any monday
+        let cron = CronExpression::parse("0 0 1 * 1").unwrap();
+        // 2024-06-01 is a saturday, 2024-06-03 a monday
+        assert!(cron.matches(&at(2024, 6, 1, 0, 0)));
+        assert!(cron.matches(&at(2024, 6, 3, 0, 0)));
+        assert!(!cron.matches(&at(2024, 6, 4, 0, 0)));
+    }
+
+    #[test]
+    fn a_star_day_matches_only_the_weekday() {
+        let cron = CronExpression::parse("0 0 * * 1").unwrap();
+        assert!(cron.matches(&at(2024, 6, 3, 0, 0)));
+        assert!(!cron.matches(&at(2024, 6, 1, 0, 0)));
+    }
+
+    #[test]
+    fn a_star_step_day_needs_both_to_match() {
+        // odd days that are mondays, not odd days or mondays
+        let cron = CronExpression::parse("0 0 */2 * 1").unwrap();
+        // 2024-06-03 is an odd monday, 2024-06-10 an even one, 2024-06-05 an odd wednesday
+        assert!(cron.matches(&at(2024, 6, 3, 0, 0)));
+        assert!(!cron.matches(&at(2024, 6, 10, 0, 0)));
+        assert!(!cron.matches(&at(2024, 6, 5, 0, 0)));
+    }
+
+    #[test]
+    fn next_after_is_the_next_matching_minute() {
+        let cron = CronExpression::parse("*/15 * * * *").unwrap();
+        assert_eq!(
+            cron.next_after(at(2024, 6, 3, 10, 7)),
+            Some(at(2024, 6, 3, 10, 15))
+        );
+        assert_eq!(
+            cron.next_after(at(2024, 6, 3, 10, 15)),
+            Some(at(2024, 6, 3, 10, 30))
+        );
+    }
+
+    #[test]
+    fn a_date_that_never_comes_never_runs() {
+        let cron = CronExpression::parse("0 0 31 2 *").unwrap();
+        assert_eq!(cron.next_after(at(2024, 6, 3, 10, 7)), None);
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// How a request authenticates. Every value can contain placeholders.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuthSchema {
+    Basic {
+        username: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        password: String,
+    },
+    Bearer {
+        token: String,
+    },
+    // Answers the digest challenge of a 401 response
+    Digest {
+        username: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        password: String,
+    },
+    // NTLMv2 handshake, the connection is kept between the round trips
+    Ntlm {
+        username: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        password: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        domain: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        workstation: String,
+    },
+    // AWS Signature Version 4. Credentials that are not set are read from
+    // AWS_ACCESS_KEY_ID, AWS_SECRET_ACCESS_KEY, AWS_SESSION_TOKEN and AWS_REGION
+    #[serde(rename = "aws_sigv4")]
+    AwsSigv4 {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        access_key: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secret_key: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        session_token: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        region: Option<String>,
+        service: String, // eg: execute-api, s3
+    },
+    // A keyed hash of the request put in a header, as webhook senders do.
+    // payload and value are rendered when signing, with {{body}}, {{method}}, {{url}},
+    // {{path}} and {{timestamp}} in payload and {{signature}}, {{timestamp}} in value
+    Hmac {
+        #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+        algorithm: HmacAlgorithmSchema,
+        key: String,
+        #[serde(default = "default_hmac_header")]
+        header: String,
+        #[serde(default = "default_hmac_payload")]
+        payload: String,
+        #[serde(default = "default_hmac_value")]
+        value: String, // eg: sha256={{signature}}
+        #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+        encoding: SignatureEncodingSchema,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum HmacAlgorithmSchema {
+    Sha1,
+    #[default]
+    Sha256,
+    Sha512,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureEncodingSchema {
+    #[default]
+    Hex,
+    Base64,
+}
+
+fn default_hmac_header() -> String {
+    return "X-Signature".to_string();
+}
+
+fn default_hmac_payload() -> String {
+    return "{{body}}".to_string();
+}
+
+fn default_hmac_value() -> String {
+    return "{{signature}}".to_string();
+}
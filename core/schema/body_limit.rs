@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// Caps the size of response bodies kept in memory, so huge downloads don't fill the app or the history.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct BodyLimitSchema {
+    pub max_size: u64, // in bytes, as received before decompression
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub policy: BodyLimitPolicySchema,
+}
+
+/// What happens to a response body over the limit.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum BodyLimitPolicySchema {
+    #[default]
+    Abort, // the request fails
+    Truncate, // the start of the body is kept, followed by a marker
+    File,     // the whole body is written to a temporary file, the start of it is kept
+}
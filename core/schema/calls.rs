@@ -1,11 +1,161 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
-/// Represents the definition of a single environment variable.
+/// Represents the call sequences of a project, keyed by name.
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
 #[serde(rename_all = "snake_case")]
 pub struct CallSchema {
-    pub main: Vec<String>, // Use Value to allow any YAML type
+    pub main: SequenceSchema,
     #[serde(flatten)] // Flatten environment-specific overrides into this struct
-    pub overrides: HashMap<String, Vec<String>>,
+    pub overrides: IndexMap<String, SequenceSchema>,
+}
+
+impl CallSchema {
+    // Names of the sequences, main first
+    pub fn names(&self) -> Vec<String> {
+        let mut names = self.overrides.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+        names.insert(0, "main".to_string());
+        return names;
+    }
+}
+
+/// A call sequence, either a list of request names or a definition with hooks.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum SequenceSchema {
+    Steps(Vec<String>),
+    Definition(SequenceDefinitionSchema),
+}
+
+impl Default for SequenceSchema {
+    fn default() -> Self {
+        return SequenceSchema::Steps(vec![]);
+    }
+}
+
+impl SequenceSchema {
+    pub fn definition(&self) -> SequenceDefinitionSchema {
+        return match self {
+            SequenceSchema::Steps(steps) => SequenceDefinitionSchema {
+                steps: steps
+                    .iter()
+                    .map(|step| StepSchema::Request(step.clone()))
+                    .collect(),
+                ..Default::default()
+            },
+            SequenceSchema::Definition(definition) => definition.clone(),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct SequenceDefinitionSchema {
+    pub steps: Vec<StepSchema>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<HookSchema>, // runs before the steps, the steps are skipped if one fails
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<HookSchema>, // always runs after the steps, even when one failed
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub on_failure: FailurePolicySchema,
+    #[serde(default = "default_sequence_retries")]
+    pub retries: u32, // times to run the sequence again when on_failure is retry
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub context: SequenceContextSchema,
+}
+
+impl SequenceDefinitionSchema {
+    pub fn step_names(&self) -> Vec<String> {
+        return self
+            .steps
+            .iter()
+            .map(|step| step.name().to_string())
+            .collect();
+    }
+
+    // Number of times the sequence may run
+    pub fn attempts(&self) -> u32 {
+        return match self.on_failure {
+            FailurePolicySchema::Retry => 1 + self.retries,
+            _ => 1,
+        };
+    }
+}
+
+fn default_sequence_retries() -> u32 {
+    return 1;
+}
+
+/// What happens to the rest of a sequence when a step fails.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FailurePolicySchema {
+    #[default]
+    Abort, // skip the remaining steps
+    Continue, // call the remaining steps, the sequence still fails
+    Retry,    // run the whole sequence again, up to `retries` times
+}
+
+/// Which responses and state the requests of a sequence see.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceContextSchema {
+    #[default]
+    Shared, // uses the run's context, what the sequence sets stays after it
+    Inherit,  // starts from a copy of the run's context, discarded after the sequence
+    Isolated, // starts empty, discarded after the sequence
+}
+
+/// A step of a sequence, either a request name or a request with options.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum StepSchema {
+    Request(String),
+    Definition {
+        request: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        allow_failure: bool, // a failure of this step doesn't fail the sequence
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>, // added to the tags of the request when selecting steps
+    },
+}
+
+impl StepSchema {
+    pub fn name(&self) -> &str {
+        return match self {
+            StepSchema::Request(name) => name,
+            StepSchema::Definition { request, .. } => request,
+        };
+    }
+
+    pub fn allow_failure(&self) -> bool {
+        return match self {
+            StepSchema::Request(_) => false,
+            StepSchema::Definition { allow_failure, .. } => *allow_failure,
+        };
+    }
+
+    pub fn tags(&self) -> &[String] {
+        return match self {
+            StepSchema::Request(_) => &[],
+            StepSchema::Definition { tags, .. } => tags,
+        };
+    }
+}
+
+/// A request name or shell command run around the steps of a sequence.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum HookSchema {
+    Request(String),
+    Command { command: String },
+}
+
+impl HookSchema {
+    pub fn label(&self) -> String {
+        return match self {
+            HookSchema::Request(name) => name.clone(),
+            HookSchema::Command { command } => format!("$ {}", command),
+        };
+    }
 }
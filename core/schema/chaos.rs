@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+/// Faults injected into the requests of a run, to see how retries and the api behave under failure.
+///
+/// Probabilities go from 0 (never) to 1 (every request), faults are only injected with `--chaos`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ChaosSchema {
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub delay: f64, // probability of holding a request before sending it
+    #[serde(default = "default_delay_min")]
+    pub delay_min: u32, // shortest injected delay in milliseconds
+    #[serde(default = "default_delay_max")]
+    pub delay_max: u32, // longest injected delay in milliseconds
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub drop: f64, // probability of dropping the connection once the request is sent, its response is lost
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub duplicate: f64, // probability of sending a request twice, the response of the second call is kept
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>, // the same seed injects the same faults into the same requests, random when unset
+}
+
+fn default_delay_min() -> u32 {
+    return 100;
+}
+
+fn default_delay_max() -> u32 {
+    return 2000;
+}
+
+impl Default for ChaosSchema {
+    fn default() -> Self {
+        return ChaosSchema {
+            delay: 0.0,
+            delay_min: default_delay_min(),
+            delay_max: default_delay_max(),
+            drop: 0.0,
+            duplicate: 0.0,
+            seed: None,
+        };
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+/// A hash of the request body sent as a header, for apis checking the integrity of uploads.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ChecksumSchema {
+    pub algorithm: ChecksumAlgorithmSchema,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>, // Content-MD5 for md5, Content-Digest (as sha-256=:<base64>:) for sha256
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub encoding: ChecksumEncodingSchema, // of the hash in a custom header
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumAlgorithmSchema {
+    Md5,
+    Sha256,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ChecksumEncodingSchema {
+    #[default]
+    Base64,
+    Hex, // eg: for x-amz-content-sha256
+}
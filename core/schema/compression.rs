@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+
+/// The content-encoding a request body is compressed with before it's sent.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionSchema {
+    Gzip,
+    Br,
+}
+
+impl CompressionSchema {
+    // the value of the content-encoding header
+    pub fn encoding(&self) -> &'static str {
+        return match self {
+            CompressionSchema::Gzip => "gzip",
+            CompressionSchema::Br => "br",
+        };
+    }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
 
 use serde::{Deserialize, Serialize};
 
@@ -6,15 +6,35 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct EnvironmentVariableSchema {
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
     pub default: serde_yaml::Value, // Use Value to allow any YAML type
+    #[serde(default, rename = "type", skip_serializing_if = "Option::is_none")]
+    pub kind: Option<VariableTypeSchema>, // the values are checked against it before requests are sent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<serde_yaml::Value>, // the values allowed with the enum type
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub required: bool, // the variable needs a value in the environment of the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub os_env: Option<String>, // os environment variable taking precedence over the value, eg: API_KEY
     #[serde(flatten)] // Flatten environment-specific overrides into this struct
-    pub overrides: HashMap<String, serde_yaml::Value>,
+    pub overrides: IndexMap<String, serde_yaml::Value>,
+}
+
+/// The type of the values of an environment variable.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum VariableTypeSchema {
+    String,
+    Int,
+    Bool,
+    Url,
+    Enum, // one of the variable's choices
 }
 
 impl EnvironmentVariableSchema {
     #[allow(unused)]
     pub fn new(value: serde_yaml::Value, ovr: Vec<(String, serde_yaml::Value)>) -> Self {
-        let mut overrides = HashMap::new();
+        let mut overrides = IndexMap::new();
 
         for (key, v) in ovr {
             overrides.insert(key, v);
@@ -22,6 +42,10 @@ impl EnvironmentVariableSchema {
 
         return EnvironmentVariableSchema {
             default: value,
+            kind: None,
+            choices: vec![],
+            required: false,
+            os_env: None,
             overrides,
         };
     }
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+
+/// Settings of the http client shared by the requests of a run.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct HttpClientSchema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_max_idle_per_host: Option<usize>, // idle connections kept open for each host
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pool_idle_timeout: Option<u32>, // close idle connections after this many milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_keepalive: Option<u32>, // interval of tcp keep-alive probes in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_timeout: Option<u32>, // time allowed to establish a connection in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tcp_nodelay: Option<bool>, // disable nagle's algorithm, on by default
+}
@@ -0,0 +1,11 @@
+use serde::{Deserialize, Serialize};
+
+/// What a `{{placeholder}}` that doesn't resolve becomes.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InterpolationModeSchema {
+    #[default]
+    Strict, // the request fails
+    Literal, // left as written, eg: {{token}}
+    Empty,   // replaced with an empty string
+}
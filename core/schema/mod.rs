@@ -1,6 +1,32 @@
+pub mod auth;
+pub mod body_limit;
 pub mod calls;
+pub mod chaos;
+pub mod checksum;
+pub mod compression;
 pub mod env;
+pub mod http;
+pub mod interpolation;
+pub mod notification;
+pub mod paginate;
+pub mod poll;
 pub mod project;
+pub mod range;
+pub mod rate_limit;
+pub mod redirect;
+pub mod redaction;
+pub mod reporter;
 pub mod request_body;
 pub mod request_config;
 pub mod roots;
+pub mod schedule;
+pub mod snapshot;
+pub mod soap;
+pub mod telemetry;
+pub mod upload;
+pub mod validation;
+
+// Fields left as their default aren't written when a schema is saved
+pub(crate) fn is_default<T: Default + PartialEq>(value: &T) -> bool {
+    return *value == T::default();
+}
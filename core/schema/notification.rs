@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+
+/// Where the summary of a run is sent once it's over.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct NotificationSchema {
+    #[serde(flatten)]
+    pub channel: NotificationChannelSchema,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub always: bool, // also sent when the run passed, only failed runs are sent otherwise
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotificationChannelSchema {
+    // posts the json summary to the url
+    #[serde(rename = "webhook")]
+    Webhook { url: String },
+    // posts a message to a slack incoming webhook
+    #[serde(rename = "slack")]
+    Slack { url: String },
+    // a notification of the operating system, with notify-send on linux
+    #[serde(rename = "desktop")]
+    Desktop,
+}
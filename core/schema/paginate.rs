@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Follows the pages of a paginated endpoint and gathers the items of every page,
+/// the next page is found with one of `next`, `link_header` or `page_param`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PaginateSchema {
+    pub items: String, // json path of the items of a page, eg: $.data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next: Option<String>, // json path of the url of the next page, eg: $.links.next
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub link_header: bool, // follow the rel="next" url of the Link header
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_param: Option<String>, // query parameter incremented for each page until one has no items, eg: page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<String>, // stop once this condition on the latest page holds, eg: response.body.has_more == false
+    #[serde(default = "default_max_pages")]
+    pub max_pages: u32,
+}
+
+fn default_max_pages() -> u32 {
+    return 100;
+}
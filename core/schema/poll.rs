@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Calls a request again until a condition holds, eg: a job status endpoint
+/// that has to be checked until the job completes.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PollSchema {
+    // time to wait between attempts in milliseconds
+    #[serde(default = "default_interval")]
+    pub interval: u32,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    // condition on the latest response, eg: response.status == 200 && response.body.state == 'done'
+    pub until: String,
+}
+
+fn default_interval() -> u32 {
+    return 1000;
+}
+
+fn default_max_attempts() -> u32 {
+    return 10;
+}
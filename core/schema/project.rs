@@ -3,9 +3,9 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
 pub struct ProjectDefinationSchema {
     pub name: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub description: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub version: Option<String>,
 }
 
@@ -14,4 +14,4 @@ impl ProjectDefinationSchema {
         let res = self.version.clone().unwrap_or_else(|| "0.0.1".to_string());
         return res;
     }
-}
\ No newline at end of file
+}
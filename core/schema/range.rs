@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// The byte ranges asked for in the Range header, eg: 0-499, 1000- (to the end) or -500 (the last 500 bytes).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum RangeSchema {
+    Single(String),
+    Multiple(Vec<String>),
+}
+
+impl RangeSchema {
+    pub fn ranges(&self) -> Vec<String> {
+        return match self {
+            RangeSchema::Single(range) => vec![range.clone()],
+            RangeSchema::Multiple(ranges) => ranges.clone(),
+        };
+    }
+}
+
+/// Downloads the response body in ranges fetched in parallel, then checks they reassemble into the whole file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DownloadSchema {
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: u64, // in bytes, the last range holds the rest
+    #[serde(default = "default_parallel")]
+    pub parallel: usize, // ranges fetched at the same time
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub verify: bool, // also download the whole file and compare it with the reassembled one
+}
+
+// 1MiB
+fn default_chunk_size() -> u64 {
+    return 1024 * 1024;
+}
+
+fn default_parallel() -> usize {
+    return 4;
+}
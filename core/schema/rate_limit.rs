@@ -0,0 +1,95 @@
+use serde::{Deserialize, Deserializer, Serialize};
+
+// One request every 1000 seconds, slower rates are set with min_interval
+const MIN_MAX_PER_SECOND: f64 = 0.001;
+
+/// Limits how often requests are sent, so runs don't trip upstream rate limits.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
+pub struct RateLimitSchema {
+    #[serde(
+        default,
+        deserialize_with = "deserialize_rate",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_per_second: Option<f64>, // eg: 5 sends at most one request every 200ms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_interval: Option<u32>, // minimum time between two requests in milliseconds
+}
+
+impl RateLimitSchema {
+    // The time to leave between two requests, the stricter of both settings
+    pub fn spacing(&self) -> Option<std::time::Duration> {
+        let from_rate = self
+            .max_per_second
+            .filter(|rate| *rate >= MIN_MAX_PER_SECOND)
+            .map(|rate| std::time::Duration::from_secs_f64(1.0 / rate));
+        let from_interval = self
+            .min_interval
+            .map(|ms| std::time::Duration::from_millis(ms as u64));
+
+        return from_rate.max(from_interval);
+    }
+}
+
+// Rates whose spacing can't be waited for, eg: 0 or 1e-300, fail when the file is read
+fn deserialize_rate<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    let rate = Option::<f64>::deserialize(deserializer)?;
+
+    return match rate {
+        Some(rate) if !rate.is_finite() || rate < MIN_MAX_PER_SECOND => {
+            Err(serde::de::Error::custom(format!(
+                "max_per_second has to be at least {} (one request every 1000 seconds), got {}. \
+                 Use min_interval for slower rates",
+                MIN_MAX_PER_SECOND, rate
+            )))
+        }
+        rate => Ok(rate),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> Result<RateLimitSchema, serde_yaml::Error> {
+        return serde_yaml::from_str(yaml);
+    }
+
+    #[test]
+    fn spacing_is_the_stricter_setting() {
+        let limit = parse("{max_per_second: 5, min_interval: 100}").unwrap();
+        assert_eq!(limit.spacing(), Some(std::time::Duration::from_millis(200)));
+
+        let limit = parse("{max_per_second: 50, min_interval: 100}").unwrap();
+        assert_eq!(limit.spacing(), Some(std::time::Duration::from_millis(100)));
+
+        let limit = parse("{max_per_second: 0.001}").unwrap();
+        assert_eq!(limit.spacing(), Some(std::time::Duration::from_secs(1000)));
+
+        assert_eq!(parse("{}").unwrap().spacing(), None);
+    }
+
+    #[test]
+    fn rejects_rates_that_cant_be_waited_for() {
+        for rate in ["0", "-2", "1e-300", "0.0009", ".nan", ".inf"] {
+            let error = parse(&format!("{{max_per_second: {}}}", rate)).unwrap_err();
+            assert!(
+                error
+                    .to_string()
+                    .starts_with("max_per_second has to be at least 0.001"),
+                "{}: {}",
+                rate,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn spacing_ignores_rates_set_in_code_that_cant_be_waited_for() {
+        let limit = RateLimitSchema {
+            max_per_second: Some(1e-300),
+            min_interval: None,
+        };
+        assert_eq!(limit.spacing(), None);
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// Rules for hiding sensitive values when requests and responses leave the runner
+/// (bundles, history, logs, exports and the app's response views).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RedactionSchema {
+    // header names whose values are replaced, compared case-insensitively
+    #[serde(default = "default_redacted_headers")]
+    pub headers: Vec<String>,
+    // env variables whose values are replaced wherever they appear (urls, bodies)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<String>,
+    // json paths of body fields whose values are replaced, eg: $.password, $..token
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
+}
+
+impl Default for RedactionSchema {
+    fn default() -> Self {
+        return RedactionSchema {
+            headers: default_redacted_headers(),
+            variables: vec![],
+            paths: vec![],
+        };
+    }
+}
+
+fn default_redacted_headers() -> Vec<String> {
+    return vec![
+        "authorization".to_string(),
+        "proxy-authorization".to_string(),
+        "cookie".to_string(),
+        "set-cookie".to_string(),
+        "x-api-key".to_string(),
+    ];
+}
@@ -0,0 +1,32 @@
+use serde::{Deserialize, Serialize};
+
+/// How redirect responses are handled for a request.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RedirectSchema {
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub policy: RedirectPolicySchema,
+    #[serde(default = "default_max_redirects")]
+    pub max: usize, // hops to follow before failing
+}
+
+impl Default for RedirectSchema {
+    fn default() -> Self {
+        return RedirectSchema {
+            policy: RedirectPolicySchema::default(),
+            max: default_max_redirects(),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RedirectPolicySchema {
+    #[default]
+    Follow,
+    None,   // the redirect response itself is returned
+    Record, // follow, keeping each hop in response.redirects
+}
+
+fn default_max_redirects() -> usize {
+    return 10;
+}
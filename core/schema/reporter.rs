@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// A reporter to enable for runs of the project.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReporterSchema {
+    #[serde(rename = "console")]
+    Console,
+    #[serde(rename = "json")]
+    Json { path: String },
+    #[serde(rename = "junit")]
+    Junit { path: String },
+    #[serde(rename = "html")]
+    Html { path: String },
+    // request counts, errors and latency histograms in the prometheus text format
+    #[serde(rename = "prometheus")]
+    Prometheus { path: String },
+    // runs a command with the json report written to its stdin
+    #[serde(rename = "command")]
+    Command { command: String },
+}
@@ -1,34 +1,130 @@
+use std::collections::BTreeMap;
+
+use indexmap::IndexMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::soap::SoapVersionSchema;
+
 /// Represents the body section of a request.
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 #[serde(tag = "type", rename_all = "snake_case")] // Use 'type' field to determine which variant to deserialize
 pub enum RequestBodySchema {
     #[serde(rename = "json")]
     Json {
+        #[serde(default, skip_serializing_if = "crate::schema::is_default")]
         content: serde_yaml::Value, // Use Value to allow any JSON structure (object or array)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>, // Json or yaml file used as the content, relative to the request file
     },
     #[serde(rename = "graphql")]
     Graphql {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
         query: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        query_file: Option<String>, // .graphql file used as the query, its #import lines are resolved
+        #[serde(skip_serializing_if = "Option::is_none")]
         variables: Option<serde_yaml::Value>, // GraphQL variables as a JSON-like structure
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        validate: bool, // check the query against the endpoint's introspected schema before sending
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        persisted: bool, // send the sha256 hash of the query (automatic persisted queries)
     },
     #[serde(rename = "xml")]
     Xml {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
         content: String, // XML content as a string
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>,
+    },
+    #[serde(rename = "soap")]
+    Soap {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        content: String, // the payload, wrapped in the envelope's body
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        header: Option<String>, // xml put in the envelope's header, eg: ws-security elements
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        action: Option<String>, // the SOAPAction of the operation
+        #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+        version: SoapVersionSchema,
     },
     #[serde(rename = "text")]
     Text {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
         content: String, // Text content as a string
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>,
     },
     #[serde(rename = "form-urlencoded")]
     FormUrlencoded {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
         content: String, // Form URL-encoded string
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        fields: Option<IndexMap<String, String>>, // Encoded and appended to the content when sent
     },
     #[serde(rename = "multipart")]
     Multipart {
         parts: Vec<MultipartPartSchema>, // List of multipart parts
     },
+    #[serde(rename = "binary")]
+    Binary {
+        path: String, // File sent as the raw body, relative to the request file
+        #[serde(skip_serializing_if = "Option::is_none")]
+        content_type: Option<String>, // Defaults to application/octet-stream
+    },
+    #[serde(rename = "msgpack")]
+    Msgpack {
+        #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+        content: serde_yaml::Value, // encoded as MessagePack when sent
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>, // Json or yaml file used as the content, relative to the request file
+    },
+    #[serde(rename = "cbor")]
+    Cbor {
+        #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+        content: serde_yaml::Value, // encoded as CBOR when sent
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>, // Json or yaml file used as the content, relative to the request file
+    },
+    #[serde(rename = "ndjson")]
+    Ndjson {
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        content: Vec<serde_yaml::Value>, // rows, each sent as a line of json
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_file: Option<String>, // .ndjson/.jsonl file, or a json or yaml list, relative to the request file
+    },
+    // Any other type, sent by the BodySerializer registered for it. Its fields are kept as written.
+    #[serde(untagged)]
+    Custom {
+        #[serde(rename = "type")]
+        kind: String,
+        #[serde(flatten)]
+        fields: BTreeMap<String, serde_yaml::Value>,
+    },
+}
+
+impl RequestBodySchema {
+    // The `type` of the body, eg: json
+    pub fn body_type(&self) -> &str {
+        return match self {
+            RequestBodySchema::Json { .. } => "json",
+            RequestBodySchema::Graphql { .. } => "graphql",
+            RequestBodySchema::Xml { .. } => "xml",
+            RequestBodySchema::Soap { .. } => "soap",
+            RequestBodySchema::Text { .. } => "text",
+            RequestBodySchema::FormUrlencoded { .. } => "form-urlencoded",
+            RequestBodySchema::Multipart { .. } => "multipart",
+            RequestBodySchema::Binary { .. } => "binary",
+            RequestBodySchema::Msgpack { .. } => "msgpack",
+            RequestBodySchema::Cbor { .. } => "cbor",
+            RequestBodySchema::Ndjson { .. } => "ndjson",
+            RequestBodySchema::Custom { kind, .. } => kind,
+        };
+    }
 }
 
 /// Represents a single part within a multipart request body.
@@ -41,6 +137,7 @@ pub enum MultipartPartSchema {
     File {
         name: String,
         path: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
         mime_type: Option<String>, // Optional MIME type
     },
 }
@@ -1,16 +1,71 @@
+use indexmap::IndexMap;
+
 use serde::{Deserialize, Serialize};
 
+use super::{
+    body_limit::BodyLimitSchema,
+    checksum::ChecksumSchema,
+    compression::CompressionSchema,
+    paginate::PaginateSchema,
+    poll::PollSchema,
+    range::{DownloadSchema, RangeSchema},
+    rate_limit::RateLimitSchema,
+    redirect::RedirectSchema,
+    upload::UploadSchema,
+};
+
 /// Represents the configuration section of a request.
 #[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq)]
 pub struct RequestConfigSchema {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub require: Vec<String>, // defaults to empty vec if not present
-    pub delay: Option<u32>,   // e.g., "500ms", "1s"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub delay: Option<u32>, // e.g., "500ms", "1s"
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub timeout: Option<u32>, // e.g., "30s"
-    #[serde(default)] // default to 0 if not present
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    // default to 0 if not present
     pub retries: u32,
-    #[serde(default)]
-    pub class: Option<String>, // where to group this request
-    #[serde(default)]
-    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub retry_statuses: Vec<u16>, // responses retried like failed connections, eg: [502, 503, 429]
+    #[serde(default, alias = "group", skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>, // where to group this request, nested with /, eg: users/admin
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>, // labels to select the request in a run, eg: smoke, auth
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub insecure: bool, // skip tls certificate verification
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll: Option<PollSchema>, // call again until a condition on the response holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paginate: Option<PaginateSchema>, // call the following pages and gather their items in response.items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload: Option<UploadSchema>, // send the file of the binary body in chunks, eg: with tus
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub range: Option<RangeSchema>, // sent as the Range header, eg: 0-499 or [0-99, -100]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download: Option<DownloadSchema>, // fetch the body in ranges in parallel and reassemble it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect: Option<RedirectSchema>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accept_encoding: Vec<String>, // sent as the accept-encoding header, eg: [gzip, br]
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub resolve: IndexMap<String, String>, // host -> ip used instead of dns, like curl --resolve
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_decompression: bool, // keep the body as received, without decoding its content-encoding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compress: Option<CompressionSchema>, // compress the request body before sending, setting Content-Encoding
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checksums: Vec<ChecksumSchema>, // headers with a hash of the body as sent, eg: Content-MD5
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_socket: Option<String>, // connect through this socket instead of tcp, eg: /var/run/docker.sock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache: Option<bool>, // use the http cache of the run, defaults to the project's cache setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitSchema>, // applies to this request, on top of the project's limit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_limit: Option<BodyLimitSchema>, // overrides the project's limit on the size of the response body
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub idempotency_key: bool, // sends an Idempotency-Key header, a new uuid for each call kept across its retries
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub capture_wire: bool, // keep the messages as sent and received on the response, see WireCapture
 }
@@ -1,8 +1,12 @@
-use std::{collections::HashMap};
+use indexmap::IndexMap;
 
 use crate::schema::{
-    calls::CallSchema, env::EnvironmentVariableSchema, request_body::RequestBodySchema,
-    request_config::RequestConfigSchema,
+    auth::AuthSchema, body_limit::BodyLimitSchema, calls::CallSchema, chaos::ChaosSchema,
+    env::EnvironmentVariableSchema, http::HttpClientSchema, interpolation::InterpolationModeSchema,
+    notification::NotificationSchema, rate_limit::RateLimitSchema, redaction::RedactionSchema,
+    reporter::ReporterSchema, request_body::RequestBodySchema, request_config::RequestConfigSchema,
+    schedule::ScheduleSchema, snapshot::SnapshotSchema, telemetry::TelemetrySchema,
+    validation::ResponseValidationSchema,
 };
 
 use super::project::ProjectDefinationSchema;
@@ -11,25 +15,81 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct ProjectRootSchema {
     pub project: ProjectDefinationSchema,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub requests_dir: Option<String>,
-    #[serde(default)]
-    pub env: HashMap<String, EnvironmentVariableSchema>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_invalid_requests: bool, // log request files that fail to load instead of failing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixtures_dir: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshots_dir: Option<String>, // where response snapshots are stored, snapshots by default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openapi: Option<String>, // spec of the api, responses are checked against it with --contract
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_dir: Option<String>, // each run of `dotapi run` writes its logs to a file in this folder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state_file: Option<String>, // keep the state of runs in this file, eg: .nd-state.yaml
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub env: IndexMap<String, EnvironmentVariableSchema>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<EnvironmentVariableSchema>, // joined with relative request urls, per environment
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub interpolation: InterpolationModeSchema, // what unresolved placeholders become, requests can override it
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub resolve: IndexMap<String, String>, // host -> ip for every request, requests can override them
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cache: bool, // keep an http cache for the run, honouring cache-control and validators
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<RateLimitSchema>, // shared by every request of the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chaos: Option<ChaosSchema>, // faults injected into requests when running with --chaos
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body_limit: Option<BodyLimitSchema>, // what to do with response bodies over a size, requests can override it
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub http: HttpClientSchema, // connection pool and tcp settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<TelemetrySchema>, // opentelemetry tracing of runs
     pub calls: CallSchema,
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub redact: RedactionSchema,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reporters: Vec<ReporterSchema>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<NotificationSchema>, // sent the summary of runs of `dotapi run`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedules: Vec<ScheduleSchema>, // sequences run periodically by `dotapi schedule`
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Default)]
 pub struct RequestRootSchema {
     pub method: String,
     pub url: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub doc: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>, // condition to call the request, skipped when false
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpolation: Option<InterpolationModeSchema>, // overrides the project's
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub config: Option<RequestConfigSchema>, // Optional config block
-    #[serde(default)]
-    pub headers: Option<HashMap<String, String>>, // Optional headers block
-    #[serde(default)]
-    pub query: Option<HashMap<String, String>>, // Optional query block, values can be complex
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<AuthSchema>, // Optional authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<IndexMap<String, String>>, // Optional headers block
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path_params: Option<IndexMap<String, String>>, // Values of the :name segments of the url
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub query: Option<IndexMap<String, String>>, // Optional query block, values can be complex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub body: Option<RequestBodySchema>, // Optional body block
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub capture: IndexMap<String, String>, // variables to set from the response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assert: Vec<String>, // conditions the response must satisfy
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assert_lines: Vec<String>, // conditions each line of an ndjson response (or item of a json list) must satisfy
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<ResponseValidationSchema>, // json schema of the response body
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot: Option<SnapshotSchema>, // compare the response with the one stored by the first run
 }
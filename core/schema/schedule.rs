@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+use crate::schema::notification::NotificationSchema;
+
+/// A sequence run on a schedule by `dotapi schedule`, to monitor an api.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScheduleSchema {
+    pub name: String,
+    #[serde(default = "default_sequence")]
+    pub sequence: String, // the call sequence to run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cron: Option<String>, // minute hour day-of-month month day-of-week, eg: */5 * * * *
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every: Option<u32>, // seconds between runs, used when there's no cron
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notify: Vec<NotificationSchema>, // sent the summary of runs
+}
+
+fn default_sequence() -> String {
+    return "main".to_string();
+}
@@ -0,0 +1,8 @@
+use serde::{Deserialize, Serialize};
+
+/// Compares the response with the one stored the first time the request ran.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct SnapshotSchema {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>, // dotted paths in the body that change between calls, `*` matches any key or index (eg: data.*.id)
+}
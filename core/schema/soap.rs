@@ -0,0 +1,30 @@
+use serde::{Deserialize, Serialize};
+
+/// The SOAP version of a soap body, it decides the envelope namespace and how the action is sent.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum SoapVersionSchema {
+    #[default]
+    #[serde(rename = "1.1")]
+    V11, // text/xml with a SOAPAction header
+    #[serde(rename = "1.2")]
+    V12, // application/soap+xml with the action as a content-type parameter
+}
+
+impl SoapVersionSchema {
+    pub fn namespace(&self) -> &'static str {
+        return match self {
+            SoapVersionSchema::V11 => "http://schemas.xmlsoap.org/soap/envelope/",
+            SoapVersionSchema::V12 => "http://www.w3.org/2003/05/soap-envelope",
+        };
+    }
+
+    pub fn content_type(&self, action: Option<&str>) -> String {
+        return match (self, action) {
+            (SoapVersionSchema::V11, _) => "text/xml; charset=utf-8".to_string(),
+            (SoapVersionSchema::V12, None) => "application/soap+xml; charset=utf-8".to_string(),
+            (SoapVersionSchema::V12, Some(action)) => {
+                format!("application/soap+xml; charset=utf-8; action=\"{}\"", action)
+            }
+        };
+    }
+}
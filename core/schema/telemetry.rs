@@ -0,0 +1,35 @@
+use indexmap::IndexMap;
+
+use serde::{Deserialize, Serialize};
+
+/// OpenTelemetry tracing of runs: a span for the run and one for each request sent.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TelemetrySchema {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>, // otlp/http collector, eg: http://localhost:4318, spans aren't exported without it
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub headers: IndexMap<String, String>, // sent along with the exported spans, eg: an api key
+    #[serde(default = "default_propagate")]
+    pub propagate: bool, // adds a traceparent header to requests so services join the trace
+}
+
+fn default_service_name() -> String {
+    return "dotapi".to_string();
+}
+
+fn default_propagate() -> bool {
+    return true;
+}
+
+impl Default for TelemetrySchema {
+    fn default() -> Self {
+        return TelemetrySchema {
+            endpoint: None,
+            service_name: default_service_name(),
+            headers: IndexMap::new(),
+            propagate: default_propagate(),
+        };
+    }
+}
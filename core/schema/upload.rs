@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+
+/// Sends the file of a binary body in chunks with a resumable upload protocol,
+/// starting from the request's url. The other calls of the protocol are derived from it.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct UploadSchema {
+    pub protocol: UploadProtocolSchema,
+    #[serde(default = "default_chunk_size")]
+    pub chunk_size: u64, // in bytes, the last chunk holds the rest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub upload_id: Option<String>, // parts: json path or expression of the id in the initiate response, eg: $.UploadId
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part_url: Option<String>, // parts: url of each part, with {{upload_id}} and {{part_number}}
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complete_url: Option<String>, // parts: url completing the upload, with {{upload_id}}
+    #[serde(default, skip_serializing_if = "crate::schema::is_default")]
+    pub complete_format: UploadCompleteFormatSchema, // parts: body listing the parts sent to complete_url
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadProtocolSchema {
+    Tus, // tus 1.0: creates the upload, then PATCHes chunks to its Location, resuming from the server's offset
+    Parts, // S3 multipart style: initiate, PUT numbered parts, then complete with their ETags
+    ContentRange, // the chunks are sent to the url one after the other with a Content-Range, eg: GCS resumable uploads
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UploadCompleteFormatSchema {
+    #[default]
+    Xml, // <CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>..</ETag></Part></CompleteMultipartUpload>
+    Json, // {"parts": [{"part_number": 1, "etag": ".."}]}
+}
+
+// 5MiB, the smallest part S3 accepts
+fn default_chunk_size() -> u64 {
+    return 5 * 1024 * 1024;
+}
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// A JSON Schema the response body is validated against,
+/// either the path to a json/yaml file (relative to the request file) or the schema itself.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ResponseValidationSchema {
+    Path(String),
+    Inline(serde_yaml::Value),
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fs::{LoadFailure, LoadFailureKind, read_request},
+    index::RequestHeaderSchema,
+    schema::roots::RequestRootSchema,
+};
+
+pub const SCHEMA_CACHE_FILE: &str = ".nd-cache.json";
+
+// Identifies a version of a file, a file with a different stamp is parsed again
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    modified_secs: u64,
+    modified_nanos: u32,
+    size: u64,
+}
+
+impl FileStamp {
+    async fn of(path: &Path) -> Option<FileStamp> {
+        let metadata = tokio::fs::metadata(path).await.ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+
+        return Some(FileStamp {
+            modified_secs: modified.as_secs(),
+            modified_nanos: modified.subsec_nanos(),
+            size: metadata.len(),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSchema {
+    stamp: FileStamp,
+    header: RequestHeaderSchema,
+    // the whole request, once it has been loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    schema: Option<serde_json::Value>,
+}
+
+/// Parsed request files, keyed by path and checked against the file's modification time.
+///
+/// Kept as json next to the project file, which parses much faster than the yaml
+/// of the requests, so repeated runs only parse the files that changed.
+/// Listing a request only parses and caches its header, the rest is parsed when it's loaded.
+#[derive(Debug, Default)]
+pub struct SchemaCache {
+    path: Option<PathBuf>,
+    entries: HashMap<PathBuf, CachedSchema>,
+    changed: bool,
+}
+
+impl SchemaCache {
+    // The cache of the project at root, empty if it doesn't exist or can't be read
+    pub async fn open(root: &Path) -> SchemaCache {
+        let path = root.join(SCHEMA_CACHE_FILE);
+        let entries = match tokio::fs::read_to_string(&path).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => HashMap::new(),
+        };
+
+        return SchemaCache {
+            path: Some(path),
+            entries,
+            changed: false,
+        };
+    }
+
+    // Reads a request file, parsing it only if it changed since it was cached
+    pub async fn read_request(&mut self, path: &Path) -> Result<RequestRootSchema, LoadFailure> {
+        let stamp = FileStamp::of(path).await;
+
+        if let (Some(stamp), Some(cached)) = (stamp, self.entries.get(path))
+            && cached.stamp == stamp
+            && let Some(schema) = &cached.schema
+            && let Ok(schema) = serde_json::from_value(schema.clone())
+        {
+            return Ok(schema);
+        }
+
+        let schema = read_request(path).await?;
+
+        // values json can't represent (eg: non string keys) are parsed every time
+        if let (Some(stamp), Ok(value)) = (stamp, serde_json::to_value(&schema)) {
+            self.entries.insert(
+                path.to_path_buf(),
+                CachedSchema {
+                    stamp,
+                    header: serde_json::from_value(value.clone()).unwrap_or_default(),
+                    schema: Some(value),
+                },
+            );
+            self.changed = true;
+        }
+
+        return Ok(schema);
+    }
+
+    // The header of a request file, from the cache if the file hasn't changed.
+    // Otherwise only the header is parsed, requests that don't load are still listed
+    // and fail when they are loaded.
+    pub(crate) async fn read_header(
+        &mut self,
+        path: &Path,
+    ) -> Result<RequestHeaderSchema, LoadFailure> {
+        if let Some(header) = self.cached_header(path).await {
+            return Ok(header);
+        }
+
+        let stamp = FileStamp::of(path).await;
+        let failure = |kind: LoadFailureKind, message: String| LoadFailure {
+            path: path.to_path_buf(),
+            kind,
+            message,
+        };
+
+        let content = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|e| failure(LoadFailureKind::Missing, e.to_string()))?;
+
+        let header = serde_yaml::from_str::<RequestHeaderSchema>(&content)
+            .map_err(|e| failure(LoadFailureKind::Parse, e.to_string()))?;
+
+        if let Some(stamp) = stamp {
+            self.entries.insert(
+                path.to_path_buf(),
+                CachedSchema {
+                    stamp,
+                    header: header.clone(),
+                    schema: None,
+                },
+            );
+            self.changed = true;
+        }
+
+        return Ok(header);
+    }
+
+    pub(crate) async fn cached_header(&self, path: &Path) -> Option<RequestHeaderSchema> {
+        let cached = self.entries.get(path)?;
+        if FileStamp::of(path).await? != cached.stamp {
+            return None;
+        }
+
+        return Some(cached.header.clone());
+    }
+
+    // Writes the cache back if a file was parsed, dropping files that no longer exist
+    pub async fn save(&mut self) -> anyhow::Result<()> {
+        let path = match &self.path {
+            Some(path) if self.changed => path,
+            _ => return Ok(()),
+        };
+
+        self.entries.retain(|file, _| file.exists());
+        tokio::fs::write(path, serde_json::to_string(&self.entries)?)
+            .await
+            .with_context(|| format!("Failed to write schema cache {:?}", path))?;
+
+        self.changed = false;
+        return Ok(());
+    }
+}
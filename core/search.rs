@@ -0,0 +1,280 @@
+use crate::{fs::FileObject, history::HistoryEntry, schema::roots::RequestRootSchema};
+
+// Characters of text kept on each side of a match in a response body
+const SNIPPET_CONTEXT: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SearchField {
+    Name,
+    Url,
+    Doc,
+    Response,
+}
+
+impl SearchField {
+    pub fn label(&self) -> &'static str {
+        return match self {
+            SearchField::Name => "name",
+            SearchField::Url => "url",
+            SearchField::Doc => "doc",
+            SearchField::Response => "response",
+        };
+    }
+}
+
+/// A request matching a search, with the text that matched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchMatch {
+    // call name of the request
+    pub request: String,
+    pub field: SearchField,
+    pub text: String,
+    pub score: i64,
+    // the history entry whose response matched
+    pub history_id: Option<uuid::Uuid>,
+}
+
+// Searches request names, urls and docs with a fuzzy match, and the recorded response
+// bodies for the query as written. Best matches first, one per request and field.
+pub fn search(
+    query: &str,
+    requests: &[FileObject<RequestRootSchema>],
+    history: &[HistoryEntry],
+    limit: usize,
+) -> Vec<SearchMatch> {
+    let query = query.trim();
+    if query.is_empty() {
+        return vec![];
+    }
+
+    let mut matches = vec![];
+
+    for request in requests {
+        let name = request.get_call_name();
+        let fields = [
+            (SearchField::Name, name.clone()),
+            (SearchField::Url, request.object.url.clone()),
+            (SearchField::Doc, request.object.doc.clone()),
+        ];
+
+        for (field, text) in fields {
+            if let Some(score) = fuzzy_score(query, &text) {
+                matches.push(SearchMatch {
+                    request: name.clone(),
+                    field,
+                    text,
+                    score,
+                    history_id: None,
+                });
+            }
+        }
+    }
+
+    // history is newest first, only the latest matching response of a request is kept
+    let lowered = query.to_lowercase();
+    for entry in history {
+        let name = &entry.exchange.name;
+        let seen = matches
+            .iter()
+            .any(|m| m.request == *name && m.field == SearchField::Response);
+        if seen {
+            continue;
+        }
+
+        let body = &entry.exchange.response.body;
+        if let Some(index) = body.to_lowercase().find(&lowered) {
+            matches.push(SearchMatch {
+                request: name.clone(),
+                field: SearchField::Response,
+                text: snippet(body, index, query.len()),
+                score: 0,
+                history_id: Some(entry.id),
+            });
+        }
+    }
+
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then(a.field.cmp(&b.field))
+            .then(a.request.cmp(&b.request))
+    });
+    matches.truncate(limit);
+    return matches;
+}
+
+// Scores text containing the characters of query in order, case-insensitively.
+// Consecutive characters and characters starting a word score higher, None when it doesn't match.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i64> {
+    let text = text.chars().collect::<Vec<char>>();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous: Option<usize> = None;
+
+    for wanted in query.chars().filter(|c| !c.is_whitespace()) {
+        let wanted = wanted.to_lowercase().next()?;
+        let index = (position..text.len())
+            .find(|&index| text[index].to_lowercase().next() == Some(wanted))?;
+
+        score += 1;
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            score += 5;
+        }
+        if index == 0 || !text[index - 1].is_alphanumeric() {
+            score += 3;
+        }
+        // matches further in the text score a bit less
+        score -= (index - position).min(10) as i64;
+
+        previous = Some(index);
+        position = index + 1;
+    }
+
+    return Some(score);
+}
+
+// The text around a match, on a single line
+fn snippet(text: &str, index: usize, length: usize) -> String {
+    // lowercasing can change the length of some characters
+    let index = index.min(text.len());
+    let mut start = index.saturating_sub(SNIPPET_CONTEXT);
+    while !text.is_char_boundary(start) {
+        start -= 1;
+    }
+    let mut end = (index + length + SNIPPET_CONTEXT).min(text.len());
+    while !text.is_char_boundary(end) {
+        end += 1;
+    }
+
+    let mut result = text[start..end]
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if start > 0 {
+        result.insert(0, '…');
+    }
+    if end < text.len() {
+        result.push('…');
+    }
+
+    return result;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::bundle::{Bundle, BundleRequest, BundleResponse};
+
+    fn request(name: &str, url: &str, doc: &str) -> FileObject<RequestRootSchema> {
+        return FileObject::new(
+            PathBuf::from(format!("/project/requests/{}.nd", name)),
+            serde_yaml::from_str(&format!("method: GET\nurl: \"{}\"\ndoc: \"{}\"", url, doc))
+                .unwrap(),
+        );
+    }
+
+    fn entry(name: &str, body: &str) -> HistoryEntry {
+        return HistoryEntry {
+            id: uuid::Uuid::new_v4(),
+            exchange: Bundle {
+                name: name.to_string(),
+                environment: None,
+                created_at: 0,
+                request: BundleRequest {
+                    method: "GET".to_string(),
+                    url: "http://localhost".to_string(),
+                    headers: vec![],
+                    body: None,
+                },
+                response: BundleResponse {
+                    status: 200,
+                    headers: Default::default(),
+                    body: body.to_string(),
+                    duration_ms: 1,
+                },
+            },
+        };
+    }
+
+    #[test]
+    fn fuzzy_scores_prefer_consecutive_characters_at_word_starts() {
+        assert_eq!(fuzzy_score("xyz", "get_users"), None);
+        assert_eq!(fuzzy_score("sru", "users"), None);
+
+        let prefix = fuzzy_score("user", "users").unwrap();
+        let inside = fuzzy_score("user", "list_users").unwrap();
+        let scattered = fuzzy_score("user", "update_some_record").unwrap();
+        assert!(prefix > inside, "{} {}", prefix, inside);
+        assert!(inside > scattered, "{} {}", inside, scattered);
+
+        // case and spaces in the query don't matter
+        assert_eq!(
+            fuzzy_score("Get U", "get_users"),
+            fuzzy_score("getu", "get_users")
+        );
+    }
+
+    #[test]
+    fn requests_match_by_name_url_and_doc_best_first() {
+        let requests = [
+            request("login", "{{base_url}}/auth/login", "Signs users in"),
+            request("users", "{{base_url}}/users", "Lists the accounts"),
+        ];
+
+        let matches = search("users", &requests, &[], 10);
+
+        let found = matches
+            .iter()
+            .map(|m| (m.request.as_str(), m.field))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            found,
+            vec![
+                ("users", SearchField::Name),
+                ("login", SearchField::Doc),
+                ("users", SearchField::Url),
+            ]
+        );
+        assert_eq!(matches[1].text, "Signs users in");
+
+        assert_eq!(search("users", &requests, &[], 1).len(), 1);
+        assert!(search("  ", &requests, &[], 10).is_empty());
+    }
+
+    #[test]
+    fn responses_match_the_query_as_written_with_the_latest_entry() {
+        let newest = entry("users", "{\"items\": [{\"email\": \"Ada@example.com\"}]}");
+        let history = [
+            newest.clone(),
+            entry("users", "{\"email\": \"ada@example.com\"}"),
+            entry("login", "{\"token\": \"abc\"}"),
+        ];
+
+        let matches = search("ada@example", &[], &history, 10);
+
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].field, SearchField::Response);
+        assert_eq!(matches[0].history_id, Some(newest.id));
+        assert_eq!(
+            matches[0].text,
+            "{\"items\": [{\"email\": \"Ada@example.com\"}]}"
+        );
+
+        // the characters of the query aren't searched for out of order in bodies
+        assert!(search("aexample", &[], &history, 10).is_empty());
+    }
+
+    #[test]
+    fn long_bodies_are_cut_around_the_match() {
+        let body = format!("{}needle\n\n{}", "a ".repeat(100), "b ".repeat(100));
+        let history = [entry("users", &body)];
+
+        let text = &search("needle", &[], &history, 10)[0].text;
+
+        assert!(text.starts_with('…') && text.ends_with('…'), "{}", text);
+        assert!(text.contains("a needle b"), "{}", text);
+        assert!(!text.contains('\n'));
+    }
+}
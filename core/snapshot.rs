@@ -0,0 +1,265 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    diff::{diff_json, normalize_body},
+    runner::{ResponseObject, Runner},
+    schema::snapshot::SnapshotSchema,
+};
+
+// Differences listed in a failure, the rest are counted
+const MAX_DIFFERENCES: usize = 10;
+
+/// How the runner treats the snapshots of requests.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SnapshotMode {
+    #[default]
+    // fail when a response differs from its snapshot, requests without one store it
+    Compare,
+    // store every response as the new snapshot
+    Update,
+}
+
+/// The status and normalized body of a response, stored in the snapshots folder.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+impl From<&ResponseObject> for Snapshot {
+    fn from(response: &ResponseObject) -> Self {
+        return Snapshot {
+            status: response.status,
+            body: normalize_body(response),
+        };
+    }
+}
+
+impl Runner {
+    pub fn get_snapshots_dir(&self) -> PathBuf {
+        return self
+            .project
+            .get_root_dir()
+            .join(match &self.project.object.snapshots_dir {
+                Some(dir) => dir,
+                None => "snapshots",
+            });
+    }
+
+    pub fn get_snapshot_path(&self, name: &str) -> PathBuf {
+        return self.get_snapshots_dir().join(format!("{}.json", name));
+    }
+
+    // Compares a response with the snapshot of its request, returning how it drifted.
+    // The snapshot is written when there's none yet or the mode is Update.
+    pub(crate) async fn check_snapshot(
+        &self,
+        name: &str,
+        schema: &SnapshotSchema,
+        response: &ResponseObject,
+    ) -> anyhow::Result<Vec<String>> {
+        let actual = Snapshot::from(response);
+        let path = self.get_snapshot_path(name);
+
+        if self.snapshot_mode == SnapshotMode::Update || !path.exists() {
+            tokio::fs::create_dir_all(self.get_snapshots_dir()).await?;
+            tokio::fs::write(&path, serde_json::to_string_pretty(&actual)?)
+                .await
+                .with_context(|| format!("Failed to write snapshot {:?}", &path))?;
+
+            tracing::info!("Stored the snapshot of {}", name);
+            return Ok(vec![]);
+        }
+
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read snapshot {:?}", &path))?;
+        let expected = serde_json::from_str::<Snapshot>(&content)
+            .with_context(|| format!("Invalid snapshot for \"{}\"", name))?;
+
+        let mut failures = vec![];
+        if expected.status != actual.status {
+            failures.push(format!(
+                "snapshot: expected status {}, got {}",
+                expected.status, actual.status
+            ));
+        }
+
+        let mut differences = vec![];
+        diff_json(
+            &mut vec![],
+            &expected.body,
+            &actual.body,
+            &schema.ignore,
+            &mut differences,
+        );
+
+        for difference in differences.iter().take(MAX_DIFFERENCES) {
+            let path = match difference.path.is_empty() {
+                true => "body",
+                false => &difference.path,
+            };
+            failures.push(match (&difference.left, &difference.right) {
+                (Some(left), Some(right)) => {
+                    format!("snapshot: {} changed from {} to {}", path, left, right)
+                }
+                (None, Some(right)) => format!("snapshot: {} was added: {}", path, right),
+                _ => format!("snapshot: {} was removed", path),
+            });
+        }
+        if differences.len() > MAX_DIFFERENCES {
+            failures.push(format!(
+                "snapshot: and {} more differences",
+                differences.len() - MAX_DIFFERENCES
+            ));
+        }
+
+        return Ok(failures);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fs::FileObject;
+
+    fn runner() -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-snapshot-{}", uuid::Uuid::new_v4()));
+        return Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![],
+        )
+        .unwrap();
+    }
+
+    fn response(status: u16, body: &str) -> ResponseObject {
+        return ResponseObject {
+            status,
+            headers: [("content-type".to_string(), "application/json".to_string())].into(),
+            body: body.as_bytes().to_vec(),
+            ..Default::default()
+        };
+    }
+
+    fn ignore(paths: &[&str]) -> SnapshotSchema {
+        return SnapshotSchema {
+            ignore: paths.iter().map(|path| path.to_string()).collect(),
+        };
+    }
+
+    #[tokio::test]
+    async fn the_first_run_stores_the_snapshot() {
+        let runner = runner();
+        let path = runner.get_snapshot_path("user");
+        assert!(path.starts_with(runner.project.get_root_dir().join("snapshots")));
+
+        let failures = runner
+            .check_snapshot("user", &ignore(&[]), &response(200, "{\"id\": 1}"))
+            .await
+            .unwrap();
+
+        assert!(failures.is_empty());
+        let stored = serde_json::from_str::<Snapshot>(&std::fs::read_to_string(&path).unwrap());
+        assert_eq!(
+            stored.unwrap(),
+            Snapshot {
+                status: 200,
+                body: serde_json::json!({ "id": 1 })
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn a_response_that_drifted_fails_with_its_differences() {
+        let runner = runner();
+        let schema = ignore(&["updated_at"]);
+        runner
+            .check_snapshot(
+                "user",
+                &schema,
+                &response(200, "{\"id\": 1, \"name\": \"a\", \"updated_at\": 1}"),
+            )
+            .await
+            .unwrap();
+
+        let failures = runner
+            .check_snapshot(
+                "user",
+                &schema,
+                &response(
+                    201,
+                    "{\"id\": 1, \"name\": \"b\", \"admin\": true, \"updated_at\": 2}",
+                ),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            failures,
+            vec![
+                "snapshot: expected status 200, got 201",
+                "snapshot: admin was added: true",
+                "snapshot: name changed from \"a\" to \"b\"",
+            ]
+        );
+
+        // the same response, key order and formatting aside, passes
+        let failures = runner
+            .check_snapshot(
+                "user",
+                &schema,
+                &response(200, "{\"updated_at\": 3,\n \"name\": \"a\", \"id\": 1}"),
+            )
+            .await
+            .unwrap();
+        assert!(failures.is_empty(), "{:?}", failures);
+    }
+
+    #[tokio::test]
+    async fn only_the_first_differences_are_listed() {
+        let runner = runner();
+        let items = |value: u32| {
+            let items = (0..15).map(|_| value.to_string()).collect::<Vec<_>>();
+            format!("[{}]", items.join(","))
+        };
+        runner
+            .check_snapshot("items", &ignore(&[]), &response(200, &items(1)))
+            .await
+            .unwrap();
+
+        let failures = runner
+            .check_snapshot("items", &ignore(&[]), &response(200, &items(2)))
+            .await
+            .unwrap();
+
+        assert_eq!(failures.len(), MAX_DIFFERENCES + 1);
+        assert_eq!(failures[0], "snapshot: 0 changed from 1 to 2");
+        assert_eq!(failures[10], "snapshot: and 5 more differences");
+    }
+
+    #[tokio::test]
+    async fn update_mode_replaces_the_snapshot() {
+        let mut runner = runner();
+        runner
+            .check_snapshot("user", &ignore(&[]), &response(200, "{\"id\": 1}"))
+            .await
+            .unwrap();
+
+        runner.snapshot_mode = SnapshotMode::Update;
+        let failures = runner
+            .check_snapshot("user", &ignore(&[]), &response(200, "{\"id\": 2}"))
+            .await
+            .unwrap();
+        assert!(failures.is_empty());
+
+        runner.snapshot_mode = SnapshotMode::Compare;
+        let failures = runner
+            .check_snapshot("user", &ignore(&[]), &response(200, "{\"id\": 2}"))
+            .await
+            .unwrap();
+        assert!(failures.is_empty(), "{:?}", failures);
+    }
+}
@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use serde_yaml::Value;
+
+use crate::interpolation::EnvMap;
+
+/// Values set during a run, eg: by the captures of a request, usable as variables by later requests.
+///
+/// The state lives for the run unless the project sets `state_file`, then it's read when the
+/// runner is created and written every time it changes, so later runs start from it.
+#[derive(Default)]
+pub struct StateStore {
+    values: EnvMap,
+    path: Option<PathBuf>,
+}
+
+impl StateStore {
+    // A state kept in the file at path, starting with the values already saved in it
+    pub fn persisted(path: PathBuf) -> anyhow::Result<StateStore> {
+        let mut state = StateStore {
+            values: EnvMap::new(),
+            path: Some(path),
+        };
+
+        state.reload()?;
+        return Ok(state);
+    }
+
+    pub fn path(&self) -> Option<&PathBuf> {
+        return self.path.as_ref();
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        return self.values.get(key);
+    }
+
+    pub fn set(&mut self, key: &str, value: Value) {
+        self.values.insert(key.to_string(), value);
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        return self.values.remove(key);
+    }
+
+    pub fn values(&self) -> &EnvMap {
+        return &self.values;
+    }
+
+    // A state for a nested scope, starting from these values when inherited. It's never persisted.
+    pub fn scoped(&self, inherit: bool) -> StateStore {
+        return StateStore {
+            values: if inherit {
+                self.values.clone()
+            } else {
+                EnvMap::new()
+            },
+            path: None,
+        };
+    }
+
+    // The values as they are now, to go back to with restore
+    pub fn snapshot(&self) -> EnvMap {
+        return self.values.clone();
+    }
+
+    // Goes back to the values of a snapshot, saving them if the state is persisted
+    pub async fn restore(&mut self, values: EnvMap) -> anyhow::Result<()> {
+        self.values = values;
+        return self.save().await;
+    }
+
+    // Forgets every value, the state file is left as is until the next save
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    // Goes back to the values saved in the state file, or an empty state if it isn't persisted
+    pub fn reload(&mut self) -> anyhow::Result<()> {
+        self.values.clear();
+
+        let path = match &self.path {
+            Some(path) if path.exists() => path,
+            _ => return Ok(()),
+        };
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read state file {:?}", path))?;
+        if !content.trim().is_empty() {
+            self.values = serde_yaml::from_str(&content)
+                .with_context(|| format!("Invalid state file {:?}", path))?;
+        }
+
+        return Ok(());
+    }
+
+    // Writes the values to the state file, if the state is persisted
+    pub async fn save(&self) -> anyhow::Result<()> {
+        let path = match &self.path {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut values = self.values.iter().collect::<Vec<_>>();
+        values.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut mapping = serde_yaml::Mapping::new();
+        for (key, value) in values {
+            mapping.insert(Value::String(key.clone()), value.clone());
+        }
+
+        tokio::fs::write(path, serde_yaml::to_string(&mapping)?)
+            .await
+            .with_context(|| format!("Failed to write state file {:?}", path))?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fs::FileObject, runner::Runner, schema::roots::ProjectRootSchema};
+
+    fn state_file() -> PathBuf {
+        return std::env::temp_dir().join(format!("dotapi-state-{}.yaml", uuid::Uuid::new_v4()));
+    }
+
+    #[tokio::test]
+    async fn a_persisted_state_is_saved_sorted_and_read_back() {
+        let path = state_file();
+        let mut state = StateStore::persisted(path.clone()).unwrap();
+        assert!(state.values().is_empty());
+
+        state.set("token", Value::from("abc"));
+        state.set("id", Value::from(7));
+        state.save().await.unwrap();
+
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "id: 7\ntoken: abc\n"
+        );
+
+        let reopened = StateStore::persisted(path.clone()).unwrap();
+        assert_eq!(reopened.get("token"), Some(&Value::from("abc")));
+        assert_eq!(reopened.get("id"), Some(&Value::from(7)));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn reload_drops_the_values_that_were_not_saved() {
+        let path = state_file();
+        let mut state = StateStore::persisted(path.clone()).unwrap();
+        state.set("token", Value::from("abc"));
+        state.save().await.unwrap();
+
+        state.set("token", Value::from("def"));
+        state.set("other", Value::from(true));
+        state.reload().unwrap();
+
+        assert_eq!(state.get("token"), Some(&Value::from("abc")));
+        assert_eq!(state.get("other"), None);
+
+        // cleared values are only gone from the file once saved
+        state.clear();
+        state.reload().unwrap();
+        assert_eq!(state.get("token"), Some(&Value::from("abc")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restoring_a_snapshot_saves_it() {
+        let path = state_file();
+        let mut state = StateStore::persisted(path.clone()).unwrap();
+        state.set("attempt", Value::from(1));
+        let snapshot = state.snapshot();
+
+        state.set("attempt", Value::from(2));
+        state.save().await.unwrap();
+        state.restore(snapshot).await.unwrap();
+
+        assert_eq!(state.get("attempt"), Some(&Value::from(1)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "attempt: 1\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn scoped_states_are_never_persisted() {
+        let path = state_file();
+        let mut state = StateStore::persisted(path.clone()).unwrap();
+        state.set("token", Value::from("abc"));
+        state.save().await.unwrap();
+
+        let mut inherited = state.scoped(true);
+        assert_eq!(inherited.get("token"), Some(&Value::from("abc")));
+        assert_eq!(inherited.path(), None);
+        inherited.set("token", Value::from("def"));
+        inherited.save().await.unwrap();
+
+        let isolated = state.scoped(false);
+        assert!(isolated.values().is_empty());
+
+        assert_eq!(state.get("token"), Some(&Value::from("abc")));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "token: abc\n");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn runners_start_from_the_state_file_of_the_project() {
+        let root = std::env::temp_dir().join(format!("dotapi-state-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("state.yaml"), "token: abc\n").unwrap();
+        let project = serde_yaml::from_str::<ProjectRootSchema>(
+            "project: {name: state}\ncalls: {main: []}\nstate_file: state.yaml",
+        )
+        .unwrap();
+
+        let runner =
+            Runner::new(FileObject::new(root.join(".nd-project"), project), vec![]).unwrap();
+
+        assert_eq!(runner.state.path(), Some(&root.join("state.yaml")));
+        assert_eq!(runner.state.get("token"), Some(&Value::from("abc")));
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn an_invalid_state_file_fails() {
+        let path = state_file();
+        std::fs::write(&path, "- not\n- a map\n").unwrap();
+
+        let error = StateStore::persisted(path.clone()).err().unwrap();
+        assert!(
+            error.to_string().starts_with("Invalid state file"),
+            "{}",
+            error
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+}
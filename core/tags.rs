@@ -0,0 +1,127 @@
+use crate::{runner::Runner, schema::calls::StepSchema};
+
+/// Selects the steps of a run by their tags, eg: `--tags smoke,auth --exclude-tags slow`.
+///
+/// A step is selected when it has one of the included tags, or any tags when none are
+/// included, and none of the excluded ones. Tags are compared case-insensitively.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TagFilter {
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+}
+
+impl TagFilter {
+    pub fn new(include: Vec<String>, exclude: Vec<String>) -> TagFilter {
+        return TagFilter { include, exclude };
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.include.is_empty() && self.exclude.is_empty();
+    }
+
+    pub fn matches(&self, tags: &[String]) -> bool {
+        let has = |wanted: &String| tags.iter().any(|tag| tag.eq_ignore_ascii_case(wanted));
+
+        if self.exclude.iter().any(has) {
+            return false;
+        }
+
+        return self.include.is_empty() || self.include.iter().any(has);
+    }
+}
+
+impl Runner {
+    // The tags of a step along with the ones of its request
+    pub fn step_tags(&self, step: &StepSchema) -> Vec<String> {
+        let mut tags = step.tags().to_vec();
+
+        if let Ok(request) = self.find_request(step.name()) {
+            for tag in request.object.config.iter().flat_map(|config| &config.tags) {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+        }
+
+        return tags;
+    }
+
+    // Whether the step is called with the tag filter of the run
+    pub fn is_selected(&self, step: &StepSchema) -> bool {
+        if self.tags.is_empty() {
+            return true;
+        }
+
+        let selected = self.tags.matches(&self.step_tags(step));
+        if !selected {
+            tracing::debug!("{} is not selected by its tags", step.name());
+        }
+
+        return selected;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fs::FileObject, schema::roots::RequestRootSchema};
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        return values.iter().map(|value| value.to_string()).collect();
+    }
+
+    fn step(yaml: &str) -> StepSchema {
+        return serde_yaml::from_str(yaml).unwrap();
+    }
+
+    #[test]
+    fn an_empty_filter_matches_everything() {
+        let filter = TagFilter::default();
+
+        assert!(filter.is_empty());
+        assert!(filter.matches(&[]));
+        assert!(filter.matches(&tags(&["slow"])));
+    }
+
+    #[test]
+    fn included_tags_select_and_excluded_ones_win() {
+        let filter = TagFilter::new(tags(&["smoke", "auth"]), tags(&["slow"]));
+
+        assert!(filter.matches(&tags(&["auth"])));
+        assert!(filter.matches(&tags(&["SMOKE", "users"])));
+        assert!(!filter.matches(&tags(&["users"])));
+        assert!(!filter.matches(&[]));
+        assert!(!filter.matches(&tags(&["smoke", "Slow"])));
+
+        let exclude_only = TagFilter::new(vec![], tags(&["slow"]));
+        assert!(exclude_only.matches(&[]));
+        assert!(!exclude_only.matches(&tags(&["slow"])));
+    }
+
+    #[test]
+    fn steps_are_selected_by_their_tags_and_their_request_ones() {
+        let root = std::env::temp_dir().join(format!("dotapi-tags-{}", uuid::Uuid::new_v4()));
+        let request = serde_yaml::from_str::<RequestRootSchema>(
+            "method: GET\nurl: http://localhost/users\nconfig: {tags: [users, slow]}",
+        )
+        .unwrap();
+        let mut runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![FileObject::new(root.join("requests/users.nd"), request)],
+        )
+        .unwrap();
+
+        let tagged = step("{request: users, tags: [smoke, users]}");
+        assert_eq!(runner.step_tags(&tagged), tags(&["smoke", "users", "slow"]));
+        assert!(runner.is_selected(&tagged));
+
+        runner.tags = TagFilter::new(tags(&["smoke"]), vec![]);
+        assert!(runner.is_selected(&tagged));
+        assert!(!runner.is_selected(&step("users")));
+
+        // the request's tags are excluded whatever the step adds
+        runner.tags = TagFilter::new(vec![], tags(&["slow"]));
+        assert!(!runner.is_selected(&tagged));
+        assert!(runner.is_selected(&step("{request: missing, tags: [smoke]}")));
+    }
+}
@@ -0,0 +1,449 @@
+mod otlp;
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use crate::{
+    middleware::RequestMiddleware,
+    report::RunReport,
+    runner::{ResponseObject, Runner},
+    schema::telemetry::TelemetrySchema,
+};
+
+/// Ids of a span within its trace, hex encoded like in the traceparent header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanContext {
+    pub trace_id: String,
+    pub span_id: String,
+}
+
+impl SpanContext {
+    fn new_trace() -> SpanContext {
+        return SpanContext {
+            trace_id: format!("{:032x}", rand::random::<u128>()),
+            span_id: new_span_id(),
+        };
+    }
+
+    fn child(&self) -> SpanContext {
+        return SpanContext {
+            trace_id: self.trace_id.clone(),
+            span_id: new_span_id(),
+        };
+    }
+
+    // The w3c trace context header of a sampled span
+    pub fn traceparent(&self) -> String {
+        return format!("00-{}-{}-01", self.trace_id, self.span_id);
+    }
+}
+
+fn new_span_id() -> String {
+    return format!("{:016x}", rand::random::<u64>());
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    String(String),
+    Int(i64),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpanKind {
+    // the run of a sequence
+    Internal,
+    // a request sent
+    Client,
+}
+
+/// A timed operation of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Span {
+    pub name: String,
+    pub context: SpanContext,
+    pub parent_span_id: Option<String>,
+    pub kind: SpanKind,
+    pub start: SystemTime,
+    pub end: SystemTime,
+    pub attributes: Vec<(String, AttributeValue)>,
+    // set when the operation failed
+    pub error: Option<String>,
+}
+
+impl Span {
+    fn new(
+        name: String,
+        context: SpanContext,
+        parent: Option<&SpanContext>,
+        kind: SpanKind,
+    ) -> Span {
+        let now = SystemTime::now();
+        return Span {
+            name,
+            context,
+            parent_span_id: parent.map(|parent| parent.span_id.clone()),
+            kind,
+            start: now,
+            end: now,
+            attributes: vec![],
+            error: None,
+        };
+    }
+
+    fn set(&mut self, key: &str, value: AttributeValue) {
+        self.attributes.push((key.to_string(), value));
+    }
+
+    // Hides the secrets of the url and the error, they leave the runner with the export
+    fn redact(&mut self, redact: &impl Fn(&str) -> String) {
+        for (key, value) in self.attributes.iter_mut() {
+            if let ("url.full", AttributeValue::String(url)) = (key.as_str(), &value) {
+                *value = AttributeValue::String(redact(url));
+            }
+        }
+        if let Some(error) = &self.error {
+            self.error = Some(redact(error));
+        }
+    }
+}
+
+/// Records the spans of the runs of a runner and exports them once each run is over.
+pub struct Tracer {
+    schema: TelemetrySchema,
+    // the span of the run in progress, the requests it sends are its children
+    run: Mutex<Option<Span>>,
+    // requests sent and not answered yet, keyed by request name
+    open: Mutex<HashMap<String, Span>>,
+    finished: Mutex<Vec<Span>>,
+    client: reqwest::Client,
+}
+
+impl Tracer {
+    pub fn new(schema: TelemetrySchema) -> Tracer {
+        return Tracer {
+            schema,
+            run: Mutex::new(None),
+            open: Mutex::new(HashMap::new()),
+            finished: Mutex::new(vec![]),
+            client: reqwest::Client::new(),
+        };
+    }
+
+    pub fn start_run(&self, name: &str, environment: Option<&str>) {
+        let mut span = Span::new(
+            name.to_string(),
+            SpanContext::new_trace(),
+            None,
+            SpanKind::Internal,
+        );
+        span.set("dotapi.run", AttributeValue::String(name.to_string()));
+        if let Some(environment) = environment {
+            span.set(
+                "dotapi.environment",
+                AttributeValue::String(environment.to_string()),
+            );
+        }
+
+        if let Ok(mut run) = self.run.lock() {
+            *run = Some(span);
+        }
+    }
+
+    // Opens the span of a request, in the trace of the run or a trace of its own
+    pub fn start_request(&self, name: &str, request: &mut reqwest::Request) {
+        let parent = match self.run.lock() {
+            Ok(run) => run.as_ref().map(|run| run.context.clone()),
+            Err(_) => None,
+        };
+        let context = match &parent {
+            Some(parent) => parent.child(),
+            None => SpanContext::new_trace(),
+        };
+
+        let mut span = Span::new(
+            format!("{} {}", request.method(), name),
+            context,
+            parent.as_ref(),
+            SpanKind::Client,
+        );
+        span.set("dotapi.request", AttributeValue::String(name.to_string()));
+        span.set(
+            "http.request.method",
+            AttributeValue::String(request.method().to_string()),
+        );
+        span.set(
+            "url.full",
+            AttributeValue::String(request.url().to_string()),
+        );
+        if let Some(host) = request.url().host_str() {
+            span.set("server.address", AttributeValue::String(host.to_string()));
+        }
+
+        // a traceparent set by the request itself is kept
+        if self.schema.propagate
+            && !request.headers().contains_key("traceparent")
+            && let Ok(value) = span.context.traceparent().parse()
+        {
+            request.headers_mut().insert("traceparent", value);
+        }
+
+        // a request sent again before being answered, eg: after a timeout
+        let previous = match self.open.lock() {
+            Ok(mut open) => open.insert(name.to_string(), span),
+            Err(_) => None,
+        };
+        if let Some(previous) = previous {
+            self.finish(previous, Some("No response".to_string()));
+        }
+    }
+
+    pub fn end_request(&self, name: &str, status: u16) {
+        let span = match self.open.lock() {
+            Ok(mut open) => open.remove(name),
+            Err(_) => None,
+        };
+        let mut span = match span {
+            Some(span) => span,
+            None => return,
+        };
+
+        span.set(
+            "http.response.status_code",
+            AttributeValue::Int(status as i64),
+        );
+        let error = match status >= 400 {
+            true => Some(format!("HTTP {}", status)),
+            false => None,
+        };
+        self.finish(span, error);
+    }
+
+    // Closes the span of the run, requests that failed get the error of their entry.
+    // The urls and errors of the spans are redacted with `redact`, eg: Runner::redact_text.
+    pub fn end_run(&self, report: &RunReport, redact: impl Fn(&str) -> String) {
+        let open = match self.open.lock() {
+            Ok(mut open) => open.drain().collect::<Vec<(String, Span)>>(),
+            Err(_) => vec![],
+        };
+        for (_, span) in open {
+            self.finish(span, Some("No response".to_string()));
+        }
+
+        let run = match self.run.lock() {
+            Ok(mut run) => run.take(),
+            Err(_) => None,
+        };
+        let mut run = match run {
+            Some(run) => run,
+            None => return,
+        };
+
+        if let Ok(mut finished) = self.finished.lock() {
+            for entry in report.entries.iter().filter(|entry| !entry.passed()) {
+                let span = finished.iter_mut().rev().find(|span| {
+                    span.context.trace_id == run.context.trace_id
+                        && span.error.is_none()
+                        && span.attributes.iter().any(|(key, value)| {
+                            key == "dotapi.request"
+                                && *value == AttributeValue::String(entry.name.clone())
+                        })
+                });
+                if let Some(span) = span {
+                    span.error = entry.error.clone();
+                }
+            }
+
+            for span in finished.iter_mut() {
+                span.redact(&redact);
+            }
+        }
+
+        run.set(
+            "dotapi.requests",
+            AttributeValue::Int(report.entries.len() as i64),
+        );
+        run.set(
+            "dotapi.failures",
+            AttributeValue::Int(report.failures() as i64),
+        );
+        run.set("dotapi.cancelled", AttributeValue::Bool(report.cancelled));
+        let error = match report.passed() {
+            true => None,
+            false => Some(format!("{} requests failed", report.failures())),
+        };
+        self.finish(run, error);
+    }
+
+    fn finish(&self, mut span: Span, error: Option<String>) {
+        span.end = SystemTime::now();
+        span.error = error;
+        if let Ok(mut finished) = self.finished.lock() {
+            finished.push(span);
+        }
+    }
+
+    // Sends the finished spans to the collector, they're dropped when there's none
+    pub async fn export(&self) -> anyhow::Result<()> {
+        let spans = match self.finished.lock() {
+            Ok(mut finished) => std::mem::take(&mut *finished),
+            Err(_) => vec![],
+        };
+        let endpoint = match &self.schema.endpoint {
+            Some(endpoint) if !spans.is_empty() => endpoint,
+            _ => return Ok(()),
+        };
+
+        return otlp::export(&self.client, endpoint, &self.schema, &spans).await;
+    }
+}
+
+/// Opens and closes the spans of the requests the runner sends.
+struct TraceMiddleware {
+    tracer: Arc<Tracer>,
+}
+
+impl RequestMiddleware for TraceMiddleware {
+    fn before_send(&self, name: &str, request: &mut reqwest::Request) -> anyhow::Result<()> {
+        self.tracer.start_request(name, request);
+        return Ok(());
+    }
+
+    fn after_receive(&self, name: &str, response: &mut ResponseObject) -> anyhow::Result<()> {
+        self.tracer.end_request(name, response.status);
+        return Ok(());
+    }
+}
+
+impl Runner {
+    // Traces the runs, the spans are exported once each run is over
+    pub fn enable_telemetry(&mut self, schema: TelemetrySchema) {
+        let tracer = Arc::new(Tracer::new(schema));
+        self.add_middleware(Box::new(TraceMiddleware {
+            tracer: tracer.clone(),
+        }));
+        self.tracer = Some(tracer);
+    }
+
+    // Traces the runs when the project file sets up telemetry
+    pub fn use_project_telemetry(&mut self) {
+        if let Some(schema) = self.project.object.telemetry.clone() {
+            self.enable_telemetry(schema);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ReportEntry;
+
+    fn tracer() -> Tracer {
+        return Tracer::new(serde_yaml::from_str("{}").unwrap());
+    }
+
+    fn request(url: &str) -> reqwest::Request {
+        return reqwest::Request::new(reqwest::Method::GET, reqwest::Url::parse(url).unwrap());
+    }
+
+    fn attribute<'a>(span: &'a Span, key: &str) -> Option<&'a AttributeValue> {
+        return span
+            .attributes
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value);
+    }
+
+    fn report(entries: Vec<ReportEntry>) -> RunReport {
+        return RunReport {
+            name: "main".to_string(),
+            environment: None,
+            entries,
+            duration_ms: 0,
+            attempts: 1,
+            cancelled: false,
+        };
+    }
+
+    #[test]
+    fn requests_are_children_of_the_run_and_carry_a_traceparent() {
+        let tracer = tracer();
+        tracer.start_run("main", Some("dev"));
+
+        let mut login = request("https://x.dev/login");
+        tracer.start_request("login", &mut login);
+        tracer.end_request("login", 200);
+        tracer.end_run(&report(vec![]), |text| text.to_string());
+
+        let finished = tracer.finished.lock().unwrap();
+        let (request, run) = (&finished[0], &finished[1]);
+        assert_eq!(request.name, "GET login");
+        assert_eq!(request.context.trace_id, run.context.trace_id);
+        assert_eq!(request.parent_span_id.as_ref(), Some(&run.context.span_id));
+        assert_eq!(
+            login.headers()["traceparent"],
+            request.context.traceparent().as_str()
+        );
+        assert_eq!(
+            attribute(run, "dotapi.environment"),
+            Some(&AttributeValue::String("dev".to_string()))
+        );
+    }
+
+    #[test]
+    fn urls_and_errors_are_redacted_at_the_end_of_the_run() {
+        let tracer = tracer();
+        tracer.start_run("main", None);
+
+        tracer.start_request("users", &mut request("https://x.dev/users?key=s3cret"));
+        tracer.end_request("users", 200);
+        tracer.start_request("hang", &mut request("https://x.dev/s3cret/hang"));
+
+        let failed = ReportEntry {
+            name: "users".to_string(),
+            method: "GET".to_string(),
+            url: String::new(),
+            status: Some(200),
+            duration_ms: 0,
+            error: Some("body has s3cret".to_string()),
+            skipped: false,
+            allow_failure: false,
+            assertion_failures: vec![],
+            warnings: vec![],
+        };
+        tracer.end_run(&report(vec![failed]), |text| {
+            text.replace("s3cret", "<redacted>")
+        });
+
+        let finished = tracer.finished.lock().unwrap();
+        let url = |name: &str| {
+            let span = finished.iter().find(|span| span.name == name).unwrap();
+            return attribute(span, "url.full").cloned();
+        };
+        assert_eq!(
+            url("GET users"),
+            Some(AttributeValue::String(
+                "https://x.dev/users?key=<redacted>".to_string()
+            ))
+        );
+        assert_eq!(
+            url("GET hang"),
+            Some(AttributeValue::String(
+                "https://x.dev/<redacted>/hang".to_string()
+            ))
+        );
+
+        let users = finished
+            .iter()
+            .find(|span| span.name == "GET users")
+            .unwrap();
+        assert_eq!(users.error.as_deref(), Some("body has <redacted>"));
+        assert_eq!(
+            finished.last().unwrap().error.as_deref(),
+            Some("1 requests failed")
+        );
+    }
+}
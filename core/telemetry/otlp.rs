@@ -0,0 +1,94 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use serde_json::{Value, json};
+
+use crate::schema::telemetry::TelemetrySchema;
+
+use super::{AttributeValue, Span, SpanKind};
+
+// Posts the spans to the collector with the json encoding of otlp/http
+pub(crate) async fn export(
+    client: &reqwest::Client,
+    endpoint: &str,
+    schema: &TelemetrySchema,
+    spans: &[Span],
+) -> anyhow::Result<()> {
+    let url = format!("{}/v1/traces", endpoint.trim_end_matches('/'));
+    let body = json!({
+        "resourceSpans": [{
+            "resource": {
+                "attributes": [attribute(
+                    "service.name",
+                    &AttributeValue::String(schema.service_name.clone())
+                )],
+            },
+            "scopeSpans": [{
+                "scope": { "name": "dotapi", "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans.iter().map(span).collect::<Vec<Value>>(),
+            }],
+        }],
+    });
+
+    let mut request = client.post(&url).json(&body);
+    for (key, value) in &schema.headers {
+        request = request.header(key, value);
+    }
+
+    request
+        .send()
+        .await?
+        .error_for_status()
+        .with_context(|| format!("{} refused the spans", url))?;
+    return Ok(());
+}
+
+fn span(span: &Span) -> Value {
+    // span kinds and status codes of the otlp protocol
+    let kind = match span.kind {
+        SpanKind::Internal => 1,
+        SpanKind::Client => 3,
+    };
+    let status = match &span.error {
+        Some(message) => json!({ "code": 2, "message": message }),
+        None => json!({ "code": 1 }),
+    };
+
+    let mut value = json!({
+        "traceId": span.context.trace_id,
+        "spanId": span.context.span_id,
+        "name": span.name,
+        "kind": kind,
+        "startTimeUnixNano": unix_nanos(span.start),
+        "endTimeUnixNano": unix_nanos(span.end),
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| attribute(key, value))
+            .collect::<Vec<Value>>(),
+        "status": status,
+    });
+    if let Some(parent) = &span.parent_span_id {
+        value["parentSpanId"] = json!(parent);
+    }
+
+    return value;
+}
+
+fn attribute(key: &str, value: &AttributeValue) -> Value {
+    // 64 bit integers are strings in otlp's json
+    let value = match value {
+        AttributeValue::String(value) => json!({ "stringValue": value }),
+        AttributeValue::Int(value) => json!({ "intValue": value.to_string() }),
+        AttributeValue::Bool(value) => json!({ "boolValue": value }),
+    };
+    return json!({ "key": key, "value": value });
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    return time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string();
+}
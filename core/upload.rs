@@ -0,0 +1,689 @@
+use std::{ops::Range, time::Duration};
+
+use anyhow::Context;
+use base64::Engine;
+
+use crate::{
+    assertions::capture,
+    interpolation::{interpolate_string, value_to_string},
+    runner::{ResolvedRequest, ResponseObject, Runner},
+    schema::{
+        request_body::RequestBodySchema,
+        upload::{UploadCompleteFormatSchema, UploadProtocolSchema, UploadSchema},
+    },
+};
+
+const TUS_VERSION: &str = "1.0.0";
+
+/// What was sent by a request uploading its file in chunks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upload {
+    pub size: u64,
+    pub chunks: u32,
+    // tus: the url of the upload created
+    pub url: Option<String>,
+    // parts: the id of the upload initiated
+    pub upload_id: Option<String>,
+}
+
+impl Upload {
+    pub fn to_value(&self) -> serde_yaml::Value {
+        let mut value = serde_yaml::Mapping::new();
+        value.insert("size".into(), self.size.into());
+        value.insert("chunks".into(), self.chunks.into());
+        value.insert(
+            "url".into(),
+            self.url.clone().map(Into::into).unwrap_or_default(),
+        );
+        value.insert(
+            "upload_id".into(),
+            self.upload_id.clone().map(Into::into).unwrap_or_default(),
+        );
+        return serde_yaml::Value::Mapping(value);
+    }
+}
+
+impl Runner {
+    // Sends the file of the binary body with the upload protocol, the last response of the protocol is returned.
+    // Each call goes through execute_with_retries, so it's retried, rate limited and signed like any request.
+    pub(crate) async fn upload(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        upload: &UploadSchema,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        let path = match &resolved.body {
+            Some(RequestBodySchema::Binary { path, .. }) => resolved.base_dir.join(path),
+            _ => anyhow::bail!("Request \"{}\" has to upload a binary body", name),
+        };
+        if upload.chunk_size == 0 {
+            anyhow::bail!("The chunk_size of request \"{}\" can't be 0", name);
+        }
+
+        let size = tokio::fs::metadata(&path)
+            .await
+            .with_context(|| format!("Failed to read body file {:?}", &path))?
+            .len();
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        tracing::info!(
+            "{} uploading {} bytes in chunks of {}",
+            name,
+            size,
+            upload.chunk_size
+        );
+
+        return match upload.protocol {
+            UploadProtocolSchema::Tus => {
+                self.upload_tus(name, resolved, upload, size, &filename)
+                    .await
+            }
+            UploadProtocolSchema::Parts => self.upload_parts(name, resolved, upload, size).await,
+            UploadProtocolSchema::ContentRange => {
+                self.upload_content_range(name, resolved, upload, size)
+                    .await
+            }
+        };
+    }
+
+    // Creates the upload with a POST to the url, then PATCHes the chunks to its Location.
+    // A chunk that fails is resumed from the offset the server reports, up to `retries` times.
+    async fn upload_tus(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        upload: &UploadSchema,
+        size: u64,
+        filename: &str,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        let mut create = step(resolved, "POST", &resolved.url);
+        create.set_header("Tus-Resumable", TUS_VERSION);
+        create.set_header("Upload-Length", &size.to_string());
+        create.set_header(
+            "Upload-Metadata",
+            &format!(
+                "filename {}",
+                base64::engine::general_purpose::STANDARD.encode(filename)
+            ),
+        );
+
+        let (mut response, mut duration) = self.execute_with_retries(name, &create).await?;
+        check_status(name, "creating the upload", &response, &[])?;
+
+        let location = response
+            .header("location")
+            .cloned()
+            .context("The tus server didn't return the Location of the upload")?;
+        let url = reqwest::Url::parse(&resolved.url)?
+            .join(&location)?
+            .to_string();
+
+        let mut offset = 0;
+        let mut chunks = 0;
+        let mut resumes = 0;
+        while offset < size {
+            let end = (offset + upload.chunk_size).min(size);
+            let mut patch = chunk_step(resolved, "PATCH", &url, offset..end);
+            // resumed from the server's offset instead of resending the same chunk
+            patch.config.retries = 0;
+            patch.set_header("Tus-Resumable", TUS_VERSION);
+            patch.set_header("Upload-Offset", &offset.to_string());
+            patch.set_header("Content-Type", "application/offset+octet-stream");
+
+            let failure = match self.execute_with_retries(name, &patch).await {
+                Ok((chunk, elapsed)) => {
+                    duration += elapsed;
+                    match check_status(name, &format!("sending offset {}", offset), &chunk, &[]) {
+                        Ok(()) => {
+                            let next = chunk
+                                .header("upload-offset")
+                                .and_then(|offset| offset.parse().ok())
+                                .unwrap_or(end);
+                            // a server that doesn't move the offset forward would be sent chunks forever
+                            if next <= offset || next > size {
+                                anyhow::bail!(
+                                    "Upload of \"{}\" failed: the tus server moved the offset from {} to {} (of {} bytes)",
+                                    name,
+                                    offset,
+                                    next,
+                                    size
+                                );
+                            }
+                            offset = next;
+                            chunks += 1;
+                            response = chunk;
+                            continue;
+                        }
+                        Err(e) => e,
+                    }
+                }
+                Err(e) => e,
+            };
+
+            if resumes >= resolved.config.retries {
+                return Err(failure);
+            }
+            resumes += 1;
+
+            let mut head = step(resolved, "HEAD", &url);
+            head.set_header("Tus-Resumable", TUS_VERSION);
+            let (current, elapsed) = self.execute_with_retries(name, &head).await?;
+            duration += elapsed;
+            offset = current
+                .header("upload-offset")
+                .and_then(|offset| offset.parse().ok())
+                .context("The tus server didn't return the Upload-Offset of the upload")?;
+
+            let error = self.redact_text(&format!("{:#}", failure));
+            tracing::warn!(
+                request = name,
+                "{} upload failed ({}), resuming at offset {}",
+                name,
+                error,
+                offset
+            );
+        }
+
+        response.upload = Some(Upload {
+            size,
+            chunks,
+            url: Some(url),
+            upload_id: None,
+        });
+        return Ok((response, duration));
+    }
+
+    // Initiates the upload with the request itself, PUTs the parts and completes the upload with their ETags
+    async fn upload_parts(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        upload: &UploadSchema,
+        size: u64,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        let query = upload.upload_id.as_ref().with_context(|| {
+            format!(
+                "Request \"{}\" has to set the upload_id to upload parts",
+                name
+            )
+        })?;
+
+        let initiate = step(resolved, &resolved.method, &resolved.url);
+        let (response, mut duration) = self.execute_with_retries(name, &initiate).await?;
+        check_status(name, "initiating the upload", &response, &[])?;
+
+        let mut context = self.build_context();
+        context.insert("response".to_string(), response.to_value());
+        let upload_id = value_to_string(
+            &capture(query, &context)
+                .with_context(|| format!("Failed to find the upload id with `{}`", query))?,
+        );
+
+        let mut env = self.build_env();
+        env.insert("upload_id".to_string(), upload_id.clone().into());
+
+        let mut etags = vec![];
+        for (index, chunk) in chunks(size, upload.chunk_size).into_iter().enumerate() {
+            let number = index + 1;
+            env.insert("part_number".to_string(), (number as u64).into());
+
+            let url = match &upload.part_url {
+                Some(url) => interpolate_string(url, &env)?,
+                None => with_query(
+                    &resolved.url,
+                    &[
+                        ("partNumber", number.to_string()),
+                        ("uploadId", upload_id.clone()),
+                    ],
+                )?,
+            };
+            let part = chunk_step(resolved, "PUT", &url, chunk);
+
+            let (response, elapsed) = self.execute_with_retries(name, &part).await?;
+            duration += elapsed;
+            check_status(name, &format!("sending part {}", number), &response, &[])?;
+
+            let etag = response
+                .header("etag")
+                .cloned()
+                .with_context(|| format!("Part {} of the upload has no ETag", number))?;
+            etags.push((number, etag));
+        }
+
+        let url = match &upload.complete_url {
+            Some(url) => interpolate_string(url, &env)?,
+            None => with_query(&resolved.url, &[("uploadId", upload_id.clone())])?,
+        };
+        let mut complete = step(resolved, "POST", &url);
+        // the content type of the file doesn't apply to the list of parts
+        complete.remove_header("content-type");
+        complete.body = Some(complete_body(upload.complete_format, &etags));
+
+        let (mut response, elapsed) = self.execute_with_retries(name, &complete).await?;
+        duration += elapsed;
+        check_status(name, "completing the upload", &response, &[])?;
+
+        response.upload = Some(Upload {
+            size,
+            chunks: etags.len() as u32,
+            url: None,
+            upload_id: Some(upload_id),
+        });
+        return Ok((response, duration));
+    }
+
+    // Sends the chunks to the url one after the other, each with the Content-Range it covers.
+    // The responses before the last one can be 308 Resume Incomplete.
+    async fn upload_content_range(
+        &self,
+        name: &str,
+        resolved: &ResolvedRequest,
+        upload: &UploadSchema,
+        size: u64,
+    ) -> anyhow::Result<(ResponseObject, Duration)> {
+        let mut last = None;
+        let mut duration = Duration::ZERO;
+        let ranges = chunks(size, upload.chunk_size);
+
+        for chunk in &ranges {
+            let mut request = chunk_step(resolved, &resolved.method, &resolved.url, chunk.clone());
+            let range = match size {
+                0 => "bytes */0".to_string(),
+                _ => format!("bytes {}-{}/{}", chunk.start, chunk.end - 1, size),
+            };
+            request.set_header("Content-Range", &range);
+
+            let (response, elapsed) = self.execute_with_retries(name, &request).await?;
+            duration += elapsed;
+            check_status(name, &format!("sending {}", range), &response, &[308])?;
+            last = Some(response);
+        }
+
+        let mut response = last.context("Nothing was uploaded")?;
+        response.upload = Some(Upload {
+            size,
+            chunks: ranges.len() as u32,
+            url: None,
+            upload_id: None,
+        });
+        return Ok((response, duration));
+    }
+}
+
+// A call of the protocol, with the headers and auth of the request but no body
+fn step(resolved: &ResolvedRequest, method: &str, url: &str) -> ResolvedRequest {
+    let mut step = resolved.clone();
+    step.method = method.to_string();
+    step.url = url.to_string();
+    step.body = None;
+    step.chunk = None;
+    return step;
+}
+
+// A call of the protocol sending a chunk, the binary body is kept for the chunk's bytes to be read from its file
+fn chunk_step(
+    resolved: &ResolvedRequest,
+    method: &str,
+    url: &str,
+    chunk: Range<u64>,
+) -> ResolvedRequest {
+    let mut step = step(resolved, method, url);
+    step.body = resolved.body.clone();
+    step.chunk = Some(chunk);
+    return step;
+}
+
+// The calls of the protocol fail the request unless they succeed
+fn check_status(
+    name: &str,
+    action: &str,
+    response: &ResponseObject,
+    allowed: &[u16],
+) -> anyhow::Result<()> {
+    if (200..300).contains(&response.status) || allowed.contains(&response.status) {
+        return Ok(());
+    }
+
+    anyhow::bail!(
+        "Upload of \"{}\" failed {}: the server returned {}",
+        name,
+        action,
+        response.status
+    );
+}
+
+// The byte ranges of the chunks, an empty file is sent as a single empty chunk
+fn chunks(size: u64, chunk_size: u64) -> Vec<Range<u64>> {
+    if size == 0 {
+        return std::iter::once(0..0).collect();
+    }
+
+    return (0..size)
+        .step_by(chunk_size as usize)
+        .map(|start| start..(start + chunk_size).min(size))
+        .collect();
+}
+
+// The url with its query replaced
+fn with_query(url: &str, pairs: &[(&str, String)]) -> anyhow::Result<String> {
+    let mut url = reqwest::Url::parse(url).with_context(|| format!("Invalid url {}", url))?;
+    url.query_pairs_mut().clear().extend_pairs(pairs);
+    return Ok(url.to_string());
+}
+
+fn complete_body(
+    format: UploadCompleteFormatSchema,
+    etags: &[(usize, String)],
+) -> RequestBodySchema {
+    return match format {
+        UploadCompleteFormatSchema::Xml => RequestBodySchema::Xml {
+            content: format!(
+                "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+                etags
+                    .iter()
+                    .map(|(number, etag)| format!(
+                        "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                        number,
+                        etag.replace('&', "&amp;").replace('<', "&lt;")
+                    ))
+                    .collect::<String>()
+            ),
+            content_file: None,
+        },
+        UploadCompleteFormatSchema::Json => {
+            let parts = etags
+                .iter()
+                .map(|(number, etag)| serde_json::json!({ "part_number": number, "etag": etag }))
+                .collect::<Vec<_>>();
+            RequestBodySchema::Json {
+                content: serde_yaml::to_value(serde_json::json!({ "parts": parts }))
+                    .unwrap_or_default(),
+                content_file: None,
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_cover_the_file_and_the_last_holds_the_rest() {
+        assert_eq!(chunks(10, 4), [0..4, 4..8, 8..10]);
+        assert_eq!(chunks(8, 4), [0..4, 4..8]);
+        assert_eq!(chunks(3, 4), std::iter::once(0..3).collect::<Vec<_>>());
+        assert_eq!(chunks(0, 4), std::iter::once(0..0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn replaces_the_query_of_the_url() {
+        assert_eq!(
+            with_query(
+                "https://s3.dev/bucket/a.bin?uploads",
+                &[
+                    ("partNumber", "2".to_string()),
+                    ("uploadId", "a b".to_string())
+                ]
+            )
+            .unwrap(),
+            "https://s3.dev/bucket/a.bin?partNumber=2&uploadId=a+b"
+        );
+        assert!(with_query("not a url", &[]).is_err());
+    }
+
+    #[test]
+    fn completes_with_the_etags_of_the_parts() {
+        let etags = [(1, "\"a\"".to_string()), (2, "<b>".to_string())];
+
+        match complete_body(UploadCompleteFormatSchema::Xml, &etags) {
+            RequestBodySchema::Xml { content, .. } => assert_eq!(
+                content,
+                "<CompleteMultipartUpload>\
+                 <Part><PartNumber>1</PartNumber><ETag>\"a\"</ETag></Part>\
+                 <Part><PartNumber>2</PartNumber><ETag>&lt;b></ETag></Part>\
+                 </CompleteMultipartUpload>"
+            ),
+            body => panic!("unexpected body {:?}", body),
+        };
+        match complete_body(UploadCompleteFormatSchema::Json, &etags) {
+            RequestBodySchema::Json { content, .. } => {
+                assert_eq!(content["parts"][1]["part_number"], 2);
+                assert_eq!(content["parts"][1]["etag"], "<b>");
+            }
+            body => panic!("unexpected body {:?}", body),
+        };
+    }
+
+    #[test]
+    fn only_the_failures_of_the_protocol_fail_the_upload() {
+        let response = |status| ResponseObject {
+            status,
+            ..Default::default()
+        };
+
+        assert!(check_status("a", "sending", &response(204), &[]).is_ok());
+        assert!(check_status("a", "sending", &response(308), &[308]).is_ok());
+        assert_eq!(
+            check_status("a", "sending part 2", &response(308), &[])
+                .unwrap_err()
+                .to_string(),
+            "Upload of \"a\" failed sending part 2: the server returned 308"
+        );
+    }
+
+    /// A request received by the test server.
+    #[derive(Debug)]
+    struct Received {
+        method: String,
+        path: String,
+        headers: Vec<(String, String)>,
+        body: Vec<u8>,
+    }
+
+    impl Received {
+        fn header(&self, name: &str) -> &str {
+            return self
+                .headers
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+                .unwrap_or_default();
+        }
+    }
+
+    // Serves the requests on a local port with the response `respond` gives for each,
+    // the requests received are kept in the returned list
+    async fn serve(
+        respond: fn(&Received) -> String,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<Received>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let received = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+
+        let requests = received.clone();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut data = vec![];
+                let mut buffer = [0; 4096];
+
+                let head_end = loop {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    data.extend_from_slice(&buffer[..read]);
+                    if let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") {
+                        break end + 4;
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&data[..head_end]).to_string();
+                let mut lines = head.lines();
+                let mut request_line = lines.next().unwrap().split(' ');
+                let method = request_line.next().unwrap().to_string();
+                let path = request_line.next().unwrap().to_string();
+                let headers = lines
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .collect::<Vec<_>>();
+
+                let mut request = Received {
+                    method,
+                    path,
+                    headers,
+                    body: vec![],
+                };
+                let length = request.header("content-length").parse().unwrap_or(0);
+                while data.len() < head_end + length {
+                    let read = stream.read(&mut buffer).await.unwrap();
+                    data.extend_from_slice(&buffer[..read]);
+                }
+                request.body = data[head_end..head_end + length].to_vec();
+
+                let response = respond(&request);
+                requests.lock().unwrap().push(request);
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return (format!("http://{}", address), received);
+    }
+
+    fn response(status: &str, headers: &[(&str, String)], body: &str) -> String {
+        let headers = headers
+            .iter()
+            .map(|(key, value)| format!("{}: {}\r\n", key, value))
+            .collect::<String>();
+        return format!(
+            "HTTP/1.1 {}\r\n{}content-length: {}\r\nconnection: close\r\n\r\n{}",
+            status,
+            headers,
+            body.len(),
+            body
+        );
+    }
+
+    // A runner with a request uploading a 10 bytes file in chunks of 4
+    fn runner(url: &str, upload: &str) -> Runner {
+        let root = std::env::temp_dir().join(format!("dotapi-upload-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("requests")).unwrap();
+        std::fs::write(root.join("requests/file.bin"), "0123456789").unwrap();
+
+        let request = serde_yaml::from_str::<crate::schema::roots::RequestRootSchema>(&format!(
+            "method: POST\nurl: {}/upload\nbody: {{type: binary, path: file.bin}}\nconfig: {{upload: {}}}",
+            url, upload
+        ))
+        .unwrap();
+
+        return Runner::new(
+            crate::fs::FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![crate::fs::FileObject::new(
+                root.join("requests/file.nd"),
+                request,
+            )],
+        )
+        .unwrap();
+    }
+
+    async fn upload(runner: &Runner) -> ResponseObject {
+        let resolved = runner.resolve_request("file").unwrap();
+        let upload = resolved.config.upload.clone().unwrap();
+        let (response, _) = runner.upload("file", &resolved, &upload).await.unwrap();
+        return response;
+    }
+
+    fn bodies(received: &[Received], method: &str) -> Vec<String> {
+        return received
+            .iter()
+            .filter(|request| request.method == method)
+            .map(|request| String::from_utf8_lossy(&request.body).to_string())
+            .collect();
+    }
+
+    #[tokio::test]
+    async fn tus_patches_the_bytes_of_each_chunk() {
+        let (url, received) = serve(|request| match request.method.as_str() {
+            "POST" => response("201 Created", &[("Location", "/files/1".to_string())], ""),
+            _ => {
+                let offset = request.header("upload-offset").parse::<usize>().unwrap();
+                let offset = (offset + request.body.len()).to_string();
+                response("204 No Content", &[("Upload-Offset", offset)], "")
+            }
+        })
+        .await;
+
+        let runner = runner(&url, "{protocol: tus, chunk_size: 4}");
+        let response = upload(&runner).await;
+        let received = received.lock().unwrap();
+
+        assert_eq!(received[0].header("upload-length"), "10");
+        assert!(received[0].body.is_empty());
+        assert_eq!(bodies(&received, "PATCH"), ["0123", "4567", "89"]);
+        assert!(
+            received[1..]
+                .iter()
+                .all(|request| request.path == "/files/1")
+        );
+        assert_eq!(response.upload.unwrap().chunks, 3);
+    }
+
+    #[tokio::test]
+    async fn parts_put_the_bytes_of_each_part() {
+        let (url, received) = serve(|request| match request.method.as_str() {
+            "PUT" => response(
+                "200 OK",
+                &[("ETag", format!("\"{}\"", request.body.len()))],
+                "",
+            ),
+            _ => response(
+                "200 OK",
+                &[("Content-Type", "application/json".to_string())],
+                r#"{"UploadId": "u1"}"#,
+            ),
+        })
+        .await;
+
+        let runner = runner(
+            &url,
+            "{protocol: parts, chunk_size: 4, upload_id: $.UploadId}",
+        );
+        upload(&runner).await;
+        let received = received.lock().unwrap();
+
+        assert_eq!(bodies(&received, "PUT"), ["0123", "4567", "89"]);
+        assert_eq!(received[1].path, "/upload?partNumber=1&uploadId=u1");
+        assert_eq!(
+            String::from_utf8_lossy(&received[4].body),
+            "<CompleteMultipartUpload>\
+             <Part><PartNumber>1</PartNumber><ETag>\"4\"</ETag></Part>\
+             <Part><PartNumber>2</PartNumber><ETag>\"4\"</ETag></Part>\
+             <Part><PartNumber>3</PartNumber><ETag>\"2\"</ETag></Part>\
+             </CompleteMultipartUpload>"
+        );
+    }
+
+    #[tokio::test]
+    async fn content_range_sends_the_bytes_of_each_range() {
+        let (url, received) = serve(|request| match request.header("content-range") {
+            "bytes 8-9/10" => response("200 OK", &[], ""),
+            _ => response("308 Resume Incomplete", &[], ""),
+        })
+        .await;
+
+        let runner = runner(&url, "{protocol: content_range, chunk_size: 4}");
+        let response = upload(&runner).await;
+        let received = received.lock().unwrap();
+
+        assert_eq!(bodies(&received, "POST"), ["0123", "4567", "89"]);
+        let ranges = received
+            .iter()
+            .map(|request| request.header("content-range"))
+            .collect::<Vec<_>>();
+        assert_eq!(ranges, ["bytes 0-3/10", "bytes 4-7/10", "bytes 8-9/10"]);
+        assert_eq!(response.status, 200);
+    }
+}
@@ -0,0 +1,225 @@
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use anyhow::Context;
+
+use crate::{
+    middleware::RequestMiddleware,
+    runner::{ResponseObject, Runner},
+};
+
+/// The messages of a call as they went over the connection, in their HTTP/1.1 form.
+///
+/// The request is the one handed to the http client once signed, with the Host and
+/// Content-Length headers the connection adds. The response keeps its headers and its body
+/// as received, before decompression. Bodies are de-chunked, streamed request bodies
+/// (multipart) aren't read. Nothing is redacted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WireCapture {
+    pub request: Vec<u8>,
+    pub response: Vec<u8>,
+}
+
+// The request line, headers and body of a request
+pub(crate) fn request_bytes(request: &reqwest::Request) -> Vec<u8> {
+    let url = request.url();
+    let target = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+
+    let mut head = format!(
+        "{} {} {:?}\r\n",
+        request.method(),
+        target,
+        request.version()
+    );
+
+    if !request.headers().contains_key(reqwest::header::HOST) {
+        let host = url.host_str().unwrap_or_default();
+        match url.port() {
+            Some(port) => head.push_str(&format!("host: {}:{}\r\n", host, port)),
+            None => head.push_str(&format!("host: {}\r\n", host)),
+        };
+    }
+
+    for (key, value) in request.headers() {
+        head.push_str(&format!(
+            "{}: {}\r\n",
+            key,
+            String::from_utf8_lossy(value.as_bytes())
+        ));
+    }
+
+    let body = match request.body() {
+        Some(body) => match body.as_bytes() {
+            Some(bytes) => {
+                head.push_str(&format!("content-length: {}\r\n", bytes.len()));
+                bytes.to_vec()
+            }
+            None => {
+                head.push_str("transfer-encoding: chunked\r\n");
+                b"<streamed body>".to_vec()
+            }
+        },
+        None => vec![],
+    };
+
+    let mut bytes = head.into_bytes();
+    bytes.extend_from_slice(b"\r\n");
+    bytes.extend(body);
+    return bytes;
+}
+
+// The status line and headers of a response, its body is appended once read
+pub(crate) fn response_head(response: &reqwest::Response) -> Vec<u8> {
+    let mut head = format!("{:?} {}\r\n", response.version(), response.status());
+
+    for (key, value) in response.headers() {
+        head.push_str(&format!(
+            "{}: {}\r\n",
+            key,
+            String::from_utf8_lossy(value.as_bytes())
+        ));
+    }
+
+    head.push_str("\r\n");
+    return head.into_bytes();
+}
+
+/// Writes the wire capture of every call to a folder, as <n>-<name>.request and <n>-<name>.response.
+struct WireMiddleware {
+    dir: PathBuf,
+    count: AtomicUsize,
+}
+
+impl RequestMiddleware for WireMiddleware {
+    fn after_receive(&self, name: &str, response: &mut ResponseObject) -> anyhow::Result<()> {
+        let wire = match &response.wire {
+            Some(wire) => wire,
+            None => return Ok(()),
+        };
+
+        let count = self.count.fetch_add(1, Ordering::Relaxed) + 1;
+        let stem = format!("{:03}-{}", count, name.replace(['/', '\\'], "_"));
+
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {:?}", self.dir))?;
+        for (extension, bytes) in [("request", &wire.request), ("response", &wire.response)] {
+            let path = self.dir.join(format!("{}.{}", stem, extension));
+            std::fs::write(&path, bytes).with_context(|| format!("Failed to write {:?}", path))?;
+        }
+
+        return Ok(());
+    }
+}
+
+impl Runner {
+    // Captures the messages of every call, written to dir when given
+    pub fn enable_wire_capture(&mut self, dir: Option<PathBuf>) {
+        self.capture_wire = true;
+
+        if let Some(dir) = dir {
+            self.add_middleware(Box::new(WireMiddleware {
+                dir,
+                count: AtomicUsize::new(0),
+            }));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::{fs::FileObject, schema::roots::RequestRootSchema};
+
+    // Answers every request with a small json body
+    async fn serve() -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut request = [0; 4096];
+                let _ = stream.read(&mut request).await;
+                let response = "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\ncontent-length: 9\r\nconnection: close\r\n\r\n{\"id\": 1}";
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        return address.to_string();
+    }
+
+    #[test]
+    fn requests_are_written_with_their_host_and_body_length() {
+        let request = reqwest::Client::new()
+            .post("http://localhost:8080/users?page=2")
+            .header("x-token", "abc")
+            .body("{\"name\": \"a\"}")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(request_bytes(&request)).unwrap(),
+            "POST /users?page=2 HTTP/1.1\r\nhost: localhost:8080\r\nx-token: abc\r\ncontent-length: 13\r\n\r\n{\"name\": \"a\"}"
+        );
+    }
+
+    #[test]
+    fn a_request_without_a_body_ends_with_its_headers() {
+        let request = reqwest::Client::new()
+            .get("https://example.com/")
+            .header("host", "api.example.com")
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            String::from_utf8(request_bytes(&request)).unwrap(),
+            "GET / HTTP/1.1\r\nhost: api.example.com\r\n\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn each_call_is_written_to_the_capture_folder() {
+        let address = serve().await;
+        let root = std::env::temp_dir().join(format!("dotapi-wire-{}", uuid::Uuid::new_v4()));
+        let request = serde_yaml::from_str::<RequestRootSchema>(&format!(
+            "method: GET\nurl: http://{}/users/1",
+            address
+        ))
+        .unwrap();
+        let mut runner = Runner::new(
+            FileObject::new(root.join(".nd-project"), Default::default()),
+            vec![FileObject::new(root.join("requests/users/get.nd"), request)],
+        )
+        .unwrap();
+        runner.enable_wire_capture(Some(root.join("wire")));
+
+        let name = runner.request_names()[0].clone();
+        runner.call_request(&name).await.unwrap();
+        runner.call_request(&name).await.unwrap();
+
+        let stem = format!("002-{}", name.replace(['/', '\\'], "_"));
+        let sent = std::fs::read_to_string(root.join(format!("wire/{}.request", stem))).unwrap();
+        let received =
+            std::fs::read_to_string(root.join(format!("wire/{}.response", stem))).unwrap();
+
+        assert!(
+            sent.starts_with(&format!("GET /users/1 HTTP/1.1\r\nhost: {}\r\n", address)),
+            "{}",
+            sent
+        );
+        assert!(
+            received.starts_with("HTTP/1.1 201 Created\r\n"),
+            "{}",
+            received
+        );
+        assert!(received.ends_with("\r\n\r\n{\"id\": 1}"), "{}", received);
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}